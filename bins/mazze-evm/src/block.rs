@@ -0,0 +1,209 @@
+//! Decodes the blocks of the `BlockchainTests` fixtures and validates their
+//! headers against the parent header, as an Ethereum client after the merge
+//! does before executing a block.
+
+use crate::{ledger::Result, runner::BlockInfo};
+use keccak_hash::{keccak, KECCAK_EMPTY_LIST_RLP};
+use mazze_types::{Address, BigEndianHash, Bloom, H256, U256};
+use rlp::{DecoderError, Rlp};
+
+/// The gas limit can't change by 1/1024 or more of the parent gas limit.
+const GAS_LIMIT_BOUND_DIVISOR: u64 = 1024;
+const MIN_GAS_LIMIT: u64 = 5000;
+const MAX_EXTRA_DATA_SIZE: usize = 32;
+/// The parent gas target is half of its gas limit (EIP-1559).
+const ELASTICITY_MULTIPLIER: u64 = 2;
+const BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+
+/// The header fields of a block. Later forks append fields the execution
+/// does not check, which are still covered by the hash.
+pub struct Header {
+    pub hash: H256,
+    pub parent_hash: H256,
+    pub ommers_hash: H256,
+    pub coinbase: Address,
+    pub state_root: H256,
+    pub transactions_root: H256,
+    pub receipts_root: H256,
+    pub logs_bloom: Bloom,
+    pub difficulty: U256,
+    pub number: u64,
+    pub gas_limit: U256,
+    pub gas_used: U256,
+    pub timestamp: u64,
+    pub extra_data: Vec<u8>,
+    pub prev_randao: H256,
+    pub nonce: Vec<u8>,
+    pub base_fee: Option<U256>,
+    pub withdrawals_root: Option<H256>,
+}
+
+impl Header {
+    fn decode(header: &Rlp) -> std::result::Result<Header, DecoderError> {
+        let item_count = header.item_count()?;
+        Ok(Header {
+            hash: keccak(header.as_raw()),
+            parent_hash: header.val_at(0)?,
+            ommers_hash: header.val_at(1)?,
+            coinbase: header.val_at(2)?,
+            state_root: header.val_at(3)?,
+            transactions_root: header.val_at(4)?,
+            receipts_root: header.val_at(5)?,
+            logs_bloom: header.val_at(6)?,
+            difficulty: header.val_at(7)?,
+            number: header.val_at(8)?,
+            gas_limit: header.val_at(9)?,
+            gas_used: header.val_at(10)?,
+            timestamp: header.val_at(11)?,
+            extra_data: header.val_at(12)?,
+            prev_randao: header.val_at(13)?,
+            nonce: header.val_at(14)?,
+            base_fee: if item_count > 15 {
+                Some(header.val_at(15)?)
+            } else {
+                None
+            },
+            withdrawals_root: if item_count > 16 {
+                Some(header.val_at(16)?)
+            } else {
+                None
+            },
+        })
+    }
+
+    /// The base fee of the child blocks (EIP-1559).
+    fn child_base_fee(&self) -> Option<U256> {
+        let base_fee = self.base_fee?;
+        let target = self.gas_limit / ELASTICITY_MULTIPLIER;
+        let denominator = U256::from(BASE_FEE_MAX_CHANGE_DENOMINATOR);
+        Some(if self.gas_used == target || target.is_zero() {
+            base_fee
+        } else if self.gas_used > target {
+            let delta =
+                base_fee * (self.gas_used - target) / target / denominator;
+            base_fee + delta.max(U256::one())
+        } else {
+            base_fee
+                - base_fee * (target - self.gas_used) / target / denominator
+        })
+    }
+
+    /// Check the header against the header of its parent.
+    pub fn validate(&self, parent: &Header) -> Result<()> {
+        if self.parent_hash != parent.hash {
+            return Err(format!(
+                "parent hash {:?}, expected {:?}",
+                self.parent_hash, parent.hash
+            ));
+        }
+        if self.number != parent.number + 1 {
+            return Err(format!(
+                "number {}, expected {}",
+                self.number,
+                parent.number + 1
+            ));
+        }
+        if self.timestamp <= parent.timestamp {
+            return Err(format!(
+                "timestamp {} is not after the parent timestamp {}",
+                self.timestamp, parent.timestamp
+            ));
+        }
+        let bound = parent.gas_limit / GAS_LIMIT_BOUND_DIVISOR;
+        let change = if self.gas_limit > parent.gas_limit {
+            self.gas_limit - parent.gas_limit
+        } else {
+            parent.gas_limit - self.gas_limit
+        };
+        if change >= bound || self.gas_limit < U256::from(MIN_GAS_LIMIT) {
+            return Err(format!(
+                "gas limit {} out of bound of the parent gas limit {}",
+                self.gas_limit, parent.gas_limit
+            ));
+        }
+        if self.gas_used > self.gas_limit {
+            return Err(format!(
+                "gas used {} above the gas limit {}",
+                self.gas_used, self.gas_limit
+            ));
+        }
+        if self.extra_data.len() > MAX_EXTRA_DATA_SIZE {
+            return Err(format!(
+                "extra data of {} bytes",
+                self.extra_data.len()
+            ));
+        }
+        if !self.difficulty.is_zero()
+            || self.nonce.iter().any(|byte| *byte != 0)
+            || self.ommers_hash != KECCAK_EMPTY_LIST_RLP
+        {
+            return Err("proof of work fields after the merge".into());
+        }
+        if self.base_fee != parent.child_base_fee() {
+            return Err(format!(
+                "base fee {:?}, expected {:?}",
+                self.base_fee,
+                parent.child_base_fee()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A block in the network encoding.
+pub struct Block {
+    pub header: Header,
+    /// The encoded transactions, typed transactions as their envelope.
+    pub transactions: Vec<Vec<u8>>,
+    /// The encoded withdrawals, `None` before Shanghai.
+    pub withdrawals: Option<Vec<Vec<u8>>>,
+    pub info: BlockInfo,
+}
+
+impl Block {
+    pub fn decode(raw: &[u8]) -> Result<Block> {
+        decode_block(&Rlp::new(raw))
+            .map_err(|e| format!("invalid block encoding: {:?}", e))
+    }
+}
+
+fn decode_block(block: &Rlp) -> std::result::Result<Block, DecoderError> {
+    let header = Header::decode(&block.at(0)?)?;
+
+    let mut transactions = vec![];
+    for tx in block.at(1)?.iter() {
+        // Typed transactions are wrapped as RLP strings.
+        if tx.is_list() {
+            transactions.push(tx.as_raw().to_vec());
+        } else {
+            transactions.push(tx.data()?.to_vec());
+        }
+    }
+
+    let mut withdrawals = None;
+    let mut credited = vec![];
+    if block.item_count()? > 3 {
+        let mut encoded = vec![];
+        for withdrawal in block.at(3)?.iter() {
+            credited.push((withdrawal.val_at(2)?, withdrawal.val_at(3)?));
+            encoded.push(withdrawal.as_raw().to_vec());
+        }
+        withdrawals = Some(encoded);
+    }
+
+    let info = BlockInfo {
+        coinbase: header.coinbase,
+        number: header.number,
+        gas_limit: header.gas_limit,
+        timestamp: header.timestamp,
+        prev_randao: header.prev_randao.into_uint(),
+        base_fee: header.base_fee.unwrap_or_default(),
+        withdrawals: credited,
+    };
+    Ok(Block {
+        header,
+        transactions,
+        withdrawals,
+        info,
+    })
+}
@@ -0,0 +1,230 @@
+//! Runs the `BlockchainTests` fixtures.
+//!
+//! Each block is validated against its parent header and executed on top of
+//! the latest accepted state. The transactions, receipts and withdrawals
+//! roots, the logs bloom, the gas used and the state root of the header are
+//! then compared with the execution, and the block is only accepted if they
+//! all match. The blocks the fixture marks as invalid must be rejected and
+//! leave the state unchanged. A test finally compares the hash of the last
+//! block and, when the fixture gives it, the `postState`.
+//!
+//! The system contract calls of Cancun and Prague are not performed, so the
+//! tests deploying the system contracts are skipped.
+
+use crate::{
+    block::{Block, Header},
+    fork::Fork,
+    ledger::{Ledger, Result},
+    report::{diff_alloc, read_json, Outcome, Report},
+    runner::{
+        encode_receipt, new_machine, rejection_reason, BlockExecutor,
+        DEFAULT_CHAIN_ID,
+    },
+    transaction::decode_raw,
+    trie::{ordered_trie_root, state_root},
+    types::{BlockchainTest, HexAddress, TestBlock},
+};
+use mazze_executor::machine::Machine;
+use mazze_types::{Address, H256};
+use primitives::{log_entry::build_bloom, TransactionStatus};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+/// The contracts called by the block itself: the beacon roots (EIP-4788),
+/// the history storage (EIP-2935), and the withdrawal (EIP-7002) and
+/// consolidation (EIP-7251) request contracts.
+const SYSTEM_CONTRACTS: [&str; 4] = [
+    "000F3df6D732807Ef1319fB7B8bB8522d0Beac02",
+    "0000F90827F1C53a10cb7A02335B175320002935",
+    "00000961Ef480Eb55e80D19ad83579A64c007002",
+    "0000BBdDc7CE488642fb579F8B00f3a590007251",
+];
+
+pub fn run_file(
+    path: &Path, fork_filter: Option<&str>, report: &mut Report,
+) -> Result<()> {
+    let tests: BTreeMap<String, BlockchainTest> = read_json(path)?;
+    for (name, test) in &tests {
+        if fork_filter.map_or(false, |fork| fork != test.network) {
+            continue;
+        }
+        let outcome = match Fork::from_name(&test.network) {
+            Some(fork) => run_test(test, fork).unwrap_or_else(Outcome::Fail),
+            None => Outcome::Skip(format!("unsupported fork {}", test.network)),
+        };
+        report.record(name, outcome);
+    }
+    Ok(())
+}
+
+fn run_test(test: &BlockchainTest, fork: Fork) -> Result<Outcome> {
+    if SYSTEM_CONTRACTS.iter().any(|address| {
+        let address = address.parse().expect("valid address");
+        test.pre.contains_key(&HexAddress(address))
+    }) {
+        return Ok(Outcome::Skip("calls a system contract".into()));
+    }
+
+    let machine = new_machine(fork);
+    let mut ledger = Ledger::new(&machine, &test.pre)?;
+    let mut addresses: BTreeSet<_> = test
+        .pre
+        .keys()
+        .chain(test.post_state.iter().flat_map(|post| post.keys()))
+        .map(|address| address.0)
+        .collect();
+
+    let genesis = Block::decode(&test.genesis_rlp.0)?.header;
+    let genesis_root = state_root(&ledger.dump(&addresses)?);
+    if genesis_root != genesis.state_root {
+        return Ok(Outcome::Fail(format!(
+            "genesis state root {:?}, expected {:?}",
+            genesis_root, genesis.state_root
+        )));
+    }
+
+    let mut parent = genesis;
+    for (number, block) in test.blocks.iter().enumerate() {
+        let result =
+            apply_block(&machine, &mut ledger, &parent, block, &mut addresses);
+        match (result, &block.expect_exception) {
+            (Ok(header), None) => parent = header,
+            (Err(reason), None) => {
+                return Ok(Outcome::Fail(format!(
+                    "block {} rejected: {}",
+                    number, reason
+                )))
+            }
+            (Ok(_), Some(exception)) => {
+                return Ok(Outcome::Fail(format!(
+                    "block {} accepted, expected {}",
+                    number, exception
+                )))
+            }
+            (Err(_), Some(_)) => {}
+        }
+    }
+
+    if let Some(expected) = &test.lastblockhash {
+        if expected.as_h256() != Some(parent.hash) {
+            return Ok(Outcome::Fail(format!(
+                "last block hash {:?}, expected {:?}",
+                parent.hash, expected
+            )));
+        }
+    }
+    let diff = match &test.post_state {
+        Some(expected) => {
+            diff_alloc(expected, &ledger.dump(&addresses)?, &BTreeSet::new())
+        }
+        None => vec![],
+    };
+    Ok(if diff.is_empty() {
+        Outcome::Pass
+    } else {
+        Outcome::Fail(diff.join("\n"))
+    })
+}
+
+/// Validate and execute a block on top of the latest state of the ledger,
+/// and return its header if the block is valid. The state is only committed
+/// for a valid block.
+fn apply_block(
+    machine: &Machine, ledger: &mut Ledger, parent: &Header, block: &TestBlock,
+    addresses: &mut BTreeSet<Address>,
+) -> Result<Header> {
+    let Block {
+        header,
+        transactions,
+        withdrawals,
+        info,
+    } = Block::decode(&block.rlp.0)?;
+    if let Some(expected) = &block.block_header {
+        if expected.hash.as_h256() != Some(header.hash) {
+            return Err(format!(
+                "block hash {:?}, expected {:?}",
+                header.hash, expected.hash
+            ));
+        }
+    }
+    header.validate(parent)?;
+    check_root(
+        "transactions root",
+        ordered_trie_root(&transactions),
+        header.transactions_root,
+    )?;
+    match (&withdrawals, header.withdrawals_root) {
+        (Some(withdrawals), Some(root)) => check_root(
+            "withdrawals root",
+            ordered_trie_root(withdrawals),
+            root,
+        )?,
+        (None, None) => {}
+        _ => return Err("withdrawals do not match the header".into()),
+    }
+
+    let mut state = ledger.open()?;
+    let mut executor = BlockExecutor::new(machine, &info, DEFAULT_CHAIN_ID);
+    let mut receipts = vec![];
+    let mut logs = vec![];
+    for (index, raw) in transactions.iter().enumerate() {
+        let tx = decode_raw(raw)
+            .map_err(|e| format!("transaction {}: {}", index, e))?;
+        let outcome = executor.transact(&mut state, &tx)?;
+        if let Some(reason) = rejection_reason(&outcome) {
+            return Err(format!("transaction {} rejected: {}", index, reason));
+        }
+        executor.pay_priority_fee(&mut state, &outcome)?;
+
+        // A typed transaction starts with its type, a legacy transaction
+        // with an RLP list.
+        let tx_type = raw.first().copied().filter(|byte| *byte < 0x80);
+        let tx_logs = outcome.transaction_logs();
+        receipts.push(encode_receipt(
+            tx_type,
+            outcome.outcome_status() == TransactionStatus::Success,
+            &executor.gas_used(),
+            &tx_logs,
+        ));
+        logs.extend(tx_logs);
+    }
+    executor.apply_withdrawals(&mut state, &info.withdrawals)?;
+    addresses.extend(executor.touched().iter().cloned());
+
+    if executor.gas_used() != header.gas_used {
+        return Err(format!(
+            "gas used {}, header has {}",
+            executor.gas_used(),
+            header.gas_used
+        ));
+    }
+    check_root(
+        "receipts root",
+        ordered_trie_root(&receipts),
+        header.receipts_root,
+    )?;
+    if build_bloom(&logs) != header.logs_bloom {
+        return Err("logs bloom does not match the header".into());
+    }
+
+    let parent_epoch = ledger.latest_epoch();
+    ledger.commit(state)?;
+    let root = state_root(&ledger.dump(addresses)?);
+    if let Err(e) = check_root("state root", root, header.state_root) {
+        ledger.revert_to(parent_epoch);
+        return Err(e);
+    }
+    Ok(header)
+}
+
+fn check_root(name: &str, computed: H256, expected: H256) -> Result<()> {
+    if computed != expected {
+        return Err(format!(
+            "{} {:?}, header has {:?}",
+            name, computed, expected
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,54 @@
+//! Maps the fork names of the fixtures to the chain parameters of the
+//! executor.
+
+use mazze_executor::spec::CommonParams;
+use mazze_types::SpaceMap;
+
+/// The Ethereum forks whose rules the eSpace follows. Earlier forks have gas
+/// schedules and opcode sets the executor never implemented, so their tests
+/// are skipped rather than reported as failures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Fork {
+    Shanghai,
+    Cancun,
+    Prague,
+    Osaka,
+}
+
+impl Fork {
+    /// Parse a fork name. Transition networks such as
+    /// `ShanghaiToCancunAtTime15k` are not supported.
+    pub fn from_name(name: &str) -> Option<Fork> {
+        match name {
+            "Shanghai" => Some(Fork::Shanghai),
+            "Cancun" => Some(Fork::Cancun),
+            "Prague" => Some(Fork::Prague),
+            "Osaka" => Some(Fork::Osaka),
+            _ => None,
+        }
+    }
+
+    /// The chain parameters with exactly the features of this fork activated
+    /// from the genesis.
+    pub fn params(&self) -> CommonParams {
+        // All the transitions happen at the genesis by default.
+        let mut params = CommonParams::default();
+        let transitions = &mut params.transition_numbers;
+        if *self < Fork::Cancun {
+            transitions.cancun_opcodes = u64::MAX;
+            transitions.cip144 = u64::MAX;
+        }
+        if *self < Fork::Prague {
+            transitions.eip7702 = u64::MAX;
+            transitions.eip2537 = u64::MAX;
+        }
+        if *self < Fork::Osaka {
+            transitions.eip7692 = SpaceMap::new(u64::MAX, u64::MAX);
+        }
+        // The P-256 precompile does not exist on Ethereum.
+        transitions.rip7212 = u64::MAX;
+        // Nor does the eSpace sponsorship.
+        transitions.eth_sponsor = u64::MAX;
+        params
+    }
+}
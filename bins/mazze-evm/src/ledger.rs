@@ -0,0 +1,182 @@
+//! An in-memory ledger holding the eSpace accounts of a test.
+
+use crate::types::{Account, Alloc, Data, HexAddress, Uint};
+use mazze_executor::{
+    internal_contract::initialize_internal_contract_accounts,
+    machine::Machine,
+    state::{CleanupMode, State},
+    substate::Substate,
+};
+use mazze_statedb::{access_mode, StateDb, StateDbExt};
+use mazze_storage::{
+    state_manager::StateManagerTrait,
+    tests::{new_state_manager_for_unit_test, FakeStateManager},
+    StateIndex,
+};
+use mazze_types::{Address, AddressSpaceUtil, BigEndianHash, H256};
+use primitives::{
+    EpochId, SkipInputCheck, StorageKey, StorageKeyWithSpace, StorageValue,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub type Result<T> = std::result::Result<T, String>;
+
+/// A chain of committed states, each one on top of the previous one.
+pub struct Ledger {
+    storage_manager: FakeStateManager,
+    /// The epoch of the latest committed state.
+    latest_epoch: EpochId,
+    committed_epochs: u64,
+}
+
+impl Ledger {
+    /// Make a ledger whose genesis state contains the internal contracts and
+    /// the eSpace accounts of `alloc`.
+    pub fn new(machine: &Machine, alloc: &Alloc) -> Result<Self> {
+        let mut ledger = Ledger {
+            storage_manager: new_state_manager_for_unit_test(),
+            latest_epoch: EpochId::default(),
+            committed_epochs: 0,
+        };
+
+        let mut state = State::new(StateDb::new(
+            ledger.storage_manager.get_state_for_genesis_write(),
+        ))
+        .map_err(|e| format!("failed to initialize state: {}", e))?;
+        initialize_internal_contract_accounts(
+            &mut state,
+            machine.internal_contracts().initialized_at_genesis(),
+        )
+        .map_err(|e| e.to_string())?;
+        for (address, account) in alloc {
+            set_account(&mut state, &address.0, account)
+                .map_err(|e| e.to_string())?;
+        }
+        state
+            .commit(ledger.latest_epoch, /* debug_record = */ None)
+            .map_err(|e| e.to_string())?;
+
+        Ok(ledger)
+    }
+
+    /// Open a state on top of the latest committed state.
+    pub fn open(&self) -> Result<State> {
+        State::new(self.open_db()?).map_err(|e| e.to_string())
+    }
+
+    /// Commit `state`, which must have been opened by `open`, and return the
+    /// state root.
+    pub fn commit(&mut self, state: State) -> Result<H256> {
+        self.committed_epochs += 1;
+        self.latest_epoch = EpochId::from_low_u64_be(self.committed_epochs);
+        let result = state
+            .commit(self.latest_epoch, /* debug_record = */ None)
+            .map_err(|e| e.to_string())?;
+        Ok(result.state_root.state_root.compute_state_root_hash())
+    }
+
+    /// The epoch of the latest committed state.
+    pub fn latest_epoch(&self) -> EpochId { self.latest_epoch }
+
+    /// Make the state committed at `epoch` the latest state again, dropping
+    /// the states committed on top of it.
+    pub fn revert_to(&mut self, epoch: EpochId) {
+        self.latest_epoch = epoch;
+    }
+
+    /// Read the eSpace accounts in `addresses` from the latest committed
+    /// state. Empty accounts are left out, as they do not exist on Ethereum
+    /// after EIP-161.
+    pub fn dump(&self, addresses: &BTreeSet<Address>) -> Result<Alloc> {
+        let mut db = self.open_db()?;
+        let mut alloc = Alloc::new();
+        for address in addresses {
+            let account = read_account(&mut db, address).map_err(|e| {
+                format!("failed to read account {:?}: {}", address, e)
+            })?;
+            if let Some(account) = account.filter(|a| !a.is_empty()) {
+                alloc.insert(HexAddress(*address), account);
+            }
+        }
+        Ok(alloc)
+    }
+
+    fn open_db(&self) -> Result<StateDb> {
+        let storage = self
+            .storage_manager
+            .get_state_for_next_epoch(
+                StateIndex::new_for_test_only_delta_mpt(&self.latest_epoch),
+                false,
+            )
+            .map_err(|e| e.to_string())?
+            .ok_or("the latest committed state is missing")?;
+        Ok(StateDb::new(storage))
+    }
+}
+
+fn set_account(
+    state: &mut State, address: &Address, account: &Account,
+) -> mazze_statedb::Result<()> {
+    let address = address.with_evm_space();
+    state.add_balance(
+        &address,
+        &account.balance.0,
+        CleanupMode::ForceCreate,
+    )?;
+    state.set_nonce(&address, &account.nonce.0)?;
+    if !account.code.is_empty() {
+        state.init_code(&address, account.code.0.clone(), Address::zero())?;
+    }
+    let mut substate = Substate::new();
+    for (key, value) in &account.storage {
+        state.set_storage(
+            &address,
+            key.as_h256().as_bytes().to_vec(),
+            value.0,
+            Address::zero(),
+            &mut substate,
+        )?;
+    }
+    Ok(())
+}
+
+fn read_account(
+    db: &mut StateDb, address: &Address,
+) -> mazze_statedb::Result<Option<Account>> {
+    let address = address.with_evm_space();
+    let account = match db.get_account(&address)? {
+        Some(account) => account,
+        None => return Ok(None),
+    };
+
+    let code = db
+        .get_code(&address, &account.code_hash)?
+        .map_or_else(Vec::new, |info| (*info.code).clone());
+
+    let mut storage = BTreeMap::new();
+    let storage_root_key =
+        StorageKey::new_storage_root_key(&address.address).with_evm_space();
+    // `delete_all` in the read mode iterates the entries without deleting
+    // them.
+    let entries =
+        db.delete_all::<access_mode::Read>(storage_root_key, None)?;
+    for (key, value) in entries {
+        let key = StorageKeyWithSpace::from_key_bytes::<SkipInputCheck>(&key);
+        if let StorageKey::StorageKey { storage_key, .. } = key.key {
+            let value: StorageValue = rlp::decode(&value)?;
+            if !value.value.is_zero() {
+                storage.insert(
+                    Uint(H256::from_slice(storage_key).into_uint()),
+                    Uint(value.value),
+                );
+            }
+        }
+    }
+
+    Ok(Some(Account {
+        balance: Uint(account.balance),
+        nonce: Uint(account.nonce),
+        code: Data(code),
+        storage,
+    }))
+}
@@ -0,0 +1,77 @@
+//! mazze-evm: Runs the Ethereum conformance fixtures, `GeneralStateTests`
+//! and `BlockchainTests`, against the eSpace of the Mazze executor on an
+//! in-memory ledger, and provides a `t8n` command for differential fuzzing.
+
+mod block;
+mod blocktest;
+mod fork;
+mod ledger;
+mod report;
+mod runner;
+mod statetest;
+mod t8n;
+mod transaction;
+mod trie;
+mod types;
+
+use clap::{load_yaml, App, ArgMatches};
+use ledger::Result;
+use report::{fixture_files, Report};
+use std::{path::Path, process};
+
+type RunFile = fn(&Path, Option<&str>, &mut Report) -> Result<()>;
+
+fn main() {
+    env_logger::init();
+
+    let yaml = load_yaml!("cli.yaml");
+    let matches = App::from_yaml(yaml).get_matches();
+
+    let result = match matches.subcommand() {
+        ("statetest", Some(args)) => run_fixtures(args, statetest::run_file),
+        ("blocktest", Some(args)) => run_fixtures(args, blocktest::run_file),
+        ("t8n", Some(args)) => run_t8n(args),
+        _ => unreachable!("a subcommand is required"),
+    };
+
+    match result {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    }
+}
+
+/// Run the fixtures given in the arguments, returning whether all of them
+/// passed or were skipped.
+fn run_fixtures(args: &ArgMatches, run_file: RunFile) -> Result<bool> {
+    let fork = args.value_of("fork");
+    let mut report = Report::default();
+    for path in args.values_of("path").into_iter().flatten() {
+        for file in fixture_files(Path::new(path))? {
+            run_file(&file, fork, &mut report)?;
+        }
+    }
+    println!("{}", report.summary());
+    Ok(!report.has_failures())
+}
+
+fn run_t8n(args: &ArgMatches) -> Result<bool> {
+    let value = |name| args.value_of(name).expect("has default value");
+    let chain_id = value("state.chainid")
+        .parse()
+        .map_err(|e| format!("invalid chain id: {}", e))?;
+    t8n::run(&t8n::T8nOptions {
+        alloc: Path::new(value("input.alloc")),
+        env: Path::new(value("input.env")),
+        txs: Path::new(value("input.txs")),
+        output_dir: Path::new(value("output.basedir")),
+        output_result: value("output.result"),
+        output_alloc: value("output.alloc"),
+        fork: value("state.fork"),
+        chain_id,
+    })?;
+    Ok(true)
+}
@@ -0,0 +1,152 @@
+//! Reports the results of the tests and compares post-states.
+
+use crate::{
+    ledger::Result,
+    types::{Account, Alloc, HexAddress},
+};
+use mazze_types::Address;
+use serde::de::DeserializeOwned;
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub enum Outcome {
+    Pass,
+    Fail(String),
+    Skip(String),
+}
+
+#[derive(Default)]
+pub struct Report {
+    passed: usize,
+    failed: usize,
+    skipped: usize,
+}
+
+impl Report {
+    pub fn record(&mut self, name: &str, outcome: Outcome) {
+        match outcome {
+            Outcome::Pass => {
+                self.passed += 1;
+                println!("PASS {}", name);
+            }
+            Outcome::Fail(reason) => {
+                self.failed += 1;
+                println!("FAIL {}", name);
+                for line in reason.lines() {
+                    println!("    {}", line);
+                }
+            }
+            Outcome::Skip(reason) => {
+                self.skipped += 1;
+                println!("SKIP {}: {}", name, reason);
+            }
+        }
+    }
+
+    pub fn has_failures(&self) -> bool { self.failed > 0 }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} passed, {} failed, {} skipped",
+            self.passed, self.failed, self.skipped
+        )
+    }
+}
+
+/// Describe the differences between the expected and the actual post-state,
+/// one account field per line. The block authors are passed in `ignored`, as
+/// the eSpace does not pay the transaction fees to them during the
+/// execution.
+pub fn diff_alloc(
+    expected: &Alloc, actual: &Alloc, ignored: &BTreeSet<Address>,
+) -> Vec<String> {
+    let addresses: BTreeSet<_> = expected.keys().chain(actual.keys()).collect();
+    let mut diff = vec![];
+    for address in addresses {
+        if ignored.contains(&address.0) {
+            continue;
+        }
+        match (expected.get(address), actual.get(address)) {
+            (Some(_), None) => diff.push(format!("{:?}: missing", address.0)),
+            (None, Some(_)) => {
+                diff.push(format!("{:?}: unexpected account", address.0))
+            }
+            (Some(expected), Some(actual)) => {
+                diff_account(address, expected, actual, &mut diff)
+            }
+            (None, None) => unreachable!(),
+        }
+    }
+    diff
+}
+
+fn diff_account(
+    address: &HexAddress, expected: &Account, actual: &Account,
+    diff: &mut Vec<String>,
+) {
+    let address = address.0;
+    if expected.balance != actual.balance {
+        diff.push(format!(
+            "{:?}: balance expected {}, got {}",
+            address, expected.balance.0, actual.balance.0
+        ));
+    }
+    if expected.nonce != actual.nonce {
+        diff.push(format!(
+            "{:?}: nonce expected {}, got {}",
+            address, expected.nonce.0, actual.nonce.0
+        ));
+    }
+    if expected.code != actual.code {
+        diff.push(format!(
+            "{:?}: code expected {} bytes, got {} bytes",
+            address,
+            expected.code.0.len(),
+            actual.code.0.len()
+        ));
+    }
+    let keys: BTreeSet<_> =
+        expected.storage.keys().chain(actual.storage.keys()).collect();
+    for key in keys {
+        let expected = expected.storage.get(key).copied().unwrap_or_default();
+        let actual = actual.storage.get(key).copied().unwrap_or_default();
+        if expected != actual {
+            diff.push(format!(
+                "{:?}: storage {:#x} expected {:#x}, got {:#x}",
+                address, key.0, expected.0, actual.0
+            ));
+        }
+    }
+}
+
+/// The fixture files at `path`, searching directories recursively.
+pub fn fixture_files(path: &Path) -> Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files = vec![];
+    let mut entries = fs::read_dir(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() {
+            files.extend(fixture_files(&entry)?);
+        } else if entry.extension().map_or(false, |ext| ext == "json") {
+            files.push(entry);
+        }
+    }
+    Ok(files)
+}
+
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let file = fs::File::open(path)
+        .map_err(|e| format!("failed to open {}: {}", path.display(), e))?;
+    serde_json::from_reader(std::io::BufReader::new(file))
+        .map_err(|e| format!("failed to parse {}: {}", path.display(), e))
+}
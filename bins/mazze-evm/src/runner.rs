@@ -0,0 +1,206 @@
+//! Executes the transactions of a block on the eSpace.
+
+use crate::{
+    fork::Fork,
+    ledger::Result,
+    types::{BlockEnv, Withdrawal},
+};
+use keccak_hash::keccak;
+use mazze_executor::{
+    executive::{ExecutionOutcome, ExecutiveContext, TransactOptions},
+    machine::{new_machine_with_builtin, Machine, VmFactory},
+    state::{CleanupMode, State},
+};
+use mazze_types::{Address, AddressSpaceUtil, Space, SpaceMap, H256, U256};
+use mazze_vm_types::{Env, Spec};
+use primitives::{log_entry::build_bloom, LogEntry, SignedTransaction};
+use rlp::RlpStream;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The chain id of the eSpace in the fixtures.
+pub const DEFAULT_CHAIN_ID: u32 = 1;
+
+const GWEI: u64 = 1_000_000_000;
+
+pub fn new_machine(fork: Fork) -> Machine {
+    new_machine_with_builtin(fork.params(), VmFactory::new(1024 * 32))
+}
+
+/// The fields of a block header the execution depends on.
+pub struct BlockInfo {
+    pub coinbase: Address,
+    pub number: u64,
+    pub timestamp: u64,
+    pub gas_limit: U256,
+    /// The previous RANDAO mix, returned by the `PREVRANDAO` opcode.
+    pub prev_randao: U256,
+    pub base_fee: U256,
+    pub withdrawals: Vec<(Address, U256)>,
+}
+
+impl From<&BlockEnv> for BlockInfo {
+    fn from(env: &BlockEnv) -> Self {
+        BlockInfo {
+            coinbase: env.current_coinbase.0,
+            number: env.current_number.as_u64(),
+            timestamp: env.current_timestamp.as_u64(),
+            gas_limit: env.current_gas_limit.0,
+            prev_randao: env
+                .current_random
+                .or(env.current_difficulty)
+                .unwrap_or_default()
+                .0,
+            base_fee: env.current_base_fee.unwrap_or_default().0,
+            withdrawals: env
+                .withdrawals
+                .iter()
+                .map(|Withdrawal { address, amount }| (address.0, amount.0))
+                .collect(),
+        }
+    }
+}
+
+/// Executes the transactions of a block one after another, recording the
+/// eSpace accounts they modify.
+pub struct BlockExecutor<'a> {
+    machine: &'a Machine,
+    env: Env,
+    spec: Spec,
+    touched: BTreeSet<Address>,
+}
+
+impl<'a> BlockExecutor<'a> {
+    pub fn new(machine: &'a Machine, block: &BlockInfo, chain_id: u32) -> Self {
+        let mut chain_ids = BTreeMap::new();
+        chain_ids.insert(Space::Native, chain_id);
+        chain_ids.insert(Space::Ethereum, chain_id);
+        let env = Env {
+            chain_id: chain_ids,
+            number: block.number,
+            author: block.coinbase,
+            timestamp: block.timestamp,
+            difficulty: block.prev_randao,
+            gas_limit: block.gas_limit,
+            epoch_height: block.number,
+            // All of the base fee is burnt, as on Ethereum.
+            base_gas_price: SpaceMap::new(U256::zero(), block.base_fee),
+            burnt_gas_price: SpaceMap::new(U256::zero(), block.base_fee),
+            ..Default::default()
+        };
+        let spec = machine.spec(env.number, env.epoch_height);
+        BlockExecutor {
+            machine,
+            env,
+            spec,
+            touched: BTreeSet::new(),
+        }
+    }
+
+    pub fn gas_used(&self) -> U256 { self.env.accumulated_gas_used }
+
+    /// The eSpace accounts modified so far.
+    pub fn touched(&self) -> &BTreeSet<Address> { &self.touched }
+
+    pub fn transact(
+        &mut self, state: &mut State, tx: &SignedTransaction,
+    ) -> Result<ExecutionOutcome> {
+        state.start_access_tracking();
+        let outcome =
+            ExecutiveContext::new(state, &self.env, self.machine, &self.spec)
+                .transact(tx, TransactOptions::default());
+        self.record_access(state);
+
+        let outcome = outcome.map_err(|e| e.to_string())?;
+        self.env.accumulated_gas_used += outcome.gas_used();
+        Ok(outcome)
+    }
+
+    /// Pay the priority fee of a transaction to the block author, as
+    /// Ethereum does after each transaction. The eSpace doesn't pay it during
+    /// the execution.
+    pub fn pay_priority_fee(
+        &mut self, state: &mut State, outcome: &ExecutionOutcome,
+    ) -> Result<()> {
+        let burnt_fee = outcome.burnt_fee(&self.spec).unwrap_or_default();
+        let priority_fee = outcome.gas_fee().saturating_sub(burnt_fee);
+        state.start_access_tracking();
+        let result = state.add_balance(
+            &self.env.author.with_evm_space(),
+            &priority_fee,
+            CleanupMode::NoEmpty,
+        );
+        self.record_access(state);
+        result.map_err(|e| e.to_string())
+    }
+
+    /// Credit the withdrawals of the block, given in Gwei.
+    pub fn apply_withdrawals(
+        &mut self, state: &mut State, withdrawals: &[(Address, U256)],
+    ) -> Result<()> {
+        state.start_access_tracking();
+        let result = withdrawals.iter().try_for_each(|(address, amount)| {
+            state.add_balance(
+                &address.with_evm_space(),
+                &(*amount * U256::from(GWEI)),
+                CleanupMode::NoEmpty,
+            )
+        });
+        self.record_access(state);
+        result.map_err(|e| e.to_string())
+    }
+
+    fn record_access(&mut self, state: &mut State) {
+        let access = state.finish_access_tracking();
+        self.touched.extend(
+            access
+                .written_accounts
+                .iter()
+                .filter(|address| address.space == Space::Ethereum)
+                .map(|address| address.address),
+        );
+    }
+}
+
+/// The reason a transaction was rejected before execution, if it was.
+pub fn rejection_reason(outcome: &ExecutionOutcome) -> Option<String> {
+    match outcome {
+        ExecutionOutcome::NotExecutedDrop(e) => Some(format!("{:?}", e)),
+        ExecutionOutcome::NotExecutedToReconsiderPacking(e) => {
+            Some(format!("{:?}", e))
+        }
+        _ => None,
+    }
+}
+
+/// The hash of the RLP encoded logs, as in the Ethereum fixtures.
+pub fn logs_hash(logs: &[LogEntry]) -> H256 {
+    let mut stream = RlpStream::new();
+    append_logs(&mut stream, logs);
+    keccak(stream.out())
+}
+
+/// The encoding of an Ethereum receipt, prefixed with the type of a typed
+/// transaction as in the receipts trie.
+pub fn encode_receipt(
+    tx_type: Option<u8>, success: bool, cumulative_gas_used: &U256,
+    logs: &[LogEntry],
+) -> Vec<u8> {
+    let mut stream = RlpStream::new_list(4);
+    stream.append(&(success as u8));
+    stream.append(cumulative_gas_used);
+    stream.append(&build_bloom(logs));
+    append_logs(&mut stream, logs);
+    let mut encoded: Vec<u8> = tx_type.into_iter().collect();
+    encoded.extend(stream.out());
+    encoded
+}
+
+fn append_logs(stream: &mut RlpStream, logs: &[LogEntry]) {
+    stream.begin_list(logs.len());
+    for log in logs {
+        stream.begin_list(3);
+        stream.append(&log.address);
+        stream.append_list(&log.topics);
+        stream.append(&log.data);
+    }
+}
@@ -0,0 +1,115 @@
+//! Runs the `GeneralStateTests` fixtures.
+//!
+//! Mazze stores the ledger in a different trie than Ethereum, so the state
+//! roots of the fixtures cannot be compared. A test is checked against the
+//! hash of its logs and, when the fixture has one, against its full
+//! post-state.
+
+use crate::{
+    fork::Fork,
+    ledger::{Ledger, Result},
+    report::{diff_alloc, read_json, Outcome, Report},
+    runner::{
+        logs_hash, new_machine, rejection_reason, BlockExecutor, BlockInfo,
+        DEFAULT_CHAIN_ID,
+    },
+    transaction::{self, BuiltTransaction},
+    types::{PostState, StateTest},
+};
+use std::{collections::BTreeMap, path::Path};
+
+pub fn run_file(
+    path: &Path, fork_filter: Option<&str>, report: &mut Report,
+) -> Result<()> {
+    let tests: BTreeMap<String, StateTest> = read_json(path)?;
+    for (name, test) in &tests {
+        for (fork_name, posts) in &test.post {
+            if fork_filter.map_or(false, |fork| fork != fork_name) {
+                continue;
+            }
+            for (index, post) in posts.iter().enumerate() {
+                let id = format!("{}/{}/{}", name, fork_name, index);
+                let outcome = match Fork::from_name(fork_name) {
+                    Some(fork) => run_case(test, fork, post)
+                        .unwrap_or_else(Outcome::Fail),
+                    None => {
+                        Outcome::Skip(format!("unsupported fork {}", fork_name))
+                    }
+                };
+                report.record(&id, outcome);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_case(test: &StateTest, fork: Fork, post: &PostState) -> Result<Outcome> {
+    let tx = match transaction::from_state_test(
+        &test.transaction,
+        &post.indexes,
+        post.txbytes.as_ref().map(|raw| raw.0.as_slice()),
+        DEFAULT_CHAIN_ID,
+    ) {
+        BuiltTransaction::Valid(tx) => tx,
+        BuiltTransaction::Invalid(reason) => {
+            return Ok(expect_rejection(post, reason));
+        }
+        BuiltTransaction::Unsupported(kind) => {
+            return Ok(Outcome::Skip(format!("unsupported {}", kind)));
+        }
+    };
+
+    let machine = new_machine(fork);
+    let mut ledger = Ledger::new(&machine, &test.pre)?;
+    let mut state = ledger.open()?;
+    let block = BlockInfo::from(&test.env);
+    let mut executor = BlockExecutor::new(&machine, &block, DEFAULT_CHAIN_ID);
+
+    let outcome = executor.transact(&mut state, &tx)?;
+    if let Some(reason) = rejection_reason(&outcome) {
+        return Ok(expect_rejection(post, reason));
+    }
+    if let Some(exception) = &post.expect_exception {
+        return Ok(Outcome::Fail(format!(
+            "expected {}, but the transaction was executed",
+            exception
+        )));
+    }
+
+    let mut diff = vec![];
+    let logs = logs_hash(&outcome.transaction_logs());
+    if post.logs.as_h256() != Some(logs) {
+        diff.push(format!(
+            "logs hash expected {:?}, got {:?}",
+            post.logs.0, logs
+        ));
+    }
+
+    ledger.commit(state)?;
+    if let Some(expected) = &post.state {
+        let addresses = test
+            .pre
+            .keys()
+            .chain(expected.keys())
+            .map(|address| address.0)
+            .chain(executor.touched().iter().cloned())
+            .collect();
+        let actual = ledger.dump(&addresses)?;
+        let ignored = [block.coinbase].into_iter().collect();
+        diff.extend(diff_alloc(expected, &actual, &ignored));
+    }
+
+    Ok(if diff.is_empty() {
+        Outcome::Pass
+    } else {
+        Outcome::Fail(diff.join("\n"))
+    })
+}
+
+fn expect_rejection(post: &PostState, reason: String) -> Outcome {
+    if post.expect_exception.is_some() {
+        Outcome::Pass
+    } else {
+        Outcome::Fail(format!("transaction rejected: {}", reason))
+    }
+}
@@ -0,0 +1,171 @@
+//! A state transition tool compatible with the `evm t8n` interface, for the
+//! differential fuzzers. It reads a pre-state, a block environment and a
+//! list of transactions, and writes the execution result and the post-state.
+//!
+//! The post-state contains the accounts of the pre-state and the accounts
+//! modified by the transactions. The state root is the root of the Mazze
+//! state trie, so only the post-state can be compared across clients.
+
+use crate::{
+    fork::Fork,
+    ledger::{Ledger, Result},
+    report::read_json,
+    runner::{
+        logs_hash, new_machine, rejection_reason, BlockExecutor, BlockInfo,
+    },
+    transaction::{self, decode_raw, BuiltTransaction},
+    types::{
+        Alloc, BlockEnv, Data, HexAddress, RejectedTransaction, T8nLog,
+        T8nReceipt, T8nResult, T8nTransaction,
+    },
+};
+use primitives::{Action, LogEntry, TransactionStatus};
+use serde::Serialize;
+use std::{fs, path::Path};
+
+pub struct T8nOptions<'a> {
+    pub alloc: &'a Path,
+    pub env: &'a Path,
+    pub txs: &'a Path,
+    pub output_dir: &'a Path,
+    pub output_result: &'a str,
+    pub output_alloc: &'a str,
+    pub fork: &'a str,
+    pub chain_id: u32,
+}
+
+pub fn run(options: &T8nOptions) -> Result<()> {
+    let fork = Fork::from_name(options.fork)
+        .ok_or_else(|| format!("unsupported fork {}", options.fork))?;
+    let alloc: Alloc = read_json(options.alloc)?;
+    let env: BlockEnv = read_json(options.env)?;
+    let transactions = read_transactions(options.txs, options.chain_id)?;
+
+    let machine = new_machine(fork);
+    let mut ledger = Ledger::new(&machine, &alloc)?;
+    let mut state = ledger.open()?;
+    let block = BlockInfo::from(&env);
+    let mut executor = BlockExecutor::new(&machine, &block, options.chain_id);
+
+    let mut receipts = vec![];
+    let mut rejected = vec![];
+    let mut logs = vec![];
+    for (index, tx) in transactions.into_iter().enumerate() {
+        let tx = match tx {
+            BuiltTransaction::Valid(tx) => tx,
+            BuiltTransaction::Invalid(error) => {
+                rejected.push(RejectedTransaction { index, error });
+                continue;
+            }
+            BuiltTransaction::Unsupported(kind) => {
+                rejected.push(RejectedTransaction {
+                    index,
+                    error: format!("unsupported {}", kind),
+                });
+                continue;
+            }
+        };
+
+        let outcome = executor.transact(&mut state, &tx)?;
+        if let Some(error) = rejection_reason(&outcome) {
+            rejected.push(RejectedTransaction { index, error });
+            continue;
+        }
+
+        let tx_logs = outcome.transaction_logs();
+        let success = outcome.outcome_status() == TransactionStatus::Success;
+        let contract_address = match (tx.action(), outcome.try_as_executed()) {
+            (Action::Create, Some(executed)) if success => executed
+                .contracts_created
+                .first()
+                .map(|address| HexAddress(address.address)),
+            _ => None,
+        };
+        receipts.push(T8nReceipt {
+            transaction_hash: tx.hash().into(),
+            transaction_index: (index as u64).into(),
+            status: (success as u64).into(),
+            gas_used: outcome.gas_used().into(),
+            cumulative_gas_used: executor.gas_used().into(),
+            contract_address,
+            logs: tx_logs.iter().map(make_log).collect(),
+        });
+        logs.extend(tx_logs);
+    }
+    executor.apply_withdrawals(&mut state, &block.withdrawals)?;
+
+    let state_root = ledger.commit(state)?;
+    let addresses = alloc
+        .keys()
+        .map(|address| address.0)
+        .chain(executor.touched().iter().cloned())
+        .collect();
+    let post_alloc = ledger.dump(&addresses)?;
+
+    let result = T8nResult {
+        state_root: state_root.into(),
+        logs_hash: logs_hash(&logs).into(),
+        receipts,
+        rejected,
+        gas_used: executor.gas_used().into(),
+        current_base_fee: env.current_base_fee,
+    };
+    write_output(options.output_dir, options.output_result, &result)?;
+    write_output(options.output_dir, options.output_alloc, &post_alloc)
+}
+
+/// Read the transactions, either as a JSON array or, for a file with the
+/// `.rlp` extension, as a JSON string holding the RLP list of the signed
+/// transactions.
+fn read_transactions(
+    path: &Path, chain_id: u32,
+) -> Result<Vec<BuiltTransaction>> {
+    if path.extension().map_or(false, |ext| ext == "rlp") {
+        let Data(raw) = read_json(path)?;
+        let list = rlp::Rlp::new(&raw);
+        let items = list
+            .iter()
+            .map(|tx| {
+                let raw = if tx.is_list() {
+                    tx.as_raw()
+                } else {
+                    tx.data().map_err(|e| format!("{:?}", e))?
+                };
+                Ok(match decode_raw(raw) {
+                    Ok(tx) => BuiltTransaction::Valid(tx),
+                    Err(e) => BuiltTransaction::Invalid(e),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(items)
+    } else {
+        let txs: Vec<T8nTransaction> = read_json(path)?;
+        Ok(txs
+            .iter()
+            .map(|tx| transaction::from_t8n(tx, chain_id))
+            .collect())
+    }
+}
+
+fn make_log(log: &LogEntry) -> T8nLog {
+    T8nLog {
+        address: HexAddress(log.address),
+        topics: log.topics.iter().map(|topic| (*topic).into()).collect(),
+        data: Data(log.data.clone()),
+    }
+}
+
+fn write_output<T: Serialize>(
+    dir: &Path, name: &str, value: &T,
+) -> Result<()> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("failed to serialize {}: {}", name, e))?;
+    if name == "stdout" {
+        println!("{}", json);
+        return Ok(());
+    }
+    let path = dir.join(name);
+    fs::write(&path, json)
+        .map_err(|e| format!("failed to write {}: {}", path.display(), e))
+}
+
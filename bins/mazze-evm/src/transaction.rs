@@ -0,0 +1,297 @@
+//! Builds the eSpace transactions described by the fixtures.
+
+use crate::{
+    ledger::Result,
+    types::{
+        AccessListEntry, AuthorizationEntry, MultiTransaction, PostIndexes,
+        T8nTransaction,
+    },
+};
+use mazze_types::{Address, Space, H256, U256};
+use mazzekey::{Secret, Signature};
+use primitives::{
+    transaction::{
+        eth_transaction::eip155_signature, AuthorizationListItem,
+        Eip1559Transaction, Eip155Transaction, Eip2930Transaction,
+        Eip7702Transaction, EthereumTransaction,
+    },
+    AccessList, AccessListItem, Action, SignedTransaction, Transaction,
+    TransactionWithSignature,
+};
+
+/// The outcome of building a transaction. A transaction can be malformed on
+/// purpose, in which case the test expects it to be rejected.
+pub enum BuiltTransaction {
+    Valid(SignedTransaction),
+    Invalid(String),
+    /// A transaction of a kind the eSpace does not support.
+    Unsupported(&'static str),
+}
+
+/// Decode and recover a transaction in its network encoding.
+pub fn decode_raw(raw: &[u8]) -> Result<SignedTransaction> {
+    let tx = TransactionWithSignature::from_raw(raw)
+        .map_err(|e| format!("invalid transaction encoding: {:?}", e))?;
+    if tx.space() != Space::Ethereum {
+        return Err("not an eSpace transaction".into());
+    }
+    tx.check_y_parity()
+        .map_err(|e| format!("invalid signature: {}", e))?;
+    let public = tx
+        .recover_public()
+        .map_err(|e| format!("invalid signature: {}", e))?;
+    Ok(SignedTransaction::new(public, tx))
+}
+
+/// Build the transaction of a state test selected by `indexes`. The
+/// `txbytes` filled by the test generators are preferred, as they also cover
+/// the malformed transactions.
+pub fn from_state_test(
+    tx: &MultiTransaction, indexes: &PostIndexes, txbytes: Option<&[u8]>,
+    chain_id: u32,
+) -> BuiltTransaction {
+    if tx.blob_versioned_hashes.is_some() {
+        return BuiltTransaction::Unsupported("blob transaction");
+    }
+    if let Some(raw) = txbytes {
+        return match decode_raw(raw) {
+            Ok(tx) => BuiltTransaction::Valid(tx),
+            Err(e) => BuiltTransaction::Invalid(e),
+        };
+    }
+
+    let fields = (|| -> Result<TransactionFields> {
+        let access_list = tx.access_lists.get(indexes.data).cloned().flatten();
+        Ok(TransactionFields {
+            tx_type: if tx.authorization_list.is_some() {
+                4
+            } else if tx.max_fee_per_gas.is_some() {
+                2
+            } else if access_list.is_some() {
+                1
+            } else {
+                0
+            },
+            chain_id: Some(chain_id),
+            nonce: tx.nonce.0,
+            gas_price: tx.gas_price.map(|p| p.0),
+            max_fee_per_gas: tx.max_fee_per_gas.map(|p| p.0),
+            max_priority_fee_per_gas: tx.max_priority_fee_per_gas.map(|p| p.0),
+            gas: get_indexed(&tx.gas_limit, indexes.gas, "gasLimit")?.0,
+            to: tx.to.as_recipient()?,
+            value: get_indexed(&tx.value, indexes.value, "value")?.0,
+            data: get_indexed(&tx.data, indexes.data, "data")?.0.clone(),
+            access_list,
+            authorization_list: tx.authorization_list.clone(),
+        })
+    })();
+
+    let signed = fields.and_then(|fields| {
+        let secret = Secret::from_slice(&tx.secret_key.0)
+            .ok_or("invalid secret key")?;
+        let unsigned = fields.into_transaction()?;
+        let signed = unsigned.sign(&secret).transaction;
+        decode_raw(&rlp::encode(&signed.transaction))
+    });
+    match signed {
+        Ok(tx) => BuiltTransaction::Valid(tx),
+        Err(e) => BuiltTransaction::Invalid(e),
+    }
+}
+
+/// Build a transaction of the t8n input.
+pub fn from_t8n(tx: &T8nTransaction, chain_id: u32) -> BuiltTransaction {
+    if tx.tx_type.as_u64() == 3 {
+        return BuiltTransaction::Unsupported("blob transaction");
+    }
+
+    let signed = (|| -> Result<SignedTransaction> {
+        let tx_type = tx.tx_type.as_u64() as u8;
+        let v = tx.v.as_u64();
+        let tx_chain_id = if tx_type != 0 {
+            Some(tx.chain_id.map_or(chain_id, |id| id.as_u64() as u32))
+        } else if tx.secret_key.is_some() {
+            // Legacy transactions signed here are replay-protected unless
+            // stated otherwise.
+            if tx.protected {
+                Some(chain_id)
+            } else {
+                None
+            }
+        } else {
+            eip155_signature::extract_chain_id_from_legacy_v(v)
+                .map(|id| id as u32)
+        };
+        let unsigned = TransactionFields {
+            tx_type,
+            chain_id: tx_chain_id,
+            nonce: tx.nonce.0,
+            gas_price: tx.gas_price.map(|p| p.0),
+            max_fee_per_gas: tx.max_fee_per_gas.map(|p| p.0),
+            max_priority_fee_per_gas: tx.max_priority_fee_per_gas.map(|p| p.0),
+            gas: tx.gas.0,
+            to: match &tx.to {
+                Some(to) => to.as_recipient()?,
+                None => None,
+            },
+            value: tx.value.0,
+            data: tx.input.0.clone(),
+            access_list: tx.access_list.clone(),
+            authorization_list: tx.authorization_list.clone(),
+        }
+        .into_transaction()?;
+
+        let signed = match &tx.secret_key {
+            Some(secret) => {
+                let secret =
+                    Secret::from_slice(&secret.0).ok_or("invalid secret key")?;
+                unsigned.sign(&secret).transaction
+            }
+            None => {
+                let v = if tx_type == 0 {
+                    eip155_signature::extract_standard_v(v)
+                } else {
+                    v as u8
+                };
+                unsigned.with_signature(Signature::from_rsv(
+                    &tx.r.as_h256(),
+                    &tx.s.as_h256(),
+                    v,
+                ))
+            }
+        };
+        // Round trip through the network encoding to get the hash.
+        decode_raw(&rlp::encode(&signed.transaction))
+    })();
+
+    match signed {
+        Ok(tx) => BuiltTransaction::Valid(tx),
+        Err(e) => BuiltTransaction::Invalid(e),
+    }
+}
+
+struct TransactionFields {
+    tx_type: u8,
+    chain_id: Option<u32>,
+    nonce: U256,
+    gas_price: Option<U256>,
+    max_fee_per_gas: Option<U256>,
+    max_priority_fee_per_gas: Option<U256>,
+    gas: U256,
+    to: Option<Address>,
+    value: U256,
+    data: Vec<u8>,
+    access_list: Option<Vec<AccessListEntry>>,
+    authorization_list: Option<Vec<AuthorizationEntry>>,
+}
+
+impl TransactionFields {
+    fn into_transaction(self) -> Result<Transaction> {
+        let action = match self.to {
+            Some(to) => Action::Call(to),
+            None => Action::Create,
+        };
+        let access_list = self.access_list.as_deref().map(make_access_list);
+        let chain_id = self.chain_id.ok_or("missing chain id");
+        let max_fee_per_gas = self.max_fee_per_gas.or(self.gas_price);
+
+        let tx = match self.tx_type {
+            0 => EthereumTransaction::Eip155(Eip155Transaction {
+                nonce: self.nonce,
+                gas_price: self.gas_price.ok_or("missing gas price")?,
+                gas: self.gas,
+                action,
+                value: self.value,
+                chain_id: self.chain_id,
+                data: self.data,
+            }),
+            1 => EthereumTransaction::Eip2930(Eip2930Transaction {
+                chain_id: chain_id?,
+                nonce: self.nonce,
+                gas_price: self.gas_price.ok_or("missing gas price")?,
+                gas: self.gas,
+                action,
+                value: self.value,
+                data: self.data,
+                access_list: access_list.unwrap_or_default(),
+            }),
+            2 => EthereumTransaction::Eip1559(Eip1559Transaction {
+                chain_id: chain_id?,
+                nonce: self.nonce,
+                max_priority_fee_per_gas: self
+                    .max_priority_fee_per_gas
+                    .ok_or("missing max priority fee")?,
+                max_fee_per_gas: max_fee_per_gas.ok_or("missing max fee")?,
+                gas: self.gas,
+                action,
+                value: self.value,
+                data: self.data,
+                access_list: access_list.unwrap_or_default(),
+            }),
+            4 => EthereumTransaction::Eip7702(Eip7702Transaction {
+                chain_id: chain_id?,
+                nonce: self.nonce,
+                max_priority_fee_per_gas: self
+                    .max_priority_fee_per_gas
+                    .ok_or("missing max priority fee")?,
+                max_fee_per_gas: max_fee_per_gas.ok_or("missing max fee")?,
+                gas: self.gas,
+                action,
+                value: self.value,
+                data: self.data,
+                access_list: access_list.unwrap_or_default(),
+                authorization_list: self
+                    .authorization_list
+                    .unwrap_or_default()
+                    .iter()
+                    .map(make_authorization)
+                    .collect(),
+            }),
+            tx_type => {
+                return Err(format!("unknown transaction type {}", tx_type))
+            }
+        };
+        Ok(Transaction::Ethereum(tx))
+    }
+}
+
+fn get_indexed<'a, T>(
+    values: &'a [T], index: usize, name: &str,
+) -> Result<&'a T> {
+    values
+        .get(index)
+        .ok_or_else(|| format!("{} index {} out of range", name, index))
+}
+
+fn make_access_list(entries: &[AccessListEntry]) -> AccessList {
+    entries
+        .iter()
+        .map(|entry| AccessListItem {
+            address: entry.address.0,
+            storage_keys: entry
+                .storage_keys
+                .iter()
+                .map(|key| {
+                    key.as_h256().unwrap_or_else(|| {
+                        let mut padded = [0u8; 32];
+                        let len = key.0.len().min(32);
+                        padded[32 - len..]
+                            .copy_from_slice(&key.0[key.0.len() - len..]);
+                        H256(padded)
+                    })
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn make_authorization(entry: &AuthorizationEntry) -> AuthorizationListItem {
+    AuthorizationListItem {
+        chain_id: entry.chain_id.0,
+        address: entry.address.0,
+        nonce: entry.nonce.as_u64(),
+        y_parity: entry.y_parity.as_u64() as u8,
+        r: entry.r.0,
+        s: entry.s.0,
+    }
+}
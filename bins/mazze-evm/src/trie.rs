@@ -0,0 +1,145 @@
+//! The roots of the Ethereum Merkle Patricia tries, which the block headers
+//! of the fixtures commit to. Mazze stores its ledger in a different trie, so
+//! the roots are computed from the accounts read back from the ledger.
+
+use crate::types::Alloc;
+use keccak_hash::{keccak, KECCAK_NULL_RLP};
+use mazze_types::H256;
+use rlp::RlpStream;
+use std::collections::BTreeMap;
+
+/// The root of the trie holding `entries`.
+pub fn trie_root(entries: &BTreeMap<Vec<u8>, Vec<u8>>) -> H256 {
+    if entries.is_empty() {
+        return KECCAK_NULL_RLP;
+    }
+    let items: Vec<(Vec<u8>, &[u8])> = entries
+        .iter()
+        .map(|(key, value)| (nibbles(key), value.as_slice()))
+        .collect();
+    keccak(encode_node(&items, 0))
+}
+
+/// The root of a trie keyed by the RLP encoded indexes of `values`, as the
+/// transactions, receipts and withdrawals tries.
+pub fn ordered_trie_root<T: AsRef<[u8]>>(values: &[T]) -> H256 {
+    trie_root(
+        &values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| {
+                (rlp::encode(&index).to_vec(), value.as_ref().to_vec())
+            })
+            .collect(),
+    )
+}
+
+/// The root of the secure trie of the accounts in `alloc`.
+pub fn state_root(alloc: &Alloc) -> H256 {
+    let mut accounts = BTreeMap::new();
+    for (address, account) in alloc {
+        let storage = account
+            .storage
+            .iter()
+            .filter(|(_, value)| !value.0.is_zero())
+            .map(|(key, value)| {
+                (
+                    keccak(key.as_h256()).as_bytes().to_vec(),
+                    rlp::encode(&value.0).to_vec(),
+                )
+            })
+            .collect();
+        let mut stream = RlpStream::new_list(4);
+        stream.append(&account.nonce.0);
+        stream.append(&account.balance.0);
+        stream.append(&trie_root(&storage));
+        stream.append(&keccak(&account.code.0));
+        accounts.insert(
+            keccak(address.0).as_bytes().to_vec(),
+            stream.out().to_vec(),
+        );
+    }
+    trie_root(&accounts)
+}
+
+fn nibbles(key: &[u8]) -> Vec<u8> {
+    key.iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect()
+}
+
+/// The compact encoding of a path, flagged as the path of a leaf or of an
+/// extension.
+fn hex_prefix(path: &[u8], leaf: bool) -> Vec<u8> {
+    let flag = if leaf { 0x20 } else { 0x00 };
+    let mut encoded = Vec::with_capacity(path.len() / 2 + 1);
+    let rest = if path.len() % 2 == 1 {
+        encoded.push(flag | 0x10 | path[0]);
+        &path[1..]
+    } else {
+        encoded.push(flag);
+        path
+    };
+    encoded.extend(rest.chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+    encoded
+}
+
+/// The RLP encoding of the node holding `items`, whose keys share their
+/// first `depth` nibbles. The items are sorted by key.
+fn encode_node(items: &[(Vec<u8>, &[u8])], depth: usize) -> Vec<u8> {
+    let mut stream = RlpStream::new();
+    if let [(key, value)] = items {
+        stream.begin_list(2);
+        stream.append(&hex_prefix(&key[depth..], true));
+        stream.append(value);
+        return stream.out().to_vec();
+    }
+
+    // The first and the last keys share the prefix of all the keys.
+    let (first, last) = (&items[0].0, &items[items.len() - 1].0);
+    let shared = first[depth..]
+        .iter()
+        .zip(&last[depth..])
+        .take_while(|(a, b)| a == b)
+        .count();
+    if shared > 0 {
+        stream.begin_list(2);
+        stream.append(&hex_prefix(&first[depth..depth + shared], false));
+        append_child(&mut stream, &encode_node(items, depth + shared));
+        return stream.out().to_vec();
+    }
+
+    stream.begin_list(17);
+    let mut value = None;
+    let mut rest = items;
+    if rest[0].0.len() == depth {
+        value = Some(rest[0].1);
+        rest = &rest[1..];
+    }
+    for nibble in 0..16 {
+        let count = rest
+            .iter()
+            .take_while(|(key, _)| key[depth] == nibble)
+            .count();
+        if count == 0 {
+            stream.append_empty_data();
+        } else {
+            append_child(&mut stream, &encode_node(&rest[..count], depth + 1));
+        }
+        rest = &rest[count..];
+    }
+    match value {
+        Some(value) => stream.append(&value),
+        None => stream.append_empty_data(),
+    };
+    stream.out().to_vec()
+}
+
+/// Append a child node, inline if its encoding is shorter than a hash.
+fn append_child(stream: &mut RlpStream, encoded: &[u8]) {
+    if encoded.len() < 32 {
+        stream.append_raw(encoded, 1);
+    } else {
+        stream.append(&keccak(encoded));
+    }
+}
@@ -0,0 +1,366 @@
+//! The JSON formats of the Ethereum test fixtures and of the t8n tool.
+
+use mazze_types::{Address, BigEndianHash, H256, U256};
+use rustc_hex::{FromHex, ToHex};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// An unsigned integer, either hexadecimal with a `0x` prefix or decimal.
+/// Leading zeros are accepted, as the fixtures use them for storage keys.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Uint(pub U256);
+
+impl Uint {
+    pub fn as_u64(&self) -> u64 { self.0.low_u64() }
+
+    pub fn as_h256(&self) -> H256 { BigEndianHash::from_uint(&self.0) }
+}
+
+impl From<U256> for Uint {
+    fn from(value: U256) -> Self { Uint(value) }
+}
+
+impl From<u64> for Uint {
+    fn from(value: u64) -> Self { Uint(value.into()) }
+}
+
+impl<'de> Deserialize<'de> for Uint {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        let value = String::deserialize(deserializer)?;
+        let parsed = match value.strip_prefix("0x") {
+            Some("") => Ok(U256::zero()),
+            Some(hex) => {
+                let hex = hex.trim_start_matches('0');
+                if hex.is_empty() {
+                    Ok(U256::zero())
+                } else {
+                    hex.parse::<U256>().map_err(|e| format!("{:?}", e))
+                }
+            }
+            None => {
+                U256::from_dec_str(&value).map_err(|e| format!("{:?}", e))
+            }
+        };
+        parsed.map(Uint).map_err(|e| {
+            de::Error::custom(format!("invalid integer {}: {}", value, e))
+        })
+    }
+}
+
+impl Serialize for Uint {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        serializer.serialize_str(&format!("{:#x}", self.0))
+    }
+}
+
+/// Hexadecimal bytes with a `0x` prefix.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Data(pub Vec<u8>);
+
+impl Data {
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+
+    pub fn as_h256(&self) -> Option<H256> {
+        if self.0.len() == 32 {
+            Some(H256::from_slice(&self.0))
+        } else {
+            None
+        }
+    }
+
+    /// The address of a `to` field, `None` for contract creations.
+    pub fn as_recipient(&self) -> Result<Option<Address>, String> {
+        match self.0.len() {
+            0 => Ok(None),
+            20 => Ok(Some(Address::from_slice(&self.0))),
+            len => Err(format!("invalid recipient length {}", len)),
+        }
+    }
+}
+
+impl From<H256> for Data {
+    fn from(value: H256) -> Self { Data(value.as_bytes().to_vec()) }
+}
+
+impl<'de> Deserialize<'de> for Data {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        let value = String::deserialize(deserializer)?;
+        let hex = value.strip_prefix("0x").unwrap_or(&value);
+        hex.from_hex().map(Data).map_err(|e| {
+            de::Error::custom(format!("invalid hex data {}: {}", value, e))
+        })
+    }
+}
+
+impl Serialize for Data {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        serializer.serialize_str(&format!("0x{}", self.0.to_hex::<String>()))
+    }
+}
+
+/// A 20-byte address in hexadecimal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HexAddress(pub Address);
+
+impl<'de> Deserialize<'de> for HexAddress {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        let Data(bytes) = Data::deserialize(deserializer)?;
+        if bytes.len() != 20 {
+            return Err(de::Error::custom(format!(
+                "invalid address length {}",
+                bytes.len()
+            )));
+        }
+        Ok(HexAddress(Address::from_slice(&bytes)))
+    }
+}
+
+impl Serialize for HexAddress {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        Data(self.0.as_bytes().to_vec()).serialize(serializer)
+    }
+}
+
+/// An account of a pre-state or post-state.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Account {
+    #[serde(default)]
+    pub balance: Uint,
+    #[serde(default)]
+    pub nonce: Uint,
+    #[serde(default, skip_serializing_if = "Data::is_empty")]
+    pub code: Data,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub storage: BTreeMap<Uint, Uint>,
+}
+
+impl Account {
+    /// Whether the account is empty in the sense of EIP-161, with its zero
+    /// storage slots ignored.
+    pub fn is_empty(&self) -> bool {
+        self.balance.0.is_zero()
+            && self.nonce.0.is_zero()
+            && self.code.is_empty()
+            && self.storage.values().all(|value| value.0.is_zero())
+    }
+}
+
+pub type Alloc = BTreeMap<HexAddress, Account>;
+
+/// The block environment of a state test or of a t8n run.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockEnv {
+    pub current_coinbase: HexAddress,
+    pub current_gas_limit: Uint,
+    pub current_number: Uint,
+    pub current_timestamp: Uint,
+    #[serde(default)]
+    pub current_difficulty: Option<Uint>,
+    #[serde(default)]
+    pub current_random: Option<Uint>,
+    #[serde(default)]
+    pub current_base_fee: Option<Uint>,
+    #[serde(default)]
+    pub withdrawals: Vec<Withdrawal>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Withdrawal {
+    pub address: HexAddress,
+    /// The amount in Gwei.
+    pub amount: Uint,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListEntry {
+    pub address: HexAddress,
+    pub storage_keys: Vec<Data>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorizationEntry {
+    pub chain_id: Uint,
+    pub address: HexAddress,
+    pub nonce: Uint,
+    #[serde(alias = "v")]
+    pub y_parity: Uint,
+    pub r: Uint,
+    pub s: Uint,
+}
+
+/// A state test. The transaction fields indexed by `PostState::indexes`
+/// describe several transactions at once.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StateTest {
+    pub env: BlockEnv,
+    pub pre: Alloc,
+    pub transaction: MultiTransaction,
+    pub post: BTreeMap<String, Vec<PostState>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiTransaction {
+    pub data: Vec<Data>,
+    pub gas_limit: Vec<Uint>,
+    pub value: Vec<Uint>,
+    pub nonce: Uint,
+    #[serde(default)]
+    pub gas_price: Option<Uint>,
+    #[serde(default)]
+    pub max_fee_per_gas: Option<Uint>,
+    #[serde(default)]
+    pub max_priority_fee_per_gas: Option<Uint>,
+    pub to: Data,
+    pub secret_key: Data,
+    #[serde(default)]
+    pub access_lists: Vec<Option<Vec<AccessListEntry>>>,
+    #[serde(default)]
+    pub authorization_list: Option<Vec<AuthorizationEntry>>,
+    #[serde(default)]
+    pub blob_versioned_hashes: Option<Vec<Data>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostState {
+    pub logs: Data,
+    pub indexes: PostIndexes,
+    #[serde(default)]
+    pub txbytes: Option<Data>,
+    #[serde(default)]
+    pub expect_exception: Option<String>,
+    /// The full post-state, only filled by the newer test generators.
+    #[serde(default)]
+    pub state: Option<Alloc>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct PostIndexes {
+    pub data: usize,
+    pub gas: usize,
+    pub value: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockchainTest {
+    pub network: String,
+    #[serde(rename = "genesisRLP")]
+    pub genesis_rlp: Data,
+    pub pre: Alloc,
+    pub blocks: Vec<TestBlock>,
+    #[serde(default)]
+    pub post_state: Option<Alloc>,
+    #[serde(default)]
+    pub lastblockhash: Option<Data>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestBlock {
+    pub rlp: Data,
+    #[serde(default)]
+    pub expect_exception: Option<String>,
+    /// The decoded header, only given for the valid blocks.
+    #[serde(default)]
+    pub block_header: Option<TestBlockHeader>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TestBlockHeader {
+    pub hash: Data,
+}
+
+/// A transaction of the t8n input, either signed with `v`, `r` and `s`, or
+/// to be signed with `secretKey`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct T8nTransaction {
+    #[serde(rename = "type", default)]
+    pub tx_type: Uint,
+    #[serde(default)]
+    pub chain_id: Option<Uint>,
+    pub nonce: Uint,
+    #[serde(default)]
+    pub gas_price: Option<Uint>,
+    #[serde(default)]
+    pub max_fee_per_gas: Option<Uint>,
+    #[serde(default)]
+    pub max_priority_fee_per_gas: Option<Uint>,
+    #[serde(alias = "gasLimit")]
+    pub gas: Uint,
+    #[serde(default)]
+    pub to: Option<Data>,
+    #[serde(default)]
+    pub value: Uint,
+    #[serde(default, alias = "data")]
+    pub input: Data,
+    #[serde(default)]
+    pub access_list: Option<Vec<AccessListEntry>>,
+    #[serde(default)]
+    pub authorization_list: Option<Vec<AuthorizationEntry>>,
+    #[serde(default)]
+    pub v: Uint,
+    #[serde(default)]
+    pub r: Uint,
+    #[serde(default)]
+    pub s: Uint,
+    #[serde(default)]
+    pub secret_key: Option<Data>,
+    /// Whether a legacy transaction is replay-protected by EIP-155.
+    #[serde(default = "default_protected")]
+    pub protected: bool,
+}
+
+fn default_protected() -> bool { true }
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct T8nResult {
+    /// The root of the Mazze state trie, which is not comparable with the
+    /// state root of Ethereum.
+    pub state_root: Data,
+    pub logs_hash: Data,
+    pub receipts: Vec<T8nReceipt>,
+    pub rejected: Vec<RejectedTransaction>,
+    pub gas_used: Uint,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_base_fee: Option<Uint>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct T8nReceipt {
+    pub transaction_hash: Data,
+    pub transaction_index: Uint,
+    pub status: Uint,
+    pub gas_used: Uint,
+    pub cumulative_gas_used: Uint,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract_address: Option<HexAddress>,
+    pub logs: Vec<T8nLog>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct T8nLog {
+    pub address: HexAddress,
+    pub topics: Vec<Data>,
+    pub data: Data,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RejectedTransaction {
+    pub index: usize,
+    pub error: String,
+}
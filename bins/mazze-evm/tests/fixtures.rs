@@ -0,0 +1,163 @@
+//! Runs the `mazze-evm` binary against the fixtures in `tests/fixtures`.
+
+use serde_json::Value;
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_mazze-evm"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn statetest_fixtures_pass() {
+    let path = fixtures().join("statetest");
+    let output = run(&["statetest", path.to_str().unwrap()]);
+    let stdout = stdout(&output);
+    assert!(output.status.success(), "{}", stdout);
+
+    assert!(stdout.contains("PASS sstoreAndTransfer/Cancun/0"));
+    assert!(stdout.contains("PASS sstoreAndTransfer/Prague/2"));
+    assert!(stdout.contains("PASS nonceTooHigh/Cancun/0"));
+    assert!(
+        stdout.contains("SKIP sstoreAndTransfer/London/0: unsupported fork")
+    );
+    assert!(
+        stdout.contains("7 passed, 0 failed, 1 skipped"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn statetest_fork_filter() {
+    let path = fixtures().join("statetest");
+    let output =
+        run(&["statetest", "--fork", "Prague", path.to_str().unwrap()]);
+    let stdout = stdout(&output);
+    assert!(output.status.success(), "{}", stdout);
+    assert!(
+        stdout.contains("3 passed, 0 failed, 0 skipped"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn statetest_reports_post_state_diff() {
+    let path = fixtures().join("failing/wrongStorage.json");
+    let output = run(&["statetest", path.to_str().unwrap()]);
+    let stdout = stdout(&output);
+    assert_eq!(output.status.code(), Some(1), "{}", stdout);
+
+    assert!(stdout.contains("FAIL sstoreAndTransfer/Cancun/0"));
+    assert!(
+        stdout.contains("storage 0x0 expected 0x2, got 0x1"),
+        "{}",
+        stdout
+    );
+    assert!(
+        stdout.contains("0 passed, 1 failed, 0 skipped"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn blocktest_fixtures_pass() {
+    let path = fixtures().join("blocktest");
+    let output = run(&["blocktest", path.to_str().unwrap()]);
+    let stdout = stdout(&output);
+    assert!(output.status.success(), "{}", stdout);
+
+    // The invalid blocks between the two valid blocks are rejected.
+    assert!(stdout.contains("PASS transfersAndInvalidBlocks"));
+    assert!(
+        stdout.contains("1 passed, 0 failed, 0 skipped"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn blocktest_reports_accepted_block_and_post_state_diff() {
+    let path = fixtures().join("failing/wrongBlocks.json");
+    let output = run(&["blocktest", path.to_str().unwrap()]);
+    let stdout = stdout(&output);
+    assert_eq!(output.status.code(), Some(1), "{}", stdout);
+
+    assert!(stdout.contains("FAIL transfersAndInvalidBlocks"));
+    assert!(
+        stdout.contains("balance expected 1000000008, got 1000000007"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("FAIL validBlockExpectedInvalid"));
+    assert!(
+        stdout.contains(
+            "block 0 accepted, expected BlockException.INVALID_STATE_ROOT"
+        ),
+        "{}",
+        stdout
+    );
+    assert!(
+        stdout.contains("0 passed, 2 failed, 0 skipped"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn t8n_writes_result_and_alloc() {
+    let dir = fixtures().join("t8n");
+    let arg = |name: &str| dir.join(name).to_str().unwrap().to_string();
+    let (alloc, env, txs) =
+        (arg("alloc.json"), arg("env.json"), arg("txs.json"));
+    let output = run(&[
+        "t8n",
+        "--input.alloc",
+        &alloc,
+        "--input.env",
+        &env,
+        "--input.txs",
+        &txs,
+        "--output.result",
+        "stdout",
+        "--output.alloc",
+        "stdout",
+        "--state.fork",
+        "Cancun",
+    ]);
+    let stdout = stdout(&output);
+    assert!(output.status.success(), "{}", stdout);
+
+    let values: Vec<Value> = serde_json::Deserializer::from_str(&stdout)
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let (result, alloc) = (&values[0], &values[1]);
+
+    // The first transaction sets a storage slot, the second one has a nonce
+    // too high and is rejected.
+    assert_eq!(result["gasUsed"], "0xa862");
+    assert_eq!(result["receipts"].as_array().unwrap().len(), 1);
+    assert_eq!(result["receipts"][0]["status"], "0x1");
+    assert_eq!(result["rejected"][0]["index"], 1);
+
+    let contract = &alloc["0x095e7baea6a6c7c4c2dfeb977efac326af552d87"];
+    assert_eq!(contract["storage"]["0x0"], "0x1");
+    let sender = &alloc["0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b"];
+    assert_eq!(sender["nonce"], "0x1");
+}
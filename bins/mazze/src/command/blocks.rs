@@ -0,0 +1,73 @@
+// Importing the necessary crates and modules
+use clap;
+use client::{
+    common::block_export::{export_blocks, import_blocks},
+    configuration::Configuration,
+};
+use std::path::PathBuf;
+
+// Enum defining the block import and export commands
+#[derive(Debug, PartialEq)]
+pub enum BlocksCmd {
+    Export(ExportBlocks), // Command to write main chain blocks to a file
+    Import(ImportBlocks), // Command to insert the blocks in a file
+}
+
+// Struct for the ExportBlocks command
+#[derive(Debug, PartialEq)]
+pub struct ExportBlocks {
+    pub from: u64,    // First epoch to export
+    pub to: u64,      // Last epoch to export
+    pub out: PathBuf, // File to write the blocks to
+}
+
+impl ExportBlocks {
+    // Constructor for ExportBlocks
+    pub fn new(matches: &clap::ArgMatches) -> Result<Self, String> {
+        let from = parse_epoch(matches, "from")?;
+        let to = parse_epoch(matches, "to")?;
+        let out = matches
+            .value_of("out")
+            .expect("CLI argument is required; qed")
+            .into();
+        Ok(Self { from, to, out })
+    }
+}
+
+// Struct for the ImportBlocks command
+#[derive(Debug, PartialEq)]
+pub struct ImportBlocks {
+    pub file: PathBuf, // File written by export-blocks
+}
+
+impl ImportBlocks {
+    // Constructor for ImportBlocks
+    pub fn new(matches: &clap::ArgMatches) -> Self {
+        let file = matches
+            .value_of("file")
+            .expect("CLI argument is required; qed")
+            .into();
+        Self { file }
+    }
+}
+
+fn parse_epoch(matches: &clap::ArgMatches, name: &str) -> Result<u64, String> {
+    let value = matches
+        .value_of(name)
+        .expect("CLI argument is required; qed");
+    value
+        .parse()
+        .map_err(|_| format!("Invalid epoch number for --{}: {}", name, value))
+}
+
+// Function to execute the block import or export command
+pub fn execute(
+    command: BlocksCmd, conf: &mut Configuration,
+) -> Result<String, String> {
+    match command {
+        BlocksCmd::Export(cmd) => {
+            export_blocks(conf, cmd.from, cmd.to, &cmd.out)
+        }
+        BlocksCmd::Import(cmd) => import_blocks(conf, &cmd.file),
+    }
+}
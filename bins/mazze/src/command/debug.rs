@@ -0,0 +1,48 @@
+// Importing the necessary crates and modules
+use clap;
+use client::{
+    common::reexecute::reexecute_epochs, configuration::Configuration,
+};
+
+// Enum defining the different debug commands
+#[derive(Debug, PartialEq)]
+pub enum DebugCmd {
+    Reexecute(Reexecute), // Command to execute stored epochs again
+}
+
+// Struct for the Reexecute command
+#[derive(Debug, PartialEq)]
+pub struct Reexecute {
+    pub from: u64, // First epoch to execute again
+    pub to: u64,   // Last epoch to execute again
+}
+
+impl Reexecute {
+    // Constructor for Reexecute
+    pub fn new(matches: &clap::ArgMatches) -> Result<Self, String> {
+        let from = parse_epoch(matches, "from")?;
+        let to = match matches.value_of("to") {
+            Some(_) => parse_epoch(matches, "to")?,
+            None => from,
+        };
+        Ok(Self { from, to })
+    }
+}
+
+fn parse_epoch(matches: &clap::ArgMatches, name: &str) -> Result<u64, String> {
+    let value = matches
+        .value_of(name)
+        .expect("CLI argument is required; qed");
+    value
+        .parse()
+        .map_err(|_| format!("Invalid epoch number for --{}: {}", name, value))
+}
+
+// Function to execute the debug command
+pub fn execute(
+    command: DebugCmd, conf: &mut Configuration,
+) -> Result<String, String> {
+    match command {
+        DebugCmd::Reexecute(cmd) => reexecute_epochs(conf, cmd.from, cmd.to),
+    }
+}
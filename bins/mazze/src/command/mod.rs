@@ -1,5 +1,9 @@
 
 
 pub mod account;
+pub mod blocks;
+pub mod debug;
 pub mod helpers;
 pub mod rpc;
+pub mod snapshot;
+pub mod state;
@@ -0,0 +1,94 @@
+// Importing the necessary crates and modules
+use clap;
+use client::{
+    common::snapshot_archive::{
+        convert_snapshot_db_backend, export_snapshot_archive,
+        import_snapshot_archive,
+    },
+    configuration::Configuration,
+};
+use std::path::PathBuf;
+
+// Enum defining the snapshot archive commands
+#[derive(Debug, PartialEq)]
+pub enum SnapshotCmd {
+    Export(ExportSnapshot), // Command to write a snapshot to an archive
+    Import(ImportSnapshot), // Command to restore a snapshot from an archive
+    Convert(ConvertSnapshot), // Command to convert the snapshot db backend
+}
+
+// Struct for the ExportSnapshot command
+#[derive(Debug, PartialEq)]
+pub struct ExportSnapshot {
+    pub epoch: u64,    // Epoch of the snapshot
+    pub path: PathBuf, // Directory or tarball to write the archive to
+}
+
+impl ExportSnapshot {
+    // Constructor for ExportSnapshot
+    pub fn new(matches: &clap::ArgMatches) -> Result<Self, String> {
+        let value = matches
+            .value_of("epoch")
+            .expect("CLI argument is required; qed");
+        let epoch = value.parse().map_err(|_| {
+            format!("Invalid epoch number for --epoch: {}", value)
+        })?;
+        let path = parse_path(matches);
+        Ok(Self { epoch, path })
+    }
+}
+
+// Struct for the ImportSnapshot command
+#[derive(Debug, PartialEq)]
+pub struct ImportSnapshot {
+    pub path: PathBuf, // Directory or tarball written by snapshot export
+}
+
+impl ImportSnapshot {
+    // Constructor for ImportSnapshot
+    pub fn new(matches: &clap::ArgMatches) -> Self {
+        Self {
+            path: parse_path(matches),
+        }
+    }
+}
+
+// Struct for the ConvertSnapshot command
+#[derive(Debug, PartialEq)]
+pub struct ConvertSnapshot {
+    pub from: String, // Snapshot db backend the snapshots are kept in
+}
+
+impl ConvertSnapshot {
+    // Constructor for ConvertSnapshot
+    pub fn new(matches: &clap::ArgMatches) -> Self {
+        Self {
+            from: matches
+                .value_of("from")
+                .expect("CLI argument is required; qed")
+                .into(),
+        }
+    }
+}
+
+fn parse_path(matches: &clap::ArgMatches) -> PathBuf {
+    matches
+        .value_of("path")
+        .expect("CLI argument is required; qed")
+        .into()
+}
+
+// Function to execute the snapshot command
+pub fn execute(
+    command: SnapshotCmd, conf: &mut Configuration,
+) -> Result<String, String> {
+    match command {
+        SnapshotCmd::Export(cmd) => {
+            export_snapshot_archive(conf, cmd.epoch, &cmd.path)
+        }
+        SnapshotCmd::Import(cmd) => import_snapshot_archive(conf, &cmd.path),
+        SnapshotCmd::Convert(cmd) => {
+            convert_snapshot_db_backend(conf, &cmd.from)
+        }
+    }
+}
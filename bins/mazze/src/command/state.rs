@@ -0,0 +1,69 @@
+// Importing the necessary crates and modules
+use clap;
+use client::{
+    common::{
+        state_dump::dump_state_to_file, state_stats::collect_state_stats,
+    },
+    configuration::Configuration,
+};
+use std::path::PathBuf;
+
+// Enum defining the state commands
+#[derive(Debug, PartialEq)]
+pub enum StateCmd {
+    Dump(DumpState), // Command to write the state of an epoch to a file
+    Stats(StateStats), // Command to collect the statistics of a snapshot
+}
+
+// Struct for the DumpState command
+#[derive(Debug, PartialEq)]
+pub struct DumpState {
+    pub epoch: u64,   // Epoch of the state
+    pub out: PathBuf, // File to write the accounts to
+}
+
+impl DumpState {
+    // Constructor for DumpState
+    pub fn new(matches: &clap::ArgMatches) -> Result<Self, String> {
+        let value = matches
+            .value_of("epoch")
+            .expect("CLI argument is required; qed");
+        let epoch = value.parse().map_err(|_| {
+            format!("Invalid epoch number for --epoch: {}", value)
+        })?;
+        let out = matches
+            .value_of("out")
+            .expect("CLI argument is required; qed")
+            .into();
+        Ok(Self { epoch, out })
+    }
+}
+
+// Struct for the StateStats command
+#[derive(Debug, PartialEq)]
+pub struct StateStats {
+    pub epoch: u64, // Snapshot epoch of the state
+}
+
+impl StateStats {
+    // Constructor for StateStats
+    pub fn new(matches: &clap::ArgMatches) -> Result<Self, String> {
+        let value = matches
+            .value_of("epoch")
+            .expect("CLI argument is required; qed");
+        let epoch = value.parse().map_err(|_| {
+            format!("Invalid epoch number for --epoch: {}", value)
+        })?;
+        Ok(Self { epoch })
+    }
+}
+
+// Function to execute the state command
+pub fn execute(
+    command: StateCmd, conf: &mut Configuration,
+) -> Result<String, String> {
+    match command {
+        StateCmd::Dump(cmd) => dump_state_to_file(conf, cmd.epoch, &cmd.out),
+        StateCmd::Stats(cmd) => collect_state_stats(conf, cmd.epoch),
+    }
+}
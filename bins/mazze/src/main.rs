@@ -16,7 +16,13 @@ use client::{
     full::FullClient,
     light::LightClient,
 };
-use command::account::{AccountCmd, ImportAccounts, ListAccounts, NewAccount};
+use command::{
+    account::{AccountCmd, ImportAccounts, ListAccounts, NewAccount},
+    blocks::{BlocksCmd, ExportBlocks, ImportBlocks},
+    debug::{DebugCmd, Reexecute},
+    snapshot::{ConvertSnapshot, ExportSnapshot, ImportSnapshot, SnapshotCmd},
+    state::{DumpState, StateCmd, StateStats},
+};
 use log::{info, LevelFilter};
 use log4rs::{
     append::{console::ConsoleAppender, file::FileAppender},
@@ -203,6 +209,72 @@ fn handle_sub_command(matches: &ArgMatches) -> Result<Option<String>, String> {
         return Ok(Some(execute_output));
     }
 
+    // Handle debug sub-commands, which load the node data from the data dir
+    if let ("debug", Some(debug_matches)) = matches.subcommand() {
+        let debug_cmd = match debug_matches.subcommand() {
+            ("reexecute", Some(reexecute_matches)) => {
+                DebugCmd::Reexecute(Reexecute::new(reexecute_matches)?)
+            }
+            _ => unreachable!(),
+        };
+        let mut conf = Configuration::parse(matches)?;
+        let execute_output = command::debug::execute(debug_cmd, &mut conf)?;
+        return Ok(Some(execute_output));
+    }
+
+    // Handle snapshot sub-commands, which load the node data from the data dir
+    if let ("snapshot", Some(snapshot_matches)) = matches.subcommand() {
+        let snapshot_cmd = match snapshot_matches.subcommand() {
+            ("export", Some(export_matches)) => {
+                SnapshotCmd::Export(ExportSnapshot::new(export_matches)?)
+            }
+            ("import", Some(import_matches)) => {
+                SnapshotCmd::Import(ImportSnapshot::new(import_matches))
+            }
+            ("convert", Some(convert_matches)) => {
+                SnapshotCmd::Convert(ConvertSnapshot::new(convert_matches))
+            }
+            _ => unreachable!(),
+        };
+        let mut conf = Configuration::parse(matches)?;
+        let execute_output =
+            command::snapshot::execute(snapshot_cmd, &mut conf)?;
+        return Ok(Some(execute_output));
+    }
+
+    // Handle state sub-commands, which load the node data from the data dir
+    if let ("state", Some(state_matches)) = matches.subcommand() {
+        let state_cmd = match state_matches.subcommand() {
+            ("dump", Some(dump_matches)) => {
+                StateCmd::Dump(DumpState::new(dump_matches)?)
+            }
+            ("stats", Some(stats_matches)) => {
+                StateCmd::Stats(StateStats::new(stats_matches)?)
+            }
+            _ => unreachable!(),
+        };
+        let mut conf = Configuration::parse(matches)?;
+        let execute_output = command::state::execute(state_cmd, &mut conf)?;
+        return Ok(Some(execute_output));
+    }
+
+    // Handle block export and import, which load the node data from the data
+    // dir without starting the network
+    let blocks_cmd = match matches.subcommand() {
+        ("export-blocks", Some(export_matches)) => {
+            Some(BlocksCmd::Export(ExportBlocks::new(export_matches)?))
+        }
+        ("import-blocks", Some(import_matches)) => {
+            Some(BlocksCmd::Import(ImportBlocks::new(import_matches)))
+        }
+        _ => None,
+    };
+    if let Some(blocks_cmd) = blocks_cmd {
+        let mut conf = Configuration::parse(matches)?;
+        let execute_output = command::blocks::execute(blocks_cmd, &mut conf)?;
+        return Ok(Some(execute_output));
+    }
+
     // Handle general RPC commands
     let mut subcmd_matches = matches;
     while let Some(m) = subcmd_matches.subcommand().1 {
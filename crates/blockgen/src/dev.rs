@@ -0,0 +1,163 @@
+use crate::BlockGenerator;
+use log::info;
+use mazze_types::{H256, U256};
+use mazzecore::ConsensusGraphTrait;
+use primitives::EpochNumber;
+use std::{
+    cmp::max,
+    collections::{BTreeMap, HashSet},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The chain snapshots and the clock controlled by the dev mode RPCs.
+#[derive(Default)]
+pub struct DevChain {
+    /// The main block of the latest executed epoch of each snapshot.
+    snapshots: BTreeMap<U256, H256>,
+    next_snapshot_id: U256,
+    /// The offset in seconds added to the system time.
+    time_offset: i64,
+    next_block_timestamp: Option<u64>,
+}
+
+impl DevChain {
+    fn system_time() -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64
+    }
+
+    /// The timestamp of the next generated block, before it is adjusted to
+    /// be later than or equal to its parent's.
+    pub fn take_block_timestamp(&mut self) -> u64 {
+        match self.next_block_timestamp.take() {
+            Some(timestamp) => {
+                // The following blocks continue from this timestamp.
+                self.time_offset = timestamp as i64 - Self::system_time();
+                timestamp
+            }
+            None => max(Self::system_time() + self.time_offset, 0) as u64,
+        }
+    }
+}
+
+impl BlockGenerator {
+    /// Take a snapshot of the latest executed state, and return its id.
+    pub fn snapshot(&self) -> U256 {
+        let consensus_graph = self.consensus_graph();
+        let epoch = consensus_graph.best_executed_state_epoch_number();
+        let hash = consensus_graph
+            .get_hash_from_epoch_number(EpochNumber::Number(epoch))
+            .expect("executed epoch is on the main chain");
+
+        let mut dev_chain = self.dev_chain.lock();
+        let id = dev_chain.next_snapshot_id;
+        dev_chain.next_snapshot_id = id + 1;
+        dev_chain.snapshots.insert(id, hash);
+        info!("Take snapshot {} at epoch {} {:?}", id, epoch, hash);
+        id
+    }
+
+    /// Revert the chain to the snapshot `id`, and remove the snapshot and the
+    /// ones taken after it. Return `false` if the snapshot does not exist.
+    ///
+    /// The blocks above the snapshot are removed from the graph and the db,
+    /// and the consensus graph is rebuilt, so the snapshot block is the best
+    /// block again. The transactions of the removed blocks are returned to
+    /// the transaction pool. The states of the removed epochs are left in
+    /// the storage, but they are no longer reachable.
+    pub fn revert_to_snapshot(&self, id: U256) -> Result<bool, String> {
+        let _guard = self.dev_revert_lock.lock();
+        let snapshot = {
+            let mut dev_chain = self.dev_chain.lock();
+            let mut snapshots = dev_chain.snapshots.split_off(&id);
+            match snapshots.remove(&id) {
+                Some(hash) => hash,
+                None => {
+                    dev_chain.snapshots.append(&mut snapshots);
+                    return Ok(false);
+                }
+            }
+        };
+
+        let consensus_graph = self.consensus_graph();
+        let snapshot_epoch = consensus_graph
+            .get_block_epoch_number(&snapshot)
+            .ok_or("the snapshot block is not on the main chain")?;
+        let era_genesis_height = self
+            .graph
+            .data_man
+            .block_height_by_hash(
+                &self.graph.data_man.get_cur_consensus_era_genesis_hash(),
+            )
+            .ok_or("era genesis not found")?;
+        if snapshot_epoch < era_genesis_height {
+            return Err("the snapshot is before the current era".into());
+        }
+
+        let mut reverted = HashSet::new();
+        for epoch in snapshot_epoch + 1..=consensus_graph.best_epoch_number() {
+            reverted.extend(
+                consensus_graph
+                    .get_block_hashes_by_epoch(EpochNumber::Number(epoch))?,
+            );
+        }
+        let mut reverted_transactions = Vec::new();
+        for hash in &reverted {
+            if let Some(block) = self.graph.data_man.block_by_hash(hash, false)
+            {
+                reverted_transactions.extend(block.transactions.clone());
+            }
+        }
+        // No removed epoch can be executed while its data is removed.
+        consensus_graph.wait_for_execution_queue();
+        let mut removed =
+            self.graph.remove_blocks_and_rebuild_consensus(&reverted);
+        info!(
+            "Revert to snapshot {} at epoch {} {:?}, remove {} blocks",
+            id,
+            snapshot_epoch,
+            snapshot,
+            removed.len()
+        );
+
+        // The changes queued on the snapshot block were made after it.
+        removed.push(snapshot);
+        consensus_graph.discard_dev_state_changes(&removed);
+        consensus_graph.compute_main_epochs_for_dev(&snapshot)?;
+        consensus_graph
+            .recycle_reverted_transactions(&snapshot, reverted_transactions)?;
+        Ok(true)
+    }
+
+    /// Add `seconds` to the clock of the generated blocks, and return the
+    /// total offset.
+    pub fn increase_time(&self, seconds: u64) -> i64 {
+        let mut dev_chain = self.dev_chain.lock();
+        dev_chain.time_offset += seconds as i64;
+        dev_chain.time_offset
+    }
+
+    /// Set the timestamp of the next generated block. It cannot be earlier
+    /// than the timestamp of the best block.
+    pub fn set_next_block_timestamp(
+        &self, timestamp: u64,
+    ) -> Result<(), String> {
+        let best_block_hash = self.consensus_graph().best_block_hash();
+        let best_timestamp = self
+            .graph
+            .data_man
+            .block_header_by_hash(&best_block_hash)
+            .ok_or("best block header not found")?
+            .timestamp();
+        if timestamp < best_timestamp {
+            return Err(format!(
+                "timestamp {} is earlier than the best block timestamp {}",
+                timestamp, best_timestamp
+            ));
+        }
+        self.dev_chain.lock().next_block_timestamp = Some(timestamp);
+        Ok(())
+    }
+}
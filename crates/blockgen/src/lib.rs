@@ -1,9 +1,13 @@
 
+mod dev;
 pub mod miner;
 
-use crate::miner::{
-    stratum::{Options as StratumOption, Stratum},
-    work_notify::NotifyWork,
+use crate::{
+    dev::DevChain,
+    miner::{
+        stratum::{Options as StratumOption, Stratum},
+        work_notify::NotifyWork,
+    },
 };
 use lazy_static::lazy_static;
 use log::{debug, info, trace, warn};
@@ -32,7 +36,7 @@ use std::{
     thread,
     time::{self, Instant},
 };
-use time::{Duration, SystemTime, UNIX_EPOCH};
+use time::{Duration, SystemTime};
 use txgen::SharedTransactionGenerator;
 lazy_static! {
     static ref PACKED_ACCOUNT_SIZE: Arc<dyn Gauge<usize>> =
@@ -64,6 +68,9 @@ pub struct BlockGenerator {
     workers: Mutex<Vec<(Worker, mpsc::Sender<ProofOfWorkProblem>)>>,
     pub stratum: RwLock<Option<Stratum>>,
     pos_verifier: Arc<PosVerifier>,
+    dev_chain: Mutex<DevChain>,
+    /// Held while reverting the chain, so that no other block is generated.
+    dev_revert_lock: Mutex<()>,
 }
 
 pub struct Worker {
@@ -165,6 +172,8 @@ impl BlockGenerator {
             workers: Mutex::new(Vec::new()),
             stratum: RwLock::new(None),
             pos_verifier,
+            dev_chain: Default::default(),
+            dev_revert_lock: Mutex::new(()),
         }
     }
 
@@ -260,10 +269,7 @@ impl BlockGenerator {
             expected_difficulty = U256::from(difficulty);
         }
 
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let now = self.dev_chain.lock().take_block_timestamp();
 
         // Adjust the timestamp of the currently mined block to be later
         // than or equal to its parent's.
@@ -1072,6 +1078,7 @@ impl BlockGenerator {
                 _ => {}
             }
             if !self.sync.catch_up_mode() {
+                let _guard = self.dev_revert_lock.lock();
                 self.generate_block(
                     3000,
                     self.graph.verification_config.max_block_size_in_bytes,
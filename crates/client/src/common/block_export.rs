@@ -0,0 +1,434 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+use mazze_parameters::block::MAX_BLOCK_SIZE_IN_BYTES;
+use mazze_types::H256;
+use mazzecore::{
+    sync::BlockHeaderInsertionResult, ConsensusGraphTrait, NodeType,
+    SynchronizationGraph,
+};
+use parking_lot::{Condvar, Mutex};
+use primitives::Block;
+use rlp::{Rlp, RlpStream};
+
+use super::{initialize_common_modules, Configuration};
+
+const BLOCK_FILE_MAGIC: &[u8] = b"mazze-blocks";
+const BLOCK_FILE_VERSION: u8 = 1;
+/// The maximal size of an item in a block file, which leaves room for the
+/// header of a block with the largest body.
+const MAX_BLOCK_FILE_ITEM_SIZE: u64 = 2 * MAX_BLOCK_SIZE_IN_BYTES as u64;
+
+/// The first item of a block file, which describes the chain the blocks are
+/// exported from.
+struct BlockFileHeader {
+    native_chain_id: u32,
+    evm_chain_id: u32,
+    genesis_hash: H256,
+    from: u64,
+    to: u64,
+}
+
+impl BlockFileHeader {
+    fn rlp_bytes(&self) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(7);
+        stream
+            .append(&BLOCK_FILE_MAGIC.to_vec())
+            .append(&BLOCK_FILE_VERSION)
+            .append(&self.native_chain_id)
+            .append(&self.evm_chain_id)
+            .append(&self.genesis_hash)
+            .append(&self.from)
+            .append(&self.to);
+        stream.out().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let rlp = Rlp::new(bytes);
+        let invalid = |e| format!("invalid block file header: {:?}", e);
+        let magic: Vec<u8> = rlp.val_at(0).map_err(invalid)?;
+        if magic != BLOCK_FILE_MAGIC {
+            bail!("not a block file");
+        }
+        let version: u8 = rlp.val_at(1).map_err(invalid)?;
+        if version != BLOCK_FILE_VERSION {
+            bail!("unsupported block file version {}", version);
+        }
+        Ok(Self {
+            native_chain_id: rlp.val_at(2).map_err(invalid)?,
+            evm_chain_id: rlp.val_at(3).map_err(invalid)?,
+            genesis_hash: rlp.val_at(4).map_err(invalid)?,
+            from: rlp.val_at(5).map_err(invalid)?,
+            to: rlp.val_at(6).map_err(invalid)?,
+        })
+    }
+}
+
+/// Read the next RLP list from `reader`, or `None` at the end of the file.
+fn read_rlp_list<R: Read>(reader: &mut R) -> Result<Option<Vec<u8>>, String> {
+    let mut first = [0u8; 1];
+    match reader.read_exact(&mut first) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+            return Ok(None)
+        }
+        Err(e) => return Err(e.to_string()),
+    }
+    let mut item = vec![first[0]];
+    let payload_len = match first[0] {
+        0xc0..=0xf7 => (first[0] - 0xc0) as u64,
+        0xf8..=0xff => {
+            let len_of_len = (first[0] - 0xf7) as usize;
+            let mut len_bytes = vec![0u8; len_of_len];
+            reader
+                .read_exact(&mut len_bytes)
+                .map_err(|e| e.to_string())?;
+            item.extend_from_slice(&len_bytes);
+            len_bytes
+                .iter()
+                .fold(0u64, |len, byte| (len << 8) | *byte as u64)
+        }
+        _ => bail!("the block file is corrupted: expect an RLP list"),
+    };
+    // The length is read from the file, so the payload is not allocated
+    // before it is read.
+    if payload_len > MAX_BLOCK_FILE_ITEM_SIZE {
+        bail!(
+            "the block file is corrupted: an item of {} bytes",
+            payload_len
+        );
+    }
+    let header_len = item.len();
+    reader
+        .by_ref()
+        .take(payload_len)
+        .read_to_end(&mut item)
+        .map_err(|e| e.to_string())?;
+    if item.len() - header_len != payload_len as usize {
+        bail!("the block file is truncated");
+    }
+    Ok(Some(item))
+}
+
+/// Write the blocks of the epochs in `[from, to]` on the main chain to
+/// `out` in topological order. The file starts with a header that records
+/// the chain ids and the genesis hash, followed by the RLP encoding of each
+/// block.
+pub fn export_blocks(
+    conf: &mut Configuration, from: u64, to: u64, out: &Path,
+) -> Result<String, String> {
+    if from > to {
+        bail!("invalid epoch range [{}, {}]", from, to);
+    }
+    let node_type = conf.node_type();
+    if node_type == NodeType::Light {
+        bail!("light nodes do not keep the block bodies to export");
+    }
+    let chain_id = conf.chain_id_params().read().get_chain_id(from);
+
+    conf.read_only = true;
+    let exit = Arc::new((Mutex::new(false), Condvar::new()));
+    let (
+        _machine,
+        _secret_store,
+        _genesis_accounts,
+        data_man,
+        _pow,
+        _pos_verifier,
+        _txpool,
+        _consensus,
+        _sync_graph,
+        _network,
+        _common_impl,
+        _accounts,
+        _notifications,
+        _pubsub,
+        _runtime,
+        _eth_pubsub,
+    ) = initialize_common_modules(conf, exit, node_type)?;
+
+    let header = BlockFileHeader {
+        native_chain_id: chain_id.in_native_space(),
+        evm_chain_id: chain_id.in_evm_space(),
+        genesis_hash: data_man.true_genesis.hash(),
+        from,
+        to,
+    };
+    let file = File::create(out)
+        .map_err(|e| format!("failed to create {:?}: {}", out, e))?;
+    let mut writer = BufWriter::new(file);
+    writer
+        .write_all(&header.rlp_bytes())
+        .map_err(|e| e.to_string())?;
+
+    let mut block_count = 0;
+    for epoch_number in from..=to {
+        let hashes = data_man
+            .all_epoch_set_hashes_from_db(epoch_number)
+            .ok_or_else(|| {
+                format!("epoch {} is not on the main chain", epoch_number)
+            })?;
+        for hash in hashes {
+            let block = data_man
+                .block_by_hash(&hash, false /* update_cache */)
+                .ok_or_else(|| format!("block {:?} is missing", hash))?;
+            writer
+                .write_all(&rlp::encode(&*block))
+                .map_err(|e| e.to_string())?;
+            block_count += 1;
+        }
+    }
+    writer.flush().map_err(|e| e.to_string())?;
+
+    Ok(format!(
+        "exported {} blocks of epochs [{}, {}] to {:?}",
+        block_count, from, to, out
+    ))
+}
+
+/// Insert the blocks in a file written by `export_blocks` into the
+/// synchronization graph and the consensus graph without starting the
+/// network, and wait until they are executed. Blocks that are already known
+/// are skipped.
+pub fn import_blocks(
+    conf: &mut Configuration, path: &Path,
+) -> Result<String, String> {
+    let node_type = conf.node_type();
+    if node_type == NodeType::Light {
+        bail!("light nodes cannot execute the imported blocks");
+    }
+    let file = File::open(path)
+        .map_err(|e| format!("failed to open {:?}: {}", path, e))?;
+    let mut reader = BufReader::new(file);
+    let header = match read_rlp_list(&mut reader)? {
+        Some(bytes) => BlockFileHeader::decode(&bytes)?,
+        None => bail!("the block file is empty"),
+    };
+
+    conf.offline = true;
+    let exit = Arc::new((Mutex::new(false), Condvar::new()));
+    let (
+        _machine,
+        _secret_store,
+        _genesis_accounts,
+        data_man,
+        _pow,
+        _pos_verifier,
+        _txpool,
+        consensus,
+        sync_graph,
+        _network,
+        _common_impl,
+        _accounts,
+        _notifications,
+        _pubsub,
+        _runtime,
+        _eth_pubsub,
+    ) = initialize_common_modules(conf, exit, node_type)?;
+
+    if header.genesis_hash != data_man.true_genesis.hash() {
+        bail!(
+            "the blocks are exported from genesis {:?}, but the genesis of \
+             this node is {:?}",
+            header.genesis_hash,
+            data_man.true_genesis.hash()
+        );
+    }
+    let chain_id = conf.chain_id_params().read().get_chain_id(header.from);
+    if header.native_chain_id != chain_id.in_native_space()
+        || header.evm_chain_id != chain_id.in_evm_space()
+    {
+        bail!(
+            "the blocks are exported from chain {}/{}, but this node runs \
+             chain {}/{}",
+            header.native_chain_id,
+            header.evm_chain_id,
+            chain_id.in_native_space(),
+            chain_id.in_evm_space()
+        );
+    }
+    sync_graph.recover_graph_from_db_offline()?;
+
+    let mut imported = 0;
+    let mut skipped = 0;
+    let mut last_imported = None;
+    while let Some(bytes) = read_rlp_list(&mut reader)? {
+        let mut block: Block = rlp::decode(&bytes)
+            .map_err(|e| format!("failed to decode block: {:?}", e))?;
+        let hash = block.hash();
+        if sync_graph.contains_block(&hash) {
+            skipped += 1;
+            continue;
+        }
+        // The blocks are exported in topological order, so a block only
+        // depends on unknown blocks if the epochs before the file are
+        // missing, and it would never be processed.
+        let block_header = &block.block_header;
+        if let Some(missing) = std::iter::once(block_header.parent_hash())
+            .chain(block_header.referee_hashes())
+            .find(|h| {
+                !sync_graph.contains_block(h)
+                    && data_man.block_header_by_hash(h).is_none()
+            })
+        {
+            bail!(
+                "block {:?} depends on the unknown block {:?}, the epochs \
+                 before {} must be imported first",
+                hash,
+                missing,
+                header.from
+            );
+        }
+        data_man.recover_block(&mut block).map_err(|e| {
+            format!("failed to recover block {:?}: {:?}", hash, e)
+        })?;
+        if insert_block(&sync_graph, block)? {
+            imported += 1;
+            last_imported = Some(hash);
+        } else {
+            skipped += 1;
+        }
+    }
+
+    while sync_graph.is_consensus_worker_busy() {
+        thread::sleep(Duration::from_millis(100));
+    }
+    if let Some(hash) = last_imported {
+        consensus.wait_for_generation(&hash);
+    }
+
+    Ok(format!(
+        "imported {} blocks of epochs [{}, {}], skipped {} known blocks, \
+         best epoch {}",
+        imported,
+        header.from,
+        header.to,
+        skipped,
+        consensus.best_epoch_number()
+    ))
+}
+
+/// Insert a recovered block like a block received from the network. Returns
+/// `false` if the block has already been processed.
+fn insert_block(
+    sync_graph: &SynchronizationGraph, mut block: Block,
+) -> Result<bool, String> {
+    let hash = block.hash();
+    match sync_graph.block_header_by_hash(&hash) {
+        Some(header) => block.block_header = header,
+        None => {
+            let (insert_result, _) = sync_graph.insert_block_header(
+                &mut block.block_header,
+                true,  // need_to_verify
+                false, // bench_mode
+                false, // insert_into_consensus
+                true,  // persistent
+            );
+            match insert_result {
+                BlockHeaderInsertionResult::Invalid => {
+                    bail!("block {:?} has an invalid header", hash)
+                }
+                BlockHeaderInsertionResult::TemporarySkipped => {
+                    bail!("the synchronization graph is locked")
+                }
+                _ if !insert_result.should_process_body() => return Ok(false),
+                _ => {}
+            }
+        }
+    }
+    let insert_result = sync_graph.insert_block(
+        block, true,  /* need_to_verify */
+        true,  /* persistent */
+        false, /* recover_from_db */
+    );
+    if insert_result.is_invalid() {
+        bail!("block {:?} is invalid", hash);
+    }
+    Ok(insert_result.is_valid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mazze_types::U256;
+    use primitives::BlockHeaderBuilder;
+
+    #[test]
+    fn test_block_file_round_trip() {
+        let header = BlockFileHeader {
+            native_chain_id: 1,
+            evm_chain_id: 71,
+            genesis_hash: H256::from_low_u64_be(1),
+            from: 3,
+            to: 4,
+        };
+        let blocks: Vec<Block> = (0..2u64)
+            .map(|i| {
+                Block::new(
+                    BlockHeaderBuilder::new()
+                        .with_parent_hash(H256::from_low_u64_be(i))
+                        .with_height(i + 1)
+                        .with_nonce(U256::from(i))
+                        .build(),
+                    vec![],
+                )
+            })
+            .collect();
+        let mut file = header.rlp_bytes();
+        for block in &blocks {
+            file.extend_from_slice(&rlp::encode(block));
+        }
+
+        let mut reader = &file[..];
+        let decoded = BlockFileHeader::decode(
+            &read_rlp_list(&mut reader).unwrap().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(decoded.native_chain_id, 1);
+        assert_eq!(decoded.evm_chain_id, 71);
+        assert_eq!(decoded.genesis_hash, header.genesis_hash);
+        assert_eq!((decoded.from, decoded.to), (3, 4));
+        for block in &blocks {
+            let bytes = read_rlp_list(&mut reader).unwrap().unwrap();
+            let decoded: Block = rlp::decode(&bytes).unwrap();
+            assert_eq!(decoded.hash(), block.hash());
+        }
+        assert!(read_rlp_list(&mut reader).unwrap().is_none());
+
+        // A truncated block.
+        let mut reader = &file[..file.len() - 1];
+        read_rlp_list(&mut reader).unwrap();
+        read_rlp_list(&mut reader).unwrap();
+        assert!(read_rlp_list(&mut reader).is_err());
+    }
+
+    #[test]
+    fn test_block_file_item_size() {
+        // An RLP list with a payload of `len` bytes, which is missing.
+        let list_header = |len: u64| {
+            let mut item = vec![0xfb];
+            item.extend_from_slice(&len.to_be_bytes()[4..]);
+            item
+        };
+        let err =
+            read_rlp_list(&mut &list_header(MAX_BLOCK_FILE_ITEM_SIZE + 1)[..])
+                .unwrap_err();
+        assert!(err.contains("corrupted"));
+        let err =
+            read_rlp_list(&mut &list_header(MAX_BLOCK_FILE_ITEM_SIZE)[..])
+                .unwrap_err();
+        assert!(err.contains("truncated"));
+
+        // A list claiming a payload of 2^64 - 1 bytes.
+        let mut item = vec![0xff];
+        item.extend_from_slice(&[0xff; 8]);
+        let err = read_rlp_list(&mut &item[..]).unwrap_err();
+        assert!(err.contains("corrupted"));
+    }
+}
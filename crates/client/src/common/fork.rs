@@ -0,0 +1,804 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use keccak_hash::{keccak, KECCAK_EMPTY};
+use mazze_addr::Network;
+use mazze_parameters::staking::MAZZIES_PER_STORAGE_COLLATERAL_UNIT;
+use mazze_storage::{ForkSource, Result as StorageResult};
+use mazze_types::{
+    address_util::AddressUtil, Address, AddressSpaceUtil, Space, H256, U256,
+};
+use network::NetworkConfiguration;
+use primitives::{
+    account::{ContractAccount, StoragePoints},
+    storage::STORAGE_LAYOUT_REGULAR_V0,
+    Account, CodeInfo, SponsorInfo, StorageKey, StorageKeyWithSpace,
+    StorageValue,
+};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::rpc::types::{
+    Account as RpcAccount, Bytes, RpcAddress, SponsorInfo as RpcSponsorInfo,
+    Status as RpcStatus, StorageRangePage,
+};
+
+const FORK_RPC_TIMEOUT: Duration = Duration::from_secs(30);
+/// The number of times a request is retried when the remote node is not
+/// reachable. The interval between the retries doubles each time.
+const FORK_RPC_RETRY_COUNT: u32 = 4;
+const FORK_RPC_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+/// The JSON-RPC error code of a method the remote node does not expose.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// The remote node which a dev node forks its state from.
+#[derive(Debug, Clone)]
+pub struct ForkConfiguration {
+    /// The Mazze RPC, which serves the native space state.
+    pub url: Option<String>,
+    /// The eth-space RPC, which serves the eth space state.
+    pub eth_url: Option<String>,
+    /// The epoch to fork both spaces from. It's the latest state epoch of the
+    /// Mazze RPC, or the latest block of the eth-space RPC without it, if not
+    /// set.
+    pub epoch: Option<u64>,
+}
+
+/// Fetch the state of a remote node through its JSON-RPC.
+pub struct RpcForkSource {
+    native: Option<NativeRemote>,
+    eth: Option<EthRemote>,
+}
+
+impl RpcForkSource {
+    pub fn new(conf: ForkConfiguration) -> Result<Self, String> {
+        let native = match conf.url {
+            Some(url) => {
+                let client = RpcClient { url };
+                let status: RpcStatus =
+                    client.call("mazze_getStatus", json!([]))?;
+                Some((client, status))
+            }
+            None => None,
+        };
+        let eth = conf.eth_url.map(|url| RpcClient { url });
+        // The two spaces are forked at the same epoch, otherwise the state
+        // of one space could be ahead of the other.
+        let epoch = match (conf.epoch, &native, &eth) {
+            (Some(epoch), _, _) => epoch,
+            (None, Some((_, status)), _) => status.latest_state.as_u64(),
+            (None, None, Some(client)) => {
+                client.call::<U256>("eth_blockNumber", json!([]))?.as_u64()
+            }
+            (None, None, None) => {
+                return Ok(Self {
+                    native: None,
+                    eth: None,
+                })
+            }
+        };
+        info!("Fork the state at epoch {}", epoch);
+        Ok(Self {
+            native: native.map(|(client, status)| {
+                NativeRemote::new(client, status, epoch)
+            }),
+            eth: eth.map(|client| EthRemote::new(client, epoch)),
+        })
+    }
+}
+
+impl ForkSource for RpcForkSource {
+    fn get(
+        &self, access_key: StorageKeyWithSpace,
+    ) -> StorageResult<Option<Box<[u8]>>> {
+        let result = match access_key.space {
+            Space::Native => match &self.native {
+                Some(remote) => remote.get(access_key.key),
+                None => Ok(None),
+            },
+            Space::Ethereum => match &self.eth {
+                Some(remote) => remote.get(access_key.key),
+                None => Ok(None),
+            },
+        };
+        result.map_err(|e| {
+            format!("Failed to fetch forked state {:?}: {}", access_key, e)
+                .into()
+        })
+    }
+}
+
+struct RpcClient {
+    url: String,
+}
+
+impl RpcClient {
+    fn call<T: DeserializeOwned>(
+        &self, method: &str, params: Value,
+    ) -> Result<T, String> {
+        self.call_if_available(method, params)?.ok_or_else(|| {
+            format!("{} {}: the method is not available", self.url, method)
+        })
+    }
+
+    /// Call a method the remote node may not expose, e.g. one of the debug
+    /// RPC. It returns `None` if the method is not found.
+    fn call_if_available<T: DeserializeOwned>(
+        &self, method: &str, params: Value,
+    ) -> Result<Option<T>, String> {
+        let mut retry = 0;
+        let mut body = loop {
+            match self.send(method, &params) {
+                Ok(body) => break body,
+                Err((true, e)) if retry < FORK_RPC_RETRY_COUNT => {
+                    warn!("Retry the forked state request: {}", e);
+                    thread::sleep(FORK_RPC_RETRY_INTERVAL * 2u32.pow(retry));
+                    retry += 1;
+                }
+                Err((_, e)) => return Err(e),
+            }
+        };
+        if let Some(error) = body.get("error") {
+            if error["code"].as_i64() == Some(METHOD_NOT_FOUND) {
+                return Ok(None);
+            }
+            return Err(format!("{} {}: {}", self.url, method, error));
+        }
+        serde_json::from_value(body["result"].take())
+            .map(Some)
+            .map_err(|e| format!("{} {}: {}", self.url, method, e))
+    }
+
+    /// Send a request and return the response body. The error is paired with
+    /// whether it's transient, i.e. the request may succeed if retried.
+    fn send(
+        &self, method: &str, params: &Value,
+    ) -> Result<Value, (bool, String)> {
+        let response = ureq::post(&self.url)
+            .timeout(FORK_RPC_TIMEOUT)
+            .send_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }));
+        if let Some(e) = response.synthetic_error() {
+            return Err((true, format!("{} {}: {}", self.url, method, e)));
+        }
+        if !response.ok() {
+            return Err((
+                response.status() == 429 || response.server_error(),
+                format!("{} {}: {}", self.url, method, response.status_line()),
+            ));
+        }
+        response
+            .into_json()
+            .map_err(|e| (true, format!("{} {}: {}", self.url, method, e)))
+    }
+}
+
+struct NativeRemote {
+    client: RpcClient,
+    network: Network,
+    epoch: String,
+    /// Whether the remote node serves `debug_getStorageRange`, which is the
+    /// only RPC returning the owner of a storage entry.
+    storage_range_available: AtomicBool,
+}
+
+impl NativeRemote {
+    fn new(client: RpcClient, status: RpcStatus, epoch: u64) -> Self {
+        Self {
+            client,
+            network: NetworkConfiguration::network_id_to_known_mazze_network(
+                status.network_id.as_u64(),
+            ),
+            epoch: format!("{:#x}", epoch),
+            storage_range_available: AtomicBool::new(true),
+        }
+    }
+
+    fn get(&self, key: StorageKey) -> Result<Option<Box<[u8]>>, String> {
+        Ok(match key {
+            StorageKey::AccountKey(address) => self
+                .account(&Address::from_slice(address))?
+                .map(|account| rlp::encode(&account).into()),
+            StorageKey::StorageRootKey(address) => self
+                .account(&Address::from_slice(address))?
+                .map(|_| STORAGE_LAYOUT_REGULAR_V0.to_bytes().into()),
+            StorageKey::StorageKey {
+                address_bytes,
+                storage_key,
+            } => {
+                let address = Address::from_slice(address_bytes);
+                if address.is_builtin_address() {
+                    return Ok(None);
+                }
+                let value: Option<H256> = self.client.call(
+                    "mazze_getStorageAt",
+                    json!([
+                        self.rpc_address(&address)?,
+                        U256::from_big_endian(storage_key),
+                        self.epoch
+                    ]),
+                )?;
+                let value = value.map_or(U256::zero(), |value| {
+                    U256::from_big_endian(value.as_bytes())
+                });
+                if value.is_zero() {
+                    return Ok(None);
+                }
+                storage_value(value, self.storage_owner(&address, storage_key)?)
+            }
+            StorageKey::CodeKey {
+                address_bytes,
+                code_hash_bytes,
+            } => {
+                let address = Address::from_slice(address_bytes);
+                let code: Bytes = self.client.call(
+                    "mazze_getCode",
+                    json!([self.rpc_address(&address)?, self.epoch]),
+                )?;
+                code_info(code, code_hash_bytes)
+            }
+            // Deposit lists and vote lists are not forked.
+            _ => None,
+        })
+    }
+
+    /// The owner of a storage entry, which is only served by the debug RPC.
+    /// Without it, the entry has no owner and is charged to the contract
+    /// itself.
+    fn storage_owner(
+        &self, address: &Address, storage_key: &[u8],
+    ) -> Result<Option<Address>, String> {
+        if !self.storage_range_available.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        let page: Option<StorageRangePage> = self.client.call_if_available(
+            "debug_getStorageRange",
+            json!([
+                self.rpc_address(address)?,
+                self.epoch,
+                H256::from_slice(storage_key),
+                "0x1"
+            ]),
+        )?;
+        let page = match page {
+            Some(page) => page,
+            None => {
+                info!(
+                    "debug_getStorageRange is not available, the forked \
+                     storage entries are owned by their contracts"
+                );
+                self.storage_range_available.store(false, Ordering::Relaxed);
+                return Ok(None);
+            }
+        };
+        Ok(match page.storage.into_iter().next() {
+            Some(entry) if entry.key.as_bytes() == storage_key => {
+                entry.owner.map(|owner| owner.hex_address)
+            }
+            _ => None,
+        })
+    }
+
+    fn account(&self, address: &Address) -> Result<Option<Account>, String> {
+        // The internal contracts and the global parameters stay local.
+        if address.is_builtin_address() {
+            return Ok(None);
+        }
+        let account: RpcAccount = self.client.call(
+            "mazze_getAccount",
+            json!([self.rpc_address(address)?, self.epoch]),
+        )?;
+        let sponsor_info = if address.is_contract_address() {
+            self.sponsor_info(address)?
+        } else {
+            Default::default()
+        };
+        // The RPC counts the used storage points in the collateral.
+        let collateral_for_storage =
+            account.collateral_for_storage.saturating_sub(
+                sponsor_info
+                    .storage_points
+                    .as_ref()
+                    .map_or(U256::zero(), |x| x.used),
+            );
+        let account = Account::from_contract_account(
+            *address,
+            ContractAccount {
+                balance: account.balance,
+                nonce: account.nonce,
+                code_hash: account.code_hash,
+                staking_balance: account.staking_balance,
+                collateral_for_storage,
+                accumulated_interest_return: account
+                    .accumulated_interest_return,
+                admin: account.admin.hex_address,
+                sponsor_info,
+            },
+        );
+        // An account that does not exist is returned as an empty one.
+        let empty = account.balance.is_zero()
+            && account.nonce.is_zero()
+            && account.code_hash == KECCAK_EMPTY
+            && account.staking_balance.is_zero()
+            && account.collateral_for_storage.is_zero();
+        Ok(if empty { None } else { Some(account) })
+    }
+
+    fn sponsor_info(&self, address: &Address) -> Result<SponsorInfo, String> {
+        let info: RpcSponsorInfo = self.client.call(
+            "mazze_getSponsorInfo",
+            json!([self.rpc_address(address)?, self.epoch]),
+        )?;
+        let storage_points = if info.available_storage_points.is_zero()
+            && info.used_storage_points.is_zero()
+        {
+            None
+        } else {
+            Some(StoragePoints {
+                unused: info.available_storage_points
+                    * *MAZZIES_PER_STORAGE_COLLATERAL_UNIT,
+                used: info.used_storage_points
+                    * *MAZZIES_PER_STORAGE_COLLATERAL_UNIT,
+            })
+        };
+        Ok(SponsorInfo {
+            sponsor_for_gas: info.sponsor_for_gas.hex_address,
+            sponsor_for_collateral: info.sponsor_for_collateral.hex_address,
+            sponsor_gas_bound: info.sponsor_gas_bound,
+            sponsor_balance_for_gas: info.sponsor_balance_for_gas,
+            sponsor_balance_for_collateral: info.sponsor_balance_for_collateral,
+            storage_points,
+        })
+    }
+
+    fn rpc_address(&self, address: &Address) -> Result<RpcAddress, String> {
+        RpcAddress::try_from_h160(*address, self.network)
+    }
+}
+
+struct EthRemote {
+    client: RpcClient,
+    block: String,
+}
+
+impl EthRemote {
+    fn new(client: RpcClient, block: u64) -> Self {
+        Self {
+            client,
+            block: format!("{:#x}", block),
+        }
+    }
+
+    fn get(&self, key: StorageKey) -> Result<Option<Box<[u8]>>, String> {
+        Ok(match key {
+            StorageKey::AccountKey(address) => self
+                .account(&Address::from_slice(address))?
+                .map(|account| rlp::encode(&account).into()),
+            StorageKey::StorageRootKey(address) => self
+                .account(&Address::from_slice(address))?
+                .map(|_| STORAGE_LAYOUT_REGULAR_V0.to_bytes().into()),
+            StorageKey::StorageKey {
+                address_bytes,
+                storage_key,
+            } => {
+                let value: H256 = self.client.call(
+                    "eth_getStorageAt",
+                    json!([
+                        Address::from_slice(address_bytes),
+                        H256::from_slice(storage_key),
+                        self.block
+                    ]),
+                )?;
+                storage_value(U256::from_big_endian(value.as_bytes()), None)
+            }
+            StorageKey::CodeKey {
+                address_bytes,
+                code_hash_bytes,
+            } => {
+                let code = self.code(&Address::from_slice(address_bytes))?;
+                code_info(code, code_hash_bytes)
+            }
+            _ => None,
+        })
+    }
+
+    fn code(&self, address: &Address) -> Result<Bytes, String> {
+        self.client
+            .call("eth_getCode", json!([address, self.block]))
+    }
+
+    fn account(&self, address: &Address) -> Result<Option<Account>, String> {
+        let balance: U256 = self
+            .client
+            .call("eth_getBalance", json!([address, self.block]))?;
+        let nonce: U256 = self
+            .client
+            .call("eth_getTransactionCount", json!([address, self.block]))?;
+        let code = self.code(address)?;
+        if balance.is_zero() && nonce.is_zero() && code.0.is_empty() {
+            return Ok(None);
+        }
+        let mut account = Account::new_empty_with_balance(
+            &address.with_evm_space(),
+            &balance,
+            &nonce,
+        );
+        account.code_hash = keccak(&code.0);
+        Ok(Some(account))
+    }
+}
+
+fn storage_value(value: U256, owner: Option<Address>) -> Option<Box<[u8]>> {
+    if value.is_zero() {
+        return None;
+    }
+    let value = StorageValue { value, owner };
+    Some(rlp::encode(&value).into())
+}
+
+/// The code is ignored if it does not match the code hash, e.g. when the
+/// contract has been destroyed on the remote chain.
+fn code_info(code: Bytes, code_hash: &[u8]) -> Option<Box<[u8]>> {
+    if code.0.is_empty() || keccak(&code.0).as_bytes() != code_hash {
+        return None;
+    }
+    let code_info = CodeInfo {
+        code: Arc::new(code.0),
+        owner: Address::zero(),
+    };
+    Some(rlp::encode(&code_info).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpc_core::{IoHandler, Params, Result as JsonRpcResult};
+    use jsonrpc_http_server::ServerBuilder;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_eth_fork_source() {
+        let mut io = IoHandler::new();
+        io.add_method("eth_blockNumber", |_| -> JsonRpcResult<Value> {
+            Ok(json!("0x10"))
+        });
+        io.add_method("eth_getBalance", |params: Params| -> JsonRpcResult<_> {
+            let (_, block): (Address, String) = params.parse()?;
+            assert_eq!(block, "0x10");
+            Ok(json!("0x64"))
+        });
+        io.add_method("eth_getTransactionCount", |_| -> JsonRpcResult<_> {
+            Ok(json!("0x2"))
+        });
+        io.add_method("eth_getCode", |_| -> JsonRpcResult<_> {
+            Ok(json!("0x6001"))
+        });
+        io.add_method("eth_getStorageAt", |params: Params| {
+            let (_, position, _): (Address, H256, String) = params.parse()?;
+            JsonRpcResult::Ok(json!(position))
+        });
+        let server = ServerBuilder::new(io)
+            .start_http(&"127.0.0.1:0".parse().unwrap())
+            .unwrap();
+
+        let source = RpcForkSource::new(ForkConfiguration {
+            url: None,
+            eth_url: Some(format!("http://{}", server.address())),
+            epoch: None,
+        })
+        .unwrap();
+        let address =
+            Address::from_str("1000000000000000000000000000000000000001")
+                .unwrap();
+
+        let raw = source
+            .get(StorageKey::new_account_key(&address).with_evm_space())
+            .unwrap()
+            .unwrap();
+        let account =
+            Account::new_from_rlp(address, &rlp::Rlp::new(&raw)).unwrap();
+        assert_eq!(account.address(), &address.with_evm_space());
+        assert_eq!(account.balance, 100.into());
+        assert_eq!(account.nonce, 2.into());
+        assert_eq!(account.code_hash, keccak([0x60u8, 0x01]));
+
+        let raw = source
+            .get(
+                StorageKey::new_code_key(&address, &account.code_hash)
+                    .with_evm_space(),
+            )
+            .unwrap()
+            .unwrap();
+        let code_info: CodeInfo = rlp::decode(&raw).unwrap();
+        assert_eq!(*code_info.code, vec![0x60, 0x01]);
+
+        let position = H256::from_low_u64_be(7);
+        let raw = source
+            .get(
+                StorageKey::new_storage_key(&address, position.as_bytes())
+                    .with_evm_space(),
+            )
+            .unwrap()
+            .unwrap();
+        let value: StorageValue = rlp::decode(&raw).unwrap();
+        assert_eq!(value.value, 7.into());
+
+        // The accounts not in the native space are not fetched.
+        assert_eq!(
+            source
+                .get(StorageKey::new_account_key(&address).with_native_space())
+                .unwrap(),
+            None
+        );
+
+        server.close();
+    }
+
+    const CONTRACT: &str = "8000000000000000000000000000000000000001";
+
+    /// A native space node at epoch 0x10, whose only contract `CONTRACT` has
+    /// 42 stored at 0x7. It serves `debug_getStorageRange` if `storage_range`
+    /// is set.
+    fn native_io(storage_range: bool) -> IoHandler {
+        let network = Network::Test;
+        let rpc_address = |address: &str| {
+            let address = Address::from_str(address).unwrap();
+            serde_json::to_value(
+                RpcAddress::try_from_h160(address, network).unwrap(),
+            )
+            .unwrap()
+        };
+        let (contract_json, admin, sponsor) = (
+            rpc_address(CONTRACT),
+            rpc_address("1000000000000000000000000000000000000002"),
+            rpc_address("1000000000000000000000000000000000000003"),
+        );
+
+        let mut io = IoHandler::new();
+        io.add_method("mazze_getStatus", |_| -> JsonRpcResult<Value> {
+            Ok(json!({
+                "bestHash": H256::zero(),
+                "chainId": "0x1",
+                "ethereumSpaceChainId": "0x47",
+                "networkId": "0x1",
+                "epochNumber": "0x20",
+                "blockNumber": "0x20",
+                "pendingTxNumber": "0x0",
+                "latestCheckpoint": "0x0",
+                "latestConfirmed": "0x10",
+                "latestState": "0x10",
+                "latestFinalized": "0x0",
+            }))
+        });
+        let account_address = contract_json.clone();
+        io.add_method("mazze_getAccount", move |params: Params| {
+            let (_, epoch): (Value, String) = params.parse()?;
+            assert_eq!(epoch, "0x10");
+            JsonRpcResult::Ok(json!({
+                "address": account_address,
+                "balance": "0x64",
+                "nonce": "0x1",
+                "codeHash": keccak([0x60u8, 0x01]),
+                "stakingBalance": "0x0",
+                // It includes the used storage points.
+                "collateralForStorage": (*MAZZIES_PER_STORAGE_COLLATERAL_UNIT
+                    * 3)
+                    .to_string(),
+                "accumulatedInterestReturn": "0x0",
+                "admin": admin,
+            }))
+        });
+        io.add_method("mazze_getSponsorInfo", move |_| {
+            JsonRpcResult::Ok(json!({
+                "sponsorForGas": sponsor,
+                "sponsorForCollateral": sponsor,
+                "sponsorGasBound": "0x5",
+                "sponsorBalanceForGas": "0x6",
+                "sponsorBalanceForCollateral": "0x7",
+                "availableStoragePoints": "0x1",
+                "usedStoragePoints": "0x2",
+            }))
+        });
+        io.add_method("mazze_getStorageAt", |params: Params| {
+            let (_, position, epoch): (Value, U256, String) = params.parse()?;
+            assert_eq!(epoch, "0x10");
+            let value = if position == 7.into() {
+                json!(H256::from_low_u64_be(42))
+            } else {
+                Value::Null
+            };
+            JsonRpcResult::Ok(value)
+        });
+        if storage_range {
+            let owner = rpc_address(CONTRACT);
+            io.add_method("debug_getStorageRange", move |params: Params| {
+                let (_, _, start_key, limit): (Value, String, H256, String) =
+                    params.parse()?;
+                assert_eq!(limit, "0x1");
+                let position = H256::from_low_u64_be(7);
+                let storage = if start_key <= position {
+                    json!([{
+                        "key": position,
+                        "value": "0x2a",
+                        "owner": owner,
+                    }])
+                } else {
+                    json!([])
+                };
+                JsonRpcResult::Ok(json!({
+                    "storage": storage,
+                    "nextKey": null,
+                }))
+            });
+        }
+        io
+    }
+
+    #[test]
+    fn test_native_fork_source() {
+        let server = ServerBuilder::new(native_io(true))
+            .start_http(&"127.0.0.1:0".parse().unwrap())
+            .unwrap();
+
+        let source = RpcForkSource::new(ForkConfiguration {
+            url: Some(format!("http://{}", server.address())),
+            eth_url: None,
+            epoch: None,
+        })
+        .unwrap();
+        let address = Address::from_str(CONTRACT).unwrap();
+
+        let raw = source
+            .get(StorageKey::new_account_key(&address).with_native_space())
+            .unwrap()
+            .unwrap();
+        let account =
+            Account::new_from_rlp(address, &rlp::Rlp::new(&raw)).unwrap();
+        assert_eq!(account.balance, 100.into());
+        assert_eq!(account.nonce, 1.into());
+        assert_eq!(
+            account.admin,
+            Address::from_str("1000000000000000000000000000000000000002")
+                .unwrap()
+        );
+        let sponsor_info = &account.sponsor_info;
+        assert_eq!(
+            sponsor_info.sponsor_for_gas,
+            Address::from_str("1000000000000000000000000000000000000003")
+                .unwrap()
+        );
+        assert_eq!(sponsor_info.sponsor_gas_bound, 5.into());
+        assert_eq!(sponsor_info.sponsor_balance_for_gas, 6.into());
+        assert_eq!(sponsor_info.sponsor_balance_for_collateral, 7.into());
+        let storage_points = sponsor_info.storage_points.as_ref().unwrap();
+        assert_eq!(storage_points.unused, *MAZZIES_PER_STORAGE_COLLATERAL_UNIT);
+        assert_eq!(
+            storage_points.used,
+            *MAZZIES_PER_STORAGE_COLLATERAL_UNIT * 2
+        );
+        assert_eq!(
+            account.collateral_for_storage,
+            *MAZZIES_PER_STORAGE_COLLATERAL_UNIT
+        );
+
+        let position = H256::from_low_u64_be(7);
+        let raw = source
+            .get(
+                StorageKey::new_storage_key(&address, position.as_bytes())
+                    .with_native_space(),
+            )
+            .unwrap()
+            .unwrap();
+        let value: StorageValue = rlp::decode(&raw).unwrap();
+        assert_eq!(value.value, 42.into());
+        assert_eq!(value.owner, Some(address));
+
+        // The entries which do not exist are not forked.
+        let position = H256::from_low_u64_be(6);
+        assert_eq!(
+            source
+                .get(
+                    StorageKey::new_storage_key(&address, position.as_bytes())
+                        .with_native_space()
+                )
+                .unwrap(),
+            None
+        );
+
+        // The accounts not in the eth space are not fetched.
+        assert_eq!(
+            source
+                .get(StorageKey::new_account_key(&address).with_evm_space())
+                .unwrap(),
+            None
+        );
+
+        server.close();
+    }
+
+    #[test]
+    fn test_native_fork_source_without_storage_range() {
+        let server = ServerBuilder::new(native_io(false))
+            .start_http(&"127.0.0.1:0".parse().unwrap())
+            .unwrap();
+
+        let source = RpcForkSource::new(ForkConfiguration {
+            url: Some(format!("http://{}", server.address())),
+            eth_url: None,
+            epoch: None,
+        })
+        .unwrap();
+        let address = Address::from_str(CONTRACT).unwrap();
+
+        // The entry is owned by the contract without the debug RPC.
+        for _ in 0..2 {
+            let position = H256::from_low_u64_be(7);
+            let raw = source
+                .get(
+                    StorageKey::new_storage_key(&address, position.as_bytes())
+                        .with_native_space(),
+                )
+                .unwrap()
+                .unwrap();
+            let value: StorageValue = rlp::decode(&raw).unwrap();
+            assert_eq!(value.value, 42.into());
+            assert_eq!(value.owner, None);
+        }
+
+        server.close();
+    }
+
+    #[test]
+    fn test_fork_spaces_at_same_epoch() {
+        let mut io = native_io(false);
+        // The eth space is ahead of the latest state of the native space.
+        io.add_method("eth_blockNumber", |_| -> JsonRpcResult<Value> {
+            Ok(json!("0x20"))
+        });
+        io.add_method("eth_getBalance", |params: Params| -> JsonRpcResult<_> {
+            let (_, block): (Address, String) = params.parse()?;
+            assert_eq!(block, "0x10");
+            Ok(json!("0x64"))
+        });
+        io.add_method("eth_getTransactionCount", |_| -> JsonRpcResult<_> {
+            Ok(json!("0x0"))
+        });
+        io.add_method("eth_getCode", |_| -> JsonRpcResult<_> {
+            Ok(json!("0x"))
+        });
+        let server = ServerBuilder::new(io)
+            .start_http(&"127.0.0.1:0".parse().unwrap())
+            .unwrap();
+
+        let url = format!("http://{}", server.address());
+        let source = RpcForkSource::new(ForkConfiguration {
+            url: Some(url.clone()),
+            eth_url: Some(url),
+            epoch: None,
+        })
+        .unwrap();
+        let address =
+            Address::from_str("1000000000000000000000000000000000000001")
+                .unwrap();
+
+        let raw = source
+            .get(StorageKey::new_account_key(&address).with_evm_space())
+            .unwrap()
+            .unwrap();
+        let account =
+            Account::new_from_rlp(address, &rlp::Rlp::new(&raw)).unwrap();
+        assert_eq!(account.balance, 100.into());
+
+        server.close();
+    }
+}
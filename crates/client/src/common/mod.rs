@@ -1,5 +1,12 @@
 
 
+pub mod block_export;
+pub mod fork;
+pub mod reexecute;
+pub mod snapshot_archive;
+pub mod state_dump;
+pub mod state_stats;
+
 use std::{
     collections::HashMap,
     fs::create_dir_all,
@@ -26,8 +33,9 @@ pub use mazzecore::pos::pos::PosDropHandle;
 use mazzecore::{
     block_data_manager::BlockDataManager,
     consensus::pos_handler::{PosConfiguration, PosVerifier},
-    genesis_block::{self as genesis, genesis_block},
+    genesis_block::{self as genesis, genesis_block, genesis_block_from_dump},
     pow::PowComputer,
+    state_dump::load_state_dump_file,
     statistics::Statistics,
     sync::SyncPhaseType,
     ConsensusGraph, LightProvider, NodeType, Notifications, Stopable,
@@ -54,6 +62,7 @@ use txgen::{DirectTransactionGenerator, TransactionGenerator};
 pub use crate::configuration::Configuration;
 use crate::{
     accounts::{account_provider, keys_path},
+    common::fork::RpcForkSource,
     configuration::parse_config_address_string,
     rpc::{
         extractor::RpcExtractor,
@@ -249,7 +258,14 @@ pub fn initialize_common_modules(
         //         .map(|s| s.into_bytes())
         // };
         let default_passwd = Some(vec![]);
-        if key_path.exists() {
+        if conf.is_offline() && !key_path.exists() {
+            // The node is not started, so a key is not saved for it.
+            let mut rng = StdRng::from_rng(OsRng).unwrap();
+            (
+                ConfigKey::new(ConsensusPrivateKey::generate(&mut rng)),
+                ConfigKey::new(ConsensusVRFPrivateKey::generate(&mut rng)),
+            )
+        } else if key_path.exists() {
             let passwd = match default_passwd {
                 Some(p) => p,
                 None => rpassword::read_password_from_tty(Some("PoS key detected, please input your encryption password.\nPassword:")).map_err(|e| format!("{:?}", e))?.into_bytes()
@@ -298,10 +314,16 @@ pub fn initialize_common_modules(
         .map_err(|e| format!("Failed to open database {:?}", e))?;
 
     let secret_store = Arc::new(SecretStore::new());
-    let storage_manager = Arc::new(
-        StorageManager::new(conf.storage_config(&node_type))
-            .expect("Failed to initialize storage."),
-    );
+    let mut storage_manager = StorageManager::new(
+        conf.storage_config(&node_type),
+    )
+    .expect("Failed to initialize storage.");
+    if let Some(fork_config) = conf.fork_config() {
+        storage_manager
+            .set_fork_source(Box::new(RpcForkSource::new(fork_config)?))
+            .map_err(|e| format!("Failed to open the fork state: {}", e))?;
+    }
+    let storage_manager = Arc::new(storage_manager);
     {
         let storage_manager_log_weak_ptr = Arc::downgrade(&storage_manager);
         let exit_clone = exit.clone();
@@ -358,16 +380,47 @@ pub fn initialize_common_modules(
     let vm = VmFactory::new(1024 * 32);
     let machine = Arc::new(new_machine_with_builtin(conf.common_params(), vm));
 
-    let genesis_block = genesis_block(
-        &storage_manager,
-        genesis_accounts.clone(),
-        Address::from_str(GENESIS_VERSION).unwrap(),
-        U256::zero(),
-        machine.clone(),
-        conf.raw_conf.execute_genesis, /* need_to_execute */
-        conf.raw_conf.chain_id,
-        &initial_nodes,
-    );
+    // A chain started from a state dump takes the whole genesis state from
+    // the dump, so only the genesis accounts above which are in the dump are
+    // kept, with the dumped balances. The dump is streamed into the genesis
+    // state rather than loaded at once.
+    let (genesis_block, genesis_accounts) = match conf
+        .raw_conf
+        .genesis_state_dump
+    {
+        Some(ref file) => {
+            let mut dumped_balances = HashMap::new();
+            let dumped_accounts =
+                load_state_dump_file(file)?.inspect(|account| {
+                    if let Ok(account) = account {
+                        let address = account.address_with_space();
+                        if genesis_accounts.contains_key(&address) {
+                            dumped_balances.insert(address, account.balance);
+                        }
+                    }
+                });
+            let genesis_block = genesis_block_from_dump(
+                &storage_manager,
+                dumped_accounts,
+                Address::from_str(GENESIS_VERSION).unwrap(),
+                U256::zero(),
+            )?;
+            (genesis_block, dumped_balances)
+        }
+        None => {
+            let genesis_block = genesis_block(
+                &storage_manager,
+                genesis_accounts.clone(),
+                Address::from_str(GENESIS_VERSION).unwrap(),
+                U256::zero(),
+                machine.clone(),
+                conf.raw_conf.execute_genesis, /* need_to_execute */
+                conf.raw_conf.chain_id,
+                &initial_nodes,
+            );
+            (genesis_block, genesis_accounts)
+        }
+    };
     storage_manager.notify_genesis_hash(genesis_block.hash());
     let mut genesis_accounts = genesis_accounts;
     let genesis_accounts = genesis_accounts
@@ -398,12 +451,15 @@ pub fn initialize_common_modules(
         let private_key = ConsensusPrivateKey::generate(&mut rng);
         let vrf_private_key = ConsensusVRFPrivateKey::generate(&mut rng);
         let mut network = NetworkService::new(network_config.clone());
-        network
-            .initialize((
-                private_key.public_key(),
-                vrf_private_key.public_key(),
-            ))
-            .unwrap();
+        // The network is not needed to inspect the node data offline.
+        if !conf.is_offline() {
+            network
+                .initialize((
+                    private_key.public_key(),
+                    vrf_private_key.public_key(),
+                ))
+                .unwrap();
+        }
         Arc::new(network)
     };
 
@@ -633,6 +689,31 @@ pub fn initialize_not_light_node_modules(
         ).expect("Memory usage thread start fails");
     }
 
+    if data_man.log_index_enabled() {
+        // Only a weak reference is kept so that the graceful shutdown is not
+        // blocked by the backfill.
+        let data_man = Arc::downgrade(&data_man);
+        thread::Builder::new()
+            .name("LogIndexBackfill".into())
+            .spawn(move || BlockDataManager::backfill_log_index(data_man))
+            .expect("Log index backfill thread start fails");
+    }
+
+    if let Some(state_stats_period) = data_man.state_stats_period() {
+        let data_man = Arc::downgrade(&data_man);
+        let exit = exit.clone();
+        thread::Builder::new()
+            .name("StateStats".into())
+            .spawn(move || {
+                BlockDataManager::collect_state_stats_periodically(
+                    data_man,
+                    state_stats_period,
+                    exit,
+                )
+            })
+            .expect("State stats thread start fails");
+    }
+
     let (maybe_txgen, maybe_direct_txgen) = initialize_txgens(
         consensus.clone(),
         txpool.clone(),
@@ -0,0 +1,152 @@
+use std::{fmt::Write, sync::Arc};
+
+use mazze_types::H256;
+use mazzecore::{
+    consensus::{EpochReexecution, TransactionMismatch},
+    NodeType,
+};
+use parking_lot::{Condvar, Mutex};
+use rustc_hex::ToHex;
+
+use super::{initialize_common_modules, Configuration};
+
+/// Execute the epochs in `[from, to]` again on top of the stored states
+/// without starting the node, and compare the receipts, the logs bloom and
+/// the state roots with the stored ones. It stops at the first divergent
+/// epoch, and reports the first mismatching transaction. The accounts and
+/// storage entries it writes are compared at the end of the epoch, because
+/// the state after each transaction is not stored.
+///
+/// The node data is opened read only and nothing is executed or persisted
+/// while the graph is recovered.
+pub fn reexecute_epochs(
+    conf: &mut Configuration, from: u64, to: u64,
+) -> Result<String, String> {
+    if from > to {
+        bail!("invalid epoch range [{}, {}]", from, to);
+    }
+    let node_type = conf.node_type();
+    if node_type == NodeType::Light {
+        bail!("light nodes do not keep the states to execute epochs");
+    }
+
+    conf.read_only = true;
+    let exit = Arc::new((Mutex::new(false), Condvar::new()));
+    let (
+        _machine,
+        _secret_store,
+        _genesis_accounts,
+        _data_man,
+        _pow,
+        _pos_verifier,
+        _txpool,
+        consensus,
+        sync_graph,
+        _network,
+        _common_impl,
+        _accounts,
+        _notifications,
+        _pubsub,
+        _runtime,
+        _eth_pubsub,
+    ) = initialize_common_modules(conf, exit, node_type)?;
+    sync_graph.recover_graph_from_db_offline()?;
+
+    let mut output = String::new();
+    for epoch_number in from..=to {
+        let result = consensus
+            .reexecute_epoch(epoch_number)
+            .map_err(|e| format!("epoch {}: {}", epoch_number, e))?;
+        if result.is_consistent() {
+            writeln!(
+                output,
+                "epoch {} {:?}: OK",
+                epoch_number, result.epoch_hash
+            )
+            .unwrap();
+        } else {
+            write_divergence(&mut output, epoch_number, &result);
+            break;
+        }
+    }
+    Ok(output.trim_end().to_string())
+}
+
+fn write_divergence(
+    output: &mut String, epoch_number: u64, result: &EpochReexecution,
+) {
+    let state_root = |r: &EpochReexecution, expected: bool| -> H256 {
+        let commitment = if expected { &r.expected } else { &r.actual };
+        commitment
+            .state_root_with_aux_info
+            .state_root
+            .compute_state_root_hash()
+    };
+    writeln!(
+        output,
+        "epoch {} {:?}: DIVERGED",
+        epoch_number, result.epoch_hash
+    )
+    .unwrap();
+    writeln!(
+        output,
+        "  state root:      expected {:?}, actual {:?}",
+        state_root(result, true),
+        state_root(result, false)
+    )
+    .unwrap();
+    writeln!(
+        output,
+        "  receipts root:   expected {:?}, actual {:?}",
+        result.expected.receipts_root, result.actual.receipts_root
+    )
+    .unwrap();
+    writeln!(
+        output,
+        "  logs bloom hash: expected {:?}, actual {:?}",
+        result.expected.logs_bloom_hash, result.actual.logs_bloom_hash
+    )
+    .unwrap();
+
+    if let Some(TransactionMismatch {
+        block_hash,
+        tx_hash,
+        index,
+        expected,
+        actual,
+    }) = &result.first_mismatch
+    {
+        writeln!(
+            output,
+            "first mismatching transaction {:?} (block {:?}, index {})",
+            tx_hash, block_hash, index
+        )
+        .unwrap();
+        writeln!(output, "  expected receipt: {:?}", expected).unwrap();
+        writeln!(output, "  actual receipt:   {:?}", actual).unwrap();
+    }
+
+    if !result.account_diffs.is_empty() {
+        let accounts = if result.first_mismatch.is_some() {
+            "the accounts written by the first mismatching transaction"
+        } else {
+            "the accounts written in the epoch"
+        };
+        writeln!(output, "end of epoch state diff of {}", accounts).unwrap();
+    }
+    for diff in &result.account_diffs {
+        writeln!(output, "account {:?}", diff.address).unwrap();
+        writeln!(output, "  expected: {:?}", diff.before).unwrap();
+        writeln!(output, "  actual:   {:?}", diff.after).unwrap();
+        for slot in &diff.storage {
+            writeln!(
+                output,
+                "  storage 0x{}: expected {:#x}, actual {:#x}",
+                slot.key.to_hex::<String>(),
+                slot.before,
+                slot.after
+            )
+            .unwrap();
+        }
+    }
+}
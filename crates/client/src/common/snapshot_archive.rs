@@ -0,0 +1,134 @@
+use std::{path::Path, str::FromStr, sync::Arc, thread, time::Duration};
+
+use mazze_storage::{SnapshotDbBackend, StorageManager};
+use mazzecore::{
+    sync::{export_snapshot, import_snapshot},
+    ConsensusGraphTrait, NodeType,
+};
+use parking_lot::{Condvar, Mutex};
+use primitives::EpochNumber;
+
+use super::{initialize_common_modules, Configuration};
+
+/// Write the snapshot of the main chain epoch `epoch` to the directory or
+/// the tarball at `path`, along with the headers and the consensus proofs to
+/// verify it. `epoch` must be a snapshot epoch whose snapshot is still kept by
+/// the node.
+pub fn export_snapshot_archive(
+    conf: &mut Configuration, epoch: u64, path: &Path,
+) -> Result<String, String> {
+    let node_type = conf.node_type();
+    if node_type == NodeType::Light {
+        bail!("light nodes do not keep the state snapshots");
+    }
+    let chunk_size = conf.raw_conf.chunk_size_byte;
+
+    conf.read_only = true;
+    let exit = Arc::new((Mutex::new(false), Condvar::new()));
+    let (
+        _machine,
+        _secret_store,
+        _genesis_accounts,
+        data_man,
+        _pow,
+        _pos_verifier,
+        _txpool,
+        consensus,
+        sync_graph,
+        _network,
+        _common_impl,
+        _accounts,
+        _notifications,
+        _pubsub,
+        _runtime,
+        _eth_pubsub,
+    ) = initialize_common_modules(conf, exit, node_type)?;
+    sync_graph.recover_graph_from_db_offline()?;
+
+    let snapshot_epoch_count = data_man.get_snapshot_epoch_count() as u64;
+    if epoch % snapshot_epoch_count != 0 {
+        bail!(
+            "epoch {} is not a snapshot epoch, which is a multiple of {}",
+            epoch,
+            snapshot_epoch_count
+        );
+    }
+    let snapshot_epoch_id =
+        consensus.get_hash_from_epoch_number(EpochNumber::Number(epoch))?;
+    let chunk_count =
+        export_snapshot(&sync_graph, &snapshot_epoch_id, path, chunk_size)
+            .map_err(|e| format!("failed to export snapshot: {}", e))?;
+
+    Ok(format!(
+        "exported snapshot {:?} of epoch {} in {} chunks to {:?}",
+        snapshot_epoch_id, epoch, chunk_count, path
+    ))
+}
+
+/// Restore the snapshot archive at `path` without starting the network. The
+/// headers in the archive are inserted if the node has not synced them, and
+/// the node uses the snapshot instead of syncing the checkpoint state from
+/// peers at the next start.
+pub fn import_snapshot_archive(
+    conf: &mut Configuration, path: &Path,
+) -> Result<String, String> {
+    let node_type = conf.node_type();
+    if node_type == NodeType::Light {
+        bail!("light nodes do not keep the state snapshots");
+    }
+
+    conf.offline = true;
+    let exit = Arc::new((Mutex::new(false), Condvar::new()));
+    let (
+        _machine,
+        _secret_store,
+        _genesis_accounts,
+        _data_man,
+        _pow,
+        _pos_verifier,
+        _txpool,
+        _consensus,
+        sync_graph,
+        _network,
+        _common_impl,
+        _accounts,
+        _notifications,
+        _pubsub,
+        _runtime,
+        _eth_pubsub,
+    ) = initialize_common_modules(conf, exit, node_type)?;
+    sync_graph.recover_graph_from_db();
+    while sync_graph.is_consensus_worker_busy() {
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    let snapshot_epoch_id = import_snapshot(&sync_graph, path)
+        .map_err(|e| format!("failed to import snapshot: {}", e))?;
+    Ok(format!(
+        "imported snapshot {:?} from {:?}",
+        snapshot_epoch_id, path
+    ))
+}
+
+/// Convert the snapshots kept in the `source_backend` snapshot dbs into the
+/// configured `snapshot_db_backend`, so that the node can switch the backend
+/// without syncing the state again. The node must not be running.
+pub fn convert_snapshot_db_backend(
+    conf: &mut Configuration, source_backend: &str,
+) -> Result<String, String> {
+    let node_type = conf.node_type();
+    if node_type == NodeType::Light {
+        bail!("light nodes do not keep the state snapshots");
+    }
+    let source_backend = SnapshotDbBackend::from_str(source_backend)?;
+    let storage_conf = conf.storage_config(&node_type);
+    let converted_snapshots = StorageManager::convert_snapshot_db_backend(
+        &storage_conf,
+        source_backend,
+    )
+    .map_err(|e| format!("failed to convert snapshots: {}", e))?;
+    Ok(format!(
+        "converted {} snapshots from {:?} to {:?}",
+        converted_snapshots, source_backend, storage_conf.snapshot_db_backend
+    ))
+}
@@ -0,0 +1,63 @@
+use std::{fs::File, io::BufWriter, path::Path, sync::Arc};
+
+use mazzecore::{state_dump::dump_state, ConsensusGraphTrait, NodeType};
+use parking_lot::{Condvar, Mutex};
+use primitives::EpochNumber;
+
+use super::{initialize_common_modules, Configuration};
+
+/// Write every account in the state after the main chain epoch `epoch` to
+/// `out` as NDJSON, which can be used as `genesis_state_dump` to start a new
+/// chain. The state of the epoch must still be kept by the node.
+pub fn dump_state_to_file(
+    conf: &mut Configuration, epoch: u64, out: &Path,
+) -> Result<String, String> {
+    let node_type = conf.node_type();
+    if node_type == NodeType::Light {
+        bail!("light nodes do not keep the states to dump");
+    }
+
+    conf.read_only = true;
+    let exit = Arc::new((Mutex::new(false), Condvar::new()));
+    let (
+        _machine,
+        _secret_store,
+        _genesis_accounts,
+        _data_man,
+        _pow,
+        _pos_verifier,
+        _txpool,
+        consensus,
+        sync_graph,
+        _network,
+        _common_impl,
+        _accounts,
+        _notifications,
+        _pubsub,
+        _runtime,
+        _eth_pubsub,
+    ) = initialize_common_modules(conf, exit, node_type)?;
+    sync_graph.recover_graph_from_db_offline()?;
+
+    let epoch_hash =
+        consensus.get_hash_from_epoch_number(EpochNumber::Number(epoch))?;
+    // The accounts are iterated from one view of the state, while their code
+    // and storage are read from another.
+    let open_state = || {
+        consensus
+            .get_storage_state_by_epoch_number(
+                EpochNumber::Number(epoch),
+                "epoch",
+            )
+            .map_err(|e| format!("failed to read the state: {}", e))
+    };
+    let (mut state, mut reader) = (open_state()?, open_state()?);
+    let file = File::create(out)
+        .map_err(|e| format!("failed to create {:?}: {}", out, e))?;
+    let count = dump_state(&mut state, &mut reader, &mut BufWriter::new(file))?;
+
+    Ok(format!(
+        "dumped {} accounts of epoch {} {:?} to {:?}",
+        count, epoch, epoch_hash, out
+    ))
+}
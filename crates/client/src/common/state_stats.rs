@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use mazzecore::{ConsensusGraphTrait, NodeType};
+use parking_lot::{Condvar, Mutex};
+use primitives::EpochNumber;
+
+use super::{initialize_common_modules, Configuration};
+use crate::rpc::types::StateStats as RpcStateStats;
+
+/// Walk the snapshot of the main chain epoch `epoch` and return its state
+/// statistics. The snapshot must still be kept by the node. The node data is
+/// opened read only, so the statistics are not stored for
+/// `debug_getStateStats`.
+pub fn collect_state_stats(
+    conf: &mut Configuration, epoch: u64,
+) -> Result<String, String> {
+    let node_type = conf.node_type();
+    if node_type == NodeType::Light {
+        bail!("light nodes do not keep the snapshots to walk");
+    }
+
+    conf.read_only = true;
+    let exit = Arc::new((Mutex::new(false), Condvar::new()));
+    let (
+        _machine,
+        _secret_store,
+        _genesis_accounts,
+        data_man,
+        _pow,
+        _pos_verifier,
+        _txpool,
+        consensus,
+        sync_graph,
+        network,
+        _common_impl,
+        _accounts,
+        _notifications,
+        _pubsub,
+        _runtime,
+        _eth_pubsub,
+    ) = initialize_common_modules(conf, exit, node_type)?;
+    sync_graph.recover_graph_from_db_offline()?;
+
+    let snapshot_epoch_count = data_man
+        .storage_manager
+        .get_storage_manager()
+        .get_snapshot_epoch_count() as u64;
+    if epoch % snapshot_epoch_count != 0 {
+        bail!(
+            "epoch {} is not a multiple of the snapshot epoch count {}",
+            epoch,
+            snapshot_epoch_count
+        );
+    }
+    let epoch_hash =
+        consensus.get_hash_from_epoch_number(EpochNumber::Number(epoch))?;
+    let stats = data_man
+        .collect_state_stats(&epoch_hash, epoch, &|| false)?
+        .ok_or_else(|| {
+            format!(
+                "the snapshot of epoch {} {:?} is not kept",
+                epoch, epoch_hash
+            )
+        })?;
+
+    let stats = RpcStateStats::try_from(stats, *network.get_network_type())?;
+    serde_json::to_string_pretty(&stats)
+        .map_err(|e| format!("failed to serialize the state stats: {}", e))
+}
@@ -20,11 +20,16 @@ use mazze_parameters::{
 };
 use mazze_storage::{
     defaults::DEFAULT_DEBUG_SNAPSHOT_CHECKER_THREADS, storage_dir,
-    ConsensusParam, ProvideExtraSnapshotSyncConfig, StorageConfiguration,
+    ConsensusParam, ProvideExtraSnapshotSyncConfig, SnapshotDbBackend,
+    StorageConfiguration,
 };
 use mazze_types::{Address, AllChainID, Space, SpaceMap, H256, U256};
 use mazzecore::{
-    block_data_manager::{DataManagerConfiguration, DbType},
+    block_data_manager::{
+        state_change_set::DEFAULT_MAX_HISTORICAL_STATE_EPOCH_COUNT,
+        state_stats::DEFAULT_STATE_STATS_TOP_N, DataManagerConfiguration,
+        DbType,
+    },
     block_parameters::*,
     cache_config::{
         DEFAULT_INVALID_BLOCK_HASH_CACHE_SIZE_IN_COUNT,
@@ -48,9 +53,12 @@ use network::DiscoveryConfiguration;
 use primitives::block_header::CIP112_TRANSITION_HEIGHT;
 use txgen::TransactionGeneratorConfig;
 
-use crate::rpc::{
-    impls::RpcImplConfiguration, rpc_apis::ApiSet, HttpConfiguration,
-    TcpConfiguration, WsConfiguration,
+use crate::{
+    common::fork::ForkConfiguration,
+    rpc::{
+        impls::RpcImplConfiguration, rpc_apis::ApiSet, HttpConfiguration,
+        TcpConfiguration, WsConfiguration,
+    },
 };
 
 lazy_static! {
@@ -110,6 +118,11 @@ build_config! {
         // Controls block generation speed.
         // Only effective in `dev` mode
         (dev_block_interval_ms, (Option<u64>), None)
+        // Fork the state of another node, in `dev` mode. The missing accounts,
+        // code and storage are fetched on demand from its RPC.
+        (fork_url, (Option<String>), None)
+        (fork_eth_url, (Option<String>), None)
+        (fork_epoch, (Option<u64>), None)
         (enable_state_expose, (bool), false)
         (generate_tx, (bool), false)
         (generate_tx_period_us, (Option<u64>), Some(100_000))
@@ -143,6 +156,9 @@ build_config! {
         (heavy_block_difficulty_ratio, (u64), HEAVY_BLOCK_DEFAULT_DIFFICULTY_RATIO)
         (genesis_accounts, (Option<String>), None)
         (genesis_secrets, (Option<String>), None)
+        // An NDJSON state dump written by `mazze state dump` to start a new
+        // chain from.
+        (genesis_state_dump, (Option<String>), None)
         (initial_difficulty, (Option<u64>), None)
         (tanzanite_transition_height, (u64), TANZANITE_HEIGHT)
         (hydra_transition_number, (Option<u64>), None)
@@ -163,6 +179,12 @@ build_config! {
         (next_hardfork_transition_height, (Option<u64>), None)
         (cip1559_transition_height, (Option<u64>), None)
         (cancun_opcodes_transition_number, (Option<u64>), None)
+        (eip7702_transition_number, (Option<u64>), None)
+        (rip7212_transition_number, (Option<u64>), None)
+        (eip2537_transition_number, (Option<u64>), None)
+        (eip7692_native_transition_number, (Option<u64>), None)
+        (eip7692_eth_transition_number, (Option<u64>), None)
+        (eth_sponsor_transition_number, (Option<u64>), None)
         (referee_bound, (usize), REFEREE_DEFAULT_BOUND)
         (params_dao_vote_period, (u64), DAO_PARAMETER_VOTE_PERIOD)
         (timer_chain_beta, (u64), TIMER_CHAIN_DEFAULT_BETA)
@@ -283,6 +305,7 @@ build_config! {
         (additional_maintained_reward_epoch_count, (Option<usize>), None)
         (additional_maintained_trace_epoch_count, (Option<usize>), None)
         (additional_maintained_transaction_index_epoch_count, (Option<usize>), None)
+        (additional_maintained_state_change_set_epoch_count, (Option<usize>), None)
         (block_cache_gc_period_ms, (u64), 5_000)
         (block_db_dir, (Option<String>), None)
         (block_db_type, (String), "rocksdb".to_string())
@@ -321,9 +344,19 @@ build_config! {
         (max_trans_count_received_in_catch_up, (u64), 60_000)
         (persist_tx_index, (bool), false)
         (persist_block_number_index, (bool), true)
+        (persist_cross_space_index, (bool), false)
+        (persist_log_index, (bool), false)
+        (persist_address_tx_index, (bool), false)
+        (address_tx_index_internal_transfers, (bool), false)
+        (freezer_dir, (Option<String>), None)
+        (state_stats_period_s, (Option<u64>), None)
+        (state_stats_top_n, (usize), DEFAULT_STATE_STATS_TOP_N)
+        (persist_state_change_set, (bool), false)
+        (max_historical_state_epoch_count, (u64), DEFAULT_MAX_HISTORICAL_STATE_EPOCH_COUNT)
         (print_memory_usage_period_s, (Option<u64>), None)
         (target_block_gas_limit, (u64), DEFAULT_TARGET_BLOCK_GAS_LIMIT)
         (executive_trace, (bool), false)
+        (parallel_execution, (bool), false)
         (check_status_genesis, (bool), true)
         (packing_gas_limit_block_count, (u64), 10)
         (poll_lifetime_in_seconds, (Option<u32>), None)
@@ -406,6 +439,11 @@ build_config! {
             (Vec<ProvideExtraSnapshotSyncConfig>),
             vec![ProvideExtraSnapshotSyncConfig::StableCheckpoint],
             ProvideExtraSnapshotSyncConfig::parse_config_list)
+        // The db in which the snapshots are kept, "sqlite" or "rocksdb".
+        // After switching the backend, existing snapshots have to be
+        // converted with `snapshot convert --from` before starting the node.
+        (snapshot_db_backend, (SnapshotDbBackend), SnapshotDbBackend::Sqlite,
+            SnapshotDbBackend::from_str)
         (node_type, (Option<NodeType>), None, NodeType::from_str)
         (public_rpc_apis, (ApiSet), ApiSet::Safe, ApiSet::from_str)
         (public_evm_rpc_apis, (ApiSet), ApiSet::Evm, ApiSet::from_str)
@@ -429,17 +467,29 @@ macro_rules! set_conf {
 #[derive(Debug)]
 pub struct Configuration {
     pub raw_conf: RawConfiguration,
+    /// Open the node data without modifying it, e.g. for the offline debug
+    /// commands. The writes to the databases are kept in memory.
+    pub read_only: bool,
+    /// Process the node data without starting the network, e.g. for the
+    /// offline import commands, which still persist their writes.
+    pub offline: bool,
 }
 
 impl Default for Configuration {
     fn default() -> Self {
         Configuration {
             raw_conf: Default::default(),
+            read_only: false,
+            offline: false,
         }
     }
 }
 
 impl Configuration {
+    /// Whether the node data is processed by an offline command instead of a
+    /// running node.
+    pub fn is_offline(&self) -> bool { self.read_only || self.offline }
+
     pub fn parse(matches: &clap::ArgMatches) -> Result<Configuration, String> {
         let mut config = Configuration::default();
         config.raw_conf = RawConfiguration::parse(matches)?;
@@ -569,13 +619,14 @@ impl Configuration {
                 Some(p) => db::DatabaseCompactionProfile::from_str(p).unwrap(),
                 None => db::DatabaseCompactionProfile::default(),
             };
-        let db_config = db::db_config(
+        let mut db_config = db::db_config(
             &db_dir,
             self.raw_conf.rocksdb_cache_size.clone(),
             compact_profile,
             NUM_COLUMNS.clone(),
             self.raw_conf.rocksdb_disable_wal,
         );
+        db_config.read_only = self.read_only;
         (db_dir, db_config)
     }
 
@@ -660,6 +711,7 @@ impl Configuration {
             get_logs_filter_max_limit: self.raw_conf.get_logs_filter_max_limit,
             sync_state_starting_epoch: self.raw_conf.sync_state_starting_epoch,
             sync_state_epoch_gap: self.raw_conf.sync_state_epoch_gap,
+            dev_mode: self.is_dev_mode(),
         };
         match self.raw_conf.node_type {
             Some(NodeType::Archive) => {
@@ -726,6 +778,23 @@ impl Configuration {
         )
     }
 
+    pub fn fork_config(&self) -> Option<ForkConfiguration> {
+        if self.raw_conf.fork_url.is_none()
+            && self.raw_conf.fork_eth_url.is_none()
+        {
+            return None;
+        }
+        if !self.is_dev_mode() {
+            error!("fork_url and fork_eth_url are only supported in dev mode!");
+            return None;
+        }
+        Some(ForkConfiguration {
+            url: self.raw_conf.fork_url.clone(),
+            eth_url: self.raw_conf.fork_eth_url.clone(),
+            epoch: self.raw_conf.fork_epoch,
+        })
+    }
+
     pub fn tx_gen_config(&self) -> Option<TransactionGeneratorConfig> {
         if self.is_test_or_dev_mode() &&
             // FIXME: this is not a good condition to check.
@@ -814,6 +883,8 @@ impl Configuration {
                 .raw_conf
                 .use_isolated_db_for_mpt_table_height,
             keep_era_genesis_snapshot: self.raw_conf.keep_era_genesis_snapshot,
+            snapshot_db_backend: self.raw_conf.snapshot_db_backend,
+            read_only: self.read_only,
         }
     }
 
@@ -945,6 +1016,22 @@ impl Configuration {
             persist_block_number_index: self
                 .raw_conf
                 .persist_block_number_index,
+            persist_cross_space_index: self.raw_conf.persist_cross_space_index,
+            persist_log_index: self.raw_conf.persist_log_index,
+            persist_address_tx_index: self.raw_conf.persist_address_tx_index,
+            address_tx_index_internal_transfers: self
+                .raw_conf
+                .address_tx_index_internal_transfers,
+            freezer_dir: self.raw_conf.freezer_dir.as_ref().map(PathBuf::from),
+            state_stats_period: self
+                .raw_conf
+                .state_stats_period_s
+                .map(Duration::from_secs),
+            state_stats_top_n: self.raw_conf.state_stats_top_n,
+            persist_state_change_set: self.raw_conf.persist_state_change_set,
+            max_historical_state_epoch_count: self
+                .raw_conf
+                .max_historical_state_epoch_count,
             tx_cache_index_maintain_timeout: Duration::from_millis(
                 self.raw_conf.tx_cache_index_maintain_timeout_ms,
             ),
@@ -968,6 +1055,9 @@ impl Configuration {
             additional_maintained_transaction_index_epoch_count: self
                 .raw_conf
                 .additional_maintained_transaction_index_epoch_count,
+            additional_maintained_state_change_set_epoch_count: self
+                .raw_conf
+                .additional_maintained_state_change_set_epoch_count,
             checkpoint_gc_time_in_epoch_count: (self
                 .raw_conf
                 .checkpoint_gc_time_in_era_count
@@ -1077,6 +1167,7 @@ impl Configuration {
             get_logs_filter_max_limit: self.raw_conf.get_logs_filter_max_limit,
             dev_pack_tx_immediately: self.is_dev_mode()
                 && self.raw_conf.dev_block_interval_ms.is_none(),
+            dev_mode: self.is_dev_mode(),
             max_payload_bytes: self.raw_conf.jsonrpc_ws_max_payload_bytes,
             enable_metrics: self.raw_conf.rpc_enable_metrics,
             poll_lifetime_in_seconds: self.raw_conf.poll_lifetime_in_seconds,
@@ -1151,6 +1242,7 @@ impl Configuration {
     pub fn execution_config(&self) -> ConsensusExecutionConfiguration {
         ConsensusExecutionConfiguration {
             executive_trace: self.raw_conf.executive_trace,
+            parallel_execution: self.raw_conf.parallel_execution,
         }
     }
 
@@ -1444,6 +1536,51 @@ impl Configuration {
             .or(self.raw_conf.next_hardfork_transition_number)
             .unwrap_or(default_transition_time);
 
+        //
+        // Set EOA code (EIP-7702)
+        //
+        params.transition_numbers.eip7702 = self
+            .raw_conf
+            .eip7702_transition_number
+            .unwrap_or(default_transition_time);
+
+        //
+        // P-256 signature verification precompile (RIP-7212)
+        //
+        params.transition_numbers.rip7212 = self
+            .raw_conf
+            .rip7212_transition_number
+            .unwrap_or(default_transition_time);
+
+        //
+        // BLS12-381 precompiles (EIP-2537)
+        //
+        params.transition_numbers.eip2537 = self
+            .raw_conf
+            .eip2537_transition_number
+            .unwrap_or(default_transition_time);
+
+        //
+        // EVM Object Format (EIP-7692), which is not activated in the native
+        // space unless configured.
+        //
+        params.transition_numbers.eip7692 = SpaceMap::new(
+            self.raw_conf
+                .eip7692_native_transition_number
+                .unwrap_or(u64::MAX),
+            self.raw_conf
+                .eip7692_eth_transition_number
+                .unwrap_or(default_transition_time),
+        );
+
+        //
+        // Gas sponsorship for eSpace contracts
+        //
+        params.transition_numbers.eth_sponsor = self
+            .raw_conf
+            .eth_sponsor_transition_number
+            .unwrap_or(default_transition_time);
+
         if params.transition_heights.cip1559
             < self.raw_conf.pos_reference_enable_height
         {
@@ -44,6 +44,7 @@ use self::{
         mazze::{MazzeHandler, LocalRpcImpl, RpcImpl, TestRpcImpl},
         mazze_filter::MazzeFilterClient,
         common::RpcImpl as CommonImpl,
+        dev::DevHandler,
         eth_pubsub::PubSubClient as EthPubSubClient,
         light::{
             MazzeHandler as LightMazzeHandler, DebugRpcImpl as LightDebugRpcImpl,
@@ -57,6 +58,7 @@ use self::{
     traits::{
         mazze::{Mazze, MazzeFilter},
         debug::LocalRpc,
+        dev::Dev,
         eth_space::{
             eth::{Eth, EthFilter},
             eth_pubsub::EthPubSub,
@@ -394,6 +396,25 @@ fn setup_rpc_apis(
                     PoSInterceptor::new(common.pos_handler.clone());
                 handler.extend_with(RpcProxy::new(pos, pos_interceptor));
             }
+            Api::Dev => {
+                // The cheat RPCs mutate the state directly.
+                if rpc.config.dev_mode {
+                    let dev = DevHandler::new(
+                        rpc.consensus.clone(),
+                        rpc.sync.clone(),
+                        rpc.tx_pool.clone(),
+                        rpc.block_gen.clone(),
+                    )
+                    .to_delegate();
+                    extend_with_interceptor(
+                        &mut handler,
+                        &rpc.config,
+                        dev,
+                        throttling_conf,
+                        throttling_section,
+                    );
+                }
+            }
         }
     }
 
@@ -528,6 +549,9 @@ fn setup_rpc_apis_light(
             Api::Pos => {
                 warn!("Light nodes do not support PoS RPC");
             }
+            Api::Dev => {
+                warn!("Light nodes do not support dev RPC");
+            }
         }
     }
     handler
@@ -7,6 +7,8 @@ pub struct RpcImplConfiguration {
     /// receiving a new tx through RPC calling to pack and execute this
     /// transaction.
     pub dev_pack_tx_immediately: bool,
+    /// If it's `true`, the dev mode cheat RPCs can be enabled.
+    pub dev_mode: bool,
 
     // maximum response payload size allowed
     // note: currently we only handle this for `mazze_getEpochReceipts`,
@@ -23,5 +25,5 @@ pub mod eth;
 pub mod pos;
 pub mod trace;
 
-pub use mazze::{mazze_filter, common, light, pool, pubsub};
+pub use mazze::{mazze_filter, common, dev, light, pool, pubsub};
 pub use eth::{debug, eth_filter, eth_handler::EthHandler, eth_pubsub};
@@ -1,9 +1,16 @@
-use std::convert::TryInto;
+use std::{collections::BTreeMap, convert::TryInto};
 
 use crate::rpc::{
     error_codes::invalid_params_msg,
+    impls::eth::eth_handler::exec_call_request,
     traits::eth_space::debug::Debug,
-    types::eth::{BlockNumber, CallRequest},
+    types::{
+        eth::{
+            AccountRangeResult, BlockNumber, CallRequest, DumpAccount,
+            GasProfile, StorageRangeEntry, StorageRangeResult,
+        },
+        Bytes,
+    },
 };
 use alloy_rpc_types_trace::geth::{
     GethDebugBuiltInTracerType,
@@ -11,10 +18,30 @@ use alloy_rpc_types_trace::geth::{
     GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace, NoopFrame,
     TraceResult,
 };
-use mazze_types::{Space, H256};
-use mazzecore::{ConsensusGraph, SharedConsensusGraph};
+use mazze_execute_helper::observer::gas_profiler::GasProfileKey;
+use mazze_statedb::StateDbExt;
+use mazze_types::{AddressSpaceUtil, BigEndianHash, Space, H160, H256};
+use mazzecore::{
+    rpc_errors::Error as MazzeRpcError, ConsensusGraph, SharedConsensusGraph,
+};
 use geth_tracer::to_alloy_h256;
 use jsonrpc_core::Result as JsonRpcResult;
+use keccak_hash::KECCAK_EMPTY;
+use primitives::EpochNumber;
+
+/// The maximum number of entries returned by `debug_storageRangeAt` and
+/// `debug_accountRange`, and of the storage entries of each account returned
+/// by `debug_accountRange`.
+const MAX_STATE_RANGE_RESULTS: usize = 1000;
+
+/// Convert `bytes` like `BytesToHash` and `BytesToAddress` of geth, which keep
+/// the last `N` bytes and pad zeros on the left.
+fn to_fixed_bytes<const N: usize>(bytes: &[u8]) -> [u8; N] {
+    let mut fixed = [0u8; N];
+    let len = bytes.len().min(N);
+    fixed[N - len..].copy_from_slice(&bytes[bytes.len() - len..]);
+    fixed
+}
 
 pub struct GethDebugHandler {
     consensus: SharedConsensusGraph,
@@ -185,4 +212,167 @@ impl Debug for GethDebugHandler {
         let _ = opts;
         todo!("not implemented yet");
     }
+
+    fn debug_profile_transaction(
+        &self, hash: H256,
+    ) -> JsonRpcResult<GasProfile> {
+        let tx_index = self
+            .consensus
+            .get_data_manager()
+            .transaction_index_by_hash(&hash, false /* update_cache */)
+            .ok_or(invalid_params_msg("invalid tx hash"))?;
+
+        let epoch_num = self
+            .consensus
+            .get_block_epoch_number(&tx_index.block_hash)
+            .ok_or(invalid_params_msg("invalid tx hash"))?;
+
+        let profile = self
+            .consensus_graph()
+            .collect_tx_gas_profile(epoch_num, hash)
+            .map_err(|e| invalid_params_msg(&format!("invalid tx hash: {e}")))?
+            .ok_or(invalid_params_msg("transaction is not executed"))?;
+
+        Ok(profile.into())
+    }
+
+    fn debug_profile_call(
+        &self, request: CallRequest, block_number: Option<BlockNumber>,
+    ) -> JsonRpcResult<GasProfile> {
+        let (execution_outcome, _estimation) = exec_call_request(
+            self.consensus_graph(),
+            request,
+            block_number,
+            true, /* profile_gas */
+        )?;
+
+        let profile = execution_outcome
+            .try_as_executed()
+            .and_then(|executed| executed.ext_result.get::<GasProfileKey>())
+            .cloned()
+            .ok_or_else(|| {
+                invalid_params_msg(&format!(
+                    "transaction can not be executed: {}",
+                    execution_outcome.error_message()
+                ))
+            })?;
+
+        Ok(profile.into())
+    }
+
+    fn debug_storage_range_at(
+        &self, block_hash: H256, _tx_index: usize, address: H160,
+        key_start: Bytes, max_result: usize,
+    ) -> JsonRpcResult<StorageRangeResult> {
+        let epoch_num = self
+            .consensus_graph()
+            .get_block_epoch_number_with_main_check(&block_hash, false)?;
+        let mut state_db = self.consensus.get_eth_state_db_by_epoch_number(
+            EpochNumber::Number(epoch_num),
+            "block_hash",
+        )?;
+
+        let key_start = H256(to_fixed_bytes(&key_start.0));
+        let (entries, next_key) = state_db
+            .get_storage_range(
+                &address.with_evm_space(),
+                key_start.as_bytes(),
+                max_result.min(MAX_STATE_RANGE_RESULTS),
+            )
+            .map_err(|err| MazzeRpcError::from(err))?;
+        let storage = entries
+            .into_iter()
+            .map(|(key, value)| {
+                let key = H256::from_slice(&key);
+                let entry = StorageRangeEntry {
+                    key: Some(key),
+                    value: H256::from_uint(&value.value),
+                };
+                (key, entry)
+            })
+            .collect();
+
+        Ok(StorageRangeResult {
+            storage,
+            next_key: next_key.map(|key| H256::from_slice(&key)),
+        })
+    }
+
+    fn debug_account_range(
+        &self, block_number: BlockNumber, start: Bytes, max_results: usize,
+        no_code: Option<bool>, no_storage: Option<bool>,
+        _incompletes: Option<bool>,
+    ) -> JsonRpcResult<AccountRangeResult> {
+        let epoch_num = match block_number {
+            BlockNumber::Hash {
+                hash,
+                require_canonical,
+            } => EpochNumber::Number(
+                self.consensus_graph()
+                    .get_block_epoch_number_with_main_check(
+                        &hash,
+                        require_canonical,
+                    )?,
+            ),
+            block_number => block_number.try_into()?,
+        };
+        let mut state_db = self
+            .consensus
+            .get_eth_state_db_by_epoch_number(epoch_num, "block_number")?;
+
+        let start = H160(to_fixed_bytes(&start.0));
+        let (accounts, next_address) = state_db
+            .get_account_range(
+                Space::Ethereum,
+                &start,
+                max_results.min(MAX_STATE_RANGE_RESULTS),
+            )
+            .map_err(|err| MazzeRpcError::from(err))?;
+
+        let mut dump = BTreeMap::new();
+        for account in accounts {
+            let address = *account.address();
+            let code = if no_code.unwrap_or(false)
+                || account.code_hash == KECCAK_EMPTY
+            {
+                None
+            } else {
+                state_db
+                    .get_code(&address, &account.code_hash)
+                    .map_err(|err| MazzeRpcError::from(err))?
+                    .map(|info| Bytes::new((*info.code).clone()))
+            };
+            let (storage, next_storage_key) = if no_storage.unwrap_or(false) {
+                (None, None)
+            } else {
+                let (entries, next_key) = state_db
+                    .get_storage_range(&address, &[], MAX_STATE_RANGE_RESULTS)
+                    .map_err(|err| MazzeRpcError::from(err))?;
+                let storage = entries
+                    .into_iter()
+                    .map(|(key, value)| {
+                        (H256::from_slice(&key), H256::from_uint(&value.value))
+                    })
+                    .collect();
+                (Some(storage), next_key.map(|key| H256::from_slice(&key)))
+            };
+            dump.insert(
+                address.address,
+                DumpAccount {
+                    balance: account.balance,
+                    nonce: account.nonce,
+                    code_hash: account.code_hash,
+                    code,
+                    storage,
+                    next_storage_key,
+                    address: address.address,
+                },
+            );
+        }
+
+        Ok(AccountRangeResult {
+            accounts: dump,
+            next: next_address.map(|address| Bytes::new(address.0.to_vec())),
+        })
+    }
 }
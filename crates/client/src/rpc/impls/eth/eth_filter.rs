@@ -25,7 +25,7 @@ use crate::rpc::{
         MAX_BLOCK_HISTORY_SIZE,
     },
     traits::eth_space::eth::EthFilter,
-    types::eth::{BlockNumber, EthRpcLogFilter, FilterChanges, Log},
+    types::eth::{EthRpcLogFilter, FilterChanges, Log},
 };
 use mazzecore::rpc_errors::Error as MazzeRpcError;
 use jsonrpc_core::{Error as RpcError, ErrorCode, Result as RpcResult};
@@ -49,6 +49,9 @@ pub trait Filterable {
         &self, filter: &LogFilter, epoch: (u64, Vec<H256>), removed: bool,
     ) -> RpcResult<Vec<Log>>;
 
+    /// Get logs of the pending block that match the given filter.
+    fn pending_logs(&self, filter: &LogFilter) -> RpcResult<Vec<Log>>;
+
     /// Get a reference to the poll manager.
     fn polls(&self) -> &Mutex<PollManager<SyncPollFilter<Log>>>;
 
@@ -227,6 +230,16 @@ impl Filterable for EthFilterClient {
         Ok(result)
     }
 
+    fn pending_logs(&self, filter: &LogFilter) -> RpcResult<Vec<Log>> {
+        let pending_block = self.consensus_graph().pending_block()?;
+
+        Ok(pending_block
+            .logs(filter)
+            .into_iter()
+            .map(Log::from_pending)
+            .collect())
+    }
+
     /// Get a reference to the poll manager.
     fn polls(&self) -> &Mutex<PollManager<SyncPollFilter<Log>>> { &self.polls }
 
@@ -375,15 +388,7 @@ impl<T: Filterable + Send + Sync + 'static> EthFilter for T {
         debug!("create filter: {:?}", filter);
         let mut polls = self.polls().lock();
         let epoch_number = self.best_executed_epoch_number();
-
-        if filter.to_block == Some(BlockNumber::Pending) {
-            bail!(RpcError {
-                code: ErrorCode::InvalidRequest,
-                message: "Filter logs from pending blocks is not supported"
-                    .into(),
-                data: None,
-            })
-        }
+        let include_pending = filter.includes_pending();
 
         let filter: LogFilter =
             filter.into_primitive(self.shared_consensus_graph())?;
@@ -395,7 +400,7 @@ impl<T: Filterable + Send + Sync + 'static> EthFilter for T {
                 epoch_number - 1
             },
             filter,
-            include_pending: false,
+            include_pending,
             previous_logs: VecDeque::with_capacity(MAX_BLOCK_HISTORY_SIZE),
             recent_reported_epochs: VecDeque::with_capacity(
                 MAX_BLOCK_HISTORY_SIZE,
@@ -557,7 +562,7 @@ impl<T: Filterable + Send + Sync + 'static> EthFilter for T {
 
     /// Returns all logs matching given filter (in a range 'from' - 'to').
     fn filter_logs(&self, index: H128) -> RpcResult<Vec<Log>> {
-        let (filter, _) = {
+        let (filter, include_pending) = {
             let mut polls = self.polls().lock();
 
             match polls.poll(&index).and_then(|f| {
@@ -580,10 +585,12 @@ impl<T: Filterable + Send + Sync + 'static> EthFilter for T {
         };
 
         // retrieve logs
-        Ok(limit_logs(
-            self.logs(filter)?,
-            self.get_logs_filter_max_limit(),
-        ))
+        let mut logs = self.logs(filter.clone())?;
+        if include_pending {
+            logs.extend(self.pending_logs(&filter)?);
+        }
+
+        Ok(limit_logs(logs, self.get_logs_filter_max_limit()))
     }
 
     /// Uninstalls filter.
@@ -5,7 +5,9 @@ use crate::rpc::{
         call_execution_error, internal_error, invalid_params,
         request_rejected_in_catch_up_mode, unknown_block,
     },
-    impls::RpcImplConfiguration,
+    impls::{
+        mazze::mazze_handler::address_transactions_page, RpcImplConfiguration,
+    },
     traits::eth_space::eth::Eth,
     types::{
         eth::{
@@ -13,7 +15,8 @@ use crate::rpc::{
             CallRequest, EthRpcLogFilter, Log, Receipt, SyncInfo, SyncStatus,
             Transaction,
         },
-        Bytes, FeeHistory, Index, MAX_GAS_CALL_REQUEST, U64 as HexU64,
+        AddressTransactionCursor, AddressTransactionsPage, Bytes, FeeHistory,
+        Index, MAX_GAS_CALL_REQUEST, U64 as HexU64,
     },
 };
 use mazze_execute_helper::estimation::{
@@ -43,13 +46,14 @@ use primitives::{
     receipt::EVM_SPACE_SUCCESS,
     transaction::{
         Eip1559Transaction, Eip155Transaction, Eip2930Transaction,
-        EthereumTransaction::*, EIP1559_TYPE, EIP2930_TYPE, LEGACY_TX_TYPE,
+        Eip7702Transaction, EthereumTransaction::*, EIP1559_TYPE,
+        EIP2930_TYPE, EIP7702_TYPE, LEGACY_TX_TYPE,
     },
     Action, BlockHashOrEpochNumber, EpochNumber, SignedTransaction, StorageKey,
     StorageValue, TransactionStatus, TransactionWithSignature,
 };
 use rustc_hex::ToHex;
-use std::{cmp::min, convert::TryInto};
+use std::{cmp::min, collections::HashSet, convert::TryInto};
 
 pub struct EthHandler {
     config: RpcImplConfiguration,
@@ -88,7 +92,9 @@ pub fn sign_call(
     let action = request.to.map_or(Action::Create, |addr| Action::Call(addr));
     let value = request.value.unwrap_or_default();
 
-    let default_type_id = if request.max_fee_per_gas.is_some()
+    let default_type_id = if request.authorization_list.is_some() {
+        EIP7702_TYPE
+    } else if request.max_fee_per_gas.is_some()
         || request.max_priority_fee_per_gas.is_some()
     {
         EIP1559_TYPE
@@ -109,6 +115,12 @@ pub fn sign_call(
     let max_priority_fee_per_gas =
         request.max_priority_fee_per_gas.unwrap_or(U256::zero());
     let access_list = request.access_list.unwrap_or(vec![]);
+    let authorization_list = request
+        .authorization_list
+        .unwrap_or(vec![])
+        .into_iter()
+        .map(Into::into)
+        .collect();
     let data = request.data.unwrap_or_default().into_vec();
 
     let transaction = match transaction_type.as_usize() as u8 {
@@ -142,6 +154,18 @@ pub fn sign_call(
             data,
             access_list,
         }),
+        EIP7702_TYPE => Eip7702(Eip7702Transaction {
+            chain_id,
+            nonce,
+            max_priority_fee_per_gas,
+            max_fee_per_gas,
+            gas,
+            action,
+            value,
+            data,
+            access_list,
+            authorization_list,
+        }),
         x => {
             return Err(
                 invalid_params("Unrecognized transaction type", x).into()
@@ -159,75 +183,116 @@ fn block_tx_by_index(
 ) -> Option<Transaction> {
     match phantom_block {
         None => None,
-        Some(pb) => match pb.transactions.get(idx) {
-            None => None,
-            Some(tx) => {
-                let block_number = Some(pb.main_header.height().into());
-                let receipt = pb.receipts.get(idx).unwrap();
-                let status = receipt.outcome_status.in_space(Space::Ethereum);
-                let contract_address = match status == EVM_SPACE_SUCCESS {
-                    true => Transaction::deployed_contract_address(&tx),
-                    false => None,
-                };
-                Some(Transaction::from_signed(
-                    &tx,
-                    (
-                        Some(pb.main_header.hash()),
-                        block_number,
-                        Some(idx.into()),
-                    ),
-                    (Some(status.into()), contract_address),
-                ))
-            }
-        },
+        Some(pb) => phantom_block_tx(&pb, idx),
     }
 }
 
-impl EthHandler {
-    fn exec_transaction(
-        &self, mut request: CallRequest,
-        block_number_or_hash: Option<BlockNumber>,
-    ) -> MazzeRpcResult<(ExecutionOutcome, EstimateExt)> {
-        let consensus_graph = self.consensus_graph();
+fn phantom_block_tx(pb: &PhantomBlock, idx: usize) -> Option<Transaction> {
+    match pb.transactions.get(idx) {
+        None => None,
+        Some(tx) => {
+            let block_number = Some(pb.main_header.height().into());
+            let receipt = pb.receipts.get(idx).unwrap();
+            let status = receipt.outcome_status.in_space(Space::Ethereum);
+            let contract_address = match status == EVM_SPACE_SUCCESS {
+                true => Transaction::deployed_contract_address(&tx),
+                false => None,
+            };
+            Some(Transaction::from_signed(
+                &tx,
+                (Some(pb.main_header.hash()), block_number, Some(idx.into())),
+                (Some(status.into()), contract_address),
+            ))
+        }
+    }
+}
 
-        let epoch = match block_number_or_hash.unwrap_or_default() {
-            BlockNumber::Hash { hash, .. } => {
-                match consensus_graph.get_block_epoch_number(&hash) {
-                    Some(e) => {
-                        // do not expose non-main blocks in eth RPC
-                        let main = consensus_graph
-                            .get_block_hashes_by_epoch(EpochNumber::Number(e))?
-                            .last()
-                            .cloned();
-
-                        if Some(hash) != main {
-                            bail!("Block {:?} not found", hash);
-                        }
-
-                        EpochNumber::Number(e)
+/// Execute an eth space call request virtually on the state of the requested
+/// block, or on the pending block.
+pub(crate) fn exec_call_request(
+    consensus_graph: &ConsensusGraph, mut request: CallRequest,
+    block_number_or_hash: Option<BlockNumber>, profile_gas: bool,
+) -> MazzeRpcResult<(ExecutionOutcome, EstimateExt)> {
+    let epoch = match block_number_or_hash.unwrap_or_default() {
+        BlockNumber::Pending => None,
+        BlockNumber::Hash { hash, .. } => {
+            match consensus_graph.get_block_epoch_number(&hash) {
+                Some(e) => {
+                    // do not expose non-main blocks in eth RPC
+                    let main = consensus_graph
+                        .get_block_hashes_by_epoch(EpochNumber::Number(e))?
+                        .last()
+                        .cloned();
+
+                    if Some(hash) != main {
+                        bail!("Block {:?} not found", hash);
                     }
-                    None => bail!("Block {:?} not found", hash),
+
+                    Some(EpochNumber::Number(e))
                 }
+                None => bail!("Block {:?} not found", hash),
             }
-            epoch => epoch.try_into()?,
-        };
+        }
+        epoch => Some(epoch.try_into()?),
+    };
 
-        // if gas_price is zero, it is considered as not set
-        request.unset_zero_gas_price();
+    // if gas_price is zero, it is considered as not set
+    request.unset_zero_gas_price();
 
-        let estimate_request = EstimateRequest {
-            has_sender: request.from.is_some(),
-            has_gas_limit: request.gas.is_some(),
-            has_gas_price: request.gas_price.is_some(),
-            has_nonce: request.nonce.is_some(),
-            has_storage_limit: false,
-        };
+    let estimate_request = EstimateRequest {
+        has_sender: request.from.is_some(),
+        has_gas_limit: request.gas.is_some(),
+        has_gas_price: request.gas_price.is_some(),
+        has_nonce: request.nonce.is_some(),
+        has_storage_limit: false,
+        profile_gas,
+    };
 
-        let chain_id = self.consensus.best_chain_id();
-        let signed_tx = sign_call(chain_id.in_evm_space(), request)?;
+    let chain_id = consensus_graph.best_chain_id();
+    let signed_tx = sign_call(chain_id.in_evm_space(), request)?;
 
-        trace!("call tx {:?}, request {:?}", signed_tx, estimate_request);
-        consensus_graph.call_virtual(&signed_tx, epoch, estimate_request)
+    trace!("call tx {:?}, request {:?}", signed_tx, estimate_request);
+    match epoch {
+        Some(epoch) => {
+            consensus_graph.call_virtual(&signed_tx, epoch, estimate_request)
+        }
+        None => consensus_graph
+            .call_virtual_on_pending_block(&signed_tx, estimate_request),
+    }
+}
+
+impl EthHandler {
+    fn exec_transaction(
+        &self, request: CallRequest, block_number_or_hash: Option<BlockNumber>,
+    ) -> MazzeRpcResult<(ExecutionOutcome, EstimateExt)> {
+        exec_call_request(
+            self.consensus_graph(),
+            request,
+            block_number_or_hash,
+            false, /* profile_gas */
+        )
+    }
+
+    /// Return the eth space view of the block `block_num`. The `pending` tag
+    /// is answered from the pending block.
+    fn phantom_block_by_number(
+        &self, block_num: BlockNumber,
+    ) -> RpcResult<Option<PhantomBlock>> {
+        if block_num == BlockNumber::Pending {
+            let pending_block = self.consensus_graph().pending_block()?;
+            return Ok(Some(pending_block.phantom_block()));
+        }
+
+        // keep read lock to ensure consistent view
+        let _inner = self.consensus_graph().inner.read();
+
+        self.consensus_graph()
+            .get_phantom_block_by_number(
+                block_num.try_into()?,
+                None,
+                false, /* include_traces */
+            )
+            .map_err(RpcError::invalid_params)
     }
 
     fn send_transaction_with_signature(
@@ -375,6 +440,7 @@ impl EthHandler {
                 .is_some()
                 .then_some(U64::from(tx.type_id())),
             burnt_gas_fee: receipt.burnt_gas_fee,
+            gas_covered_by_sponsor: receipt.gas_sponsor_paid,
         })
     }
 
@@ -512,6 +578,18 @@ impl Eth for EthHandler {
     fn balance(
         &self, address: H160, num: Option<BlockNumber>,
     ) -> jsonrpc_core::Result<U256> {
+        if let Some(BlockNumber::Pending) = num {
+            info!(
+                "RPC Request: eth_getBalance address={:?} block_num=pending",
+                address
+            );
+
+            let pending_block = self.consensus_graph().pending_block()?;
+            return Ok(pending_block
+                .balance(&address.with_evm_space())
+                .map_err(|err| MazzeRpcError::from(err))?);
+        }
+
         let epoch_num = num.unwrap_or_default().try_into()?;
 
         info!(
@@ -532,6 +610,20 @@ impl Eth for EthHandler {
     fn storage_at(
         &self, address: H160, position: U256, block_num: Option<BlockNumber>,
     ) -> jsonrpc_core::Result<H256> {
+        if let Some(BlockNumber::Pending) = block_num {
+            info!(
+                "RPC Request: eth_getStorageAt address={:?}, position={:?}, block_num=pending)",
+                address, position
+            );
+
+            let pending_block = self.consensus_graph().pending_block()?;
+            let position: H256 = H256::from_uint(&position);
+            let value = pending_block
+                .storage_at(&address.with_evm_space(), position.as_ref())
+                .map_err(|err| MazzeRpcError::from(err))?;
+            return Ok(H256::from_uint(&value));
+        }
+
         let epoch_num = block_num.unwrap_or_default().try_into()?;
 
         info!(
@@ -589,18 +681,7 @@ impl Eth for EthHandler {
     ) -> jsonrpc_core::Result<Option<RpcBlock>> {
         info!("RPC Request: eth_getBlockByNumber block_number={:?} include_txs={:?}", block_num, include_txs);
 
-        let phantom_block = {
-            // keep read lock to ensure consistent view
-            let _inner = self.consensus_graph().inner.read();
-
-            self.consensus_graph()
-                .get_phantom_block_by_number(
-                    block_num.try_into()?,
-                    None,
-                    false, /* include_traces */
-                )
-                .map_err(RpcError::invalid_params)?
-        };
+        let phantom_block = self.phantom_block_by_number(block_num)?;
 
         match phantom_block {
             None => Ok(None),
@@ -617,9 +698,11 @@ impl Eth for EthHandler {
         );
 
         let nonce = match num {
-            Some(BlockNumber::Pending) => {
-                self.tx_pool.get_next_nonce(&address.with_evm_space())
-            }
+            Some(BlockNumber::Pending) => self
+                .consensus_graph()
+                .pending_block()?
+                .nonce(&address.with_evm_space())
+                .map_err(|err| MazzeRpcError::from(err))?,
             _ => {
                 let num = num.unwrap_or_default().try_into()?;
 
@@ -667,18 +750,7 @@ impl Eth for EthHandler {
             block_num
         );
 
-        let phantom_block = {
-            // keep read lock to ensure consistent view
-            let _inner = self.consensus_graph().inner.read();
-
-            self.consensus_graph()
-                .get_phantom_block_by_number(
-                    block_num.try_into()?,
-                    None,
-                    false, /* include_traces */
-                )
-                .map_err(RpcError::invalid_params)?
-        };
+        let phantom_block = self.phantom_block_by_number(block_num)?;
 
         match phantom_block {
             None => Ok(None),
@@ -727,6 +799,19 @@ impl Eth for EthHandler {
     fn code_at(
         &self, address: H160, epoch_num: Option<BlockNumber>,
     ) -> jsonrpc_core::Result<Bytes> {
+        if let Some(BlockNumber::Pending) = epoch_num {
+            info!(
+                "RPC Request: eth_getCode address={:?} epoch_num=pending",
+                address
+            );
+
+            let pending_block = self.consensus_graph().pending_block()?;
+            let code = pending_block
+                .code(&address.with_evm_space())
+                .map_err(|err| MazzeRpcError::from(err))?;
+            return Ok(Bytes::new(code.map_or(vec![], |c| (*c).clone())));
+        }
+
         let epoch_num = epoch_num.unwrap_or_default().try_into()?;
 
         info!(
@@ -1046,6 +1131,73 @@ impl Eth for EthHandler {
         Ok(self.get_tx_from_txpool(hash))
     }
 
+    fn phantom_transactions_by_native_hash(
+        &self, hash: H256,
+    ) -> jsonrpc_core::Result<Option<Vec<Transaction>>> {
+        info!(
+            "RPC Request: eth_getPhantomTransactionsByNativeHash({:?})",
+            hash
+        );
+
+        let data_man = self.consensus.get_data_manager();
+        if !data_man.cross_space_index_enabled() {
+            return Err(RpcError::invalid_params(
+                "cross-space index not found, did you enable 'persist_cross_space_index'?",
+            ));
+        }
+
+        // The transaction is executed in at most one epoch on the current main
+        // chain, while the other indexed epochs have left it.
+        let mut main_epoch = None;
+        for epoch_hash in data_man.cross_space_epochs_by_tx_hash(&hash) {
+            let epoch_num = match data_man.block_height_by_hash(&epoch_hash) {
+                None => continue,
+                Some(n) => n,
+            };
+            let phantom_block = match self
+                .consensus_graph()
+                .get_phantom_block_by_number(
+                    EpochNumber::Number(epoch_num),
+                    None,
+                    false, /* include_traces */
+                )
+                .map_err(RpcError::invalid_params)?
+            {
+                None => continue,
+                Some(b) => b,
+            };
+            if phantom_block.main_header.hash() == epoch_hash {
+                main_epoch = Some((epoch_hash, phantom_block));
+                break;
+            }
+        }
+        let (epoch_hash, phantom_block) = match main_epoch {
+            None => return Ok(None),
+            Some(epoch) => epoch,
+        };
+
+        let phantom_hashes: HashSet<H256> = data_man
+            .cross_space_transfers_by_epoch(&epoch_hash)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|transfer| transfer.native_tx_hash == hash)
+            .map(|transfer| transfer.phantom_tx_hash)
+            .collect();
+        if phantom_hashes.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            phantom_block
+                .transactions
+                .iter()
+                .enumerate()
+                .filter(|(_, tx)| phantom_hashes.contains(&tx.hash()))
+                .filter_map(|(idx, _)| phantom_block_tx(&phantom_block, idx))
+                .collect(),
+        ))
+    }
+
     fn transaction_by_block_hash_and_index(
         &self, hash: H256, idx: Index,
     ) -> jsonrpc_core::Result<Option<Transaction>> {
@@ -1070,18 +1222,7 @@ impl Eth for EthHandler {
     ) -> jsonrpc_core::Result<Option<Transaction>> {
         info!("RPC Request: eth_getTransactionByBlockNumberAndIndex block_num={:?}, idx={:?}", block_num, idx);
 
-        let phantom_block = {
-            // keep read lock to ensure consistent view
-            let _inner = self.consensus_graph().inner.read();
-
-            self.consensus_graph()
-                .get_phantom_block_by_number(
-                    block_num.try_into()?,
-                    None,
-                    false, /* include_traces */
-                )
-                .map_err(RpcError::invalid_params)?
-        };
+        let phantom_block = self.phantom_block_by_number(block_num)?;
 
         Ok(block_tx_by_index(phantom_block, idx.value()))
     }
@@ -1179,13 +1320,34 @@ impl Eth for EthHandler {
     fn logs(&self, filter: EthRpcLogFilter) -> jsonrpc_core::Result<Vec<Log>> {
         info!("RPC Request: eth_getLogs({:?})", filter);
 
+        let include_pending = filter.includes_pending();
+        let only_pending = filter.only_pending();
+
         let filter: LogFilter =
             filter.into_primitive(self.consensus.clone())?;
 
-        let logs = self
-            .consensus_graph()
-            .logs(filter)
-            .map_err(|err| MazzeRpcError::from(err))?;
+        let mut logs = if only_pending {
+            vec![]
+        } else {
+            self.consensus_graph()
+                .logs(filter.clone())
+                .map_err(|err| MazzeRpcError::from(err))?
+                .into_iter()
+                .map(|l| {
+                    Log::try_from_localized(l, self.consensus.clone(), false)
+                })
+                .collect::<Result<_, _>>()?
+        };
+
+        if include_pending {
+            let pending_block = self.consensus_graph().pending_block()?;
+            logs.extend(
+                pending_block
+                    .logs(&filter)
+                    .into_iter()
+                    .map(Log::from_pending),
+            );
+        }
 
         // If the results does not fit into `max_limit`, report an error
         if let Some(max_limit) = self.config.get_logs_filter_max_limit {
@@ -1194,11 +1356,7 @@ impl Eth for EthHandler {
             }
         }
 
-        Ok(logs
-            .iter()
-            .cloned()
-            .map(|l| Log::try_from_localized(l, self.consensus.clone(), false))
-            .collect::<Result<_, _>>()?)
+        Ok(logs)
     }
 
     fn submit_hashrate(&self, _: U256, _: H256) -> jsonrpc_core::Result<bool> {
@@ -1218,24 +1376,18 @@ impl Eth for EthHandler {
         let block_num = block_num.unwrap_or_default();
 
         let b = {
-            // keep read lock to ensure consistent view
-            let _inner = self.consensus_graph().inner.read();
-
             let phantom_block = match block_num {
-                BlockNumber::Hash { hash, .. } => self
-                    .consensus_graph()
-                    .get_phantom_block_by_hash(
-                        &hash, false, /* include_traces */
-                    )
-                    .map_err(RpcError::invalid_params)?,
-                _ => self
-                    .consensus_graph()
-                    .get_phantom_block_by_number(
-                        block_num.try_into()?,
-                        None,
-                        false, /* include_traces */
-                    )
-                    .map_err(RpcError::invalid_params)?,
+                BlockNumber::Hash { hash, .. } => {
+                    // keep read lock to ensure consistent view
+                    let _inner = self.consensus_graph().inner.read();
+
+                    self.consensus_graph()
+                        .get_phantom_block_by_hash(
+                            &hash, false, /* include_traces */
+                        )
+                        .map_err(RpcError::invalid_params)?
+                }
+                _ => self.phantom_block_by_number(block_num)?,
             };
 
             match phantom_block {
@@ -1287,4 +1439,20 @@ impl Eth for EthHandler {
             pending_count: pending_count.into(),
         })
     }
+
+    fn transactions_by_address(
+        &self, address: H160, cursor: Option<AddressTransactionCursor>,
+        limit: Option<U64>,
+    ) -> jsonrpc_core::Result<AddressTransactionsPage> {
+        info!(
+            "RPC Request: eth_getTransactionsByAddress address={:?} cursor={:?} limit={:?}",
+            address, cursor, limit
+        );
+        Ok(address_transactions_page(
+            self.consensus_graph(),
+            &Address::from(address).with_evm_space(),
+            cursor,
+            limit,
+        )?)
+    }
 }
@@ -0,0 +1,256 @@
+use crate::rpc::{
+    error_codes::{internal_error_msg, invalid_params},
+    impls::eth::eth_handler::sign_call as sign_eth_call,
+    traits::dev::Dev,
+    types::{
+        errors::check_rpc_address_network, eth::CallRequest as EthCallRequest,
+        sign_call, Bytes, CallRequest, DevAddress, U64,
+    },
+};
+use blockgen::BlockGenerator;
+use mazze_parameters::block::MAX_TRANSACTION_COUNT_PER_BLOCK;
+use mazze_types::{
+    AddressSpaceUtil, AddressWithSpace, BigEndianHash, Space, H256, U256,
+};
+use mazzecore::{
+    consensus::DevStateChange, rpc_errors::invalid_params_check,
+    ConsensusGraph, ConsensusGraphTrait, SharedConsensusGraph,
+    SharedSynchronizationService, SharedTransactionPool,
+};
+use jsonrpc_core::Result as JsonRpcResult;
+use parking_lot::Mutex;
+use primitives::SignedTransaction;
+use std::sync::Arc;
+
+pub struct DevHandler {
+    consensus: SharedConsensusGraph,
+    sync: SharedSynchronizationService,
+    tx_pool: SharedTransactionPool,
+    block_gen: Arc<BlockGenerator>,
+    /// Serializes the epochs mined and reverted by the cheat RPCs.
+    mining: Mutex<()>,
+}
+
+impl DevHandler {
+    pub fn new(
+        consensus: SharedConsensusGraph, sync: SharedSynchronizationService,
+        tx_pool: SharedTransactionPool, block_gen: Arc<BlockGenerator>,
+    ) -> Self {
+        DevHandler {
+            consensus,
+            sync,
+            tx_pool,
+            block_gen,
+            mining: Mutex::new(()),
+        }
+    }
+
+    fn consensus_graph(&self) -> &ConsensusGraph {
+        self.consensus
+            .as_any()
+            .downcast_ref::<ConsensusGraph>()
+            .expect("downcast should succeed")
+    }
+
+    fn address(&self, address: DevAddress) -> JsonRpcResult<AddressWithSpace> {
+        match address {
+            DevAddress::Native(address) => {
+                invalid_params_check(
+                    "address",
+                    check_rpc_address_network(
+                        Some(address.network),
+                        self.sync.network.get_network_type(),
+                    ),
+                )?;
+                Ok(address.hex_address.with_native_space())
+            }
+            DevAddress::Ethereum(address) => Ok(address.with_evm_space()),
+        }
+    }
+
+    /// Mine a synthetic epoch of a single block, which applies `changes` and
+    /// packs the pending transactions, and execute it at once so its state
+    /// is the latest state.
+    fn mine_epoch(&self, changes: Vec<DevStateChange>) -> JsonRpcResult<H256> {
+        let _guard = self.mining.lock();
+        let consensus_graph = self.consensus_graph();
+        let parent_hash = self.consensus.best_block_hash();
+        if !changes.is_empty() {
+            consensus_graph.queue_dev_state_changes(parent_hash, changes);
+        }
+        // The block is processed by consensus when `generate_block` returns.
+        let generated = self.block_gen.generate_block(
+            MAX_TRANSACTION_COUNT_PER_BLOCK,
+            self.sync
+                .get_synchronization_graph()
+                .verification_config
+                .max_block_size_in_bytes,
+            vec![],
+        );
+        let on_parent = consensus_graph
+            .data_man
+            .block_header_by_hash(&generated)
+            .map_or(false, |header| *header.parent_hash() == parent_hash);
+        if !on_parent || self.consensus.best_block_hash() != generated {
+            bail!(internal_error_msg(
+                "the mined block is not the child of the best block"
+            ));
+        }
+        consensus_graph
+            .compute_main_epochs_for_dev(&generated)
+            .map_err(|e| internal_error_msg(&e))?;
+        Ok(generated)
+    }
+
+    fn commit_state_change(&self, change: DevStateChange) -> JsonRpcResult<()> {
+        info!("RPC Request: dev state change {:?}", change);
+        self.mine_epoch(vec![change])?;
+        Ok(())
+    }
+
+    fn send_impersonated_transaction(
+        &self, signed: SignedTransaction,
+    ) -> JsonRpcResult<H256> {
+        let sender = signed.sender();
+        let tx = self
+            .tx_pool
+            .insert_impersonated_transaction(signed.unsigned.clone(), sender)
+            .map_err(|e| invalid_params("tx", e))?;
+        self.mine_epoch(vec![])?;
+        Ok(tx.hash())
+    }
+
+    /// The gas and gas price limits of the pool.
+    fn default_gas_and_price(&self, space: Space) -> (U256, U256) {
+        let config = &self.tx_pool.config;
+        let gas_price = match space {
+            Space::Native => config.min_native_tx_price,
+            Space::Ethereum => config.min_eth_tx_price,
+        };
+        (*config.max_tx_gas.read(), gas_price.into())
+    }
+}
+
+impl Dev for DevHandler {
+    fn set_balance(
+        &self, address: DevAddress, balance: U256,
+    ) -> JsonRpcResult<()> {
+        let address = self.address(address)?;
+        self.commit_state_change(DevStateChange::Balance(address, balance))
+    }
+
+    fn set_code(&self, address: DevAddress, code: Bytes) -> JsonRpcResult<()> {
+        let address = self.address(address)?;
+        self.commit_state_change(DevStateChange::Code(address, code.into()))
+    }
+
+    fn set_nonce(&self, address: DevAddress, nonce: U256) -> JsonRpcResult<()> {
+        let address = self.address(address)?;
+        self.commit_state_change(DevStateChange::Nonce(address, nonce))
+    }
+
+    fn set_storage_at(
+        &self, address: DevAddress, position: H256, value: H256,
+    ) -> JsonRpcResult<()> {
+        let address = self.address(address)?;
+        self.commit_state_change(DevStateChange::Storage(
+            address,
+            position,
+            value.into_uint(),
+        ))
+    }
+
+    fn impersonate_account(&self, address: DevAddress) -> JsonRpcResult<()> {
+        let address = self.address(address)?;
+        info!("RPC Request: dev_impersonateAccount {:?}", address);
+        self.tx_pool.impersonate_sender(address);
+        Ok(())
+    }
+
+    fn stop_impersonating_account(
+        &self, address: DevAddress,
+    ) -> JsonRpcResult<()> {
+        let address = self.address(address)?;
+        info!("RPC Request: dev_stopImpersonatingAccount {:?}", address);
+        self.tx_pool.stop_impersonating_sender(&address);
+        Ok(())
+    }
+
+    fn send_transaction(
+        &self, mut request: EthCallRequest,
+    ) -> JsonRpcResult<H256> {
+        info!("RPC Request: dev_sendTransaction {:?}", request);
+        let sender = match request.from {
+            Some(from) => from.with_evm_space(),
+            None => bail!(invalid_params("from", "the sender is required")),
+        };
+        let (gas, gas_price) = self.default_gas_and_price(Space::Ethereum);
+        request.gas = request.gas.or(Some(gas));
+        request.gas_price = request.gas_price.or(Some(gas_price));
+        request.nonce = request
+            .nonce
+            .or_else(|| Some(self.tx_pool.get_next_nonce(&sender)));
+
+        let chain_id = self.consensus.best_chain_id().in_evm_space();
+        let signed = sign_eth_call(chain_id, request)?;
+        self.send_impersonated_transaction(signed)
+    }
+
+    fn send_native_transaction(
+        &self, mut request: CallRequest,
+    ) -> JsonRpcResult<H256> {
+        info!("RPC Request: dev_sendNativeTransaction {:?}", request);
+        let sender = match request.from.clone() {
+            Some(from) => self.address(DevAddress::Native(from))?,
+            None => bail!(invalid_params("from", "the sender is required")),
+        };
+        if let Some(to) = &request.to {
+            self.address(DevAddress::Native(to.clone()))?;
+        }
+        let (gas, gas_price) = self.default_gas_and_price(Space::Native);
+        request.gas = request.gas.or(Some(gas));
+        request.gas_price = request.gas_price.or(Some(gas_price));
+        request.nonce = request
+            .nonce
+            .or_else(|| Some(self.tx_pool.get_next_nonce(&sender)));
+
+        let epoch_height = self.consensus.best_epoch_number();
+        let chain_id = self.consensus.best_chain_id().in_native_space();
+        let signed = sign_call(epoch_height, chain_id, request)?;
+        self.send_impersonated_transaction(signed)
+    }
+
+    fn snapshot(&self) -> JsonRpcResult<U256> {
+        info!("RPC Request: evm_snapshot");
+        Ok(self.block_gen.snapshot())
+    }
+
+    fn revert(&self, id: U256) -> JsonRpcResult<bool> {
+        info!("RPC Request: evm_revert {}", id);
+        let _guard = self.mining.lock();
+        self.block_gen
+            .revert_to_snapshot(id)
+            .map_err(|e| internal_error_msg(&e))
+    }
+
+    fn increase_time(&self, seconds: U64) -> JsonRpcResult<i64> {
+        info!("RPC Request: evm_increaseTime {}", seconds);
+        Ok(self.block_gen.increase_time(seconds.as_u64()))
+    }
+
+    fn set_next_block_timestamp(&self, timestamp: U64) -> JsonRpcResult<()> {
+        info!("RPC Request: evm_setNextBlockTimestamp {}", timestamp);
+        self.block_gen
+            .set_next_block_timestamp(timestamp.as_u64())
+            .map_err(|e| invalid_params("timestamp", e))
+    }
+
+    fn mine(&self, timestamp: Option<U64>) -> JsonRpcResult<()> {
+        info!("RPC Request: evm_mine {:?}", timestamp);
+        if let Some(timestamp) = timestamp {
+            self.set_next_block_timestamp(timestamp)?;
+        }
+        self.mine_epoch(vec![])?;
+        Ok(())
+    }
+}
@@ -37,18 +37,22 @@ use crate::{
         traits::{mazze::Mazze, debug::LocalRpc, test::TestRpc},
         types::{
             errors::check_rpc_address_network,
+            eth::GasProfile,
             pos::{Block as PosBlock, PoSEpochReward},
             Account as RpcAccount, AccountPendingInfo,
-            AccountPendingTransactions, BlameInfo, Block as RpcBlock,
-            BlockHashOrEpochNumber, Bytes, CallRequest, MazzeFeeHistory,
-            MazzeRpcLogFilter, CheckBalanceAgainstTransactionResponse,
-            ConsensusGraphStates, EpochNumber,
+            AccountPendingTransactions, AccountRangePage,
+            AddressTransactionCursor, AddressTransactionsPage, BlameInfo,
+            Block as RpcBlock, BlockHashOrEpochNumber, Bytes, CallRequest,
+            MazzeFeeHistory, MazzeRpcLogFilter,
+            CheckBalanceAgainstTransactionResponse,
+            ConsensusGraphStates, CrossSpaceTransfer as RpcCrossSpaceTransfer,
+            CrossSpaceTransferFilter, EpochNumber,
             EstimateGasAndCollateralResponse, FeeHistory, Log as RpcLog,
             PoSEconomics, Receipt as RpcReceipt, RewardInfo as RpcRewardInfo,
-            RpcAddress, SendTxRequest, SponsorInfo, StatOnGasLoad,
-            Status as RpcStatus, StorageCollateralInfo, SyncGraphStates,
-            TokenSupplyInfo, Transaction as RpcTransaction, VoteParamsInfo,
-            WrapTransaction, U64 as HexU64,
+            RpcAddress, SendTxRequest, SponsorInfo, StatOnGasLoad, StateStats,
+            Status as RpcStatus, StorageCollateralInfo, StorageRangePage,
+            SyncGraphStates, TokenSupplyInfo, Transaction as RpcTransaction,
+            VoteParamsInfo, WrapTransaction, U64 as HexU64,
         },
         RpcBoxFuture, RpcResult,
     },
@@ -1262,6 +1266,8 @@ impl Mazze for MazzeHandler {
         fn get_vote_params(&self, epoch_num: Option<EpochNumber>) -> JsonRpcResult<VoteParamsInfo>;
         fn get_pos_reward_by_epoch(&self, epoch: EpochNumber) -> JsonRpcResult<Option<PoSEpochReward>>;
         fn get_fee_burnt(&self, epoch: Option<EpochNumber>) -> JsonRpcResult<U256>;
+        fn get_cross_space_transfers(&self, filter: CrossSpaceTransferFilter) -> JsonRpcResult<Vec<RpcCrossSpaceTransfer>>;
+        fn get_transactions_by_address(&self, address: RpcAddress, cursor: Option<AddressTransactionCursor>, limit: Option<U64>) -> JsonRpcResult<AddressTransactionsPage>;
         fn max_priority_fee_per_gas(&self) -> BoxFuture<U256>;
     }
 }
@@ -1374,5 +1380,10 @@ impl LocalRpc for DebugRpcImpl {
         fn sync_graph_state(&self) -> JsonRpcResult<SyncGraphStates>;
         fn transactions_by_epoch(&self, epoch_number: U64) -> JsonRpcResult<Vec<WrapTransaction>>;
         fn transactions_by_block(&self, block_hash: H256) -> JsonRpcResult<Vec<WrapTransaction>>;
+        fn storage_range(&self, address: RpcAddress, epoch_number: Option<EpochNumber>, start_key: Option<H256>, limit: Option<U64>) -> JsonRpcResult<StorageRangePage>;
+        fn account_range(&self, epoch_number: Option<EpochNumber>, start_address: Option<RpcAddress>, limit: Option<U64>) -> JsonRpcResult<AccountRangePage>;
+        fn state_stats(&self, epoch_number: Option<U64>) -> JsonRpcResult<Option<StateStats>>;
+        fn transaction_gas_profile(&self, tx_hash: H256) -> JsonRpcResult<GasProfile>;
+        fn call_gas_profile(&self, request: CallRequest, epoch_number: Option<EpochNumber>) -> JsonRpcResult<GasProfile>;
     }
 }
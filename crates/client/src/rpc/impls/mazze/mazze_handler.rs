@@ -10,7 +10,10 @@ use crate::rpc::{
     },
 };
 use blockgen::BlockGenerator;
-use mazze_execute_helper::estimation::{decode_error, EstimateExt};
+use mazze_execute_helper::{
+    estimation::{decode_error, EstimateExt},
+    observer::gas_profiler::GasProfileKey,
+};
 use mazze_executor::{
     executive::{ExecutionError, ExecutionOutcome, TxDropError},
     internal_contract::storage_point_prop,
@@ -24,8 +27,8 @@ use mazze_statedb::{
     StateDbExt,
 };
 use mazze_types::{
-    Address, AddressSpaceUtil, BigEndianHash, Space, H256, H520, U128, U256,
-    U64,
+    Address, AddressSpaceUtil, AddressWithSpace, BigEndianHash, Space, H256,
+    H520, U128, U256, U64,
 };
 use mazze_vm_types::Error as VmError;
 use mazzecore::{
@@ -71,16 +74,20 @@ use crate::{
         },
         traits::{mazze::Mazze, debug::LocalRpc, test::TestRpc},
         types::{
-            eth::Transaction as EthTransaction, pos::Block as PosBlock,
+            eth::{GasProfile, Transaction as EthTransaction},
+            pos::Block as PosBlock,
             sign_call, Account as RpcAccount, AccountPendingInfo,
-            AccountPendingTransactions, BlameInfo, Block as RpcBlock,
-            BlockHashOrEpochNumber, Bytes, CallRequest, MazzeRpcLogFilter,
+            AccountPendingTransactions, AccountRangePage, AddressTransaction,
+            AddressTransactionCursor, AddressTransactionsPage, BlameInfo,
+            Block as RpcBlock, BlockHashOrEpochNumber, Bytes, CallRequest,
             CheckBalanceAgainstTransactionResponse, ConsensusGraphStates,
-            EpochNumber, EstimateGasAndCollateralResponse, Log as RpcLog,
+            CrossSpaceTransfer as RpcCrossSpaceTransfer,
+            CrossSpaceTransferFilter, EpochNumber,
+            EstimateGasAndCollateralResponse, Log as RpcLog, MazzeRpcLogFilter,
             PackedOrExecuted, Receipt as RpcReceipt,
-            RewardInfo as RpcRewardInfo, SendTxRequest, Status as RpcStatus,
-            StorageCollateralInfo, SyncGraphStates,
-            Transaction as RpcTransaction,
+            RewardInfo as RpcRewardInfo, SendTxRequest, StateStats,
+            Status as RpcStatus, StorageCollateralInfo, StorageEntry,
+            StorageRangePage, SyncGraphStates, Transaction as RpcTransaction,
         },
         RpcResult,
     },
@@ -107,6 +114,80 @@ use primitives::transaction::{
 };
 use serde::Serialize;
 
+/// The maximum number of epochs searched by `mazze_getCrossSpaceTransfers`.
+const MAX_CROSS_SPACE_TRANSFER_EPOCHS: u64 = 1000;
+
+/// The default and the maximum page size of `mazze_getTransactionsByAddress`
+/// and `eth_getTransactionsByAddress`.
+const DEFAULT_ADDRESS_TRANSACTIONS_LIMIT: u64 = 100;
+const MAX_ADDRESS_TRANSACTIONS_LIMIT: u64 = 1000;
+
+/// The default and the maximum page size of `debug_getStorageRange` and
+/// `debug_getAccountRange`.
+const DEFAULT_STATE_RANGE_LIMIT: u64 = 100;
+const MAX_STATE_RANGE_LIMIT: u64 = 1000;
+
+/// Returns a page of the transactions of an address in either space for
+/// `mazze_getTransactionsByAddress` and `eth_getTransactionsByAddress`.
+pub(crate) fn address_transactions_page(
+    consensus_graph: &ConsensusGraph, address: &AddressWithSpace,
+    cursor: Option<AddressTransactionCursor>, limit: Option<U64>,
+) -> RpcResult<AddressTransactionsPage> {
+    let data_man = consensus_graph.get_data_manager();
+    if !data_man.address_tx_index_enabled() {
+        bail!(invalid_params_msg(
+            "address transaction index not found, did you enable 'persist_address_tx_index'?"
+        ));
+    }
+    let limit =
+        limit.map_or(DEFAULT_ADDRESS_TRANSACTIONS_LIMIT, |l| l.as_u64());
+    if limit == 0 || limit > MAX_ADDRESS_TRANSACTIONS_LIMIT {
+        bail!(invalid_params(
+            "limit",
+            format!(
+                "limit should be in [1, {}]",
+                MAX_ADDRESS_TRANSACTIONS_LIMIT
+            )
+        ));
+    }
+
+    let entries = data_man.address_transactions(
+        address,
+        cursor.map(|c| (c.epoch_number.as_u64(), c.index.as_u64())),
+        limit as usize,
+    );
+    // The cursor follows the last entry read, even if it is skipped.
+    let next_cursor = match entries.last() {
+        Some((entry, _)) if entries.len() == limit as usize => {
+            Some(AddressTransactionCursor::from(entry))
+        }
+        _ => None,
+    };
+
+    let mut main_hashes = BTreeMap::new();
+    let mut transactions = vec![];
+    for (entry, epoch_hash) in entries {
+        // Skip the entries of the epochs which have left the main chain
+        // after a reorg and are not indexed again yet.
+        let main_hash =
+            *main_hashes.entry(entry.epoch_number).or_insert_with(|| {
+                consensus_graph
+                    .get_hash_from_epoch_number(
+                        primitives::EpochNumber::Number(entry.epoch_number),
+                    )
+                    .ok()
+            });
+        if main_hash == Some(epoch_hash) {
+            transactions.push(AddressTransaction::from(entry));
+        }
+    }
+
+    Ok(AddressTransactionsPage {
+        transactions,
+        next_cursor,
+    })
+}
+
 #[derive(Debug)]
 pub(crate) struct BlockExecInfo {
     pub(crate) block_receipts: Arc<BlockReceipts>,
@@ -120,7 +201,7 @@ pub struct RpcImpl {
     pub config: RpcImplConfiguration,
     pub consensus: SharedConsensusGraph,
     pub sync: SharedSynchronizationService,
-    block_gen: Arc<BlockGenerator>,
+    pub block_gen: Arc<BlockGenerator>,
     pub tx_pool: SharedTransactionPool,
     maybe_txgen: Option<Arc<TransactionGenerator>>,
     maybe_direct_txgen: Option<Arc<Mutex<DirectTransactionGenerator>>>,
@@ -1124,6 +1205,11 @@ impl RpcImpl {
                 )) if tx_data_len > 0 => {
                     unsigned.data = vec![0; tx_data_len];
                 }
+                Transaction::Ethereum(EthereumTransaction::Eip7702(
+                    ref mut unsigned,
+                )) if tx_data_len > 0 => {
+                    unsigned.data = vec![0; tx_data_len];
+                }
                 _ => {}
             };
 
@@ -1259,8 +1345,8 @@ impl RpcImpl {
         let epoch = Some(
             self.get_epoch_number_with_main_check(block_hash_or_epoch_number)?,
         );
-        let (execution_outcome, _estimation) =
-            self.exec_transaction(request, epoch)?;
+        let (execution_outcome, _estimation) = self
+            .exec_transaction(request, epoch, false /* profile_gas */)?;
         match execution_outcome {
             ExecutionOutcome::NotExecutedDrop(TxDropError::OldNonce(
                 expected,
@@ -1310,8 +1396,8 @@ impl RpcImpl {
         info!(
             "RPC Request: mazze_estimateGasAndCollateral request={:?}, epoch={:?}",request,epoch
         );
-        let (execution_outcome, estimation) =
-            self.exec_transaction(request, epoch)?;
+        let (execution_outcome, estimation) = self
+            .exec_transaction(request, epoch, false /* profile_gas */)?;
         match execution_outcome {
             ExecutionOutcome::NotExecutedDrop(TxDropError::OldNonce(
                 expected,
@@ -1427,6 +1513,7 @@ impl RpcImpl {
 
     fn exec_transaction(
         &self, request: CallRequest, epoch: Option<EpochNumber>,
+        profile_gas: bool,
     ) -> RpcResult<(ExecutionOutcome, EstimateExt)> {
         let rpc_request_network = invalid_params_check(
             "request",
@@ -1453,6 +1540,7 @@ impl RpcImpl {
                 || request.max_priority_fee_per_gas.is_some(),
             has_nonce: request.nonce.is_some(),
             has_storage_limit: request.storage_limit.is_some(),
+            profile_gas,
         };
 
         let epoch_height = consensus_graph
@@ -1621,6 +1709,122 @@ impl RpcImpl {
         Ok(state_db.get_global_param::<TotalBurnt1559>()?)
     }
 
+    fn get_cross_space_transfers(
+        &self, filter: CrossSpaceTransferFilter,
+    ) -> RpcResult<Vec<RpcCrossSpaceTransfer>> {
+        info!("RPC Request: mazze_getCrossSpaceTransfers({:?})", filter);
+
+        let consensus_graph = self.consensus_graph();
+        let data_man = consensus_graph.get_data_manager();
+        if !data_man.cross_space_index_enabled() {
+            bail!(invalid_params_msg(
+                "cross-space index not found, did you enable 'persist_cross_space_index'?"
+            ));
+        }
+
+        // The epochs to search, which are (epoch number, epoch hash) on the
+        // current main chain.
+        let mut epochs = BTreeMap::new();
+        match &filter.transaction_hashes {
+            Some(hashes)
+                if filter.from_epoch.is_none() && filter.to_epoch.is_none() =>
+            {
+                for hash in hashes {
+                    for epoch_hash in
+                        data_man.cross_space_epochs_by_tx_hash(hash)
+                    {
+                        let epoch_number =
+                            match data_man.block_height_by_hash(&epoch_hash) {
+                                Some(epoch_number) => epoch_number,
+                                None => continue,
+                            };
+                        // The epoch may have left the main chain.
+                        if consensus_graph
+                            .get_hash_from_epoch_number(
+                                primitives::EpochNumber::Number(epoch_number),
+                            )
+                            .ok()
+                            == Some(epoch_hash)
+                        {
+                            epochs.insert(epoch_number, epoch_hash);
+                        }
+                    }
+                }
+            }
+            _ => {
+                let from_epoch = consensus_graph.get_height_from_epoch_number(
+                    filter
+                        .from_epoch
+                        .clone()
+                        .unwrap_or(EpochNumber::LatestState)
+                        .into(),
+                )?;
+                let to_epoch = consensus_graph.get_height_from_epoch_number(
+                    filter
+                        .to_epoch
+                        .clone()
+                        .unwrap_or(EpochNumber::LatestState)
+                        .into(),
+                )?;
+                if from_epoch > to_epoch {
+                    bail!(invalid_params(
+                        "filter",
+                        "fromEpoch should not be greater than toEpoch"
+                    ));
+                }
+                if to_epoch - from_epoch >= MAX_CROSS_SPACE_TRANSFER_EPOCHS {
+                    bail!(invalid_params(
+                        "filter",
+                        format!(
+                            "the epoch range should not exceed {}",
+                            MAX_CROSS_SPACE_TRANSFER_EPOCHS
+                        )
+                    ));
+                }
+                for epoch_number in from_epoch..=to_epoch {
+                    let epoch_hash = consensus_graph
+                        .get_hash_from_epoch_number(
+                            primitives::EpochNumber::Number(epoch_number),
+                        )?;
+                    epochs.insert(epoch_number, epoch_hash);
+                }
+            }
+        }
+
+        let mut transfers = vec![];
+        for (epoch_number, epoch_hash) in epochs {
+            for transfer in data_man
+                .cross_space_transfers_by_epoch(&epoch_hash)
+                .unwrap_or_default()
+            {
+                if filter.matches(&transfer) {
+                    transfers.push(RpcCrossSpaceTransfer::new(
+                        transfer,
+                        epoch_number,
+                    ));
+                }
+            }
+        }
+        Ok(transfers)
+    }
+
+    fn get_transactions_by_address(
+        &self, address: RpcAddress, cursor: Option<AddressTransactionCursor>,
+        limit: Option<U64>,
+    ) -> RpcResult<AddressTransactionsPage> {
+        self.check_address_network(address.network)?;
+        info!(
+            "RPC Request: mazze_getTransactionsByAddress address={:?} cursor={:?} limit={:?}",
+            address, cursor, limit
+        );
+        address_transactions_page(
+            self.consensus_graph(),
+            &address.hex_address.with_native_space(),
+            cursor,
+            limit,
+        )
+    }
+
     pub fn set_db_crash(
         &self, crash_probability: f64, crash_exit_code: i32,
     ) -> RpcResult<()> {
@@ -2063,6 +2267,178 @@ impl RpcImpl {
         self.get_transactions(&block, main, epoch_number)
     }
 
+    fn state_range_limit(limit: Option<U64>) -> RpcResult<usize> {
+        let limit = limit.map_or(DEFAULT_STATE_RANGE_LIMIT, |l| l.as_u64());
+        if limit == 0 || limit > MAX_STATE_RANGE_LIMIT {
+            bail!(invalid_params(
+                "limit",
+                format!("limit should be in [1, {}]", MAX_STATE_RANGE_LIMIT)
+            ));
+        }
+        Ok(limit as usize)
+    }
+
+    fn storage_range(
+        &self, address: RpcAddress, epoch_number: Option<EpochNumber>,
+        start_key: Option<H256>, limit: Option<U64>,
+    ) -> RpcResult<StorageRangePage> {
+        self.check_address_network(address.network)?;
+        let epoch_number =
+            epoch_number.unwrap_or(EpochNumber::LatestState).into();
+        info!(
+            "RPC Request: debug_getStorageRange address={:?} epoch_number={:?} start_key={:?} limit={:?}",
+            address, epoch_number, start_key, limit
+        );
+        let limit = Self::state_range_limit(limit)?;
+
+        let mut state_db = self
+            .consensus
+            .get_state_db_by_epoch_number(epoch_number, "epoch_number")?;
+        let (entries, next_key) = state_db.get_storage_range(
+            &address.hex_address.with_native_space(),
+            start_key.unwrap_or_default().as_bytes(),
+            limit,
+        )?;
+        let storage = entries
+            .into_iter()
+            .map(|(key, value)| {
+                StorageEntry::try_from(&key, value, address.network)
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(StorageRangePage {
+            storage,
+            next_key: next_key.map(|key| H256::from_slice(&key)),
+        })
+    }
+
+    fn transaction_gas_profile(&self, tx_hash: H256) -> RpcResult<GasProfile> {
+        info!(
+            "RPC Request: debug_getTransactionGasProfile tx_hash={:?}",
+            tx_hash
+        );
+        let tx_index = self
+            .consensus
+            .get_data_manager()
+            .transaction_index_by_hash(&tx_hash, false /* update_cache */)
+            .ok_or_else(|| {
+                invalid_params("tx_hash", "transaction not found")
+            })?;
+        let epoch_number = self
+            .consensus
+            .get_block_epoch_number(&tx_index.block_hash)
+            .ok_or_else(|| {
+                invalid_params("tx_hash", "transaction is not executed")
+            })?;
+
+        let profile = self
+            .consensus_graph()
+            .collect_tx_gas_profile(epoch_number, tx_hash)?
+            .ok_or_else(|| {
+                invalid_params("tx_hash", "transaction is not executed")
+            })?;
+        Ok(profile.into())
+    }
+
+    fn call_gas_profile(
+        &self, request: CallRequest, epoch: Option<EpochNumber>,
+    ) -> RpcResult<GasProfile> {
+        info!(
+            "RPC Request: debug_getCallGasProfile request={:?} epoch={:?}",
+            request, epoch
+        );
+        let (execution_outcome, _estimation) =
+            self.exec_transaction(request, epoch, true /* profile_gas */)?;
+
+        let profile = execution_outcome
+            .try_as_executed()
+            .and_then(|executed| executed.ext_result.get::<GasProfileKey>())
+            .cloned()
+            .ok_or_else(|| {
+                call_execution_error(
+                    "Transaction can not be executed".into(),
+                    execution_outcome.error_message(),
+                )
+            })?;
+        Ok(profile.into())
+    }
+
+    fn account_range(
+        &self, epoch_number: Option<EpochNumber>,
+        start_address: Option<RpcAddress>, limit: Option<U64>,
+    ) -> RpcResult<AccountRangePage> {
+        if let Some(address) = &start_address {
+            self.check_address_network(address.network)?;
+        }
+        let epoch_number =
+            epoch_number.unwrap_or(EpochNumber::LatestState).into();
+        info!(
+            "RPC Request: debug_getAccountRange epoch_number={:?} start_address={:?} limit={:?}",
+            epoch_number, start_address, limit
+        );
+        let limit = Self::state_range_limit(limit)?;
+        let network = *self.sync.network.get_network_type();
+
+        let mut state_db = self
+            .consensus
+            .get_state_db_by_epoch_number(epoch_number, "epoch_number")?;
+        let (accounts, next_address) = state_db.get_account_range(
+            Space::Native,
+            &start_address.map_or(Address::zero(), |a| a.hex_address),
+            limit,
+        )?;
+        let accounts = accounts
+            .into_iter()
+            .map(|account| RpcAccount::try_from(account, network))
+            .collect::<Result<_, _>>()?;
+        let next_address = match next_address {
+            Some(address) => Some(RpcAddress::try_from_h160(address, network)?),
+            None => None,
+        };
+
+        Ok(AccountRangePage {
+            accounts,
+            next_address,
+        })
+    }
+
+    fn state_stats(
+        &self, epoch_number: Option<U64>,
+    ) -> RpcResult<Option<StateStats>> {
+        info!(
+            "RPC Request: debug_getStateStats epoch_number={:?}",
+            epoch_number
+        );
+        let data_man = self.consensus.get_data_manager();
+        let stats = match epoch_number {
+            Some(epoch_number) => {
+                let snapshot_epoch_count = data_man
+                    .storage_manager
+                    .get_storage_manager()
+                    .get_snapshot_epoch_count()
+                    as u64;
+                if epoch_number.as_u64() % snapshot_epoch_count != 0 {
+                    bail!(invalid_params(
+                        "epoch_number",
+                        format!(
+                            "epoch_number should be a multiple of the snapshot epoch count {}",
+                            snapshot_epoch_count
+                        )
+                    ));
+                }
+                data_man.state_stats_by_height(epoch_number.as_u64())
+            }
+            None => data_man.latest_state_stats(),
+        };
+        match stats {
+            Some(stats) => Ok(Some(StateStats::try_from(
+                stats,
+                *self.sync.network.get_network_type(),
+            )?)),
+            None => Ok(None),
+        }
+    }
+
     fn get_transactions(
         &self, blocks: &Vec<Arc<Block>>, main: &Arc<Block>, epoch_number: u64,
     ) -> JsonRpcResult<Vec<WrapTransaction>> {
@@ -2322,6 +2698,8 @@ impl Mazze for MazzeHandler {
             fn get_collateral_info(&self, epoch_num: Option<EpochNumber>) -> JsonRpcResult<StorageCollateralInfo>;
             fn get_vote_params(&self, epoch_num: Option<EpochNumber>) -> JsonRpcResult<VoteParamsInfo>;
             fn get_fee_burnt(&self, epoch_num: Option<EpochNumber>) -> JsonRpcResult<U256>;
+            fn get_cross_space_transfers(&self, filter: CrossSpaceTransferFilter) -> JsonRpcResult<Vec<RpcCrossSpaceTransfer>>;
+            fn get_transactions_by_address(&self, address: RpcAddress, cursor: Option<AddressTransactionCursor>, limit: Option<U64>) -> JsonRpcResult<AddressTransactionsPage>;
         }
     }
 }
@@ -2446,6 +2824,11 @@ impl LocalRpc for LocalRpcImpl {
             fn sign_transaction(&self, tx: SendTxRequest, password: Option<String>) -> JsonRpcResult<String>;
             fn transactions_by_epoch(&self, epoch_number: U64) -> JsonRpcResult<Vec<WrapTransaction>>;
             fn transactions_by_block(&self, block_hash: H256) -> JsonRpcResult<Vec<WrapTransaction>>;
+            fn storage_range(&self, address: RpcAddress, epoch_number: Option<EpochNumber>, start_key: Option<H256>, limit: Option<U64>) -> JsonRpcResult<StorageRangePage>;
+            fn account_range(&self, epoch_number: Option<EpochNumber>, start_address: Option<RpcAddress>, limit: Option<U64>) -> JsonRpcResult<AccountRangePage>;
+            fn state_stats(&self, epoch_number: Option<U64>) -> JsonRpcResult<Option<StateStats>>;
+            fn transaction_gas_profile(&self, tx_hash: H256) -> JsonRpcResult<GasProfile>;
+            fn call_gas_profile(&self, request: CallRequest, epoch: Option<EpochNumber>) -> JsonRpcResult<GasProfile>;
         }
     }
 }
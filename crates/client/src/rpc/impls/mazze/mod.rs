@@ -1,3 +1,4 @@
+pub mod dev;
 pub mod mazze_filter;
 pub mod mazze_handler;
 pub mod common;
@@ -254,6 +254,7 @@ impl PosHandler {
             has_gas_price: request.gas_price.is_some(),
             has_nonce: request.nonce.is_some(),
             has_storage_limit: request.storage_limit.is_some(),
+            profile_gas: false,
         };
 
         let epoch_height = consensus_graph
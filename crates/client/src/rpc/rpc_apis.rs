@@ -18,6 +18,7 @@ pub enum Api {
     Pos,
     EthPubsub,
     EthDebug,
+    Dev,
 }
 
 impl FromStr for Api {
@@ -36,6 +37,7 @@ impl FromStr for Api {
             "pos" => Ok(Pos),
             "ethpubsub" => Ok(EthPubsub),
             "ethdebug" => Ok(EthDebug),
+            "dev" => Ok(Dev),
             _ => Err("Unknown api type".into()),
         }
     }
@@ -54,6 +56,7 @@ impl Display for Api {
             Api::Pos => write!(f, "pos"),
             Api::EthPubsub => write!(f, "ethpubsub"),
             Api::EthDebug => write!(f, "ethdebug"),
+            Api::Dev => write!(f, "dev"),
         }
     }
 }
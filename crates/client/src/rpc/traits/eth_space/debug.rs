@@ -1,9 +1,15 @@
-use crate::rpc::types::eth::{BlockNumber, CallRequest};
+use crate::rpc::types::{
+    eth::{
+        AccountRangeResult, BlockNumber, CallRequest, GasProfile,
+        StorageRangeResult,
+    },
+    Bytes,
+};
 use alloy_rpc_types_trace::geth::{
     GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace,
     TraceResult,
 };
-use mazze_types::H256;
+use mazze_types::{H160, H256};
 use jsonrpc_core::Result as JsonRpcResult;
 use jsonrpc_derive::rpc;
 
@@ -38,4 +44,38 @@ pub trait Debug {
         &self, request: CallRequest, block_number: Option<BlockNumber>,
         opts: Option<GethDebugTracingCallOptions>,
     ) -> JsonRpcResult<GethTrace>;
+
+    /// Execute the transaction again and return the gas it used by contract,
+    /// function, call depth and opcode, and as folded call stacks for the
+    /// flame graph tools.
+    #[rpc(name = "debug_profileTransaction")]
+    fn debug_profile_transaction(
+        &self, tx_hash: H256,
+    ) -> JsonRpcResult<GasProfile>;
+
+    /// Execute a call like `eth_call`, and return the gas it used like
+    /// `debug_profileTransaction`.
+    #[rpc(name = "debug_profileCall")]
+    fn debug_profile_call(
+        &self, request: CallRequest, block_number: Option<BlockNumber>,
+    ) -> JsonRpcResult<GasProfile>;
+
+    /// Returns the storage of the contract at `address` in the order of the
+    /// storage keys, starting from `key_start`. The state is committed once
+    /// per epoch, so the storage is read from the state after the epoch of
+    /// `block_hash` is executed, and `tx_index` is ignored.
+    #[rpc(name = "debug_storageRangeAt")]
+    fn debug_storage_range_at(
+        &self, block_hash: H256, tx_index: usize, address: H160,
+        key_start: Bytes, max_result: usize,
+    ) -> JsonRpcResult<StorageRangeResult>;
+
+    /// Returns the accounts in the order of their addresses, starting from
+    /// `start`.
+    #[rpc(name = "debug_accountRange")]
+    fn debug_account_range(
+        &self, block_number: BlockNumber, start: Bytes, max_results: usize,
+        no_code: Option<bool>, no_storage: Option<bool>,
+        incompletes: Option<bool>,
+    ) -> JsonRpcResult<AccountRangeResult>;
 }
@@ -27,7 +27,8 @@ use crate::rpc::types::{
         AccountPendingTransactions, Block, BlockNumber, CallRequest,
         EthRpcLogFilter, FilterChanges, Log, Receipt, SyncStatus, Transaction,
     },
-    Bytes, FeeHistory, Index,
+    AddressTransactionCursor, AddressTransactionsPage, Bytes, FeeHistory,
+    Index,
 };
 
 /// Eth rpc interface.
@@ -179,6 +180,13 @@ pub trait Eth {
         &self, transaction_hash: H256,
     ) -> Result<Option<Transaction>>;
 
+    /// Returns the phantom transactions of the cross-space calls and
+    /// withdrawals in a native transaction.
+    #[rpc(name = "eth_getPhantomTransactionsByNativeHash")]
+    fn phantom_transactions_by_native_hash(
+        &self, native_transaction_hash: H256,
+    ) -> Result<Option<Vec<Transaction>>>;
+
     /// Returns transaction at given block hash and index.
     #[rpc(name = "eth_getTransactionByBlockHashAndIndex")]
     fn transaction_by_block_hash_and_index(
@@ -255,6 +263,14 @@ pub trait Eth {
         &self, address: H160, maybe_start_nonce: Option<U256>,
         maybe_limit: Option<U64>,
     ) -> Result<AccountPendingTransactions>;
+
+    /// Returns the transactions sent or received by an eth space address,
+    /// from the newest to the oldest, starting after `cursor`.
+    #[rpc(name = "eth_getTransactionsByAddress")]
+    fn transactions_by_address(
+        &self, address: H160, cursor: Option<AddressTransactionCursor>,
+        limit: Option<U64>,
+    ) -> Result<AddressTransactionsPage>;
 }
 
 /// Eth filters rpc api (polling).
@@ -1,10 +1,11 @@
 
 
 use crate::rpc::types::{
-    BlockHashOrEpochNumber, Bytes as RpcBytes, ConsensusGraphStates,
-    EpochNumber, Receipt as RpcReceipt, RpcAddress, SendTxRequest,
-    StatOnGasLoad, SyncGraphStates, Transaction as RpcTransaction,
-    WrapTransaction,
+    eth::GasProfile, AccountRangePage, BlockHashOrEpochNumber,
+    Bytes as RpcBytes, CallRequest, ConsensusGraphStates, EpochNumber,
+    Receipt as RpcReceipt, RpcAddress, SendTxRequest, StatOnGasLoad,
+    StateStats, StorageRangePage, SyncGraphStates,
+    Transaction as RpcTransaction, WrapTransaction,
 };
 use mazze_types::{H256, H520, U128, U64};
 use mazzecore::verification::EpochReceiptProof;
@@ -130,4 +131,42 @@ pub trait LocalRpc {
     fn transactions_by_block(
         &self, block_hash: H256,
     ) -> JsonRpcResult<Vec<WrapTransaction>>;
+
+    /// Returns the storage of a contract in the order of the storage keys,
+    /// starting from `start_key`.
+    #[rpc(name = "debug_getStorageRange")]
+    fn storage_range(
+        &self, address: RpcAddress, epoch_number: Option<EpochNumber>,
+        start_key: Option<H256>, limit: Option<U64>,
+    ) -> JsonRpcResult<StorageRangePage>;
+
+    /// Returns the accounts in the order of the addresses, starting from
+    /// `start_address`.
+    #[rpc(name = "debug_getAccountRange")]
+    fn account_range(
+        &self, epoch_number: Option<EpochNumber>,
+        start_address: Option<RpcAddress>, limit: Option<U64>,
+    ) -> JsonRpcResult<AccountRangePage>;
+
+    /// Returns the state statistics collected from the snapshot of an epoch,
+    /// or the latest collected ones if `epoch_number` is omitted.
+    #[rpc(name = "debug_getStateStats")]
+    fn state_stats(
+        &self, epoch_number: Option<U64>,
+    ) -> JsonRpcResult<Option<StateStats>>;
+
+    /// Profiles the gas used by an executed transaction of either space by
+    /// contract, function, call depth and opcode. The addresses are in hex,
+    /// as in the folded call stacks.
+    #[rpc(name = "debug_getTransactionGasProfile")]
+    fn transaction_gas_profile(
+        &self, tx_hash: H256,
+    ) -> JsonRpcResult<GasProfile>;
+
+    /// Profiles the gas used by a call executed on the state of
+    /// `epoch_number`, like `debug_getTransactionGasProfile`.
+    #[rpc(name = "debug_getCallGasProfile")]
+    fn call_gas_profile(
+        &self, request: CallRequest, epoch_number: Option<EpochNumber>,
+    ) -> JsonRpcResult<GasProfile>;
 }
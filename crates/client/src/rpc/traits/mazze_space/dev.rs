@@ -0,0 +1,89 @@
+use crate::rpc::types::{
+    eth::CallRequest as EthCallRequest, Bytes, CallRequest, DevAddress, U64,
+};
+use mazze_types::{H256, U256};
+use jsonrpc_core::Result as JsonRpcResult;
+use jsonrpc_derive::rpc;
+
+/// Dev mode cheat RPCs. The state changes are committed by mining a single
+/// block, whose epoch is executed at once, so they are in the latest state
+/// when a call returns.
+#[rpc(server)]
+pub trait Dev {
+    #[rpc(
+        name = "dev_setBalance",
+        alias("anvil_setBalance", "hardhat_setBalance")
+    )]
+    fn set_balance(
+        &self, address: DevAddress, balance: U256,
+    ) -> JsonRpcResult<()>;
+
+    /// Empty code clears the code of the account.
+    #[rpc(name = "dev_setCode", alias("anvil_setCode", "hardhat_setCode"))]
+    fn set_code(&self, address: DevAddress, code: Bytes) -> JsonRpcResult<()>;
+
+    #[rpc(name = "dev_setNonce", alias("anvil_setNonce", "hardhat_setNonce"))]
+    fn set_nonce(&self, address: DevAddress, nonce: U256) -> JsonRpcResult<()>;
+
+    /// The storage collateral of the slot is not settled.
+    #[rpc(
+        name = "dev_setStorageAt",
+        alias("anvil_setStorageAt", "hardhat_setStorageAt")
+    )]
+    fn set_storage_at(
+        &self, address: DevAddress, position: H256, value: H256,
+    ) -> JsonRpcResult<()>;
+
+    /// Accept the unsigned transactions of `address`, sent by
+    /// `dev_sendTransaction` or `dev_sendNativeTransaction`.
+    #[rpc(
+        name = "dev_impersonateAccount",
+        alias("anvil_impersonateAccount", "hardhat_impersonateAccount")
+    )]
+    fn impersonate_account(&self, address: DevAddress) -> JsonRpcResult<()>;
+
+    #[rpc(
+        name = "dev_stopImpersonatingAccount",
+        alias(
+            "anvil_stopImpersonatingAccount",
+            "hardhat_stopImpersonatingAccount"
+        )
+    )]
+    fn stop_impersonating_account(
+        &self, address: DevAddress,
+    ) -> JsonRpcResult<()>;
+
+    /// Send an eth space transaction of an impersonated account, and mine it.
+    #[rpc(name = "dev_sendTransaction")]
+    fn send_transaction(&self, request: EthCallRequest) -> JsonRpcResult<H256>;
+
+    /// Send a native space transaction of an impersonated account, and mine
+    /// it.
+    #[rpc(name = "dev_sendNativeTransaction")]
+    fn send_native_transaction(
+        &self, request: CallRequest,
+    ) -> JsonRpcResult<H256>;
+
+    /// Take a snapshot of the latest state, and return its id.
+    #[rpc(name = "evm_snapshot")]
+    fn snapshot(&self) -> JsonRpcResult<U256>;
+
+    /// Revert the chain to a snapshot, and remove the snapshot and the ones
+    /// taken after it. The blocks after the snapshot are removed, and their
+    /// transactions are returned to the transaction pool.
+    #[rpc(name = "evm_revert")]
+    fn revert(&self, id: U256) -> JsonRpcResult<bool>;
+
+    /// Add `seconds` to the timestamps of the next blocks, and return the
+    /// total offset in seconds.
+    #[rpc(name = "evm_increaseTime")]
+    fn increase_time(&self, seconds: U64) -> JsonRpcResult<i64>;
+
+    #[rpc(name = "evm_setNextBlockTimestamp")]
+    fn set_next_block_timestamp(&self, timestamp: U64) -> JsonRpcResult<()>;
+
+    /// Mine the pending transactions in a single block, with an optional
+    /// timestamp.
+    #[rpc(name = "evm_mine")]
+    fn mine(&self, timestamp: Option<U64>) -> JsonRpcResult<()>;
+}
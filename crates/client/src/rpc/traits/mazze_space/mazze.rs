@@ -2,9 +2,11 @@
 
 use crate::rpc::types::{
     pos::PoSEpochReward, Account as RpcAccount, AccountPendingInfo,
-    AccountPendingTransactions, Block, BlockHashOrEpochNumber, Bytes,
+    AccountPendingTransactions, AddressTransactionCursor,
+    AddressTransactionsPage, Block, BlockHashOrEpochNumber, Bytes,
     CallRequest, MazzeFeeHistory, MazzeFilterChanges, MazzeRpcLogFilter,
-    CheckBalanceAgainstTransactionResponse, EpochNumber,
+    CheckBalanceAgainstTransactionResponse, CrossSpaceTransfer,
+    CrossSpaceTransferFilter, EpochNumber,
     EstimateGasAndCollateralResponse, Log as RpcLog, PoSEconomics,
     Receipt as RpcReceipt, RewardInfo as RpcRewardInfo, RpcAddress,
     SponsorInfo, Status as RpcStatus, StorageCollateralInfo, TokenSupplyInfo,
@@ -297,6 +299,21 @@ pub trait Mazze {
         &self, epoch_number: Option<EpochNumber>,
     ) -> JsonRpcResult<VoteParamsInfo>;
 
+    /// Returns the cross-space calls and withdrawals matching the filter,
+    /// with their phantom transactions in the eth space.
+    #[rpc(name = "mazze_getCrossSpaceTransfers")]
+    fn get_cross_space_transfers(
+        &self, filter: CrossSpaceTransferFilter,
+    ) -> JsonRpcResult<Vec<CrossSpaceTransfer>>;
+
+    /// Returns the transactions sent or received by an address, from the
+    /// newest to the oldest, starting after `cursor`.
+    #[rpc(name = "mazze_getTransactionsByAddress")]
+    fn get_transactions_by_address(
+        &self, address: RpcAddress, cursor: Option<AddressTransactionCursor>,
+        limit: Option<U64>,
+    ) -> JsonRpcResult<AddressTransactionsPage>;
+
     //        /// Returns transaction at given block hash and index.
     //        #[rpc(name = "mazze_getTransactionByBlockHashAndIndex")]
     //        fn transaction_by_block_hash_and_index(&self, H256, Index) ->
@@ -1,5 +1,6 @@
 pub mod mazze;
 pub mod debug;
+pub mod dev;
 pub mod pool;
 pub mod pos;
 pub mod pubsub;
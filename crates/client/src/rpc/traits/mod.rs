@@ -1,6 +1,6 @@
 
 
-pub use mazze_space::{mazze, debug, pool, pos, pubsub, test, trace};
+pub use mazze_space::{mazze, debug, dev, pool, pos, pubsub, test, trace};
 
 pub mod mazze_space;
 pub mod eth_space;
@@ -1,11 +1,14 @@
 
 
 mod account;
+mod address_transactions;
 mod blame_info;
 mod block;
 mod bytes;
 pub mod mazze;
 mod consensus_graph_states;
+mod cross_space;
+mod dev;
 mod epoch_number;
 pub mod errors;
 pub mod eth;
@@ -21,6 +24,8 @@ mod receipt;
 mod reward_info;
 mod sponsor_info;
 mod stat_on_gas_load;
+mod state_range;
+mod state_stats;
 mod status;
 mod storage_collateral_info;
 mod sync_graph_states;
@@ -34,6 +39,9 @@ mod vote_params_info;
 
 pub use self::{
     account::Account,
+    address_transactions::{
+        AddressTransaction, AddressTransactionCursor, AddressTransactionsPage,
+    },
     blame_info::BlameInfo,
     block::{Block, BlockTransactions, Header},
     bytes::Bytes,
@@ -49,6 +57,8 @@ pub use self::{
         MazzeFeeHistory,
     },
     consensus_graph_states::ConsensusGraphStates,
+    cross_space::{CrossSpaceTransfer, CrossSpaceTransferFilter},
+    dev::DevAddress,
     epoch_number::{BlockHashOrEpochNumber, EpochNumber},
     fee_history::FeeHistory,
     filter::{MazzeFilterChanges, MazzeFilterLog, MazzeRpcLogFilter, RevertTo},
@@ -60,6 +70,8 @@ pub use self::{
     reward_info::RewardInfo,
     sponsor_info::SponsorInfo,
     stat_on_gas_load::StatOnGasLoad,
+    state_range::{AccountRangePage, StorageEntry, StorageRangePage},
+    state_stats::StateStats,
     status::Status,
     storage_collateral_info::StorageCollateralInfo,
     sync_graph_states::SyncGraphStates,
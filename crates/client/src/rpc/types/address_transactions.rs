@@ -0,0 +1,54 @@
+use mazze_types::{H256, U64};
+use mazzecore::block_data_manager::AddressTxEntry;
+
+/// The position of a transaction in the history of an address, which is
+/// used as the cursor of `mazze_getTransactionsByAddress` and
+/// `eth_getTransactionsByAddress`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressTransactionCursor {
+    pub epoch_number: U64,
+    /// The position of the transaction in its epoch.
+    pub index: U64,
+}
+
+impl From<&AddressTxEntry> for AddressTransactionCursor {
+    fn from(entry: &AddressTxEntry) -> Self {
+        AddressTransactionCursor {
+            epoch_number: entry.epoch_number.into(),
+            index: entry.index.into(),
+        }
+    }
+}
+
+/// A transaction sent or received by an address.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressTransaction {
+    pub transaction_hash: H256,
+    pub block_hash: H256,
+    pub epoch_number: U64,
+    /// The position of the transaction in its epoch.
+    pub index: U64,
+}
+
+impl From<AddressTxEntry> for AddressTransaction {
+    fn from(entry: AddressTxEntry) -> Self {
+        AddressTransaction {
+            transaction_hash: entry.tx_hash,
+            block_hash: entry.block_hash,
+            epoch_number: entry.epoch_number.into(),
+            index: entry.index.into(),
+        }
+    }
+}
+
+/// A page of the transactions of an address from the newest to the oldest.
+/// The next page starts after `nextCursor`, which is `null` on the last page.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressTransactionsPage {
+    pub transactions: Vec<AddressTransaction>,
+    pub next_cursor: Option<AddressTransactionCursor>,
+}
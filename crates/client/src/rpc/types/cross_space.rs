@@ -0,0 +1,79 @@
+use crate::rpc::types::EpochNumber;
+use mazze_types::{H160, H256, U256, U64};
+use mazzecore::block_data_manager::CrossSpaceTransfer as PrimitiveTransfer;
+use primitives::Action;
+
+/// Filter of `mazze_getCrossSpaceTransfers`. If only `transactionHashes` is
+/// given, the epochs where the transactions are indexed are searched.
+/// Otherwise, the epochs in `[fromEpoch, toEpoch]` are searched, which
+/// default to `latest_state`.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct CrossSpaceTransferFilter {
+    pub from_epoch: Option<EpochNumber>,
+    pub to_epoch: Option<EpochNumber>,
+    /// The native transaction hashes or the phantom transaction hashes.
+    pub transaction_hashes: Option<Vec<H256>>,
+    /// The eth space addresses which send or receive the transfers.
+    pub addresses: Option<Vec<H160>>,
+}
+
+impl CrossSpaceTransferFilter {
+    pub fn matches(&self, transfer: &PrimitiveTransfer) -> bool {
+        if let Some(hashes) = &self.transaction_hashes {
+            if !hashes.contains(&transfer.native_tx_hash)
+                && !hashes.contains(&transfer.phantom_tx_hash)
+            {
+                return false;
+            }
+        }
+        if let Some(addresses) = &self.addresses {
+            let to = match transfer.action {
+                Action::Call(to) => Some(to),
+                Action::Create => None,
+            };
+            if !addresses.contains(&transfer.from)
+                && !to.map_or(false, |to| addresses.contains(&to))
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// A cross-space call or withdrawal, and its phantom transaction in the eth
+/// space. A transfer from the zero address moves value into the eth space,
+/// and a transfer to the zero address moves value out of the eth space.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrossSpaceTransfer {
+    pub epoch_number: U64,
+    pub block_hash: H256,
+    pub transaction_hash: H256,
+    pub phantom_transaction_hash: H256,
+    pub from: H160,
+    /// `None` for a contract creation.
+    pub to: Option<H160>,
+    pub value: U256,
+    pub success: bool,
+}
+
+impl CrossSpaceTransfer {
+    pub fn new(transfer: PrimitiveTransfer, epoch_number: u64) -> Self {
+        CrossSpaceTransfer {
+            epoch_number: epoch_number.into(),
+            block_hash: transfer.block_hash,
+            transaction_hash: transfer.native_tx_hash,
+            phantom_transaction_hash: transfer.phantom_tx_hash,
+            from: transfer.from,
+            to: match transfer.action {
+                Action::Call(to) => Some(to),
+                Action::Create => None,
+            },
+            value: transfer.value,
+            success: transfer.success,
+        }
+    }
+}
@@ -0,0 +1,12 @@
+use crate::rpc::types::RpcAddress;
+use mazze_types::H160;
+use serde::Deserialize;
+
+/// An account of the dev mode cheat RPCs. A base32 address is in the native
+/// space, and a hex address is in the eth space.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum DevAddress {
+    Native(RpcAddress),
+    Ethereum(H160),
+}
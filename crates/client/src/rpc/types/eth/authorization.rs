@@ -0,0 +1,48 @@
+use mazze_types::{H160, U256, U64};
+use primitives::transaction::AuthorizationListItem;
+
+/// A signed authorization of an EIP-7702 transaction.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Authorization {
+    /// The chain id the authorization is valid on, zero for any chain.
+    pub chain_id: U256,
+    /// The address the code of the authority is delegated to.
+    pub address: H160,
+    /// The nonce of the authority.
+    pub nonce: U64,
+    /// The parity of the y coordinate of the signature.
+    pub y_parity: U64,
+    /// The R field of the signature.
+    pub r: U256,
+    /// The S field of the signature.
+    pub s: U256,
+}
+
+impl From<AuthorizationListItem> for Authorization {
+    fn from(item: AuthorizationListItem) -> Self {
+        Authorization {
+            chain_id: item.chain_id,
+            address: item.address,
+            nonce: item.nonce.into(),
+            y_parity: item.y_parity.into(),
+            r: item.r,
+            s: item.s,
+        }
+    }
+}
+
+impl From<Authorization> for AuthorizationListItem {
+    fn from(authorization: Authorization) -> Self {
+        AuthorizationListItem {
+            chain_id: authorization.chain_id,
+            address: authorization.address,
+            nonce: authorization.nonce.as_u64(),
+            // An out-of-range parity makes the signature invalid.
+            y_parity: authorization.y_parity.as_u64().min(u8::MAX as u64)
+                as u8,
+            r: authorization.r,
+            s: authorization.s,
+        }
+    }
+}
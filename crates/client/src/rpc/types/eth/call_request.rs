@@ -16,6 +16,7 @@
 // You should have received a copy of the GNU General Public License
 // along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
 
+use super::Authorization;
 use crate::rpc::types::Bytes;
 use mazze_types::{H160, U256, U64};
 use primitives::AccessList;
@@ -43,6 +44,8 @@ pub struct CallRequest {
     /// Miner bribe
     pub max_priority_fee_per_gas: Option<U256>,
     pub access_list: Option<AccessList>,
+    /// EIP-7702 authorization list
+    pub authorization_list: Option<Vec<Authorization>>,
     #[serde(rename = "type")]
     pub transaction_type: Option<U64>,
 }
@@ -113,6 +113,18 @@ pub struct EthRpcLogFilter {
 }
 
 impl EthRpcLogFilter {
+    /// Whether the filter covers the pending block.
+    pub fn includes_pending(&self) -> bool {
+        self.block_hash.is_none()
+            && matches!(self.to_block, Some(BlockNumber::Pending))
+    }
+
+    /// Whether the filter only covers the pending block.
+    pub fn only_pending(&self) -> bool {
+        self.includes_pending()
+            && matches!(self.from_block, Some(BlockNumber::Pending))
+    }
+
     pub fn into_primitive(
         self, consensus: SharedConsensusGraph,
     ) -> Result<PrimitiveFilter, RpcError> {
@@ -0,0 +1,123 @@
+use mazze_execute_helper::observer::gas_profiler::{
+    opcode_name, GasProfile as ExecGasProfile, GasStat,
+};
+use mazze_types::{H160, U256, U64};
+use serde::Serialize;
+
+/// The gas used by a transaction, as returned by `debug_profileTransaction`
+/// and `debug_profileCall`. The gas of an entry excludes the gas used by the
+/// sub-calls, and the entries are sorted by decreasing gas.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasProfile {
+    /// The gas used by the execution, without the intrinsic gas.
+    pub gas_used: U256,
+    pub contracts: Vec<ContractGas>,
+    pub functions: Vec<FunctionGas>,
+    /// Sorted by call depth.
+    pub depths: Vec<DepthGas>,
+    pub opcodes: Vec<OpcodeGas>,
+    /// The call stacks in the folded format of the flame graph tools.
+    pub folded: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractGas {
+    pub address: H160,
+    pub gas: U256,
+    pub steps: U64,
+    pub calls: U64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionGas {
+    pub address: H160,
+    /// The 4-byte selector, `constructor` or `fallback`.
+    pub function: String,
+    pub gas: U256,
+    pub steps: U64,
+    pub calls: U64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepthGas {
+    pub depth: U64,
+    pub gas: U256,
+    pub steps: U64,
+    pub calls: U64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpcodeGas {
+    pub opcode: String,
+    pub gas: U256,
+    pub count: U64,
+}
+
+impl From<ExecGasProfile> for GasProfile {
+    fn from(profile: ExecGasProfile) -> Self {
+        let folded = profile.folded();
+
+        let mut contracts: Vec<_> = profile
+            .contracts
+            .into_iter()
+            .map(|(address, GasStat { gas, steps, calls })| ContractGas {
+                address,
+                gas,
+                steps: steps.into(),
+                calls: calls.into(),
+            })
+            .collect();
+        contracts.sort_by(|a, b| b.gas.cmp(&a.gas));
+
+        let mut functions: Vec<_> = profile
+            .functions
+            .into_iter()
+            .map(|((address, function), GasStat { gas, steps, calls })| {
+                FunctionGas {
+                    address,
+                    function: function.to_string(),
+                    gas,
+                    steps: steps.into(),
+                    calls: calls.into(),
+                }
+            })
+            .collect();
+        functions.sort_by(|a, b| b.gas.cmp(&a.gas));
+
+        let depths = profile
+            .depths
+            .into_iter()
+            .map(|(depth, GasStat { gas, steps, calls })| DepthGas {
+                depth: depth.into(),
+                gas,
+                steps: steps.into(),
+                calls: calls.into(),
+            })
+            .collect();
+
+        let mut opcodes: Vec<_> = profile
+            .opcodes
+            .into_iter()
+            .map(|(opcode, stat)| OpcodeGas {
+                opcode: opcode_name(opcode),
+                gas: stat.gas,
+                count: stat.steps.into(),
+            })
+            .collect();
+        opcodes.sort_by(|a, b| b.gas.cmp(&a.gas));
+
+        GasProfile {
+            gas_used: profile.gas_used,
+            contracts,
+            functions,
+            depths,
+            opcodes,
+            folded,
+        }
+    }
+}
@@ -87,6 +87,23 @@ impl Log {
         })
     }
 
+    /// Construct a log of the pending block, which is not known to the
+    /// consensus graph so its hash cannot be resolved through an epoch.
+    pub fn from_pending(e: LocalizedLogEntry) -> Log {
+        Log {
+            address: e.entry.address,
+            topics: e.entry.topics.into_iter().map(Into::into).collect(),
+            data: e.entry.data.into(),
+            block_hash: e.block_hash,
+            block_number: e.epoch_number.into(),
+            transaction_hash: e.transaction_hash.into(),
+            transaction_index: e.transaction_index.into(),
+            log_index: Some(e.log_index.into()),
+            transaction_log_index: Some(e.transaction_log_index.into()),
+            removed: false,
+        }
+    }
+
     pub fn try_from(_e: LogEntry) -> Result<Log, String> {
         unimplemented!();
         // Ok(Log {
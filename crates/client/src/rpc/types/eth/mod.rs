@@ -1,12 +1,15 @@
 
 
+mod authorization;
 mod block;
 mod block_number;
 mod call_request;
 pub mod eth_pubsub;
 mod filter;
+mod gas_profile;
 mod log;
 mod receipt;
+mod state_range;
 mod sync;
 mod trace;
 mod trace_filter;
@@ -14,12 +17,17 @@ mod transaction;
 mod tx_pool;
 
 pub use self::{
+    authorization::Authorization,
     block::{Block, Header},
     block_number::BlockNumber,
     call_request::CallRequest,
     filter::{EthRpcLogFilter, FilterChanges},
+    gas_profile::GasProfile,
     log::Log,
     receipt::Receipt,
+    state_range::{
+        AccountRangeResult, DumpAccount, StorageRangeEntry, StorageRangeResult,
+    },
     sync::{SyncInfo, SyncStatus},
     trace::{LocalizedTrace, Res},
     trace_filter::TraceFilter,
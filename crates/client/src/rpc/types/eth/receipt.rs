@@ -58,4 +58,6 @@ pub struct Receipt {
     pub tx_exec_error_msg: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub burnt_gas_fee: Option<U256>,
+    /// Whether the gas fee is paid by the sponsor of the callee
+    pub gas_covered_by_sponsor: bool,
 }
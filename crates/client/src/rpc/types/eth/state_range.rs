@@ -0,0 +1,46 @@
+use crate::rpc::types::Bytes;
+use mazze_types::{H160, H256, U256};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// The result of `debug_storageRangeAt`. Unlike geth, the storage of an
+/// account isn't keyed by the hash of the storage keys, so the entries are
+/// keyed and ordered by the storage keys themselves.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageRangeResult {
+    pub storage: BTreeMap<H256, StorageRangeEntry>,
+    pub next_key: Option<H256>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StorageRangeEntry {
+    pub key: Option<H256>,
+    pub value: H256,
+}
+
+/// The result of `debug_accountRange`. The accounts are ordered by their
+/// addresses, and `next` is the address to start the next page from.
+#[derive(Debug, Serialize)]
+pub struct AccountRangeResult {
+    pub accounts: BTreeMap<H160, DumpAccount>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<Bytes>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DumpAccount {
+    pub balance: U256,
+    pub nonce: U256,
+    pub code_hash: H256,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage: Option<BTreeMap<H256, H256>>,
+    /// Set if the storage is cut short, to read the rest of it with
+    /// `debug_storageRangeAt` from this key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_storage_key: Option<H256>,
+    pub address: H160,
+}
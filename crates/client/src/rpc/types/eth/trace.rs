@@ -37,6 +37,8 @@ pub enum CreateType {
     CREATE,
     /// CREATE2
     CREATE2,
+    /// EOFCREATE
+    EOFCREATE,
 }
 
 impl From<MazzeCreateType> for CreateType {
@@ -45,6 +47,7 @@ impl From<MazzeCreateType> for CreateType {
             MazzeCreateType::None => CreateType::None,
             MazzeCreateType::CREATE => CreateType::CREATE,
             MazzeCreateType::CREATE2 => CreateType::CREATE2,
+            MazzeCreateType::EOFCREATE => CreateType::EOFCREATE,
         }
     }
 }
@@ -0,0 +1,47 @@
+use super::{Account, RpcAddress};
+use mazze_addr::Network;
+use mazze_types::{H256, U256};
+use primitives::StorageValue;
+
+/// A storage entry of a contract.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageEntry {
+    pub key: H256,
+    pub value: U256,
+    /// The account which pays the collateral for the entry.
+    pub owner: Option<RpcAddress>,
+}
+
+impl StorageEntry {
+    pub fn try_from(
+        key: &[u8], value: StorageValue, network: Network,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            key: H256::from_slice(key),
+            value: value.value,
+            owner: match value.owner {
+                Some(owner) => Some(RpcAddress::try_from_h160(owner, network)?),
+                None => None,
+            },
+        })
+    }
+}
+
+/// A page of the storage of a contract in the order of the keys. The next
+/// page starts from `nextKey`, which is `null` on the last page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageRangePage {
+    pub storage: Vec<StorageEntry>,
+    pub next_key: Option<H256>,
+}
+
+/// A page of the accounts in the order of the addresses. The next page
+/// starts from `nextAddress`, which is `null` on the last page.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountRangePage {
+    pub accounts: Vec<Account>,
+    pub next_address: Option<RpcAddress>,
+}
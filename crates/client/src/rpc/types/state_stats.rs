@@ -0,0 +1,115 @@
+use super::RpcAddress;
+use mazze_addr::Network;
+use mazze_types::{H160, H256, U256, U64};
+use mazzecore::block_data_manager::{
+    ContractCollateral as PrimitiveContractCollateral,
+    ContractSlots as PrimitiveContractSlots,
+    KeyTypeStats as PrimitiveKeyTypeStats,
+    SpaceStateStats as PrimitiveSpaceStateStats,
+    StateStats as PrimitiveStateStats,
+};
+
+/// The statistics of the state in the snapshot of an epoch.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateStats {
+    pub snapshot_epoch_hash: H256,
+    pub snapshot_epoch_number: U64,
+    pub native: SpaceStateStats<RpcAddress>,
+    pub ethereum: SpaceStateStats<H160>,
+    pub key_types: Vec<KeyTypeStats>,
+}
+
+/// The accounts and storage of a space. The contracts are sorted from the
+/// largest, and there is no collateral in the eSpace.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpaceStateStats<Address> {
+    pub accounts: U64,
+    pub contracts: U64,
+    pub storage_slots: U64,
+    pub top_contracts_by_slots: Vec<ContractSlots<Address>>,
+    pub top_contracts_by_collateral: Vec<ContractCollateral<Address>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractSlots<Address> {
+    pub address: Address,
+    pub slots: U64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractCollateral<Address> {
+    pub address: Address,
+    pub collateral: U256,
+}
+
+/// The number and total size of the keys of a type in both spaces.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyTypeStats {
+    pub key_type: String,
+    pub keys: U64,
+    pub key_bytes: U64,
+    pub value_bytes: U64,
+}
+
+impl<Address> SpaceStateStats<Address> {
+    fn try_from<F>(
+        stats: PrimitiveSpaceStateStats, convert_address: F,
+    ) -> Result<Self, String>
+    where F: Fn(H160) -> Result<Address, String> {
+        Ok(Self {
+            accounts: stats.accounts.into(),
+            contracts: stats.contracts.into(),
+            storage_slots: stats.storage_slots.into(),
+            top_contracts_by_slots: stats
+                .top_contracts_by_slots
+                .into_iter()
+                .map(|contract: PrimitiveContractSlots| {
+                    Ok(ContractSlots {
+                        address: convert_address(contract.address)?,
+                        slots: contract.slots.into(),
+                    })
+                })
+                .collect::<Result<_, String>>()?,
+            top_contracts_by_collateral: stats
+                .top_contracts_by_collateral
+                .into_iter()
+                .map(|contract: PrimitiveContractCollateral| {
+                    Ok(ContractCollateral {
+                        address: convert_address(contract.address)?,
+                        collateral: contract.collateral,
+                    })
+                })
+                .collect::<Result<_, String>>()?,
+        })
+    }
+}
+
+impl StateStats {
+    pub fn try_from(
+        stats: PrimitiveStateStats, network: Network,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            snapshot_epoch_hash: stats.snapshot_epoch_id,
+            snapshot_epoch_number: stats.snapshot_height.into(),
+            native: SpaceStateStats::try_from(stats.native, |address| {
+                RpcAddress::try_from_h160(address, network)
+            })?,
+            ethereum: SpaceStateStats::try_from(stats.ethereum, Ok)?,
+            key_types: stats
+                .key_types
+                .into_iter()
+                .map(|key_type: PrimitiveKeyTypeStats| KeyTypeStats {
+                    key_type: key_type.key_type,
+                    keys: key_type.keys.into(),
+                    key_bytes: key_type.key_bytes.into(),
+                    value_bytes: key_type.value_bytes.into(),
+                })
+                .collect(),
+        })
+    }
+}
@@ -21,7 +21,7 @@ use std::{cmp, collections::HashMap, error, fs, io, mem, path::Path, result};
 use parking_lot::{Mutex, MutexGuard, RwLock};
 use rocksdb::{
     BlockBasedOptions, CFHandle, ColumnFamilyOptions, DBOptions, ReadOptions,
-    Writable, WriteBatch, WriteOptions, DB,
+    SeekKey, Writable, WriteBatch, WriteOptions, DB,
 };
 
 use fs_swap::{swap, swap_nonatomic};
@@ -168,6 +168,9 @@ pub struct DatabaseConfig {
     pub columns: u32,
     /// Disable WAL if set to `true`
     pub disable_wal: bool,
+    /// Keep all the writes in memory if set to `true`, so that the database
+    /// on disk is never modified, e.g. when it's inspected offline.
+    pub read_only: bool,
 }
 
 impl DatabaseConfig {
@@ -200,6 +203,7 @@ impl Default for DatabaseConfig {
             compaction: CompactionProfile::default(),
             columns: 1,
             disable_wal: false,
+            read_only: false,
         }
     }
 }
@@ -361,6 +365,11 @@ impl Database {
         // attempt database repair if it has been previously marked as corrupted
         let db_corrupted = Path::new(path).join(Database::CORRUPTION_FILE_NAME);
         if db_corrupted.exists() {
+            if config.read_only {
+                return Err(other_io_err(
+                    "DB has been previously marked as corrupted",
+                ));
+            }
             warn!(
                 "DB has been previously marked as corrupted, attempting repair"
             );
@@ -399,6 +408,7 @@ impl Database {
                 }
                 Ok(db)
             }
+            Err(e) if config.read_only => Err(e),
             Err(_) => {
                 // retry and create CFs
                 match DB::open_cf(
@@ -419,7 +429,7 @@ impl Database {
 
         let db = match db {
             Ok(db) => db,
-            Err(ref s) if is_corrupted(s) => {
+            Err(ref s) if is_corrupted(s) && !config.read_only => {
                 warn!("DB corrupted: {}, attempting repair", s);
                 DB::repair(opts.clone(), path).map_err(other_io_err)?;
                 let db = DB::open_cf(opts, path, cf_options)
@@ -519,6 +529,10 @@ impl Database {
 
     /// Commit buffered changes to database.
     pub fn flush(&self) -> io::Result<()> {
+        if self.config.read_only {
+            // The buffered changes are kept in memory.
+            return Ok(());
+        }
         let mut lock = self.flushing_lock.lock();
         // If RocksDB batch allocation fails the thread gets terminated and the
         // lock is released. The value inside the lock is used to detect
@@ -538,6 +552,10 @@ impl Database {
 
     /// Commit transaction to database.
     pub fn write(&self, tr: DBTransaction) -> io::Result<()> {
+        if self.config.read_only {
+            self.write_buffered(tr);
+            return Ok(());
+        }
         match *self.db.read() {
             Some(ref cfs) => {
                 let batch = WriteBatch::default();
@@ -599,6 +617,41 @@ impl Database {
         }
     }
 
+    /// Get at most `limit` key-value pairs of a column in key order, starting
+    /// from `lower_bound_incl` and stopping before `upper_bound_excl`.
+    ///
+    /// Only flushed values are read: changes added with `write_buffered` are
+    /// not visible until `flush`.
+    pub fn read_range(
+        &self, col: u32, lower_bound_incl: &[u8],
+        upper_bound_excl: Option<&[u8]>, limit: usize,
+    ) -> io::Result<Vec<KeyValuePair>> {
+        match *self.db.read() {
+            Some(ref cfs) => {
+                let mut iter = cfs.db.iter_cf_opt(
+                    cfs.get_cf(col as usize),
+                    ReadOptions::default(),
+                );
+                iter.seek(SeekKey::Key(lower_bound_incl))
+                    .map_err(other_io_err)?;
+                let mut result = Vec::new();
+                while result.len() < limit
+                    && iter.valid().map_err(other_io_err)?
+                {
+                    if let Some(upper_bound_excl) = upper_bound_excl {
+                        if iter.key() >= upper_bound_excl {
+                            break;
+                        }
+                    }
+                    result.push((iter.key().into(), iter.value().into()));
+                    iter.next().map_err(other_io_err)?;
+                }
+                Ok(result)
+            }
+            None => Err(other_io_err("Database is closed")),
+        }
+    }
+
     /// Close the database
     fn close(&self) {
         *self.db.write() = None;
@@ -608,6 +661,9 @@ impl Database {
 
     /// Restore the database from a copy at given path.
     pub fn restore(&self, new_db: &str) -> io::Result<()> {
+        if self.config.read_only {
+            return Err(other_io_err("Database is read only"));
+        }
         self.close();
 
         // swap is guaranteed to be atomic
@@ -662,6 +718,9 @@ impl Database {
 
     /// Drop a column family.
     pub fn drop_column(&self) -> io::Result<()> {
+        if self.config.read_only {
+            return Err(other_io_err("Database is read only"));
+        }
         match *self.db.write() {
             Some(DBAndColumns {
                 ref mut db,
@@ -678,6 +737,9 @@ impl Database {
 
     /// Add a column family.
     pub fn add_column(&self) -> io::Result<()> {
+        if self.config.read_only {
+            return Err(other_io_err("Database is read only"));
+        }
         match *self.db.write() {
             Some(DBAndColumns {
                 ref mut db,
@@ -774,13 +836,25 @@ mod tests {
 
         assert_eq!(&*db.get(0, key1.as_bytes()).unwrap().unwrap(), b"cat");
 
-        // TODO implement iter
-        //        let contents: Vec<_> = db.iter(None).collect();
-        //        assert_eq!(contents.len(), 2);
-        //        assert_eq!(&*contents[0].0, key1.as_bytes());
-        //        assert_eq!(&*contents[0].1, b"cat");
-        //        assert_eq!(&*contents[1].0, key2.as_bytes());
-        //        assert_eq!(&*contents[1].1, b"dog");
+        let contents = db.read_range(0, &[], None, usize::MAX).unwrap();
+        assert_eq!(contents.len(), 2);
+        assert_eq!(&*contents[0].0, key1.as_bytes());
+        assert_eq!(&*contents[0].1, b"cat");
+        assert_eq!(&*contents[1].0, key2.as_bytes());
+        assert_eq!(&*contents[1].1, b"dog");
+        let contents = db.read_range(0, &[], None, 1).unwrap();
+        assert_eq!(contents.len(), 1);
+        assert_eq!(&*contents[0].0, key1.as_bytes());
+        let contents = db
+            .read_range(0, key1.as_bytes(), Some(key2.as_bytes()), 10)
+            .unwrap();
+        assert_eq!(contents.len(), 1);
+        assert_eq!(&*contents[0].0, key1.as_bytes());
+        let contents = db
+            .read_range(0, &key1.as_bytes()[..31], Some(&[3]), 10)
+            .unwrap();
+        assert_eq!(contents.len(), 1);
+        assert_eq!(&*contents[0].1, b"cat");
 
         let mut batch = db.transaction();
         batch.delete(0, key1.as_bytes());
@@ -921,6 +995,45 @@ mod tests {
         assert_eq!(db.get(0, b"foo").unwrap().unwrap(), b"baz");
     }
 
+    #[test]
+    fn read_only_keeps_writes_in_memory() {
+        let tempdir = TempDir::new("").unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        {
+            let db = Database::open(&DatabaseConfig::default(), path).unwrap();
+            let mut batch = db.transaction();
+            batch.put(0, b"foo", b"bar");
+            db.write(batch).unwrap();
+        }
+
+        let mut config = DatabaseConfig::default();
+        config.read_only = true;
+        {
+            let db = Database::open(&config, path).unwrap();
+            let mut batch = db.transaction();
+            batch.put(0, b"foo", b"baz");
+            batch.put(0, b"qux", b"quux");
+            db.write(batch).unwrap();
+            let mut batch = db.transaction();
+            batch.delete(0, b"qux");
+            db.write_buffered(batch);
+            db.flush().unwrap();
+
+            assert_eq!(db.get(0, b"foo").unwrap().unwrap(), b"baz");
+            assert!(db.get(0, b"qux").unwrap().is_none());
+            assert!(db.add_column().is_err());
+        }
+
+        let db = Database::open(&DatabaseConfig::default(), path).unwrap();
+        assert_eq!(db.get(0, b"foo").unwrap().unwrap(), b"bar");
+        assert!(db.get(0, b"qux").unwrap().is_none());
+
+        // The missing columns are not created in a read only database.
+        config.columns = 2;
+        drop(db);
+        assert!(Database::open(&config, path).is_err());
+    }
+
     #[test]
     fn test_memory_property() {
         let tempdir = TempDir::new("").unwrap();
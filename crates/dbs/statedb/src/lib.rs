@@ -35,6 +35,9 @@ mod impls {
     // modified or deleted since the last checkpoint.
     type Checkpoint = BTreeMap<Key, Option<Value>>;
 
+    // The original values of the keys modified by the committed changes.
+    type ChangeSet = BTreeMap<Key, Value>;
+
     // Use generic type for better test-ability.
     pub struct StateDb {
         /// Contains the original storage key values for all loaded and
@@ -47,6 +50,10 @@ mod impls {
 
         /// Checkpoints allow callers to revert un-committed changes.
         checkpoints: Vec<Checkpoint>,
+
+        /// The original values of all keys modified since the state db is
+        /// created, which revert the committed changes.
+        change_set: ChangeSet,
     }
 
     // Note: Not used currently.
@@ -71,6 +78,7 @@ mod impls {
                 accessed_entries: Default::default(),
                 storage,
                 checkpoints: Default::default(),
+                change_set: Default::default(),
             }
         }
 
@@ -276,6 +284,32 @@ mod impls {
             Ok(deleted_kvs)
         }
 
+        /// Read at most `limit` accounts in `space` in the order of the
+        /// addresses, starting from `start_address`. Only the accounts in the
+        /// storage are read, the changes not committed are ignored.
+        pub fn read_account_range(
+            &mut self, space: Space, start_address: &[u8], limit: usize,
+        ) -> Result<Vec<MptKeyValue>> {
+            Ok(self
+                .storage
+                .read_account_range(space, start_address, limit)?)
+        }
+
+        /// Read at most `limit` storage entries of `address` in the order of
+        /// the storage keys, starting from `start_key`. Only the entries in
+        /// the storage are read, the changes not committed are ignored.
+        pub fn read_storage_range(
+            &mut self, address: &AddressWithSpace, start_key: &[u8],
+            limit: usize,
+        ) -> Result<Vec<MptKeyValue>> {
+            Ok(self.storage.read_storage_range(
+                address.space,
+                address.address.as_bytes(),
+                start_key,
+                limit,
+            )?)
+        }
+
         /// Load the storage layout for state commits.
         /// Modification to storage layout is the same as modification of
         /// any other key-values. But as required by MPT structure we
@@ -455,11 +489,24 @@ mod impls {
                     debug_record.as_deref_mut(),
                 )?;
             }
-            // Mark all modification applied.
-            self.accessed_entries = Default::default();
+            // Mark all modification applied, and keep the original values of
+            // the modified keys.
+            let accessed_entries =
+                std::mem::take(self.accessed_entries.get_mut());
+            for (k, v) in accessed_entries {
+                if v.is_modified() {
+                    self.change_set.entry(k).or_insert(v.original_value);
+                }
+            }
             Ok(())
         }
 
+        /// Take the original values of the keys modified by the applied
+        /// changes, in the order of the keys.
+        pub fn take_change_set(&mut self) -> Vec<(Key, Value)> {
+            std::mem::take(&mut self.change_set).into_iter().collect()
+        }
+
         /// This method is only used for genesis block because state root is
         /// required to compute genesis epoch_id. For other blocks there are
         /// deferred execution so the state root computation is merged inside
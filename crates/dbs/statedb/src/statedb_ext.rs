@@ -4,10 +4,10 @@ use mazze_parameters::internal_contract_addresses::SYSTEM_STORAGE_ADDRESS;
 use rlp::Rlp;
 
 use mazze_internal_common::debug::ComputeEpochDebugRecord;
-use mazze_types::{AddressWithSpace, H256, U256};
+use mazze_types::{Address, AddressWithSpace, Space, H256, U256};
 use primitives::{
-    is_default::IsDefault, Account, CodeInfo, DepositList, StorageKey,
-    StorageKeyWithSpace, VoteStakeList,
+    is_default::IsDefault, Account, CodeInfo, DepositList, SkipInputCheck,
+    StorageKey, StorageKeyWithSpace, StorageValue, VoteStakeList,
 };
 
 use crate::global_params::{GlobalParamKey, InterestRate};
@@ -49,6 +49,20 @@ pub trait StateDbExt {
         debug_record: Option<&mut ComputeEpochDebugRecord>,
     ) -> Result<()>;
 
+    /// Read at most `limit` storage entries of `address` in the order of the
+    /// storage keys, starting from `start_key`. Returns the entries and the
+    /// key to start the next page from, if there are more entries.
+    fn get_storage_range(
+        &mut self, address: &AddressWithSpace, start_key: &[u8], limit: usize,
+    ) -> Result<(Vec<(Vec<u8>, StorageValue)>, Option<Vec<u8>>)>;
+
+    /// Read at most `limit` accounts in `space` in the order of the
+    /// addresses, starting from `start_address`. Returns the accounts and the
+    /// address to start the next page from, if there are more accounts.
+    fn get_account_range(
+        &mut self, space: Space, start_address: &Address, limit: usize,
+    ) -> Result<(Vec<Account>, Option<Address>)>;
+
     // This function is used to check whether the db has been initialized when
     // create a state. So we can know the loaded `None` represents "not
     // initialized" or "zero value".
@@ -147,6 +161,63 @@ impl StateDbExt for StateDbGeneric {
         self.set::<U256>(T::STORAGE_KEY, value, debug_record)
     }
 
+    fn get_storage_range(
+        &mut self, address: &AddressWithSpace, start_key: &[u8], limit: usize,
+    ) -> Result<(Vec<(Vec<u8>, StorageValue)>, Option<Vec<u8>>)> {
+        let storage_key_of = |key: &[u8]| {
+            let storage_key =
+                StorageKeyWithSpace::from_key_bytes::<SkipInputCheck>(key);
+            match storage_key.key {
+                StorageKey::StorageKey { storage_key, .. } => {
+                    storage_key.to_vec()
+                }
+                _ => unreachable!("read_storage_range only returns storage"),
+            }
+        };
+        let mut kvs = self.read_storage_range(
+            address,
+            start_key,
+            limit.saturating_add(1),
+        )?;
+        let next_key = if kvs.len() > limit {
+            kvs.pop().map(|(key, _)| storage_key_of(&key))
+        } else {
+            None
+        };
+        let mut storage = Vec::with_capacity(kvs.len());
+        for (key, value) in kvs {
+            storage.push((
+                storage_key_of(&key),
+                ::rlp::decode::<StorageValue>(&value)?,
+            ));
+        }
+        Ok((storage, next_key))
+    }
+
+    fn get_account_range(
+        &mut self, space: Space, start_address: &Address, limit: usize,
+    ) -> Result<(Vec<Account>, Option<Address>)> {
+        let mut kvs = self.read_account_range(
+            space,
+            start_address.as_bytes(),
+            limit.saturating_add(1),
+        )?;
+        let next_address = if kvs.len() > limit {
+            kvs.pop().map(|(key, _)| {
+                Address::from_slice(&key[..StorageKeyWithSpace::ACCOUNT_BYTES])
+            })
+        } else {
+            None
+        };
+        let mut accounts = Vec::with_capacity(kvs.len());
+        for (key, value) in kvs {
+            let address =
+                Address::from_slice(&key[..StorageKeyWithSpace::ACCOUNT_BYTES]);
+            accounts.push(Account::new_from_rlp(address, &Rlp::new(&value))?);
+        }
+        Ok((accounts, next_address))
+    }
+
     fn is_initialized(&self) -> Result<bool> {
         let interest_rate_opt = self.get::<U256>(InterestRate::STORAGE_KEY)?;
         Ok(interest_rate_opt.is_some())
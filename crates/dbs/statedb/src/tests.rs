@@ -1,12 +1,16 @@
 
 
-use super::StateDbGeneric;
+use super::{StateDbExt, StateDbGeneric};
 use mazze_internal_common::StateRootWithAuxInfo;
 use mazze_storage::{
     utils::access_mode, ErrorKind, MptKeyValue, Result, StorageStateTrait,
 };
+use mazze_types::{Address, AddressSpaceUtil, BigEndianHash, Space, H256};
 use parking_lot::Mutex;
-use primitives::{EpochId, StorageKey, StorageKeyWithSpace, MERKLE_NULL_NODE};
+use primitives::{
+    Account, EpochId, SkipInputCheck, StorageKey, StorageKeyWithSpace,
+    StorageValue as PrimitiveStorageValue, MERKLE_NULL_NODE,
+};
 use std::collections::HashMap;
 
 type StorageValue = Box<[u8]>;
@@ -135,6 +139,52 @@ impl StorageStateTrait for MockStorage {
 
         Ok(Some(kvs))
     }
+
+    fn read_account_range(
+        &mut self, space: Space, start_address: &[u8], limit: usize,
+    ) -> Result<Vec<MptKeyValue>> {
+        let mut kvs: Vec<_> = self
+            .contents
+            .iter()
+            .filter(|(k, _)| {
+                match StorageKeyWithSpace::from_key_bytes::<SkipInputCheck>(k) {
+                    StorageKeyWithSpace {
+                        key: StorageKey::AccountKey(address_bytes),
+                        space: key_space,
+                    } => key_space == space && address_bytes >= start_address,
+                    _ => false,
+                }
+            })
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        kvs.sort();
+        kvs.truncate(limit);
+        *self.num_reads.get_mut() += kvs.len() as u64;
+        Ok(kvs)
+    }
+
+    fn read_storage_range(
+        &mut self, space: Space, address: &[u8], start_key: &[u8], limit: usize,
+    ) -> Result<Vec<MptKeyValue>> {
+        let prefix = StorageKey::StorageRootKey(address)
+            .with_space(space)
+            .to_key_bytes();
+        let lower_bound = [&*prefix, start_key].concat();
+        let mut kvs: Vec<_> = self
+            .contents
+            .iter()
+            .filter(|(k, _)| {
+                k.starts_with(&prefix)
+                    && k.len() > prefix.len()
+                    && **k >= lower_bound
+            })
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        kvs.sort();
+        kvs.truncate(limit);
+        *self.num_reads.get_mut() += kvs.len() as u64;
+        Ok(kvs)
+    }
 }
 
 type StateDbTest = StateDbGeneric;
@@ -327,3 +377,118 @@ fn test_checkpoint_evict_memory() {
     // value is removed from state-db
     assert_eq!(state_db.get_from_cache(&key(b"00")), None);
 }
+
+#[test]
+fn test_change_set() {
+    let mut state_db = init_state_db();
+
+    // (11, v0) --> (11, v1)
+    state_db
+        .set_raw(storage_key(b"11"), value(b"v1"), None)
+        .unwrap();
+
+    // (00, v0) --> (00, v1) --> (00, v0) [back to original value]
+    state_db
+        .set_raw(storage_key(b"00"), value(b"v1"), None)
+        .unwrap();
+    state_db
+        .set_raw(storage_key(b"00"), value(b"v0"), None)
+        .unwrap();
+
+    // delete (22, v0)
+    state_db.delete(storage_key(b"22"), None).unwrap();
+
+    // create (33, v0)
+    state_db
+        .set_raw(storage_key(b"33"), value(b"v0"), None)
+        .unwrap();
+
+    state_db.commit(MERKLE_NULL_NODE, None).unwrap();
+
+    // only the original values of the modified keys are kept
+    assert_eq!(
+        state_db.take_change_set(),
+        vec![
+            (key(b"11"), Some(value(b"v0").into())),
+            (key(b"22"), Some(value(b"v0").into())),
+            (key(b"33"), None),
+        ]
+    );
+    assert!(state_db.take_change_set().is_empty());
+}
+
+#[test]
+fn test_storage_range() {
+    let address = Address::from_low_u64_be(1).with_native_space();
+    let mut contents = RawStorage::new();
+    for slot in [3u64, 1, 2] {
+        let slot = H256::from_low_u64_be(slot);
+        let value = PrimitiveStorageValue {
+            value: slot.into_uint(),
+            owner: None,
+        };
+        contents.insert(
+            StorageKey::new_storage_key(&address.address, slot.as_bytes())
+                .with_native_space()
+                .to_key_bytes(),
+            rlp::encode(&value).into_boxed_slice(),
+        );
+    }
+    let mut state_db =
+        StateDbTest::new(Box::new(MockStorage::with_contents(contents)));
+
+    let (entries, next_key) =
+        state_db.get_storage_range(&address, &[], 2).unwrap();
+    let keys: Vec<_> = entries.iter().map(|(key, _)| key.clone()).collect();
+    assert_eq!(
+        keys,
+        vec![
+            H256::from_low_u64_be(1).as_bytes().to_vec(),
+            H256::from_low_u64_be(2).as_bytes().to_vec()
+        ]
+    );
+    assert_eq!(entries[1].1.value, 2.into());
+    assert_eq!(next_key, Some(H256::from_low_u64_be(3).as_bytes().to_vec()));
+
+    let (entries, next_key) = state_db
+        .get_storage_range(&address, &next_key.unwrap(), 2)
+        .unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].1.value, 3.into());
+    assert_eq!(next_key, None);
+}
+
+#[test]
+fn test_account_range() {
+    let mut contents = RawStorage::new();
+    for i in [3u64, 1, 2] {
+        let address = Address::from_low_u64_be(i).with_native_space();
+        let account = Account::new_empty_with_balance(
+            &address,
+            &i.into(), /* balance */
+            &0.into(), /* nonce */
+        );
+        contents.insert(
+            StorageKey::new_account_key(&address.address)
+                .with_native_space()
+                .to_key_bytes(),
+            rlp::encode(&account).into_boxed_slice(),
+        );
+    }
+    let mut state_db =
+        StateDbTest::new(Box::new(MockStorage::with_contents(contents)));
+
+    let (accounts, next_address) = state_db
+        .get_account_range(Space::Native, &Address::from_low_u64_be(2), 1)
+        .unwrap();
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(accounts[0].address().address, Address::from_low_u64_be(2));
+    assert_eq!(accounts[0].balance, 2.into());
+    assert_eq!(next_address, Some(Address::from_low_u64_be(3)));
+
+    let (accounts, next_address) = state_db
+        .get_account_range(Space::Ethereum, &Address::zero(), 10)
+        .unwrap();
+    assert!(accounts.is_empty());
+    assert_eq!(next_address, None);
+}
@@ -575,6 +575,9 @@ impl CowNodeRef {
         key_prefix: CompressedPathRaw, values: &mut KVInserterType,
         db: &mut DeltaDbOwnedReadTraitObj,
     ) -> Result<()> {
+        if !values.visit_subtree(&key_prefix) {
+            return Ok(());
+        }
         if guarded_trie_node.as_ref().as_ref().has_value() {
             assert!(CompressedPathRaw::has_second_nibble(
                 key_prefix.path_mask()
@@ -594,6 +597,9 @@ impl CowNodeRef {
         let node_memory_manager = trie.get_node_memory_manager();
         let allocator = node_memory_manager.get_allocator();
         for (i, node_ref) in children_table.iter() {
+            if values.is_done() {
+                break;
+            }
             let mut cow_child_node =
                 Self::new((*node_ref).into(), owned_node_set, self.mpt_id);
             let child_node = cow_child_node.get_trie_node(
@@ -566,8 +566,23 @@ impl<'trie, 'db: 'trie> SubTrieVisitor<'trie, 'db> {
 
     /// return all key/value pairs given the prefix
     pub fn traversal(
-        mut self, key: KeyPart, key_remaining: KeyPart,
+        self, key: KeyPart, key_remaining: KeyPart,
     ) -> Result<Option<Vec<MptKeyValue>>> {
+        let mut values = vec![];
+        if self.traversal_with(key, key_remaining, &mut values)? {
+            Ok(Some(values))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Push the key/value pairs given the prefix into `values`, which may
+    /// skip subtrees or stop the iteration early. Returns false if there is
+    /// no subtree for the prefix.
+    pub fn traversal_with<KVInserterType: KVInserter<MptKeyValue>>(
+        mut self, key: KeyPart, key_remaining: KeyPart,
+        values: &mut KVInserterType,
+    ) -> Result<bool> {
         let node_memory_manager = self.node_memory_manager();
         let allocator = node_memory_manager.get_allocator();
         let mut node_cow = self.root.take();
@@ -580,7 +595,7 @@ impl<'trie, 'db: 'trie> SubTrieVisitor<'trie, 'db> {
 
         let key_prefix: CompressedPathRaw;
         match trie_node_ref.walk::<access_mode::Write>(key_remaining) {
-            WalkStop::ChildNotFound { .. } => return Ok(None),
+            WalkStop::ChildNotFound { .. } => return Ok(false),
             WalkStop::Arrived => {
                 // To enumerate the subtree.
                 key_prefix = key.into();
@@ -592,7 +607,7 @@ impl<'trie, 'db: 'trie> SubTrieVisitor<'trie, 'db> {
                 ..
             } => {
                 if key_child_index.is_some() {
-                    return Ok(None);
+                    return Ok(false);
                 }
                 // To enumerate the subtree.
                 key_prefix = CompressedPathRaw::join_connected_paths(
@@ -607,24 +622,22 @@ impl<'trie, 'db: 'trie> SubTrieVisitor<'trie, 'db> {
                 ..
             } => {
                 drop(trie_node_ref);
-                let values = self
+                return self
                     .new_visitor_for_subtree(child_node.clone().into())
-                    .traversal(key, key_remaining)?;
-                return Ok(values);
+                    .traversal_with(key, key_remaining, values);
             }
         }
 
         let trie_node = GuardedValue::take(trie_node_ref);
-        let mut values = vec![];
         node_cow.iterate_internal(
             self.owned_node_set.get_ref(),
             self.get_trie_ref(),
             trie_node,
             key_prefix,
-            &mut values,
+            values,
             &mut *self.db.get_mut().to_owned_read()?,
         )?;
-        Ok(Some(values))
+        Ok(true)
     }
 
     // In a method we visit node one or 2 times but borrow-checker prevent
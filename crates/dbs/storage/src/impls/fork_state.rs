@@ -0,0 +1,295 @@
+// `ForkState` is a wrapper around other state implementations that fetches the
+// entries missing from the local state from a remote chain on demand. It lets
+// a dev node run on top of the state of another chain, while the new blocks
+// are mined and committed locally.
+
+/// The remote chain which a forked state is built upon.
+pub trait ForkSource: Send + Sync {
+    /// Fetch the value of `access_key` from the remote chain at the fork
+    /// point, encoded in the same way as in the local storage.
+    fn get(&self, access_key: StorageKeyWithSpace)
+        -> Result<Option<Box<[u8]>>>;
+}
+
+/// The keys deleted locally up to an epoch. Each key is prefixed with its
+/// space, and it hides the keys it is a prefix of, e.g. a deleted account
+/// hides its storage and code.
+type DeletedKeys = HashSet<Vec<u8>>;
+
+/// The entries fetched from the fork source, and the keys deleted locally.
+/// It is shared by all the states, so each entry is fetched at most once.
+///
+/// The deleted keys are recorded by epoch, so that a state only sees the
+/// deletions made on its own chain, e.g. the deletions of the reverted epochs
+/// are not seen by the states after a revert.
+pub struct ForkCache {
+    source: Box<dyn ForkSource>,
+    /// `None` means that the entry does not exist on the remote chain.
+    entries: RwLock<HashMap<Vec<u8>, Option<Box<[u8]>>>>,
+    deletions: Mutex<PersistedForkDeletions>,
+}
+
+/// The in mem deleted keys and the on disk deletions db are always in sync.
+struct PersistedForkDeletions {
+    /// Each committed epoch is persisted with its parent and the keys deleted
+    /// in it, so that the deleted keys are rebuilt after a restart.
+    deletions_db: KvdbSqlite<Box<[u8]>>,
+    /// The keys deleted up to each committed epoch. An epoch without
+    /// deletions shares the keys of its parent.
+    deleted_keys_by_epoch: HashMap<EpochId, Arc<DeletedKeys>>,
+}
+
+#[derive(RlpEncodable, RlpDecodable)]
+struct EpochDeletions {
+    parent: EpochId,
+    deleted_keys: Vec<Vec<u8>>,
+}
+
+impl PersistedForkDeletions {
+    fn new(deletions_db: KvdbSqlite<Box<[u8]>>) -> Result<Self> {
+        let mut result = Self {
+            deletions_db,
+            // The map is loaded later
+            deleted_keys_by_epoch: Default::default(),
+        };
+        result.load_persist_state()?;
+        Ok(result)
+    }
+
+    fn get(&self, epoch: &EpochId) -> Arc<DeletedKeys> {
+        // The states before the fork, e.g. the genesis state, have nothing
+        // deleted.
+        self.deleted_keys_by_epoch
+            .get(epoch)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn insert(
+        &mut self, epoch: EpochId, parent: EpochId, deleted_keys: &DeletedKeys,
+    ) -> Result<()> {
+        let epoch_deletions = EpochDeletions {
+            parent,
+            deleted_keys: deleted_keys.iter().cloned().collect(),
+        };
+        self.deletions_db
+            .put(epoch.as_ref(), &epoch_deletions.rlp_bytes())?;
+        self.insert_in_mem(epoch, epoch_deletions);
+        Ok(())
+    }
+
+    fn insert_in_mem(
+        &mut self, epoch: EpochId, epoch_deletions: EpochDeletions,
+    ) {
+        let parent_keys = self.get(&epoch_deletions.parent);
+        let deleted_keys = if epoch_deletions.deleted_keys.is_empty() {
+            parent_keys
+        } else {
+            let mut deleted_keys = (*parent_keys).clone();
+            deleted_keys.extend(epoch_deletions.deleted_keys);
+            Arc::new(deleted_keys)
+        };
+        self.deleted_keys_by_epoch.insert(epoch, deleted_keys);
+    }
+
+    fn load_persist_state(&mut self) -> Result<()> {
+        let (maybe_connection, statements) =
+            self.deletions_db.destructure_mut();
+        let mut iter = kvdb_sqlite_iter_range_impl(
+            maybe_connection,
+            statements,
+            &[],
+            None,
+            |row: &Statement<'_>| {
+                let key = row.read::<Vec<u8>>(0)?;
+                let value = row.read::<Vec<u8>>(1)?;
+
+                if key.len() != EpochId::len_bytes() {
+                    Err(DecoderError::RlpInvalidLength.into())
+                } else {
+                    Ok((
+                        EpochId::from_slice(&key),
+                        EpochDeletions::decode(&Rlp::new(&value))?,
+                    ))
+                }
+            },
+        )?;
+        let mut pending = HashMap::new();
+        while let Some((epoch, epoch_deletions)) = iter.next()? {
+            pending.insert(epoch, epoch_deletions);
+        }
+
+        // The parents are loaded before their children.
+        loop {
+            let mut chain = match pending.keys().next() {
+                Some(epoch) => vec![*epoch],
+                None => break,
+            };
+            while let Some(parent_deletions) =
+                pending.get(chain.last().unwrap())
+            {
+                if !pending.contains_key(&parent_deletions.parent) {
+                    break;
+                }
+                chain.push(parent_deletions.parent);
+            }
+            for epoch in chain.into_iter().rev() {
+                let epoch_deletions = pending.remove(&epoch).unwrap();
+                self.insert_in_mem(epoch, epoch_deletions);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ForkCache {
+    pub fn new(
+        source: Box<dyn ForkSource>, deletions_db: KvdbSqlite<Box<[u8]>>,
+    ) -> Result<Self> {
+        Ok(Self {
+            source,
+            entries: Default::default(),
+            deletions: Mutex::new(PersistedForkDeletions::new(deletions_db)?),
+        })
+    }
+
+    pub fn get(
+        &self, access_key: StorageKeyWithSpace,
+    ) -> Result<Option<Box<[u8]>>> {
+        let key = access_key.to_key_bytes();
+        if let Some(value) = self.entries.read().get(&key) {
+            return Ok(value.clone());
+        }
+
+        let value = self.source.get(access_key)?;
+        trace!("fetch forked key={:?}, value={:?}", access_key, value);
+        self.entries.write().insert(key, value.clone());
+        Ok(value)
+    }
+
+    fn deleted_keys(&self, epoch: &EpochId) -> Arc<DeletedKeys> {
+        self.deletions.lock().get(epoch)
+    }
+
+    fn commit_deleted_keys(
+        &self, epoch: EpochId, parent: EpochId, deleted_keys: &DeletedKeys,
+    ) -> Result<()> {
+        self.deletions.lock().insert(epoch, parent, deleted_keys)
+    }
+}
+
+pub struct ForkState {
+    state: Box<dyn StateTrait>,
+    fork: Arc<ForkCache>,
+    parent: EpochId,
+    /// The keys deleted up to the parent epoch.
+    parent_deleted_keys: Arc<DeletedKeys>,
+    /// The keys deleted by this state.
+    deleted_keys: DeletedKeys,
+}
+
+impl ForkState {
+    /// `parent` is the epoch whose state `state` is built upon, or the epoch
+    /// of `state` itself if it's not committed.
+    pub fn new(
+        state: Box<dyn StateTrait>, fork: Arc<ForkCache>, parent: EpochId,
+    ) -> Self {
+        let parent_deleted_keys = fork.deleted_keys(&parent);
+        Self {
+            state,
+            fork,
+            parent,
+            parent_deleted_keys,
+            deleted_keys: Default::default(),
+        }
+    }
+
+    fn deleted_key(access_key: StorageKeyWithSpace) -> Vec<u8> {
+        let mut key = vec![access_key.space as u8];
+        key.extend_from_slice(&access_key.to_key_bytes());
+        key
+    }
+
+    fn is_deleted(&self, access_key: StorageKeyWithSpace) -> bool {
+        let key = Self::deleted_key(access_key);
+        // A deleted key is at least as long as a space prefixed account key.
+        (StorageKeyWithSpace::ACCOUNT_BYTES + 1..=key.len()).any(|len| {
+            self.deleted_keys.contains(&key[..len])
+                || self.parent_deleted_keys.contains(&key[..len])
+        })
+    }
+}
+
+impl StateTrait for ForkState {
+    delegate! {
+        to self.state {
+            fn set(&mut self, access_key: StorageKeyWithSpace, value: Box<[u8]>) -> Result<()>;
+            fn delete_test_only(&mut self, access_key: StorageKeyWithSpace) -> Result<Option<Box<[u8]>>>;
+            fn read_all(&mut self, access_key_prefix: StorageKeyWithSpace) -> Result<Option<Vec<MptKeyValue>>>;
+            fn read_account_range(&mut self, space: Space, start_address: &[u8], limit: usize) -> Result<Vec<MptKeyValue>>;
+            fn read_storage_range(&mut self, space: Space, address: &[u8], start_key: &[u8], limit: usize) -> Result<Vec<MptKeyValue>>;
+            fn for_each_account(&mut self, space: Space, f: &mut dyn FnMut(MptKeyValue) -> Result<()>) -> Result<()>;
+            fn compute_state_root(&mut self) -> Result<StateRootWithAuxInfo>;
+            fn get_state_root(&self) -> Result<StateRootWithAuxInfo>;
+        }
+    }
+
+    fn get(
+        &self, access_key: StorageKeyWithSpace,
+    ) -> Result<Option<Box<[u8]>>> {
+        match self.state.get(access_key)? {
+            Some(value) => Ok(Some(value)),
+            None if self.is_deleted(access_key) => Ok(None),
+            None => self.fork.get(access_key),
+        }
+    }
+
+    fn delete(&mut self, access_key: StorageKeyWithSpace) -> Result<()> {
+        // The storage and the code of a deleted account are deleted too.
+        self.deleted_keys.insert(Self::deleted_key(access_key));
+        self.state.delete(access_key)
+    }
+
+    fn delete_all(
+        &mut self, access_key_prefix: StorageKeyWithSpace,
+    ) -> Result<Option<Vec<MptKeyValue>>> {
+        self.deleted_keys
+            .insert(Self::deleted_key(access_key_prefix));
+        self.state.delete_all(access_key_prefix)
+    }
+
+    fn commit(&mut self, epoch_id: EpochId) -> Result<StateRootWithAuxInfo> {
+        let state_root = self.state.commit(epoch_id)?;
+        self.fork.commit_deleted_keys(
+            epoch_id,
+            self.parent,
+            &self.deleted_keys,
+        )?;
+        Ok(state_root)
+    }
+}
+
+use crate::{
+    impls::{
+        errors::*,
+        merkle_patricia_trie::MptKeyValue,
+        storage_db::kvdb_sqlite::{
+            kvdb_sqlite_iter_range_impl, KvdbSqliteDestructureTrait,
+        },
+    },
+    state::*,
+    KeyValueDbTrait, KvdbSqlite,
+};
+use delegate::delegate;
+use fallible_iterator::FallibleIterator;
+use mazze_internal_common::StateRootWithAuxInfo;
+use mazze_types::Space;
+use parking_lot::{Mutex, RwLock};
+use primitives::{EpochId, StorageKeyWithSpace};
+use rlp::{Decodable, DecoderError, Encodable, Rlp};
+use rlp_derive::{RlpDecodable, RlpEncodable};
+use sqlite::Statement;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
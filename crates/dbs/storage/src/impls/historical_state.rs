@@ -0,0 +1,178 @@
+// `HistoricalState` is a read-only view of the state of an epoch which is no
+// longer available in the storage. It is built upon the state of a later
+// epoch, with the change sets of the epochs in between applied backwards,
+// i.e. the original values of the keys modified after the epoch.
+
+pub struct HistoricalState {
+    state: Box<dyn StateTrait>,
+    /// The values of the keys modified after the epoch. `None` means that the
+    /// key did not exist.
+    original_values: BTreeMap<Vec<u8>, Option<Box<[u8]>>>,
+}
+
+impl HistoricalState {
+    pub fn new(
+        state: Box<dyn StateTrait>,
+        original_values: BTreeMap<Vec<u8>, Option<Box<[u8]>>>,
+    ) -> Self {
+        Self {
+            state,
+            original_values,
+        }
+    }
+}
+
+impl StateTrait for HistoricalState {
+    fn get(
+        &self, access_key: StorageKeyWithSpace,
+    ) -> Result<Option<Box<[u8]>>> {
+        match self.original_values.get(&access_key.to_key_bytes()) {
+            Some(value) => Ok(value.clone()),
+            None => self.state.get(access_key),
+        }
+    }
+
+    fn read_all(
+        &mut self, access_key_prefix: StorageKeyWithSpace,
+    ) -> Result<Option<Vec<MptKeyValue>>> {
+        let prefix = access_key_prefix.to_key_bytes();
+        let mut kvs = self
+            .state
+            .read_all(access_key_prefix)?
+            .unwrap_or_default()
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        for (k, v) in self
+            .original_values
+            .range::<[u8], _>((Included(&*prefix), Unbounded))
+            .take_while(|(k, _)| k.starts_with(&prefix))
+        {
+            match v {
+                Some(v) => kvs.insert(k.clone(), v.clone()),
+                None => kvs.remove(k),
+            };
+        }
+        if kvs.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(kvs.into_iter().collect()))
+        }
+    }
+
+    fn read_account_range(
+        &mut self, space: Space, start_address: &[u8], limit: usize,
+    ) -> Result<Vec<MptKeyValue>> {
+        // The account keys start with the address, with the storage keys of
+        // the accounts in between.
+        let original_values = self
+            .original_values
+            .range::<[u8], _>((Included(start_address), Unbounded))
+            .filter(|(k, _)| {
+                let storage_key =
+                    StorageKeyWithSpace::from_key_bytes::<SkipInputCheck>(k);
+                storage_key.space == space
+                    && matches!(storage_key.key, StorageKey::AccountKey(_))
+            })
+            .collect::<Vec<_>>();
+        // See read_storage_range for why the extra entries are enough.
+        let mut kvs = self
+            .state
+            .read_account_range(
+                space,
+                start_address,
+                limit.saturating_add(original_values.len()),
+            )?
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        for (k, v) in original_values {
+            match v {
+                Some(v) => kvs.insert(k.clone(), v.clone()),
+                None => kvs.remove(k),
+            };
+        }
+        Ok(kvs.into_iter().take(limit).collect())
+    }
+
+    fn read_storage_range(
+        &mut self, space: Space, address: &[u8], start_key: &[u8], limit: usize,
+    ) -> Result<Vec<MptKeyValue>> {
+        let prefix = StorageKey::StorageRootKey(address)
+            .with_space(space)
+            .to_key_bytes();
+        let lower_bound_incl = [&*prefix, start_key].concat();
+        let original_values = self
+            .original_values
+            .range::<[u8], _>((Included(&*lower_bound_incl), Unbounded))
+            .take_while(|(k, _)| k.starts_with(&prefix))
+            .filter(|(k, _)| k.len() > prefix.len())
+            .collect::<Vec<_>>();
+        // Each original value removes at most one entry of the later state,
+        // so the page read with as many more entries still has `limit`
+        // entries before its last key.
+        let mut kvs = self
+            .state
+            .read_storage_range(
+                space,
+                address,
+                start_key,
+                limit.saturating_add(original_values.len()),
+            )?
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        for (k, v) in original_values {
+            match v {
+                Some(v) => kvs.insert(k.clone(), v.clone()),
+                None => kvs.remove(k),
+            };
+        }
+        Ok(kvs.into_iter().take(limit).collect())
+    }
+
+    fn set(
+        &mut self, _access_key: StorageKeyWithSpace, _value: Box<[u8]>,
+    ) -> Result<()> {
+        bail!(READ_ONLY_ERROR)
+    }
+
+    fn delete(&mut self, _access_key: StorageKeyWithSpace) -> Result<()> {
+        bail!(READ_ONLY_ERROR)
+    }
+
+    fn delete_test_only(
+        &mut self, _access_key: StorageKeyWithSpace,
+    ) -> Result<Option<Box<[u8]>>> {
+        bail!(READ_ONLY_ERROR)
+    }
+
+    fn delete_all(
+        &mut self, _access_key_prefix: StorageKeyWithSpace,
+    ) -> Result<Option<Vec<MptKeyValue>>> {
+        bail!(READ_ONLY_ERROR)
+    }
+
+    fn compute_state_root(&mut self) -> Result<StateRootWithAuxInfo> {
+        bail!(READ_ONLY_ERROR)
+    }
+
+    fn get_state_root(&self) -> Result<StateRootWithAuxInfo> {
+        bail!(READ_ONLY_ERROR)
+    }
+
+    fn commit(&mut self, _epoch_id: EpochId) -> Result<StateRootWithAuxInfo> {
+        bail!(READ_ONLY_ERROR)
+    }
+}
+
+const READ_ONLY_ERROR: &str = "Historical states are read-only";
+
+use crate::{
+    impls::{errors::*, merkle_patricia_trie::MptKeyValue},
+    state::*,
+};
+use mazze_internal_common::StateRootWithAuxInfo;
+use mazze_types::Space;
+use primitives::{EpochId, SkipInputCheck, StorageKey, StorageKeyWithSpace};
+use std::{
+    collections::BTreeMap,
+    ops::Bound::{Included, Unbounded},
+};
@@ -32,6 +32,13 @@ pub type MptKeyValue = (Vec<u8>, Box<[u8]>);
 /// Classes implement KVInserter is used to store key-values in MPT iteration.
 pub trait KVInserter<Value> {
     fn push(&mut self, v: Value) -> Result<()>;
+
+    /// Whether the subtree under `key_prefix` may have key-values to push.
+    /// The iteration skips the subtrees which aren't needed.
+    fn visit_subtree(&self, _key_prefix: &CompressedPathRaw) -> bool { true }
+
+    /// Whether enough key-values are pushed to stop the iteration.
+    fn is_done(&self) -> bool { false }
 }
 
 impl<Value> KVInserter<Value> for Vec<Value> {
@@ -3,6 +3,8 @@
 #[macro_use]
 pub(super) mod merkle_patricia_trie;
 pub(super) mod delta_mpt;
+pub(super) mod fork_state;
+pub(super) mod historical_state;
 pub(super) mod node_merkle_proof;
 pub(super) mod proof_merger;
 pub(super) mod recording_storage;
@@ -87,6 +87,26 @@ impl<Storage: StateTrait + StateTraitExt> StateTrait
             }
         }
     }
+
+    fn read_account_range(
+        &mut self, space: Space, start_address: &[u8], limit: usize,
+    ) -> Result<Vec<MptKeyValue>> {
+        let kvs =
+            self.storage
+                .read_account_range(space, start_address, limit)?;
+        self.record_kvs(&kvs)?;
+        Ok(kvs)
+    }
+
+    fn read_storage_range(
+        &mut self, space: Space, address: &[u8], start_key: &[u8], limit: usize,
+    ) -> Result<Vec<MptKeyValue>> {
+        let kvs = self
+            .storage
+            .read_storage_range(space, address, start_key, limit)?;
+        self.record_kvs(&kvs)?;
+        Ok(kvs)
+    }
 }
 
 use crate::{
@@ -98,5 +118,6 @@ use crate::{
 };
 use mazze_internal_common::StateRootWithAuxInfo;
 use delegate::delegate;
+use mazze_types::Space;
 use parking_lot::Mutex;
 use primitives::{CheckInput, EpochId, StorageKeyWithSpace};
@@ -303,6 +303,25 @@ impl<Main: StateTrait> StateTrait for ReplicatedState<Main> {
         self.state.read_all(access_key_prefix)
     }
 
+    fn read_account_range(
+        &mut self, space: Space, start_address: &[u8], limit: usize,
+    ) -> Result<Vec<MptKeyValue>> {
+        self.state.read_account_range(space, start_address, limit)
+    }
+
+    fn read_storage_range(
+        &mut self, space: Space, address: &[u8], start_key: &[u8], limit: usize,
+    ) -> Result<Vec<MptKeyValue>> {
+        self.state
+            .read_storage_range(space, address, start_key, limit)
+    }
+
+    fn for_each_account(
+        &mut self, space: Space, f: &mut dyn FnMut(MptKeyValue) -> Result<()>,
+    ) -> Result<()> {
+        self.state.for_each_account(space, f)
+    }
+
     fn compute_state_root(&mut self) -> Result<StateRootWithAuxInfo> {
         self.replication_handler
             .send_op(StateOperation::ComputeStateRoot);
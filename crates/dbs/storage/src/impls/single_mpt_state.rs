@@ -1,14 +1,19 @@
 use crate::{
-    impls::{errors::*, state::ChildrenMerkleMap},
+    impls::{
+        errors::*,
+        merkle_patricia_trie::{CompressedPathRaw, KVInserter},
+        state::{cmp_key_prefix, may_have_account_key, ChildrenMerkleMap},
+    },
     state::StateTrait,
     utils::access_mode,
     CowNodeRef, DeltaMpt, MptKeyValue, NodeRefDeltaMpt, OwnedNodeSet,
     SubTrieVisitor,
 };
 use mazze_internal_common::{StateRootAuxInfo, StateRootWithAuxInfo};
+use mazze_types::Space;
 use primitives::{
-    EpochId, MerkleHash, MptValue, StateRoot, StorageKeyWithSpace,
-    MERKLE_NULL_NODE,
+    EpochId, MerkleHash, MptValue, SkipInputCheck, StateRoot, StorageKey,
+    StorageKeyWithSpace, MERKLE_NULL_NODE,
 };
 use std::{
     cell::UnsafeCell, collections::HashSet, hint::unreachable_unchecked,
@@ -376,6 +381,65 @@ impl StateTrait for SingleMptState {
         self.delete_all_impl::<access_mode::Read>(access_key_prefix)
     }
 
+    fn read_account_range(
+        &mut self, space: Space, start_address: &[u8], limit: usize,
+    ) -> Result<Vec<MptKeyValue>> {
+        self.ensure_temp_slab_for_db_load();
+
+        // The keys are ordered by the address, so the trie is iterated from
+        // `start_address` without descending into the storage of the
+        // accounts.
+        let mut accounts = OrderedKeyRange::new(
+            start_address,
+            limit,
+            |key: &[u8]| {
+                let storage_key =
+                    StorageKeyWithSpace::from_key_bytes::<SkipInputCheck>(key);
+                storage_key.space == space
+                    && matches!(storage_key.key, StorageKey::AccountKey(_))
+            },
+            |key_prefix: &CompressedPathRaw| {
+                may_have_account_key(
+                    key_prefix,
+                    space,
+                    StorageKeyWithSpace::ACCOUNT_BYTES,
+                )
+            },
+        );
+        SubTrieVisitor::new(
+            &self.trie,
+            self.trie_root.clone(),
+            &mut self.owned_node_set,
+        )?
+        .traversal_with(&[], &[], &mut accounts)?;
+        Ok(accounts.kvs)
+    }
+
+    fn read_storage_range(
+        &mut self, space: Space, address: &[u8], start_key: &[u8], limit: usize,
+    ) -> Result<Vec<MptKeyValue>> {
+        self.ensure_temp_slab_for_db_load();
+
+        let key_prefix = StorageKey::StorageRootKey(address)
+            .with_space(space)
+            .to_key_bytes();
+        let lower_bound_incl = [&*key_prefix, start_key].concat();
+        // The storage root key is not a storage entry.
+        let mut storage = OrderedKeyRange::new(
+            &lower_bound_incl,
+            limit,
+            |key: &[u8]| key.len() > key_prefix.len(),
+            |_: &CompressedPathRaw| true,
+        );
+        SubTrieVisitor::new(
+            &self.trie,
+            self.trie_root.clone(),
+            &mut self.owned_node_set,
+        )?
+        .traversal_with(&key_prefix, &key_prefix, &mut storage)?;
+        Ok(storage.kvs)
+    }
+
     fn compute_state_root(&mut self) -> Result<StateRootWithAuxInfo> {
         self.ensure_temp_slab_for_db_load();
 
@@ -411,3 +475,60 @@ impl StateTrait for SingleMptState {
         Ok(self.state_root(merkle_root))
     }
 }
+
+/// Collects the key-values accepted by `filter` from the trie in the order of
+/// the keys, starting from `lower_bound_incl` until there are `limit` ones.
+struct OrderedKeyRange<'a, Filter, SubtreeFilter> {
+    lower_bound_incl: &'a [u8],
+    limit: usize,
+    filter: Filter,
+    /// Accepts the subtrees to visit by their key prefix.
+    subtree_filter: SubtreeFilter,
+    kvs: Vec<MptKeyValue>,
+}
+
+impl<'a, Filter, SubtreeFilter> OrderedKeyRange<'a, Filter, SubtreeFilter>
+where
+    Filter: Fn(&[u8]) -> bool,
+    SubtreeFilter: Fn(&CompressedPathRaw) -> bool,
+{
+    fn new(
+        lower_bound_incl: &'a [u8], limit: usize, filter: Filter,
+        subtree_filter: SubtreeFilter,
+    ) -> Self {
+        Self {
+            lower_bound_incl,
+            limit,
+            filter,
+            subtree_filter,
+            kvs: Vec::new(),
+        }
+    }
+}
+
+impl<'a, Filter, SubtreeFilter> KVInserter<MptKeyValue>
+    for OrderedKeyRange<'a, Filter, SubtreeFilter>
+where
+    Filter: Fn(&[u8]) -> bool,
+    SubtreeFilter: Fn(&CompressedPathRaw) -> bool,
+{
+    fn push(&mut self, (k, v): MptKeyValue) -> Result<()> {
+        // Skip the tombStones.
+        if !v.is_empty()
+            && &*k >= self.lower_bound_incl
+            && (self.filter)(&k)
+            && !self.is_done()
+        {
+            self.kvs.push((k, v));
+        }
+        Ok(())
+    }
+
+    fn visit_subtree(&self, key_prefix: &CompressedPathRaw) -> bool {
+        cmp_key_prefix(key_prefix, self.lower_bound_incl)
+            != std::cmp::Ordering::Less
+            && (self.subtree_filter)(key_prefix)
+    }
+
+    fn is_done(&self) -> bool { self.kvs.len() >= self.limit }
+}
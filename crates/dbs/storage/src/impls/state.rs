@@ -307,6 +307,29 @@ impl StateTrait for State {
         self.delete_all_impl::<access_mode::Read>(access_key_prefix)
     }
 
+    fn read_account_range(
+        &mut self, space: Space, start_address: &[u8], limit: usize,
+    ) -> Result<Vec<MptKeyValue>> {
+        let mut result = Vec::new();
+        self.for_each_account_impl(space, start_address, limit, &mut |kv| {
+            result.push(kv);
+            Ok(())
+        })?;
+        Ok(result)
+    }
+
+    fn for_each_account(
+        &mut self, space: Space, f: &mut dyn FnMut(MptKeyValue) -> Result<()>,
+    ) -> Result<()> {
+        self.for_each_account_impl(space, &[], usize::MAX, f)
+    }
+
+    fn read_storage_range(
+        &mut self, space: Space, address: &[u8], start_key: &[u8], limit: usize,
+    ) -> Result<Vec<MptKeyValue>> {
+        self.read_storage_range_impl(space, address, start_key, limit)
+    }
+
     fn compute_state_root(&mut self) -> Result<StateRootWithAuxInfo> {
         self.ensure_temp_slab_for_db_load();
 
@@ -950,6 +973,356 @@ impl State {
             Ok(Some(result))
         }
     }
+
+    /// Call `f` with at most `limit` accounts in `space` whose addresses are
+    /// not less than `start_address`, in the order of the addresses.
+    ///
+    /// Keys in Delta Trie and Intermediate Trie are ordered by the padded hash
+    /// of the address, so both tries are enumerated to collect the accounts
+    /// which override Snapshot DB, without descending into the storage of the
+    /// accounts. Snapshot DB is ordered by the address, so it's iterated from
+    /// `start_address` until there are `limit` accounts, skipping the storage
+    /// and the code of each address. The tries are enumerated once however
+    /// many accounts are read, so reading all the accounts with one call is
+    /// much cheaper than reading them page by page.
+    fn for_each_account_impl(
+        &mut self, space: Space, start_address: &[u8], limit: usize,
+        f: &mut dyn FnMut(MptKeyValue) -> Result<()>,
+    ) -> Result<()> {
+        self.ensure_temp_slab_for_db_load();
+
+        let mut trie_accounts = TrieKeyRange::new(
+            limit,
+            |key: &[u8]| {
+                let storage_key =
+                    StorageKeyWithSpace::from_key_bytes::<SkipInputCheck>(key);
+                match storage_key.key {
+                    StorageKey::AccountKey(address_bytes) => {
+                        storage_key.space == space
+                            && address_bytes >= start_address
+                    }
+                    _ => false,
+                }
+            },
+            |key_prefix: &CompressedPathRaw| {
+                may_have_account_key(
+                    key_prefix,
+                    space,
+                    DELTA_MPT_ACCOUNT_KEYPART_BYTES,
+                )
+            },
+        );
+        // Delta Trie goes first so it takes precedence.
+        if let Some(root_node) = &self.delta_trie_root {
+            SubTrieVisitor::new(
+                &self.delta_trie,
+                root_node.clone(),
+                &mut self.owned_node_set,
+            )?
+            .traversal_with(&[], &[], &mut trie_accounts)?;
+        }
+        if let (Some(root_node), Some(intermediate_trie)) =
+            (&self.intermediate_trie_root, &self.maybe_intermediate_trie)
+        {
+            SubTrieVisitor::new(
+                intermediate_trie,
+                root_node.clone(),
+                &mut self.owned_node_set,
+            )?
+            .traversal_with(&[], &[], &mut trie_accounts)?;
+        }
+
+        let mut kv_iterator = self.snapshot_db.snapshot_kv_iterator()?.take();
+        let snapshot_db = &self.snapshot_db;
+        let mut lower_bound_incl = Some(start_address.to_vec());
+        let next_snapshot_account = || -> Result<Option<MptKeyValue>> {
+            while let Some(lower_bound) = lower_bound_incl.take() {
+                let address_bytes = match kv_iterator
+                    .iter_range(lower_bound.as_slice(), None)?
+                    .take()
+                    .next()?
+                {
+                    None => return Ok(None),
+                    Some((key, _)) => {
+                        key[..StorageKeyWithSpace::ACCOUNT_BYTES].to_vec()
+                    }
+                };
+                // Skip the other keys of the address.
+                lower_bound_incl =
+                    to_key_prefix_iter_upper_bound(&address_bytes);
+                let key = StorageKey::AccountKey(&address_bytes)
+                    .with_space(space)
+                    .to_key_bytes();
+                if let Some(value) = snapshot_db.get(&key)? {
+                    return Ok(Some((key, value)));
+                }
+            }
+            Ok(None)
+        };
+
+        merge_trie_and_snapshot_kvs(
+            trie_accounts.kvs,
+            next_snapshot_account,
+            limit,
+            f,
+        )
+    }
+
+    /// Read the storage of `address` in `space` whose storage keys are not
+    /// less than `start_key`, in the order of the storage keys.
+    ///
+    /// Like the accounts, the storage of the address is enumerated in Delta
+    /// Trie and Intermediate Trie, while Snapshot DB is iterated from
+    /// `start_key` until there are `limit` entries.
+    fn read_storage_range_impl(
+        &mut self, space: Space, address: &[u8], start_key: &[u8], limit: usize,
+    ) -> Result<Vec<MptKeyValue>> {
+        self.ensure_temp_slab_for_db_load();
+
+        let storage_prefix =
+            StorageKey::StorageRootKey(address).with_space(space);
+        let key_prefix = storage_prefix.to_key_bytes();
+        let lower_bound_incl = [&*key_prefix, start_key].concat();
+        // The storage root key is not a storage entry.
+        let mut trie_storage = TrieKeyRange::new(
+            limit,
+            |key: &[u8]| {
+                key.len() > key_prefix.len() && key >= &*lower_bound_incl
+            },
+            |_: &CompressedPathRaw| true,
+        );
+        // Delta Trie goes first so it takes precedence.
+        if let Some(root_node) = &self.delta_trie_root {
+            let delta_mpt_key_prefix = storage_prefix
+                .to_delta_mpt_key_bytes(&self.delta_trie_key_padding);
+            SubTrieVisitor::new(
+                &self.delta_trie,
+                root_node.clone(),
+                &mut self.owned_node_set,
+            )?
+            .traversal_with(
+                &delta_mpt_key_prefix,
+                &delta_mpt_key_prefix,
+                &mut trie_storage,
+            )?;
+        }
+        if let (
+            Some(root_node),
+            Some(intermediate_trie),
+            Some(intermediate_trie_key_padding),
+        ) = (
+            &self.intermediate_trie_root,
+            &self.maybe_intermediate_trie,
+            &self.maybe_intermediate_trie_key_padding,
+        ) {
+            let intermediate_mpt_key_prefix = storage_prefix
+                .to_delta_mpt_key_bytes(intermediate_trie_key_padding);
+            SubTrieVisitor::new(
+                intermediate_trie,
+                root_node.clone(),
+                &mut self.owned_node_set,
+            )?
+            .traversal_with(
+                &intermediate_mpt_key_prefix,
+                &intermediate_mpt_key_prefix,
+                &mut trie_storage,
+            )?;
+        }
+
+        let mut kv_iterator = self.snapshot_db.snapshot_kv_iterator()?.take();
+        let upper_bound_excl = to_key_prefix_iter_upper_bound(&key_prefix);
+        let mut snapshot_kvs = kv_iterator
+            .iter_range(
+                lower_bound_incl.as_slice(),
+                upper_bound_excl.as_ref().map(|v| &**v),
+            )?
+            .take();
+        let next_snapshot_kv = || -> Result<Option<MptKeyValue>> {
+            while let Some((key, value)) = snapshot_kvs.next()? {
+                if key.len() > key_prefix.len() {
+                    return Ok(Some((key, value)));
+                }
+            }
+            Ok(None)
+        };
+
+        let mut result = Vec::new();
+        merge_trie_and_snapshot_kvs(
+            trie_storage.kvs,
+            next_snapshot_kv,
+            limit,
+            &mut |kv| {
+                result.push(kv);
+                Ok(())
+            },
+        )?;
+        Ok(result)
+    }
+}
+
+/// Collects the key-values accepted by `filter` from Delta Trie and
+/// Intermediate Trie, whose keys aren't in the order of the key bytes. Only
+/// the `limit` smallest keys with a value and the tombStones before them are
+/// kept, and a key pushed earlier takes precedence.
+struct TrieKeyRange<Filter, SubtreeFilter> {
+    limit: usize,
+    /// Accepts the key bytes.
+    filter: Filter,
+    /// Accepts the subtrees to visit by their delta mpt key prefix.
+    subtree_filter: SubtreeFilter,
+    /// The key-values by the key bytes, where an empty value is a tombStone.
+    kvs: BTreeMap<Vec<u8>, Box<[u8]>>,
+    values_count: usize,
+    /// Once a key with a value is dropped for the limit, the keys not less
+    /// than it are ignored.
+    upper_bound_excl: Option<Vec<u8>>,
+}
+
+impl<Filter, SubtreeFilter> TrieKeyRange<Filter, SubtreeFilter>
+where
+    Filter: Fn(&[u8]) -> bool,
+    SubtreeFilter: Fn(&CompressedPathRaw) -> bool,
+{
+    fn new(
+        limit: usize, filter: Filter, subtree_filter: SubtreeFilter,
+    ) -> Self {
+        Self {
+            limit,
+            filter,
+            subtree_filter,
+            kvs: BTreeMap::new(),
+            values_count: 0,
+            upper_bound_excl: None,
+        }
+    }
+}
+
+impl<Filter, SubtreeFilter> KVInserter<MptKeyValue>
+    for TrieKeyRange<Filter, SubtreeFilter>
+where
+    Filter: Fn(&[u8]) -> bool,
+    SubtreeFilter: Fn(&CompressedPathRaw) -> bool,
+{
+    fn push(&mut self, (k, v): MptKeyValue) -> Result<()> {
+        let key = StorageKeyWithSpace::from_delta_mpt_key(&k).to_key_bytes();
+        if !(self.filter)(&key)
+            || self.kvs.contains_key(&key)
+            || self
+                .upper_bound_excl
+                .as_ref()
+                .map_or(false, |bound| key >= *bound)
+        {
+            return Ok(());
+        }
+        if !v.is_empty() {
+            self.values_count += 1;
+        }
+        self.kvs.insert(key, v);
+        if self.values_count > self.limit {
+            // Drop the largest key with a value and the tombStones after it.
+            let bound = self
+                .kvs
+                .iter()
+                .rev()
+                .find(|(_, v)| !v.is_empty())
+                .map(|(k, _)| k.clone())
+                .unwrap();
+            self.kvs.retain(|k, _| *k < bound);
+            self.values_count -= 1;
+            self.upper_bound_excl = Some(bound);
+        }
+        Ok(())
+    }
+
+    fn visit_subtree(&self, key_prefix: &CompressedPathRaw) -> bool {
+        (self.subtree_filter)(key_prefix)
+    }
+}
+
+/// Merge the key-values from the tries and from Snapshot DB, both in the order
+/// of the keys, and call `f` with at most `limit` of them. The tries override
+/// Snapshot DB, where an empty value is a tombStone.
+fn merge_trie_and_snapshot_kvs(
+    trie_kvs: BTreeMap<Vec<u8>, Box<[u8]>>,
+    mut next_snapshot_kv: impl FnMut() -> Result<Option<MptKeyValue>>,
+    limit: usize, f: &mut dyn FnMut(MptKeyValue) -> Result<()>,
+) -> Result<()> {
+    let mut trie_kvs = trie_kvs.into_iter().peekable();
+    let mut snapshot_kv = next_snapshot_kv()?;
+    let mut count = 0;
+    while count < limit {
+        let from_trie = match (trie_kvs.peek(), &snapshot_kv) {
+            (None, None) => break,
+            (Some(_), None) => true,
+            (None, Some(_)) => false,
+            (Some((trie_key, _)), Some((snapshot_key, _))) => {
+                trie_key <= snapshot_key
+            }
+        };
+        if from_trie {
+            let (key, value) = trie_kvs.next().unwrap();
+            if snapshot_kv.as_ref().map_or(false, |(k, _)| *k == key) {
+                snapshot_kv = next_snapshot_kv()?;
+            }
+            if !value.is_empty() {
+                f((key, value))?;
+                count += 1;
+            }
+        } else {
+            f(snapshot_kv.take().unwrap())?;
+            count += 1;
+            snapshot_kv = next_snapshot_kv()?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether the subtree under `key_prefix` may have account keys in `space`,
+/// where the account key of the native space is `account_key_bytes` long and
+/// the account key of the ethereum space is followed by the space type.
+pub(super) fn may_have_account_key(
+    key_prefix: &CompressedPathRaw, space: Space, account_key_bytes: usize,
+) -> bool {
+    let path_steps = key_prefix.path_steps() as usize;
+    let account_key_steps = account_key_bytes * 2;
+    match space {
+        Space::Native => path_steps <= account_key_steps,
+        Space::Ethereum => {
+            path_steps <= account_key_steps + 2
+                && (account_key_steps..path_steps).all(|i| {
+                    nibble_at(key_prefix.path_slice(), i)
+                        == nibble_at(
+                            StorageKeyWithSpace::EVM_SPACE_TYPE,
+                            i - account_key_steps,
+                        )
+                })
+        }
+    }
+}
+
+/// Compare `key_prefix` with the same number of leading nibbles of `key`,
+/// where `key_prefix` is greater if `key` is its proper prefix.
+pub(super) fn cmp_key_prefix(
+    key_prefix: &CompressedPathRaw, key: &[u8],
+) -> std::cmp::Ordering {
+    for i in 0..key_prefix.path_steps() as usize {
+        if i >= key.len() * 2 {
+            return std::cmp::Ordering::Greater;
+        }
+        match nibble_at(key_prefix.path_slice(), i).cmp(&nibble_at(key, i)) {
+            std::cmp::Ordering::Equal => {}
+            ordering => return ordering,
+        }
+    }
+    std::cmp::Ordering::Equal
+}
+
+fn nibble_at(bytes: &[u8], index: usize) -> u8 {
+    if index % 2 == 0 {
+        CompressedPathRaw::first_nibble(bytes[index / 2])
+    } else {
+        CompressedPathRaw::second_nibble(bytes[index / 2])
+    }
 }
 
 use crate::{
@@ -958,7 +1331,8 @@ use crate::{
         errors::*,
         merkle_patricia_trie::{
             mpt_cursor::{BasicPathNode, CursorOpenPathTerminal, MptCursor},
-            KVInserter, MptKeyValue, TrieProof, VanillaChildrenTable,
+            CompressedPathRaw, CompressedPathTrait, KVInserter, MptKeyValue,
+            TrieProof, VanillaChildrenTable,
         },
         node_merkle_proof::NodeMerkleProof,
         state_manager::*,
@@ -970,12 +1344,12 @@ use crate::{
     StorageRootProof,
 };
 use mazze_internal_common::{StateRootAuxInfo, StateRootWithAuxInfo};
-use mazze_types::AddressWithSpace;
+use mazze_types::{AddressWithSpace, Space};
 use fallible_iterator::FallibleIterator;
 use primitives::{
     DeltaMptKeyPadding, EpochId, MerkleHash, MptValue, NodeMerkleTriplet,
     SkipInputCheck, StateRoot, StaticBool, StorageKey, StorageKeyWithSpace,
-    StorageRoot, MERKLE_NULL_NODE, NULL_EPOCH,
+    StorageRoot, DELTA_MPT_ACCOUNT_KEYPART_BYTES, MERKLE_NULL_NODE, NULL_EPOCH,
 };
 use rustc_hex::ToHex;
 use std::{
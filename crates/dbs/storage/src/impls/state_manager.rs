@@ -1,7 +1,7 @@
 
 
 pub type DeltaDbManager = DeltaDbManagerRocksdb;
-pub type SnapshotDbManager = SnapshotDbManagerSqlite;
+pub type SnapshotDbManager = SnapshotDbManagerByBackend;
 pub type SnapshotDb = <SnapshotDbManager as SnapshotDbManagerTrait>::SnapshotDb;
 
 pub struct StateTrees {
@@ -35,6 +35,9 @@ pub struct StateTrees {
 pub struct StateManager {
     storage_manager: Arc<StorageManager>,
     single_mpt_storage_manager: Option<Arc<SingleMptStorageManager>>,
+    /// The remote chain which the states are forked from, in dev mode.
+    #[ignore_malloc_size_of = "insignificant"]
+    fork: Option<Arc<ForkCache>>,
     pub number_committed_nodes: AtomicUsize,
 }
 
@@ -57,6 +60,7 @@ impl StateManager {
                 conf.single_mpt_cache_start_size,
                 conf.single_mpt_cache_size,
                 conf.single_mpt_slab_idle_size,
+                conf.read_only,
             ))
         } else {
             None
@@ -66,10 +70,39 @@ impl StateManager {
         Ok(Self {
             storage_manager,
             single_mpt_storage_manager,
+            fork: None,
             number_committed_nodes: Default::default(),
         })
     }
 
+    /// Build the states on top of the state of a remote chain. The entries
+    /// missing from the local state are fetched from `source` on demand.
+    pub fn set_fork_source(
+        &mut self, source: Box<dyn ForkSource>,
+    ) -> Result<()> {
+        let (_, deletions_db) = KvdbSqlite::open_or_create(
+            self.storage_manager
+                .storage_conf
+                .path_storage_dir
+                .join("fork_deletions_db"),
+            FORK_DELETIONS_KVDB_STATEMENTS.clone(),
+            false, /* unsafe_mode */
+        )?;
+        self.fork = Some(Arc::new(ForkCache::new(source, deletions_db)?));
+        Ok(())
+    }
+
+    pub fn is_forked(&self) -> bool { self.fork.is_some() }
+
+    fn with_fork(
+        &self, state: Box<dyn StateTrait>, parent: EpochId,
+    ) -> Box<dyn StateTrait> {
+        match &self.fork {
+            Some(fork) => Box::new(ForkState::new(state, fork.clone(), parent)),
+            None => state,
+        }
+    }
+
     pub fn log_usage(&self) {
         self.storage_manager.log_usage();
         debug!(
@@ -714,12 +747,62 @@ impl StateManager {
     pub fn config(&self) -> &StorageConfiguration {
         &self.storage_manager.storage_conf
     }
+
+    /// Convert the snapshots kept in `source_backend` into the snapshot db
+    /// backend of `conf`. See `StorageManager::convert_snapshot_db_backend`.
+    pub fn convert_snapshot_db_backend(
+        conf: &StorageConfiguration, source_backend: SnapshotDbBackend,
+    ) -> Result<usize> {
+        StorageManager::convert_snapshot_db_backend(conf, source_backend)
+    }
 }
 
 impl StateManagerTrait for StateManager {
     fn get_state_no_commit(
         self: &Arc<Self>, state_index: StateIndex, try_open: bool,
         space: Option<Space>,
+    ) -> Result<Option<Box<dyn StateTrait>>> {
+        let epoch_id = state_index.epoch_id;
+        Ok(self
+            .get_local_state_no_commit(state_index, try_open, space)?
+            .map(|state| self.with_fork(state, epoch_id)))
+    }
+
+    fn get_state_for_genesis_write(self: &Arc<Self>) -> Box<dyn StateTrait> {
+        let state = self.get_state_for_genesis_write_inner();
+        if self.single_mpt_storage_manager.is_none() {
+            return Box::new(state);
+        }
+        let single_mpt_storage_manager =
+            self.single_mpt_storage_manager.as_ref().unwrap();
+        let single_mpt_state = single_mpt_storage_manager
+            .get_state_for_genesis()
+            .expect("single_mpt genesis initialize error");
+        Box::new(ReplicatedState::new(
+            state,
+            single_mpt_state,
+            single_mpt_storage_manager.get_state_filter(),
+        ))
+    }
+
+    fn get_state_for_next_epoch(
+        self: &Arc<Self>, parent_epoch_id: StateIndex,
+        recover_mpt_during_construct_main_state: bool,
+    ) -> Result<Option<Box<dyn StateTrait>>> {
+        let parent = parent_epoch_id.epoch_id;
+        Ok(self
+            .get_local_state_for_next_epoch(
+                parent_epoch_id,
+                recover_mpt_during_construct_main_state,
+            )?
+            .map(|state| self.with_fork(state, parent)))
+    }
+}
+
+impl StateManager {
+    fn get_local_state_no_commit(
+        self: &Arc<Self>, state_index: StateIndex, try_open: bool,
+        space: Option<Space>,
     ) -> Result<Option<Box<dyn StateTrait>>> {
         let maybe_state_trees =
             self.get_state_trees(&state_index, try_open, false);
@@ -758,23 +841,6 @@ impl StateManagerTrait for StateManager {
         }
     }
 
-    fn get_state_for_genesis_write(self: &Arc<Self>) -> Box<dyn StateTrait> {
-        let state = self.get_state_for_genesis_write_inner();
-        if self.single_mpt_storage_manager.is_none() {
-            return Box::new(state);
-        }
-        let single_mpt_storage_manager =
-            self.single_mpt_storage_manager.as_ref().unwrap();
-        let single_mpt_state = single_mpt_storage_manager
-            .get_state_for_genesis()
-            .expect("single_mpt genesis initialize error");
-        Box::new(ReplicatedState::new(
-            state,
-            single_mpt_state,
-            single_mpt_storage_manager.get_state_filter(),
-        ))
-    }
-
     // Currently we use epoch number to decide whether or not to
     // start a new delta trie. The value of parent_epoch_id is only
     // known after the computation is done.
@@ -789,7 +855,7 @@ impl StateManagerTrait for StateManager {
     //
     // Due to the complexity of the latter approach, we stay with the
     // simple approach.
-    fn get_state_for_next_epoch(
+    fn get_local_state_for_next_epoch(
         self: &Arc<Self>, parent_epoch_id: StateIndex,
         recover_mpt_during_construct_main_state: bool,
     ) -> Result<Option<Box<dyn StateTrait>>> {
@@ -840,14 +906,31 @@ impl StateManagerTrait for StateManager {
     }
 }
 
+lazy_static! {
+    static ref FORK_DELETIONS_KVDB_STATEMENTS: Arc<KvdbSqliteStatements> =
+        Arc::new(
+            KvdbSqliteStatements::make_statements(
+                &["value"],
+                &["BLOB"],
+                &storage_dir::FORK_DELETIONS_DB_NAME,
+                false
+            )
+            .unwrap()
+        );
+}
+
 use crate::{
     impls::{
         delta_mpt::*,
         errors::*,
+        fork_state::{ForkCache, ForkSource, ForkState},
         replicated_state::ReplicatedState,
         storage_db::{
             delta_db_manager_rocksdb::DeltaDbManagerRocksdb,
-            snapshot_db_manager_sqlite::SnapshotDbManagerSqlite,
+            kvdb_sqlite::KvdbSqliteStatements,
+            snapshot_db_by_backend::{
+                SnapshotDbBackend, SnapshotDbManagerByBackend,
+            },
         },
         storage_manager::{
             single_mpt_storage_manager::SingleMptStorageManager,
@@ -857,8 +940,9 @@ use crate::{
     state::*,
     state_manager::*,
     storage_db::*,
+    storage_dir,
     utils::guarded_value::GuardedValue,
-    StorageConfiguration,
+    KvdbSqlite, StorageConfiguration,
 };
 use mazze_types::Space;
 use malloc_size_of_derive::MallocSizeOf as MallocSizeOfDerive;
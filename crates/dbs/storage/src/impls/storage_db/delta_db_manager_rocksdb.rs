@@ -2,6 +2,7 @@
 
 pub struct DeltaDbManagerRocksdb {
     delta_db_path: PathBuf,
+    read_only: bool,
     creation_mutex: Mutex<()>,
 }
 
@@ -17,18 +18,29 @@ impl DeltaDbManagerRocksdb {
         },
         columns: 1,
         disable_wal: false,
+        read_only: false,
     };
 
-    pub fn new(delta_db_path: PathBuf) -> Result<DeltaDbManagerRocksdb> {
+    pub fn new(
+        delta_db_path: PathBuf, read_only: bool,
+    ) -> Result<DeltaDbManagerRocksdb> {
         if !delta_db_path.exists() {
             fs::create_dir_all(delta_db_path.clone())?;
         }
 
         Ok(Self {
             delta_db_path,
+            read_only,
             creation_mutex: Default::default(),
         })
     }
+
+    fn rocksdb_config(&self) -> DatabaseConfig {
+        DatabaseConfig {
+            read_only: self.read_only,
+            ..Self::ROCKSDB_CONFIG
+        }
+    }
 }
 
 impl DeltaDbManagerTrait for DeltaDbManagerRocksdb {
@@ -54,7 +66,7 @@ impl DeltaDbManagerTrait for DeltaDbManagerRocksdb {
         } else {
             Ok(KvdbRocksdb {
                 kvdb: Arc::new(Database::open(
-                    &Self::ROCKSDB_CONFIG,
+                    &self.rocksdb_config(),
                     path.to_str().unwrap(),
                 )?),
                 col: 0,
@@ -69,7 +81,7 @@ impl DeltaDbManagerTrait for DeltaDbManagerRocksdb {
         if path.exists() {
             Ok(Some(KvdbRocksdb {
                 kvdb: Arc::new(Database::open(
-                    &Self::ROCKSDB_CONFIG,
+                    &self.rocksdb_config(),
                     path.to_str().unwrap(),
                 )?),
                 col: 0,
@@ -0,0 +1,1014 @@
+/// The db in which the snapshots are kept.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SnapshotDbBackend {
+    Sqlite,
+    Rocksdb,
+}
+
+impl Default for SnapshotDbBackend {
+    fn default() -> Self { SnapshotDbBackend::Sqlite }
+}
+
+impl FromStr for SnapshotDbBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "sqlite" => Ok(SnapshotDbBackend::Sqlite),
+            "rocksdb" => Ok(SnapshotDbBackend::Rocksdb),
+            _ => Err(format!(
+                "unknown snapshot db backend {}, expected sqlite or rocksdb",
+                s
+            )),
+        }
+    }
+}
+
+/// The snapshot db manager of the configured snapshot db backend.
+pub enum SnapshotDbManagerByBackend {
+    Sqlite(SnapshotDbManagerSqlite),
+    Rocksdb(SnapshotDbManagerRocksdb),
+}
+
+pub enum SnapshotDbByBackend {
+    Sqlite(SnapshotDbSqlite),
+    Rocksdb(SnapshotDbRocksdb),
+}
+
+pub enum SnapshotDbWriteByBackend {
+    Sqlite(SnapshotDbWriteable),
+    Rocksdb(SnapshotDbRocksdb),
+}
+
+pub enum SnapshotMptByBackend<SqliteMpt, RocksdbMpt> {
+    Sqlite(SqliteMpt),
+    Rocksdb(RocksdbMpt),
+}
+
+pub enum SnapshotKvdbByBackend<ValueType> {
+    Sqlite(KvdbSqliteSharded<ValueType>),
+    Rocksdb(SnapshotRocksdbColumn<ValueType>),
+}
+
+pub struct SnapshotKvdbByBackendIteratorTag();
+
+impl SnapshotDbManagerByBackend {
+    pub fn new(
+        backend: SnapshotDbBackend, snapshot_path: PathBuf,
+        max_open_snapshots: u16, use_isolated_db_for_mpt_table: bool,
+        use_isolated_db_for_mpt_table_height: Option<u64>,
+        era_epoch_count: u64,
+    ) -> Result<Self> {
+        match backend {
+            SnapshotDbBackend::Sqlite => {
+                Ok(SnapshotDbManagerByBackend::Sqlite(
+                    SnapshotDbManagerSqlite::new(
+                        snapshot_path,
+                        max_open_snapshots,
+                        use_isolated_db_for_mpt_table,
+                        use_isolated_db_for_mpt_table_height,
+                        era_epoch_count,
+                    )?,
+                ))
+            }
+            SnapshotDbBackend::Rocksdb => {
+                if use_isolated_db_for_mpt_table {
+                    bail!("The rocksdb snapshot db keeps the MPT in the snapshot db, use_isolated_db_for_mpt_table is not supported.");
+                }
+                Ok(SnapshotDbManagerByBackend::Rocksdb(
+                    SnapshotDbManagerRocksdb::new(
+                        snapshot_path,
+                        max_open_snapshots,
+                    )?,
+                ))
+            }
+        }
+    }
+
+    pub fn backend(&self) -> SnapshotDbBackend {
+        match self {
+            SnapshotDbManagerByBackend::Sqlite(_) => SnapshotDbBackend::Sqlite,
+            SnapshotDbManagerByBackend::Rocksdb(_) => {
+                SnapshotDbBackend::Rocksdb
+            }
+        }
+    }
+
+    pub fn update_latest_snapshot_id(&self, snapshot_id: EpochId, height: u64) {
+        if let SnapshotDbManagerByBackend::Sqlite(manager) = self {
+            manager.update_latest_snapshot_id(snapshot_id, height);
+        }
+    }
+
+    pub fn clean_snapshot_epoch_id_before_recovered(&self) {
+        if let SnapshotDbManagerByBackend::Sqlite(manager) = self {
+            manager.clean_snapshot_epoch_id_before_recovered();
+        }
+    }
+
+    pub fn set_reconstruct_snapshot_id(
+        &self, reconstruct_main: Option<EpochId>,
+    ) {
+        match self {
+            SnapshotDbManagerByBackend::Sqlite(manager) => {
+                manager.set_reconstruct_snapshot_id(reconstruct_main)
+            }
+            SnapshotDbManagerByBackend::Rocksdb(manager) => {
+                manager.set_reconstruct_snapshot_id(reconstruct_main)
+            }
+        }
+    }
+
+    pub fn recreate_latest_mpt_snapshot(&self) -> Result<()> {
+        match self {
+            SnapshotDbManagerByBackend::Sqlite(manager) => {
+                manager.recreate_latest_mpt_snapshot()
+            }
+            SnapshotDbManagerByBackend::Rocksdb(_) => Ok(()),
+        }
+    }
+}
+
+impl SnapshotDbManagerTrait for SnapshotDbManagerByBackend {
+    type SnapshotDb = SnapshotDbByBackend;
+    type SnapshotDbWrite = SnapshotDbWriteByBackend;
+
+    fn get_snapshot_dir(&self) -> &Path {
+        match self {
+            SnapshotDbManagerByBackend::Sqlite(manager) => {
+                manager.get_snapshot_dir()
+            }
+            SnapshotDbManagerByBackend::Rocksdb(manager) => {
+                manager.get_snapshot_dir()
+            }
+        }
+    }
+
+    fn get_snapshot_db_name(&self, snapshot_epoch_id: &EpochId) -> String {
+        match self {
+            SnapshotDbManagerByBackend::Sqlite(manager) => {
+                manager.get_snapshot_db_name(snapshot_epoch_id)
+            }
+            SnapshotDbManagerByBackend::Rocksdb(manager) => {
+                manager.get_snapshot_db_name(snapshot_epoch_id)
+            }
+        }
+    }
+
+    fn get_snapshot_db_path(&self, snapshot_epoch_id: &EpochId) -> PathBuf {
+        match self {
+            SnapshotDbManagerByBackend::Sqlite(manager) => {
+                manager.get_snapshot_db_path(snapshot_epoch_id)
+            }
+            SnapshotDbManagerByBackend::Rocksdb(manager) => {
+                manager.get_snapshot_db_path(snapshot_epoch_id)
+            }
+        }
+    }
+
+    fn get_mpt_snapshot_dir(&self) -> &Path {
+        match self {
+            SnapshotDbManagerByBackend::Sqlite(manager) => {
+                manager.get_mpt_snapshot_dir()
+            }
+            SnapshotDbManagerByBackend::Rocksdb(manager) => {
+                manager.get_mpt_snapshot_dir()
+            }
+        }
+    }
+
+    fn get_latest_mpt_snapshot_db_name(&self) -> String {
+        match self {
+            SnapshotDbManagerByBackend::Sqlite(manager) => {
+                manager.get_latest_mpt_snapshot_db_name()
+            }
+            SnapshotDbManagerByBackend::Rocksdb(manager) => {
+                manager.get_latest_mpt_snapshot_db_name()
+            }
+        }
+    }
+
+    fn recovery_latest_mpt_snapshot_from_checkpoint(
+        &self, snapshot_epoch_id: &EpochId,
+        before_era_main_hash: Option<EpochId>,
+    ) -> Result<()> {
+        match self {
+            SnapshotDbManagerByBackend::Sqlite(manager) => manager
+                .recovery_latest_mpt_snapshot_from_checkpoint(
+                    snapshot_epoch_id,
+                    before_era_main_hash,
+                ),
+            SnapshotDbManagerByBackend::Rocksdb(manager) => manager
+                .recovery_latest_mpt_snapshot_from_checkpoint(
+                    snapshot_epoch_id,
+                    before_era_main_hash,
+                ),
+        }
+    }
+
+    fn create_mpt_snapshot_from_latest(
+        &self, new_snapshot_epoch_id: &EpochId,
+    ) -> Result<()> {
+        match self {
+            SnapshotDbManagerByBackend::Sqlite(manager) => {
+                manager.create_mpt_snapshot_from_latest(new_snapshot_epoch_id)
+            }
+            SnapshotDbManagerByBackend::Rocksdb(manager) => {
+                manager.create_mpt_snapshot_from_latest(new_snapshot_epoch_id)
+            }
+        }
+    }
+
+    fn get_epoch_id_from_snapshot_db_name(
+        &self, snapshot_db_name: &str,
+    ) -> Result<EpochId> {
+        match self {
+            SnapshotDbManagerByBackend::Sqlite(manager) => {
+                manager.get_epoch_id_from_snapshot_db_name(snapshot_db_name)
+            }
+            SnapshotDbManagerByBackend::Rocksdb(manager) => {
+                manager.get_epoch_id_from_snapshot_db_name(snapshot_db_name)
+            }
+        }
+    }
+
+    fn try_get_new_snapshot_epoch_from_temp_path(
+        &self, dir_name: &str,
+    ) -> Option<EpochId> {
+        match self {
+            SnapshotDbManagerByBackend::Sqlite(manager) => {
+                SnapshotDbManagerTrait::try_get_new_snapshot_epoch_from_temp_path(
+                    manager, dir_name,
+                )
+            }
+            SnapshotDbManagerByBackend::Rocksdb(manager) => {
+                manager.try_get_new_snapshot_epoch_from_temp_path(dir_name)
+            }
+        }
+    }
+
+    fn try_get_new_snapshot_epoch_from_mpt_temp_path(
+        &self, dir_name: &str,
+    ) -> Option<EpochId> {
+        match self {
+            SnapshotDbManagerByBackend::Sqlite(manager) => {
+                SnapshotDbManagerTrait::try_get_new_snapshot_epoch_from_mpt_temp_path(
+                    manager, dir_name,
+                )
+            }
+            SnapshotDbManagerByBackend::Rocksdb(manager) => manager
+                .try_get_new_snapshot_epoch_from_mpt_temp_path(dir_name),
+        }
+    }
+
+    fn new_snapshot_by_merging<'m>(
+        &self, old_snapshot_epoch_id: &EpochId, snapshot_epoch_id: EpochId,
+        delta_mpt: DeltaMptIterator, in_progress_snapshot_info: SnapshotInfo,
+        snapshot_info_map_rwlock: &'m RwLock<PersistedSnapshotInfoMap>,
+        new_epoch_height: u64, recover_mpt_with_kv_snapshot_exist: bool,
+    ) -> Result<(RwLockWriteGuard<'m, PersistedSnapshotInfoMap>, SnapshotInfo)>
+    {
+        match self {
+            SnapshotDbManagerByBackend::Sqlite(manager) => manager
+                .new_snapshot_by_merging(
+                    old_snapshot_epoch_id,
+                    snapshot_epoch_id,
+                    delta_mpt,
+                    in_progress_snapshot_info,
+                    snapshot_info_map_rwlock,
+                    new_epoch_height,
+                    recover_mpt_with_kv_snapshot_exist,
+                ),
+            SnapshotDbManagerByBackend::Rocksdb(manager) => manager
+                .new_snapshot_by_merging(
+                    old_snapshot_epoch_id,
+                    snapshot_epoch_id,
+                    delta_mpt,
+                    in_progress_snapshot_info,
+                    snapshot_info_map_rwlock,
+                    new_epoch_height,
+                    recover_mpt_with_kv_snapshot_exist,
+                ),
+        }
+    }
+
+    fn get_snapshot_by_epoch_id(
+        &self, snapshot_epoch_id: &EpochId, try_open: bool,
+        open_mpt_snapshot: bool,
+    ) -> Result<Option<Self::SnapshotDb>> {
+        match self {
+            SnapshotDbManagerByBackend::Sqlite(manager) => Ok(manager
+                .get_snapshot_by_epoch_id(
+                    snapshot_epoch_id,
+                    try_open,
+                    open_mpt_snapshot,
+                )?
+                .map(SnapshotDbByBackend::Sqlite)),
+            SnapshotDbManagerByBackend::Rocksdb(manager) => Ok(manager
+                .get_snapshot_by_epoch_id(
+                    snapshot_epoch_id,
+                    try_open,
+                    open_mpt_snapshot,
+                )?
+                .map(SnapshotDbByBackend::Rocksdb)),
+        }
+    }
+
+    fn destroy_snapshot(&self, snapshot_epoch_id: &EpochId) -> Result<()> {
+        match self {
+            SnapshotDbManagerByBackend::Sqlite(manager) => {
+                manager.destroy_snapshot(snapshot_epoch_id)
+            }
+            SnapshotDbManagerByBackend::Rocksdb(manager) => {
+                manager.destroy_snapshot(snapshot_epoch_id)
+            }
+        }
+    }
+
+    fn new_temp_snapshot_for_full_sync(
+        &self, snapshot_epoch_id: &EpochId, merkle_root: &MerkleHash,
+        new_epoch_height: u64,
+    ) -> Result<Self::SnapshotDbWrite> {
+        match self {
+            SnapshotDbManagerByBackend::Sqlite(manager) => {
+                Ok(SnapshotDbWriteByBackend::Sqlite(
+                    manager.new_temp_snapshot_for_full_sync(
+                        snapshot_epoch_id,
+                        merkle_root,
+                        new_epoch_height,
+                    )?,
+                ))
+            }
+            SnapshotDbManagerByBackend::Rocksdb(manager) => {
+                Ok(SnapshotDbWriteByBackend::Rocksdb(
+                    manager.new_temp_snapshot_for_full_sync(
+                        snapshot_epoch_id,
+                        merkle_root,
+                        new_epoch_height,
+                    )?,
+                ))
+            }
+        }
+    }
+
+    fn finalize_full_sync_snapshot<'m>(
+        &self, snapshot_epoch_id: &EpochId, merkle_root: &MerkleHash,
+        snapshot_info_map_rwlock: &'m RwLock<PersistedSnapshotInfoMap>,
+    ) -> Result<RwLockWriteGuard<'m, PersistedSnapshotInfoMap>> {
+        match self {
+            SnapshotDbManagerByBackend::Sqlite(manager) => manager
+                .finalize_full_sync_snapshot(
+                    snapshot_epoch_id,
+                    merkle_root,
+                    snapshot_info_map_rwlock,
+                ),
+            SnapshotDbManagerByBackend::Rocksdb(manager) => manager
+                .finalize_full_sync_snapshot(
+                    snapshot_epoch_id,
+                    merkle_root,
+                    snapshot_info_map_rwlock,
+                ),
+        }
+    }
+}
+
+impl KeyValueDbTypes for SnapshotDbByBackend {
+    type ValueType = Box<[u8]>;
+}
+
+impl KeyValueDbTraitRead for SnapshotDbByBackend {
+    fn get(&self, key: &[u8]) -> Result<Option<Box<[u8]>>> {
+        match self {
+            SnapshotDbByBackend::Sqlite(db) => db.get(key),
+            SnapshotDbByBackend::Rocksdb(db) => db.get(key),
+        }
+    }
+}
+
+impl KeyValueDbTraitOwnedRead for SnapshotDbByBackend {
+    fn get_mut(&mut self, key: &[u8]) -> Result<Option<Box<[u8]>>> {
+        match self {
+            SnapshotDbByBackend::Sqlite(db) => db.get_mut(key),
+            SnapshotDbByBackend::Rocksdb(db) => db.get_mut(key),
+        }
+    }
+}
+
+impl KeyValueDbTraitSingleWriter for SnapshotDbByBackend {
+    fn delete(&mut self, key: &[u8]) -> Result<Option<Option<Box<[u8]>>>> {
+        match self {
+            SnapshotDbByBackend::Sqlite(db) => db.delete(key),
+            SnapshotDbByBackend::Rocksdb(db) => db.delete(key),
+        }
+    }
+
+    fn put(
+        &mut self, key: &[u8], value: &[u8],
+    ) -> Result<Option<Option<Box<[u8]>>>> {
+        match self {
+            SnapshotDbByBackend::Sqlite(db) => db.put(key, value),
+            SnapshotDbByBackend::Rocksdb(db) => db.put(key, value),
+        }
+    }
+}
+
+impl<'db> OpenSnapshotMptTrait<'db> for SnapshotDbByBackend {
+    type SnapshotDbAsOwnedType = SnapshotMptByBackend<
+        <SnapshotDbSqlite as OpenSnapshotMptTrait<'db>>::SnapshotDbAsOwnedType,
+        SnapshotMptRocksdb,
+    >;
+    type SnapshotDbBorrowMutType = SnapshotMptByBackend<
+        <SnapshotDbSqlite as OpenSnapshotMptTrait<'db>>::SnapshotDbBorrowMutType,
+        SnapshotMptRocksdb,
+    >;
+    type SnapshotDbBorrowSharedType = SnapshotMptByBackend<
+        <SnapshotDbSqlite as OpenSnapshotMptTrait<'db>>::SnapshotDbBorrowSharedType,
+        SnapshotMptRocksdb,
+    >;
+
+    fn open_snapshot_mpt_owned(
+        &'db mut self,
+    ) -> Result<Self::SnapshotDbBorrowMutType> {
+        match self {
+            SnapshotDbByBackend::Sqlite(db) => {
+                Ok(SnapshotMptByBackend::Sqlite(db.open_snapshot_mpt_owned()?))
+            }
+            SnapshotDbByBackend::Rocksdb(db) => {
+                Ok(SnapshotMptByBackend::Rocksdb(
+                    OpenSnapshotMptTrait::open_snapshot_mpt_owned(db)?,
+                ))
+            }
+        }
+    }
+
+    fn open_snapshot_mpt_as_owned(
+        &'db self,
+    ) -> Result<Self::SnapshotDbAsOwnedType> {
+        match self {
+            SnapshotDbByBackend::Sqlite(db) => Ok(
+                SnapshotMptByBackend::Sqlite(db.open_snapshot_mpt_as_owned()?),
+            ),
+            SnapshotDbByBackend::Rocksdb(db) => Ok(
+                SnapshotMptByBackend::Rocksdb(db.open_snapshot_mpt_as_owned()?),
+            ),
+        }
+    }
+
+    fn open_snapshot_mpt_shared(
+        &'db self,
+    ) -> Result<Self::SnapshotDbBorrowSharedType> {
+        match self {
+            SnapshotDbByBackend::Sqlite(db) => {
+                Ok(SnapshotMptByBackend::Sqlite(db.open_snapshot_mpt_shared()?))
+            }
+            SnapshotDbByBackend::Rocksdb(db) => Ok(
+                SnapshotMptByBackend::Rocksdb(db.open_snapshot_mpt_shared()?),
+            ),
+        }
+    }
+}
+
+impl SnapshotDbByBackend {
+    pub fn dumped_delta_kv_set_keys_iterator(
+        &self,
+    ) -> Result<SnapshotKvdbByBackend<Box<[u8]>>> {
+        match self {
+            SnapshotDbByBackend::Sqlite(db) => {
+                Ok(SnapshotKvdbByBackend::Sqlite(
+                    db.dumped_delta_kv_set_keys_iterator()?,
+                ))
+            }
+            SnapshotDbByBackend::Rocksdb(db) => {
+                Ok(SnapshotKvdbByBackend::Rocksdb(
+                    db.dumped_delta_kv_set_keys_iterator()?,
+                ))
+            }
+        }
+    }
+
+    pub fn dumped_delta_kv_delete_keys_iterator(
+        &self,
+    ) -> Result<SnapshotKvdbByBackend<()>> {
+        match self {
+            SnapshotDbByBackend::Sqlite(db) => {
+                Ok(SnapshotKvdbByBackend::Sqlite(
+                    db.dumped_delta_kv_delete_keys_iterator()?,
+                ))
+            }
+            SnapshotDbByBackend::Rocksdb(db) => {
+                Ok(SnapshotKvdbByBackend::Rocksdb(
+                    db.dumped_delta_kv_delete_keys_iterator()?,
+                ))
+            }
+        }
+    }
+}
+
+impl SnapshotDbTrait for SnapshotDbByBackend {
+    type SnapshotKvdbIterTraitTag = SnapshotKvdbByBackendIteratorTag;
+    type SnapshotKvdbIterType = SnapshotKvdbByBackend<Box<[u8]>>;
+
+    /// The null snapshot is empty for every backend.
+    fn get_null_snapshot() -> Self {
+        SnapshotDbByBackend::Sqlite(SnapshotDbSqlite::get_null_snapshot())
+    }
+
+    /// The snapshot db is opened by the snapshot db manager of the backend.
+    fn open(
+        _snapshot_path: &Path, _readonly: bool,
+        _already_open_snapshots: &AlreadyOpenSnapshots<Self>,
+        _open_semaphore: &Arc<Semaphore>,
+    ) -> Result<SnapshotDbByBackend> {
+        unreachable!()
+    }
+
+    /// The snapshot db is created by the snapshot db manager of the backend.
+    fn create(
+        _snapshot_path: &Path,
+        _already_open_snapshots: &AlreadyOpenSnapshots<Self>,
+        _open_semaphore: &Arc<Semaphore>, _mpt_table_in_current_db: bool,
+    ) -> Result<SnapshotDbByBackend> {
+        unreachable!()
+    }
+
+    fn direct_merge(
+        &mut self, _old_snapshot_db: Option<&Arc<SnapshotDbByBackend>>,
+        _mpt_snapshot: &mut Option<SnapshotMptDbSqlite>,
+        _recover_mpt_with_kv_snapshot_exist: bool,
+        _in_reconstruct_snapshot_state: bool,
+    ) -> Result<MerkleHash> {
+        unreachable!()
+    }
+
+    fn copy_and_merge(
+        &mut self, _old_snapshot_db: &Arc<SnapshotDbByBackend>,
+        _mpt_snapshot_db: &mut Option<SnapshotMptDbSqlite>,
+        _in_reconstruct_snapshot_state: bool,
+    ) -> Result<MerkleHash> {
+        unreachable!()
+    }
+
+    fn start_transaction(&mut self) -> Result<()> {
+        match self {
+            SnapshotDbByBackend::Sqlite(db) => {
+                SnapshotDbTrait::start_transaction(db)
+            }
+            SnapshotDbByBackend::Rocksdb(db) => {
+                SnapshotDbTrait::start_transaction(db)
+            }
+        }
+    }
+
+    fn commit_transaction(&mut self) -> Result<()> {
+        match self {
+            SnapshotDbByBackend::Sqlite(db) => {
+                SnapshotDbTrait::commit_transaction(db)
+            }
+            SnapshotDbByBackend::Rocksdb(db) => {
+                SnapshotDbTrait::commit_transaction(db)
+            }
+        }
+    }
+
+    fn is_mpt_table_in_current_db(&self) -> bool {
+        match self {
+            SnapshotDbByBackend::Sqlite(db) => db.is_mpt_table_in_current_db(),
+            SnapshotDbByBackend::Rocksdb(db) => db.is_mpt_table_in_current_db(),
+        }
+    }
+
+    fn snapshot_kv_iterator(
+        &self,
+    ) -> Result<
+        Wrap<
+            Self::SnapshotKvdbIterType,
+            dyn KeyValueDbIterableTrait<
+                MptKeyValue,
+                [u8],
+                SnapshotKvdbByBackendIteratorTag,
+            >,
+        >,
+    > {
+        match self {
+            SnapshotDbByBackend::Sqlite(db) => {
+                Ok(Wrap(SnapshotKvdbByBackend::Sqlite(
+                    db.snapshot_kv_iterator()?.take(),
+                )))
+            }
+            SnapshotDbByBackend::Rocksdb(db) => {
+                Ok(Wrap(SnapshotKvdbByBackend::Rocksdb(
+                    db.snapshot_kv_iterator()?.take(),
+                )))
+            }
+        }
+    }
+}
+
+impl KeyValueDbTypes for SnapshotDbWriteByBackend {
+    type ValueType = Box<[u8]>;
+}
+
+impl SnapshotDbWriteableTrait for SnapshotDbWriteByBackend {
+    type SnapshotDbBorrowMutType = SnapshotMptByBackend<
+        <SnapshotDbWriteable as SnapshotDbWriteableTrait>::SnapshotDbBorrowMutType,
+        SnapshotMptRocksdb,
+    >;
+
+    fn start_transaction(&mut self) -> Result<()> {
+        match self {
+            SnapshotDbWriteByBackend::Sqlite(db) => db.start_transaction(),
+            SnapshotDbWriteByBackend::Rocksdb(db) => {
+                SnapshotDbWriteableTrait::start_transaction(db)
+            }
+        }
+    }
+
+    fn commit_transaction(&mut self) -> Result<()> {
+        match self {
+            SnapshotDbWriteByBackend::Sqlite(db) => db.commit_transaction(),
+            SnapshotDbWriteByBackend::Rocksdb(db) => {
+                SnapshotDbWriteableTrait::commit_transaction(db)
+            }
+        }
+    }
+
+    fn put_kv(
+        &mut self, key: &[u8], value: &[u8],
+    ) -> Result<Option<Option<Box<[u8]>>>> {
+        match self {
+            SnapshotDbWriteByBackend::Sqlite(db) => db.put_kv(key, value),
+            SnapshotDbWriteByBackend::Rocksdb(db) => db.put_kv(key, value),
+        }
+    }
+
+    fn open_snapshot_mpt_owned(
+        &mut self,
+    ) -> Result<Self::SnapshotDbBorrowMutType> {
+        match self {
+            SnapshotDbWriteByBackend::Sqlite(db) => {
+                Ok(SnapshotMptByBackend::Sqlite(db.open_snapshot_mpt_owned()?))
+            }
+            SnapshotDbWriteByBackend::Rocksdb(db) => {
+                Ok(SnapshotMptByBackend::Rocksdb(
+                    SnapshotDbWriteableTrait::open_snapshot_mpt_owned(db)?,
+                ))
+            }
+        }
+    }
+}
+
+impl<SqliteMpt: SnapshotMptTraitRead, RocksdbMpt: SnapshotMptTraitRead>
+    SnapshotMptTraitRead for SnapshotMptByBackend<SqliteMpt, RocksdbMpt>
+{
+    fn get_merkle_root(&self) -> MerkleHash {
+        match self {
+            SnapshotMptByBackend::Sqlite(mpt) => mpt.get_merkle_root(),
+            SnapshotMptByBackend::Rocksdb(mpt) => mpt.get_merkle_root(),
+        }
+    }
+
+    fn load_node(
+        &mut self, path: &dyn CompressedPathTrait,
+    ) -> Result<Option<SnapshotMptNode>> {
+        match self {
+            SnapshotMptByBackend::Sqlite(mpt) => mpt.load_node(path),
+            SnapshotMptByBackend::Rocksdb(mpt) => mpt.load_node(path),
+        }
+    }
+}
+
+impl<
+        SqliteMpt: SnapshotMptTraitReadAndIterate,
+        RocksdbMpt: SnapshotMptTraitReadAndIterate,
+    > SnapshotMptTraitReadAndIterate
+    for SnapshotMptByBackend<SqliteMpt, RocksdbMpt>
+{
+    fn iterate_subtree_trie_nodes_without_root(
+        &mut self, path: &dyn CompressedPathTrait,
+    ) -> Result<Box<dyn SnapshotMptIteraterTrait + '_>> {
+        match self {
+            SnapshotMptByBackend::Sqlite(mpt) => {
+                mpt.iterate_subtree_trie_nodes_without_root(path)
+            }
+            SnapshotMptByBackend::Rocksdb(mpt) => {
+                mpt.iterate_subtree_trie_nodes_without_root(path)
+            }
+        }
+    }
+}
+
+impl<SqliteMpt: SnapshotMptTraitRw, RocksdbMpt: SnapshotMptTraitRw>
+    SnapshotMptTraitRw for SnapshotMptByBackend<SqliteMpt, RocksdbMpt>
+{
+    fn delete_node(&mut self, path: &dyn CompressedPathTrait) -> Result<()> {
+        match self {
+            SnapshotMptByBackend::Sqlite(mpt) => mpt.delete_node(path),
+            SnapshotMptByBackend::Rocksdb(mpt) => mpt.delete_node(path),
+        }
+    }
+
+    fn write_node(
+        &mut self, path: &dyn CompressedPathTrait, trie_node: &SnapshotMptNode,
+    ) -> Result<()> {
+        match self {
+            SnapshotMptByBackend::Sqlite(mpt) => {
+                mpt.write_node(path, trie_node)
+            }
+            SnapshotMptByBackend::Rocksdb(mpt) => {
+                mpt.write_node(path, trie_node)
+            }
+        }
+    }
+}
+
+macro_rules! enable_KeyValueDbIterableTrait_for_SnapshotKvdbByBackend {
+    ($ValueType:ty) => {
+        impl<'a>
+            WrappedLifetimeFamily<
+                'a,
+                dyn FallibleIterator<
+                    Item = (Vec<u8>, $ValueType),
+                    Error = Error,
+                >,
+            >
+            for KvdbIterIterator<
+                (Vec<u8>, $ValueType),
+                [u8],
+                SnapshotKvdbByBackendIteratorTag,
+            >
+        {
+            type Out = Box<
+                dyn 'a
+                    + FallibleIterator<
+                        Item = (Vec<u8>, $ValueType),
+                        Error = Error,
+                    >,
+            >;
+        }
+
+        impl
+            WrappedTrait<
+                dyn FallibleIterator<
+                    Item = (Vec<u8>, $ValueType),
+                    Error = Error,
+                >,
+            >
+            for KvdbIterIterator<
+                (Vec<u8>, $ValueType),
+                [u8],
+                SnapshotKvdbByBackendIteratorTag,
+            >
+        {
+        }
+
+        impl
+            KeyValueDbIterableTrait<
+                (Vec<u8>, $ValueType),
+                [u8],
+                SnapshotKvdbByBackendIteratorTag,
+            > for SnapshotKvdbByBackend<$ValueType>
+        {
+            fn iter_range(
+                &mut self, lower_bound_incl: &[u8],
+                upper_bound_excl: Option<&[u8]>,
+            ) -> Result<
+                Wrap<
+                    KvdbIterIterator<
+                        (Vec<u8>, $ValueType),
+                        [u8],
+                        SnapshotKvdbByBackendIteratorTag,
+                    >,
+                    dyn FallibleIterator<
+                        Item = (Vec<u8>, $ValueType),
+                        Error = Error,
+                    >,
+                >,
+            > {
+                let iter: Box<
+                    dyn '_
+                        + FallibleIterator<
+                            Item = (Vec<u8>, $ValueType),
+                            Error = Error,
+                        >,
+                > = match self {
+                    SnapshotKvdbByBackend::Sqlite(kvdb) => Box::new(
+                        kvdb.iter_range(lower_bound_incl, upper_bound_excl)?
+                            .take(),
+                    ),
+                    SnapshotKvdbByBackend::Rocksdb(kvdb) => Box::new(
+                        kvdb.iter_range(lower_bound_incl, upper_bound_excl)?
+                            .take(),
+                    ),
+                };
+                Ok(Wrap(iter))
+            }
+
+            fn iter_range_excl(
+                &mut self, lower_bound_excl: &[u8], upper_bound_excl: &[u8],
+            ) -> Result<
+                Wrap<
+                    KvdbIterIterator<
+                        (Vec<u8>, $ValueType),
+                        [u8],
+                        SnapshotKvdbByBackendIteratorTag,
+                    >,
+                    dyn FallibleIterator<
+                        Item = (Vec<u8>, $ValueType),
+                        Error = Error,
+                    >,
+                >,
+            > {
+                let iter: Box<
+                    dyn '_
+                        + FallibleIterator<
+                            Item = (Vec<u8>, $ValueType),
+                            Error = Error,
+                        >,
+                > = match self {
+                    SnapshotKvdbByBackend::Sqlite(kvdb) => Box::new(
+                        kvdb.iter_range_excl(
+                            lower_bound_excl,
+                            upper_bound_excl,
+                        )?
+                        .take(),
+                    ),
+                    SnapshotKvdbByBackend::Rocksdb(kvdb) => Box::new(
+                        kvdb.iter_range_excl(
+                            lower_bound_excl,
+                            upper_bound_excl,
+                        )?
+                        .take(),
+                    ),
+                };
+                Ok(Wrap(iter))
+            }
+        }
+
+        impl
+            ElementSatisfy<
+                dyn KeyValueDbIterableTrait<
+                    (Vec<u8>, $ValueType),
+                    [u8],
+                    SnapshotKvdbByBackendIteratorTag,
+                >,
+            > for SnapshotKvdbByBackend<$ValueType>
+        {
+            fn to_constrain_object(
+                &self,
+            ) -> &(dyn KeyValueDbIterableTrait<
+                (Vec<u8>, $ValueType),
+                [u8],
+                SnapshotKvdbByBackendIteratorTag,
+            > + 'static) {
+                self
+            }
+
+            fn to_constrain_object_mut(
+                &mut self,
+            ) -> &mut (dyn KeyValueDbIterableTrait<
+                (Vec<u8>, $ValueType),
+                [u8],
+                SnapshotKvdbByBackendIteratorTag,
+            > + 'static) {
+                self
+            }
+        }
+
+        impl
+            WrappedLifetimeFamily<
+                '_,
+                dyn KeyValueDbIterableTrait<
+                    (Vec<u8>, $ValueType),
+                    [u8],
+                    SnapshotKvdbByBackendIteratorTag,
+                >,
+            > for SnapshotKvdbByBackend<$ValueType>
+        {
+            type Out = Self;
+        }
+
+        impl
+            WrappedTrait<
+                dyn KeyValueDbIterableTrait<
+                    (Vec<u8>, $ValueType),
+                    [u8],
+                    SnapshotKvdbByBackendIteratorTag,
+                >,
+            > for SnapshotKvdbByBackend<$ValueType>
+        {
+        }
+    };
+}
+
+enable_KeyValueDbIterableTrait_for_SnapshotKvdbByBackend!(Box<[u8]>);
+enable_KeyValueDbIterableTrait_for_SnapshotKvdbByBackend!(());
+
+/// Copy the snapshot of `snapshot_info` from the snapshot dbs of
+/// `source_manager` into the snapshot dbs of `target_manager`. The copy is
+/// written as a full sync snapshot, so that a failed conversion leaves only
+/// a temporary snapshot behind, which is removed on restart.
+pub fn convert_snapshot_db(
+    source_manager: &SnapshotDbManagerByBackend,
+    target_manager: &SnapshotDbManagerByBackend, snapshot_info: &SnapshotInfo,
+    snapshot_info_map_rwlock: &RwLock<PersistedSnapshotInfoMap>,
+) -> Result<()> {
+    let snapshot_epoch_id = snapshot_info.get_snapshot_epoch_id();
+    let merkle_root = snapshot_info.merkle_root;
+    let source_snapshot_db = source_manager
+        .get_snapshot_by_epoch_id(
+            snapshot_epoch_id,
+            /* try_open = */ false,
+            /* open_mpt_snapshot = */ true,
+        )?
+        .ok_or(Error::from(ErrorKind::SnapshotNotFound))?;
+    let mut source_mpt = source_snapshot_db.open_snapshot_mpt_as_owned()?;
+    if source_mpt.get_merkle_root() != merkle_root {
+        bail!(format!(
+            "The snapshot {:?} has merkle root {:?}, expected {:?}",
+            snapshot_epoch_id,
+            source_mpt.get_merkle_root(),
+            merkle_root
+        ));
+    }
+
+    let mut target_snapshot_db = target_manager
+        .new_temp_snapshot_for_full_sync(
+            snapshot_epoch_id,
+            &merkle_root,
+            snapshot_info.height,
+        )?;
+    target_snapshot_db.start_transaction()?;
+    let mut kv_iterator = source_snapshot_db.snapshot_kv_iterator()?.take();
+    let mut kvs = kv_iterator.iter_range(&[], None)?.take();
+    while let Some((key, value)) = kvs.next()? {
+        target_snapshot_db.put_kv(&key, &value)?;
+    }
+    drop(kvs);
+
+    let mut target_mpt = target_snapshot_db.open_snapshot_mpt_owned()?;
+    let root_path = CompressedPathRaw::default();
+    if let Some(root_node) = source_mpt.load_node(&root_path)? {
+        target_mpt.write_node(&root_path, &root_node)?;
+        let mut nodes =
+            source_mpt.iterate_subtree_trie_nodes_without_root(&root_path)?;
+        while let Some((path, node)) = nodes.next()? {
+            target_mpt.write_node(&path, &node)?;
+        }
+    }
+    drop(target_mpt);
+    target_snapshot_db.commit_transaction()?;
+    drop(target_snapshot_db);
+    drop(source_mpt);
+    drop(source_snapshot_db);
+
+    target_manager.finalize_full_sync_snapshot(
+        snapshot_epoch_id,
+        &merkle_root,
+        snapshot_info_map_rwlock,
+    )?;
+    Ok(())
+}
+
+use crate::{
+    impls::{
+        delta_mpt::DeltaMptIterator,
+        errors::*,
+        merkle_patricia_trie::{CompressedPathRaw, CompressedPathTrait},
+        storage_db::{
+            kvdb_sqlite_sharded::KvdbSqliteSharded,
+            snapshot_db_manager_rocksdb::SnapshotDbManagerRocksdb,
+            snapshot_db_manager_sqlite::{
+                AlreadyOpenSnapshots, SnapshotDbManagerSqlite,
+                SnapshotDbWriteable,
+            },
+            snapshot_db_rocksdb::{
+                SnapshotDbRocksdb, SnapshotMptRocksdb, SnapshotRocksdbColumn,
+            },
+            snapshot_db_sqlite::SnapshotDbSqlite,
+            snapshot_mpt_db_sqlite::SnapshotMptDbSqlite,
+        },
+        storage_manager::PersistedSnapshotInfoMap,
+    },
+    storage_db::{
+        KeyValueDbIterableTrait, KeyValueDbTraitOwnedRead, KeyValueDbTraitRead,
+        KeyValueDbTraitSingleWriter, KeyValueDbTypes, KvdbIterIterator,
+        OpenSnapshotMptTrait, SnapshotDbManagerTrait, SnapshotDbTrait,
+        SnapshotDbWriteableTrait, SnapshotInfo, SnapshotMptIteraterTrait,
+        SnapshotMptNode, SnapshotMptTraitRead, SnapshotMptTraitReadAndIterate,
+        SnapshotMptTraitRw,
+    },
+    utils::{
+        tuple::ElementSatisfy,
+        wrap::{Wrap, WrappedLifetimeFamily, WrappedTrait},
+    },
+    MptKeyValue,
+};
+use fallible_iterator::FallibleIterator;
+use parking_lot::{RwLock, RwLockWriteGuard};
+use primitives::{EpochId, MerkleHash};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+use tokio::sync::Semaphore;
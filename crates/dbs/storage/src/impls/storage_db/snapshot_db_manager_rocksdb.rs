@@ -0,0 +1,465 @@
+/// The snapshot db manager for snapshots in rocksdb. The snapshot MPT is
+/// always kept in the snapshot db, so there is no isolated MPT snapshot to
+/// maintain.
+pub struct SnapshotDbManagerRocksdb {
+    snapshot_path: PathBuf,
+    /// Only scanned by `scan_persist_state`, since the snapshot MPT is kept
+    /// in the snapshot db.
+    mpt_snapshot_path: PathBuf,
+    already_open_snapshots: AlreadyOpenSnapshots<SnapshotKvDbRocksdb>,
+    /// Set a limit on the number of open snapshots. When the limit is reached,
+    /// consensus initiated open should wait, other non-critical opens such as
+    /// rpc initiated opens should simply abort when the limit is reached.
+    open_snapshot_semaphore: Arc<Semaphore>,
+    open_create_delete_lock: Mutex<()>,
+    reconstruct_snapshot_id_for_reboot: RwLock<Option<EpochId>>,
+}
+
+impl SnapshotDbManagerRocksdb {
+    const MPT_SNAPSHOT_DIR: &'static str = "mpt_snapshot";
+    const SNAPSHOT_DB_ROCKSDB_DIR_PREFIX: &'static str = "rocksdb_";
+
+    pub fn new(
+        snapshot_path: PathBuf, max_open_snapshots: u16,
+    ) -> Result<Self> {
+        if !snapshot_path.exists() {
+            fs::create_dir_all(snapshot_path.clone())?;
+        }
+
+        let mpt_snapshot_path = snapshot_path
+            .parent()
+            .unwrap()
+            .join(SnapshotDbManagerRocksdb::MPT_SNAPSHOT_DIR);
+        if !mpt_snapshot_path.exists() {
+            fs::create_dir_all(mpt_snapshot_path.clone())?;
+        }
+
+        Ok(Self {
+            snapshot_path,
+            mpt_snapshot_path,
+            already_open_snapshots: Default::default(),
+            open_snapshot_semaphore: Arc::new(Semaphore::new(
+                max_open_snapshots as usize,
+            )),
+            open_create_delete_lock: Default::default(),
+            reconstruct_snapshot_id_for_reboot: RwLock::new(None),
+        })
+    }
+
+    pub fn set_reconstruct_snapshot_id(
+        &self, reconstruct_main: Option<EpochId>,
+    ) {
+        debug!("set_reconstruct_snapshot_id to {:?}", reconstruct_main);
+        *self.reconstruct_snapshot_id_for_reboot.write() = reconstruct_main;
+    }
+
+    fn open_snapshot_readonly(
+        &self, snapshot_path: PathBuf, try_open: bool,
+    ) -> Result<Option<SnapshotDbRocksdb>> {
+        // To serialize simultaneous opens.
+        let _open_lock = self.open_create_delete_lock.lock();
+
+        if let Some(already_open) =
+            self.already_open_snapshots.read().get(&snapshot_path)
+        {
+            match already_open {
+                None => {
+                    // Already open for exclusive write
+                    return Ok(None);
+                }
+                Some(open_shared_weak) => {
+                    if let Some(already_open) = Weak::upgrade(open_shared_weak)
+                    {
+                        return Ok(Some(SnapshotDbRocksdb {
+                            snapshot_db: already_open,
+                        }));
+                    }
+                }
+            }
+        }
+        if !snapshot_path.exists() {
+            return Ok(None);
+        }
+
+        let semaphore_permit = if try_open {
+            self.open_snapshot_semaphore
+                .try_acquire()
+                // Unfortunately we have to use map_error because the
+                // TryAcquireError isn't public.
+                .map_err(|_err| ErrorKind::SemaphoreTryAcquireError)?
+        } else {
+            executor::block_on(self.open_snapshot_semaphore.acquire())
+        };
+
+        // If it's not in already_open_snapshots, the rocksdb must have been
+        // closed.
+        while let Some(already_open) =
+            self.already_open_snapshots.read().get(&snapshot_path)
+        {
+            match already_open {
+                None => {
+                    // Already open for exclusive write
+                    return Ok(None);
+                }
+                Some(open_shared_weak) => {
+                    match Weak::upgrade(open_shared_weak) {
+                        None => {
+                            // The db is being closed, wait for it to be
+                            // removed from `already_open_snapshots`. See
+                            // SnapshotDbManagerSqlite.
+                            thread::sleep(Duration::from_millis(5));
+                            continue;
+                        }
+                        Some(already_open) => {
+                            return Ok(Some(SnapshotDbRocksdb {
+                                snapshot_db: already_open,
+                            }));
+                        }
+                    }
+                }
+            }
+        }
+
+        let snapshot_db = Arc::new(SnapshotKvDbRocksdb::open(
+            snapshot_path.as_path(),
+            &self.already_open_snapshots,
+            &self.open_snapshot_semaphore,
+        )?);
+
+        semaphore_permit.forget();
+        self.already_open_snapshots
+            .write()
+            .insert(snapshot_path, Some(Arc::downgrade(&snapshot_db)));
+
+        Ok(Some(SnapshotDbRocksdb { snapshot_db }))
+    }
+
+    fn open_snapshot_write(
+        &self, snapshot_path: PathBuf,
+    ) -> Result<SnapshotKvDbRocksdb> {
+        let _open_lock = self.open_create_delete_lock.lock();
+
+        if self
+            .already_open_snapshots
+            .read()
+            .get(&snapshot_path)
+            .is_some()
+        {
+            bail!(ErrorKind::SnapshotAlreadyExists)
+        }
+
+        let semaphore_permit =
+            executor::block_on(self.open_snapshot_semaphore.acquire());
+
+        // Simultaneous creation fails here.
+        if self
+            .already_open_snapshots
+            .read()
+            .get(&snapshot_path)
+            .is_some()
+        {
+            bail!(ErrorKind::SnapshotAlreadyExists)
+        }
+
+        let snapshot_db = SnapshotKvDbRocksdb::create(
+            snapshot_path.as_path(),
+            &self.already_open_snapshots,
+            &self.open_snapshot_semaphore,
+        )?;
+
+        semaphore_permit.forget();
+        self.already_open_snapshots
+            .write()
+            .insert(snapshot_path, None);
+        Ok(snapshot_db)
+    }
+
+    pub fn on_close(
+        already_open_snapshots: &AlreadyOpenSnapshots<SnapshotKvDbRocksdb>,
+        open_semaphore: &Arc<Semaphore>, path: &Path, remove_on_close: bool,
+    ) {
+        // Destroy at close. See SnapshotDbManagerSqlite::on_close for why the
+        // removal error is ignored.
+        if remove_on_close {
+            Self::fs_remove_snapshot(path);
+        }
+        already_open_snapshots.write().remove(path);
+        open_semaphore.add_permits(1);
+    }
+
+    fn fs_remove_snapshot(path: &Path) {
+        debug!("Remove snapshot at {}", path.display());
+        let path = path.to_owned();
+        thread::spawn(move || {
+            if let Err(e) = fs::remove_dir_all(&path) {
+                error!("remove snapshot err: path={:?} err={:?}", path, e);
+            }
+            debug!("Finish removing snapshot at {}", path.display());
+        });
+    }
+
+    fn get_merge_temp_snapshot_db_path(
+        &self, old_snapshot_epoch_id: &EpochId, new_snapshot_epoch_id: &EpochId,
+    ) -> PathBuf {
+        self.snapshot_path.join(
+            Self::SNAPSHOT_DB_ROCKSDB_DIR_PREFIX.to_string()
+                + "merge_temp_"
+                + &old_snapshot_epoch_id.as_ref().to_hex::<String>()
+                + &new_snapshot_epoch_id.as_ref().to_hex::<String>(),
+        )
+    }
+
+    fn get_full_sync_temp_snapshot_db_path(
+        &self, snapshot_epoch_id: &EpochId, merkle_root: &MerkleHash,
+    ) -> PathBuf {
+        self.snapshot_path.join(
+            Self::SNAPSHOT_DB_ROCKSDB_DIR_PREFIX.to_string()
+                + "full_sync_temp_"
+                + &snapshot_epoch_id.as_ref().to_hex::<String>()
+                + &merkle_root.as_ref().to_hex::<String>(),
+        )
+    }
+
+    fn rename_snapshot_db<P: AsRef<Path>>(
+        old_path: P, new_path: P,
+    ) -> Result<()> {
+        Ok(fs::rename(old_path, new_path)?)
+    }
+}
+
+impl SnapshotDbManagerTrait for SnapshotDbManagerRocksdb {
+    type SnapshotDb = SnapshotDbRocksdb;
+    type SnapshotDbWrite = SnapshotDbRocksdb;
+
+    fn get_snapshot_dir(&self) -> &Path { self.snapshot_path.as_path() }
+
+    fn get_snapshot_db_name(&self, snapshot_epoch_id: &EpochId) -> String {
+        Self::SNAPSHOT_DB_ROCKSDB_DIR_PREFIX.to_string()
+            + &snapshot_epoch_id.as_ref().to_hex::<String>()
+    }
+
+    fn get_snapshot_db_path(&self, snapshot_epoch_id: &EpochId) -> PathBuf {
+        self.snapshot_path
+            .join(&self.get_snapshot_db_name(snapshot_epoch_id))
+    }
+
+    fn get_mpt_snapshot_dir(&self) -> &Path { self.mpt_snapshot_path.as_path() }
+
+    fn get_latest_mpt_snapshot_db_name(&self) -> String {
+        Self::SNAPSHOT_DB_ROCKSDB_DIR_PREFIX.to_string()
+            + SnapshotDbManagerSqlite::LATEST_MPT_SNAPSHOT_DIR
+    }
+
+    /// The snapshot MPT is in the snapshot db, there is nothing to recover.
+    fn recovery_latest_mpt_snapshot_from_checkpoint(
+        &self, _snapshot_epoch_id: &EpochId,
+        _before_era_main_hash: Option<EpochId>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// The snapshot MPT is in the snapshot db, there is nothing to copy.
+    fn create_mpt_snapshot_from_latest(
+        &self, _new_snapshot_epoch_id: &EpochId,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn get_epoch_id_from_snapshot_db_name(
+        &self, snapshot_db_name: &str,
+    ) -> Result<EpochId> {
+        let prefix_len = Self::SNAPSHOT_DB_ROCKSDB_DIR_PREFIX.len();
+        Ok(EpochId::from_str(&snapshot_db_name[prefix_len..])
+            .map_err(|_op| "not correct snapshot db name")?)
+    }
+
+    fn try_get_new_snapshot_epoch_from_temp_path(
+        &self, dir_name: &str,
+    ) -> Option<EpochId> {
+        let prefix =
+            Self::SNAPSHOT_DB_ROCKSDB_DIR_PREFIX.to_string() + "merge_temp_";
+
+        if dir_name.starts_with(&prefix) {
+            match EpochId::from_str(
+                &dir_name[(prefix.len() + EpochId::len_bytes() * 2)..],
+            ) {
+                Ok(e) => Some(e),
+                Err(e) => {
+                    error!(
+                        "get new snapshot epoch id from temp path failed: {}",
+                        e
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        }
+    }
+
+    fn try_get_new_snapshot_epoch_from_mpt_temp_path(
+        &self, _dir_name: &str,
+    ) -> Option<EpochId> {
+        None
+    }
+
+    fn new_snapshot_by_merging<'m>(
+        &self, old_snapshot_epoch_id: &EpochId, snapshot_epoch_id: EpochId,
+        delta_mpt: DeltaMptIterator,
+        mut in_progress_snapshot_info: SnapshotInfo,
+        snapshot_info_map_rwlock: &'m RwLock<PersistedSnapshotInfoMap>,
+        new_epoch_height: u64, recover_mpt_with_kv_snapshot_exist: bool,
+    ) -> Result<(RwLockWriteGuard<'m, PersistedSnapshotInfoMap>, SnapshotInfo)>
+    {
+        info!(
+            "new_snapshot_by_merging: old={:?} new={:?} new epoch height={}",
+            old_snapshot_epoch_id, snapshot_epoch_id, new_epoch_height
+        );
+        if recover_mpt_with_kv_snapshot_exist {
+            bail!("The rocksdb snapshot db keeps the MPT in the snapshot, there is no MPT to recover.");
+        }
+        let temp_db_path = self.get_merge_temp_snapshot_db_path(
+            old_snapshot_epoch_id,
+            &snapshot_epoch_id,
+        );
+        let new_snapshot_db_path =
+            self.get_snapshot_db_path(&snapshot_epoch_id);
+
+        let snapshot_kv_db =
+            Arc::new(self.open_snapshot_write(temp_db_path.clone())?);
+        snapshot_kv_db.dump_delta_mpt(&delta_mpt)?;
+
+        let old_snapshot_db = if *old_snapshot_epoch_id == NULL_EPOCH {
+            None
+        } else {
+            Some(
+                self.open_snapshot_readonly(
+                    self.get_snapshot_db_path(old_snapshot_epoch_id),
+                    /* try_open = */ false,
+                )?
+                .ok_or(Error::from(ErrorKind::SnapshotNotFound))?,
+            )
+        };
+        let new_snapshot_root = snapshot_kv_db.copy_and_merge(
+            old_snapshot_db.as_ref().map(|db| &db.snapshot_db),
+            self.reconstruct_snapshot_id_for_reboot
+                .write()
+                .take()
+                .is_some_and(|v| v == snapshot_epoch_id),
+        )?;
+
+        in_progress_snapshot_info.merkle_root = new_snapshot_root;
+        drop(old_snapshot_db);
+        drop(snapshot_kv_db);
+        let locked = snapshot_info_map_rwlock.write();
+        Self::rename_snapshot_db(&temp_db_path, &new_snapshot_db_path)?;
+
+        Ok((locked, in_progress_snapshot_info))
+    }
+
+    fn get_snapshot_by_epoch_id(
+        &self, snapshot_epoch_id: &EpochId, try_open: bool,
+        _open_mpt_snapshot: bool,
+    ) -> Result<Option<Self::SnapshotDb>> {
+        if snapshot_epoch_id.eq(&NULL_EPOCH) {
+            return Ok(Some(Self::SnapshotDb::get_null_snapshot()));
+        } else {
+            let path = self.get_snapshot_db_path(snapshot_epoch_id);
+            self.open_snapshot_readonly(path, try_open)
+        }
+    }
+
+    fn destroy_snapshot(&self, snapshot_epoch_id: &EpochId) -> Result<()> {
+        debug!("destroy snapshot {:?}", snapshot_epoch_id);
+        let path = self.get_snapshot_db_path(snapshot_epoch_id);
+        let maybe_snapshot = loop {
+            match self.already_open_snapshots.read().get(&path) {
+                Some(Some(snapshot)) => {
+                    match Weak::upgrade(snapshot) {
+                        None => {
+                            // This is transient and we wait for the db to be
+                            // fully closed.
+                            thread::sleep(Duration::from_millis(5));
+                            continue;
+                        }
+                        Some(snapshot) => break Some(snapshot),
+                    }
+                }
+                Some(None) => {
+                    // Mazze always write on a snapshot db under a temporary
+                    // name. All completed snapshots are readonly.
+                    unreachable!("Try to destroy a snapshot being open exclusively for write.")
+                }
+                None => break None,
+            };
+        };
+
+        match maybe_snapshot {
+            None => {
+                if snapshot_epoch_id.ne(&NULL_EPOCH) {
+                    Self::fs_remove_snapshot(&path);
+                }
+            }
+            Some(snapshot) => {
+                snapshot.set_remove_on_last_close();
+            }
+        };
+
+        Ok(())
+    }
+
+    fn new_temp_snapshot_for_full_sync(
+        &self, snapshot_epoch_id: &EpochId, merkle_root: &MerkleHash,
+        _epoch_height: u64,
+    ) -> Result<Self::SnapshotDbWrite> {
+        let temp_db_path = self.get_full_sync_temp_snapshot_db_path(
+            snapshot_epoch_id,
+            merkle_root,
+        );
+        Ok(SnapshotDbRocksdb {
+            snapshot_db: Arc::new(self.open_snapshot_write(temp_db_path)?),
+        })
+    }
+
+    fn finalize_full_sync_snapshot<'m>(
+        &self, snapshot_epoch_id: &EpochId, merkle_root: &MerkleHash,
+        snapshot_info_map_rwlock: &'m RwLock<PersistedSnapshotInfoMap>,
+    ) -> Result<RwLockWriteGuard<'m, PersistedSnapshotInfoMap>> {
+        let temp_db_path = self.get_full_sync_temp_snapshot_db_path(
+            snapshot_epoch_id,
+            merkle_root,
+        );
+        let final_db_path = self.get_snapshot_db_path(snapshot_epoch_id);
+        let locked = snapshot_info_map_rwlock.write();
+        Self::rename_snapshot_db(&temp_db_path, &final_db_path)?;
+        Ok(locked)
+    }
+}
+
+use crate::{
+    impls::{
+        delta_mpt::DeltaMptIterator,
+        errors::*,
+        storage_db::{
+            snapshot_db_manager_sqlite::{
+                AlreadyOpenSnapshots, SnapshotDbManagerSqlite,
+            },
+            snapshot_db_rocksdb::{SnapshotDbRocksdb, SnapshotKvDbRocksdb},
+        },
+        storage_manager::PersistedSnapshotInfoMap,
+    },
+    storage_db::{SnapshotDbManagerTrait, SnapshotDbTrait, SnapshotInfo},
+};
+use futures::executor;
+use parking_lot::{Mutex, RwLock, RwLockWriteGuard};
+use primitives::{EpochId, MerkleHash, NULL_EPOCH};
+use rustc_hex::ToHex;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Weak},
+    thread,
+    time::Duration,
+};
+use tokio::sync::Semaphore;
@@ -0,0 +1,791 @@
+/// A snapshot db in rocksdb. The snapshot key-values, the snapshot MPT and
+/// the dumped delta MPT are kept in separate columns of the same db.
+pub struct SnapshotKvDbRocksdb {
+    // Option because we need an empty snapshot db for empty snapshot.
+    maybe_db: Option<Database>,
+    already_open_snapshots: AlreadyOpenSnapshots<Self>,
+    open_semaphore: Arc<Semaphore>,
+    path: PathBuf,
+    remove_on_close: AtomicBool,
+    /// The number of writes buffered by the db since the last flush. Range
+    /// reads only see flushed values, so they flush the buffered writes
+    /// first.
+    unflushed_writes: AtomicUsize,
+}
+
+pub struct SnapshotDbRocksdb {
+    pub snapshot_db: Arc<SnapshotKvDbRocksdb>,
+}
+
+pub struct SnapshotMptRocksdb {
+    snapshot_db: Arc<SnapshotKvDbRocksdb>,
+    merkle_root: MerkleHash,
+}
+
+/// A column of a snapshot db which can be iterated by range.
+pub struct SnapshotRocksdbColumn<ValueType> {
+    snapshot_db: Arc<SnapshotKvDbRocksdb>,
+    col: u32,
+    _value_type: PhantomData<ValueType>,
+}
+
+/// Reads a range of a snapshot db column page by page, so that the
+/// iterator doesn't borrow the db and doesn't load the whole range.
+pub struct SnapshotRocksdbRangeIter<ValueType> {
+    snapshot_db: Arc<SnapshotKvDbRocksdb>,
+    col: u32,
+    // None when the range is exhausted.
+    next_lower_bound_incl: Option<Vec<u8>>,
+    upper_bound_excl: Option<Vec<u8>>,
+    page: vec::IntoIter<(Box<[u8]>, Box<[u8]>)>,
+    _value_type: PhantomData<ValueType>,
+}
+
+pub struct SnapshotRocksdbIteratorTag();
+
+/// The value types which can be read from a snapshot db column.
+pub trait SnapshotRocksdbValue {
+    fn from_db_value(value: Box<[u8]>) -> Self;
+}
+
+impl SnapshotRocksdbValue for Box<[u8]> {
+    fn from_db_value(value: Box<[u8]>) -> Self { value }
+}
+
+impl SnapshotRocksdbValue for () {
+    fn from_db_value(_value: Box<[u8]>) -> Self {}
+}
+
+impl Drop for SnapshotKvDbRocksdb {
+    fn drop(&mut self) {
+        if !self.path.as_os_str().is_empty() {
+            // Dropping the db writes the buffered changes and closes it.
+            self.maybe_db.take();
+            SnapshotDbManagerRocksdb::on_close(
+                &self.already_open_snapshots,
+                &self.open_semaphore,
+                &self.path,
+                self.remove_on_close.load(Ordering::Relaxed),
+            )
+        }
+    }
+}
+
+impl SnapshotKvDbRocksdb {
+    pub const COL_KV: u32 = 0;
+    pub const COL_MPT: u32 = 1;
+    /// These two columns are temporary for the merging process, but they
+    /// remain to help other nodes to do 1-step syncing.
+    pub const COL_DELTA_KV_SET: u32 = 2;
+    pub const COL_DELTA_KV_DELETE: u32 = 3;
+    const ROCKSDB_CONFIG: DatabaseConfig = DatabaseConfig {
+        max_open_files: 512,
+        memory_budget: None,
+        compaction: CompactionProfile {
+            initial_file_size: 512 * 1048576 as u64,
+            block_size: 16 * 1024,
+            write_rate_limit: Some(64 * 1048576 as u64),
+        },
+        columns: 4,
+        disable_wal: false,
+        read_only: false,
+    };
+    const MAX_UNFLUSHED_WRITES: usize = 100_000;
+    const RANGE_READ_PAGE_SIZE: usize = 1000;
+
+    pub fn get_null_snapshot() -> Self {
+        Self {
+            maybe_db: None,
+            already_open_snapshots: Default::default(),
+            open_semaphore: Arc::new(Semaphore::new(0)),
+            path: Default::default(),
+            remove_on_close: Default::default(),
+            unflushed_writes: Default::default(),
+        }
+    }
+
+    pub fn open(
+        snapshot_path: &Path,
+        already_open_snapshots: &AlreadyOpenSnapshots<Self>,
+        open_semaphore: &Arc<Semaphore>,
+    ) -> Result<Self> {
+        Ok(Self {
+            maybe_db: Some(Database::open(
+                &Self::ROCKSDB_CONFIG,
+                snapshot_path.to_str().unwrap(),
+            )?),
+            already_open_snapshots: already_open_snapshots.clone(),
+            open_semaphore: open_semaphore.clone(),
+            path: snapshot_path.to_path_buf(),
+            remove_on_close: Default::default(),
+            unflushed_writes: Default::default(),
+        })
+    }
+
+    pub fn create(
+        snapshot_path: &Path,
+        already_open_snapshots: &AlreadyOpenSnapshots<Self>,
+        open_semaphore: &Arc<Semaphore>,
+    ) -> Result<Self> {
+        fs::create_dir_all(snapshot_path)?;
+        match Self::open(snapshot_path, already_open_snapshots, open_semaphore)
+        {
+            Err(e) => {
+                fs::remove_dir_all(&snapshot_path)?;
+                bail!(e);
+            }
+            Ok(db) => Ok(db),
+        }
+    }
+
+    pub fn set_remove_on_last_close(&self) {
+        self.remove_on_close.store(true, Ordering::Relaxed);
+    }
+
+    pub fn get(&self, col: u32, key: &[u8]) -> Result<Option<Box<[u8]>>> {
+        match &self.maybe_db {
+            None => Ok(None),
+            Some(db) => Ok(db
+                .get(col, key)?
+                .map(|db_value| db_value.into_boxed_slice())),
+        }
+    }
+
+    pub fn put(&self, col: u32, key: &[u8], value: &[u8]) -> Result<()> {
+        // Safe to unwrap since we are not on a NULL snapshot.
+        let db = self.maybe_db.as_ref().unwrap();
+        let mut transaction = db.transaction();
+        transaction.put(col, key, value);
+        db.write_buffered(transaction);
+        self.on_buffered_write()
+    }
+
+    pub fn delete(&self, col: u32, key: &[u8]) -> Result<()> {
+        // Safe to unwrap since we are not on a NULL snapshot.
+        let db = self.maybe_db.as_ref().unwrap();
+        let mut transaction = db.transaction();
+        transaction.delete(col, key);
+        db.write_buffered(transaction);
+        self.on_buffered_write()
+    }
+
+    fn on_buffered_write(&self) -> Result<()> {
+        if self.unflushed_writes.fetch_add(1, Ordering::Relaxed) + 1
+            >= Self::MAX_UNFLUSHED_WRITES
+        {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&self) -> Result<()> {
+        if let Some(db) = &self.maybe_db {
+            self.unflushed_writes.store(0, Ordering::Relaxed);
+            db.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn read_range(
+        &self, col: u32, lower_bound_incl: &[u8],
+        upper_bound_excl: Option<&[u8]>, limit: usize,
+    ) -> Result<Vec<(Box<[u8]>, Box<[u8]>)>> {
+        match &self.maybe_db {
+            None => Ok(vec![]),
+            Some(db) => {
+                if self.unflushed_writes.load(Ordering::Relaxed) > 0 {
+                    self.flush()?;
+                }
+                Ok(db.read_range(
+                    col,
+                    lower_bound_incl,
+                    upper_bound_excl,
+                    limit,
+                )?)
+            }
+        }
+    }
+
+    // FIXME: add rate limit.
+    pub fn dump_delta_mpt(&self, delta_mpt: &DeltaMptIterator) -> Result<()> {
+        debug!("dump_delta_mpt starts");
+        delta_mpt.iterate(&mut DeltaMptMergeDumperRocksdb { db: self })?;
+        self.flush()
+    }
+
+    fn apply_update_to_kvdb(self: &Arc<Self>) -> Result<()> {
+        let mut delete_keys_iter = SnapshotRocksdbRangeIter::<()>::new(
+            self.clone(),
+            Self::COL_DELTA_KV_DELETE,
+            vec![],
+            None,
+        );
+        while let Some((key, ())) = delete_keys_iter.next()? {
+            self.delete(Self::COL_KV, &key)?;
+        }
+        let mut set_keys_iter = SnapshotRocksdbRangeIter::<Box<[u8]>>::new(
+            self.clone(),
+            Self::COL_DELTA_KV_SET,
+            vec![],
+            None,
+        );
+        while let Some((key, value)) = set_keys_iter.next()? {
+            self.put(Self::COL_KV, &key, &value)?;
+        }
+        self.flush()
+    }
+
+    /// Copy the key-values of the old snapshot into this db, apply the
+    /// dumped delta MPT and save the merged snapshot MPT into this db.
+    pub fn copy_and_merge(
+        self: &Arc<Self>, old_snapshot_db: Option<&Arc<Self>>,
+        in_reconstruct_snapshot_state: bool,
+    ) -> Result<MerkleHash> {
+        debug!("copy_and_merge begins.");
+        if let Some(old_snapshot_db) = old_snapshot_db {
+            let mut kv_iter = SnapshotRocksdbRangeIter::<Box<[u8]>>::new(
+                old_snapshot_db.clone(),
+                Self::COL_KV,
+                vec![],
+                None,
+            );
+            while let Some((key, value)) = kv_iter.next()? {
+                self.put(Self::COL_KV, &key, &value)?;
+            }
+        }
+        self.apply_update_to_kvdb()?;
+
+        let mut save_as_mpt = SnapshotMptRocksdb::new(self.clone())?;
+        let mut base_mpt;
+        let mut mpt_merger = match old_snapshot_db {
+            Some(old_snapshot_db) => {
+                base_mpt = SnapshotMptRocksdb::new(old_snapshot_db.clone())?;
+                MptMerger::new(
+                    Some(
+                        &mut base_mpt
+                            as &mut dyn SnapshotMptTraitReadAndIterate,
+                    ),
+                    &mut save_as_mpt as &mut dyn SnapshotMptTraitRw,
+                )
+            }
+            None => MptMerger::new(
+                None,
+                &mut save_as_mpt as &mut dyn SnapshotMptTraitRw,
+            ),
+        };
+        let snapshot_root = mpt_merger.merge_insertion_deletion_separated(
+            SnapshotRocksdbRangeIter::<()>::new(
+                self.clone(),
+                Self::COL_DELTA_KV_DELETE,
+                vec![],
+                None,
+            ),
+            SnapshotRocksdbRangeIter::<Box<[u8]>>::new(
+                self.clone(),
+                Self::COL_DELTA_KV_SET,
+                vec![],
+                None,
+            ),
+            in_reconstruct_snapshot_state,
+        )?;
+        self.flush()?;
+
+        Ok(snapshot_root)
+    }
+}
+
+pub struct DeltaMptMergeDumperRocksdb<'a> {
+    db: &'a SnapshotKvDbRocksdb,
+}
+
+impl<'a> KVInserter<MptKeyValue> for DeltaMptMergeDumperRocksdb<'a> {
+    fn push(&mut self, x: MptKeyValue) -> Result<()> {
+        let (mpt_key, value) = x;
+        let snapshot_key =
+            StorageKeyWithSpace::from_delta_mpt_key(&mpt_key).to_key_bytes();
+        if value.len() > 0 {
+            self.db.put(
+                SnapshotKvDbRocksdb::COL_DELTA_KV_SET,
+                &snapshot_key,
+                &value,
+            )
+        } else {
+            self.db.put(
+                SnapshotKvDbRocksdb::COL_DELTA_KV_DELETE,
+                &snapshot_key,
+                &[],
+            )
+        }
+    }
+}
+
+impl<ValueType> SnapshotRocksdbRangeIter<ValueType> {
+    fn new(
+        snapshot_db: Arc<SnapshotKvDbRocksdb>, col: u32,
+        lower_bound_incl: Vec<u8>, upper_bound_excl: Option<Vec<u8>>,
+    ) -> Self {
+        Self {
+            snapshot_db,
+            col,
+            next_lower_bound_incl: Some(lower_bound_incl),
+            upper_bound_excl,
+            page: vec![].into_iter(),
+            _value_type: PhantomData,
+        }
+    }
+}
+
+impl<ValueType: SnapshotRocksdbValue> FallibleIterator
+    for SnapshotRocksdbRangeIter<ValueType>
+{
+    type Error = Error;
+    type Item = (Vec<u8>, ValueType);
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        loop {
+            if let Some((key, value)) = self.page.next() {
+                return Ok(Some((
+                    key.into_vec(),
+                    ValueType::from_db_value(value),
+                )));
+            }
+            let lower_bound_incl = match self.next_lower_bound_incl.take() {
+                None => return Ok(None),
+                Some(lower_bound_incl) => lower_bound_incl,
+            };
+            let page = self.snapshot_db.read_range(
+                self.col,
+                &lower_bound_incl,
+                self.upper_bound_excl.as_ref().map(|v| &**v),
+                SnapshotKvDbRocksdb::RANGE_READ_PAGE_SIZE,
+            )?;
+            if page.len() == SnapshotKvDbRocksdb::RANGE_READ_PAGE_SIZE {
+                // The smallest key after the last key of the page.
+                let mut next_lower_bound_incl = page.last().unwrap().0.to_vec();
+                next_lower_bound_incl.push(0);
+                self.next_lower_bound_incl = Some(next_lower_bound_incl);
+            }
+            self.page = page.into_iter();
+        }
+    }
+}
+
+impl<ValueType> SnapshotRocksdbColumn<ValueType> {
+    pub fn new(snapshot_db: Arc<SnapshotKvDbRocksdb>, col: u32) -> Self {
+        Self {
+            snapshot_db,
+            col,
+            _value_type: PhantomData,
+        }
+    }
+}
+
+impl<'a, ValueType: 'static + SnapshotRocksdbValue>
+    WrappedLifetimeFamily<
+        'a,
+        dyn FallibleIterator<Item = (Vec<u8>, ValueType), Error = Error>,
+    >
+    for KvdbIterIterator<(Vec<u8>, ValueType), [u8], SnapshotRocksdbIteratorTag>
+{
+    type Out = SnapshotRocksdbRangeIter<ValueType>;
+}
+
+impl<ValueType: 'static + SnapshotRocksdbValue>
+    WrappedTrait<
+        dyn FallibleIterator<Item = (Vec<u8>, ValueType), Error = Error>,
+    >
+    for KvdbIterIterator<(Vec<u8>, ValueType), [u8], SnapshotRocksdbIteratorTag>
+{
+}
+
+impl<ValueType: 'static + SnapshotRocksdbValue>
+    KeyValueDbIterableTrait<
+        (Vec<u8>, ValueType),
+        [u8],
+        SnapshotRocksdbIteratorTag,
+    > for SnapshotRocksdbColumn<ValueType>
+{
+    fn iter_range(
+        &mut self, lower_bound_incl: &[u8], upper_bound_excl: Option<&[u8]>,
+    ) -> Result<
+        Wrap<
+            KvdbIterIterator<
+                (Vec<u8>, ValueType),
+                [u8],
+                SnapshotRocksdbIteratorTag,
+            >,
+            dyn FallibleIterator<Item = (Vec<u8>, ValueType), Error = Error>,
+        >,
+    > {
+        Ok(Wrap(SnapshotRocksdbRangeIter::new(
+            self.snapshot_db.clone(),
+            self.col,
+            lower_bound_incl.to_vec(),
+            upper_bound_excl.map(|v| v.to_vec()),
+        )))
+    }
+
+    fn iter_range_excl(
+        &mut self, lower_bound_excl: &[u8], upper_bound_excl: &[u8],
+    ) -> Result<
+        Wrap<
+            KvdbIterIterator<
+                (Vec<u8>, ValueType),
+                [u8],
+                SnapshotRocksdbIteratorTag,
+            >,
+            dyn FallibleIterator<Item = (Vec<u8>, ValueType), Error = Error>,
+        >,
+    > {
+        // The smallest key after lower_bound_excl.
+        let mut lower_bound_incl = lower_bound_excl.to_vec();
+        lower_bound_incl.push(0);
+        Ok(Wrap(SnapshotRocksdbRangeIter::new(
+            self.snapshot_db.clone(),
+            self.col,
+            lower_bound_incl,
+            Some(upper_bound_excl.to_vec()),
+        )))
+    }
+}
+
+impl<ValueType: 'static + SnapshotRocksdbValue>
+    ElementSatisfy<
+        dyn KeyValueDbIterableTrait<
+            (Vec<u8>, ValueType),
+            [u8],
+            SnapshotRocksdbIteratorTag,
+        >,
+    > for SnapshotRocksdbColumn<ValueType>
+{
+    fn to_constrain_object(
+        &self,
+    ) -> &(dyn KeyValueDbIterableTrait<
+        (Vec<u8>, ValueType),
+        [u8],
+        SnapshotRocksdbIteratorTag,
+    > + 'static) {
+        self
+    }
+
+    fn to_constrain_object_mut(
+        &mut self,
+    ) -> &mut (dyn KeyValueDbIterableTrait<
+        (Vec<u8>, ValueType),
+        [u8],
+        SnapshotRocksdbIteratorTag,
+    > + 'static) {
+        self
+    }
+}
+
+impl<ValueType: 'static + SnapshotRocksdbValue>
+    WrappedLifetimeFamily<
+        '_,
+        dyn KeyValueDbIterableTrait<
+            (Vec<u8>, ValueType),
+            [u8],
+            SnapshotRocksdbIteratorTag,
+        >,
+    > for SnapshotRocksdbColumn<ValueType>
+{
+    type Out = Self;
+}
+
+impl<ValueType: 'static + SnapshotRocksdbValue>
+    WrappedTrait<
+        dyn KeyValueDbIterableTrait<
+            (Vec<u8>, ValueType),
+            [u8],
+            SnapshotRocksdbIteratorTag,
+        >,
+    > for SnapshotRocksdbColumn<ValueType>
+{
+}
+
+impl SnapshotMptRocksdb {
+    pub fn new(snapshot_db: Arc<SnapshotKvDbRocksdb>) -> Result<Self> {
+        let mut mpt = Self {
+            snapshot_db,
+            merkle_root: MERKLE_NULL_NODE,
+        };
+        if let Some(root_node) = mpt.load_node(&CompressedPathRaw::default())? {
+            mpt.merkle_root = *root_node.get_merkle();
+        }
+        Ok(mpt)
+    }
+}
+
+impl SnapshotMptTraitRead for SnapshotMptRocksdb {
+    fn get_merkle_root(&self) -> MerkleHash { self.merkle_root }
+
+    fn load_node(
+        &mut self, path: &dyn CompressedPathTrait,
+    ) -> Result<Option<SnapshotMptNode>> {
+        let key = mpt_node_path_to_db_key(path);
+        match self.snapshot_db.get(SnapshotKvDbRocksdb::COL_MPT, &key)? {
+            None => Ok(None),
+            Some(rlp) => {
+                Ok(Some(SnapshotMptNode::load_rlp_and_check(&rlp, path)?))
+            }
+        }
+    }
+}
+
+impl SnapshotMptTraitReadAndIterate for SnapshotMptRocksdb {
+    fn iterate_subtree_trie_nodes_without_root(
+        &mut self, path: &dyn CompressedPathTrait,
+    ) -> Result<Box<dyn SnapshotMptIteraterTrait + '_>> {
+        let begin_key_excl = mpt_node_path_to_db_key(path);
+
+        let mut end_key_excl = begin_key_excl.clone();
+        // The key is non empty. See also comment for compressed_path_to_db_key.
+        *end_key_excl.last_mut().unwrap() += 1;
+
+        Ok(Box::new(
+            SnapshotRocksdbColumn::<Box<[u8]>>::new(
+                self.snapshot_db.clone(),
+                SnapshotKvDbRocksdb::COL_MPT,
+            )
+            .iter_range_excl(&begin_key_excl, &end_key_excl)?
+            .take()
+            .map(|(key, value)| {
+                Ok((
+                    mpt_node_path_from_db_key(&key)?,
+                    SnapshotMptNode::decode(&Rlp::new(&value))?,
+                ))
+            }),
+        ))
+    }
+}
+
+impl SnapshotMptTraitRw for SnapshotMptRocksdb {
+    fn delete_node(&mut self, path: &dyn CompressedPathTrait) -> Result<()> {
+        let key = mpt_node_path_to_db_key(path);
+        self.snapshot_db.delete(SnapshotKvDbRocksdb::COL_MPT, &key)
+    }
+
+    fn write_node(
+        &mut self, path: &dyn CompressedPathTrait, trie_node: &SnapshotMptNode,
+    ) -> Result<()> {
+        let key = mpt_node_path_to_db_key(path);
+        self.snapshot_db.put(
+            SnapshotKvDbRocksdb::COL_MPT,
+            &key,
+            &trie_node.rlp_bytes(),
+        )
+    }
+}
+
+impl KeyValueDbTypes for SnapshotDbRocksdb {
+    type ValueType = Box<[u8]>;
+}
+
+impl KeyValueDbTraitRead for SnapshotDbRocksdb {
+    fn get(&self, key: &[u8]) -> Result<Option<Box<[u8]>>> {
+        self.snapshot_db.get(SnapshotKvDbRocksdb::COL_KV, key)
+    }
+}
+
+impl KeyValueDbTraitOwnedRead for SnapshotDbRocksdb {
+    fn get_mut(&mut self, key: &[u8]) -> Result<Option<Box<[u8]>>> {
+        self.snapshot_db.get(SnapshotKvDbRocksdb::COL_KV, key)
+    }
+}
+
+impl KeyValueDbTraitSingleWriter for SnapshotDbRocksdb {
+    fn delete(&mut self, key: &[u8]) -> Result<Option<Option<Box<[u8]>>>> {
+        self.snapshot_db.delete(SnapshotKvDbRocksdb::COL_KV, key)?;
+        Ok(None)
+    }
+
+    fn put(
+        &mut self, key: &[u8], value: &[u8],
+    ) -> Result<Option<Option<Box<[u8]>>>> {
+        self.snapshot_db
+            .put(SnapshotKvDbRocksdb::COL_KV, key, value)?;
+        Ok(None)
+    }
+}
+
+impl<'db> OpenSnapshotMptTrait<'db> for SnapshotDbRocksdb {
+    type SnapshotDbAsOwnedType = SnapshotMptRocksdb;
+    type SnapshotDbBorrowMutType = SnapshotMptRocksdb;
+    type SnapshotDbBorrowSharedType = SnapshotMptRocksdb;
+
+    fn open_snapshot_mpt_owned(
+        &'db mut self,
+    ) -> Result<Self::SnapshotDbBorrowMutType> {
+        SnapshotMptRocksdb::new(self.snapshot_db.clone())
+    }
+
+    fn open_snapshot_mpt_as_owned(
+        &'db self,
+    ) -> Result<Self::SnapshotDbAsOwnedType> {
+        SnapshotMptRocksdb::new(self.snapshot_db.clone())
+    }
+
+    fn open_snapshot_mpt_shared(
+        &'db self,
+    ) -> Result<Self::SnapshotDbBorrowSharedType> {
+        SnapshotMptRocksdb::new(self.snapshot_db.clone())
+    }
+}
+
+impl SnapshotDbRocksdb {
+    pub fn dumped_delta_kv_set_keys_iterator(
+        &self,
+    ) -> Result<SnapshotRocksdbColumn<Box<[u8]>>> {
+        Ok(SnapshotRocksdbColumn::new(
+            self.snapshot_db.clone(),
+            SnapshotKvDbRocksdb::COL_DELTA_KV_SET,
+        ))
+    }
+
+    pub fn dumped_delta_kv_delete_keys_iterator(
+        &self,
+    ) -> Result<SnapshotRocksdbColumn<()>> {
+        Ok(SnapshotRocksdbColumn::new(
+            self.snapshot_db.clone(),
+            SnapshotKvDbRocksdb::COL_DELTA_KV_DELETE,
+        ))
+    }
+}
+
+impl SnapshotDbTrait for SnapshotDbRocksdb {
+    type SnapshotKvdbIterTraitTag = SnapshotRocksdbIteratorTag;
+    type SnapshotKvdbIterType = SnapshotRocksdbColumn<Box<[u8]>>;
+
+    fn get_null_snapshot() -> Self {
+        Self {
+            snapshot_db: Arc::new(SnapshotKvDbRocksdb::get_null_snapshot()),
+        }
+    }
+
+    /// The snapshot db is opened by SnapshotKvDbRocksdb::open.
+    fn open(
+        _snapshot_path: &Path, _readonly: bool,
+        _already_open_snapshots: &AlreadyOpenSnapshots<Self>,
+        _open_semaphore: &Arc<Semaphore>,
+    ) -> Result<SnapshotDbRocksdb> {
+        unreachable!()
+    }
+
+    /// The snapshot db is created by SnapshotKvDbRocksdb::create.
+    fn create(
+        _snapshot_path: &Path,
+        _already_open_snapshots: &AlreadyOpenSnapshots<Self>,
+        _open_semaphore: &Arc<Semaphore>, _mpt_table_in_current_db: bool,
+    ) -> Result<SnapshotDbRocksdb> {
+        unreachable!()
+    }
+
+    /// The snapshot is merged by SnapshotKvDbRocksdb::copy_and_merge.
+    fn direct_merge(
+        &mut self, _old_snapshot_db: Option<&Arc<SnapshotDbRocksdb>>,
+        _mpt_snapshot: &mut Option<SnapshotMptDbSqlite>,
+        _recover_mpt_with_kv_snapshot_exist: bool,
+        _in_reconstruct_snapshot_state: bool,
+    ) -> Result<MerkleHash> {
+        unreachable!()
+    }
+
+    /// The snapshot is merged by SnapshotKvDbRocksdb::copy_and_merge.
+    fn copy_and_merge(
+        &mut self, _old_snapshot_db: &Arc<SnapshotDbRocksdb>,
+        _mpt_snapshot_db: &mut Option<SnapshotMptDbSqlite>,
+        _in_reconstruct_snapshot_state: bool,
+    ) -> Result<MerkleHash> {
+        unreachable!()
+    }
+
+    fn start_transaction(&mut self) -> Result<()> { Ok(()) }
+
+    fn commit_transaction(&mut self) -> Result<()> { self.snapshot_db.flush() }
+
+    /// The snapshot MPT is always kept in the snapshot db.
+    fn is_mpt_table_in_current_db(&self) -> bool { true }
+
+    fn snapshot_kv_iterator(
+        &self,
+    ) -> Result<
+        Wrap<
+            Self::SnapshotKvdbIterType,
+            dyn KeyValueDbIterableTrait<
+                MptKeyValue,
+                [u8],
+                SnapshotRocksdbIteratorTag,
+            >,
+        >,
+    > {
+        Ok(Wrap(SnapshotRocksdbColumn::new(
+            self.snapshot_db.clone(),
+            SnapshotKvDbRocksdb::COL_KV,
+        )))
+    }
+}
+
+impl SnapshotDbWriteableTrait for SnapshotDbRocksdb {
+    type SnapshotDbBorrowMutType = SnapshotMptRocksdb;
+
+    fn start_transaction(&mut self) -> Result<()> { Ok(()) }
+
+    fn commit_transaction(&mut self) -> Result<()> { self.snapshot_db.flush() }
+
+    fn put_kv(
+        &mut self, key: &[u8], value: &[u8],
+    ) -> Result<Option<Option<Box<[u8]>>>> {
+        self.put(key, value)
+    }
+
+    fn open_snapshot_mpt_owned(
+        &mut self,
+    ) -> Result<Self::SnapshotDbBorrowMutType> {
+        SnapshotMptRocksdb::new(self.snapshot_db.clone())
+    }
+}
+
+use crate::{
+    impls::{
+        delta_mpt::DeltaMptIterator,
+        errors::*,
+        merkle_patricia_trie::{
+            CompressedPathRaw, CompressedPathTrait, KVInserter, MptKeyValue,
+            MptMerger,
+        },
+        storage_db::{
+            snapshot_db_manager_rocksdb::SnapshotDbManagerRocksdb,
+            snapshot_db_manager_sqlite::AlreadyOpenSnapshots,
+            snapshot_mpt::{
+                mpt_node_path_from_db_key, mpt_node_path_to_db_key,
+            },
+            snapshot_mpt_db_sqlite::SnapshotMptDbSqlite,
+        },
+    },
+    storage_db::{
+        KeyValueDbIterableTrait, KeyValueDbTraitOwnedRead, KeyValueDbTraitRead,
+        KeyValueDbTraitSingleWriter, KeyValueDbTypes, KvdbIterIterator,
+        OpenSnapshotMptTrait, SnapshotDbTrait, SnapshotDbWriteableTrait,
+        SnapshotMptIteraterTrait, SnapshotMptNode, SnapshotMptTraitRead,
+        SnapshotMptTraitReadAndIterate, SnapshotMptTraitRw,
+    },
+    utils::{
+        tuple::ElementSatisfy,
+        wrap::{Wrap, WrappedLifetimeFamily, WrappedTrait},
+    },
+};
+use fallible_iterator::FallibleIterator;
+use kvdb_rocksdb::{CompactionProfile, Database, DatabaseConfig};
+use primitives::{MerkleHash, StorageKeyWithSpace, MERKLE_NULL_NODE};
+use rlp::{Decodable, Encodable, Rlp};
+use std::{
+    fs,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    vec,
+};
+use tokio::sync::Semaphore;
@@ -35,12 +35,13 @@ impl SingleMptStorageManager {
     pub fn new_arc(
         db_path: PathBuf, space: Option<Space>, available_height: u64,
         cache_start_size: u32, cache_size: u32, idle_size: u32,
+        read_only: bool,
     ) -> Arc<Self> {
         if !db_path.exists() {
             fs::create_dir_all(&db_path).expect("db path create error");
         }
         let db_manager = Arc::new(SingleMptDbManager {
-            db_manager: DeltaDbManager::new(db_path)
+            db_manager: DeltaDbManager::new(db_path, read_only)
                 .expect("DeltaDb initialize error"),
             opened_mpt: Mutex::new(None),
         });
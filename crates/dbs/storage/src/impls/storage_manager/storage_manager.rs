@@ -227,6 +227,7 @@ impl StorageManager {
 
         let delta_db_manager = Arc::new(DeltaDbManager::new(
             storage_conf.path_delta_mpts_dir.clone(),
+            storage_conf.read_only,
         )?);
         let new_storage_manager_result = Ok(Arc::new(Self {
             delta_db_manager: delta_db_manager.clone(),
@@ -236,6 +237,7 @@ impl StorageManager {
             )?),
             snapshot_manager: Box::new(SnapshotManager::<SnapshotDbManager> {
                 snapshot_db_manager: SnapshotDbManager::new(
+                    storage_conf.snapshot_db_backend,
                     storage_conf.path_snapshot_dir.clone(),
                     storage_conf.max_open_snapshots,
                     storage_conf.use_isolated_db_for_mpt_table,
@@ -306,6 +308,76 @@ impl StorageManager {
         new_storage_manager_result
     }
 
+    /// Convert the snapshots kept by the node from the snapshot dbs of
+    /// `source_backend` into the snapshot dbs of the configured backend, and
+    /// remove the converted source snapshots. Returns the number of converted
+    /// snapshots.
+    ///
+    /// The node must not be running during the conversion.
+    pub fn convert_snapshot_db_backend(
+        storage_conf: &StorageConfiguration, source_backend: SnapshotDbBackend,
+    ) -> Result<usize> {
+        if source_backend == storage_conf.snapshot_db_backend {
+            bail!(format!(
+                "The snapshot db backend is already {:?}",
+                source_backend
+            ));
+        }
+        let (_, snapshot_info_db) = KvdbSqlite::open_or_create(
+            &storage_conf.path_snapshot_info_db,
+            SNAPSHOT_KVDB_STATEMENTS.clone(),
+            false, /* unsafe_mode */
+        )?;
+        let snapshot_info_map =
+            RwLock::new(PersistedSnapshotInfoMap::new(snapshot_info_db)?);
+        let new_snapshot_db_manager = |backend| {
+            SnapshotDbManager::new(
+                backend,
+                storage_conf.path_snapshot_dir.clone(),
+                storage_conf.max_open_snapshots,
+                storage_conf.use_isolated_db_for_mpt_table,
+                storage_conf.use_isolated_db_for_mpt_table_height,
+                storage_conf.consensus_param.era_epoch_count,
+            )
+        };
+        let source_manager = new_snapshot_db_manager(source_backend)?;
+        let target_manager =
+            new_snapshot_db_manager(storage_conf.snapshot_db_backend)?;
+
+        let snapshot_infos: Vec<SnapshotInfo> = snapshot_info_map
+            .read()
+            .get_map()
+            .values()
+            .filter(|snapshot_info| {
+                snapshot_info.snapshot_info_kept_to_provide_sync
+                    != SnapshotKeptToProvideSyncStatus::InfoOnly
+                    && *snapshot_info.get_snapshot_epoch_id() != NULL_EPOCH
+            })
+            .cloned()
+            .collect();
+        let mut converted_snapshots = 0;
+        for snapshot_info in &snapshot_infos {
+            let source_path = source_manager
+                .get_snapshot_db_path(snapshot_info.get_snapshot_epoch_id());
+            // Already converted by an interrupted earlier run.
+            if !source_path.exists() {
+                continue;
+            }
+            convert_snapshot_db(
+                &source_manager,
+                &target_manager,
+                snapshot_info,
+                &snapshot_info_map,
+            )?;
+            // Removed synchronously because destroy_snapshot removes the
+            // files in a background thread, which may not finish before the
+            // process exits.
+            fs::remove_dir_all(&source_path)?;
+            converted_snapshots += 1;
+        }
+        Ok(converted_snapshots)
+    }
+
     pub fn find_merkle_root(
         current_snapshots: &Vec<SnapshotInfo>, epoch_id: &EpochId,
     ) -> Option<MerkleHash> {
@@ -408,6 +480,16 @@ impl StorageManager {
             .map(Clone::clone)
     }
 
+    /// The epoch ids and heights of the snapshots known to the storage, which
+    /// may be off the main chain or already removed.
+    pub fn get_current_snapshot_epochs(&self) -> Vec<(EpochId, u64)> {
+        self.current_snapshots
+            .read()
+            .iter()
+            .map(|info| (*info.get_snapshot_epoch_id(), info.height))
+            .collect()
+    }
+
     pub fn get_delta_mpt(
         self: &Arc<Self>, snapshot_epoch_id: &EpochId,
     ) -> Result<Arc<DeltaMpt>> {
@@ -1368,6 +1450,12 @@ impl StorageManager {
             if snapshot_epoch_id == NULL_EPOCH {
                 continue;
             }
+            if self.storage_conf.read_only {
+                bail!(
+                    "Snapshot {:?} is missing, start the node to repair it",
+                    snapshot_epoch_id
+                );
+            }
             // Remove the delta mpt if the snapshot is missing.
             self.delta_db_manager
                 .destroy_delta_db(
@@ -1429,6 +1517,12 @@ impl StorageManager {
                 "Missing intermediate mpt and delta mpt for snapshot {:?}",
                 snapshot_epoch_id
             );
+            if self.storage_conf.read_only {
+                bail!(
+                    "Delta mpt of snapshot {:?} is missing, start the node to repair it",
+                    snapshot_epoch_id
+                );
+            }
             snapshot_info_map.remove(&snapshot_epoch_id)?;
             self.snapshot_manager
                 .get_snapshot_db_manager()
@@ -1589,6 +1683,7 @@ use crate::{
                 kvdb_sqlite_iter_range_impl, KvdbSqliteDestructureTrait,
                 KvdbSqliteStatements,
             },
+            snapshot_db_by_backend::{convert_snapshot_db, SnapshotDbBackend},
             snapshot_kv_db_sqlite::test_lib::check_key_value_load,
         },
         storage_manager::snapshot_manager::SnapshotManager,
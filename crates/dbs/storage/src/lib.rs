@@ -32,6 +32,7 @@ pub mod storage_dir {
     lazy_static! {
         pub static ref DELTA_MPTS_DIR: PathBuf =
             ["storage_db", "delta_mpts"].iter().collect::<PathBuf>();
+        pub static ref FORK_DELETIONS_DB_NAME: &'static str = "fork_deletions";
         pub static ref SNAPSHOT_DIR: PathBuf =
             ["storage_db", "snapshot"].iter().collect::<PathBuf>();
         pub static ref SNAPSHOT_INFO_DB_NAME: &'static str = "snapshot_info";
@@ -127,6 +128,10 @@ pub struct StorageConfiguration {
     pub use_isolated_db_for_mpt_table: bool,
     pub use_isolated_db_for_mpt_table_height: Option<u64>,
     pub keep_era_genesis_snapshot: bool,
+    pub snapshot_db_backend: SnapshotDbBackend,
+    /// Keep the writes to the delta MPTs in memory, so that the storage is
+    /// inspected offline without being modified.
+    pub read_only: bool,
 }
 
 impl StorageConfiguration {
@@ -175,6 +180,8 @@ impl StorageConfiguration {
             use_isolated_db_for_mpt_table: false,
             use_isolated_db_for_mpt_table_height: None,
             keep_era_genesis_snapshot: false,
+            snapshot_db_backend: SnapshotDbBackend::Sqlite,
+            read_only: false,
         }
     }
 
@@ -199,6 +206,8 @@ pub use self::{
         defaults,
         delta_mpt::*,
         errors::{Error, ErrorKind, Result},
+        fork_state::{ForkCache, ForkSource, ForkState},
+        historical_state::HistoricalState,
         merkle_patricia_trie::{
             mpt_cursor::rlp_key_value_len, simple_mpt::*,
             trie_proof::TrieProofNode, CompressedPathRaw, KVInserter,
@@ -212,6 +221,10 @@ pub use self::{
         storage_db::{
             kvdb_rocksdb::KvdbRocksdb,
             kvdb_sqlite::{KvdbSqlite, KvdbSqliteStatements},
+            snapshot_db_by_backend::{
+                SnapshotDbBackend, SnapshotDbManagerByBackend,
+            },
+            snapshot_db_manager_rocksdb::SnapshotDbManagerRocksdb,
             snapshot_db_manager_sqlite::SnapshotDbManagerSqlite,
             sqlite::SqliteConnection,
         },
@@ -8,7 +8,7 @@
 /// A writable state is copy-on-write reference to the base state in the
 /// state manager. State is supposed to be owned by single user.
 pub use super::impls::state::State;
-use mazze_types::AddressWithSpace;
+use mazze_types::{AddressWithSpace, Space};
 
 pub type WithProof = primitives::static_bool::Yes;
 pub type NoProof = primitives::static_bool::No;
@@ -36,6 +36,38 @@ pub trait StateTrait: Sync + Send {
     fn read_all(
         &mut self, access_key_prefix: StorageKeyWithSpace,
     ) -> Result<Option<Vec<MptKeyValue>>>;
+    // Read at most `limit` accounts in `space` in the order of the addresses,
+    // starting from `start_address`.
+    fn read_account_range(
+        &mut self, space: Space, start_address: &[u8], limit: usize,
+    ) -> Result<Vec<MptKeyValue>>;
+    // Read at most `limit` storage entries of `address` in `space` in the
+    // order of the storage keys, starting from `start_key`.
+    fn read_storage_range(
+        &mut self, space: Space, address: &[u8], start_key: &[u8], limit: usize,
+    ) -> Result<Vec<MptKeyValue>>;
+    // Call `f` with every account in `space` in the order of the addresses.
+    // The default reads the accounts page by page with `read_account_range`,
+    // states which can do better in a single pass override it.
+    fn for_each_account(
+        &mut self, space: Space, f: &mut dyn FnMut(MptKeyValue) -> Result<()>,
+    ) -> Result<()> {
+        const PAGE_SIZE: usize = 1000;
+        let mut start_address = Some(vec![]);
+        while let Some(start) = start_address.take() {
+            let mut page =
+                self.read_account_range(space, &start, PAGE_SIZE + 1)?;
+            if page.len() > PAGE_SIZE {
+                let (key, _) = page.pop().unwrap();
+                start_address =
+                    Some(key[..StorageKeyWithSpace::ACCOUNT_BYTES].to_vec());
+            }
+            for kv in page {
+                f(kv)?;
+            }
+        }
+        Ok(())
+    }
 
     // Finalize
     /// It's costly to compute state root however it's only necessary to compute
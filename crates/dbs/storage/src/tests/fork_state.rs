@@ -0,0 +1,168 @@
+struct FakeForkSource {
+    entries: HashMap<Vec<u8>, Box<[u8]>>,
+    fetch_count: Arc<AtomicUsize>,
+}
+
+impl ForkSource for FakeForkSource {
+    fn get(
+        &self, access_key: StorageKeyWithSpace,
+    ) -> Result<Option<Box<[u8]>>> {
+        self.fetch_count.fetch_add(1, Ordering::SeqCst);
+        Ok(self.entries.get(&access_key.to_key_bytes()).cloned())
+    }
+}
+
+fn set_fork_source(
+    state_manager: &mut FakeStateManager,
+    entries: &HashMap<Vec<u8>, Box<[u8]>>, fetch_count: &Arc<AtomicUsize>,
+) {
+    Arc::get_mut(&mut **state_manager)
+        .unwrap()
+        .set_fork_source(Box::new(FakeForkSource {
+            entries: entries.clone(),
+            fetch_count: fetch_count.clone(),
+        }))
+        .unwrap();
+}
+
+fn next_state(
+    state_manager: &FakeStateManager, parent: &EpochId,
+    parent_root: &StateRootWithAuxInfo, height: u64,
+) -> Box<dyn StateTrait> {
+    state_manager
+        .get_state_for_next_epoch(
+            StateIndex::new_for_next_epoch(
+                parent,
+                parent_root,
+                height,
+                state_manager
+                    .get_storage_manager()
+                    .get_snapshot_epoch_count(),
+            ),
+            false,
+        )
+        .unwrap()
+        .unwrap()
+}
+
+#[test]
+fn test_fork_state_get() {
+    let address = Address::from_low_u64_be(1);
+    let account_key = StorageKey::new_account_key(&address).with_native_space();
+    let storage_key =
+        StorageKey::new_storage_key(&address, &[1; 32]).with_native_space();
+    let missing_key =
+        StorageKey::new_storage_key(&address, &[2; 32]).with_native_space();
+
+    let fetch_count = Arc::new(AtomicUsize::new(0));
+    let mut entries = HashMap::new();
+    entries.insert(account_key.to_key_bytes(), vec![1u8].into());
+    entries.insert(storage_key.to_key_bytes(), vec![2u8].into());
+
+    let mut state_manager = new_state_manager_for_unit_test();
+    set_fork_source(&mut state_manager, &entries, &fetch_count);
+    let genesis_epoch = EpochId::from_low_u64_be(1);
+    let genesis_root = state_manager
+        .get_state_for_genesis_write()
+        .commit(genesis_epoch)
+        .unwrap();
+
+    let mut state =
+        next_state(&state_manager, &genesis_epoch, &genesis_root, 1);
+    assert_eq!(state.get(storage_key).unwrap(), Some(vec![2u8].into()));
+    assert_eq!(state.get(missing_key).unwrap(), None);
+    assert_eq!(fetch_count.load(Ordering::SeqCst), 2);
+
+    // The fetched entries are cached.
+    assert_eq!(state.get(storage_key).unwrap(), Some(vec![2u8].into()));
+    assert_eq!(state.get(missing_key).unwrap(), None);
+    assert_eq!(fetch_count.load(Ordering::SeqCst), 2);
+
+    // The local entries take precedence.
+    state.set(storage_key, vec![3u8].into()).unwrap();
+    assert_eq!(state.get(storage_key).unwrap(), Some(vec![3u8].into()));
+
+    // The entries of a deleted account are not fetched again.
+    state.delete(storage_key).unwrap();
+    state.delete(account_key).unwrap();
+    assert_eq!(state.get(storage_key).unwrap(), None);
+    assert_eq!(state.get(account_key).unwrap(), None);
+
+    // The same address in the other space is not affected.
+    let evm_account_key =
+        StorageKey::new_account_key(&address).with_evm_space();
+    assert_eq!(state.get(evm_account_key).unwrap(), None);
+    assert_eq!(fetch_count.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn test_fork_state_deletions_by_epoch() {
+    let address = Address::from_low_u64_be(1);
+    let account_key = StorageKey::new_account_key(&address).with_native_space();
+    let storage_key =
+        StorageKey::new_storage_key(&address, &[1; 32]).with_native_space();
+
+    let fetch_count = Arc::new(AtomicUsize::new(0));
+    let mut entries = HashMap::new();
+    entries.insert(account_key.to_key_bytes(), vec![1u8].into());
+    entries.insert(storage_key.to_key_bytes(), vec![2u8].into());
+
+    let mut state_manager = new_state_manager_for_unit_test();
+    set_fork_source(&mut state_manager, &entries, &fetch_count);
+    let genesis_epoch = EpochId::from_low_u64_be(1);
+    let genesis_root = state_manager
+        .get_state_for_genesis_write()
+        .commit(genesis_epoch)
+        .unwrap();
+
+    // The account is deleted in epoch 2.
+    let epoch_2 = EpochId::from_low_u64_be(2);
+    let root_2 = {
+        let mut state =
+            next_state(&state_manager, &genesis_epoch, &genesis_root, 1);
+        state.delete(account_key).unwrap();
+        state.commit(epoch_2).unwrap()
+    };
+
+    // The deletion is seen by the children of epoch 2.
+    let epoch_3 = EpochId::from_low_u64_be(3);
+    let root_3 = {
+        let mut state = next_state(&state_manager, &epoch_2, &root_2, 2);
+        assert_eq!(state.get(account_key).unwrap(), None);
+        assert_eq!(state.get(storage_key).unwrap(), None);
+        state.commit(epoch_3).unwrap()
+    };
+
+    // It's not seen by a sibling of epoch 2, e.g. after epoch 2 is reverted.
+    {
+        let state =
+            next_state(&state_manager, &genesis_epoch, &genesis_root, 1);
+        assert_eq!(state.get(account_key).unwrap(), Some(vec![1u8].into()));
+        assert_eq!(state.get(storage_key).unwrap(), Some(vec![2u8].into()));
+    }
+
+    // The deletions are loaded again with the fork source.
+    set_fork_source(&mut state_manager, &entries, &fetch_count);
+    let state = next_state(&state_manager, &epoch_3, &root_3, 3);
+    assert_eq!(state.get(account_key).unwrap(), None);
+    assert_eq!(state.get(storage_key).unwrap(), None);
+    let state = next_state(&state_manager, &genesis_epoch, &genesis_root, 1);
+    assert_eq!(state.get(storage_key).unwrap(), Some(vec![2u8].into()));
+}
+
+use crate::{
+    impls::errors::*,
+    state::*,
+    tests::{new_state_manager_for_unit_test, FakeStateManager},
+    ForkSource, StateIndex, StorageManagerTrait,
+};
+use mazze_internal_common::StateRootWithAuxInfo;
+use mazze_types::Address;
+use primitives::{EpochId, StorageKey, StorageKeyWithSpace};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
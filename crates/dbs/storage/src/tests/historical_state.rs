@@ -0,0 +1,139 @@
+#[test]
+fn test_historical_state_reads() {
+    let state_manager = new_state_manager_for_unit_test();
+    let addresses: Vec<Address> =
+        (1..=4).map(Address::from_low_u64_be).collect();
+    let account_key = |i: usize| {
+        StorageKey::new_account_key(&addresses[i]).with_native_space()
+    };
+    let slot = |i: u64| H256::from_low_u64_be(i);
+    let slots: Vec<H256> = (1..=3).map(slot).collect();
+    let storage_key = |i: usize| {
+        StorageKey::new_storage_key(&addresses[0], slots[i].as_bytes())
+            .with_native_space()
+    };
+
+    // The later state has accounts 1 to 3 and the slots 1 and 2 of account 1.
+    let mut state = state_manager.get_state_for_genesis_write();
+    for i in 0..3 {
+        state.set(account_key(i), vec![i as u8 + 1].into()).unwrap();
+    }
+    for i in 0..2 {
+        state
+            .set(storage_key(i), slots[i].as_bytes().into())
+            .unwrap();
+    }
+    state
+        .set(
+            StorageKey::new_account_key(&addresses[1]).with_evm_space(),
+            vec![1u8].into(),
+        )
+        .unwrap();
+    let epoch_id = EpochId::from_low_u64_be(1);
+    state.compute_state_root().unwrap();
+    state.commit(epoch_id).unwrap();
+    let later_state = state_manager
+        .get_state_for_next_epoch(
+            StateIndex::new_for_test_only_delta_mpt(&epoch_id),
+            false,
+        )
+        .unwrap()
+        .unwrap();
+
+    // After the epoch, account 1 is modified, account 2 and slot 2 are
+    // created, and account 4 and slot 3 are deleted.
+    let mut original_values = BTreeMap::new();
+    original_values
+        .insert(account_key(0).to_key_bytes(), Some(vec![0u8].into()));
+    original_values.insert(account_key(1).to_key_bytes(), None);
+    original_values
+        .insert(account_key(3).to_key_bytes(), Some(vec![4u8].into()));
+    original_values.insert(storage_key(1).to_key_bytes(), None);
+    original_values.insert(
+        storage_key(2).to_key_bytes(),
+        Some(slots[2].as_bytes().into()),
+    );
+    let mut state = HistoricalState::new(later_state, original_values);
+
+    assert_eq!(state.get(account_key(0)).unwrap(), Some(vec![0u8].into()));
+    assert_eq!(state.get(account_key(1)).unwrap(), None);
+    assert_eq!(state.get(account_key(2)).unwrap(), Some(vec![3u8].into()));
+    assert_eq!(state.get(account_key(3)).unwrap(), Some(vec![4u8].into()));
+    assert_eq!(state.get(storage_key(1)).unwrap(), None);
+
+    let keys = |kvs: Vec<MptKeyValue>| -> Vec<Vec<u8>> {
+        kvs.into_iter().map(|(key, _)| key).collect()
+    };
+    assert_eq!(
+        keys(
+            state
+                .read_all(
+                    StorageKey::new_storage_root_key(&addresses[0])
+                        .with_native_space()
+                )
+                .unwrap()
+                .unwrap()
+        ),
+        vec![storage_key(0).to_key_bytes(), storage_key(2).to_key_bytes()]
+    );
+    assert!(state
+        .read_all(
+            StorageKey::new_storage_root_key(&addresses[2]).with_native_space()
+        )
+        .unwrap()
+        .is_none());
+
+    let account_keys = |indices: &[usize]| -> Vec<Vec<u8>> {
+        indices
+            .iter()
+            .map(|i| account_key(*i).to_key_bytes())
+            .collect()
+    };
+    assert_eq!(
+        keys(state.read_account_range(Space::Native, &[], 10).unwrap()),
+        account_keys(&[0, 2, 3])
+    );
+    assert_eq!(
+        keys(state.read_account_range(Space::Native, &[], 2).unwrap()),
+        account_keys(&[0, 2])
+    );
+    assert_eq!(
+        keys(
+            state
+                .read_account_range(Space::Native, addresses[2].as_bytes(), 2)
+                .unwrap()
+        ),
+        account_keys(&[2, 3])
+    );
+    assert_eq!(
+        state.read_account_range(Space::Ethereum, &[], 10).unwrap(),
+        vec![(
+            StorageKey::new_account_key(&addresses[1])
+                .with_evm_space()
+                .to_key_bytes(),
+            vec![1u8].into()
+        )]
+    );
+    assert_eq!(
+        keys(
+            state
+                .read_storage_range(
+                    Space::Native,
+                    addresses[0].as_bytes(),
+                    &[],
+                    10
+                )
+                .unwrap()
+        ),
+        vec![storage_key(0).to_key_bytes(), storage_key(2).to_key_bytes()]
+    );
+    assert!(state.set(account_key(0), vec![1u8].into()).is_err());
+}
+
+use crate::{
+    state::*, state_manager::*, tests::new_state_manager_for_unit_test,
+    HistoricalState, MptKeyValue,
+};
+use mazze_types::{Address, Space, H256};
+use primitives::{EpochId, StorageKey};
+use std::collections::BTreeMap;
@@ -3,6 +3,10 @@
 mod snapshot;
 pub use snapshot::FakeSnapshotMptDb;
 
+#[cfg(test)]
+mod fork_state;
+#[cfg(test)]
+mod historical_state;
 #[cfg(test)]
 mod proofs;
 #[cfg(test)]
@@ -61,6 +65,7 @@ pub struct FakeStateManager {
 impl FakeStateManager {
     fn new(
         mazze_data_dir: String, snapshot_epoch_count: u32,
+        snapshot_db_backend: SnapshotDbBackend,
     ) -> Result<Self> {
         // Use a random directory to prevent conflicts in concurrently running
         // tests.
@@ -76,6 +81,7 @@ impl FakeStateManager {
         storage_conf.delta_mpts_cache_start_size = 1_000_000;
         storage_conf.delta_mpts_node_map_vec_size = 20_000_000;
         storage_conf.delta_mpts_slab_idle_size = 200_000;
+        storage_conf.snapshot_db_backend = snapshot_db_backend;
 
         Ok(FakeStateManager {
             data_dir: unit_test_data_dir,
@@ -113,6 +119,13 @@ impl DerefMut for FakeStateManager {
 #[cfg(any(test, feature = "testonly_code"))]
 pub fn new_state_manager_for_unit_test_with_snapshot_epoch_count(
     snapshot_epoch_count: u32,
+) -> FakeStateManager {
+    new_fake_state_manager(snapshot_epoch_count, SnapshotDbBackend::default())
+}
+
+#[cfg(any(test, feature = "testonly_code"))]
+fn new_fake_state_manager(
+    snapshot_epoch_count: u32, snapshot_db_backend: SnapshotDbBackend,
 ) -> FakeStateManager {
     const WITH_LOGGER: bool = false;
     if WITH_LOGGER {
@@ -140,6 +153,7 @@ pub fn new_state_manager_for_unit_test_with_snapshot_epoch_count(
     FakeStateManager::new(
         "./mazze_unit_test_data_dir".to_string(),
         snapshot_epoch_count,
+        snapshot_db_backend,
     )
     .unwrap()
 }
@@ -152,6 +166,14 @@ pub fn new_state_manager_for_unit_test() -> FakeStateManager {
     )
 }
 
+#[cfg(test)]
+fn new_state_manager_for_unit_test_with_backend(
+    snapshot_db_backend: SnapshotDbBackend,
+) -> FakeStateManager {
+    let snapshot_epoch_count = 10;
+    new_fake_state_manager(snapshot_epoch_count, snapshot_db_backend)
+}
+
 #[derive(Default)]
 pub struct DumpedMptKvIterator {
     pub kv: Vec<MptKeyValue>,
@@ -250,7 +272,9 @@ pub fn print_mpt_key(key: &[u8]) {
 }
 
 #[cfg(any(test, feature = "testonly_code"))]
-use crate::{impls::state_manager::StateManager, StorageConfiguration};
+use crate::{
+    impls::state_manager::StateManager, SnapshotDbBackend, StorageConfiguration,
+};
 use crate::{
     impls::{
         errors::*,
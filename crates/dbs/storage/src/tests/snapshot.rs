@@ -613,6 +613,197 @@ fn test_two_way_merge() {
     in_place_mod_mpt.assert_eq(&save_as_mode_mpt);
 }
 
+#[cfg(test)]
+fn load_snapshot_kvs(
+    snapshot_db_manager: &SnapshotDbManagerByBackend,
+    snapshot_epoch_id: &EpochId,
+) -> (MerkleHash, Vec<MptKeyValue>) {
+    let snapshot_db = snapshot_db_manager
+        .get_snapshot_by_epoch_id(
+            snapshot_epoch_id,
+            /* try_open = */ false,
+            /* open_mpt_snapshot = */ true,
+        )
+        .unwrap()
+        .unwrap();
+    let merkle_root = snapshot_db
+        .open_snapshot_mpt_shared()
+        .unwrap()
+        .get_merkle_root();
+    let mut kv_iterator = snapshot_db.snapshot_kv_iterator().unwrap().take();
+    let kvs: Vec<MptKeyValue> = kv_iterator
+        .iter_range(&[], None)
+        .unwrap()
+        .take()
+        .collect()
+        .unwrap();
+    // The snapshot mpt must prove every key value of the snapshot.
+    check_key_value_load(
+        &snapshot_db,
+        fallible_iterator::convert(kvs.clone().into_iter().map(Ok::<_, Error>)),
+        /* check_value = */ true,
+    )
+    .unwrap();
+    (merkle_root, kvs)
+}
+
+#[cfg(test)]
+#[test]
+fn test_convert_snapshot_db_backend() {
+    let mut state_manager =
+        new_state_manager_for_unit_test_with_backend(SnapshotDbBackend::Sqlite);
+    let snapshot_epoch_count = state_manager
+        .get_storage_manager()
+        .get_snapshot_epoch_count();
+    let keys: Vec<Vec<u8>> = generate_keys(1000);
+
+    // Commit enough epochs for the state manager to make snapshots.
+    let mut epoch_id = EpochId::default();
+    let mut state = state_manager.get_state_for_genesis_write();
+    for key in &keys {
+        state
+            .set(
+                StorageKey::AccountKey(key).with_native_space(),
+                key[..].into(),
+            )
+            .unwrap();
+    }
+    let mut state_root = state.compute_state_root().unwrap();
+    state.commit(epoch_id).unwrap();
+    let last_height = 4 * snapshot_epoch_count as u64;
+    for height in 1..=last_height {
+        while state_manager
+            .get_storage_manager()
+            .in_progress_snapshotting_tasks
+            .read()
+            .len()
+            != 0
+        {
+            thread::sleep(Duration::from_millis(100));
+        }
+        let mut state = state_manager
+            .get_state_for_next_epoch(
+                StateIndex::new_for_next_epoch(
+                    &epoch_id,
+                    &state_root,
+                    height,
+                    snapshot_epoch_count,
+                ),
+                false,
+            )
+            .unwrap()
+            .unwrap();
+        for key in keys.iter().skip(height as usize % 7).step_by(7) {
+            state
+                .set(
+                    StorageKey::AccountKey(key).with_native_space(),
+                    [&key[..], &height.to_be_bytes()].concat().into(),
+                )
+                .unwrap();
+        }
+        epoch_id = EpochId::from_low_u64_be(height);
+        state_root = state.compute_state_root().unwrap();
+        state.commit(epoch_id).unwrap();
+    }
+    while state_manager
+        .get_storage_manager()
+        .in_progress_snapshotting_tasks
+        .read()
+        .len()
+        != 0
+    {
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    let snapshot_epoch_ids: Vec<EpochId> = (1..=last_height)
+        .map(EpochId::from_low_u64_be)
+        .filter(|snapshot_epoch_id| {
+            state_manager
+                .get_storage_manager()
+                .get_snapshot_info_at_epoch(snapshot_epoch_id)
+                .is_some()
+        })
+        .collect();
+    assert!(!snapshot_epoch_ids.is_empty());
+    let snapshot_db_manager = state_manager
+        .get_storage_manager()
+        .get_snapshot_manager()
+        .get_snapshot_db_manager();
+    let expected_snapshots: Vec<(MerkleHash, Vec<MptKeyValue>)> =
+        snapshot_epoch_ids
+            .iter()
+            .map(|snapshot_epoch_id| {
+                load_snapshot_kvs(snapshot_db_manager, snapshot_epoch_id)
+            })
+            .collect();
+    for (snapshot_epoch_id, (merkle_root, _)) in
+        snapshot_epoch_ids.iter().zip(&expected_snapshots)
+    {
+        assert_eq!(
+            state_manager
+                .get_storage_manager()
+                .get_snapshot_info_at_epoch(snapshot_epoch_id)
+                .unwrap()
+                .merkle_root,
+            *merkle_root
+        );
+    }
+
+    // The conversion runs while the node is stopped.
+    let mut storage_conf = state_manager.config().clone();
+    state_manager.state_manager.take();
+
+    // Convert to rocksdb and back, checking the snapshots after each
+    // conversion and after restarting the storage with the new backend.
+    for (source_backend, target_backend) in [
+        (SnapshotDbBackend::Sqlite, SnapshotDbBackend::Rocksdb),
+        (SnapshotDbBackend::Rocksdb, SnapshotDbBackend::Sqlite),
+    ] {
+        storage_conf.snapshot_db_backend = target_backend;
+        assert!(StateManager::convert_snapshot_db_backend(
+            &storage_conf,
+            target_backend
+        )
+        .is_err());
+        assert_eq!(
+            StateManager::convert_snapshot_db_backend(
+                &storage_conf,
+                source_backend
+            )
+            .unwrap(),
+            snapshot_epoch_ids.len()
+        );
+        // Nothing is left to convert.
+        assert_eq!(
+            StateManager::convert_snapshot_db_backend(
+                &storage_conf,
+                source_backend
+            )
+            .unwrap(),
+            0
+        );
+
+        let restarted_state_manager =
+            StateManager::new(storage_conf.clone()).unwrap();
+        let storage_manager = restarted_state_manager.get_storage_manager();
+        let snapshot_db_manager = storage_manager
+            .get_snapshot_manager()
+            .get_snapshot_db_manager();
+        assert_eq!(snapshot_db_manager.backend(), target_backend);
+        for (snapshot_epoch_id, expected) in
+            snapshot_epoch_ids.iter().zip(&expected_snapshots)
+        {
+            assert!(storage_manager
+                .get_snapshot_info_at_epoch(snapshot_epoch_id)
+                .is_some());
+            assert_eq!(
+                &load_snapshot_kvs(snapshot_db_manager, snapshot_epoch_id),
+                expected
+            );
+        }
+    }
+}
+
 #[allow(unused)]
 fn test_delta_subtree_size() {
     // FIXME: complete this test.
@@ -643,14 +834,24 @@ use std::{
 #[cfg(test)]
 use crate::{
     impls::merkle_patricia_trie::{MptMerger, TrieNodeTrait},
-    impls::storage_db::snapshot_mpt::tests::verify_snapshot_db,
-    state_manager::StateManagerTrait,
+    impls::storage_db::{
+        snapshot_kv_db_sqlite::test_lib::check_key_value_load,
+        snapshot_mpt::tests::verify_snapshot_db,
+    },
+    snapshot_manager::SnapshotManagerTrait,
+    state_manager::{StateManager, StateManagerTrait},
+    storage_db::{
+        KeyValueDbIterableTrait, OpenSnapshotMptTrait, SnapshotDbManagerTrait,
+        SnapshotDbTrait,
+    },
     tests::{
         generate_keys, get_rng_for_test, new_state_manager_for_unit_test,
+        new_state_manager_for_unit_test_with_backend,
         snapshot::verifier::FakeSnapshotDb, DumpedMptKvIterator,
         TEST_NUMBER_OF_KEYS,
     },
-    StateIndex, StorageStateTraitExt,
+    MptKeyValue, SnapshotDbBackend, SnapshotDbManagerByBackend, StateIndex,
+    StorageStateTraitExt,
 };
 #[cfg(test)]
 use parking_lot::Mutex;
@@ -659,4 +860,8 @@ use primitives::{EpochId, StorageKey, MERKLE_NULL_NODE};
 #[cfg(test)]
 use rand::Rng;
 #[cfg(test)]
-use std::sync::{atomic::Ordering, Arc};
+use std::{
+    sync::{atomic::Ordering, Arc},
+    thread,
+    time::Duration,
+};
@@ -1,8 +1,7 @@
 
 
-#[test]
-fn test_empty_genesis_block() {
-    let state_manager = new_state_manager_for_unit_test();
+fn test_empty_genesis_block(backend: SnapshotDbBackend) {
+    let state_manager = new_state_manager_for_unit_test_with_backend(backend);
 
     let mut genesis_epoch_id = H256::default();
     genesis_epoch_id.as_bytes_mut()[0] = 1;
@@ -22,10 +21,9 @@ fn test_empty_genesis_block() {
         .unwrap();
 }
 
-#[test]
-fn test_set_get() {
+fn test_set_get(backend: SnapshotDbBackend) {
     let mut rng = get_rng_for_test();
-    let state_manager = new_state_manager_for_unit_test();
+    let state_manager = new_state_manager_for_unit_test_with_backend(backend);
     let mut state = state_manager.get_state_for_genesis_write();
     let mut keys: Vec<Vec<u8>> = generate_keys(TEST_NUMBER_OF_KEYS)
         .iter()
@@ -61,9 +59,8 @@ fn test_set_get() {
     state.commit(epoch_id).unwrap();
 }
 
-#[test]
-fn test_get_set_at_second_commit() {
-    let state_manager = new_state_manager_for_unit_test();
+fn test_get_set_at_second_commit(backend: SnapshotDbBackend) {
+    let state_manager = new_state_manager_for_unit_test_with_backend(backend);
     let keys: Vec<Vec<u8>> = generate_keys(TEST_NUMBER_OF_KEYS);
     let set_size = TEST_NUMBER_OF_KEYS / 10;
     let (keys_0, keys_1_new, keys_remain, keys_1_overwritten) = (
@@ -161,9 +158,8 @@ fn test_get_set_at_second_commit() {
     state_1.commit(epoch_id_1).unwrap();
 }
 
-#[test]
-fn test_snapshot_random_read_performance() {
-    let state_manager = new_state_manager_for_unit_test();
+fn test_snapshot_random_read_performance(backend: SnapshotDbBackend) {
+    let state_manager = new_state_manager_for_unit_test_with_backend(backend);
     let keys: Vec<Vec<u8>> = generate_keys(TEST_NUMBER_OF_KEYS);
 
     const EPOCHS: u8 = 20;
@@ -410,10 +406,9 @@ fn simulate_transactions(
     state_root
 }
 
-#[test]
-fn test_set_delete() {
+fn test_set_delete(backend: SnapshotDbBackend) {
     let mut rng = get_rng_for_test();
-    let state_manager = new_state_manager_for_unit_test();
+    let state_manager = new_state_manager_for_unit_test_with_backend(backend);
 
     let mut state = state_manager.get_state_for_genesis_write();
 
@@ -474,10 +469,9 @@ fn test_set_delete() {
     state.commit(epoch_id).unwrap();
 }
 
-#[test]
-fn test_set_delete_all() {
+fn test_set_delete_all(backend: SnapshotDbBackend) {
     let mut rng = get_rng_for_test();
-    let state_manager = new_state_manager_for_unit_test();
+    let state_manager = new_state_manager_for_unit_test_with_backend(backend);
 
     let mut state = state_manager.get_state_for_genesis_write();
     let empty_state_root = state.compute_state_root().unwrap();
@@ -565,10 +559,9 @@ fn test_set_delete_all() {
     assert_eq!(state_root, empty_state_root);
 }
 
-#[test]
-fn test_set_order() {
+fn test_set_order(backend: SnapshotDbBackend) {
     let mut rng = get_rng_for_test();
-    let state_manager = new_state_manager_for_unit_test();
+    let state_manager = new_state_manager_for_unit_test_with_backend(backend);
     let keys: Vec<Vec<u8>> = generate_keys(500000)
         .iter()
         .filter(|_| rng.gen_bool(0.5))
@@ -630,10 +623,9 @@ fn test_set_order() {
     assert_eq!(merkle_1, merkle_2);
 }
 
-#[test]
-fn test_set_order_concurrent() {
+fn test_set_order_concurrent(backend: SnapshotDbBackend) {
     let mut rng = get_rng_for_test();
-    let state_manager = new_state_manager_for_unit_test();
+    let state_manager = new_state_manager_for_unit_test_with_backend(backend);
     let keys = Arc::new(
         generate_keys(TEST_NUMBER_OF_KEYS / 10)
             .iter()
@@ -742,17 +734,219 @@ fn test_set_order_concurrent() {
     }
 }
 
+fn test_read_account_and_storage_range(backend: SnapshotDbBackend) {
+    let state_manager = new_state_manager_for_unit_test_with_backend(backend);
+    let addresses: Vec<Address> =
+        (1..=6).map(Address::from_low_u64_be).collect();
+    let slot = |i: u64| H256::from_low_u64_be(i);
+
+    let mut state = state_manager.get_state_for_genesis_write();
+    for address in &addresses[..5] {
+        state
+            .set(
+                StorageKey::new_account_key(address).with_native_space(),
+                address.as_bytes().into(),
+            )
+            .unwrap();
+        for i in 1..=3 {
+            state
+                .set(
+                    StorageKey::new_storage_key(address, slot(i).as_bytes())
+                        .with_native_space(),
+                    slot(i).as_bytes().into(),
+                )
+                .unwrap();
+        }
+    }
+    state
+        .set(
+            StorageKey::new_account_key(&addresses[2]).with_evm_space(),
+            addresses[2].as_bytes().into(),
+        )
+        .unwrap();
+    let mut epoch_id = H256::default();
+    epoch_id.as_bytes_mut()[0] = 1;
+    state.compute_state_root().unwrap();
+    state.commit(epoch_id).unwrap();
+
+    let mut state = state_manager
+        .get_state_for_next_epoch(
+            StateIndex::new_for_test_only_delta_mpt(&epoch_id),
+            false,
+        )
+        .unwrap()
+        .unwrap();
+    state
+        .delete(StorageKey::new_account_key(&addresses[3]).with_native_space())
+        .unwrap();
+    state
+        .set(
+            StorageKey::new_account_key(&addresses[5]).with_native_space(),
+            addresses[5].as_bytes().into(),
+        )
+        .unwrap();
+    state
+        .delete(
+            StorageKey::new_storage_key(&addresses[1], slot(2).as_bytes())
+                .with_native_space(),
+        )
+        .unwrap();
+    state
+        .set(
+            StorageKey::new_storage_key(&addresses[1], slot(4).as_bytes())
+                .with_native_space(),
+            slot(4).as_bytes().into(),
+        )
+        .unwrap();
+
+    let accounts = |kvs: Vec<MptKeyValue>| -> Vec<Address> {
+        kvs.iter()
+            .map(|(key, value)| {
+                let address = Address::from_slice(
+                    &key[..StorageKeyWithSpace::ACCOUNT_BYTES],
+                );
+                assert_eq!(address.as_bytes(), &**value);
+                address
+            })
+            .collect()
+    };
+    let expected = vec![
+        addresses[0],
+        addresses[1],
+        addresses[2],
+        addresses[4],
+        addresses[5],
+    ];
+    assert_eq!(
+        accounts(state.read_account_range(Space::Native, &[], 10).unwrap()),
+        expected
+    );
+    // The accounts are read page by page.
+    let mut start_address = vec![];
+    let mut paged = vec![];
+    loop {
+        let mut page = accounts(
+            state
+                .read_account_range(Space::Native, &start_address, 3)
+                .unwrap(),
+        );
+        if page.len() < 3 {
+            paged.append(&mut page);
+            break;
+        }
+        start_address = page.pop().unwrap().as_bytes().to_vec();
+        paged.append(&mut page);
+    }
+    assert_eq!(paged, expected);
+    let mut all_accounts = vec![];
+    state
+        .for_each_account(Space::Native, &mut |kv| {
+            all_accounts.push(kv);
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(accounts(all_accounts), expected);
+    assert_eq!(
+        accounts(
+            state
+                .read_account_range(Space::Native, addresses[3].as_bytes(), 1)
+                .unwrap()
+        ),
+        vec![addresses[4]]
+    );
+    assert_eq!(
+        accounts(state.read_account_range(Space::Ethereum, &[], 10).unwrap()),
+        vec![addresses[2]]
+    );
+
+    let slots = |kvs: Vec<MptKeyValue>| -> Vec<H256> {
+        kvs.iter()
+            .map(|(key, value)| {
+                let slot = H256::from_slice(&key[key.len() - 32..]);
+                assert_eq!(slot.as_bytes(), &**value);
+                slot
+            })
+            .collect()
+    };
+    let address = addresses[1].as_bytes();
+    assert_eq!(
+        slots(
+            state
+                .read_storage_range(Space::Native, address, &[], 10)
+                .unwrap()
+        ),
+        vec![slot(1), slot(3), slot(4)]
+    );
+    assert_eq!(
+        slots(
+            state
+                .read_storage_range(Space::Native, address, &[], 2)
+                .unwrap()
+        ),
+        vec![slot(1), slot(3)]
+    );
+    assert_eq!(
+        slots(
+            state
+                .read_storage_range(
+                    Space::Native,
+                    address,
+                    slot(2).as_bytes(),
+                    2
+                )
+                .unwrap()
+        ),
+        vec![slot(3), slot(4)]
+    );
+    assert!(state
+        .read_storage_range(Space::Ethereum, address, &[], 10)
+        .unwrap()
+        .is_empty());
+}
+
+/// Generates a `#[test]` per snapshot db backend for each test function.
+macro_rules! snapshot_db_backend_tests {
+    ($($test:ident),* $(,)?) => {
+        mod sqlite {
+            $(
+                #[test]
+                fn $test() { super::$test(crate::SnapshotDbBackend::Sqlite) }
+            )*
+        }
+
+        mod rocksdb {
+            $(
+                #[test]
+                fn $test() { super::$test(crate::SnapshotDbBackend::Rocksdb) }
+            )*
+        }
+    };
+}
+
+snapshot_db_backend_tests!(
+    test_empty_genesis_block,
+    test_set_get,
+    test_get_set_at_second_commit,
+    test_snapshot_random_read_performance,
+    test_set_delete,
+    test_set_delete_all,
+    test_set_order,
+    test_set_order_concurrent,
+    test_read_account_and_storage_range,
+);
+
 use crate::{
     state::*,
     state_manager::*,
     tests::{
-        generate_keys, get_rng_for_test, new_state_manager_for_unit_test,
-        FakeStateManager, TEST_NUMBER_OF_KEYS,
+        generate_keys, get_rng_for_test,
+        new_state_manager_for_unit_test_with_backend, FakeStateManager,
+        TEST_NUMBER_OF_KEYS,
     },
-    StateRootWithAuxInfo,
+    MptKeyValue, SnapshotDbBackend, StateRootWithAuxInfo,
 };
 use mazze_types::{
-    address_util::AddressUtil, Address, AddressSpaceUtil, H256, U256,
+    address_util::AddressUtil, Address, AddressSpaceUtil, Space, H256, U256,
 };
 use primitives::{Account, StorageKey, StorageKeyWithSpace};
 use rand::{
@@ -0,0 +1,559 @@
+//! An optional index of the transactions sent or received by each address on
+//! the main chain. The entries of an address are kept in buckets of
+//! `ADDRESS_TX_INDEX_BUCKET_SIZE` epochs, along with the list of its
+//! non-empty buckets, so that the history can be paged from the newest entry
+//! with point reads only.
+
+use super::{AddressTxEntry, AddressTxEpoch, BlockDataManager};
+use mazze_execute_helper::exec_tracer::{
+    Action as TraceAction, BlockExecTraces,
+};
+use mazze_executor::observer::AddressPocket;
+use mazze_types::{
+    address_util::AddressUtil, Address, AddressSpaceUtil, AddressWithSpace,
+    Space, H256,
+};
+use mazze_vm_types::{contract_address, CreateContractAddress};
+use primitives::{
+    Action, Block, BlockReceipts, SignedTransaction, TransactionStatus,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+
+/// The number of epochs in a bucket of entries.
+pub const ADDRESS_TX_INDEX_BUCKET_SIZE: u64 = 256;
+
+fn address_key(address: &AddressWithSpace) -> Vec<u8> {
+    let mut key = Vec::with_capacity(Address::len_bytes() + 1);
+    key.push(match address.space {
+        Space::Native => 0,
+        Space::Ethereum => 1,
+    });
+    key.extend_from_slice(address.address.as_bytes());
+    key
+}
+
+fn created_address(tx: &SignedTransaction) -> AddressWithSpace {
+    let address = match tx.space() {
+        Space::Native => {
+            let (mut address, _) = contract_address(
+                CreateContractAddress::FromSenderNonceAndCodeHash,
+                0,
+                &tx.sender,
+                tx.nonce(),
+                tx.data(),
+            );
+            address.set_contract_type_bits();
+            address
+        }
+        Space::Ethereum => {
+            contract_address(
+                CreateContractAddress::FromSenderNonce,
+                0,
+                &tx.sender,
+                tx.nonce(),
+                tx.data(),
+            )
+            .0
+        }
+    };
+    AddressWithSpace {
+        address,
+        space: tx.space(),
+    }
+}
+
+impl BlockDataManager {
+    pub fn address_tx_index_enabled(&self) -> bool {
+        self.config.persist_address_tx_index
+    }
+
+    /// Index the senders, recipients and created contracts of the executed
+    /// transactions in an epoch on the main chain if
+    /// `persist_address_tx_index` is enabled. The recipients of internal
+    /// transfers are also indexed if `address_tx_index_internal_transfers`
+    /// is enabled and the traces are available. If the epoch number was
+    /// indexed with another main block, the entries of the previous main
+    /// block are replaced.
+    pub fn insert_address_tx_index(
+        &self, epoch_number: u64, epoch_hash: &H256,
+        epoch_blocks: &[Arc<Block>], epoch_receipts: &[Arc<BlockReceipts>],
+    ) {
+        if !self.config.persist_address_tx_index {
+            return;
+        }
+        // The epochs sharing a bucket update the same entries.
+        let _lock = self.address_tx_index_lock.lock();
+        let old_epoch = self.db_manager.address_tx_epoch_from_db(epoch_number);
+        if let Some(ref old_epoch) = old_epoch {
+            if old_epoch.epoch_hash == *epoch_hash {
+                return;
+            }
+        }
+
+        let mut entries: BTreeMap<Vec<u8>, Vec<AddressTxEntry>> =
+            BTreeMap::new();
+        let mut index = 0;
+        for (block, receipts) in epoch_blocks.iter().zip(epoch_receipts) {
+            let traces = if self.config.address_tx_index_internal_transfers {
+                self.block_traces_by_hash_with_epoch(
+                    &block.hash(),
+                    epoch_hash,
+                    false, /* update_main_assumption */
+                    false, /* update_cache */
+                )
+            } else {
+                None
+            };
+            for (tx_idx, (tx, receipt)) in block
+                .transactions
+                .iter()
+                .zip(&receipts.receipts)
+                .enumerate()
+            {
+                if !matches!(
+                    receipt.outcome_status,
+                    TransactionStatus::Success | TransactionStatus::Failure
+                ) {
+                    continue;
+                }
+
+                let mut addresses = BTreeSet::new();
+                addresses.insert(tx.sender());
+                match tx.action() {
+                    Action::Call(to) => {
+                        addresses.insert(to.with_space(tx.space()));
+                    }
+                    Action::Create => {
+                        if receipt.outcome_status == TransactionStatus::Success
+                        {
+                            addresses.insert(created_address(tx));
+                        }
+                    }
+                }
+                if let Some(BlockExecTraces(ref tx_traces)) = traces {
+                    let transfer_recipients = tx_traces
+                        .get(tx_idx)
+                        .into_iter()
+                        .flat_map(|tx_traces| &tx_traces.0)
+                        .filter(|trace| trace.valid)
+                        .filter_map(|trace| match trace.action {
+                            TraceAction::InternalTransferAction(ref action) => {
+                                match action.to {
+                                    AddressPocket::Balance(address) => {
+                                        Some(address)
+                                    }
+                                    _ => None,
+                                }
+                            }
+                            _ => None,
+                        });
+                    addresses.extend(transfer_recipients);
+                }
+
+                for address in &addresses {
+                    entries.entry(address_key(address)).or_default().push(
+                        AddressTxEntry {
+                            epoch_number,
+                            index,
+                            block_hash: block.hash(),
+                            tx_hash: tx.hash(),
+                        },
+                    );
+                }
+                index += 1;
+            }
+        }
+
+        // The entries of the epoch are replaced with one write for each
+        // address, so a concurrent query sees either the old or the new
+        // entries of an address.
+        let bucket = epoch_number / ADDRESS_TX_INDEX_BUCKET_SIZE;
+        let stale_keys = old_epoch
+            .into_iter()
+            .flat_map(|e| e.address_keys)
+            .filter(|key| !entries.contains_key(key))
+            .collect::<Vec<_>>();
+        for (key, new_entries) in &entries {
+            self.update_address_tx_entries(
+                key,
+                bucket,
+                epoch_number,
+                new_entries,
+            );
+        }
+        for key in &stale_keys {
+            self.update_address_tx_entries(key, bucket, epoch_number, &[]);
+        }
+
+        self.db_manager.insert_address_tx_epoch_to_db(
+            epoch_number,
+            &AddressTxEpoch {
+                epoch_hash: *epoch_hash,
+                address_keys: entries.into_keys().collect(),
+            },
+        );
+    }
+
+    /// Replace the entries of `epoch_number` of an address with
+    /// `new_entries`.
+    fn update_address_tx_entries(
+        &self, address_key: &[u8], bucket: u64, epoch_number: u64,
+        new_entries: &[AddressTxEntry],
+    ) {
+        let mut bucket_entries = self
+            .db_manager
+            .address_tx_entries_from_db(address_key, bucket)
+            .unwrap_or_default();
+        let was_empty = bucket_entries.is_empty();
+        bucket_entries.retain(|e| e.epoch_number != epoch_number);
+        let pos =
+            bucket_entries.partition_point(|e| e.epoch_number < epoch_number);
+        bucket_entries.splice(pos..pos, new_entries.iter().cloned());
+
+        if bucket_entries.is_empty() {
+            if was_empty {
+                return;
+            }
+            self.db_manager
+                .remove_address_tx_entries_from_db(address_key, bucket);
+        } else {
+            self.db_manager.insert_address_tx_entries_to_db(
+                address_key,
+                bucket,
+                &bucket_entries,
+            );
+        }
+
+        if was_empty != bucket_entries.is_empty() {
+            let mut buckets = self
+                .db_manager
+                .address_tx_buckets_from_db(address_key)
+                .unwrap_or_default();
+            match buckets.binary_search(&bucket) {
+                Ok(pos) if bucket_entries.is_empty() => {
+                    buckets.remove(pos);
+                }
+                Err(pos) if !bucket_entries.is_empty() => {
+                    buckets.insert(pos, bucket);
+                }
+                _ => {}
+            }
+            if buckets.is_empty() {
+                self.db_manager
+                    .remove_address_tx_buckets_from_db(address_key);
+            } else {
+                self.db_manager
+                    .insert_address_tx_buckets_to_db(address_key, &buckets);
+            }
+        }
+    }
+
+    /// Returns at most `limit` entries of an address before `cursor`, which
+    /// is `(epoch_number, index)`, from the newest to the oldest. The entries
+    /// are paired with the main block their epoch is indexed with, and the
+    /// caller should skip the ones whose epoch has left the main chain.
+    pub fn address_transactions(
+        &self, address: &AddressWithSpace, cursor: Option<(u64, u64)>,
+        limit: usize,
+    ) -> Vec<(AddressTxEntry, H256)> {
+        if !self.config.persist_address_tx_index || limit == 0 {
+            return vec![];
+        }
+        let key = address_key(address);
+        let buckets = match self.db_manager.address_tx_buckets_from_db(&key) {
+            Some(buckets) => buckets,
+            None => return vec![],
+        };
+        let is_before_cursor = |entry: &AddressTxEntry| match cursor {
+            Some(cursor) => (entry.epoch_number, entry.index) < cursor,
+            None => true,
+        };
+
+        let mut result = vec![];
+        let mut epoch_hashes = BTreeMap::new();
+        for bucket in buckets.into_iter().rev() {
+            if let Some((cursor_epoch, _)) = cursor {
+                if bucket > cursor_epoch / ADDRESS_TX_INDEX_BUCKET_SIZE {
+                    continue;
+                }
+            }
+            let entries = self
+                .db_manager
+                .address_tx_entries_from_db(&key, bucket)
+                .unwrap_or_default();
+            for entry in entries.into_iter().rev() {
+                if !is_before_cursor(&entry) {
+                    continue;
+                }
+                let epoch_hash = *epoch_hashes
+                    .entry(entry.epoch_number)
+                    .or_insert_with(|| {
+                        self.db_manager
+                            .address_tx_epoch_from_db(entry.epoch_number)
+                            .map_or(H256::zero(), |e| e.epoch_hash)
+                    });
+                result.push((entry, epoch_hash));
+                if result.len() == limit {
+                    return result;
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::address_key;
+    use crate::{
+        block_data_manager::{BlockDataManager, DbType},
+        pow::PowComputer,
+        sync::utils::{create_simple_block_impl, initialize_data_manager},
+    };
+    use mazze_executor::machine::VmFactory;
+    use mazze_types::{Address, AddressSpaceUtil, Bloom, H256, U256};
+    use primitives::{
+        transaction::{
+            eth_transaction::Eip155Transaction,
+            native_transaction::NativeTransaction,
+        },
+        Action, Block, BlockReceipts, Receipt, SignedTransaction,
+        TransactionStatus,
+    };
+    use std::sync::Arc;
+    use tempdir::TempDir;
+
+    fn new_data_manager(db_dir: &TempDir) -> Arc<BlockDataManager> {
+        let (mut data_man, _) = initialize_data_manager(
+            db_dir.path().to_str().unwrap(),
+            DbType::Rocksdb,
+            Arc::new(PowComputer::new()),
+            VmFactory::new(1024 * 32),
+        );
+        Arc::get_mut(&mut data_man)
+            .unwrap()
+            .config
+            .persist_address_tx_index = true;
+        data_man
+    }
+
+    fn address(n: u64) -> Address { Address::from_low_u64_be(n) }
+
+    /// A block with the native transactions `(nonce, to)` sent by address 1.
+    fn block(
+        block_nonce: u64, txs: &[(u64, u64)],
+    ) -> (Arc<Block>, Arc<BlockReceipts>) {
+        let transactions = txs
+            .iter()
+            .map(|(nonce, to)| {
+                Arc::new(
+                    NativeTransaction {
+                        nonce: (*nonce).into(),
+                        action: Action::Call(address(*to)),
+                        ..Default::default()
+                    }
+                    .fake_sign(address(1).with_native_space()),
+                )
+            })
+            .collect();
+        block_with_transactions(block_nonce, transactions)
+    }
+
+    /// A block with successful transactions.
+    fn block_with_transactions(
+        block_nonce: u64, transactions: Vec<Arc<SignedTransaction>>,
+    ) -> (Arc<Block>, Arc<BlockReceipts>) {
+        let (_, block) = create_simple_block_impl(
+            H256::zero(),
+            vec![],
+            1,
+            U256::from(block_nonce),
+            U256::from(10),
+            1,
+            false,
+        );
+        let receipts = transactions
+            .iter()
+            .map(|_| {
+                Receipt::new(
+                    TransactionStatus::Success,
+                    U256::zero(),
+                    U256::zero(),
+                    false,
+                    vec![],
+                    Bloom::zero(),
+                    false,
+                    vec![],
+                    vec![],
+                    None,
+                )
+            })
+            .collect();
+        let receipts = BlockReceipts {
+            receipts,
+            block_number: 0,
+            secondary_reward: U256::zero(),
+            tx_execution_error_messages: vec![
+                String::new();
+                transactions.len()
+            ],
+        };
+        (
+            Arc::new(Block::new(block.block_header, transactions)),
+            Arc::new(receipts),
+        )
+    }
+
+    fn insert_epoch(
+        data_man: &BlockDataManager, epoch_number: u64,
+        (block, receipts): &(Arc<Block>, Arc<BlockReceipts>),
+    ) {
+        data_man.insert_address_tx_index(
+            epoch_number,
+            &block.hash(),
+            &[block.clone()],
+            &[receipts.clone()],
+        );
+    }
+
+    /// The `(epoch_number, index)` of the entries of an address before
+    /// `cursor`.
+    fn positions(
+        data_man: &BlockDataManager, n: u64, cursor: Option<(u64, u64)>,
+        limit: usize,
+    ) -> Vec<(u64, u64)> {
+        data_man
+            .address_transactions(
+                &address(n).with_native_space(),
+                cursor,
+                limit,
+            )
+            .into_iter()
+            .map(|(entry, _)| (entry.epoch_number, entry.index))
+            .collect()
+    }
+
+    #[test]
+    fn test_address_tx_index_pagination() {
+        let db_dir = TempDir::new("address_tx_index").unwrap();
+        let data_man = new_data_manager(&db_dir);
+        insert_epoch(&data_man, 1, &block(1, &[(0, 2)]));
+        insert_epoch(&data_man, 2, &block(2, &[(1, 3)]));
+        insert_epoch(&data_man, 3, &block(3, &[(2, 2), (3, 2)]));
+        // Epoch 300 is in the next bucket.
+        insert_epoch(&data_man, 300, &block(4, &[(4, 3)]));
+
+        assert_eq!(
+            positions(&data_man, 1, None, 10),
+            vec![(300, 0), (3, 1), (3, 0), (2, 0), (1, 0)]
+        );
+        assert_eq!(
+            positions(&data_man, 2, None, 10),
+            vec![(3, 1), (3, 0), (1, 0)]
+        );
+        assert_eq!(positions(&data_man, 3, None, 10), vec![(300, 0), (2, 0)]);
+
+        // Page from the newest entry with the cursor of the last entry.
+        assert_eq!(positions(&data_man, 1, None, 2), vec![(300, 0), (3, 1)]);
+        assert_eq!(
+            positions(&data_man, 1, Some((3, 1)), 2),
+            vec![(3, 0), (2, 0)]
+        );
+        assert_eq!(positions(&data_man, 1, Some((2, 0)), 2), vec![(1, 0)]);
+        assert!(positions(&data_man, 1, Some((1, 0)), 2).is_empty());
+        // A cursor in the next bucket.
+        assert_eq!(positions(&data_man, 3, Some((300, 0)), 2), vec![(2, 0)]);
+        assert!(positions(&data_man, 1, None, 0).is_empty());
+    }
+
+    #[test]
+    fn test_address_tx_index_reorg() {
+        let db_dir = TempDir::new("address_tx_index_reorg").unwrap();
+        let data_man = new_data_manager(&db_dir);
+        insert_epoch(&data_man, 1, &block(1, &[(0, 2)]));
+        insert_epoch(&data_man, 2, &block(2, &[(1, 2), (2, 3)]));
+        let old_block = block(3, &[(3, 4)]);
+        insert_epoch(&data_man, 300, &old_block);
+        // The same epoch indexed again is not changed.
+        insert_epoch(&data_man, 300, &old_block);
+        assert_eq!(positions(&data_man, 4, None, 10), vec![(300, 0)]);
+
+        // After a reorg, epoch 2 has another main block and the entries of
+        // the previous one are replaced.
+        let new_block = block(5, &[(1, 3)]);
+        insert_epoch(&data_man, 2, &new_block);
+        assert_eq!(positions(&data_man, 2, None, 10), vec![(1, 0)]);
+        assert_eq!(positions(&data_man, 3, None, 10), vec![(2, 0)]);
+        assert_eq!(
+            positions(&data_man, 1, None, 10),
+            vec![(300, 0), (2, 0), (1, 0)]
+        );
+        let entries = data_man.address_transactions(
+            &address(3).with_native_space(),
+            None,
+            10,
+        );
+        assert_eq!(entries[0].0.tx_hash, new_block.0.transactions[0].hash());
+        assert_eq!(entries[0].0.block_hash, new_block.0.hash());
+        assert_eq!(entries[0].1, new_block.0.hash());
+
+        // The new main block of epoch 300 is empty, so the bucket of address
+        // 4 is removed.
+        insert_epoch(&data_man, 300, &block(6, &[]));
+        assert!(positions(&data_man, 4, None, 10).is_empty());
+        assert!(data_man
+            .db_manager
+            .address_tx_buckets_from_db(&address_key(
+                &address(4).with_native_space()
+            ))
+            .is_none());
+        assert_eq!(positions(&data_man, 1, None, 10), vec![(2, 0), (1, 0)]);
+    }
+
+    #[test]
+    fn test_address_tx_index_eth_space() {
+        let db_dir = TempDir::new("address_tx_index_eth_space").unwrap();
+        let data_man = new_data_manager(&db_dir);
+        let eth_tx = Arc::new(
+            Eip155Transaction {
+                nonce: 0.into(),
+                action: Action::Call(address(3)),
+                chain_id: Some(1),
+                ..Default::default()
+            }
+            .fake_sign_rpc(address(1).with_evm_space()),
+        );
+        insert_epoch(&data_man, 1, &block(1, &[(0, 2)]));
+        insert_epoch(
+            &data_man,
+            2,
+            &block_with_transactions(2, vec![eth_tx.clone()]),
+        );
+
+        // The same address is indexed separately in each space.
+        let entries = data_man.address_transactions(
+            &address(1).with_evm_space(),
+            None,
+            10,
+        );
+        assert_eq!(entries.len(), 1);
+        assert_eq!((entries[0].0.epoch_number, entries[0].0.index), (2, 0));
+        assert_eq!(entries[0].0.tx_hash, eth_tx.hash());
+        assert_eq!(positions(&data_man, 1, None, 10), vec![(1, 0)]);
+
+        let entries = data_man.address_transactions(
+            &address(3).with_evm_space(),
+            None,
+            10,
+        );
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0.tx_hash, eth_tx.hash());
+        assert!(positions(&data_man, 3, None, 10).is_empty());
+        assert!(data_man
+            .address_transactions(&address(2).with_evm_space(), None, 10)
+            .is_empty());
+    }
+}
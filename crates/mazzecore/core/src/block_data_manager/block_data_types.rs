@@ -1,9 +1,11 @@
-use mazze_execute_helper::exec_tracer::BlockExecTraces;
+use mazze_execute_helper::{
+    exec_tracer::BlockExecTraces, phantom_tx::PhantomTransaction,
+};
 use mazze_internal_common::{DatabaseDecodable, DatabaseEncodable};
 use mazze_types::{Address, Bloom, H256, U256};
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use malloc_size_of_derive::MallocSizeOf as DeriveMallocSizeOf;
-use primitives::BlockReceipts;
+use primitives::{Action, BlockReceipts, TransactionStatus};
 use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
 use rlp_derive::{RlpDecodable, RlpEncodable};
 use smart_default::SmartDefault;
@@ -361,6 +363,188 @@ impl PosRewardInfo {
     }
 }
 
+/// A cross-space call or withdrawal of a native transaction, which shows up
+/// as a phantom transaction in the eth space.
+#[derive(Clone, Debug, RlpEncodable, RlpDecodable)]
+pub struct CrossSpaceTransfer {
+    /// The block packing the native transaction.
+    pub block_hash: H256,
+    pub native_tx_hash: H256,
+    pub phantom_tx_hash: H256,
+    /// The sender in the eth space. It's the zero address if the value moves
+    /// into the eth space.
+    pub from: Address,
+    /// The receiver in the eth space, or `Create` for a contract creation. It
+    /// calls the zero address if the value moves out of the eth space.
+    pub action: Action,
+    pub value: U256,
+    pub success: bool,
+}
+
+impl CrossSpaceTransfer {
+    pub fn new(
+        block_hash: H256, native_tx_hash: H256, phantom_tx: PhantomTransaction,
+        evm_chain_id: u32,
+    ) -> Self {
+        let from = phantom_tx.from;
+        let action = phantom_tx.action.clone();
+        let value = phantom_tx.value;
+        let success = phantom_tx.outcome_status == TransactionStatus::Success;
+        let phantom_tx_hash = phantom_tx.into_eip155(evm_chain_id).hash();
+        CrossSpaceTransfer {
+            block_hash,
+            native_tx_hash,
+            phantom_tx_hash,
+            from,
+            action,
+            value,
+            success,
+        }
+    }
+}
+
+/// The epochs indexed in the log index, which are contiguous.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    RlpEncodable,
+    RlpDecodable,
+    DeriveMallocSizeOf,
+)]
+pub struct LogIndexRange {
+    pub lower: u64,
+    pub upper: u64,
+}
+
+/// The main block of an indexed epoch and the index keys of its logs, which
+/// are removed from the log index if the epoch is indexed again with another
+/// main block.
+#[derive(Clone, Debug, RlpEncodable, RlpDecodable)]
+pub struct LogIndexEpoch {
+    pub epoch_hash: H256,
+    pub index_keys: Vec<Vec<u8>>,
+}
+
+/// A transaction sent or received by an address in the address transaction
+/// index, where `index` is the position of the transaction in its epoch.
+#[derive(Clone, Debug, RlpEncodable, RlpDecodable)]
+pub struct AddressTxEntry {
+    pub epoch_number: u64,
+    pub index: u64,
+    pub block_hash: H256,
+    pub tx_hash: H256,
+}
+
+/// The main block of an epoch in the address transaction index and the
+/// addresses of its transactions, whose entries are removed if the epoch is
+/// indexed again with another main block.
+#[derive(Clone, Debug, RlpEncodable, RlpDecodable)]
+pub struct AddressTxEpoch {
+    pub epoch_hash: H256,
+    pub address_keys: Vec<Vec<u8>>,
+}
+
+/// The epochs moved to the freezer, which are contiguous.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct FreezerEpochRange {
+    pub lower: u64,
+    pub upper: u64,
+}
+
+/// The position of a compressed value in the segment files of the freezer.
+#[derive(Clone, Copy, Debug, PartialEq, RlpEncodable, RlpDecodable)]
+pub struct FreezerLocation {
+    pub segment: u64,
+    pub offset: u64,
+    pub length: u64,
+}
+
+/// The statistics of the state in a snapshot, collected by walking all its
+/// key-values.
+#[derive(Clone, Debug, Default, RlpEncodable, RlpDecodable)]
+pub struct StateStats {
+    pub snapshot_epoch_id: H256,
+    pub snapshot_height: u64,
+    pub native: SpaceStateStats,
+    pub ethereum: SpaceStateStats,
+    pub key_types: Vec<KeyTypeStats>,
+}
+
+/// The accounts and storage of a space in `StateStats`. The contracts with
+/// the most storage slots and collateral are sorted from the largest, and
+/// there is no collateral in the eSpace.
+#[derive(Clone, Debug, Default, RlpEncodable, RlpDecodable)]
+pub struct SpaceStateStats {
+    pub accounts: u64,
+    pub contracts: u64,
+    pub storage_slots: u64,
+    pub top_contracts_by_slots: Vec<ContractSlots>,
+    pub top_contracts_by_collateral: Vec<ContractCollateral>,
+}
+
+#[derive(Clone, Debug, PartialEq, RlpEncodable, RlpDecodable)]
+pub struct ContractSlots {
+    pub address: Address,
+    pub slots: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, RlpEncodable, RlpDecodable)]
+pub struct ContractCollateral {
+    pub address: Address,
+    pub collateral: U256,
+}
+
+/// The number and total size of the keys of a `StorageKey` variant in both
+/// spaces.
+#[derive(Clone, Debug, Default, PartialEq, RlpEncodable, RlpDecodable)]
+pub struct KeyTypeStats {
+    pub key_type: String,
+    pub keys: u64,
+    pub key_bytes: u64,
+    pub value_bytes: u64,
+}
+
+/// The original values of the state keys modified by an epoch, which revert
+/// the state after the epoch to the state before it.
+#[derive(Clone, Debug, Default, RlpEncodable, RlpDecodable)]
+pub struct StateChangeSet {
+    pub changes: Vec<StateChange>,
+}
+
+/// The key in the storage format and its value before the epoch. `None` means
+/// that the key did not exist.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateChange {
+    pub key: Vec<u8>,
+    pub original_value: Option<Vec<u8>>,
+}
+
+impl Encodable for StateChange {
+    fn rlp_append(&self, stream: &mut RlpStream) {
+        stream.begin_list(2).append(&self.key);
+        match &self.original_value {
+            Some(value) => stream.begin_list(1).append(value),
+            None => stream.begin_list(0),
+        };
+    }
+}
+
+impl Decodable for StateChange {
+    fn decode(rlp: &Rlp) -> Result<StateChange, DecoderError> {
+        let original_value = rlp.at(1)?;
+        Ok(StateChange {
+            key: rlp.val_at(0)?,
+            original_value: match original_value.item_count()? {
+                0 => None,
+                _ => Some(original_value.val_at(0)?),
+            },
+        })
+    }
+}
+
 pub fn db_encode_list<T>(list: &[T]) -> Vec<u8>
 where T: DatabaseEncodable {
     let mut rlp_stream = RlpStream::new();
@@ -388,3 +572,12 @@ impl_db_encoding_as_rlp!(EpochExecutionContext);
 impl_db_encoding_as_rlp!(BlockRewardResult);
 impl_db_encoding_as_rlp!(BlamedHeaderVerifiedRoots);
 impl_db_encoding_as_rlp!(PosRewardInfo);
+impl_db_encoding_as_rlp!(CrossSpaceTransfer);
+impl_db_encoding_as_rlp!(LogIndexRange);
+impl_db_encoding_as_rlp!(LogIndexEpoch);
+impl_db_encoding_as_rlp!(AddressTxEntry);
+impl_db_encoding_as_rlp!(AddressTxEpoch);
+impl_db_encoding_as_rlp!(FreezerEpochRange);
+impl_db_encoding_as_rlp!(FreezerLocation);
+impl_db_encoding_as_rlp!(StateStats);
+impl_db_encoding_as_rlp!(StateChangeSet);
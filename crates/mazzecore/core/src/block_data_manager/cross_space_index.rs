@@ -0,0 +1,300 @@
+//! An optional index of the cross-space transfers. The transfers of an epoch
+//! are kept by its main block, and each native or phantom transaction keeps
+//! the epochs it is indexed in. A transaction may be indexed in several epochs
+//! after the main chain changes, so the callers check which of them is on the
+//! current main chain.
+
+use super::{BlockDataManager, CrossSpaceTransfer};
+use mazze_execute_helper::phantom_tx::recover_phantom;
+use mazze_types::H256;
+use primitives::{Block, BlockReceipts, TransactionStatus};
+use std::{collections::BTreeSet, sync::Arc};
+
+impl BlockDataManager {
+    pub fn cross_space_index_enabled(&self) -> bool {
+        self.config.persist_cross_space_index
+    }
+
+    /// Index the cross-space transfers of an epoch executed on the main chain
+    /// if `persist_cross_space_index` is enabled, so that they can be found by
+    /// either the native transaction hash or the phantom transaction hash.
+    /// The epochs without any cross-space transfer are not recorded.
+    pub fn insert_cross_space_transfers(
+        &self, epoch_hash: &H256, epoch_blocks: &[Arc<Block>],
+        epoch_receipts: &[Arc<BlockReceipts>], evm_chain_id: u32,
+    ) {
+        if !self.config.persist_cross_space_index {
+            return;
+        }
+        let mut transfers = vec![];
+        for (block, receipts) in epoch_blocks.iter().zip(epoch_receipts) {
+            for (tx, receipt) in
+                block.transactions.iter().zip(&receipts.receipts)
+            {
+                if !matches!(
+                    receipt.outcome_status,
+                    TransactionStatus::Success | TransactionStatus::Failure
+                ) {
+                    continue;
+                }
+                for phantom_tx in recover_phantom(&receipt.logs, tx.hash()) {
+                    transfers.push(CrossSpaceTransfer::new(
+                        block.hash(),
+                        tx.hash(),
+                        phantom_tx,
+                        evm_chain_id,
+                    ));
+                }
+            }
+        }
+        if transfers.is_empty() {
+            return;
+        }
+
+        let _lock = self.cross_space_index_lock.lock();
+        for tx_hash in transfer_tx_hashes(&transfers) {
+            let mut epochs = self
+                .db_manager
+                .cross_space_epochs_from_db(&tx_hash)
+                .unwrap_or_default();
+            // The epoch is executed again with the same main block.
+            if epochs.contains(epoch_hash) {
+                continue;
+            }
+            epochs.push(*epoch_hash);
+            self.db_manager
+                .insert_cross_space_epochs_to_db(&tx_hash, &epochs);
+        }
+        self.db_manager
+            .insert_cross_space_transfers_to_db(epoch_hash, &transfers);
+    }
+
+    /// Remove the cross-space transfers indexed with `epoch_hash` as the main
+    /// block, and the epoch from the indexed epochs of their transactions.
+    pub(super) fn remove_cross_space_transfers(&self, epoch_hash: &H256) {
+        let transfers =
+            match self.db_manager.cross_space_transfers_from_db(epoch_hash) {
+                Some(transfers) => transfers,
+                None => return,
+            };
+
+        let _lock = self.cross_space_index_lock.lock();
+        for tx_hash in transfer_tx_hashes(&transfers) {
+            let mut epochs = self
+                .db_manager
+                .cross_space_epochs_from_db(&tx_hash)
+                .unwrap_or_default();
+            epochs.retain(|h| h != epoch_hash);
+            if epochs.is_empty() {
+                self.db_manager.remove_cross_space_epochs_from_db(&tx_hash);
+            } else {
+                self.db_manager
+                    .insert_cross_space_epochs_to_db(&tx_hash, &epochs);
+            }
+        }
+        self.db_manager
+            .remove_cross_space_transfers_from_db(epoch_hash);
+    }
+
+    /// The cross-space transfers in an epoch. It's `None` if the epoch has no
+    /// transfer or is not indexed.
+    pub fn cross_space_transfers_by_epoch(
+        &self, epoch_hash: &H256,
+    ) -> Option<Vec<CrossSpaceTransfer>> {
+        self.db_manager.cross_space_transfers_from_db(epoch_hash)
+    }
+
+    /// The indexed epochs with a cross-space transfer of the transaction,
+    /// which is either a native transaction or a phantom transaction, in the
+    /// order they are indexed. At most one of them is on the current main
+    /// chain.
+    pub fn cross_space_epochs_by_tx_hash(&self, tx_hash: &H256) -> Vec<H256> {
+        self.db_manager
+            .cross_space_epochs_from_db(tx_hash)
+            .unwrap_or_default()
+    }
+}
+
+fn transfer_tx_hashes(transfers: &[CrossSpaceTransfer]) -> BTreeSet<H256> {
+    transfers
+        .iter()
+        .flat_map(|transfer| {
+            [transfer.native_tx_hash, transfer.phantom_tx_hash]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        block_data_manager::{BlockDataManager, DbType},
+        pow::PowComputer,
+        sync::utils::{create_simple_block_impl, initialize_data_manager},
+    };
+    use mazze_executor::{
+        internal_contract::{
+            cross_space_events::WithdrawEvent, SolidityEventTrait,
+        },
+        machine::VmFactory,
+    };
+    use mazze_parameters::internal_contract_addresses::CROSS_SPACE_CONTRACT_ADDRESS;
+    use mazze_types::{Address, AddressSpaceUtil, Bloom, Space, H256, U256};
+    use primitives::{
+        transaction::native_transaction::NativeTransaction, Block,
+        BlockReceipts, LogEntry, Receipt, TransactionStatus,
+    };
+    use solidity_abi::{ABIEncodable, EventIndexEncodable};
+    use std::sync::Arc;
+    use tempdir::TempDir;
+
+    fn new_data_manager(db_dir: &TempDir) -> Arc<BlockDataManager> {
+        let (mut data_man, _) = initialize_data_manager(
+            db_dir.path().to_str().unwrap(),
+            DbType::Rocksdb,
+            Arc::new(PowComputer::new()),
+            VmFactory::new(1024 * 32),
+        );
+        let config = &mut Arc::get_mut(&mut data_man).unwrap().config;
+        config.persist_cross_space_index = true;
+        config.additional_maintained_execution_result_epoch_count = Some(0);
+        data_man
+    }
+
+    /// A block with a native transaction withdrawing from the eth space.
+    fn withdraw_block(nonce: u64) -> (Arc<Block>, Arc<BlockReceipts>) {
+        let (_, block) = create_simple_block_impl(
+            H256::zero(),
+            vec![],
+            1,
+            U256::from(nonce),
+            U256::from(10),
+            1,
+            false,
+        );
+        let tx = NativeTransaction {
+            nonce: nonce.into(),
+            ..Default::default()
+        }
+        .fake_sign(Address::from_low_u64_be(1).with_native_space());
+        let block = Block::new(block.block_header, vec![Arc::new(tx)]);
+
+        let mut topics = vec![WithdrawEvent::EVENT_SIG];
+        topics.extend_from_slice(
+            &(Address::from_low_u64_be(2).0, Address::from_low_u64_be(1))
+                .indexed_event_encode(),
+        );
+        let log = LogEntry {
+            address: CROSS_SPACE_CONTRACT_ADDRESS,
+            topics,
+            data: (U256::from(100), U256::zero()).abi_encode(),
+            space: Space::Native,
+        };
+        let receipt = Receipt::new(
+            TransactionStatus::Success,
+            U256::zero(),
+            U256::zero(),
+            false,
+            vec![log],
+            Bloom::zero(),
+            false,
+            vec![],
+            vec![],
+            None,
+        );
+        let receipts = BlockReceipts {
+            receipts: vec![receipt],
+            block_number: 0,
+            secondary_reward: U256::zero(),
+            tx_execution_error_messages: vec![String::new()],
+        };
+        (Arc::new(block), Arc::new(receipts))
+    }
+
+    #[test]
+    fn test_cross_space_index_reorg_and_gc() {
+        let db_dir = TempDir::new("cross_space_index").unwrap();
+        let data_man = new_data_manager(&db_dir);
+        let (block_a, receipts_a) = withdraw_block(1);
+        let (block_b, _) = withdraw_block(2);
+        let native_tx_hash = block_a.transactions[0].hash();
+
+        // Block A is the main block of its epoch.
+        data_man.insert_cross_space_transfers(
+            &block_a.hash(),
+            &[block_a.clone()],
+            &[receipts_a.clone()],
+            1,
+        );
+        let transfers = data_man
+            .cross_space_transfers_by_epoch(&block_a.hash())
+            .unwrap();
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].native_tx_hash, native_tx_hash);
+        let phantom_tx_hash = transfers[0].phantom_tx_hash;
+        assert_eq!(
+            data_man.cross_space_epochs_by_tx_hash(&native_tx_hash),
+            vec![block_a.hash()]
+        );
+        assert_eq!(
+            data_man.cross_space_epochs_by_tx_hash(&phantom_tx_hash),
+            vec![block_a.hash()]
+        );
+
+        // The same epoch executed again is not indexed twice.
+        data_man.insert_cross_space_transfers(
+            &block_a.hash(),
+            &[block_a.clone()],
+            &[receipts_a.clone()],
+            1,
+        );
+        assert_eq!(
+            data_man.cross_space_epochs_by_tx_hash(&native_tx_hash),
+            vec![block_a.hash()]
+        );
+
+        // After a reorg, block A is in the epoch of block B. Both epochs are
+        // kept, as either of them may be on the main chain.
+        data_man.insert_cross_space_transfers(
+            &block_b.hash(),
+            &[block_a.clone()],
+            &[receipts_a.clone()],
+            1,
+        );
+        assert_eq!(
+            data_man.cross_space_epochs_by_tx_hash(&native_tx_hash),
+            vec![block_a.hash(), block_b.hash()]
+        );
+
+        // The epochs are garbage collected with the execution results of
+        // their main blocks.
+        data_man
+            .insert_executed_epoch_set_hashes_to_db(1, &vec![block_a.hash()]);
+        data_man.insert_skipped_epoch_set_hashes_to_db(1, &vec![]);
+        data_man.gc_base_epoch(1);
+        assert!(data_man
+            .cross_space_transfers_by_epoch(&block_a.hash())
+            .is_none());
+        assert_eq!(
+            data_man.cross_space_epochs_by_tx_hash(&native_tx_hash),
+            vec![block_b.hash()]
+        );
+        assert_eq!(
+            data_man.cross_space_epochs_by_tx_hash(&phantom_tx_hash),
+            vec![block_b.hash()]
+        );
+
+        data_man
+            .insert_executed_epoch_set_hashes_to_db(2, &vec![block_b.hash()]);
+        data_man.insert_skipped_epoch_set_hashes_to_db(2, &vec![]);
+        data_man.gc_base_epoch(2);
+        assert!(data_man
+            .cross_space_transfers_by_epoch(&block_b.hash())
+            .is_none());
+        assert!(data_man
+            .cross_space_epochs_by_tx_hash(&native_tx_hash)
+            .is_empty());
+        assert!(data_man
+            .cross_space_epochs_by_tx_hash(&phantom_tx_hash)
+            .is_empty());
+    }
+}
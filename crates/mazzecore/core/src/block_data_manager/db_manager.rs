@@ -1,19 +1,24 @@
 use crate::{
     block_data_manager::{
-        db_decode_list, db_encode_list, BlamedHeaderVerifiedRoots,
+        db_decode_list, db_encode_list, freezer::Freezer, AddressTxEntry,
+        AddressTxEpoch, BlamedHeaderVerifiedRoots,
         BlockExecutionResultWithEpoch, BlockRewardResult, BlockTracesWithEpoch,
-        CheckpointHashes, DataVersionTuple, EpochExecutionContext,
-        LocalBlockInfo, PosRewardInfo,
+        CheckpointHashes, CrossSpaceTransfer, DataVersionTuple,
+        EpochExecutionContext, FreezerEpochRange, FreezerLocation,
+        LocalBlockInfo, LogIndexEpoch, LogIndexRange, PosRewardInfo,
+        StateChangeSet, StateStats,
     },
     db::{
-        COL_BLAMED_HEADER_VERIFIED_ROOTS, COL_BLOCKS, COL_BLOCK_TRACES,
-        COL_EPOCH_NUMBER, COL_HASH_BY_BLOCK_NUMBER, COL_MISC,
-        COL_REWARD_BY_POS_EPOCH, COL_TX_INDEX,
+        COL_ADDRESS_TX_INDEX, COL_BLAMED_HEADER_VERIFIED_ROOTS, COL_BLOCKS,
+        COL_BLOCK_TRACES, COL_EPOCH_NUMBER, COL_FREEZER_INDEX,
+        COL_HASH_BY_BLOCK_NUMBER, COL_LOG_INDEX, COL_MISC,
+        COL_REWARD_BY_POS_EPOCH, COL_STATE_CHANGE_SET, COL_STATE_STATS,
+        COL_TX_INDEX,
     },
     pow::PowComputer,
     verification::VerificationConfig,
 };
-use byteorder::{ByteOrder, LittleEndian};
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
 use mazze_internal_common::{
     DatabaseDecodable, DatabaseEncodable, EpochExecutionCommitment,
 };
@@ -25,7 +30,7 @@ use db::SystemDB;
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use primitives::{Block, BlockHeader, SignedTransaction, TransactionIndex};
 use rlp::Rlp;
-use std::{collections::HashMap, fs, path::Path, sync::Arc};
+use std::{collections::HashMap, fs, io, path::Path, sync::Arc};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
@@ -37,8 +42,18 @@ const EPOCH_CONSENSUS_EXECUTION_INFO_SUFFIX_BYTE: u8 = 5;
 const EPOCH_EXECUTED_BLOCK_SET_SUFFIX_BYTE: u8 = 6;
 const EPOCH_SKIPPED_BLOCK_SET_SUFFIX_BYTE: u8 = 7;
 const BLOCK_REWARD_RESULT_SUFFIX_BYTE: u8 = 8;
+const CROSS_SPACE_TRANSFERS_SUFFIX_BYTE: u8 = 9;
+const CROSS_SPACE_TX_SUFFIX_BYTE: u8 = 10;
 const BLOCK_TERMINAL_KEY: &[u8] = b"block_terminals";
 const GC_PROGRESS_KEY: &[u8] = b"gc_progress";
+const FREEZER_EPOCH_RANGE_KEY: &[u8] = b"freezer_epoch_range";
+const LOG_INDEX_RANGE_KEY: &[u8] = b"log_index_range";
+const LATEST_STATE_STATS_KEY: &[u8] = b"latest_state_stats";
+const LOG_INDEX_EPOCH_PREFIX_BYTE: u8 = 0;
+const LOG_INDEX_POSTINGS_PREFIX_BYTE: u8 = 1;
+const ADDRESS_TX_EPOCH_PREFIX_BYTE: u8 = 0;
+const ADDRESS_TX_ENTRIES_PREFIX_BYTE: u8 = 1;
+const ADDRESS_TX_BUCKETS_PREFIX_BYTE: u8 = 2;
 
 #[derive(Clone, Copy, Hash, Ord, PartialOrd, Eq, PartialEq, EnumIter)]
 enum DBTable {
@@ -50,6 +65,11 @@ enum DBTable {
     BlockTraces,
     HashByBlockNumber,
     RewardByPosEpoch,
+    LogIndex,
+    AddressTxIndex,
+    FreezerIndex,
+    StateStats,
+    StateChangeSet,
 }
 
 fn rocks_db_col(table: DBTable) -> u32 {
@@ -62,6 +82,11 @@ fn rocks_db_col(table: DBTable) -> u32 {
         DBTable::BlockTraces => COL_BLOCK_TRACES,
         DBTable::HashByBlockNumber => COL_HASH_BY_BLOCK_NUMBER,
         DBTable::RewardByPosEpoch => COL_REWARD_BY_POS_EPOCH,
+        DBTable::LogIndex => COL_LOG_INDEX,
+        DBTable::AddressTxIndex => COL_ADDRESS_TX_INDEX,
+        DBTable::FreezerIndex => COL_FREEZER_INDEX,
+        DBTable::StateStats => COL_STATE_STATS,
+        DBTable::StateChangeSet => COL_STATE_CHANGE_SET,
     }
 }
 
@@ -75,6 +100,11 @@ fn sqlite_db_table(table: DBTable) -> String {
         DBTable::BlockTraces => "block_traces",
         DBTable::HashByBlockNumber => "hash_by_block_number",
         DBTable::RewardByPosEpoch => "reward_by_pos_epoch",
+        DBTable::LogIndex => "log_index",
+        DBTable::AddressTxIndex => "address_tx_index",
+        DBTable::FreezerIndex => "freezer_index",
+        DBTable::StateStats => "state_stats",
+        DBTable::StateChangeSet => "state_change_set",
     }
     .into()
 }
@@ -82,6 +112,8 @@ fn sqlite_db_table(table: DBTable) -> String {
 pub struct DBManager {
     table_db: HashMap<DBTable, Box<dyn KeyValueDbTrait<ValueType = Box<[u8]>>>>,
     pow: Arc<PowComputer>,
+    /// The block data of old epochs is moved here if it is set.
+    freezer: Option<Freezer>,
 }
 
 impl DBManager {
@@ -98,7 +130,11 @@ impl DBManager {
                     as Box<dyn KeyValueDbTrait<ValueType = Box<[u8]>>>,
             );
         }
-        Self { table_db, pow }
+        Self {
+            table_db,
+            pow,
+            freezer: None,
+        }
     }
 }
 
@@ -132,11 +168,69 @@ impl DBManager {
                     as Box<dyn KeyValueDbTrait<ValueType = Box<[u8]>>>,
             );
         }
-        Self { table_db, pow }
+        Self {
+            table_db,
+            pow,
+            freezer: None,
+        }
     }
 }
 
 impl DBManager {
+    pub fn with_freezer(mut self, freezer: Freezer) -> Self {
+        self.freezer = Some(freezer);
+        self
+    }
+
+    pub fn freezer_enabled(&self) -> bool { self.freezer.is_some() }
+
+    /// Move the bodies, execution results and traces of the blocks into the
+    /// freezer. The data is removed from the database only after it is
+    /// synced to the freezer and indexed, so it is kept in the database if
+    /// the freezer fails.
+    pub fn freeze_blocks(&self, block_hashes: &[H256]) -> io::Result<()> {
+        let freezer = match &self.freezer {
+            Some(freezer) => freezer,
+            None => return Ok(()),
+        };
+        let mut frozen = vec![];
+        for hash in block_hashes {
+            for (table, db_key) in [
+                (DBTable::Blocks, block_body_key(hash)),
+                (DBTable::Blocks, block_execution_result_key(hash)),
+                (DBTable::BlockTraces, hash.as_bytes().to_vec()),
+            ] {
+                if let Some(value) = self.load_from_db(table, &db_key) {
+                    let location = freezer.append(&value)?;
+                    frozen.push((table, db_key, location));
+                }
+            }
+        }
+        if frozen.is_empty() {
+            return Ok(());
+        }
+        freezer.sync()?;
+        for (table, db_key, location) in &frozen {
+            self.insert_encodable_val(
+                DBTable::FreezerIndex,
+                &freezer_index_key(*table, db_key),
+                location,
+            );
+        }
+        for (table, db_key, _) in &frozen {
+            self.remove_from_db(*table, db_key);
+        }
+        Ok(())
+    }
+
+    pub fn insert_freezer_epoch_range_to_db(&self, range: &FreezerEpochRange) {
+        self.insert_encodable_val(DBTable::Misc, FREEZER_EPOCH_RANGE_KEY, range)
+    }
+
+    pub fn freezer_epoch_range_from_db(&self) -> Option<FreezerEpochRange> {
+        self.load_decodable_val(DBTable::Misc, FREEZER_EPOCH_RANGE_KEY)
+    }
+
     pub fn insert_block_traces_to_db(
         &self, block_hash: &H256, block_traces: &BlockTracesWithEpoch,
     ) {
@@ -150,9 +244,13 @@ impl DBManager {
     pub fn block_traces_from_db(
         &self, block_hash: &H256,
     ) -> Option<BlockTracesWithEpoch> {
-        let block_traces = self
-            .load_decodable_val(DBTable::BlockTraces, block_hash.as_bytes())?;
-        Some(block_traces)
+        let encoded = self.load_from_db_or_freezer(
+            DBTable::BlockTraces,
+            block_hash.as_bytes(),
+        )?;
+        Some(
+            BlockTracesWithEpoch::db_decode(&encoded).expect("decode succeeds"),
+        )
     }
 
     /// TODO Use new_with_rlp_size
@@ -197,6 +295,241 @@ impl DBManager {
         self.load_decodable_val(DBTable::Transactions, hash.as_bytes())
     }
 
+    pub fn insert_cross_space_epochs_to_db(
+        &self, tx_hash: &H256, epoch_hashes: &Vec<H256>,
+    ) {
+        self.insert_encodable_list(
+            DBTable::Transactions,
+            &cross_space_tx_key(tx_hash),
+            epoch_hashes,
+        )
+    }
+
+    pub fn cross_space_epochs_from_db(
+        &self, tx_hash: &H256,
+    ) -> Option<Vec<H256>> {
+        self.load_decodable_list(
+            DBTable::Transactions,
+            &cross_space_tx_key(tx_hash),
+        )
+    }
+
+    pub fn remove_cross_space_epochs_from_db(&self, tx_hash: &H256) {
+        self.remove_from_db(DBTable::Transactions, &cross_space_tx_key(tx_hash))
+    }
+
+    pub fn insert_cross_space_transfers_to_db(
+        &self, epoch_hash: &H256, transfers: &Vec<CrossSpaceTransfer>,
+    ) {
+        self.insert_encodable_list(
+            DBTable::Blocks,
+            &cross_space_transfers_key(epoch_hash),
+            transfers,
+        )
+    }
+
+    pub fn cross_space_transfers_from_db(
+        &self, epoch_hash: &H256,
+    ) -> Option<Vec<CrossSpaceTransfer>> {
+        self.load_decodable_list(
+            DBTable::Blocks,
+            &cross_space_transfers_key(epoch_hash),
+        )
+    }
+
+    pub fn remove_cross_space_transfers_from_db(&self, epoch_hash: &H256) {
+        self.remove_from_db(
+            DBTable::Blocks,
+            &cross_space_transfers_key(epoch_hash),
+        )
+    }
+
+    pub fn insert_log_index_range_to_db(&self, range: &LogIndexRange) {
+        self.insert_encodable_val(DBTable::Misc, LOG_INDEX_RANGE_KEY, range)
+    }
+
+    pub fn log_index_range_from_db(&self) -> Option<LogIndexRange> {
+        self.load_decodable_val(DBTable::Misc, LOG_INDEX_RANGE_KEY)
+    }
+
+    pub fn insert_log_index_epoch_to_db(
+        &self, epoch_number: u64, epoch: &LogIndexEpoch,
+    ) {
+        self.insert_encodable_val(
+            DBTable::LogIndex,
+            &log_index_epoch_key(epoch_number),
+            epoch,
+        )
+    }
+
+    pub fn log_index_epoch_from_db(
+        &self, epoch_number: u64,
+    ) -> Option<LogIndexEpoch> {
+        self.load_decodable_val(
+            DBTable::LogIndex,
+            &log_index_epoch_key(epoch_number),
+        )
+    }
+
+    /// The postings are the sorted epoch numbers in a bucket with a log
+    /// matching `index_key`.
+    pub fn insert_log_index_postings_to_db(
+        &self, index_key: &[u8], bucket: u64, epochs: &Vec<u64>,
+    ) {
+        self.insert_to_db(
+            DBTable::LogIndex,
+            &log_index_postings_key(index_key, bucket),
+            rlp::encode_list::<u64, _>(epochs).to_vec(),
+        )
+    }
+
+    pub fn log_index_postings_from_db(
+        &self, index_key: &[u8], bucket: u64,
+    ) -> Option<Vec<u64>> {
+        let encoded = self.load_from_db(
+            DBTable::LogIndex,
+            &log_index_postings_key(index_key, bucket),
+        )?;
+        Some(Rlp::new(&encoded).as_list().expect("decode succeeds"))
+    }
+
+    pub fn remove_log_index_postings_from_db(
+        &self, index_key: &[u8], bucket: u64,
+    ) {
+        self.remove_from_db(
+            DBTable::LogIndex,
+            &log_index_postings_key(index_key, bucket),
+        )
+    }
+
+    pub fn insert_address_tx_epoch_to_db(
+        &self, epoch_number: u64, epoch: &AddressTxEpoch,
+    ) {
+        self.insert_encodable_val(
+            DBTable::AddressTxIndex,
+            &address_tx_epoch_key(epoch_number),
+            epoch,
+        )
+    }
+
+    pub fn address_tx_epoch_from_db(
+        &self, epoch_number: u64,
+    ) -> Option<AddressTxEpoch> {
+        self.load_decodable_val(
+            DBTable::AddressTxIndex,
+            &address_tx_epoch_key(epoch_number),
+        )
+    }
+
+    /// The entries of an address in a bucket, sorted by the epoch number and
+    /// the index in the epoch.
+    pub fn insert_address_tx_entries_to_db(
+        &self, address_key: &[u8], bucket: u64, entries: &Vec<AddressTxEntry>,
+    ) {
+        self.insert_encodable_list(
+            DBTable::AddressTxIndex,
+            &address_tx_entries_key(address_key, bucket),
+            entries,
+        )
+    }
+
+    pub fn address_tx_entries_from_db(
+        &self, address_key: &[u8], bucket: u64,
+    ) -> Option<Vec<AddressTxEntry>> {
+        self.load_decodable_list(
+            DBTable::AddressTxIndex,
+            &address_tx_entries_key(address_key, bucket),
+        )
+    }
+
+    pub fn remove_address_tx_entries_from_db(
+        &self, address_key: &[u8], bucket: u64,
+    ) {
+        self.remove_from_db(
+            DBTable::AddressTxIndex,
+            &address_tx_entries_key(address_key, bucket),
+        )
+    }
+
+    /// The sorted buckets with any entry of an address.
+    pub fn insert_address_tx_buckets_to_db(
+        &self, address_key: &[u8], buckets: &Vec<u64>,
+    ) {
+        self.insert_to_db(
+            DBTable::AddressTxIndex,
+            &address_tx_buckets_key(address_key),
+            rlp::encode_list::<u64, _>(buckets).to_vec(),
+        )
+    }
+
+    pub fn address_tx_buckets_from_db(
+        &self, address_key: &[u8],
+    ) -> Option<Vec<u64>> {
+        let encoded = self.load_from_db(
+            DBTable::AddressTxIndex,
+            &address_tx_buckets_key(address_key),
+        )?;
+        Some(Rlp::new(&encoded).as_list().expect("decode succeeds"))
+    }
+
+    pub fn remove_address_tx_buckets_from_db(&self, address_key: &[u8]) {
+        self.remove_from_db(
+            DBTable::AddressTxIndex,
+            &address_tx_buckets_key(address_key),
+        )
+    }
+
+    /// The state statistics of a snapshot, keyed by the snapshot height.
+    pub fn insert_state_stats_to_db(&self, stats: &StateStats) {
+        self.insert_encodable_val(
+            DBTable::StateStats,
+            &stats.snapshot_height.to_be_bytes(),
+            stats,
+        )
+    }
+
+    pub fn state_stats_from_db(
+        &self, snapshot_height: u64,
+    ) -> Option<StateStats> {
+        self.load_decodable_val(
+            DBTable::StateStats,
+            &snapshot_height.to_be_bytes(),
+        )
+    }
+
+    pub fn insert_latest_state_stats_height_to_db(&self, snapshot_height: u64) {
+        self.insert_encodable_val(
+            DBTable::Misc,
+            LATEST_STATE_STATS_KEY,
+            &snapshot_height,
+        )
+    }
+
+    pub fn latest_state_stats_height_from_db(&self) -> Option<u64> {
+        self.load_decodable_val(DBTable::Misc, LATEST_STATE_STATS_KEY)
+    }
+
+    /// The state change set of an executed epoch, keyed by the epoch hash.
+    pub fn insert_state_change_set_to_db(
+        &self, epoch_hash: &H256, change_set: &StateChangeSet,
+    ) {
+        self.insert_encodable_val(
+            DBTable::StateChangeSet,
+            epoch_hash.as_bytes(),
+            change_set,
+        )
+    }
+
+    pub fn state_change_set_from_db(
+        &self, epoch_hash: &H256,
+    ) -> Option<StateChangeSet> {
+        self.load_decodable_val(DBTable::StateChangeSet, epoch_hash.as_bytes())
+    }
+
+    pub fn remove_state_change_set_from_db(&self, epoch_hash: &H256) {
+        self.remove_from_db(DBTable::StateChangeSet, epoch_hash.as_bytes())
+    }
+
     pub fn insert_hash_by_block_number_to_db(
         &self, block_number: u64, hash: &H256,
     ) {
@@ -285,8 +618,8 @@ impl DBManager {
     pub fn block_body_from_db(
         &self, hash: &H256,
     ) -> Option<Vec<Arc<SignedTransaction>>> {
-        let encoded =
-            self.load_from_db(DBTable::Blocks, &block_body_key(hash))?;
+        let encoded = self
+            .load_from_db_or_freezer(DBTable::Blocks, &block_body_key(hash))?;
         let rlp = Rlp::new(&encoded);
         Some(
             Block::decode_body_with_tx_public(&rlp)
@@ -321,9 +654,13 @@ impl DBManager {
     pub fn block_execution_result_from_db(
         &self, hash: &H256,
     ) -> Option<BlockExecutionResultWithEpoch> {
-        self.load_decodable_val(
+        let encoded = self.load_from_db_or_freezer(
             DBTable::Blocks,
             &block_execution_result_key(hash),
+        )?;
+        Some(
+            BlockExecutionResultWithEpoch::db_decode(&encoded)
+                .expect("decode succeeds"),
         )
     }
 
@@ -532,6 +869,27 @@ impl DBManager {
             .expect("db read failure")
     }
 
+    /// Load a value from the database, or from the freezer if it has been
+    /// moved there.
+    fn load_from_db_or_freezer(
+        &self, table: DBTable, db_key: &[u8],
+    ) -> Option<Box<[u8]>> {
+        if let Some(value) = self.load_from_db(table, db_key) {
+            return Some(value);
+        }
+        let freezer = self.freezer.as_ref()?;
+        let location: FreezerLocation = self.load_decodable_val(
+            DBTable::FreezerIndex,
+            &freezer_index_key(table, db_key),
+        )?;
+        Some(
+            freezer
+                .read(&location)
+                .expect("freezer read failure")
+                .into_boxed_slice(),
+        )
+    }
+
     fn insert_encodable_val<V>(
         &self, table: DBTable, db_key: &[u8], value: &V,
     ) where V: DatabaseEncodable {
@@ -620,6 +978,65 @@ fn epoch_consensus_epoch_execution_commitment_key(hash: &H256) -> Vec<u8> {
     append_suffix(hash, EPOCH_CONSENSUS_EXECUTION_INFO_SUFFIX_BYTE)
 }
 
+fn cross_space_transfers_key(epoch_hash: &H256) -> Vec<u8> {
+    append_suffix(epoch_hash, CROSS_SPACE_TRANSFERS_SUFFIX_BYTE)
+}
+
+fn cross_space_tx_key(tx_hash: &H256) -> Vec<u8> {
+    append_suffix(tx_hash, CROSS_SPACE_TX_SUFFIX_BYTE)
+}
+
+fn log_index_epoch_key(epoch_number: u64) -> [u8; 9] {
+    let mut epoch_key = [0; 9];
+    epoch_key[0] = LOG_INDEX_EPOCH_PREFIX_BYTE;
+    BigEndian::write_u64(&mut epoch_key[1..9], epoch_number);
+    epoch_key
+}
+
+fn log_index_postings_key(index_key: &[u8], bucket: u64) -> Vec<u8> {
+    let mut key = Vec::with_capacity(index_key.len() + 9);
+    key.push(LOG_INDEX_POSTINGS_PREFIX_BYTE);
+    key.extend_from_slice(index_key);
+    let mut bucket_key = [0; 8];
+    BigEndian::write_u64(&mut bucket_key, bucket);
+    key.extend_from_slice(&bucket_key);
+    key
+}
+
+fn freezer_index_key(table: DBTable, db_key: &[u8]) -> Vec<u8> {
+    let mut key = Vec::with_capacity(db_key.len() + 1);
+    key.push(match table {
+        DBTable::BlockTraces => 1,
+        _ => 0,
+    });
+    key.extend_from_slice(db_key);
+    key
+}
+
+fn address_tx_epoch_key(epoch_number: u64) -> [u8; 9] {
+    let mut epoch_key = [0; 9];
+    epoch_key[0] = ADDRESS_TX_EPOCH_PREFIX_BYTE;
+    BigEndian::write_u64(&mut epoch_key[1..9], epoch_number);
+    epoch_key
+}
+
+fn address_tx_entries_key(address_key: &[u8], bucket: u64) -> Vec<u8> {
+    let mut key = Vec::with_capacity(address_key.len() + 9);
+    key.push(ADDRESS_TX_ENTRIES_PREFIX_BYTE);
+    key.extend_from_slice(address_key);
+    let mut bucket_key = [0; 8];
+    BigEndian::write_u64(&mut bucket_key, bucket);
+    key.extend_from_slice(&bucket_key);
+    key
+}
+
+fn address_tx_buckets_key(address_key: &[u8]) -> Vec<u8> {
+    let mut key = Vec::with_capacity(address_key.len() + 1);
+    key.push(ADDRESS_TX_BUCKETS_PREFIX_BYTE);
+    key.extend_from_slice(address_key);
+    key
+}
+
 impl MallocSizeOf for DBManager {
     fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
         // Here we only handle the case that all columns are stored within the
@@ -0,0 +1,193 @@
+//! The freezer keeps the block data of old epochs in append-only segment
+//! files. Each value is compressed and appended to the last segment, and the
+//! returned `FreezerLocation` is kept in the offset index of the database.
+
+use super::FreezerLocation;
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use parking_lot::Mutex;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+/// A new segment is started once the last one reaches this size.
+pub const FREEZER_SEGMENT_SIZE_LIMIT: u64 = 1 << 30;
+
+struct SegmentWriter {
+    segment: u64,
+    file: File,
+    length: u64,
+}
+
+pub struct Freezer {
+    dir: PathBuf,
+    writer: Mutex<SegmentWriter>,
+}
+
+impl Freezer {
+    /// Open the freezer in `dir`, and continue appending to its last segment.
+    /// The bytes appended without an index entry before a crash are left in
+    /// the segment and never read.
+    pub fn open(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let mut segment = 0;
+        for entry in fs::read_dir(dir)? {
+            let name = entry?.file_name();
+            if let Some(n) = name
+                .to_str()
+                .and_then(|name| name.strip_suffix(".seg"))
+                .and_then(|n| n.parse::<u64>().ok())
+            {
+                segment = segment.max(n);
+            }
+        }
+        let file = Self::open_segment_for_append(dir, segment)?;
+        let length = file.metadata()?.len();
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            writer: Mutex::new(SegmentWriter {
+                segment,
+                file,
+                length,
+            }),
+        })
+    }
+
+    fn segment_path(dir: &Path, segment: u64) -> PathBuf {
+        dir.join(format!("{:08}.seg", segment))
+    }
+
+    fn open_segment_for_append(dir: &Path, segment: u64) -> io::Result<File> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(Self::segment_path(dir, segment))
+    }
+
+    /// Compress and append a value. The value is durable only after `sync`.
+    pub fn append(&self, value: &[u8]) -> io::Result<FreezerLocation> {
+        let mut encoder = DeflateEncoder::new(vec![], Compression::default());
+        encoder.write_all(value)?;
+        let compressed = encoder.finish()?;
+
+        let mut writer = self.writer.lock();
+        if writer.length > 0
+            && writer.length + compressed.len() as u64
+                > FREEZER_SEGMENT_SIZE_LIMIT
+        {
+            writer.file.sync_all()?;
+            let segment = writer.segment + 1;
+            *writer = SegmentWriter {
+                segment,
+                file: Self::open_segment_for_append(&self.dir, segment)?,
+                length: 0,
+            };
+        }
+        writer.file.write_all(&compressed)?;
+        let location = FreezerLocation {
+            segment: writer.segment,
+            offset: writer.length,
+            length: compressed.len() as u64,
+        };
+        writer.length += location.length;
+        Ok(location)
+    }
+
+    pub fn sync(&self) -> io::Result<()> { self.writer.lock().file.sync_all() }
+
+    pub fn read(&self, location: &FreezerLocation) -> io::Result<Vec<u8>> {
+        let mut file =
+            File::open(Self::segment_path(&self.dir, location.segment))?;
+        file.seek(SeekFrom::Start(location.offset))?;
+        let mut compressed = vec![0; location.length as usize];
+        file.read_exact(&mut compressed)?;
+        let mut value = vec![];
+        DeflateDecoder::new(&compressed[..]).read_to_end(&mut value)?;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Freezer;
+    use crate::{
+        block_data_manager::{DbType, FreezerEpochRange},
+        pow::PowComputer,
+        sync::utils::initialize_data_manager,
+    };
+    use mazze_executor::machine::VmFactory;
+    use std::sync::Arc;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_append_and_read() {
+        let dir = TempDir::new("freezer").unwrap();
+        let values: Vec<Vec<u8>> =
+            vec![vec![1; 100], vec![], b"block body".to_vec()];
+
+        let freezer = Freezer::open(dir.path()).unwrap();
+        let locations: Vec<_> =
+            values.iter().map(|v| freezer.append(v).unwrap()).collect();
+        freezer.sync().unwrap();
+        for (value, location) in values.iter().zip(&locations) {
+            assert_eq!(&freezer.read(location).unwrap(), value);
+        }
+        drop(freezer);
+
+        // The reopened freezer appends after the existing values.
+        let freezer = Freezer::open(dir.path()).unwrap();
+        let location = freezer.append(b"more").unwrap();
+        assert_eq!(location.segment, 0);
+        let last = locations.last().unwrap();
+        assert_eq!(location.offset, last.offset + last.length);
+        assert_eq!(freezer.read(&location).unwrap(), b"more");
+        assert_eq!(freezer.read(&locations[0]).unwrap(), values[0]);
+    }
+
+    #[test]
+    fn test_freezer_epoch_range() {
+        let db_dir = TempDir::new("freezer_epoch_range").unwrap();
+        let (mut data_man, _) = initialize_data_manager(
+            db_dir.path().to_str().unwrap(),
+            DbType::Rocksdb,
+            Arc::new(PowComputer::new()),
+            VmFactory::new(1024 * 32),
+        );
+        Arc::get_mut(&mut data_man)
+            .unwrap()
+            .config
+            .additional_maintained_block_body_epoch_count = Some(0);
+        for epoch in 1..=12 {
+            data_man.insert_executed_epoch_set_hashes_to_db(epoch, &vec![]);
+            data_man.insert_skipped_epoch_set_hashes_to_db(epoch, &vec![]);
+        }
+
+        for epoch in 1..=3 {
+            data_man.freeze_epoch(epoch);
+        }
+        data_man.new_checkpoint(4, 4);
+        assert_eq!(data_man.earliest_epoch_with_block_body(), 1);
+
+        // Epochs 4 to 9 are garbage collected without being frozen.
+        data_man.new_checkpoint(10, 10);
+        assert_eq!(data_man.earliest_epoch_with_block_body(), 10);
+
+        // The frozen epochs restart, and are kept once they are contiguous
+        // with the database.
+        data_man.freeze_epoch(10);
+        data_man.new_checkpoint(12, 12);
+        assert_eq!(data_man.earliest_epoch_with_block_body(), 12);
+        data_man.freeze_epoch(11);
+        assert_eq!(data_man.earliest_epoch_with_block_body(), 10);
+        // An epoch frozen again after a restart doesn't change the range.
+        data_man.freeze_epoch(10);
+        assert_eq!(
+            data_man.db_manager.freezer_epoch_range_from_db(),
+            Some(FreezerEpochRange {
+                lower: 10,
+                upper: 11
+            })
+        );
+    }
+}
@@ -0,0 +1,501 @@
+//! An optional index of the logs on the main chain. For each log address and
+//! topic, it keeps the numbers of the epochs with such a log in buckets of
+//! `LOG_INDEX_BUCKET_SIZE` epochs, so that a log filter over a long epoch
+//! range only reads the receipts of the epochs that may match.
+
+use super::{BlockDataManager, LogIndexEpoch, LogIndexRange};
+use mazze_types::{Space, H256};
+use primitives::{filter::LogFilterParams, BlockReceipts};
+use std::{
+    collections::{BTreeSet, HashSet},
+    ops::RangeInclusive,
+    sync::{Arc, Weak},
+    thread,
+    time::Duration,
+};
+
+/// The number of epochs in a bucket of postings.
+pub const LOG_INDEX_BUCKET_SIZE: u64 = 1024;
+
+/// The index key field of the log address. The topic at position `i` uses
+/// the field `i + 1`.
+const ADDRESS_FIELD: u8 = 0;
+
+fn index_key(space: Space, field: u8, value: &[u8]) -> Vec<u8> {
+    let mut key = Vec::with_capacity(value.len() + 2);
+    key.push(match space {
+        Space::Native => 0,
+        Space::Ethereum => 1,
+    });
+    key.push(field);
+    key.extend_from_slice(value);
+    key
+}
+
+/// The epochs that may have logs matching a filter.
+pub struct LogIndexCandidates {
+    /// The epochs in the query range covered by the index.
+    indexed: RangeInclusive<u64>,
+    /// The indexed epochs with a possible match.
+    epochs: BTreeSet<u64>,
+}
+
+impl LogIndexCandidates {
+    /// Returns `false` if the epoch is indexed and has no matching log.
+    pub fn contains(&self, epoch: u64) -> bool {
+        !self.indexed.contains(&epoch) || self.epochs.contains(&epoch)
+    }
+
+    /// The number of epochs in `[from_epoch, to_epoch]` which must be
+    /// scanned, i.e. the epochs not covered by the index and the indexed
+    /// epochs with a possible match.
+    pub fn scanned_count(&self, from_epoch: u64, to_epoch: u64) -> u64 {
+        let indexed = self.indexed.end() - self.indexed.start() + 1;
+        (to_epoch - from_epoch + 1).saturating_sub(indexed)
+            + self.epochs.len() as u64
+    }
+}
+
+impl BlockDataManager {
+    pub fn log_index_enabled(&self) -> bool { self.config.persist_log_index }
+
+    /// Index the logs of an epoch executed on the main chain if
+    /// `persist_log_index` is enabled. If the epoch number was indexed with
+    /// another main block, which happens after a main chain reorg, the
+    /// entries of the previous main block are removed.
+    pub fn insert_log_index(
+        &self, epoch_number: u64, epoch_hash: &H256,
+        epoch_receipts: &[Arc<BlockReceipts>],
+    ) {
+        if !self.config.persist_log_index {
+            return;
+        }
+        let mut range = self.log_index_range.lock();
+        self.index_epoch_logs(epoch_number, epoch_hash, epoch_receipts);
+
+        let new_range = match *range {
+            // The epochs executed before the index is enabled are indexed by
+            // `backfill_log_index`.
+            None => LogIndexRange {
+                lower: epoch_number,
+                upper: epoch_number,
+            },
+            Some(LogIndexRange { lower, upper })
+                if epoch_number + 1 >= lower && epoch_number <= upper + 1 =>
+            {
+                LogIndexRange {
+                    lower: lower.min(epoch_number),
+                    upper: upper.max(epoch_number),
+                }
+            }
+            // Some epochs are executed while the index is disabled, so the
+            // indexed range restarts and the backfill fills the gap.
+            Some(LogIndexRange { upper, .. }) if epoch_number > upper => {
+                LogIndexRange {
+                    lower: epoch_number,
+                    upper: epoch_number,
+                }
+            }
+            Some(old_range) => old_range,
+        };
+        self.db_manager.insert_log_index_range_to_db(&new_range);
+        *range = Some(new_range);
+    }
+
+    fn index_epoch_logs(
+        &self, epoch_number: u64, epoch_hash: &H256,
+        epoch_receipts: &[Arc<BlockReceipts>],
+    ) {
+        let old_epoch = self.db_manager.log_index_epoch_from_db(epoch_number);
+        if let Some(ref old_epoch) = old_epoch {
+            if old_epoch.epoch_hash == *epoch_hash {
+                return;
+            }
+        }
+
+        let mut index_keys = BTreeSet::new();
+        for receipt in epoch_receipts.iter().flat_map(|r| &r.receipts) {
+            for log in &receipt.logs {
+                index_keys.insert(index_key(
+                    log.space,
+                    ADDRESS_FIELD,
+                    log.address.as_bytes(),
+                ));
+                for (i, topic) in log.topics.iter().enumerate() {
+                    index_keys.insert(index_key(
+                        log.space,
+                        i as u8 + 1,
+                        topic.as_bytes(),
+                    ));
+                }
+            }
+        }
+
+        // The new entries are added before the stale ones are removed, so a
+        // concurrent query never misses this epoch.
+        let bucket = epoch_number / LOG_INDEX_BUCKET_SIZE;
+        for key in &index_keys {
+            let mut epochs = self
+                .db_manager
+                .log_index_postings_from_db(key, bucket)
+                .unwrap_or_default();
+            if let Err(pos) = epochs.binary_search(&epoch_number) {
+                epochs.insert(pos, epoch_number);
+                self.db_manager
+                    .insert_log_index_postings_to_db(key, bucket, &epochs);
+            }
+        }
+        for key in old_epoch.into_iter().flat_map(|e| e.index_keys) {
+            if index_keys.contains(&key) {
+                continue;
+            }
+            let mut epochs = match self
+                .db_manager
+                .log_index_postings_from_db(&key, bucket)
+            {
+                Some(epochs) => epochs,
+                None => continue,
+            };
+            if let Ok(pos) = epochs.binary_search(&epoch_number) {
+                epochs.remove(pos);
+                if epochs.is_empty() {
+                    self.db_manager
+                        .remove_log_index_postings_from_db(&key, bucket);
+                } else {
+                    self.db_manager
+                        .insert_log_index_postings_to_db(&key, bucket, &epochs);
+                }
+            }
+        }
+
+        self.db_manager.insert_log_index_epoch_to_db(
+            epoch_number,
+            &LogIndexEpoch {
+                epoch_hash: *epoch_hash,
+                index_keys: index_keys.into_iter().collect(),
+            },
+        );
+    }
+
+    /// Returns the epochs in `[from_epoch, to_epoch]` that may have logs
+    /// matching `filter`, or `None` if the index is disabled or cannot narrow
+    /// down the epochs, e.g. the filter has no address or topic.
+    pub fn log_index_candidates(
+        &self, filter: &LogFilterParams, from_epoch: u64, to_epoch: u64,
+    ) -> Option<LogIndexCandidates> {
+        if !self.config.persist_log_index {
+            return None;
+        }
+        let range = (*self.log_index_range.lock())?;
+        let indexed = from_epoch.max(range.lower)..=to_epoch.min(range.upper);
+        if indexed.is_empty() {
+            return None;
+        }
+
+        // A log matches if it matches the address and every topic position,
+        // and each of them matches if the log has any of the given values.
+        let mut conditions = vec![];
+        if let Some(ref addresses) = filter.address {
+            if !addresses.is_empty() {
+                conditions.push(
+                    addresses
+                        .iter()
+                        .map(|address| {
+                            index_key(
+                                filter.space,
+                                ADDRESS_FIELD,
+                                address.as_bytes(),
+                            )
+                        })
+                        .collect::<Vec<_>>(),
+                );
+            }
+        }
+        for (i, topics) in filter.topics.iter().enumerate() {
+            if let Some(ref topics) = topics {
+                if !topics.is_empty() {
+                    conditions.push(
+                        topics
+                            .iter()
+                            .map(|topic| {
+                                index_key(
+                                    filter.space,
+                                    i as u8 + 1,
+                                    topic.as_bytes(),
+                                )
+                            })
+                            .collect(),
+                    );
+                }
+            }
+        }
+        if conditions.is_empty() {
+            return None;
+        }
+
+        let buckets = indexed.start() / LOG_INDEX_BUCKET_SIZE
+            ..=indexed.end() / LOG_INDEX_BUCKET_SIZE;
+        let mut epochs: Option<BTreeSet<u64>> = None;
+        for keys in conditions {
+            let mut matched = BTreeSet::new();
+            for key in keys.iter().collect::<HashSet<_>>() {
+                for bucket in buckets.clone() {
+                    if let Some(postings) =
+                        self.db_manager.log_index_postings_from_db(key, bucket)
+                    {
+                        matched.extend(
+                            postings
+                                .into_iter()
+                                .filter(|e| indexed.contains(e)),
+                        );
+                    }
+                }
+            }
+            epochs = Some(match epochs {
+                None => matched,
+                Some(epochs) => {
+                    epochs.intersection(&matched).cloned().collect()
+                }
+            });
+        }
+
+        Some(LogIndexCandidates {
+            indexed,
+            epochs: epochs.unwrap_or_default(),
+        })
+    }
+
+    /// Index the epochs executed before the log index is enabled, from the
+    /// lower end of the indexed range down to the earliest epoch with
+    /// execution results. It runs until the backfill finishes or the data
+    /// manager is dropped.
+    pub fn backfill_log_index(data_man: Weak<BlockDataManager>) {
+        loop {
+            let progress = match data_man.upgrade() {
+                Some(data_man) => data_man.backfill_log_index_epoch(),
+                None => return,
+            };
+            match progress {
+                Some(true) => {}
+                // Wait for the first epoch indexed on execution.
+                Some(false) => thread::sleep(Duration::from_secs(1)),
+                None => {
+                    info!("Log index backfill finished");
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Index the epoch below the indexed range. Returns `None` if there is
+    /// nothing left to backfill.
+    fn backfill_log_index_epoch(&self) -> Option<bool> {
+        let lower = match *self.log_index_range.lock() {
+            Some(range) => range.lower,
+            None => return Some(false),
+        };
+        // The genesis has no logs.
+        if lower <= self.earliest_epoch_with_execution_result().max(1) {
+            return None;
+        }
+        let epoch_number = lower - 1;
+        let epoch_hashes =
+            self.executed_epoch_set_hashes_from_db(epoch_number)?;
+        let epoch_hash = *epoch_hashes.last()?;
+        let mut epoch_receipts = Vec::with_capacity(epoch_hashes.len());
+        for block_hash in &epoch_hashes {
+            epoch_receipts.push(
+                self.block_execution_result_by_hash_with_epoch(
+                    block_hash,
+                    &epoch_hash,
+                    false, /* update_main_assumption */
+                    false, /* update_cache */
+                )?
+                .block_receipts,
+            );
+        }
+
+        let mut range = self.log_index_range.lock();
+        // The indexed range may restart while the epoch is loaded.
+        if let Some(ref mut range) = *range {
+            if range.lower == lower {
+                self.index_epoch_logs(
+                    epoch_number,
+                    &epoch_hash,
+                    &epoch_receipts,
+                );
+                range.lower = epoch_number;
+                self.db_manager.insert_log_index_range_to_db(range);
+                if epoch_number % LOG_INDEX_BUCKET_SIZE == 0 {
+                    debug!("Log index backfilled to epoch {}", epoch_number);
+                }
+            }
+        }
+        Some(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        block_data_manager::{BlockDataManager, DbType, LogIndexRange},
+        pow::PowComputer,
+        sync::utils::initialize_data_manager,
+    };
+    use mazze_executor::machine::VmFactory;
+    use mazze_types::{Address, Bloom, Space, H256, U256};
+    use primitives::{
+        filter::LogFilterParams, BlockReceipts, LogEntry, Receipt,
+        TransactionStatus,
+    };
+    use std::sync::Arc;
+    use tempdir::TempDir;
+
+    fn new_data_manager(db_dir: &TempDir) -> Arc<BlockDataManager> {
+        let (mut data_man, _) = initialize_data_manager(
+            db_dir.path().to_str().unwrap(),
+            DbType::Rocksdb,
+            Arc::new(PowComputer::new()),
+            VmFactory::new(1024 * 32),
+        );
+        Arc::get_mut(&mut data_man)
+            .unwrap()
+            .config
+            .persist_log_index = true;
+        data_man
+    }
+
+    /// The receipts of an epoch with a log from each of `addresses`.
+    fn receipts(addresses: &[u64]) -> Vec<Arc<BlockReceipts>> {
+        let logs = addresses
+            .iter()
+            .map(|address| LogEntry {
+                address: Address::from_low_u64_be(*address),
+                topics: vec![],
+                data: vec![],
+                space: Space::Native,
+            })
+            .collect();
+        let receipt = Receipt::new(
+            TransactionStatus::Success,
+            U256::zero(),
+            U256::zero(),
+            false,
+            logs,
+            Bloom::zero(),
+            false,
+            vec![],
+            vec![],
+            None,
+        );
+        vec![Arc::new(BlockReceipts {
+            receipts: vec![receipt],
+            block_number: 0,
+            secondary_reward: U256::zero(),
+            tx_execution_error_messages: vec![String::new()],
+        })]
+    }
+
+    fn filter(address: u64) -> LogFilterParams {
+        LogFilterParams {
+            address: Some(vec![Address::from_low_u64_be(address)]),
+            ..Default::default()
+        }
+    }
+
+    /// The indexed epochs in `[from_epoch, to_epoch]` with a possible log
+    /// from `address`.
+    fn candidates(
+        data_man: &BlockDataManager, address: u64, from_epoch: u64,
+        to_epoch: u64,
+    ) -> Vec<u64> {
+        let candidates = data_man
+            .log_index_candidates(&filter(address), from_epoch, to_epoch)
+            .unwrap();
+        (from_epoch..=to_epoch)
+            .filter(|epoch| candidates.contains(*epoch))
+            .collect()
+    }
+
+    #[test]
+    fn test_log_index_reorg_and_restart() {
+        let db_dir = TempDir::new("log_index").unwrap();
+        let data_man = new_data_manager(&db_dir);
+        let epoch_hash = H256::from_low_u64_be;
+
+        data_man.insert_log_index(1, &epoch_hash(1), &receipts(&[1]));
+        data_man.insert_log_index(2, &epoch_hash(2), &receipts(&[2]));
+        data_man.insert_log_index(3, &epoch_hash(3), &receipts(&[1, 2]));
+        assert_eq!(candidates(&data_man, 1, 1, 3), vec![1, 3]);
+        assert_eq!(candidates(&data_man, 2, 1, 3), vec![2, 3]);
+        assert_eq!(candidates(&data_man, 3, 1, 3), Vec::<u64>::new());
+        // Only the candidates are scanned.
+        let scanned = data_man
+            .log_index_candidates(&filter(1), 1, 3)
+            .unwrap()
+            .scanned_count(1, 3);
+        assert_eq!(scanned, 2);
+
+        // After a reorg, epoch 3 has another main block whose entries replace
+        // the previous ones.
+        data_man.insert_log_index(3, &epoch_hash(4), &receipts(&[3]));
+        assert_eq!(candidates(&data_man, 1, 1, 3), vec![1]);
+        assert_eq!(candidates(&data_man, 2, 1, 3), vec![2]);
+        assert_eq!(candidates(&data_man, 3, 1, 3), vec![3]);
+        assert_eq!(
+            data_man.db_manager.log_index_range_from_db(),
+            Some(LogIndexRange { lower: 1, upper: 3 })
+        );
+
+        // Epochs 4 and 5 are executed while the index is disabled, so the
+        // indexed range restarts and they are scanned.
+        data_man.insert_log_index(6, &epoch_hash(6), &receipts(&[1]));
+        assert_eq!(
+            data_man.db_manager.log_index_range_from_db(),
+            Some(LogIndexRange { lower: 6, upper: 6 })
+        );
+        assert_eq!(candidates(&data_man, 1, 1, 6), vec![1, 2, 3, 4, 5, 6]);
+        let scanned = data_man
+            .log_index_candidates(&filter(1), 1, 6)
+            .unwrap()
+            .scanned_count(1, 6);
+        assert_eq!(scanned, 6);
+        assert!(data_man.log_index_candidates(&filter(1), 1, 5).is_none());
+    }
+
+    #[test]
+    fn test_log_index_backfill() {
+        let db_dir = TempDir::new("log_index_backfill").unwrap();
+        let data_man = new_data_manager(&db_dir);
+        let epoch_hash = H256::from_low_u64_be;
+
+        // Nothing to backfill before the first epoch is indexed.
+        assert_eq!(data_man.backfill_log_index_epoch(), Some(false));
+
+        // Epochs 1 and 2 are executed before the index is enabled.
+        for epoch in 1..=2 {
+            data_man.insert_executed_epoch_set_hashes_to_db(
+                epoch,
+                &vec![epoch_hash(epoch)],
+            );
+            data_man.insert_block_execution_result(
+                epoch_hash(epoch),
+                epoch_hash(epoch),
+                receipts(&[epoch]).pop().unwrap(),
+                true, /* persistent */
+            );
+        }
+        data_man.insert_log_index(3, &epoch_hash(3), &receipts(&[1]));
+        assert_eq!(candidates(&data_man, 2, 1, 3), vec![1, 2]);
+
+        assert_eq!(data_man.backfill_log_index_epoch(), Some(true));
+        assert_eq!(data_man.backfill_log_index_epoch(), Some(true));
+        assert_eq!(data_man.backfill_log_index_epoch(), None);
+        assert_eq!(
+            data_man.db_manager.log_index_range_from_db(),
+            Some(LogIndexRange { lower: 1, upper: 3 })
+        );
+        assert_eq!(candidates(&data_man, 1, 1, 3), vec![1, 3]);
+        assert_eq!(candidates(&data_man, 2, 1, 3), vec![2]);
+    }
+}
@@ -28,9 +28,15 @@ use std::{
     sync::Arc,
 };
 use threadpool::ThreadPool;
+pub mod address_tx_index;
 pub mod block_data_types;
+pub mod cross_space_index;
 pub mod db_gc_manager;
 pub mod db_manager;
+pub mod freezer;
+pub mod log_index;
+pub mod state_change_set;
+pub mod state_stats;
 pub mod tx_data_manager;
 use crate::{
     block_data_manager::{
@@ -47,9 +53,16 @@ use mazze_internal_common::{
     EpochExecutionCommitment, StateAvailabilityBoundary, StateRootWithAuxInfo,
 };
 use db_gc_manager::GCProgress;
+use freezer::Freezer;
 use metrics::{register_meter_with_group, Meter, MeterTimer};
 use primitives::pos::PosBlockId;
-use std::{hash::Hash, path::Path, time::Duration};
+use state_change_set::DEFAULT_MAX_HISTORICAL_STATE_EPOCH_COUNT;
+use state_stats::DEFAULT_STATE_STATS_TOP_N;
+use std::{
+    hash::Hash,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 lazy_static! {
     static ref TX_POOL_RECOVER_TIMER: Arc<dyn Meter> =
@@ -142,6 +155,17 @@ pub struct BlockDataManager {
     cache_man: Arc<Mutex<CacheManager<CacheId>>>,
     pub target_difficulty_manager: TargetDifficultyManager,
     gc_progress: Arc<Mutex<GCProgress>>,
+    /// The epochs covered by the log index if `persist_log_index` is
+    /// enabled.
+    log_index_range: Mutex<Option<LogIndexRange>>,
+    /// The epochs moved to the freezer if `freezer_dir` is set.
+    freezer_epoch_range: Mutex<Option<FreezerEpochRange>>,
+    /// Serializes the updates of the epochs indexed for the cross-space
+    /// transactions, which are read-modify-write.
+    cross_space_index_lock: Mutex<()>,
+    /// Serializes the updates of the address transaction index, which are
+    /// read-modify-write.
+    address_tx_index_lock: Mutex<()>,
 
     /// This maintains the boundary height of available state and commitments
     /// (executed but not deleted or in `ExecutionTaskQueue`).
@@ -192,8 +216,16 @@ impl BlockDataManager {
                 pow.clone(),
             ),
         };
+        let db_manager = match config.freezer_dir {
+            Some(ref freezer_dir) => db_manager.with_freezer(
+                Freezer::open(freezer_dir).expect("Open freezer failure"),
+            ),
+            None => db_manager,
+        };
         let previous_db_progress =
             db_manager.gc_progress_from_db().unwrap_or(0);
+        let freezer_epoch_range = db_manager.freezer_epoch_range_from_db();
+        let log_index_range = db_manager.log_index_range_from_db();
 
         let data_man = Self {
             block_headers: RwLock::new(HashMap::new()),
@@ -235,6 +267,10 @@ impl BlockDataManager {
             gc_progress: Arc::new(Mutex::new(GCProgress::new(
                 previous_db_progress,
             ))),
+            log_index_range: Mutex::new(log_index_range),
+            freezer_epoch_range: Mutex::new(freezer_epoch_range),
+            cross_space_index_lock: Mutex::new(()),
+            address_tx_index_lock: Mutex::new(()),
         };
 
         data_man.initialize_instance_id();
@@ -471,6 +507,33 @@ impl BlockDataManager {
         }
     }
 
+    /// Remove a block reverted by the dev mode, with its execution results,
+    /// from memory cache and db. The transaction indices are removed if they
+    /// point to this block.
+    pub fn remove_reverted_block(&self, hash: &H256) {
+        if let Some(block) = self.block_by_hash(hash, false) {
+            for tx in &block.transactions {
+                let tx_hash = tx.hash();
+                if self
+                    .transaction_index_by_hash(&tx_hash, false)
+                    .map_or(false, |index| index.block_hash == *hash)
+                {
+                    self.transaction_indices.write().remove(&tx_hash);
+                    self.db_manager.remove_transaction_index_from_db(&tx_hash);
+                }
+            }
+        }
+        self.remove_block_header(hash, true /* remove_db */);
+        self.remove_block_body(hash, true /* remove_db */);
+        self.remove_block_result(hash, true /* remove_db */);
+        self.remove_block_traces(hash, true /* remove_db */);
+        self.remove_epoch_execution_commitment(hash);
+        self.remove_epoch_execution_commitment_from_db(hash);
+        self.remove_epoch_execution_context(hash);
+        self.remove_epoch_execution_context_from_db(hash);
+        self.db_manager.remove_state_change_set_from_db(hash);
+    }
+
     /// Get the traces for a single block without checking the assumed main
     /// block
     pub fn block_traces_by_hash(
@@ -1258,6 +1321,7 @@ impl BlockDataManager {
             }
 
             let mut evm_tx_index = 0;
+            let mut epoch_blocks = Vec::new();
 
             // Recover tx address if we will skip main chain execution
             for (block_idx, block_hash) in epoch_block_hashes.iter().enumerate()
@@ -1328,7 +1392,24 @@ impl BlockDataManager {
                         _ => {}
                     }
                 }
+                epoch_blocks.push(block);
             }
+            self.insert_cross_space_transfers(
+                epoch_hash,
+                &epoch_blocks,
+                &epoch_receipts,
+                evm_chain_id,
+            );
+            let epoch_number = self
+                .block_height_by_hash(epoch_hash)
+                .expect("main block exists");
+            self.insert_log_index(epoch_number, epoch_hash, &epoch_receipts);
+            self.insert_address_tx_index(
+                epoch_number,
+                epoch_hash,
+                &epoch_blocks,
+                &epoch_receipts,
+            );
             if let Some(reward_execution_info) = reward_execution_info {
                 for block in &reward_execution_info.epoch_blocks {
                     let h = block.as_ref().hash();
@@ -1629,26 +1710,44 @@ impl BlockDataManager {
     }
 
     pub fn earliest_epoch_with_block_body(&self) -> u64 {
-        match self.config.additional_maintained_block_body_epoch_count {
-            Some(defer) => self.gc_progress.lock().gc_end - defer as u64,
-            None => 0,
-        }
+        self.earliest_epoch_with_freezer(
+            match self.config.additional_maintained_block_body_epoch_count {
+                Some(defer) => self.gc_progress.lock().gc_end - defer as u64,
+                None => 0,
+            },
+        )
     }
 
     pub fn earliest_epoch_with_execution_result(&self) -> u64 {
-        match self
-            .config
-            .additional_maintained_execution_result_epoch_count
-        {
-            Some(defer) => self.gc_progress.lock().gc_end - defer as u64,
-            None => 0,
-        }
+        self.earliest_epoch_with_freezer(
+            match self
+                .config
+                .additional_maintained_execution_result_epoch_count
+            {
+                Some(defer) => self.gc_progress.lock().gc_end - defer as u64,
+                None => 0,
+            },
+        )
     }
 
     pub fn earliest_epoch_with_trace(&self) -> u64 {
-        match self.config.additional_maintained_trace_epoch_count {
-            Some(defer) => self.gc_progress.lock().gc_end - defer as u64,
-            None => 0,
+        self.earliest_epoch_with_freezer(
+            match self.config.additional_maintained_trace_epoch_count {
+                Some(defer) => self.gc_progress.lock().gc_end - defer as u64,
+                None => 0,
+            },
+        )
+    }
+
+    /// The epochs moved to the freezer are kept even if they are garbage
+    /// collected from the database, unless some epochs between the freezer
+    /// and the database are missing.
+    fn earliest_epoch_with_freezer(&self, earliest_epoch_in_db: u64) -> u64 {
+        match *self.freezer_epoch_range.lock() {
+            Some(range) if range.upper + 1 >= earliest_epoch_in_db => {
+                range.lower.min(earliest_epoch_in_db)
+            }
+            _ => earliest_epoch_in_db,
         }
     }
 
@@ -1735,6 +1834,11 @@ impl BlockDataManager {
                 }
             }
         };
+        // The epochs before the checkpoint are moved to the freezer before
+        // any of their data is garbage collected.
+        if self.db_manager.freezer_enabled() {
+            self.freeze_epoch(base_epoch);
+        }
         self.gc_epoch_with_defer(
             base_epoch,
             self.config.additional_maintained_block_body_epoch_count,
@@ -1756,6 +1860,73 @@ impl BlockDataManager {
             self.config.additional_maintained_trace_epoch_count,
             |h| self.db_manager.remove_block_trace_from_db(h),
         );
+        if self.config.persist_cross_space_index {
+            self.gc_epoch_with_defer(
+                base_epoch,
+                self.config
+                    .additional_maintained_execution_result_epoch_count,
+                |h| self.remove_cross_space_transfers(h),
+            );
+        }
+        // The change sets are keyed by the main block of the epochs, which
+        // may be any block in the epoch set after the main chain changes.
+        self.gc_epoch_with_defer(
+            base_epoch,
+            self.config
+                .additional_maintained_state_change_set_epoch_count,
+            |h| self.db_manager.remove_state_change_set_from_db(h),
+        );
+    }
+
+    fn freeze_epoch(&self, epoch_number: u64) {
+        let epoch_set = match self.all_epoch_set_hashes_from_db(epoch_number) {
+            Some(epoch_set) => epoch_set,
+            None => {
+                warn!(
+                    "Freezer epoch set is missing! epoch_number: {}",
+                    epoch_number
+                );
+                return;
+            }
+        };
+        // The epoch is garbage collected as if the freezer is disabled, and
+        // the frozen epochs restart after it.
+        if let Err(e) = self.db_manager.freeze_blocks(&epoch_set) {
+            error!(
+                "Freezer failure! epoch_number: {}, err: {}",
+                epoch_number, e
+            );
+            return;
+        }
+
+        let mut range = self.freezer_epoch_range.lock();
+        let new_range = match *range {
+            None => FreezerEpochRange {
+                lower: epoch_number,
+                upper: epoch_number,
+            },
+            Some(FreezerEpochRange { lower, upper })
+                if epoch_number + 1 >= lower && epoch_number <= upper + 1 =>
+            {
+                FreezerEpochRange {
+                    lower: lower.min(epoch_number),
+                    upper: upper.max(epoch_number),
+                }
+            }
+            // Some epochs are garbage collected without being frozen, e.g.
+            // while the freezer is disabled, so the frozen epochs restart.
+            Some(FreezerEpochRange { upper, .. }) if epoch_number > upper => {
+                FreezerEpochRange {
+                    lower: epoch_number,
+                    upper: epoch_number,
+                }
+            }
+            Some(old_range) => old_range,
+        };
+        if *range != Some(new_range) {
+            self.db_manager.insert_freezer_epoch_range_to_db(&new_range);
+            *range = Some(new_range);
+        }
     }
 
     fn gc_epoch_with_defer<F>(
@@ -1789,6 +1960,15 @@ pub enum DbType {
 pub struct DataManagerConfiguration {
     pub persist_tx_index: bool,
     pub persist_block_number_index: bool,
+    pub persist_cross_space_index: bool,
+    pub persist_log_index: bool,
+    pub persist_address_tx_index: bool,
+    pub address_tx_index_internal_transfers: bool,
+    pub freezer_dir: Option<PathBuf>,
+    pub state_stats_period: Option<Duration>,
+    pub state_stats_top_n: usize,
+    pub persist_state_change_set: bool,
+    pub max_historical_state_epoch_count: u64,
     pub tx_cache_index_maintain_timeout: Duration,
     pub db_type: DbType,
     pub additional_maintained_block_body_epoch_count: Option<usize>,
@@ -1796,6 +1976,7 @@ pub struct DataManagerConfiguration {
     pub additional_maintained_reward_epoch_count: Option<usize>,
     pub additional_maintained_trace_epoch_count: Option<usize>,
     pub additional_maintained_transaction_index_epoch_count: Option<usize>,
+    pub additional_maintained_state_change_set_epoch_count: Option<usize>,
     pub checkpoint_gc_time_in_epoch_count: usize,
     pub strict_tx_index_gc: bool,
 }
@@ -1812,6 +1993,16 @@ impl DataManagerConfiguration {
         Self {
             persist_tx_index,
             persist_block_number_index,
+            persist_cross_space_index: false,
+            persist_log_index: false,
+            persist_address_tx_index: false,
+            address_tx_index_internal_transfers: false,
+            freezer_dir: None,
+            state_stats_period: None,
+            state_stats_top_n: DEFAULT_STATE_STATS_TOP_N,
+            persist_state_change_set: false,
+            max_historical_state_epoch_count:
+                DEFAULT_MAX_HISTORICAL_STATE_EPOCH_COUNT,
             tx_cache_index_maintain_timeout,
             db_type,
             additional_maintained_block_body_epoch_count: None,
@@ -1819,6 +2010,7 @@ impl DataManagerConfiguration {
             additional_maintained_reward_epoch_count: None,
             additional_maintained_trace_epoch_count: None,
             additional_maintained_transaction_index_epoch_count: None,
+            additional_maintained_state_change_set_epoch_count: None,
             checkpoint_gc_time_in_epoch_count: 1,
             strict_tx_index_gc: true,
         }
@@ -0,0 +1,231 @@
+//! The optional change sets of the state, i.e. the original values of the
+//! keys modified by each executed epoch. They allow reading the state of an
+//! epoch whose snapshot has been garbage collected, by applying the change
+//! sets backwards to the earliest available state. The change sets are kept
+//! for `additional_maintained_state_change_set_epoch_count` epochs before the
+//! era checkpoint.
+
+use super::{BlockDataManager, StateChange, StateChangeSet};
+use mazze_internal_common::StateAvailabilityBoundary;
+use mazze_storage::{state::StateTrait, HistoricalState};
+use mazze_types::H256;
+use std::{collections::BTreeMap, sync::Arc};
+
+pub const DEFAULT_MAX_HISTORICAL_STATE_EPOCH_COUNT: u64 = 10_000;
+
+impl BlockDataManager {
+    pub fn state_change_set_enabled(&self) -> bool {
+        self.config.persist_state_change_set
+    }
+
+    /// Persist the original values of the keys modified by an executed epoch
+    /// if `persist_state_change_set` is enabled. The change sets are keyed by
+    /// the epoch hash, so the execution of an epoch off the main chain does
+    /// not replace the change set on the main chain.
+    pub fn insert_state_change_set(
+        &self, epoch_hash: &H256, changes: &[(Vec<u8>, Option<Arc<[u8]>>)],
+    ) {
+        if !self.config.persist_state_change_set {
+            return;
+        }
+        let change_set = StateChangeSet {
+            changes: changes
+                .iter()
+                .map(|(key, original_value)| StateChange {
+                    key: key.clone(),
+                    original_value: original_value.as_ref().map(|v| v.to_vec()),
+                })
+                .collect(),
+        };
+        self.db_manager
+            .insert_state_change_set_to_db(epoch_hash, &change_set);
+    }
+
+    /// The maximum number of change sets applied to read a historical state.
+    pub fn max_historical_state_epoch_count(&self) -> u64 {
+        self.config.max_historical_state_epoch_count
+    }
+
+    /// Build the state of an epoch from `later_state`, the state of a later
+    /// epoch on the main chain. `later_epoch_hashes` are the main chain epochs
+    /// after the epoch up to the one of `later_state`, in the order of the
+    /// epoch numbers, whose change sets are applied backwards.
+    pub fn historical_state(
+        &self, later_state: Box<dyn StateTrait>, later_epoch_hashes: &[H256],
+    ) -> Result<Box<dyn StateTrait>, String> {
+        if later_epoch_hashes.len() as u64
+            > self.config.max_historical_state_epoch_count
+        {
+            return Err(format!(
+                "Reading the state needs {} state change sets, more than \
+                 max_historical_state_epoch_count {}",
+                later_epoch_hashes.len(),
+                self.config.max_historical_state_epoch_count
+            ));
+        }
+        let original_values =
+            original_values_before(later_epoch_hashes, |epoch_hash| {
+                self.db_manager.state_change_set_from_db(epoch_hash)
+            })?;
+        Ok(Box::new(HistoricalState::new(later_state, original_values)))
+    }
+}
+
+/// The earliest main chain epoch whose state can be read, from which the
+/// change sets are applied backwards. The state at `synced_state_height` is
+/// not readable, so the following epoch is used instead.
+pub fn historical_state_base(
+    state_availability_boundary: &StateAvailabilityBoundary,
+) -> Option<(u64, H256)> {
+    let lower_bound = state_availability_boundary.lower_bound;
+    (lower_bound..=state_availability_boundary.upper_bound)
+        .map(|h| {
+            (
+                h,
+                state_availability_boundary.main_chain
+                    [(h - lower_bound) as usize],
+            )
+        })
+        .find(|(h, hash)| {
+            state_availability_boundary.check_availability(*h, hash)
+        })
+}
+
+/// The values of the keys changed by `later_epoch_hashes` before the first of
+/// these epochs.
+fn original_values_before(
+    later_epoch_hashes: &[H256],
+    mut load_change_set: impl FnMut(&H256) -> Option<StateChangeSet>,
+) -> Result<BTreeMap<Vec<u8>, Option<Box<[u8]>>>, String> {
+    let mut original_values = BTreeMap::new();
+    for epoch_hash in later_epoch_hashes {
+        let change_set = load_change_set(epoch_hash).ok_or_else(|| {
+            format!(
+                "State change set of epoch {:?} is not available",
+                epoch_hash
+            )
+        })?;
+        // The first change of a key after the epoch holds its value at the
+        // epoch.
+        for StateChange {
+            key,
+            original_value,
+        } in change_set.changes
+        {
+            original_values
+                .entry(key)
+                .or_insert_with(|| original_value.map(Into::into));
+        }
+    }
+    Ok(original_values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn change(key: &[u8], original_value: Option<&[u8]>) -> StateChange {
+        StateChange {
+            key: key.to_vec(),
+            original_value: original_value.map(|v| v.to_vec()),
+        }
+    }
+
+    #[test]
+    fn test_original_values_before_several_epochs() {
+        let epochs = [H256::from_low_u64_be(1), H256::from_low_u64_be(2)];
+        let mut change_sets = HashMap::new();
+        // Epoch 1 sets `a` to 1 and creates `b`, then epoch 2 sets `a` to 2,
+        // deletes `b` and creates `c`.
+        change_sets.insert(
+            epochs[0],
+            StateChangeSet {
+                changes: vec![change(b"a", Some(b"0")), change(b"b", None)],
+            },
+        );
+        change_sets.insert(
+            epochs[1],
+            StateChangeSet {
+                changes: vec![
+                    change(b"a", Some(b"1")),
+                    change(b"b", Some(b"1")),
+                    change(b"c", None),
+                ],
+            },
+        );
+        let original_values =
+            original_values_before(&epochs, |h| change_sets.get(h).cloned())
+                .unwrap();
+        let expected: BTreeMap<Vec<u8>, Option<Box<[u8]>>> = vec![
+            (b"a".to_vec(), Some(b"0".to_vec().into())),
+            (b"b".to_vec(), None),
+            (b"c".to_vec(), None),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(original_values, expected);
+
+        // Only the change set of epoch 2 is applied for the state of epoch 1.
+        let original_values = original_values_before(&epochs[1..], |h| {
+            change_sets.get(h).cloned()
+        })
+        .unwrap();
+        assert_eq!(
+            original_values.get(b"a".as_ref()),
+            Some(&Some(b"1".to_vec().into()))
+        );
+        assert_eq!(
+            original_values.get(b"b".as_ref()),
+            Some(&Some(b"1".to_vec().into()))
+        );
+    }
+
+    #[test]
+    fn test_original_values_before_missing_change_set() {
+        let epochs = [H256::from_low_u64_be(1), H256::from_low_u64_be(2)];
+        let change_set = StateChangeSet {
+            changes: vec![change(b"a", None)],
+        };
+        let result = original_values_before(&epochs, |h| {
+            if *h == epochs[0] {
+                Some(change_set.clone())
+            } else {
+                None
+            }
+        });
+        assert!(result.unwrap_err().contains(&format!("{:?}", epochs[1])));
+        assert!(original_values_before(&[], |_| None).unwrap().is_empty());
+    }
+
+    fn boundary(
+        lower_bound: u64, upper_bound: u64, synced_state_height: u64,
+    ) -> StateAvailabilityBoundary {
+        let mut boundary = StateAvailabilityBoundary::new(
+            H256::from_low_u64_be(lower_bound),
+            lower_bound,
+            None,
+            None,
+        );
+        for h in lower_bound + 1..=upper_bound {
+            boundary.main_chain.push(H256::from_low_u64_be(h));
+        }
+        boundary.upper_bound = upper_bound;
+        boundary.synced_state_height = synced_state_height;
+        boundary
+    }
+
+    #[test]
+    fn test_historical_state_base() {
+        assert_eq!(
+            historical_state_base(&boundary(10, 20, 5)),
+            Some((10, H256::from_low_u64_be(10)))
+        );
+        // The synced state is not readable.
+        assert_eq!(
+            historical_state_base(&boundary(10, 20, 10)),
+            Some((11, H256::from_low_u64_be(11)))
+        );
+        assert_eq!(historical_state_base(&boundary(10, 10, 10)), None);
+    }
+}
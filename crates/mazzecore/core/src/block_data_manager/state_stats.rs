@@ -0,0 +1,549 @@
+//! Statistics of the state composition, collected by walking all the
+//! key-values of a snapshot. The statistics are stored by the snapshot height
+//! so that the growth of the state can be tracked over time. The latest
+//! snapshot on the main chain is walked periodically if
+//! `state_stats_period` is set.
+
+use super::{
+    BlockDataManager, ContractCollateral, ContractSlots, KeyTypeStats,
+    SpaceStateStats, StateStats,
+};
+use fallible_iterator::FallibleIterator;
+use keccak_hash::KECCAK_EMPTY;
+use mazze_storage::{
+    storage_db::{
+        key_value_db::KeyValueDbIterableTrait, snapshot_db::SnapshotDbTrait,
+    },
+    StorageManager,
+};
+use mazze_types::{Address, Space, H256, U256};
+use parking_lot::{Condvar, Mutex};
+use primitives::{Account, SkipInputCheck, StorageKey, StorageKeyWithSpace};
+use rlp::Rlp;
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    sync::{Arc, Weak},
+    time::Duration,
+};
+
+pub const DEFAULT_STATE_STATS_TOP_N: usize = 20;
+
+/// The names of the `StorageKey` variants in `StateStats::key_types`.
+pub const STATE_STATS_KEY_TYPES: [&str; 7] = [
+    "account",
+    "storageRoot",
+    "storage",
+    "codeRoot",
+    "code",
+    "depositList",
+    "voteList",
+];
+
+/// The number of key-values walked between the checks for shutdown.
+const STOP_CHECK_INTERVAL: u64 = 10_000;
+
+fn key_type_index(key: &StorageKey) -> usize {
+    match key {
+        StorageKey::AccountKey(_) => 0,
+        StorageKey::StorageRootKey(_) => 1,
+        StorageKey::StorageKey { .. } => 2,
+        StorageKey::CodeRootKey(_) => 3,
+        StorageKey::CodeKey { .. } => 4,
+        StorageKey::DepositListKey(_) => 5,
+        StorageKey::VoteListKey(_) => 6,
+    }
+}
+
+fn key_address_bytes<'a>(key: &StorageKey<'a>) -> &'a [u8] {
+    match *key {
+        StorageKey::AccountKey(address_bytes)
+        | StorageKey::StorageRootKey(address_bytes)
+        | StorageKey::StorageKey { address_bytes, .. }
+        | StorageKey::CodeRootKey(address_bytes)
+        | StorageKey::CodeKey { address_bytes, .. }
+        | StorageKey::DepositListKey(address_bytes)
+        | StorageKey::VoteListKey(address_bytes) => address_bytes,
+    }
+}
+
+fn space_index(space: Space) -> usize {
+    match space {
+        Space::Native => 0,
+        Space::Ethereum => 1,
+    }
+}
+
+/// The `n` largest items pushed.
+struct TopN<T: Ord> {
+    n: usize,
+    heap: BinaryHeap<Reverse<T>>,
+}
+
+impl<T: Ord> TopN<T> {
+    fn new(n: usize) -> Self {
+        Self {
+            n,
+            heap: BinaryHeap::with_capacity(n + 1),
+        }
+    }
+
+    fn push(&mut self, item: T) {
+        self.heap.push(Reverse(item));
+        if self.heap.len() > self.n {
+            self.heap.pop();
+        }
+    }
+
+    /// The items sorted from the largest.
+    fn into_sorted_vec(self) -> Vec<T> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(item)| item)
+            .collect()
+    }
+}
+
+/// Accumulates the key-values of a snapshot in the key order, in which the
+/// keys of an address in both spaces are adjacent.
+struct StateStatsCollector {
+    spaces: [SpaceStateStats; 2],
+    key_types: Vec<KeyTypeStats>,
+    top_by_slots: [TopN<(u64, Address)>; 2],
+    top_by_collateral: TopN<(U256, Address)>,
+    /// The address being walked, its storage slots in each space and its
+    /// collateral in the native space.
+    current: Option<(Address, [u64; 2], U256)>,
+}
+
+impl StateStatsCollector {
+    fn new(top_n: usize) -> Self {
+        Self {
+            spaces: Default::default(),
+            key_types: STATE_STATS_KEY_TYPES
+                .iter()
+                .map(|key_type| KeyTypeStats {
+                    key_type: key_type.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            top_by_slots: [TopN::new(top_n), TopN::new(top_n)],
+            top_by_collateral: TopN::new(top_n),
+            current: None,
+        }
+    }
+
+    fn add(&mut self, key: &[u8], value: &[u8]) -> Result<(), String> {
+        let storage_key =
+            StorageKeyWithSpace::from_key_bytes::<SkipInputCheck>(key);
+        let key_type = &mut self.key_types[key_type_index(&storage_key.key)];
+        key_type.keys += 1;
+        key_type.key_bytes += key.len() as u64;
+        key_type.value_bytes += value.len() as u64;
+
+        let address = Address::from_slice(key_address_bytes(&storage_key.key));
+        if self
+            .current
+            .map_or(true, |(current, ..)| current != address)
+        {
+            self.flush_address();
+            self.current = Some((address, [0, 0], U256::zero()));
+        }
+        let (_, slots, collateral) = self.current.as_mut().unwrap();
+        let index = space_index(storage_key.space);
+        match storage_key.key {
+            StorageKey::AccountKey(_) => {
+                let account = Account::new_from_rlp(address, &Rlp::new(value))
+                    .map_err(|e| {
+                        format!("Invalid account {:?}: {:?}", address, e)
+                    })?;
+                self.spaces[index].accounts += 1;
+                if account.code_hash != KECCAK_EMPTY
+                    && !account.code_hash.is_zero()
+                {
+                    self.spaces[index].contracts += 1;
+                }
+                if storage_key.space == Space::Native {
+                    *collateral = account.collateral_for_storage;
+                }
+            }
+            StorageKey::StorageKey { .. } => {
+                self.spaces[index].storage_slots += 1;
+                slots[index] += 1;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn flush_address(&mut self) {
+        if let Some((address, slots, collateral)) = self.current.take() {
+            for (index, slots) in slots.iter().enumerate() {
+                if *slots > 0 {
+                    self.top_by_slots[index].push((*slots, address));
+                }
+            }
+            if !collateral.is_zero() {
+                self.top_by_collateral.push((collateral, address));
+            }
+        }
+    }
+
+    fn finish(
+        mut self, snapshot_epoch_id: H256, snapshot_height: u64,
+    ) -> StateStats {
+        self.flush_address();
+        let [mut native, mut ethereum] = self.spaces;
+        let [native_top_by_slots, ethereum_top_by_slots] = self.top_by_slots;
+        let to_contract_slots = |top: TopN<(u64, Address)>| {
+            top.into_sorted_vec()
+                .into_iter()
+                .map(|(slots, address)| ContractSlots { address, slots })
+                .collect()
+        };
+        native.top_contracts_by_slots = to_contract_slots(native_top_by_slots);
+        ethereum.top_contracts_by_slots =
+            to_contract_slots(ethereum_top_by_slots);
+        native.top_contracts_by_collateral = self
+            .top_by_collateral
+            .into_sorted_vec()
+            .into_iter()
+            .map(|(collateral, address)| ContractCollateral {
+                address,
+                collateral,
+            })
+            .collect();
+        StateStats {
+            snapshot_epoch_id,
+            snapshot_height,
+            native,
+            ethereum,
+            key_types: self.key_types,
+        }
+    }
+}
+
+/// Collect the statistics of the snapshot of an epoch, keeping the `top_n`
+/// largest contracts.
+fn walk_snapshot(
+    storage_manager: &StorageManager, snapshot_epoch_id: &H256,
+    snapshot_height: u64, top_n: usize, should_stop: &dyn Fn() -> bool,
+) -> Result<Option<StateStats>, String> {
+    let snapshot_db = match storage_manager
+        .get_storage_manager()
+        .get_snapshot_manager()
+        .get_snapshot_by_epoch_id(
+            snapshot_epoch_id,
+            /* try_open = */ true,
+            false,
+        )
+        .map_err(|e| e.to_string())?
+    {
+        Some(db) => db,
+        None => return Ok(None),
+    };
+
+    let mut collector = StateStatsCollector::new(top_n);
+    let mut kv_iterator = snapshot_db
+        .snapshot_kv_iterator()
+        .map_err(|e| e.to_string())?
+        .take();
+    let mut kvs = kv_iterator
+        .iter_range(&[], None)
+        .map_err(|e| e.to_string())?
+        .take();
+    let mut walked = 0u64;
+    while let Some((key, value)) = kvs.next().map_err(|e| e.to_string())? {
+        collector.add(&key, &value)?;
+        walked += 1;
+        if walked % STOP_CHECK_INTERVAL == 0 && should_stop() {
+            return Ok(None);
+        }
+    }
+    Ok(Some(collector.finish(*snapshot_epoch_id, snapshot_height)))
+}
+
+impl BlockDataManager {
+    pub fn state_stats_period(&self) -> Option<Duration> {
+        self.config.state_stats_period
+    }
+
+    pub fn state_stats_by_height(
+        &self, snapshot_height: u64,
+    ) -> Option<StateStats> {
+        self.db_manager.state_stats_from_db(snapshot_height)
+    }
+
+    pub fn latest_state_stats(&self) -> Option<StateStats> {
+        self.state_stats_by_height(
+            self.db_manager.latest_state_stats_height_from_db()?,
+        )
+    }
+
+    /// Walk the snapshot of an epoch and store its statistics. Returns
+    /// `None` if the snapshot is not available or `should_stop` returns true
+    /// during the walk.
+    pub fn collect_state_stats(
+        &self, snapshot_epoch_id: &H256, snapshot_height: u64,
+        should_stop: &dyn Fn() -> bool,
+    ) -> Result<Option<StateStats>, String> {
+        let stats = match walk_snapshot(
+            &self.storage_manager,
+            snapshot_epoch_id,
+            snapshot_height,
+            self.config.state_stats_top_n,
+            should_stop,
+        )? {
+            Some(stats) => stats,
+            None => return Ok(None),
+        };
+
+        self.db_manager.insert_state_stats_to_db(&stats);
+        if self
+            .db_manager
+            .latest_state_stats_height_from_db()
+            .map_or(true, |latest| latest <= snapshot_height)
+        {
+            self.db_manager
+                .insert_latest_state_stats_height_to_db(snapshot_height);
+        }
+        Ok(Some(stats))
+    }
+
+    /// Collect the statistics of the latest snapshot on the main chain if it
+    /// is newer than the latest statistics.
+    fn collect_latest_state_stats(
+        &self, should_stop: &dyn Fn() -> bool,
+    ) -> Result<Option<StateStats>, String> {
+        let latest_height = self.db_manager.latest_state_stats_height_from_db();
+        let mut snapshots = self
+            .storage_manager
+            .get_storage_manager()
+            .get_current_snapshot_epochs();
+        snapshots.sort_by_key(|(_, height)| Reverse(*height));
+        for (snapshot_epoch_id, height) in snapshots {
+            // The genesis snapshot is empty.
+            if height == 0 || latest_height.map_or(false, |h| height <= h) {
+                break;
+            }
+            let on_main_chain = self
+                .executed_epoch_set_hashes_from_db(height)
+                .and_then(|hashes| hashes.last().cloned())
+                == Some(snapshot_epoch_id);
+            if !on_main_chain {
+                continue;
+            }
+            if let Some(stats) = self.collect_state_stats(
+                &snapshot_epoch_id,
+                height,
+                should_stop,
+            )? {
+                return Ok(Some(stats));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Collect the statistics of the latest snapshot every
+    /// `state_stats_period` until the exit is signaled or the data manager
+    /// is dropped.
+    pub fn collect_state_stats_periodically(
+        data_man: Weak<BlockDataManager>, period: Duration,
+        exit: Arc<(Mutex<bool>, Condvar)>,
+    ) {
+        let should_stop = || *exit.0.lock();
+        loop {
+            {
+                let mut exit_lock = exit.0.lock();
+                if !exit.1.wait_for(&mut exit_lock, period).timed_out() {
+                    return;
+                }
+            }
+            let result = match data_man.upgrade() {
+                Some(data_man) => {
+                    data_man.collect_latest_state_stats(&should_stop)
+                }
+                None => return,
+            };
+            match result {
+                Ok(Some(stats)) => info!(
+                    "Collected state stats of snapshot {:?} at height {}",
+                    stats.snapshot_epoch_id, stats.snapshot_height
+                ),
+                Ok(None) => {}
+                Err(e) => warn!("Failed to collect state stats: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mazze_storage::{
+        tests::new_state_manager_for_unit_test_with_snapshot_epoch_count,
+        StateIndex, StorageManagerTrait,
+    };
+    use mazze_types::AddressSpaceUtil;
+    use std::thread;
+
+    /// The sorted key-values of a native contract with three storage slots
+    /// and of an eSpace account.
+    fn test_key_values() -> (Address, Vec<(Vec<u8>, Vec<u8>)>) {
+        let contract = Address::from_low_u64_be(1);
+        let user = Address::from_low_u64_be(2);
+        let mut contract_account =
+            Account::new_empty(&contract.with_native_space());
+        contract_account.code_hash = H256::from_low_u64_be(3);
+        contract_account.collateral_for_storage = 5.into();
+        let user_account = Account::new_empty(&user.with_evm_space());
+
+        let mut kvs = vec![
+            (
+                StorageKey::new_account_key(&contract)
+                    .with_native_space()
+                    .to_key_bytes(),
+                rlp::encode(&contract_account),
+            ),
+            (
+                StorageKey::new_account_key(&user)
+                    .with_evm_space()
+                    .to_key_bytes(),
+                rlp::encode(&user_account),
+            ),
+        ];
+        for slot in 0..3u8 {
+            kvs.push((
+                StorageKey::new_storage_key(&contract, &[slot])
+                    .with_native_space()
+                    .to_key_bytes(),
+                vec![1],
+            ));
+        }
+        kvs.sort();
+        (contract, kvs)
+    }
+
+    fn check_stats(stats: &StateStats, contract: Address) {
+        assert_eq!(stats.native.accounts, 1);
+        assert_eq!(stats.native.contracts, 1);
+        assert_eq!(stats.native.storage_slots, 3);
+        assert_eq!(stats.ethereum.accounts, 1);
+        assert_eq!(stats.ethereum.contracts, 0);
+        assert_eq!(
+            stats.native.top_contracts_by_slots,
+            vec![ContractSlots {
+                address: contract,
+                slots: 3
+            }]
+        );
+        assert_eq!(
+            stats.native.top_contracts_by_collateral,
+            vec![ContractCollateral {
+                address: contract,
+                collateral: 5.into()
+            }]
+        );
+        assert_eq!(stats.key_types[0].keys, 2);
+        assert_eq!(stats.key_types[2].keys, 3);
+    }
+
+    #[test]
+    fn test_state_stats_collector() {
+        let (contract, kvs) = test_key_values();
+        let mut collector = StateStatsCollector::new(1);
+        for (key, value) in &kvs {
+            collector.add(key, value).unwrap();
+        }
+        check_stats(&collector.finish(H256::zero(), 1), contract);
+    }
+
+    #[test]
+    fn test_walk_snapshot() {
+        let state_manager =
+            new_state_manager_for_unit_test_with_snapshot_epoch_count(2);
+        let snapshot_epoch_count = state_manager
+            .get_storage_manager()
+            .get_snapshot_epoch_count();
+        let wait_for_snapshotting = || {
+            while !state_manager
+                .get_storage_manager()
+                .in_progress_snapshotting_tasks
+                .read()
+                .is_empty()
+            {
+                thread::sleep(Duration::from_millis(100));
+            }
+        };
+
+        // Commit enough epochs on top of the genesis state for the state
+        // manager to make snapshots of it.
+        let (contract, kvs) = test_key_values();
+        let mut state = state_manager.get_state_for_genesis_write();
+        for (key, value) in &kvs {
+            state
+                .set(
+                    StorageKeyWithSpace::from_key_bytes::<SkipInputCheck>(key),
+                    value[..].into(),
+                )
+                .unwrap();
+        }
+        let mut epoch_id = H256::zero();
+        let mut state_root = state.compute_state_root().unwrap();
+        state.commit(epoch_id).unwrap();
+        let last_height = 4 * snapshot_epoch_count as u64;
+        for height in 1..=last_height {
+            wait_for_snapshotting();
+            let mut state = state_manager
+                .get_state_for_next_epoch(
+                    StateIndex::new_for_next_epoch(
+                        &epoch_id,
+                        &state_root,
+                        height,
+                        snapshot_epoch_count,
+                    ),
+                    false,
+                )
+                .unwrap()
+                .unwrap();
+            epoch_id = H256::from_low_u64_be(height);
+            state_root = state.compute_state_root().unwrap();
+            state.commit(epoch_id).unwrap();
+        }
+        wait_for_snapshotting();
+
+        let snapshot_height = (1..=last_height)
+            .find(|height| {
+                state_manager
+                    .get_storage_manager()
+                    .get_snapshot_info_at_epoch(&H256::from_low_u64_be(*height))
+                    .is_some()
+            })
+            .expect("a snapshot is made");
+        let snapshot_epoch_id = H256::from_low_u64_be(snapshot_height);
+        let stats = walk_snapshot(
+            &state_manager,
+            &snapshot_epoch_id,
+            snapshot_height,
+            1,
+            &|| false,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(stats.snapshot_epoch_id, snapshot_epoch_id);
+        assert_eq!(stats.snapshot_height, snapshot_height);
+        check_stats(&stats, contract);
+
+        // An epoch without a snapshot.
+        assert!(walk_snapshot(
+            &state_manager,
+            &H256::from_low_u64_be(last_height + 1),
+            last_height + 1,
+            1,
+            &|| false,
+        )
+        .unwrap()
+        .is_none());
+    }
+}
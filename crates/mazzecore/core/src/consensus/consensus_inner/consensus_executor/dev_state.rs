@@ -0,0 +1,234 @@
+use std::collections::{HashMap, VecDeque};
+
+use mazze_bytes::Bytes;
+use mazze_executor::state::{CleanupMode, State};
+use mazze_statedb::Result as DbResult;
+use mazze_types::{Address, AddressWithSpace, Space, H256, U256};
+
+/// A direct change to the state of an account, made by the dev mode cheat
+/// RPCs.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DevStateChange {
+    Balance(AddressWithSpace, U256),
+    Nonce(AddressWithSpace, U256),
+    /// Empty code clears the code of the account.
+    Code(AddressWithSpace, Bytes),
+    /// The storage collateral of the slot is not settled.
+    Storage(AddressWithSpace, H256, U256),
+}
+
+impl DevStateChange {
+    fn apply(&self, state: &mut State) -> DbResult<()> {
+        match self {
+            DevStateChange::Balance(address, balance) => {
+                let old_balance = state.balance(address)?;
+                if *balance >= old_balance {
+                    let by = *balance - old_balance;
+                    state.add_balance(
+                        address,
+                        &by,
+                        CleanupMode::ForceCreate,
+                    )?;
+                    state.add_total_issued(by);
+                    if address.space == Space::Ethereum {
+                        state.add_total_evm_tokens(by);
+                    }
+                } else {
+                    let by = old_balance - *balance;
+                    state.sub_balance(
+                        address,
+                        &by,
+                        &mut CleanupMode::NoEmpty,
+                    )?;
+                    state.sub_total_issued(by);
+                    if address.space == Space::Ethereum {
+                        state.sub_total_evm_tokens(by);
+                    }
+                }
+            }
+            DevStateChange::Nonce(address, nonce) => {
+                state.set_nonce(address, nonce)?;
+            }
+            DevStateChange::Code(address, code) => {
+                state.set_code(address, code.clone(), Address::zero())?;
+            }
+            DevStateChange::Storage(address, key, value) => {
+                state.overwrite_storage(
+                    address,
+                    key.as_bytes().to_vec(),
+                    *value,
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The number of blocks whose dev state changes are kept for re-execution.
+const RETAINED_PARENT_COUNT: usize = 1024;
+
+/// The dev state changes, keyed by the parent of the main block of the epoch
+/// they are applied to. The changes are applied again if the epoch is
+/// executed again, e.g. after a reorg, as long as they are retained.
+#[derive(Default)]
+pub struct DevStateChanges {
+    changes: HashMap<H256, Vec<DevStateChange>>,
+    /// The keys of `changes` in insertion order.
+    order: VecDeque<H256>,
+}
+
+impl DevStateChanges {
+    pub fn queue(&mut self, parent_hash: H256, changes: Vec<DevStateChange>) {
+        if !self.changes.contains_key(&parent_hash) {
+            self.order.push_back(parent_hash);
+            if self.order.len() > RETAINED_PARENT_COUNT {
+                let oldest = self.order.pop_front().expect("not empty");
+                self.changes.remove(&oldest);
+            }
+        }
+        self.changes.entry(parent_hash).or_default().extend(changes);
+    }
+
+    /// Discard the changes queued for the children of `parent_hash`, e.g.
+    /// because the block is reverted.
+    pub fn discard(&mut self, parent_hash: &H256) {
+        if self.changes.remove(parent_hash).is_some() {
+            self.order.retain(|hash| hash != parent_hash);
+        }
+    }
+
+    /// Apply the changes queued for the children of `parent_hash` to the
+    /// state of an epoch.
+    pub fn apply(&self, parent_hash: &H256, state: &mut State) -> DbResult<()> {
+        if let Some(changes) = self.changes.get(parent_hash) {
+            for change in changes {
+                change.apply(state)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mazze_statedb::StateDb;
+    use mazze_storage::{
+        tests::new_state_manager_for_unit_test, StorageManagerTrait,
+    };
+    use mazze_types::AddressSpaceUtil;
+
+    fn new_state() -> State {
+        let storage_manager = new_state_manager_for_unit_test();
+        State::new(StateDb::new(storage_manager.get_state_for_genesis_write()))
+            .expect("Failed to initialize state")
+    }
+
+    fn apply(changes: Vec<DevStateChange>, state: &mut State) {
+        let mut dev_state_changes = DevStateChanges::default();
+        dev_state_changes.queue(H256::zero(), changes);
+        dev_state_changes.apply(&H256::zero(), state).unwrap();
+    }
+
+    #[test]
+    fn test_set_balance() {
+        let mut state = new_state();
+        let address = Address::random().with_evm_space();
+
+        apply(
+            vec![DevStateChange::Balance(address, 100.into())],
+            &mut state,
+        );
+        assert_eq!(state.balance(&address).unwrap(), 100.into());
+        assert_eq!(state.total_issued_tokens(), 100.into());
+        assert_eq!(state.total_espace_tokens(), 100.into());
+
+        apply(
+            vec![DevStateChange::Balance(address, 40.into())],
+            &mut state,
+        );
+        assert_eq!(state.balance(&address).unwrap(), 40.into());
+        assert_eq!(state.total_issued_tokens(), 40.into());
+        assert_eq!(state.total_espace_tokens(), 40.into());
+    }
+
+    #[test]
+    fn test_set_nonce_code_and_storage() {
+        let mut state = new_state();
+        let address = Address::random().with_evm_space();
+        let key = H256::from_low_u64_be(1);
+
+        apply(
+            vec![
+                DevStateChange::Nonce(address, 7.into()),
+                DevStateChange::Code(address, vec![0x60, 0x00]),
+                DevStateChange::Storage(address, key, 9.into()),
+            ],
+            &mut state,
+        );
+        assert_eq!(state.nonce(&address).unwrap(), 7.into());
+        assert_eq!(
+            state.code(&address).unwrap().as_deref(),
+            Some(&vec![0x60, 0x00])
+        );
+        assert_eq!(
+            state.storage_at(&address, key.as_bytes()).unwrap(),
+            9.into()
+        );
+
+        apply(vec![DevStateChange::Code(address, vec![])], &mut state);
+        assert!(state
+            .code(&address)
+            .unwrap()
+            .map_or(true, |code| code.is_empty()));
+    }
+
+    #[test]
+    fn test_changes_keyed_by_parent() {
+        let mut state = new_state();
+        let address = Address::random().with_evm_space();
+        let parent = H256::random();
+
+        let mut dev_state_changes = DevStateChanges::default();
+        dev_state_changes
+            .queue(parent, vec![DevStateChange::Nonce(address, 3.into())]);
+
+        dev_state_changes
+            .apply(&H256::random(), &mut state)
+            .unwrap();
+        assert_eq!(state.nonce(&address).unwrap(), 0.into());
+
+        // The changes are kept, so a re-executed epoch gets them again.
+        for _ in 0..2 {
+            dev_state_changes.apply(&parent, &mut state).unwrap();
+            assert_eq!(state.nonce(&address).unwrap(), 3.into());
+        }
+
+        dev_state_changes.discard(&parent);
+        assert!(dev_state_changes.changes.is_empty());
+        assert!(dev_state_changes.order.is_empty());
+        let mut state = new_state();
+        dev_state_changes.apply(&parent, &mut state).unwrap();
+        assert_eq!(state.nonce(&address).unwrap(), 0.into());
+    }
+
+    #[test]
+    fn test_retained_changes_are_bounded() {
+        let mut dev_state_changes = DevStateChanges::default();
+        let address = Address::random().with_evm_space();
+        let parents: Vec<H256> = (0..RETAINED_PARENT_COUNT + 1)
+            .map(|_| H256::random())
+            .collect();
+        for parent in &parents {
+            dev_state_changes
+                .queue(*parent, vec![DevStateChange::Nonce(address, 1.into())]);
+        }
+
+        assert_eq!(dev_state_changes.changes.len(), RETAINED_PARENT_COUNT);
+        assert_eq!(dev_state_changes.order.len(), RETAINED_PARENT_COUNT);
+        assert!(!dev_state_changes.changes.contains_key(&parents[0]));
+        assert!(dev_state_changes
+            .changes
+            .contains_key(parents.last().unwrap()));
+    }
+}
@@ -1,12 +1,12 @@
 use super::ConsensusExecutionHandler;
-use std::{convert::From, sync::Arc};
+use std::{collections::HashSet, convert::From, sync::Arc};
 
 use alloy_rpc_types_trace::geth::GethDebugTracingOptions;
 use geth_tracer::{GethTraceWithHash, GethTracer, TxExecContext};
 use pow_types::StakingEvent;
 
-use mazze_statedb::{ErrorKind as DbErrorKind, Result as DbResult};
-use mazze_types::{Space, SpaceMap, H256, U256};
+use mazze_statedb::{ErrorKind as DbErrorKind, Result as DbResult, StateDb};
+use mazze_types::{AddressWithSpace, Space, SpaceMap, H256, U256};
 use primitives::{
     receipt::BlockReceipts, Action, Block, BlockNumber, EpochId, Receipt,
     SignedTransaction, TransactionIndex,
@@ -19,11 +19,16 @@ use crate::{
 };
 use mazze_execute_helper::{
     exec_tracer::TransactionExecTraces,
-    observer::Observer,
+    observer::{
+        gas_profiler::{GasProfile, GasProfiler},
+        Observer,
+    },
     tx_outcome::{make_process_tx_outcome, ProcessTxOutcome},
 };
 use mazze_executor::{
-    executive::{ExecutiveContext, TransactOptions, TransactSettings},
+    executive::{
+        ExecutionOutcome, ExecutiveContext, TransactOptions, TransactSettings,
+    },
     internal_contract::{
         block_hash_slot, epoch_hash_slot, initialize_internal_contract_accounts,
     },
@@ -31,11 +36,17 @@ use mazze_executor::{
         initialize_cip107, initialize_cip137,
         initialize_or_update_dao_voted_params, State,
     },
+    parallel::execute_in_parallel,
 };
 use mazze_vm_types::Env;
 
 pub enum VirtualCall<'a> {
     GethTrace(GethTask<'a>),
+    GasProfile(GasProfileTask<'a>),
+    /// Execute a speculative block without persisting any result.
+    PendingBlock,
+    /// Execute an executed epoch again without persisting any result.
+    Reexecute(ReexecuteTask<'a>),
 }
 
 pub struct GethTask<'a> {
@@ -44,6 +55,16 @@ pub struct GethTask<'a> {
     pub(super) answer: &'a mut Vec<GethTraceWithHash>,
 }
 
+pub struct GasProfileTask<'a> {
+    pub(super) tx_hash: H256,
+    pub(super) answer: &'a mut Option<GasProfile>,
+}
+
+pub struct ReexecuteTask<'a> {
+    /// The accounts written by each transaction, in the execution order.
+    pub(super) tx_writes: &'a mut Vec<HashSet<AddressWithSpace>>,
+}
+
 impl ConsensusExecutionHandler {
     pub(super) fn process_epoch_transactions<'a>(
         &self, epoch_id: EpochId, state: &mut State,
@@ -93,8 +114,17 @@ impl ConsensusExecutionHandler {
             )?;
         }
 
-        if let Some(VirtualCall::GethTrace(task)) = context.virtual_call {
-            std::mem::swap(&mut epoch_recorder.geth_traces, task.answer);
+        match context.virtual_call {
+            Some(VirtualCall::GethTrace(task)) => {
+                std::mem::swap(&mut epoch_recorder.geth_traces, task.answer);
+            }
+            Some(VirtualCall::GasProfile(task)) => {
+                *task.answer = epoch_recorder.gas_profile.take();
+            }
+            Some(VirtualCall::Reexecute(task)) => {
+                std::mem::swap(&mut epoch_recorder.tx_writes, task.tx_writes);
+            }
+            _ => {}
         }
 
         if !dry_run && self.pos_verifier.pos_option().is_some() {
@@ -246,16 +276,30 @@ impl ConsensusExecutionHandler {
         let mut block_recorder =
             BlockProcessRecorder::new(epoch_recorder.evm_tx_idx);
 
-        for (idx, transaction) in block.transactions.iter().enumerate() {
-            self.process_transaction(
-                idx,
-                transaction,
+        let parallel = self.config.parallel_execution
+            && block_context.epoch_context.virtual_call.is_none()
+            && block.transactions.len() > 1;
+
+        if parallel {
+            self.process_transactions_in_parallel(
                 block_context,
                 state,
                 &mut env,
                 on_local_main,
                 &mut block_recorder,
             )?;
+        } else {
+            for (idx, transaction) in block.transactions.iter().enumerate() {
+                self.process_transaction(
+                    idx,
+                    transaction,
+                    block_context,
+                    state,
+                    &mut env,
+                    on_local_main,
+                    &mut block_recorder,
+                )?;
+            }
         }
 
         block_recorder.finish_block(
@@ -268,16 +312,114 @@ impl ConsensusExecutionHandler {
         Ok(())
     }
 
+    /// Execute the transactions of a block optimistically in parallel. The
+    /// receipts and the resulting state are the same as
+    /// `process_transaction` one by one.
+    fn process_transactions_in_parallel(
+        &self, block_context: &BlockProcessContext, state: &mut State,
+        env: &mut Env, on_local_main: bool,
+        recorder: &mut BlockProcessRecorder,
+    ) -> DbResult<()> {
+        let block = block_context.block;
+        let parent_hash =
+            block_context.epoch_context.main_block.block_header.parent_hash();
+        // The execution does not depend on `env.accumulated_gas_used`, which
+        // is updated in the commit step.
+        let exec_env = env.clone();
+
+        let stats = execute_in_parallel(
+            state,
+            &block.transactions,
+            |transaction| transaction.sender(),
+            |seed| State::from_seed(self.open_state_db(parent_hash)?, seed),
+            |state, _, transaction| {
+                self.execute_transaction(
+                    transaction,
+                    block_context,
+                    state,
+                    &exec_env,
+                )
+            },
+            |state, idx, execution_outcome| {
+                self.finish_transaction(
+                    idx,
+                    &block.transactions[idx],
+                    execution_outcome,
+                    block_context,
+                    state,
+                    env,
+                    on_local_main,
+                    recorder,
+                )
+            },
+        )?;
+
+        debug!(
+            "parallel execution: block={:?} speculative_committed={} \
+             re_executed={}",
+            block.hash(),
+            stats.speculative_committed,
+            stats.re_executed
+        );
+        Ok(())
+    }
+
+    /// Open the state after executing `epoch_hash`, e.g. the state before an
+    /// epoch for the speculative execution.
+    pub(super) fn open_state_db(&self, epoch_hash: &H256) -> DbResult<StateDb> {
+        let state_index = self
+            .data_man
+            .get_state_readonly_index(epoch_hash)
+            .ok_or("state is not available")?;
+        let storage = self
+            .data_man
+            .storage_manager
+            .get_state_no_commit(
+                state_index,
+                /* try_open = */ true,
+                None,
+            )?
+            .ok_or("state deleted")?;
+        Ok(StateDb::new(storage))
+    }
+
     fn process_transaction(
         &self, idx: usize, transaction: &Arc<SignedTransaction>,
         block_context: &BlockProcessContext, state: &mut State, env: &mut Env,
         on_local_main: bool, recorder: &mut BlockProcessRecorder,
     ) -> DbResult<()> {
-        let rpc_index = recorder.tx_idx[transaction.space()];
+        let track_writes = matches!(
+            block_context.epoch_context.virtual_call,
+            Some(VirtualCall::Reexecute(_))
+        );
+        if track_writes {
+            state.start_access_tracking();
+        }
 
-        let block = &block_context.block;
-        let dry_run = block_context.epoch_context.dry_run;
+        let execution_outcome =
+            self.execute_transaction(transaction, block_context, state, env)?;
+        self.finish_transaction(
+            idx,
+            transaction,
+            execution_outcome,
+            block_context,
+            state,
+            env,
+            on_local_main,
+            recorder,
+        )?;
 
+        if track_writes {
+            let access = state.finish_access_tracking();
+            recorder.tx_writes.push(access.written_accounts);
+        }
+        Ok(())
+    }
+
+    fn execute_transaction(
+        &self, transaction: &Arc<SignedTransaction>,
+        block_context: &BlockProcessContext, state: &mut State, env: &Env,
+    ) -> DbResult<ExecutionOutcome> {
         let machine = self.machine.as_ref();
 
         let spec = machine.spec(env.number, env.epoch_height);
@@ -287,9 +429,23 @@ impl ConsensusExecutionHandler {
             settings: TransactSettings::all_checks(),
         };
 
-        let execution_outcome =
-            ExecutiveContext::new(state, env, machine, &spec)
-                .transact(transaction, options)?;
+        ExecutiveContext::new(state, env, machine, &spec)
+            .transact(transaction, options)
+    }
+
+    fn finish_transaction(
+        &self, idx: usize, transaction: &Arc<SignedTransaction>,
+        execution_outcome: ExecutionOutcome,
+        block_context: &BlockProcessContext, state: &mut State, env: &mut Env,
+        on_local_main: bool, recorder: &mut BlockProcessRecorder,
+    ) -> DbResult<()> {
+        let rpc_index = recorder.tx_idx[transaction.space()];
+
+        let block = &block_context.block;
+        let dry_run = block_context.epoch_context.dry_run;
+
+        let spec = self.machine.spec(env.number, env.epoch_height);
+
         execution_outcome.log(transaction, &block_context.block.hash());
 
         if let Some(burnt_fee) = execution_outcome
@@ -372,6 +528,14 @@ impl ConsensusExecutionHandler {
             Observer::with_no_tracing()
         };
 
+        if let Some(VirtualCall::GasProfile(ref task)) =
+            block_context.epoch_context.virtual_call
+        {
+            if transaction.hash() == task.tx_hash {
+                observer.gas_profiler = Some(GasProfiler::default());
+            }
+        }
+
         if let Some(VirtualCall::GethTrace(ref task)) =
             block_context.epoch_context.virtual_call
         {
@@ -523,6 +687,8 @@ struct EpochProcessRecorder {
     staking_events: Vec<StakingEvent>,
     repack_tx: Vec<Arc<SignedTransaction>>,
     geth_traces: Vec<GethTraceWithHash>,
+    gas_profile: Option<GasProfile>,
+    tx_writes: Vec<HashSet<AddressWithSpace>>,
 
     evm_tx_idx: usize,
 }
@@ -536,8 +702,10 @@ struct BlockProcessRecorder {
     tx_error_msg: Vec<String>,
     traces: Vec<TransactionExecTraces>,
     geth_traces: Vec<GethTraceWithHash>,
+    gas_profile: Option<GasProfile>,
     repack_tx: Vec<Arc<SignedTransaction>>,
     staking_events: Vec<StakingEvent>,
+    tx_writes: Vec<HashSet<AddressWithSpace>>,
 
     tx_idx: SpaceMap<usize>,
 }
@@ -551,8 +719,10 @@ impl BlockProcessRecorder {
             tx_error_msg: vec![],
             traces: vec![],
             geth_traces: vec![],
+            gas_profile: None,
             repack_tx: vec![],
             staking_events: vec![],
+            tx_writes: vec![],
             tx_idx,
         }
     }
@@ -589,6 +759,10 @@ impl BlockProcessRecorder {
             });
         }
 
+        if let Some(profile) = r.gas_profile {
+            self.gas_profile = Some(profile);
+        }
+
         match tx.space() {
             Space::Native => {
                 self.tx_idx[Space::Native] += 1;
@@ -632,6 +806,10 @@ impl BlockProcessRecorder {
         epoch_recorder.staking_events.extend(self.staking_events);
         epoch_recorder.repack_tx.extend(self.repack_tx);
         epoch_recorder.geth_traces.extend(self.geth_traces);
+        epoch_recorder.tx_writes.extend(self.tx_writes);
+        if let Some(profile) = self.gas_profile {
+            epoch_recorder.gas_profile = Some(profile);
+        }
 
         epoch_recorder.evm_tx_idx = self.tx_idx[Space::Ethereum];
 
@@ -1,9 +1,13 @@
 
 
+pub mod dev_state;
 mod epoch_execution;
+mod pending_execution;
+pub mod reexecution;
 
 use core::convert::TryFrom;
 use std::{
+    cmp::max,
     collections::{BTreeMap, BTreeSet, HashMap},
     convert::From,
     fmt::{Debug, Formatter},
@@ -45,6 +49,7 @@ use crate::{
             consensus_new_block_handler::ConsensusNewBlockHandler,
             StateBlameInfo,
         },
+        pending_block::PendingBlock,
         pos_handler::PosVerifier,
         ConsensusGraphInner,
     },
@@ -56,8 +61,9 @@ use crate::{
     },
     SharedTransactionPool,
 };
-use mazze_execute_helper::estimation::{
-    EstimateExt, EstimateRequest, EstimationContext,
+use mazze_execute_helper::{
+    estimation::{EstimateExt, EstimateRequest, EstimationContext},
+    observer::gas_profiler::GasProfile,
 };
 use mazze_executor::{
     executive::ExecutionOutcome,
@@ -72,7 +78,11 @@ use geth_tracer::GethTraceWithHash;
 
 use alloy_rpc_types_trace::geth::GethDebugTracingOptions;
 
-use self::epoch_execution::{GethTask, VirtualCall};
+use self::{
+    dev_state::{DevStateChange, DevStateChanges},
+    epoch_execution::{GasProfileTask, GethTask, VirtualCall},
+    reexecution::EpochReexecution,
+};
 
 lazy_static! {
     static ref CONSENSIS_EXECUTION_TIMER: Arc<dyn Meter> =
@@ -642,6 +652,20 @@ impl ConsensusExecutor {
         self.handler.call_virtual(tx, epoch_id, epoch_size, request)
     }
 
+    pub fn execute_pending_block(
+        &self, epoch_id: &H256, epoch_size: usize, block: Arc<Block>,
+    ) -> RpcResult<PendingBlock> {
+        self.handler.execute_pending_block(epoch_id, epoch_size, block)
+    }
+
+    pub fn call_virtual_on_pending_block(
+        &self, tx: &SignedTransaction, pending_block: &PendingBlock,
+        request: EstimateRequest,
+    ) -> RpcResult<(ExecutionOutcome, EstimateExt)> {
+        self.handler
+            .call_virtual_on_pending_block(tx, pending_block, request)
+    }
+
     pub fn collect_epoch_geth_trace(
         &self, epoch_block_hashes: Vec<H256>, tx_hash: Option<H256>,
         opts: GethDebugTracingOptions,
@@ -650,6 +674,105 @@ impl ConsensusExecutor {
             .collect_epoch_geth_trace(epoch_block_hashes, tx_hash, opts)
     }
 
+    pub fn collect_tx_gas_profile(
+        &self, epoch_block_hashes: Vec<H256>, tx_hash: H256,
+    ) -> RpcResult<Option<GasProfile>> {
+        self.handler
+            .collect_tx_gas_profile(epoch_block_hashes, tx_hash)
+    }
+
+    pub fn reexecute_epoch(
+        &self, epoch_block_hashes: &Vec<H256>,
+        reward_execution_info: &Option<RewardExecutionInfo>,
+    ) -> RpcResult<EpochReexecution> {
+        self.handler
+            .reexecute_epoch(epoch_block_hashes, reward_execution_info)
+    }
+
+    /// Queue dev state changes, which are applied to the epochs whose main
+    /// block is a child of `parent_hash`.
+    pub fn queue_dev_state_changes(
+        &self, parent_hash: H256, changes: Vec<DevStateChange>,
+    ) {
+        self.handler
+            .dev_state_changes
+            .lock()
+            .queue(parent_hash, changes);
+    }
+
+    /// Discard the dev state changes queued for the children of the blocks.
+    pub fn discard_dev_state_changes(&self, parent_hashes: &[H256]) {
+        let mut dev_state_changes = self.handler.dev_state_changes.lock();
+        for parent_hash in parent_hashes {
+            dev_state_changes.discard(parent_hash);
+        }
+    }
+
+    /// Wait until all the tasks currently in the queue are executed.
+    pub fn wait_for_queued_tasks(&self) {
+        if self.consensus_graph_bench_mode {
+            return;
+        }
+        let (sender, receiver) = channel();
+        self.sender
+            .lock()
+            .send(ExecutionTask::GetResult(GetExecutionResultTask {
+                epoch_hash: H256::zero(),
+                sender,
+            }))
+            .expect("Cannot fail");
+        // The zero hash is never executed, so the result is `None`.
+        receiver.recv().ok();
+    }
+
+    /// Execute the main chain epochs up to the main block `block_hash` as
+    /// main chain epochs, so their receipts and transaction indices are
+    /// available at once. The epochs executed later by the worker are
+    /// skipped.
+    pub fn compute_main_epochs_for_dev(
+        &self, block_hash: &H256, inner: &mut ConsensusGraphInner,
+    ) -> Result<(), String> {
+        let me = *inner
+            .hash_to_arena_indices
+            .get(block_hash)
+            .ok_or("Block hash not found!")?;
+        let height = inner.arena[me].height;
+        if height < inner.cur_era_genesis_height
+            || height >= inner.get_main_height()
+            || inner.get_main_block_arena_index(height) != me
+        {
+            return Err("Block is not on the main chain".into());
+        }
+        // The epochs are executed or skipped in order from the available
+        // state, so the state availability boundary reaches `height`.
+        let upper_bound = inner
+            .data_man
+            .state_availability_boundary
+            .read()
+            .upper_bound;
+        let start_height = max(upper_bound + 1, inner.cur_era_genesis_height);
+        for epoch_height in start_height..=height {
+            let epoch_arena_index =
+                inner.get_main_block_arena_index(epoch_height);
+            let reward_execution_info =
+                self.get_reward_execution_info(inner, epoch_arena_index);
+            self.enqueue_epoch(EpochExecutionTask::new(
+                epoch_arena_index,
+                inner,
+                reward_execution_info,
+                true,  /* on_local_main */
+                false, /* force_recompute */
+            ));
+        }
+        self.wait_for_result(*block_hash).map_err(|_| {
+            format!(
+                "Epoch {:?} is not executed, see the log for the error",
+                block_hash
+            )
+        })?;
+        Ok(())
+    }
+
     pub fn stop(&self) {
         // `stopped` is used to allow the execution thread to stopped even the
         // queue is not empty and `ExecutionTask::Stop` has not been
@@ -828,6 +951,7 @@ pub struct ConsensusExecutionHandler {
     machine: Arc<Machine>,
     pos_verifier: Arc<PosVerifier>,
     execution_state_prefetcher: Option<Arc<ExecutionStatePrefetcher>>,
+    dev_state_changes: Mutex<DevStateChanges>,
 }
 
 impl ConsensusExecutionHandler {
@@ -859,6 +983,7 @@ impl ConsensusExecutionHandler {
             } else {
                 None
             },
+            dev_state_changes: Default::default(),
         }
     }
 
@@ -1046,12 +1171,36 @@ impl ConsensusExecutionHandler {
             epoch_blocks.len(),
         );
 
+        // The remote node of a forked state may be unavailable, so the
+        // execution of an epoch may fail. The epoch is left unexecuted
+        // instead of crashing, and it's executed again when a later epoch is
+        // mined in dev mode.
+        let forked = self.data_man.storage_manager.is_forked();
+        if forked
+            && self
+                .data_man
+                .get_epoch_execution_commitment(
+                    main_block.block_header.parent_hash(),
+                )
+                .is_none()
+        {
+            warn!(
+                "Skip epoch {:?} as its parent epoch is not executed",
+                epoch_hash
+            );
+            return;
+        }
+
         let mut state = self
             .new_state(main_block, recover_mpt_during_construct_main_state)
             .expect("Cannot init state");
 
-        let epoch_receipts = self
-            .process_epoch_transactions(
+        let dev_state_result = self
+            .dev_state_changes
+            .lock()
+            .apply(main_block.block_header.parent_hash(), &mut state);
+        let epoch_receipts = dev_state_result.and_then(|_| {
+            self.process_epoch_transactions(
                 *epoch_hash,
                 &mut state,
                 &epoch_blocks,
@@ -1059,9 +1208,49 @@ impl ConsensusExecutionHandler {
                 on_local_main,
                 /* virtual_call */ None,
             )
+        });
+        let epoch_receipts = match epoch_receipts {
+            Ok(epoch_receipts) => epoch_receipts,
+            Err(e) if forked => {
+                error!(
+                    "Failed to execute epoch {:?} on the forked state: {}",
+                    epoch_hash, e
+                );
+                return;
+            }
             // TODO: maybe propagate the error all the way up so that the
             // program may restart by itself.
-            .expect("Can not handle db error in consensus, crashing.");
+            Err(e) => {
+                panic!("Can not handle db error in consensus, crashing: {}", e)
+            }
+        };
+
+        if on_local_main {
+            let evm_chain_id = self
+                .machine
+                .params()
+                .chain_id
+                .read()
+                .get_chain_id(main_block.block_header.height())
+                .in_evm_space();
+            self.data_man.insert_cross_space_transfers(
+                epoch_hash,
+                &epoch_blocks,
+                &epoch_receipts,
+                evm_chain_id,
+            );
+            self.data_man.insert_log_index(
+                main_block.block_header.height(),
+                epoch_hash,
+                &epoch_receipts,
+            );
+            self.data_man.insert_address_tx_index(
+                main_block.block_header.height(),
+                epoch_hash,
+                &epoch_blocks,
+                &epoch_receipts,
+            );
+        }
 
         let current_block_number =
             start_block_number + epoch_receipts.len() as u64 - 1;
@@ -1086,12 +1275,15 @@ impl ConsensusExecutionHandler {
             &mut state,
             main_block,
             current_block_number,
+            /* dry_run */ false,
         )
         .expect("db error");
 
         let commit_result = state
             .commit(*epoch_hash, debug_record.as_deref_mut())
             .expect(&concat!(file!(), ":", line!(), ":", column!()));
+        self.data_man
+            .insert_state_change_set(epoch_hash, &commit_result.change_set);
 
         if on_local_main {
             self.notify_txpool(&commit_result, epoch_hash);
@@ -1156,7 +1348,7 @@ impl ConsensusExecutionHandler {
 
     fn process_pos_interest(
         &self, state: &mut State, main_block: &Block,
-        current_block_number: u64,
+        current_block_number: u64, dry_run: bool,
     ) -> DbResult<()> {
         // TODO(peilun): Specify if we unlock before or after executing the
         // transactions.
@@ -1199,10 +1391,12 @@ impl ConsensusExecutionHandler {
                         reward_event.rewards(),
                         current_block_number,
                     )?;
-                self.data_man.insert_pos_reward(
-                    *pos_epoch,
-                    &PosRewardInfo::new(account_rewards, main_block.hash()),
-                )
+                if !dry_run {
+                    self.data_man.insert_pos_reward(
+                        *pos_epoch,
+                        &PosRewardInfo::new(account_rewards, main_block.hash()),
+                    )
+                }
             }
         }
         Ok(())
@@ -1581,6 +1775,49 @@ impl ConsensusExecutionHandler {
         )
     }
 
+    /// Open a read-only state of an executed epoch for virtual execution.
+    /// Changes made to the returned state are never committed.
+    fn open_virtual_state(
+        &self, epoch_id: &H256, epoch_height: u64, state_space: Option<Space>,
+    ) -> RpcResult<State> {
+        Ok(State::new(self.open_virtual_state_db(
+            epoch_id,
+            epoch_height,
+            state_space,
+        )?)?)
+    }
+
+    fn open_virtual_state_db(
+        &self, epoch_id: &H256, epoch_height: u64, state_space: Option<Space>,
+    ) -> RpcResult<StateDb> {
+        // Keep the lock until we get the desired State, otherwise the State may
+        // expire.
+        let state_availability_boundary =
+            self.data_man.state_availability_boundary.read();
+
+        if !state_availability_boundary.check_read_availability(
+            epoch_height,
+            epoch_id,
+            state_space,
+        ) {
+            bail!("state is not ready");
+        }
+        let state_index = self.data_man.get_state_readonly_index(epoch_id);
+        let db = StateDb::new(
+            self.data_man
+                .storage_manager
+                .get_state_no_commit(
+                    state_index.unwrap(),
+                    /* try_open = */ true,
+                    state_space,
+                )?
+                .ok_or("state deleted")?,
+        );
+        drop(state_availability_boundary);
+
+        Ok(db)
+    }
+
     pub fn call_virtual(
         &self, tx: &SignedTransaction, epoch_id: &H256, epoch_size: usize,
         request: EstimateRequest,
@@ -1618,36 +1855,17 @@ impl ConsensusExecutionHandler {
             ),
         )?;
 
-        // Keep the lock until we get the desired State, otherwise the State may
-        // expire.
-        let state_availability_boundary =
-            self.data_man.state_availability_boundary.read();
-
         let state_space = match tx.space() {
             Space::Native => None,
             Space::Ethereum => Some(Space::Ethereum),
         };
-        if !state_availability_boundary.check_read_availability(
-            best_block_header.height(),
+        let mut state = self.open_virtual_state(
             epoch_id,
+            best_block_header.height(),
             state_space,
-        ) {
-            bail!("state is not ready");
-        }
-        let state_index = self.data_man.get_state_readonly_index(epoch_id);
+        )?;
         trace!("best_block_header: {:?}", best_block_header);
         let time_stamp = best_block_header.timestamp();
-        let mut state = State::new(StateDb::new(
-            self.data_man
-                .storage_manager
-                .get_state_no_commit(
-                    state_index.unwrap(),
-                    /* try_open = */ true,
-                    state_space,
-                )?
-                .ok_or("state deleted")?,
-        ))?;
-        drop(state_availability_boundary);
 
         let miner = {
             let mut address = H160::random();
@@ -1696,11 +1914,55 @@ impl ConsensusExecutionHandler {
         &self, epoch_block_hashes: Vec<H256>, tx_hash: Option<H256>,
         opts: GethDebugTracingOptions,
     ) -> RpcResult<Vec<GethTraceWithHash>> {
+        let (epoch_blocks, mut state, start_block_number) =
+            self.open_epoch_for_replay(&epoch_block_hashes)?;
+
+        self.execute_epoch_tx_to_collect_trace(
+            &mut state,
+            &epoch_blocks,
+            start_block_number,
+            tx_hash,
+            opts,
+        )
+        .map_err(|err| err.into())
+    }
+
+    /// Execute the transactions in the epoch to profile the gas used by the
+    /// transaction `tx_hash`.
+    pub fn collect_tx_gas_profile(
+        &self, epoch_block_hashes: Vec<H256>, tx_hash: H256,
+    ) -> RpcResult<Option<GasProfile>> {
+        let (epoch_blocks, mut state, start_block_number) =
+            self.open_epoch_for_replay(&epoch_block_hashes)?;
+        let epoch_id = epoch_blocks.last().unwrap().hash();
+
+        let mut answer = None;
+        let virtual_call = VirtualCall::GasProfile(GasProfileTask {
+            tx_hash,
+            answer: &mut answer,
+        });
+        self.process_epoch_transactions(
+            epoch_id,
+            &mut state,
+            &epoch_blocks,
+            start_block_number,
+            false,
+            Some(virtual_call),
+        )?;
+
+        Ok(answer)
+    }
+
+    /// Load the blocks of an epoch, and open the state before the epoch to
+    /// execute them again.
+    fn open_epoch_for_replay(
+        &self, epoch_block_hashes: &Vec<H256>,
+    ) -> RpcResult<(Vec<Arc<Block>>, State, u64)> {
         // Get blocks in this epoch after skip checking
         let epoch_blocks = self
             .data_man
             .blocks_by_hash_list(
-                &epoch_block_hashes,
+                epoch_block_hashes,
                 true, /* update_cache */
             )
             .expect("blocks exist");
@@ -1735,7 +1997,7 @@ impl ConsensusExecutionHandler {
             )?
             .ok_or("state deleted")?;
         let state_db = StateDb::new(storage);
-        let mut state = State::new(state_db)?;
+        let state = State::new(state_db)?;
 
         let start_block_number = self
             .data_man
@@ -1743,14 +2005,7 @@ impl ConsensusExecutionHandler {
             .map(|v| v.start_block_number)
             .expect("should exist");
 
-        self.execute_epoch_tx_to_collect_trace(
-            &mut state,
-            &epoch_blocks,
-            start_block_number,
-            tx_hash,
-            opts,
-        )
-        .map_err(|err| err.into())
+        Ok((epoch_blocks, state, start_block_number))
     }
 
     /// Execute transactions in the epoch to collect traces.
@@ -1783,4 +2038,7 @@ impl ConsensusExecutionHandler {
 
 pub struct ConsensusExecutionConfiguration {
     pub executive_trace: bool,
+    /// Execute the transactions of a block optimistically in parallel. The
+    /// result is always the same as serial execution.
+    pub parallel_execution: bool,
 }
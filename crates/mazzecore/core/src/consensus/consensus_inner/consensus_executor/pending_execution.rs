@@ -0,0 +1,154 @@
+use super::{epoch_execution::VirtualCall, ConsensusExecutionHandler};
+use std::sync::Arc;
+
+use mazze_execute_helper::estimation::{
+    EstimateExt, EstimateRequest, EstimationContext,
+};
+use mazze_executor::{executive::ExecutionOutcome, state::State};
+use mazze_types::{
+    address_util::AddressUtil, AllChainID, Space, H160, H256, U256,
+};
+use mazze_vm_types::Env;
+use primitives::{Block, SignedTransaction};
+
+use crate::{
+    consensus::pending_block::PendingBlock,
+    rpc_errors::{invalid_params_check, Result as RpcResult},
+    verification::{VerifyTxLocalMode, VerifyTxMode},
+};
+
+impl ConsensusExecutionHandler {
+    /// Execute `block` as a single-block epoch on top of the state of
+    /// `epoch_id`, without persisting receipts, traces or the state.
+    pub fn execute_pending_block(
+        &self, epoch_id: &H256, epoch_size: usize, block: Arc<Block>,
+    ) -> RpcResult<PendingBlock> {
+        let epoch_header = match self.data_man.block_header_by_hash(epoch_id) {
+            Some(header) => header,
+            None => bail!("invalid epoch id"),
+        };
+
+        let start_block_number = match self.data_man.get_epoch_execution_context(epoch_id) {
+            Some(v) => v.start_block_number + epoch_size as u64,
+            None => bail!("cannot obtain the execution context. Database is potentially corrupted!"),
+        };
+
+        // The pending block may contain transactions from both spaces.
+        let mut state =
+            self.open_virtual_state(epoch_id, epoch_header.height(), None)?;
+
+        let mut receipts = self.process_epoch_transactions(
+            block.hash(),
+            &mut state,
+            &vec![block.clone()],
+            start_block_number,
+            false, /* on_local_main */
+            Some(VirtualCall::PendingBlock),
+        )?;
+
+        let evm_chain_id = self
+            .machine
+            .params()
+            .chain_id(block.block_header.height(), Space::Ethereum);
+
+        Ok(PendingBlock::new(
+            block,
+            start_block_number,
+            *epoch_id,
+            receipts.pop().expect("one block is executed"),
+            evm_chain_id,
+            state,
+        ))
+    }
+
+    /// Execute `tx` virtually as if it were appended to the pending block.
+    pub fn call_virtual_on_pending_block(
+        &self, tx: &SignedTransaction, pending_block: &PendingBlock,
+        request: EstimateRequest,
+    ) -> RpcResult<(ExecutionOutcome, EstimateExt)> {
+        let header = pending_block.header();
+        let block_height = header.height();
+        let block_number = pending_block.block_number;
+
+        let spec = self.machine.spec(block_number, block_height);
+        let transitions = &self.machine.params().transition_heights;
+
+        invalid_params_check(
+            "tx",
+            self.verification_config.verify_transaction_common(
+                tx,
+                AllChainID::fake_for_virtual(tx.chain_id().unwrap_or(1)),
+                block_height,
+                transitions,
+                VerifyTxMode::Local(VerifyTxLocalMode::Full, &spec),
+            ),
+        )?;
+
+        // The pending block carries no PoS reference, use its parent's.
+        let pos_id = self
+            .data_man
+            .block_header_by_hash(header.parent_hash())
+            .and_then(|parent| *parent.pos_reference());
+        let pos_view_number = pos_id
+            .as_ref()
+            .and_then(|id| self.pos_verifier.get_pos_view(id));
+        let main_decision_epoch = pos_id
+            .as_ref()
+            .and_then(|id| self.pos_verifier.get_main_decision(id))
+            .and_then(|hash| self.data_man.block_header_by_hash(&hash))
+            .map(|header| header.height());
+
+        let miner = {
+            let mut address = H160::random();
+            if tx.space() == Space::Native {
+                address.set_user_account_type_bits();
+            }
+            address
+        };
+
+        // Every call gets its own copy of the pending state, so concurrent
+        // queries neither serialize nor see each other's changes.
+        let mut state = State::from_seed(
+            self.open_virtual_state_db(
+                &pending_block.state_epoch,
+                pending_block.state_epoch_height(),
+                None,
+            )?,
+            pending_block.seed(),
+        )?;
+
+        let base_gas_price = header.base_price().unwrap_or_default();
+        let burnt_gas_price =
+            base_gas_price.map_all(|x| state.burnt_gas_price(x));
+
+        let env = Env {
+            chain_id: self.machine.params().chain_id_map(block_height),
+            number: block_number,
+            author: miner,
+            timestamp: header.timestamp(),
+            difficulty: Default::default(),
+            accumulated_gas_used: U256::zero(),
+            last_hash: pending_block.hash(),
+            gas_limit: tx.gas().clone(),
+            epoch_height: block_height,
+            pos_view: pos_view_number,
+            finalized_epoch: main_decision_epoch,
+            transaction_epoch_bound: self
+                .verification_config
+                .transaction_epoch_bound,
+            base_gas_price,
+            burnt_gas_price,
+        };
+
+        let r = EstimationContext::new(
+            &mut state,
+            &env,
+            self.machine.as_ref(),
+            &spec,
+        )
+        .transact_virtual(tx.clone(), request);
+
+        trace!("Execution result {:?}", r);
+        Ok(r?)
+    }
+}
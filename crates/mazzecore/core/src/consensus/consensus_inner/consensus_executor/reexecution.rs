@@ -0,0 +1,293 @@
+use super::{
+    epoch_execution::{ReexecuteTask, VirtualCall},
+    ConsensusExecutionHandler, RewardExecutionInfo,
+};
+use std::collections::BTreeSet;
+
+use mazze_executor::state::{AccountDiff, State};
+use mazze_internal_common::EpochExecutionCommitment;
+use mazze_types::{AddressWithSpace, H256};
+use primitives::{BlockHeaderBuilder, Receipt};
+
+use crate::{
+    rpc_errors::Result as RpcResult, verification::compute_receipts_root,
+};
+
+/// The result of executing an executed epoch again, compared with the stored
+/// one.
+#[derive(Debug)]
+pub struct EpochReexecution {
+    pub epoch_hash: H256,
+    /// The stored execution commitment of the epoch.
+    pub expected: EpochExecutionCommitment,
+    /// The execution commitment computed again.
+    pub actual: EpochExecutionCommitment,
+    /// The first transaction whose receipt differs from the stored one.
+    pub first_mismatch: Option<TransactionMismatch>,
+    /// The accounts whose state at the end of the epoch computed again
+    /// differs from the stored state after the epoch. Only the state after
+    /// the whole epoch is stored, so the accounts are always compared at the
+    /// end of the epoch. If some transaction mismatches, only the accounts
+    /// written by the first one are compared, otherwise all the accounts
+    /// written in the epoch.
+    pub account_diffs: Vec<AccountDiff>,
+}
+
+impl EpochReexecution {
+    pub fn is_consistent(&self) -> bool {
+        let expected_state_root = self
+            .expected
+            .state_root_with_aux_info
+            .state_root
+            .compute_state_root_hash();
+        let actual_state_root = self
+            .actual
+            .state_root_with_aux_info
+            .state_root
+            .compute_state_root_hash();
+
+        self.first_mismatch.is_none()
+            && self.account_diffs.is_empty()
+            && expected_state_root == actual_state_root
+            && self.expected.receipts_root == self.actual.receipts_root
+            && self.expected.logs_bloom_hash == self.actual.logs_bloom_hash
+    }
+}
+
+/// A transaction whose receipt computed again differs from the stored one.
+#[derive(Debug)]
+pub struct TransactionMismatch {
+    pub block_hash: H256,
+    pub tx_hash: H256,
+    /// The index of the transaction in its block.
+    pub index: usize,
+    pub expected: Receipt,
+    pub actual: Receipt,
+}
+
+impl ConsensusExecutionHandler {
+    /// Execute an executed epoch again on top of the state of its parent, and
+    /// compare the receipts and the resulting state with the stored ones.
+    /// Nothing is persisted.
+    pub fn reexecute_epoch(
+        &self, epoch_block_hashes: &Vec<H256>,
+        reward_execution_info: &Option<RewardExecutionInfo>,
+    ) -> RpcResult<EpochReexecution> {
+        let epoch_blocks = self
+            .data_man
+            .blocks_by_hash_list(
+                epoch_block_hashes,
+                false, /* update_cache */
+            )
+            .ok_or("the blocks of the epoch are missing")?;
+        let main_block = epoch_blocks.last().expect("Epoch not empty");
+        let epoch_hash = main_block.hash();
+        let epoch_height = main_block.block_header.height();
+        let parent_hash = main_block.block_header.parent_hash();
+
+        let expected = self
+            .data_man
+            .get_epoch_execution_commitment_with_db(&epoch_hash)
+            .ok_or("the epoch is not executed")?;
+        let start_block_number = self
+            .data_man
+            .get_epoch_execution_context(&epoch_hash)
+            .ok_or("the execution context of the epoch is missing")?
+            .start_block_number;
+
+        // Both the state before and after the epoch are needed.
+        {
+            let state_availability_boundary =
+                self.data_man.state_availability_boundary.read();
+            if !state_availability_boundary.check_read_availability(
+                epoch_height - 1,
+                parent_hash,
+                None,
+            ) || !state_availability_boundary.check_read_availability(
+                epoch_height,
+                &epoch_hash,
+                None,
+            ) {
+                bail!("state is not available");
+            }
+        }
+        // The execution commitments are not loaded into memory when the graph
+        // is recovered offline.
+        if self
+            .data_man
+            .load_epoch_execution_commitment_from_db(parent_hash)
+            .is_none()
+        {
+            bail!("the parent epoch is not executed");
+        }
+
+        let mut state = self.new_state(main_block, false)?;
+
+        let mut tx_writes = vec![];
+        let epoch_receipts = self.process_epoch_transactions(
+            epoch_hash,
+            &mut state,
+            &epoch_blocks,
+            start_block_number,
+            false, /* on_local_main */
+            Some(VirtualCall::Reexecute(ReexecuteTask {
+                tx_writes: &mut tx_writes,
+            })),
+        )?;
+
+        state.start_access_tracking();
+        let current_block_number =
+            start_block_number + epoch_receipts.len() as u64 - 1;
+        if let Some(reward_execution_info) = reward_execution_info {
+            let spec = self.machine.spec(current_block_number, epoch_height);
+            self.process_rewards_and_fees(
+                &mut state,
+                reward_execution_info,
+                &epoch_hash,
+                false, /* on_local_main */
+                None,  /* debug_record */
+                spec,
+            );
+        }
+        self.process_pos_interest(
+            &mut state,
+            main_block,
+            current_block_number,
+            /* dry_run */ true,
+        )?;
+        let reward_writes = state.finish_access_tracking().written_accounts;
+
+        let mut first_mismatch = None;
+        let mut tx_offset = 0;
+        'blocks: for (block, receipts) in
+            epoch_blocks.iter().zip(&epoch_receipts)
+        {
+            let stored_receipts = self
+                .data_man
+                .block_execution_result_by_hash_with_epoch(
+                    &block.hash(),
+                    &epoch_hash,
+                    false, /* update_main_assumption */
+                    false, /* update_cache */
+                )
+                .ok_or("the receipts of the epoch are missing")?
+                .block_receipts;
+            let receipt_pairs =
+                stored_receipts.receipts.iter().zip(&receipts.receipts);
+            for (index, (expected, actual)) in receipt_pairs.enumerate() {
+                if expected != actual {
+                    let mismatch = TransactionMismatch {
+                        block_hash: block.hash(),
+                        tx_hash: block.transactions[index].hash(),
+                        index,
+                        expected: expected.clone(),
+                        actual: actual.clone(),
+                    };
+                    first_mismatch = Some((tx_offset + index, mismatch));
+                    break 'blocks;
+                }
+            }
+            tx_offset += block.transactions.len();
+        }
+
+        let addresses: BTreeSet<AddressWithSpace> = match &first_mismatch {
+            Some((tx_index, _)) => {
+                tx_writes[*tx_index].iter().cloned().collect()
+            }
+            None => tx_writes
+                .iter()
+                .flatten()
+                .chain(&reward_writes)
+                .cloned()
+                .collect(),
+        };
+        let stored_state = State::new(self.open_state_db(&epoch_hash)?)?;
+        let account_diffs = state.diff_accounts(&stored_state, &addresses)?;
+
+        let actual = EpochExecutionCommitment {
+            state_root_with_aux_info: state.compute_state_root()?,
+            receipts_root: compute_receipts_root(&epoch_receipts),
+            logs_bloom_hash: BlockHeaderBuilder::compute_block_logs_bloom_hash(
+                &epoch_receipts,
+            ),
+        };
+
+        Ok(EpochReexecution {
+            epoch_hash,
+            expected,
+            actual,
+            first_mismatch: first_mismatch.map(|(_, mismatch)| mismatch),
+            account_diffs,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sync::utils::{
+        initialize_executed_synchronization_graph, insert_executed_chain,
+        remove_db_dir,
+    };
+    use mazze_types::H256;
+
+    #[test]
+    fn test_reexecute_executed_epochs() {
+        const DB_DIR: &str = "./test_reexecute_epochs.db/";
+        {
+            let (sync, consensus, data_man, genesis_block) =
+                initialize_executed_synchronization_graph(DB_DIR, 50000);
+            // The epochs 1 to 3 are executed after 8 blocks.
+            let hashes = insert_executed_chain(
+                &sync,
+                &consensus,
+                genesis_block.hash(),
+                8,
+            );
+            consensus.executor.wait_for_result(hashes[2]).unwrap();
+
+            for epoch_number in 1..=3 {
+                let result = consensus.reexecute_epoch(epoch_number).unwrap();
+                assert_eq!(
+                    result.epoch_hash,
+                    hashes[epoch_number as usize - 1]
+                );
+                assert!(result.is_consistent());
+                assert!(result.first_mismatch.is_none());
+                assert!(result.account_diffs.is_empty());
+            }
+
+            // The epochs which are not executed, or whose parent state is not
+            // available, are rejected.
+            assert!(consensus.reexecute_epoch(0).is_err());
+            assert!(consensus.reexecute_epoch(6).is_err());
+            assert!(consensus.reexecute_epoch(8).is_err());
+
+            // A divergence from the stored commitment is reported, and nothing
+            // is persisted by executing the epoch again.
+            let stored = data_man
+                .get_epoch_execution_commitment_with_db(&hashes[1])
+                .unwrap();
+            let wrong_receipts_root = H256::from_low_u64_be(1);
+            data_man.insert_epoch_execution_commitment(
+                hashes[1],
+                stored.state_root_with_aux_info.clone(),
+                wrong_receipts_root,
+                stored.logs_bloom_hash,
+            );
+            let result = consensus.reexecute_epoch(2).unwrap();
+            assert!(!result.is_consistent());
+            assert_eq!(result.expected.receipts_root, wrong_receipts_root);
+            assert_eq!(result.actual.receipts_root, stored.receipts_root);
+            assert!(result.first_mismatch.is_none());
+            assert_eq!(
+                data_man
+                    .get_epoch_execution_commitment_with_db(&hashes[1])
+                    .unwrap()
+                    .receipts_root,
+                wrong_receipts_root
+            );
+        }
+
+        remove_db_dir(DB_DIR);
+    }
+}
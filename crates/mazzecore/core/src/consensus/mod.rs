@@ -5,6 +5,7 @@ pub mod consensus_inner;
 pub mod consensus_trait;
 pub mod debug_recompute;
 mod pastset_cache;
+mod pending_block;
 pub mod pos_handler;
 
 use super::consensus::consensus_inner::{
@@ -13,24 +14,33 @@ use super::consensus::consensus_inner::{
     consensus_new_block_handler::ConsensusNewBlockHandler,
 };
 pub use crate::consensus::{
-    consensus_inner::{ConsensusGraphInner, ConsensusInnerConfig},
+    consensus_inner::{
+        consensus_executor::{
+            dev_state::DevStateChange,
+            reexecution::{EpochReexecution, TransactionMismatch},
+        },
+        ConsensusGraphInner, ConsensusInnerConfig,
+    },
     consensus_trait::{ConsensusGraphTrait, SharedConsensusGraph},
+    pending_block::PendingBlock,
 };
 use crate::{
     block_data_manager::{
-        BlockDataManager, BlockExecutionResultWithEpoch, DataVersionTuple,
+        state_change_set::historical_state_base, BlockDataManager,
+        BlockExecutionResultWithEpoch, DataVersionTuple,
     },
     consensus::{
         consensus_inner::{
             consensus_executor::ConsensusExecutionConfiguration, StateBlameInfo,
         },
+        pending_block::PendingBlockKey,
         pos_handler::PosVerifier,
     },
     pow::{PowComputer, ProofOfWorkConfig},
     rpc_errors::{invalid_params, invalid_params_check, Result as RpcResult},
     statistics::SharedStatistics,
     transaction_pool::SharedTransactionPool,
-    verification::VerificationConfig,
+    verification::{compute_transaction_root, VerificationConfig},
     NodeType, Notifications,
 };
 use mazze_execute_helper::{
@@ -39,14 +49,16 @@ use mazze_execute_helper::{
         recover_phantom_traces, ActionType, BlockExecTraces, LocalizedTrace,
         TraceFilter, TransactionExecTraces,
     },
+    observer::gas_profiler::GasProfile,
     phantom_tx::build_bloom_and_recover_phantom,
 };
 use mazze_executor::{executive::ExecutionOutcome, state::State};
 use geth_tracer::GethTraceWithHash;
 
 use alloy_rpc_types_trace::geth::GethDebugTracingOptions;
-use mazze_internal_common::ChainIdParams;
+use mazze_internal_common::{ChainIdParams, StateAvailabilityBoundary};
 use mazze_parameters::{
+    block::{MAX_BLOCK_SIZE_IN_BYTES, MAX_TRANSACTION_COUNT_PER_BLOCK},
     consensus::*,
     consensus_internal::REWARD_EPOCH_COUNT,
     rpc::{
@@ -56,7 +68,8 @@ use mazze_parameters::{
 };
 use mazze_statedb::StateDb;
 use mazze_storage::{
-    state::StateTrait, state_manager::StateManagerTrait, StorageState,
+    state::StateTrait, state_manager::StateManagerTrait, StateIndex,
+    StorageState,
 };
 use mazze_types::{AddressWithSpace, AllChainID, Bloom, Space, H256, U256};
 use either::Either;
@@ -74,8 +87,8 @@ use primitives::{
     log_entry::LocalizedLogEntry,
     pos::PosBlockId,
     receipt::Receipt,
-    BlockHeader, EpochId, EpochNumber, SignedTransaction, TransactionIndex,
-    TransactionStatus,
+    Block, BlockHeader, BlockHeaderBuilder, EpochId, EpochNumber,
+    SignedTransaction, TransactionIndex, TransactionStatus,
 };
 use rayon::prelude::*;
 use std::{
@@ -87,7 +100,7 @@ use std::{
         Arc,
     },
     thread::sleep,
-    time::Duration,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 lazy_static! {
@@ -156,6 +169,9 @@ pub struct ConsensusConfig {
     /// The number of extra epochs that we want to keep
     /// states/receipts/transactions.
     pub sync_state_epoch_gap: Option<u64>,
+    /// In dev mode, the state of the latest executed epoch is exposed without
+    /// waiting for `DEFERRED_STATE_EPOCH_COUNT` epochs.
+    pub dev_mode: bool,
 }
 
 #[derive(Debug)]
@@ -229,6 +245,10 @@ pub struct ConsensusGraph {
     /// This is always `None` for archive nodes.
     pub synced_epoch_id: Mutex<Option<EpochId>>,
     pub config: ConsensusConfig,
+
+    /// The last pending block built for RPC queries, rebuilt lazily when the
+    /// executed state or the transaction pool changes.
+    pending_block: Mutex<Option<(PendingBlockKey, Arc<PendingBlock>)>>,
 }
 
 impl MallocSizeOf for ConsensusGraph {
@@ -297,6 +317,7 @@ impl ConsensusGraph {
             ready_for_mining: AtomicBool::new(false),
             synced_epoch_id: Default::default(),
             config: conf,
+            pending_block: Default::default(),
         };
         graph.update_best_info(false /* ready_for_mining */);
         graph
@@ -648,12 +669,13 @@ impl ConsensusGraph {
         // Here we can also get `best_state_epoch` from `inner`, but that
         // would acquire the inner read lock.
         let best_epoch_number = self.best_info.read().best_epoch_number;
-        let deferred_state_height =
-            if best_epoch_number < DEFERRED_STATE_EPOCH_COUNT {
-                0
-            } else {
-                best_epoch_number - DEFERRED_STATE_EPOCH_COUNT + 1
-            };
+        let deferred_state_height = if self.config.dev_mode {
+            best_epoch_number
+        } else if best_epoch_number < DEFERRED_STATE_EPOCH_COUNT {
+            0
+        } else {
+            best_epoch_number - DEFERRED_STATE_EPOCH_COUNT + 1
+        };
         // state upper bound can be lower than deferred_state_height because
         // the execution is async. It can also be higher
         // because of optimistic execution. Here we guarantee
@@ -1015,9 +1037,13 @@ impl ConsensusGraph {
         Ok(epoch_batch_logs.into_iter().flatten().collect())
     }
 
+    /// Returns the epochs to scan for the logs matching `filter` in reverse
+    /// order. If the log index is enabled, the indexed epochs without a
+    /// possible match are skipped, and they do not count towards
+    /// `get_logs_filter_max_epoch_range`.
     pub fn get_log_filter_epoch_range(
         &self, from_epoch: EpochNumber, to_epoch: EpochNumber,
-        check_range: bool,
+        filter: &LogFilter, check_range: bool,
     ) -> Result<impl Iterator<Item = u64>, FilterError> {
         // lock so that we have a consistent view
         let _inner = self.inner.read_recursive();
@@ -1040,10 +1066,20 @@ impl ConsensusGraph {
             });
         }
 
+        let candidates =
+            self.data_man
+                .log_index_candidates(filter, from_epoch, to_epoch);
+
         if check_range {
             if let Some(max_gap) = self.config.get_logs_filter_max_epoch_range {
                 // The range includes both ends.
-                if to_epoch - from_epoch + 1 > max_gap {
+                let scanned = match candidates {
+                    Some(ref candidates) => {
+                        candidates.scanned_count(from_epoch, to_epoch)
+                    }
+                    None => to_epoch - from_epoch + 1,
+                };
+                if scanned > max_gap {
                     return Err(FilterError::EpochNumberGapTooLarge {
                         from_epoch,
                         to_epoch,
@@ -1053,7 +1089,11 @@ impl ConsensusGraph {
             }
         }
 
-        return Ok((from_epoch..=to_epoch).rev());
+        return Ok((from_epoch..=to_epoch).rev().filter(move |epoch| {
+            candidates
+                .as_ref()
+                .map_or(true, |candidates| candidates.contains(*epoch))
+        }));
     }
 
     pub fn get_trace_filter_epoch_range(
@@ -1095,7 +1135,12 @@ impl ConsensusGraph {
 
         let mut logs = self
             // iterate over epochs in reverse order
-            .get_log_filter_epoch_range(from_epoch, to_epoch, check_range)?
+            .get_log_filter_epoch_range(
+                from_epoch,
+                to_epoch,
+                filter,
+                check_range,
+            )?
             // we process epochs in each batch in parallel
             // but batches are processed one-by-one
             .chunks(self.config.get_logs_epoch_batch_size)
@@ -1435,6 +1480,89 @@ impl ConsensusGraph {
             .call_virtual(tx, &epoch_id, epoch_size, request)
     }
 
+    /// Return the speculative pending block: the transactions the pool
+    /// would pack next, executed on top of the latest executed state.
+    ///
+    /// The block is cached and only rebuilt once the latest executed state,
+    /// the best block or the transaction pool content has changed.
+    pub fn pending_block(&self) -> RpcResult<Arc<PendingBlock>> {
+        let state_epoch_number = self.best_executed_state_epoch_number();
+        let (state_epoch, state_epoch_size) = {
+            let hashes = self.get_block_hashes_by_epoch(EpochNumber::Number(
+                state_epoch_number,
+            ))?;
+            (*hashes.last().expect("main block always exist"), hashes.len())
+        };
+
+        let key = PendingBlockKey {
+            state_epoch,
+            best_block_hash: self.best_block_hash(),
+            pool_version: self.txpool.content_version(),
+        };
+
+        // Holding the lock during execution prevents concurrent requests from
+        // building the same pending block.
+        let mut cache = self.pending_block.lock();
+        if let Some((cached_key, pending_block)) = &*cache {
+            if *cached_key == key {
+                return Ok(pending_block.clone());
+            }
+        }
+
+        let (_, block_gas_limit, transactions, maybe_base_price) =
+            self.txpool.get_best_info_with_packed_transactions(
+                MAX_TRANSACTION_COUNT_PER_BLOCK,
+                MAX_BLOCK_SIZE_IN_BYTES,
+                vec![],
+            );
+
+        // The pending block is executed on the latest executed state, so it
+        // is numbered and timed as the block right after that epoch rather
+        // than after the best block, whose epoch may not be executed yet.
+        let parent_timestamp = self
+            .data_man
+            .block_header_by_hash(&state_epoch)
+            .ok_or("executed epoch header not found")?
+            .timestamp();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let header = BlockHeaderBuilder::new()
+            .with_transactions_root(compute_transaction_root(&transactions))
+            .with_parent_hash(state_epoch)
+            .with_height(state_epoch_number + 1)
+            .with_timestamp(max(parent_timestamp, now))
+            .with_gas_limit(block_gas_limit)
+            .with_base_price(maybe_base_price)
+            .build();
+
+        let pending_block = Arc::new(self.executor.execute_pending_block(
+            &state_epoch,
+            state_epoch_size,
+            Arc::new(Block::new(header, transactions)),
+        )?);
+
+        debug!(
+            "pending block rebuilt: key={:?} tx_count={}",
+            key,
+            pending_block.transactions().len()
+        );
+        *cache = Some((key, pending_block.clone()));
+
+        Ok(pending_block)
+    }
+
+    /// Execute `tx` virtually on top of the pending block.
+    pub fn call_virtual_on_pending_block(
+        &self, tx: &SignedTransaction, request: EstimateRequest,
+    ) -> RpcResult<(ExecutionOutcome, EstimateExt)> {
+        let pending_block = self.pending_block()?;
+        self.executor
+            .call_virtual_on_pending_block(tx, &pending_block, request)
+    }
+
     pub fn collect_epoch_geth_trace(
         &self, epoch_num: u64, tx_hash: Option<H256>,
         opts: GethDebugTracingOptions,
@@ -1458,6 +1586,92 @@ impl ConsensusGraph {
         )
     }
 
+    /// Profile the gas used by the transaction `tx_hash` executed in the
+    /// epoch `epoch_num`.
+    pub fn collect_tx_gas_profile(
+        &self, epoch_num: u64, tx_hash: H256,
+    ) -> RpcResult<Option<GasProfile>> {
+        let epoch = EpochNumber::Number(epoch_num);
+        self.validate_stated_epoch(&epoch)?;
+
+        let epoch_block_hashes = self.get_block_hashes_by_epoch(epoch)?;
+        self.executor
+            .collect_tx_gas_profile(epoch_block_hashes, tx_hash)
+    }
+
+    /// Execute the epoch `epoch_number` on the main chain again without
+    /// persisting anything, and compare the results with the stored ones.
+    pub fn reexecute_epoch(
+        &self, epoch_number: u64,
+    ) -> RpcResult<EpochReexecution> {
+        let (epoch_block_hashes, reward_execution_info) = {
+            let inner = &mut *self.inner.write();
+            // The state before the era genesis is not available.
+            if epoch_number <= inner.get_cur_era_genesis_height()
+                || epoch_number >= inner.get_main_height()
+            {
+                bail!("epoch {} is not in the current era", epoch_number);
+            }
+            let epoch_arena_index =
+                inner.get_main_block_arena_index(epoch_number);
+            (
+                inner.get_epoch_block_hashes(epoch_arena_index),
+                self.executor
+                    .get_reward_execution_info(inner, epoch_arena_index),
+            )
+        };
+        self.executor
+            .reexecute_epoch(&epoch_block_hashes, &reward_execution_info)
+    }
+
+    /// Queue dev state changes, which are applied to the epochs whose main
+    /// block is a child of `parent_hash`.
+    pub fn queue_dev_state_changes(
+        &self, parent_hash: H256, changes: Vec<DevStateChange>,
+    ) {
+        self.executor.queue_dev_state_changes(parent_hash, changes)
+    }
+
+    /// Discard the dev state changes queued for the children of the blocks.
+    pub fn discard_dev_state_changes(&self, parent_hashes: &[H256]) {
+        self.executor.discard_dev_state_changes(parent_hashes)
+    }
+
+    /// Wait until all the epochs currently in the execution queue are
+    /// executed.
+    pub fn wait_for_execution_queue(&self) {
+        self.executor.wait_for_queued_tasks()
+    }
+
+    /// Update the transaction pool after the chain is reverted to the
+    /// executed main block `block_hash` by the dev mode. The transactions of
+    /// the reverted blocks are returned to the pool.
+    pub fn recycle_reverted_transactions(
+        &self, block_hash: &H256, transactions: Vec<Arc<SignedTransaction>>,
+    ) -> Result<(), String> {
+        let commitment = self.executor.wait_for_result(*block_hash)?;
+        self.txpool
+            .set_best_executed_epoch(StateIndex::new_for_readonly(
+                block_hash,
+                &commitment.state_root_with_aux_info,
+            ))
+            .map_err(|e| e.to_string())?;
+        self.txpool.recycle_transactions(transactions);
+        self.txpool
+            .notify_new_best_info(self.best_info.read_recursive().clone())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Execute the main chain up to the main block `block_hash` without
+    /// waiting for it to be deferred. This is used by the dev mode to expose
+    /// the state of a new block immediately.
+    pub fn compute_main_epochs_for_dev(
+        &self, block_hash: &H256,
+    ) -> Result<(), String> {
+        let inner = &mut *self.inner.write();
+        self.executor.compute_main_epochs_for_dev(block_hash, inner)
+    }
+
     /// Get the number of processed blocks (i.e., the number of calls to
     /// on_new_block()
     pub fn get_processed_block_count(&self) -> usize {
@@ -1519,6 +1733,15 @@ impl ConsensusGraph {
         if !state_availability_boundary
             .check_read_availability(height, &hash, space)
         {
+            if height < state_availability_boundary.lower_bound
+                && self.data_man.state_change_set_enabled()
+            {
+                return self.get_historical_state_by_height(
+                    height,
+                    space,
+                    &state_availability_boundary,
+                );
+            }
             debug!(
                 "State for epoch (number={:?} hash={:?}) does not exist: out-of-bound {:?}",
                 height, hash, state_availability_boundary
@@ -1528,6 +1751,12 @@ impl ConsensusGraph {
                 height, hash, state_availability_boundary
             ));
         }
+        self.get_available_state(height, hash, space)
+    }
+
+    fn get_available_state(
+        &self, height: u64, hash: &H256, space: Option<Space>,
+    ) -> RpcResult<Box<dyn StateTrait>> {
         let maybe_state_readonly_index =
             self.data_man.get_state_readonly_index(&hash).into();
         let maybe_state = match maybe_state_readonly_index {
@@ -1556,6 +1785,43 @@ impl ConsensusGraph {
         Ok(state)
     }
 
+    /// Read the state of a main chain epoch before the available states by
+    /// applying the state change sets of the following epochs backwards to
+    /// the earliest available state.
+    fn get_historical_state_by_height(
+        &self, height: u64, space: Option<Space>,
+        state_availability_boundary: &StateAvailabilityBoundary,
+    ) -> RpcResult<Box<dyn StateTrait>> {
+        let lower_bound = state_availability_boundary.lower_bound;
+        let (base_height, base_hash) =
+            historical_state_base(state_availability_boundary)
+                .ok_or("No state is available")?;
+        let max_epoch_count = self.data_man.max_historical_state_epoch_count();
+        if base_height - height > max_epoch_count {
+            bail!(format!(
+                "State for epoch {} is more than {} epochs before the earliest available state at epoch {}",
+                height, max_epoch_count, base_height
+            ));
+        }
+        let mut later_epoch_hashes = Vec::new();
+        for h in height + 1..=base_height {
+            later_epoch_hashes.push(if h >= lower_bound {
+                state_availability_boundary.main_chain
+                    [(h - lower_bound) as usize]
+            } else {
+                self.data_man
+                    .executed_epoch_set_hashes_from_db(h)
+                    .and_then(|hashes| hashes.last().cloned())
+                    .ok_or(format!("Main block of epoch {} is missing", h))?
+            });
+        }
+        let base_state =
+            self.get_available_state(base_height, &base_hash, space)?;
+        Ok(self
+            .data_man
+            .historical_state(base_state, &later_epoch_hashes)?)
+    }
+
     /// This function is called after a new block appended to the
     /// ConsensusGraph. Because BestInformation is often queried outside. We
     /// store a version of best_info outside the inner to prevent keep
@@ -0,0 +1,299 @@
+use super::PhantomBlock;
+use mazze_execute_helper::phantom_tx::build_bloom_and_recover_phantom;
+use mazze_executor::state::{State, StateSeed};
+use mazze_statedb::Result as DbResult;
+use mazze_types::{AddressWithSpace, Bloom, Space, H256, U256};
+use primitives::{
+    filter::LogFilterParams, log_entry::LocalizedLogEntry,
+    receipt::BlockReceipts, Block, BlockHeader, SignedTransaction,
+    TransactionStatus,
+};
+use std::sync::Arc;
+
+/// Identifies the inputs a pending block was built from. A cached pending
+/// block is reused as long as none of them changes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct PendingBlockKey {
+    /// The executed epoch the pending transactions are applied on.
+    pub state_epoch: H256,
+    /// The best block the pending block is mined on.
+    pub best_block_hash: H256,
+    /// `TransactionPool::content_version` at packing time.
+    pub pool_version: u64,
+}
+
+/// A speculative block made of the transactions the pool would pack next,
+/// executed on top of the latest executed state.
+///
+/// The pending block is never committed. Its header only carries the fields
+/// relevant to execution (parent, height, timestamp, gas limit and base
+/// price); the deferred roots and blame are not meaningful.
+pub struct PendingBlock {
+    /// The synthetic block.
+    pub block: Arc<Block>,
+    /// The block number used when executing `block`.
+    pub block_number: u64,
+    /// The executed epoch whose state the pending block is applied on.
+    pub state_epoch: H256,
+    /// The receipts of `block.transactions`.
+    pub receipts: Arc<BlockReceipts>,
+    evm_chain_id: u32,
+    /// The uncommitted state after executing `block`. It is only read, so
+    /// queries do not serialize on it.
+    state: State,
+    /// The uncommitted changes of `state`, from which virtual calls build
+    /// their own state.
+    seed: StateSeed,
+}
+
+impl PendingBlock {
+    pub fn new(
+        block: Arc<Block>, block_number: u64, state_epoch: H256,
+        receipts: Arc<BlockReceipts>, evm_chain_id: u32, state: State,
+    ) -> Self {
+        let seed = state.make_seed();
+        PendingBlock {
+            block,
+            block_number,
+            state_epoch,
+            receipts,
+            evm_chain_id,
+            state,
+            seed,
+        }
+    }
+
+    pub fn header(&self) -> &BlockHeader { &self.block.block_header }
+
+    pub fn hash(&self) -> H256 { self.block.hash() }
+
+    pub fn transactions(&self) -> &Vec<Arc<SignedTransaction>> {
+        &self.block.transactions
+    }
+
+    /// The height of the executed epoch the pending block is applied on.
+    pub fn state_epoch_height(&self) -> u64 { self.header().height() - 1 }
+
+    pub(crate) fn seed(&self) -> &StateSeed { &self.seed }
+
+    pub fn balance(&self, address: &AddressWithSpace) -> DbResult<U256> {
+        self.state.balance(address)
+    }
+
+    pub fn nonce(&self, address: &AddressWithSpace) -> DbResult<U256> {
+        self.state.nonce(address)
+    }
+
+    pub fn code(
+        &self, address: &AddressWithSpace,
+    ) -> DbResult<Option<Arc<Vec<u8>>>> {
+        self.state.code(address)
+    }
+
+    pub fn storage_at(
+        &self, address: &AddressWithSpace, key: &[u8],
+    ) -> DbResult<U256> {
+        self.state.storage_at(address, key)
+    }
+
+    /// Build the eth space view of the pending block, following the same
+    /// rules as `ConsensusGraph::get_phantom_block_by_number`.
+    pub fn phantom_block(&self) -> PhantomBlock {
+        let mut phantom_block = PhantomBlock {
+            main_header: self.header().clone(),
+            transactions: vec![],
+            receipts: vec![],
+            errors: vec![],
+            bloom: Bloom::zero(),
+            traces: vec![],
+        };
+
+        let receipts = &self.receipts.receipts;
+        let errors = &self.receipts.tx_execution_error_messages;
+        let mut accumulated_gas_used = U256::zero();
+
+        for (id, tx) in self.transactions().iter().enumerate() {
+            let receipt = &receipts[id];
+
+            match tx.space() {
+                Space::Ethereum => {
+                    // we do not return non-executed transaction
+                    if receipt.outcome_status == TransactionStatus::Skipped {
+                        continue;
+                    }
+
+                    accumulated_gas_used = receipt.accumulated_gas_used;
+
+                    phantom_block.transactions.push(tx.clone());
+                    phantom_block.receipts.push(receipt.clone());
+                    phantom_block.errors.push(errors[id].clone());
+                    phantom_block.bloom.accrue_bloom(&receipt.log_bloom);
+                }
+                Space::Native => {
+                    if receipt.outcome_status != TransactionStatus::Success {
+                        continue;
+                    }
+
+                    let (phantom_txs, _) = build_bloom_and_recover_phantom(
+                        &receipt.logs[..],
+                        tx.hash(),
+                    );
+
+                    for p in phantom_txs {
+                        phantom_block.transactions.push(Arc::new(
+                            p.clone().into_eip155(self.evm_chain_id),
+                        ));
+
+                        // note: phantom txs consume no gas
+                        let phantom_receipt =
+                            p.into_receipt(accumulated_gas_used);
+
+                        phantom_block
+                            .bloom
+                            .accrue_bloom(&phantom_receipt.log_bloom);
+                        phantom_block.receipts.push(phantom_receipt);
+                        phantom_block.errors.push("".into());
+                    }
+                }
+            }
+        }
+
+        phantom_block
+    }
+
+    /// Return the logs of the pending block matching `params`. Eth space logs
+    /// are indexed as in the phantom block.
+    pub fn logs(&self, params: &LogFilterParams) -> Vec<LocalizedLogEntry> {
+        let (tx_hashes, receipts): (Vec<_>, Vec<_>) = match params.space {
+            Space::Native => (
+                self.transactions().iter().map(|tx| tx.hash()).collect(),
+                self.receipts.receipts.clone(),
+            ),
+            Space::Ethereum => {
+                let pb = self.phantom_block();
+                (
+                    pb.transactions.iter().map(|tx| tx.hash()).collect(),
+                    pb.receipts,
+                )
+            }
+        };
+
+        let block_hash = self.hash();
+        let epoch_number = self.header().height();
+        let mut log_index = 0;
+        let mut logs = vec![];
+
+        for (transaction_index, (receipt, transaction_hash)) in
+            receipts.into_iter().zip(tx_hashes).enumerate()
+        {
+            let entries = receipt
+                .logs
+                .into_iter()
+                .filter(|l| l.space == params.space)
+                .enumerate();
+
+            for (transaction_log_index, entry) in entries {
+                if params.matches(&entry) {
+                    logs.push(LocalizedLogEntry {
+                        entry,
+                        block_hash,
+                        epoch_number,
+                        transaction_hash,
+                        transaction_index,
+                        log_index,
+                        transaction_log_index,
+                    });
+                }
+                log_index += 1;
+            }
+        }
+
+        logs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        block_data_manager::DbType,
+        sync::utils::{initialize_synchronization_graph, remove_db_dir},
+    };
+    use mazze_types::{AddressSpaceUtil, U256};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_pending_block_on_executed_state() {
+        const DB_DIR: &str = "./test_pending_block.db/";
+        {
+            let (_, consensus, _, genesis_block) =
+                initialize_synchronization_graph(
+                    DB_DIR,
+                    1,
+                    1,
+                    1,
+                    1,
+                    50000,
+                    DbType::Rocksdb,
+                );
+            let genesis_hash = genesis_block.hash();
+
+            let pending_block = consensus.pending_block().unwrap();
+            assert_eq!(pending_block.state_epoch, genesis_hash);
+            assert_eq!(*pending_block.header().parent_hash(), genesis_hash);
+            assert_eq!(pending_block.header().height(), 1);
+            assert_eq!(pending_block.state_epoch_height(), 0);
+            assert!(pending_block.transactions().is_empty());
+
+            // Nothing changed, the cached pending block is reused.
+            let cached = consensus.pending_block().unwrap();
+            assert!(Arc::ptr_eq(&pending_block, &cached));
+
+            // Executed accounts change the readiness of pool transactions.
+            let version = consensus.txpool.content_version();
+            consensus.txpool.notify_modified_accounts(vec![]);
+            assert!(consensus.txpool.content_version() > version);
+            let rebuilt = consensus.pending_block().unwrap();
+            assert!(!Arc::ptr_eq(&pending_block, &rebuilt));
+
+            let version = consensus.txpool.content_version();
+            consensus.txpool.clear_tx_pool();
+            assert!(consensus.txpool.content_version() > version);
+        }
+
+        remove_db_dir(DB_DIR);
+    }
+
+    #[test]
+    fn test_pending_block_concurrent_reads() {
+        const DB_DIR: &str = "./test_pending_block_concurrent.db/";
+        {
+            let (_, consensus, _, _) = initialize_synchronization_graph(
+                DB_DIR,
+                1,
+                1,
+                1,
+                1,
+                50000,
+                DbType::Rocksdb,
+            );
+            let pending_block = consensus.pending_block().unwrap();
+            let author = pending_block.header().author().with_native_space();
+
+            // The pending state is read without locking from several
+            // threads at the same time.
+            let handles: Vec<_> = (0..4)
+                .map(|_| {
+                    let pending_block = pending_block.clone();
+                    std::thread::spawn(move || {
+                        pending_block.nonce(&author).unwrap()
+                    })
+                })
+                .collect();
+            for handle in handles {
+                assert_eq!(handle.join().unwrap(), U256::zero());
+            }
+        }
+
+        remove_db_dir(DB_DIR);
+    }
+}
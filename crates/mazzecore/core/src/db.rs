@@ -41,8 +41,18 @@ pub const COL_BLOCK_TRACES: u32 = 5;
 pub const COL_HASH_BY_BLOCK_NUMBER: u32 = 6;
 /// Column for PoS interest reward info.
 pub const COL_REWARD_BY_POS_EPOCH: u32 = 7;
+/// Column for the log index
+pub const COL_LOG_INDEX: u32 = 8;
+/// Column for the address transaction index
+pub const COL_ADDRESS_TX_INDEX: u32 = 9;
+/// Column for the offsets of the block data moved to the freezer
+pub const COL_FREEZER_INDEX: u32 = 10;
+/// Column for the state statistics of snapshots
+pub const COL_STATE_STATS: u32 = 11;
+/// Column for the per-epoch change sets of the state
+pub const COL_STATE_CHANGE_SET: u32 = 12;
 /// Number of columns in DB
-pub const NUM_COLUMNS: u32 = 8;
+pub const NUM_COLUMNS: u32 = 13;
 
 /// Modes for updating caches.
 #[derive(Clone, Copy)]
@@ -14,6 +14,7 @@ use diem_crypto::{
     bls::BLSPrivateKey, ec_vrf::EcVrfPublicKey, PrivateKey, ValidCryptoMaterial,
 };
 use diem_types::validator_config::{ConsensusPublicKey, ConsensusVRFPublicKey};
+use keccak_hash::keccak;
 use keylib::KeyPair;
 use mazze_executor::internal_contract::initialize_internal_contract_accounts;
 use mazze_internal_common::debug::ComputeEpochDebugRecord;
@@ -25,18 +26,23 @@ use mazze_parameters::{
     genesis::*,
     staking::POS_VOTE_PRICE,
 };
-use mazze_statedb::StateDb;
+use mazze_statedb::{StateDb, StateDbExt};
 use mazze_storage::{StorageManager, StorageManagerTrait};
 use mazze_types::{
     address_util::AddressUtil, Address, AddressSpaceUtil, AddressWithSpace,
     Space, H256, U256,
 };
 use primitives::{
-    Action, Block, BlockHeaderBuilder, BlockReceipts, SignedTransaction,
+    storage::STORAGE_LAYOUT_REGULAR_V0, Account, Action, Block,
+    BlockHeaderBuilder, BlockReceipts, CodeInfo, DepositList,
+    SignedTransaction, StorageKey, StorageValue, VoteStakeList,
 };
 use secret_store::SecretStore;
 
-use crate::verification::{compute_receipts_root, compute_transaction_root};
+use crate::{
+    state_dump::DumpAccount,
+    verification::{compute_receipts_root, compute_transaction_root},
+};
 use diem_types::account_address::AccountAddress;
 use mazze_executor::{
     executive::{
@@ -404,6 +410,137 @@ pub fn genesis_block(
     genesis
 }
 
+/// Build the genesis block of a new chain whose state is the state dump
+/// `accounts`, e.g. to fork a testnet from the state of an existing chain.
+/// Unlike `genesis_block`, the internal contracts, the genesis accounts and
+/// the genesis contracts are not initialized, since the dump already has
+/// them along with the global statistics kept in the storage of the internal
+/// contracts, so the genesis block has no transactions. The accounts are
+/// written to the state one at a time as they are read from the dump.
+pub fn genesis_block_from_dump(
+    storage_manager: &Arc<StorageManager>,
+    accounts: impl IntoIterator<Item = Result<DumpAccount, String>>,
+    test_net_version: Address, initial_difficulty: U256,
+) -> Result<Block, String> {
+    let mut state_db =
+        StateDb::new(storage_manager.get_state_for_genesis_write());
+    let mut count = 0;
+    for account in accounts {
+        write_dump_account(&mut state_db, &account?)?;
+        count += 1;
+    }
+    info!("Genesis state loaded from a dump of {} accounts", count);
+
+    let mut genesis_block_author = test_net_version;
+    genesis_block_author.set_user_account_type_bits();
+
+    let state_root = state_db
+        .compute_state_root(/* debug_record = */ None)
+        .map_err(|e| format!("failed to compute the state root: {}", e))?;
+    let receipt_root = compute_receipts_root(&vec![Arc::new(BlockReceipts {
+        receipts: vec![],
+        block_number: 0,
+        secondary_reward: U256::zero(),
+        tx_execution_error_messages: vec![],
+    })]);
+
+    let mut genesis = Block::new(
+        BlockHeaderBuilder::new()
+            .with_deferred_state_root(state_root.aux_info.state_root_hash)
+            .with_deferred_receipts_root(receipt_root)
+            .with_gas_limit(GENESIS_GAS_LIMIT.into())
+            .with_author(genesis_block_author)
+            .with_difficulty(initial_difficulty)
+            .with_transactions_root(compute_transaction_root(&vec![]))
+            .build(),
+        vec![],
+    );
+    genesis.block_header.compute_hash();
+    state_db
+        .commit(genesis.block_header.hash(), /* debug_record = */ None)
+        .map_err(|e| format!("failed to commit the genesis state: {}", e))?;
+    genesis.block_header.pow_hash = Some(Default::default());
+    Ok(genesis)
+}
+
+/// Write the entries of a dumped account to the state. Contracts get the
+/// regular storage layout, which is the only layout so far.
+fn write_dump_account(
+    state_db: &mut StateDb, dumped: &DumpAccount,
+) -> Result<(), String> {
+    let address = dumped.address_with_space();
+    let account = dumped.to_account()?;
+    let db_error = |e| format!("failed to write account {:?}: {}", address, e);
+
+    if let Some(code) = &dumped.code {
+        if keccak(&code.code) != dumped.code_hash {
+            bail!("the code of {:?} doesn't match its code hash", address);
+        }
+        let code_info = CodeInfo {
+            code: Arc::new(code.code.clone()),
+            owner: code.owner,
+        };
+        state_db
+            .set::<CodeInfo>(
+                StorageKey::new_code_key(&address.address, &dumped.code_hash)
+                    .with_space(address.space),
+                &code_info,
+                None,
+            )
+            .map_err(db_error)?;
+    }
+    let is_native_contract = address.space == Space::Native
+        && (address.address.is_builtin_address()
+            || address.address.is_contract_address());
+    if dumped.code.is_some() || !dumped.storage.is_empty() || is_native_contract
+    {
+        state_db
+            .set_storage_layout(&address, STORAGE_LAYOUT_REGULAR_V0, None)
+            .map_err(db_error)?;
+    }
+    for entry in &dumped.storage {
+        let value = StorageValue {
+            value: entry.value,
+            owner: entry.owner,
+        };
+        state_db
+            .set::<StorageValue>(
+                StorageKey::new_storage_key(&address.address, &entry.key)
+                    .with_space(address.space),
+                &value,
+                None,
+            )
+            .map_err(db_error)?;
+    }
+    if address.space == Space::Native {
+        state_db
+            .set::<DepositList>(
+                StorageKey::new_deposit_list_key(&address.address)
+                    .with_native_space(),
+                &DepositList(dumped.deposit_list.clone()),
+                None,
+            )
+            .map_err(db_error)?;
+        state_db
+            .set::<VoteStakeList>(
+                StorageKey::new_vote_list_key(&address.address)
+                    .with_native_space(),
+                &VoteStakeList(dumped.vote_stake_list.clone()),
+                None,
+            )
+            .map_err(db_error)?;
+    }
+    state_db
+        .set::<Account>(
+            StorageKey::new_account_key(&address.address)
+                .with_space(address.space),
+            &account,
+            None,
+        )
+        .map_err(db_error)?;
+    Ok(())
+}
+
 pub fn register_transaction(
     bls_priv_key: BLSPrivateKey, vrf_pub_key: EcVrfPublicKey, power: u64,
     genesis_chain_id: u32, legacy: bool,
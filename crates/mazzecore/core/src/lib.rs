@@ -37,6 +37,7 @@ pub mod node_type;
 pub mod pos;
 pub mod pow;
 pub mod rpc_errors;
+pub mod state_dump;
 pub mod state_exposer;
 mod state_prefetcher;
 pub mod statistics;
@@ -0,0 +1,584 @@
+//! Dump of the complete state at an epoch as NDJSON, one account per line,
+//! and the reader of such dumps to start a new chain from them with
+//! `genesis_block::genesis_block_from_dump`.
+
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::{BufRead, BufReader, Write},
+};
+
+use keccak_hash::KECCAK_EMPTY;
+use mazze_storage::StorageStateTrait;
+use mazze_types::{
+    Address, AddressSpaceUtil, AddressWithSpace, Space, H256, U256,
+};
+use primitives::{
+    account::StoragePoints, Account, CodeInfo, DepositInfo, DepositList,
+    SkipInputCheck, SponsorInfo, StorageKey, StorageKeyWithSpace, StorageValue,
+    VoteStakeInfo, VoteStakeList,
+};
+use rlp::Rlp;
+use serde::{
+    ser::{Error as _, SerializeSeq},
+    Deserialize, Serialize, Serializer,
+};
+
+/// The number of storage entries read from the storage at a time.
+const STORAGE_PAGE_SIZE: usize = 1000;
+
+/// An account in a state dump. The staking, collateral and sponsor fields
+/// only exist in the native space, and are omitted when they are zero.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DumpAccount {
+    pub address: Address,
+    pub space: Space,
+    pub balance: U256,
+    pub nonce: U256,
+    pub code_hash: H256,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<DumpCode>,
+    /// The storage entries in the order of the keys.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub storage: Vec<DumpStorageEntry>,
+    #[serde(default, skip_serializing_if = "U256::is_zero")]
+    pub staking_balance: U256,
+    #[serde(default, skip_serializing_if = "U256::is_zero")]
+    pub collateral_for_storage: U256,
+    #[serde(default, skip_serializing_if = "U256::is_zero")]
+    pub accumulated_interest_return: U256,
+    #[serde(default, skip_serializing_if = "Address::is_zero")]
+    pub admin: Address,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sponsor_info: Option<DumpSponsorInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deposit_list: Vec<DepositInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vote_stake_list: Vec<VoteStakeInfo>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DumpCode {
+    #[serde(with = "hex_bytes")]
+    pub code: Vec<u8>,
+    /// The account which pays the collateral for the code.
+    pub owner: Address,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DumpStorageEntry {
+    #[serde(with = "hex_bytes")]
+    pub key: Vec<u8>,
+    pub value: U256,
+    /// The account which pays the collateral for the entry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<Address>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DumpSponsorInfo {
+    pub sponsor_for_gas: Address,
+    pub sponsor_for_collateral: Address,
+    pub sponsor_gas_bound: U256,
+    pub sponsor_balance_for_gas: U256,
+    pub sponsor_balance_for_collateral: U256,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unused_storage_points: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub used_storage_points: Option<U256>,
+}
+
+impl From<SponsorInfo> for DumpSponsorInfo {
+    fn from(info: SponsorInfo) -> Self {
+        Self {
+            sponsor_for_gas: info.sponsor_for_gas,
+            sponsor_for_collateral: info.sponsor_for_collateral,
+            sponsor_gas_bound: info.sponsor_gas_bound,
+            sponsor_balance_for_gas: info.sponsor_balance_for_gas,
+            sponsor_balance_for_collateral: info.sponsor_balance_for_collateral,
+            unused_storage_points: info
+                .storage_points
+                .as_ref()
+                .map(|p| p.unused),
+            used_storage_points: info.storage_points.as_ref().map(|p| p.used),
+        }
+    }
+}
+
+impl From<DumpSponsorInfo> for SponsorInfo {
+    fn from(info: DumpSponsorInfo) -> Self {
+        let storage_points =
+            match (info.unused_storage_points, info.used_storage_points) {
+                (None, None) => None,
+                (unused, used) => Some(StoragePoints {
+                    unused: unused.unwrap_or_default(),
+                    used: used.unwrap_or_default(),
+                }),
+            };
+        Self {
+            sponsor_for_gas: info.sponsor_for_gas,
+            sponsor_for_collateral: info.sponsor_for_collateral,
+            sponsor_gas_bound: info.sponsor_gas_bound,
+            sponsor_balance_for_gas: info.sponsor_balance_for_gas,
+            sponsor_balance_for_collateral: info.sponsor_balance_for_collateral,
+            storage_points,
+        }
+    }
+}
+
+impl DumpAccount {
+    pub fn address_with_space(&self) -> AddressWithSpace {
+        self.address.with_space(self.space)
+    }
+
+    /// The account entry to write to the state, or an error if an account
+    /// in the eSpace has any field that only exists in the native space.
+    pub fn to_account(&self) -> Result<Account, String> {
+        let sponsor_info: SponsorInfo = self
+            .sponsor_info
+            .clone()
+            .map(Into::into)
+            .unwrap_or_default();
+        if self.space == Space::Ethereum
+            && (!self.staking_balance.is_zero()
+                || !self.collateral_for_storage.is_zero()
+                || !self.accumulated_interest_return.is_zero()
+                || !self.admin.is_zero()
+                || sponsor_info != SponsorInfo::default()
+                || !self.deposit_list.is_empty()
+                || !self.vote_stake_list.is_empty())
+        {
+            bail!(
+                "eSpace account {:?} has staking, collateral or sponsor fields",
+                self.address
+            );
+        }
+        let mut account = Account::new_empty_with_balance(
+            &self.address_with_space(),
+            &self.balance,
+            &self.nonce,
+        );
+        account.code_hash = self.code_hash;
+        account.staking_balance = self.staking_balance;
+        account.collateral_for_storage = self.collateral_for_storage;
+        account.accumulated_interest_return = self.accumulated_interest_return;
+        account.admin = self.admin;
+        account.sponsor_info = sponsor_info;
+        Ok(account)
+    }
+}
+
+/// Write every account of `state` in both spaces to `writer`, one JSON
+/// object per line in the order of the spaces and the addresses. The
+/// accounts are iterated from `state` in a single pass, while their code and
+/// storage are read from `reader`, another view of the same state. The
+/// storage is read page by page, so the memory used doesn't grow with the
+/// size of the state. Returns the number of accounts written.
+pub fn dump_state(
+    state: &mut dyn StorageStateTrait, reader: &mut dyn StorageStateTrait,
+    writer: &mut dyn Write,
+) -> Result<usize, String> {
+    let mut count = 0;
+    for space in [Space::Native, Space::Ethereum] {
+        state
+            .for_each_account(space, &mut |(key, value)| {
+                let address = address_of_key(&key).with_space(space);
+                write_account(reader, &address, &value, writer)?;
+                count += 1;
+                Ok(())
+            })
+            .map_err(|e| format!("failed to dump accounts: {}", e))?;
+    }
+    writer
+        .flush()
+        .map_err(|e| format!("failed to write: {}", e))?;
+    Ok(count)
+}
+
+/// Write an account of the state as a line of the dump.
+fn write_account(
+    state: &mut dyn StorageStateTrait, address: &AddressWithSpace,
+    raw_account: &[u8], writer: &mut dyn Write,
+) -> Result<(), String> {
+    let account = dump_account(state, address, raw_account)?;
+    let (first_page, next_key) = read_storage_page(state, address, &[])?;
+    let line = DumpAccountWithStorage {
+        account: &account,
+        storage: PagedStorage {
+            state: RefCell::new(state),
+            address,
+            first_page,
+            next_key,
+        },
+    };
+    serde_json::to_writer(&mut *writer, &line)
+        .map_err(|e| format!("failed to write account {:?}: {}", address, e))?;
+    writer
+        .write_all(b"\n")
+        .map_err(|e| format!("failed to write: {}", e))
+}
+
+/// A dumped account whose storage is read from the state while it's
+/// serialized, so that the storage of a contract isn't held in memory.
+#[derive(Serialize)]
+struct DumpAccountWithStorage<'a> {
+    /// The account without the storage.
+    #[serde(flatten)]
+    account: &'a DumpAccount,
+    #[serde(skip_serializing_if = "PagedStorage::is_empty")]
+    storage: PagedStorage<'a>,
+}
+
+struct PagedStorage<'a> {
+    state: RefCell<&'a mut dyn StorageStateTrait>,
+    address: &'a AddressWithSpace,
+    first_page: Vec<DumpStorageEntry>,
+    /// The storage key of the next page, if any.
+    next_key: Option<Vec<u8>>,
+}
+
+impl PagedStorage<'_> {
+    fn is_empty(&self) -> bool { self.first_page.is_empty() }
+}
+
+impl Serialize for PagedStorage<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        let mut seq = serializer.serialize_seq(None)?;
+        for entry in &self.first_page {
+            seq.serialize_element(entry)?;
+        }
+        let mut next_key = self.next_key.clone();
+        while let Some(start_key) = next_key.take() {
+            let (page, key) = read_storage_page(
+                &mut **self.state.borrow_mut(),
+                self.address,
+                &start_key,
+            )
+            .map_err(S::Error::custom)?;
+            for entry in &page {
+                seq.serialize_element(entry)?;
+            }
+            next_key = key;
+        }
+        seq.end()
+    }
+}
+
+/// Read at most a page of the storage of `address` from `start_key`, and the
+/// storage key of the next page if there are more entries.
+fn read_storage_page(
+    state: &mut dyn StorageStateTrait, address: &AddressWithSpace,
+    start_key: &[u8],
+) -> Result<(Vec<DumpStorageEntry>, Option<Vec<u8>>), String> {
+    let mut kvs = state
+        .read_storage_range(
+            address.space,
+            address.address.as_bytes(),
+            start_key,
+            STORAGE_PAGE_SIZE + 1,
+        )
+        .map_err(|e| {
+            format!("failed to read the storage of {:?}: {}", address, e)
+        })?;
+    let next_key = if kvs.len() > STORAGE_PAGE_SIZE {
+        kvs.pop().map(|(key, _)| storage_key_of(&key).to_vec())
+    } else {
+        None
+    };
+    let mut storage = Vec::with_capacity(kvs.len());
+    for (key, value) in kvs {
+        let value: StorageValue = rlp::decode(&value)
+            .map_err(|e| format!("invalid storage of {:?}: {}", address, e))?;
+        storage.push(DumpStorageEntry {
+            key: storage_key_of(&key).to_vec(),
+            value: value.value,
+            owner: value.owner,
+        });
+    }
+    Ok((storage, next_key))
+}
+
+fn storage_key_of(key: &[u8]) -> &[u8] {
+    match StorageKeyWithSpace::from_key_bytes::<SkipInputCheck>(key).key {
+        StorageKey::StorageKey { storage_key, .. } => storage_key,
+        _ => unreachable!("read_storage_range only returns storage keys"),
+    }
+}
+
+fn address_of_key(key: &[u8]) -> Address {
+    match StorageKeyWithSpace::from_key_bytes::<SkipInputCheck>(key).key {
+        StorageKey::AccountKey(address_bytes) => {
+            Address::from_slice(address_bytes)
+        }
+        _ => unreachable!("for_each_account only returns account keys"),
+    }
+}
+
+fn dump_account(
+    state: &mut dyn StorageStateTrait, address: &AddressWithSpace,
+    raw_account: &[u8],
+) -> Result<DumpAccount, String> {
+    let db_error = |e| format!("failed to read account {:?}: {}", address, e);
+    let account =
+        Account::new_from_rlp(address.address, &Rlp::new(raw_account))
+            .map_err(|e| format!("invalid account {:?}: {}", address, e))?;
+
+    let code = if account.code_hash == KECCAK_EMPTY
+        || account.code_hash.is_zero()
+    {
+        None
+    } else {
+        let code_key =
+            StorageKey::new_code_key(&address.address, &account.code_hash)
+                .with_space(address.space);
+        match state.get(code_key).map_err(db_error)? {
+            Some(raw) => {
+                let code_info: CodeInfo = rlp::decode(&raw).map_err(|e| {
+                    format!("invalid code of {:?}: {}", address, e)
+                })?;
+                Some(DumpCode {
+                    code: code_info.code.to_vec(),
+                    owner: code_info.owner,
+                })
+            }
+            None => None,
+        }
+    };
+
+    let (mut deposit_list, mut vote_stake_list) = (vec![], vec![]);
+    if address.space == Space::Native {
+        let deposit_key = StorageKey::new_deposit_list_key(&address.address)
+            .with_native_space();
+        if let Some(raw) = state.get(deposit_key).map_err(db_error)? {
+            let list: DepositList = rlp::decode(&raw).map_err(|e| {
+                format!("invalid deposit list of {:?}: {}", address, e)
+            })?;
+            deposit_list = list.0;
+        }
+        let vote_key =
+            StorageKey::new_vote_list_key(&address.address).with_native_space();
+        if let Some(raw) = state.get(vote_key).map_err(db_error)? {
+            let list: VoteStakeList = rlp::decode(&raw).map_err(|e| {
+                format!("invalid vote list of {:?}: {}", address, e)
+            })?;
+            vote_stake_list = list.0;
+        }
+    }
+
+    Ok(DumpAccount {
+        address: address.address,
+        space: address.space,
+        balance: account.balance,
+        nonce: account.nonce,
+        code_hash: account.code_hash,
+        code,
+        storage: vec![],
+        staking_balance: account.staking_balance,
+        collateral_for_storage: account.collateral_for_storage,
+        accumulated_interest_return: account.accumulated_interest_return,
+        admin: account.admin,
+        sponsor_info: if account.sponsor_info == SponsorInfo::default() {
+            None
+        } else {
+            Some(account.sponsor_info.into())
+        },
+        deposit_list,
+        vote_stake_list,
+    })
+}
+
+/// Open a state dump file written by `dump_state`, whose accounts are read
+/// one at a time by the returned iterator.
+pub fn load_state_dump_file(
+    path: &str,
+) -> Result<impl Iterator<Item = Result<DumpAccount, String>>, String> {
+    let file = File::open(path)
+        .map_err(|e| format!("failed to open file: {:?}", e))?;
+    Ok(read_state_dump(BufReader::new(file)))
+}
+
+/// Read the accounts of a state dump one line at a time. Empty lines are
+/// skipped.
+pub fn read_state_dump(
+    reader: impl BufRead,
+) -> impl Iterator<Item = Result<DumpAccount, String>> {
+    reader
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            line.as_ref().map_or(true, |line| !line.trim().is_empty())
+        })
+        .map(|(index, line)| {
+            let line =
+                line.map_err(|e| format!("failed to read file: {:?}", e))?;
+            serde_json::from_str(&line).map_err(|e| {
+                format!("failed to parse account at line {}: {}", index + 1, e)
+            })
+        })
+}
+
+mod hex_bytes {
+    use rustc_hex::{FromHex, ToHex};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        serializer.serialize_str(&format!("0x{}", bytes.to_hex::<String>()))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where D: Deserializer<'de> {
+        let value = String::deserialize(deserializer)?;
+        let hex = value.strip_prefix("0x").unwrap_or(&value);
+        hex.from_hex()
+            .map_err(|e| D::Error::custom(format!("invalid hex: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genesis_block::genesis_block_from_dump;
+    use keccak_hash::keccak;
+    use mazze_internal_common::StateRootWithAuxInfo;
+    use mazze_storage::{
+        new_storage_manager_for_testing, StateIndex, StorageManagerTrait,
+    };
+    use mazze_types::address_util::AddressUtil;
+
+    #[test]
+    fn test_dump_account_json() {
+        let account = DumpAccount {
+            address: Address::from_low_u64_be(1),
+            space: Space::Native,
+            balance: 10.into(),
+            nonce: 1.into(),
+            code_hash: H256::from_low_u64_be(2),
+            code: Some(DumpCode {
+                code: vec![0x60, 0x00],
+                owner: Address::from_low_u64_be(3),
+            }),
+            storage: vec![DumpStorageEntry {
+                key: b"interest_rate".to_vec(),
+                value: 4.into(),
+                owner: None,
+            }],
+            staking_balance: 5.into(),
+            collateral_for_storage: 0.into(),
+            accumulated_interest_return: 0.into(),
+            admin: Address::zero(),
+            sponsor_info: Some(SponsorInfo::default().into()),
+            deposit_list: vec![],
+            vote_stake_list: vec![],
+        };
+        let json = serde_json::to_string(&account).unwrap();
+        assert!(json.contains("\"space\":\"native\""));
+        assert!(json.contains("\"code\":\"0x6000\""));
+        assert!(!json.contains("collateralForStorage"));
+        assert!(!json.contains("depositList"));
+        assert_eq!(
+            serde_json::from_str::<DumpAccount>(&json).unwrap(),
+            account
+        );
+
+        let mut evm_account = account;
+        evm_account.space = Space::Ethereum;
+        assert!(evm_account.to_account().is_err());
+        evm_account.staking_balance = 0.into();
+        evm_account.sponsor_info = None;
+        assert_eq!(evm_account.to_account().unwrap().balance, 10.into());
+    }
+
+    #[test]
+    fn test_dump_state_round_trip() {
+        let storage_manager = new_storage_manager_for_testing();
+        let mut contract = Address::from_low_u64_be(1);
+        contract.set_contract_type_bits();
+        let mut user = Address::from_low_u64_be(2);
+        user.set_user_account_type_bits();
+        let code = vec![0x60, 0x00];
+        let account = |address: Address, space| DumpAccount {
+            address,
+            space,
+            balance: 10.into(),
+            nonce: 1.into(),
+            code_hash: KECCAK_EMPTY,
+            code: None,
+            storage: vec![],
+            staking_balance: 0.into(),
+            collateral_for_storage: 0.into(),
+            accumulated_interest_return: 0.into(),
+            admin: Address::zero(),
+            sponsor_info: None,
+            deposit_list: vec![],
+            vote_stake_list: vec![],
+        };
+        // The storage of the contract takes more than one page.
+        let mut contract_account = account(contract, Space::Native);
+        contract_account.code_hash = keccak(&code);
+        contract_account.code = Some(DumpCode { code, owner: user });
+        contract_account.storage = (0..STORAGE_PAGE_SIZE as u64 + 10)
+            .map(|i| DumpStorageEntry {
+                key: H256::from_low_u64_be(i).as_bytes().to_vec(),
+                value: (i + 1).into(),
+                owner: Some(user),
+            })
+            .collect();
+        let mut native_user = account(user, Space::Native);
+        native_user.staking_balance = 5.into();
+        // The accounts in the order of the dump.
+        let accounts = vec![
+            contract_account,
+            native_user,
+            account(Address::from_low_u64_be(3), Space::Ethereum),
+        ];
+
+        let genesis = genesis_block_from_dump(
+            &storage_manager,
+            accounts.clone().into_iter().map(Ok),
+            Address::zero(),
+            U256::zero(),
+        )
+        .unwrap();
+        let open_state = || {
+            storage_manager
+                .get_state_no_commit(
+                    StateIndex::new_for_readonly(
+                        &genesis.hash(),
+                        &StateRootWithAuxInfo::genesis(&genesis.hash()),
+                    ),
+                    /* try_open = */ false,
+                    None,
+                )
+                .unwrap()
+                .unwrap()
+        };
+        let mut dump = vec![];
+        assert_eq!(
+            dump_state(&mut *open_state(), &mut *open_state(), &mut dump)
+                .unwrap(),
+            accounts.len()
+        );
+        let dumped_accounts = read_state_dump(dump.as_slice())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(dumped_accounts, accounts);
+
+        // A new chain from the dump has the same state.
+        let new_genesis = genesis_block_from_dump(
+            &storage_manager,
+            dumped_accounts.into_iter().map(Ok),
+            Address::zero(),
+            U256::one(),
+        )
+        .unwrap();
+        assert_ne!(new_genesis.hash(), genesis.hash());
+        assert_eq!(
+            new_genesis.block_header.deferred_state_root(),
+            genesis.block_header.deferred_state_root()
+        );
+    }
+}
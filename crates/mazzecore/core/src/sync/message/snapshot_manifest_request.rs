@@ -13,7 +13,8 @@ use crate::{
         },
         request_manager::{AsAny, Request},
         state::storage::{RangedManifest, SnapshotSyncCandidate},
-        Error, ProtocolConfiguration, SYNC_PROTO_V1, SYNC_PROTO_V3,
+        Error, ProtocolConfiguration, SynchronizationGraph, SYNC_PROTO_V1,
+        SYNC_PROTO_V3,
     },
 };
 use mazze_parameters::{
@@ -67,10 +68,12 @@ impl Handleable for SnapshotManifestRequest {
             }
         };
         if self.is_initial_request() {
-            let (state_root_vec, receipt_blame_vec, bloom_blame_vec) =
-                self.get_blame_states(ctx).unwrap_or_default();
-            let block_receipts =
-                self.get_block_receipts(ctx).unwrap_or_default();
+            let (state_root_vec, receipt_blame_vec, bloom_blame_vec) = self
+                .get_blame_states(&ctx.manager.graph)
+                .unwrap_or_default();
+            let block_receipts = self
+                .get_block_receipts(&ctx.manager.graph)
+                .unwrap_or_default();
 
             debug!("handle SnapshotManifestRequest {:?}", self,);
             ctx.send_response(&SnapshotManifestResponse {
@@ -120,17 +123,17 @@ impl SnapshotManifestRequest {
     /// In the node of the request sender, to compute the state of E(i+1),
     /// it would require to compute and include the reward of
     /// E(i+1-REWARD_EPOCH_COUNT).
-    fn get_block_receipts(
-        &self, ctx: &Context,
+    pub fn get_block_receipts(
+        &self, graph: &SynchronizationGraph,
     ) -> Option<Vec<BlockExecutionResult>> {
         let mut epoch_receipts = Vec::new();
         let mut epoch_hash =
             self.snapshot_to_sync.get_snapshot_epoch_id().clone();
         for i in 0..REWARD_EPOCH_COUNT {
             if let Some(block) =
-                ctx.manager.graph.data_man.block_header_by_hash(&epoch_hash)
+                graph.data_man.block_header_by_hash(&epoch_hash)
             {
-                match ctx.manager.graph.consensus.get_block_hashes_by_epoch(
+                match graph.consensus.get_block_hashes_by_epoch(
                     EpochNumber::Number(block.height()),
                 ) {
                     Ok(ordered_executable_epoch_blocks) => {
@@ -145,9 +148,7 @@ impl SnapshotManifestRequest {
                             return None;
                         }
                         for hash in &ordered_executable_epoch_blocks {
-                            match ctx
-                                .manager
-                                .graph
+                            match graph
                                 .data_man
                                 .block_execution_result_by_hash_with_epoch(
                                     hash,
@@ -181,10 +182,7 @@ impl SnapshotManifestRequest {
                 }
                 epoch_hash = block.parent_hash().clone();
             } else {
-                warn!(
-                    "failed to find block={} in db, peer={}",
-                    epoch_hash, ctx.node_id
-                );
+                warn!("failed to find block={} in db", epoch_hash);
                 return None;
             }
         }
@@ -194,22 +192,20 @@ impl SnapshotManifestRequest {
     /// return an empty vec if some information not exist in db, caller may find
     /// another peer to send the request; otherwise return a state_blame_vec
     /// of the requested block
-    fn get_blame_states(
-        &self, ctx: &Context,
+    pub fn get_blame_states(
+        &self, graph: &SynchronizationGraph,
     ) -> Option<(Vec<StateRoot>, Vec<H256>, Vec<H256>)> {
-        let trusted_block = ctx
-            .manager
-            .graph
+        let trusted_block = graph
             .data_man
             .block_header_by_hash(&self.trusted_blame_block?)?;
-        let snapshot_epoch_block =
-            ctx.manager.graph.data_man.block_header_by_hash(
-                self.snapshot_to_sync.get_snapshot_epoch_id(),
-            )?;
+        let snapshot_epoch_block = graph.data_man.block_header_by_hash(
+            self.snapshot_to_sync.get_snapshot_epoch_id(),
+        )?;
         if trusted_block.height() < snapshot_epoch_block.height() {
             warn!(
-                "receive invalid snapshot manifest request from peer={}",
-                ctx.node_id
+                "invalid snapshot manifest request: trusted blame block {:?} \
+                 is before the snapshot",
+                trusted_block.hash()
             );
             return None;
         }
@@ -218,9 +214,7 @@ impl SnapshotManifestRequest {
         let mut blame_count = trusted_block.blame();
         let mut deferred_block_hash = block_hash;
         for _ in 0..DEFERRED_STATE_EPOCH_COUNT {
-            deferred_block_hash = *ctx
-                .manager
-                .graph
+            deferred_block_hash = *graph
                 .data_man
                 .block_header_by_hash(&deferred_block_hash)
                 .expect("All headers exist")
@@ -245,7 +239,7 @@ impl SnapshotManifestRequest {
         // loop until we have enough length of `state_root_vec`
         loop {
             if let Some(block) =
-                ctx.manager.graph.data_man.block_header_by_hash(&block_hash)
+                graph.data_man.block_header_by_hash(&block_hash)
             {
                 // We've jumped to another trusted block.
                 if block.height() + blame_count as u64 + 1
@@ -254,11 +248,8 @@ impl SnapshotManifestRequest {
                     trusted_block_height = block.height();
                     blame_count = block.blame()
                 }
-                if let Some(commitment) = ctx
-                    .manager
-                    .graph
-                    .data_man
-                    .get_epoch_execution_commitment_with_db(
+                if let Some(commitment) =
+                    graph.data_man.get_epoch_execution_commitment_with_db(
                         &deferred_block_hash,
                     )
                 {
@@ -268,10 +259,7 @@ impl SnapshotManifestRequest {
                     receipt_blame_vec.push(commitment.receipts_root);
                     bloom_blame_vec.push(commitment.logs_bloom_hash);
                 } else {
-                    warn!(
-                        "failed to find block={} in db, peer={}",
-                        block_hash, ctx.node_id
-                    );
+                    warn!("failed to find block={} in db", block_hash);
                     return None;
                 }
                 // We've collected enough states.
@@ -281,18 +269,13 @@ impl SnapshotManifestRequest {
                     break;
                 }
                 block_hash = *block.parent_hash();
-                deferred_block_hash = *ctx
-                    .manager
-                    .graph
+                deferred_block_hash = *graph
                     .data_man
                     .block_header_by_hash(&deferred_block_hash)
                     .expect("All headers received")
                     .parent_hash();
             } else {
-                warn!(
-                    "failed to find block={} in db, peer={}",
-                    block_hash, ctx.node_id
-                );
+                warn!("failed to find block={} in db", block_hash);
                 return None;
             }
         }
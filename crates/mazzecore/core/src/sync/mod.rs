@@ -16,11 +16,11 @@ pub mod utils;
 
 pub use self::{
     error::{Error, ErrorKind},
-    state::StateSyncConfiguration,
+    state::{export_snapshot, import_snapshot, StateSyncConfiguration},
     synchronization_graph::{
-        SharedSynchronizationGraph, SyncGraphConfig, SyncGraphStatistics,
-        SynchronizationGraph, SynchronizationGraphInner,
-        SynchronizationGraphNode,
+        BlockHeaderInsertionResult, SharedSynchronizationGraph,
+        SyncGraphConfig, SyncGraphStatistics, SynchronizationGraph,
+        SynchronizationGraphInner, SynchronizationGraphNode,
     },
     synchronization_phases::{
         CatchUpCheckpointPhase, CatchUpFillBlockBodyPhase,
@@ -1,11 +1,13 @@
 
 
+mod snapshot_archive;
 mod snapshot_chunk_sync;
 mod state_sync_candidate;
 mod state_sync_chunk;
 mod state_sync_manifest;
 pub mod storage;
 
-pub use self::snapshot_chunk_sync::{
-    SnapshotChunkSync, StateSyncConfiguration, Status,
+pub use self::{
+    snapshot_archive::{export_snapshot, import_snapshot},
+    snapshot_chunk_sync::{SnapshotChunkSync, StateSyncConfiguration, Status},
 };
@@ -0,0 +1,916 @@
+//! Snapshot archives for bootstrapping a node offline. An archive is a
+//! directory, or a tarball of the directory, with the chunks of a snapshot and
+//! a manifest that carries the chunk boundaries and the consensus proofs of
+//! the snapshot, i.e. the same data a node receives through
+//! `SnapshotManifestResponse` and `SnapshotChunkResponse` during state sync,
+//! so it can be served from a plain file server and verified in the same way.
+//! The archive also has the headers of the main chain, so a node which has
+//! not synced the headers can find the checkpoint and verify the proofs.
+
+use crate::{
+    block_data_manager::BlockExecutionResult,
+    sync::{
+        error::{Error, ErrorKind},
+        message::SnapshotManifestRequest,
+        state::{
+            state_sync_chunk::restore::Restorer,
+            state_sync_manifest::snapshot_manifest_manager::{
+                RelatedData, SnapshotManifestManager,
+            },
+            storage::{Chunk, RangedManifest, SnapshotSyncCandidate},
+        },
+        BlockHeaderInsertionResult, SynchronizationGraph,
+    },
+    ConsensusGraphTrait,
+};
+use mazze_storage::FullSyncVerifier;
+use mazze_types::H256;
+use primitives::{BlockHeader, EpochId, StateRoot};
+use rlp::Rlp;
+use rlp_derive::{RlpDecodable, RlpEncodable};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+const SNAPSHOT_ARCHIVE_VERSION: u8 = 1;
+const MANIFEST_FILE_NAME: &str = "manifest.rlp";
+/// The number of epochs whose headers are in one header file.
+const HEADER_FILE_EPOCH_COUNT: u64 = 1000;
+const TAR_BLOCK_SIZE: usize = 512;
+
+fn chunk_file_name(index: usize) -> String {
+    format!("chunk-{:08}.rlp", index)
+}
+
+fn header_file_name(index: u64) -> String {
+    format!("headers-{:08}.rlp", index)
+}
+
+/// An archive is written as a tarball if the path ends with `.tar`, or to a
+/// directory otherwise.
+fn is_tarball(path: &Path) -> bool {
+    path.extension().map_or(false, |ext| ext == "tar")
+}
+
+enum ArchiveWriter {
+    Directory(PathBuf),
+    Tarball(BufWriter<File>),
+}
+
+impl ArchiveWriter {
+    fn create(path: &Path) -> io::Result<Self> {
+        if is_tarball(path) {
+            Ok(ArchiveWriter::Tarball(BufWriter::new(File::create(path)?)))
+        } else {
+            fs::create_dir_all(path)?;
+            Ok(ArchiveWriter::Directory(path.to_path_buf()))
+        }
+    }
+
+    fn write_file(&mut self, name: &str, bytes: &[u8]) -> io::Result<()> {
+        match self {
+            ArchiveWriter::Directory(dir) => fs::write(dir.join(name), bytes),
+            ArchiveWriter::Tarball(writer) => {
+                writer.write_all(&tar_header(name, bytes.len() as u64)?)?;
+                writer.write_all(bytes)?;
+                writer.write_all(
+                    &[0; TAR_BLOCK_SIZE][..tar_padding(bytes.len() as u64)],
+                )
+            }
+        }
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match self {
+            ArchiveWriter::Directory(_) => Ok(()),
+            ArchiveWriter::Tarball(mut writer) => {
+                // A tarball ends with two empty blocks.
+                writer.write_all(&[0; 2 * TAR_BLOCK_SIZE])?;
+                writer.flush()
+            }
+        }
+    }
+}
+
+enum ArchiveReader {
+    Directory(PathBuf),
+    /// The offset and the size of the files in the tarball.
+    Tarball(File, HashMap<String, (u64, u64)>),
+}
+
+impl ArchiveReader {
+    fn open(path: &Path) -> io::Result<Self> {
+        if is_tarball(path) {
+            let mut file = File::open(path)?;
+            let entries = read_tar_entries(&mut file)?;
+            Ok(ArchiveReader::Tarball(file, entries))
+        } else {
+            Ok(ArchiveReader::Directory(path.to_path_buf()))
+        }
+    }
+
+    fn read_file(&mut self, name: &str) -> io::Result<Vec<u8>> {
+        match self {
+            ArchiveReader::Directory(dir) => fs::read(dir.join(name)),
+            ArchiveReader::Tarball(file, entries) => {
+                let (offset, size) = *entries.get(name).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("{} is not in the tarball", name),
+                    )
+                })?;
+                file.seek(SeekFrom::Start(offset))?;
+                let mut bytes = Vec::new();
+                file.take(size).read_to_end(&mut bytes)?;
+                if bytes.len() as u64 != size {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        format!("{} is truncated", name),
+                    ));
+                }
+                Ok(bytes)
+            }
+        }
+    }
+}
+
+fn tar_padding(size: u64) -> usize {
+    (TAR_BLOCK_SIZE - size as usize % TAR_BLOCK_SIZE) % TAR_BLOCK_SIZE
+}
+
+fn tar_checksum(header: &[u8; TAR_BLOCK_SIZE]) -> u64 {
+    // The checksum field itself is counted as spaces.
+    header
+        .iter()
+        .enumerate()
+        .map(|(i, byte)| {
+            if (148..156).contains(&i) {
+                b' ' as u64
+            } else {
+                *byte as u64
+            }
+        })
+        .sum()
+}
+
+/// The ustar header of a regular file.
+fn tar_header(name: &str, size: u64) -> io::Result<[u8; TAR_BLOCK_SIZE]> {
+    // The size field has 11 octal digits.
+    if name.len() > 100 || size >= 1 << 33 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} can't be written to a tarball", name),
+        ));
+    }
+    let mut header = [0; TAR_BLOCK_SIZE];
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    header[100..108].copy_from_slice(b"0000644\0");
+    header[108..116].copy_from_slice(b"0000000\0");
+    header[116..124].copy_from_slice(b"0000000\0");
+    header[124..136].copy_from_slice(format!("{:011o}\0", size).as_bytes());
+    header[136..148].copy_from_slice(b"00000000000\0");
+    header[156] = b'0';
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+    let checksum = tar_checksum(&header);
+    header[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+    Ok(header)
+}
+
+fn parse_tar_number(field: &[u8]) -> Option<u64> {
+    let digits = std::str::from_utf8(field)
+        .ok()?
+        .trim_matches(|c| c == '\0' || c == ' ');
+    u64::from_str_radix(digits, 8).ok()
+}
+
+fn parse_tar_string(field: &[u8]) -> String {
+    let end = field
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+/// Read the headers of the files in a tarball, which may also be made with
+/// `tar` from an archive directory. Other kinds of entries are skipped.
+fn read_tar_entries<R: Read + Seek>(
+    reader: &mut R,
+) -> io::Result<HashMap<String, (u64, u64)>> {
+    let invalid = |message: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid tarball: {}", message),
+        )
+    };
+    let len = reader.seek(SeekFrom::End(0))?;
+    let mut entries = HashMap::new();
+    let mut offset = 0;
+    let mut header = [0; TAR_BLOCK_SIZE];
+    loop {
+        reader.seek(SeekFrom::Start(offset))?;
+        reader
+            .read_exact(&mut header)
+            .map_err(|_| invalid("the end of the archive is missing"))?;
+        if header.iter().all(|byte| *byte == 0) {
+            break;
+        }
+        if &header[257..262] != b"ustar" {
+            return Err(invalid("not a ustar archive"));
+        }
+        if parse_tar_number(&header[148..156]) != Some(tar_checksum(&header)) {
+            return Err(invalid("checksum mismatch"));
+        }
+        let size = parse_tar_number(&header[124..136])
+            .ok_or_else(|| invalid("unsupported file size"))?;
+        offset += TAR_BLOCK_SIZE as u64;
+        if len.saturating_sub(offset) < size {
+            return Err(invalid("a file is truncated"));
+        }
+        if matches!(header[156], b'0' | 0) {
+            let prefix = parse_tar_string(&header[345..500]);
+            let name = parse_tar_string(&header[..100]);
+            let path = if prefix.is_empty() {
+                name
+            } else {
+                format!("{}/{}", prefix, name)
+            };
+            entries.insert(
+                path.trim_start_matches("./").to_string(),
+                (offset, size),
+            );
+        }
+        offset += size + tar_padding(size) as u64;
+    }
+    Ok(entries)
+}
+
+#[derive(RlpEncodable, RlpDecodable)]
+struct SnapshotArchiveManifest {
+    version: u8,
+    genesis_hash: H256,
+    snapshot_to_sync: SnapshotSyncCandidate,
+    trusted_blame_block: H256,
+    header_file_count: u64,
+    manifest: RangedManifest,
+    state_root_vec: Vec<StateRoot>,
+    receipt_blame_vec: Vec<H256>,
+    bloom_blame_vec: Vec<H256>,
+    block_receipts: Vec<BlockExecutionResult>,
+}
+
+/// Write the headers of the main chain epochs after genesis, which must be
+/// available up to `min_epoch`. Returns the number of header files.
+fn write_headers(
+    graph: &SynchronizationGraph, writer: &mut ArchiveWriter, min_epoch: u64,
+) -> Result<u64, Error> {
+    let mut file_count = 0;
+    let mut headers = Vec::new();
+    for epoch_number in 1..=graph.consensus.best_epoch_number() {
+        // The epoch sets of the latest epochs are not persisted yet.
+        let hashes =
+            match graph.data_man.all_epoch_set_hashes_from_db(epoch_number) {
+                Some(hashes) => hashes,
+                None if epoch_number > min_epoch => break,
+                None => bail!(ErrorKind::InternalError(format!(
+                    "the blocks of epoch {} are missing",
+                    epoch_number
+                ))),
+            };
+        for hash in hashes {
+            headers.push(
+                graph.data_man.block_header_by_hash(&hash).ok_or_else(
+                    || {
+                        ErrorKind::InternalError(format!(
+                            "header of block {:?} is missing",
+                            hash
+                        ))
+                    },
+                )?,
+            );
+        }
+        if epoch_number % HEADER_FILE_EPOCH_COUNT == 0 {
+            writer.write_file(
+                &header_file_name(file_count),
+                &rlp::encode_list::<BlockHeader, _>(&headers),
+            )?;
+            file_count += 1;
+            headers.clear();
+        }
+    }
+    if !headers.is_empty() {
+        writer.write_file(
+            &header_file_name(file_count),
+            &rlp::encode_list::<BlockHeader, _>(&headers),
+        )?;
+        file_count += 1;
+    }
+    Ok(file_count)
+}
+
+fn read_headers(
+    reader: &mut ArchiveReader, index: u64,
+) -> Result<Vec<BlockHeader>, Error> {
+    Ok(Rlp::new(&reader.read_file(&header_file_name(index))?).as_list()?)
+}
+
+/// Insert the headers in the archive which the node does not have yet, like
+/// the headers received in `CatchUpSyncBlockHeaderPhase`, and wait until the
+/// consensus graph has processed them.
+fn insert_headers(
+    graph: &SynchronizationGraph, reader: &mut ArchiveReader,
+    header_file_count: u64,
+) -> Result<(), Error> {
+    for index in 0..header_file_count {
+        for mut header in read_headers(reader, index)? {
+            if graph.contains_block_header(&header.hash()) {
+                continue;
+            }
+            let (insert_result, _) = graph.insert_block_header(
+                &mut header,
+                true,  /* need_to_verify */
+                false, /* bench_mode */
+                true,  /* insert_to_consensus */
+                true,  /* persistent */
+            );
+            match insert_result {
+                BlockHeaderInsertionResult::Invalid => {
+                    bail!(ErrorKind::InvalidSnapshotManifest(format!(
+                        "block {:?} has an invalid header",
+                        header.hash()
+                    )))
+                }
+                BlockHeaderInsertionResult::TemporarySkipped => {
+                    bail!(ErrorKind::InternalError(
+                        "the synchronization graph is locked".into()
+                    ))
+                }
+                _ => {}
+            }
+        }
+    }
+    while graph.is_consensus_worker_busy() {
+        thread::sleep(Duration::from_millis(100));
+    }
+    Ok(())
+}
+
+/// Write the snapshot of `snapshot_epoch_id` to `path`, cut into chunks of
+/// about `chunk_size` bytes. Returns the number of chunks. The manifest is
+/// written last, so an interrupted export leaves no usable archive.
+pub fn export_snapshot(
+    graph: &SynchronizationGraph, snapshot_epoch_id: &EpochId, path: &Path,
+    chunk_size: u64,
+) -> Result<usize, Error> {
+    let height = graph
+        .data_man
+        .block_header_by_hash(snapshot_epoch_id)
+        .ok_or_else(|| {
+            ErrorKind::InternalError(format!(
+                "header of snapshot {:?} is missing",
+                snapshot_epoch_id
+            ))
+        })?
+        .height();
+    let snapshot_to_sync = SnapshotSyncCandidate::FullSync {
+        height,
+        snapshot_epoch_id: *snapshot_epoch_id,
+    };
+    let trusted_blame_block = graph
+        .consensus
+        .get_trusted_blame_block_for_snapshot(snapshot_epoch_id)
+        .ok_or_else(|| {
+            ErrorKind::InternalError(format!(
+                "no trusted blame block for snapshot {:?} yet",
+                snapshot_epoch_id
+            ))
+        })?;
+
+    let request = SnapshotManifestRequest::new(
+        snapshot_to_sync.clone(),
+        Some(trusted_blame_block),
+        None, /* start_chunk */
+    );
+    let (state_root_vec, receipt_blame_vec, bloom_blame_vec) =
+        request.get_blame_states(graph).ok_or_else(|| {
+            ErrorKind::InternalError(
+                "the blame states of the snapshot are missing".into(),
+            )
+        })?;
+    let block_receipts =
+        request.get_block_receipts(graph).ok_or_else(|| {
+            ErrorKind::InternalError(
+                "the receipts of the snapshot epochs are missing".into(),
+            )
+        })?;
+
+    let storage_manager = &graph.data_man.storage_manager;
+    let (manifest, _) = RangedManifest::load(
+        &snapshot_to_sync,
+        None, /* start_key */
+        storage_manager,
+        chunk_size,
+        usize::MAX, /* max_chunks */
+    )?
+    .ok_or_else(|| {
+        ErrorKind::InternalError(format!(
+            "snapshot {:?} is not available",
+            snapshot_epoch_id
+        ))
+    })?;
+
+    let trusted_blame_height = graph
+        .data_man
+        .block_header_by_hash(&trusted_blame_block)
+        .ok_or_else(|| {
+            ErrorKind::InternalError(format!(
+                "header of trusted blame block {:?} is missing",
+                trusted_blame_block
+            ))
+        })?
+        .height();
+
+    let mut writer = ArchiveWriter::create(path)?;
+    let header_file_count =
+        write_headers(graph, &mut writer, trusted_blame_height)?;
+    let chunks = RangedManifest::convert_boundaries_to_chunks(
+        manifest.chunk_boundaries.clone(),
+    );
+    for (index, chunk_key) in chunks.iter().enumerate() {
+        let chunk = Chunk::load(
+            snapshot_epoch_id,
+            chunk_key,
+            storage_manager,
+            chunk_size * 2,
+        )?
+        .ok_or_else(|| {
+            ErrorKind::InternalError(format!(
+                "snapshot {:?} is not available",
+                snapshot_epoch_id
+            ))
+        })?;
+        writer.write_file(&chunk_file_name(index), &rlp::encode(&chunk))?;
+    }
+
+    let archive = SnapshotArchiveManifest {
+        version: SNAPSHOT_ARCHIVE_VERSION,
+        genesis_hash: graph.data_man.true_genesis.hash(),
+        snapshot_to_sync,
+        trusted_blame_block,
+        header_file_count,
+        manifest,
+        state_root_vec,
+        receipt_blame_vec,
+        bloom_blame_vec,
+        block_receipts,
+    };
+    writer.write_file(MANIFEST_FILE_NAME, &rlp::encode(&archive))?;
+    writer.finish()?;
+    Ok(chunks.len())
+}
+
+/// Restore the snapshot in the archive at `path` and the execution results of
+/// its epochs, so the node skips state sync in `CatchUpCheckpointPhase` at
+/// the next start. The headers in the archive are verified and inserted
+/// first if the node does not have them, and the snapshot must be the
+/// checkpoint the node is going to sync then. The proofs in the manifest are
+/// verified against the headers, and the chunks are verified with
+/// `FullSyncVerifier`. Returns the epoch of the restored snapshot.
+pub fn import_snapshot(
+    graph: &SynchronizationGraph, path: &Path,
+) -> Result<EpochId, Error> {
+    let mut reader = ArchiveReader::open(path)?;
+    let archive: SnapshotArchiveManifest =
+        rlp::decode(&reader.read_file(MANIFEST_FILE_NAME)?)?;
+    if archive.version != SNAPSHOT_ARCHIVE_VERSION {
+        bail!(ErrorKind::InvalidSnapshotManifest(format!(
+            "unsupported archive version {}",
+            archive.version
+        )));
+    }
+    if archive.genesis_hash != graph.data_man.true_genesis.hash() {
+        bail!(ErrorKind::InvalidSnapshotManifest(format!(
+            "the snapshot is exported from genesis {:?}",
+            archive.genesis_hash
+        )));
+    }
+    insert_headers(graph, &mut reader, archive.header_file_count)?;
+
+    let snapshot_epoch_id = *archive.snapshot_to_sync.get_snapshot_epoch_id();
+    let epoch_to_sync = graph.consensus.get_to_sync_epoch_id();
+    if snapshot_epoch_id != epoch_to_sync {
+        bail!(ErrorKind::InvalidSnapshotManifest(format!(
+            "the node syncs the state of checkpoint {:?}, not {:?}",
+            epoch_to_sync, snapshot_epoch_id
+        )));
+    }
+    if graph
+        .data_man
+        .load_epoch_execution_commitment_from_db(&snapshot_epoch_id)
+        .is_some()
+    {
+        bail!(ErrorKind::InvalidSnapshotManifest(format!(
+            "the state of checkpoint {:?} already exists",
+            snapshot_epoch_id
+        )));
+    }
+    if graph
+        .consensus
+        .get_trusted_blame_block_for_snapshot(&snapshot_epoch_id)
+        != Some(archive.trusted_blame_block)
+    {
+        bail!(ErrorKind::InvalidSnapshotManifest(format!(
+            "trusted blame block {:?} is not on the local main chain",
+            archive.trusted_blame_block
+        )));
+    }
+
+    let (
+        blame_vec_offset,
+        true_state_root_by_blame_info,
+        snapshot_info,
+        parent_snapshot_info,
+    ) = SnapshotManifestManager::validate_blame_states(
+        graph,
+        &snapshot_epoch_id,
+        &archive.trusted_blame_block,
+        &archive.state_root_vec,
+        &archive.receipt_blame_vec,
+        &archive.bloom_blame_vec,
+    )
+    .ok_or_else(|| {
+        ErrorKind::InvalidSnapshotManifest(
+            "invalid blame state in manifest".into(),
+        )
+    })?;
+    let epoch_receipts = SnapshotManifestManager::validate_epoch_receipts(
+        graph,
+        blame_vec_offset,
+        &snapshot_epoch_id,
+        &archive.receipt_blame_vec,
+        &archive.bloom_blame_vec,
+        &archive.block_receipts,
+    )
+    .ok_or_else(|| {
+        ErrorKind::InvalidSnapshotManifest(
+            "invalid epoch receipts in manifest".into(),
+        )
+    })?;
+    if archive.manifest.next.is_some() {
+        bail!(ErrorKind::InvalidSnapshotManifest(
+            "the manifest is incomplete".into()
+        ));
+    }
+    archive.manifest.validate(&snapshot_info.merkle_root)?;
+
+    let storage_manager = &graph.data_man.storage_manager;
+    let chunk_boundaries = archive.manifest.chunk_boundaries;
+    let verifier = FullSyncVerifier::new(
+        chunk_boundaries.len() + 1,
+        chunk_boundaries.clone(),
+        archive.manifest.chunk_boundary_proofs,
+        snapshot_info.merkle_root,
+        storage_manager
+            .get_storage_manager()
+            .get_snapshot_manager()
+            .get_snapshot_db_manager(),
+        snapshot_info.get_snapshot_epoch_id(),
+        snapshot_info.height,
+    )?;
+    let mut restorer =
+        Restorer::new(snapshot_epoch_id, snapshot_info.merkle_root);
+    restorer.initialize_verifier(verifier);
+    let chunks = RangedManifest::convert_boundaries_to_chunks(chunk_boundaries);
+    for (index, chunk_key) in chunks.into_iter().enumerate() {
+        let chunk: Chunk =
+            rlp::decode(&reader.read_file(&chunk_file_name(index))?)?;
+        chunk.validate(&chunk_key)?;
+        if !restorer.append(chunk_key, chunk) {
+            bail!(ErrorKind::InvalidSnapshotChunk(format!(
+                "chunk {} does not match the manifest",
+                index
+            )));
+        }
+    }
+    restorer.finalize_restoration(
+        storage_manager.clone(),
+        snapshot_info.clone(),
+        parent_snapshot_info.clone(),
+        true_state_root_by_blame_info.state_root.delta_root,
+    )?;
+
+    RelatedData {
+        true_state_root_by_blame_info,
+        blame_vec_offset,
+        receipt_blame_vec: archive.receipt_blame_vec,
+        bloom_blame_vec: archive.bloom_blame_vec,
+        epoch_receipts,
+        snapshot_info,
+        parent_snapshot_info,
+    }
+    .restore_execution_state(&graph.data_man);
+    Ok(snapshot_epoch_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        block_data_manager::DbType,
+        pow::PowComputer,
+        sync::utils::{
+            initialize_data_manager, initialize_executed_synchronization_graph,
+            insert_executed_chain,
+        },
+    };
+    use mazze_executor::machine::VmFactory;
+    use mazze_parameters::consensus_internal::REWARD_EPOCH_COUNT;
+    use mazze_storage::storage_db::SnapshotInfo;
+    use std::{io::Cursor, sync::Arc, thread::sleep};
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_tarball_entries() {
+        let dir = TempDir::new("snapshot_archive_tarball").unwrap();
+        let path = dir.path().join("archive.tar");
+        let mut writer = ArchiveWriter::create(&path).unwrap();
+        writer.write_file("chunk-00000000.rlp", &[7; 1000]).unwrap();
+        writer.write_file("empty.rlp", &[]).unwrap();
+        writer.write_file(MANIFEST_FILE_NAME, &[1; 512]).unwrap();
+        writer.finish().unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        // The files are padded to blocks, and the archive ends with two
+        // empty blocks.
+        assert_eq!(bytes.len(), 3 * TAR_BLOCK_SIZE + 1536 + 2 * TAR_BLOCK_SIZE);
+        let mut reader = ArchiveReader::open(&path).unwrap();
+        assert_eq!(
+            reader.read_file("chunk-00000000.rlp").unwrap(),
+            vec![7; 1000]
+        );
+        assert_eq!(reader.read_file("empty.rlp").unwrap(), vec![]);
+        assert_eq!(reader.read_file(MANIFEST_FILE_NAME).unwrap(), vec![1; 512]);
+        assert_eq!(
+            reader.read_file("chunk-00000001.rlp").unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+
+        // A tarball made with `tar -C <dir> .` has an entry for the directory,
+        // and the names start with "./".
+        let mut header = tar_header("./", 0).unwrap();
+        header[156] = b'5';
+        let checksum = tar_checksum(&header);
+        header[148..156]
+            .copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+        let mut tarball = header.to_vec();
+        tarball.extend_from_slice(&tar_header("./manifest.rlp", 1).unwrap());
+        tarball.extend_from_slice(&[1; TAR_BLOCK_SIZE]);
+        tarball.extend_from_slice(&[0; 2 * TAR_BLOCK_SIZE]);
+        let entries = read_tar_entries(&mut Cursor::new(&tarball)).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries.get(MANIFEST_FILE_NAME),
+            Some(&(2 * TAR_BLOCK_SIZE as u64, 1))
+        );
+
+        // A corrupted header, a truncated file and a missing end.
+        let mut corrupted = bytes.clone();
+        corrupted[0] = b'x';
+        assert!(read_tar_entries(&mut Cursor::new(&corrupted)).is_err());
+        let truncated = &bytes[..TAR_BLOCK_SIZE + 500];
+        assert!(read_tar_entries(&mut Cursor::new(truncated)).is_err());
+        let unfinished = &bytes[..bytes.len() - 2 * TAR_BLOCK_SIZE];
+        assert!(read_tar_entries(&mut Cursor::new(unfinished)).is_err());
+    }
+
+    /// Export the proofs of a snapshot and the headers of an executed chain to
+    /// an archive, and verify and restore them from the archive like
+    /// `import_snapshot`. The state of the snapshot itself is not in the
+    /// archive, because the snapshots are only made every
+    /// `SNAPSHOT_EPOCHS_CAPACITY` epochs.
+    fn check_archive_round_trip(path: &Path) {
+        let db_dir = TempDir::new("snapshot_archive_export").unwrap();
+        let (sync, consensus, data_man, genesis_block) =
+            initialize_executed_synchronization_graph(
+                db_dir.path().to_str().unwrap(),
+                50000,
+            );
+        let hashes =
+            insert_executed_chain(&sync, &consensus, genesis_block.hash(), 30);
+        // The epochs up to 25 are executed after 30 blocks.
+        while data_man
+            .get_epoch_execution_commitment_with_db(&hashes[24])
+            .is_none()
+        {
+            sleep(Duration::from_millis(10));
+        }
+
+        let snapshot_epoch_id = hashes[12];
+        let trusted_blame_block = hashes[24];
+        let snapshot_to_sync = SnapshotSyncCandidate::FullSync {
+            height: 13,
+            snapshot_epoch_id,
+        };
+        let request = SnapshotManifestRequest::new(
+            snapshot_to_sync.clone(),
+            Some(trusted_blame_block),
+            None, /* start_chunk */
+        );
+        let (state_root_vec, receipt_blame_vec, bloom_blame_vec) =
+            request.get_blame_states(&sync).unwrap();
+        // The deferred states from the trusted blame block back to the
+        // rewarded epochs of the snapshot.
+        assert_eq!(state_root_vec.len(), 19);
+        for (i, state_root) in state_root_vec.iter().enumerate() {
+            let commitment = data_man
+                .get_epoch_execution_commitment_with_db(&hashes[19 - i])
+                .unwrap();
+            assert_eq!(
+                *state_root,
+                commitment.state_root_with_aux_info.state_root
+            );
+            assert_eq!(receipt_blame_vec[i], commitment.receipts_root);
+            assert_eq!(bloom_blame_vec[i], commitment.logs_bloom_hash);
+        }
+        let block_receipts = request.get_block_receipts(&sync).unwrap();
+        assert_eq!(block_receipts.len(), REWARD_EPOCH_COUNT as usize);
+        // A trusted blame block before the snapshot is rejected.
+        assert!(SnapshotManifestRequest::new(
+            snapshot_to_sync.clone(),
+            Some(hashes[11]),
+            None, /* start_chunk */
+        )
+        .get_blame_states(&sync)
+        .is_none());
+
+        // The headers of epochs 26 and later are not persisted yet.
+        assert!(write_headers(
+            &sync,
+            &mut ArchiveWriter::create(path).unwrap(),
+            27, /* min_epoch */
+        )
+        .is_err());
+
+        let chunk = Chunk {
+            keys: vec![vec![1], vec![2]],
+            values: vec![vec![3], vec![4]],
+        };
+        let mut writer = ArchiveWriter::create(path).unwrap();
+        let header_file_count =
+            write_headers(&sync, &mut writer, 25 /* min_epoch */).unwrap();
+        writer
+            .write_file(&chunk_file_name(0), &rlp::encode(&chunk))
+            .unwrap();
+        let archive = SnapshotArchiveManifest {
+            version: SNAPSHOT_ARCHIVE_VERSION,
+            genesis_hash: genesis_block.hash(),
+            snapshot_to_sync: snapshot_to_sync.clone(),
+            trusted_blame_block,
+            header_file_count,
+            manifest: RangedManifest::default(),
+            state_root_vec,
+            receipt_blame_vec,
+            bloom_blame_vec,
+            block_receipts,
+        };
+        writer
+            .write_file(MANIFEST_FILE_NAME, &rlp::encode(&archive))
+            .unwrap();
+        writer.finish().unwrap();
+
+        let mut reader = ArchiveReader::open(path).unwrap();
+        let archive: SnapshotArchiveManifest =
+            rlp::decode(&reader.read_file(MANIFEST_FILE_NAME).unwrap())
+                .unwrap();
+        assert_eq!(archive.genesis_hash, genesis_block.hash());
+        assert_eq!(archive.snapshot_to_sync, snapshot_to_sync);
+        assert_eq!(archive.trusted_blame_block, trusted_blame_block);
+        let decoded: Chunk =
+            rlp::decode(&reader.read_file(&chunk_file_name(0)).unwrap())
+                .unwrap();
+        assert_eq!((decoded.keys, decoded.values), (chunk.keys, chunk.values));
+        let mut headers = vec![];
+        for index in 0..archive.header_file_count {
+            headers.extend(read_headers(&mut reader, index).unwrap());
+        }
+        assert_eq!(
+            headers
+                .iter()
+                .map(|header| header.hash())
+                .collect::<Vec<_>>(),
+            hashes[..25].to_vec()
+        );
+
+        // The receipts are verified with the blame vectors.
+        let blame_vec_offset = 7;
+        let epoch_receipts = SnapshotManifestManager::validate_epoch_receipts(
+            &sync,
+            blame_vec_offset,
+            &snapshot_epoch_id,
+            &archive.receipt_blame_vec,
+            &archive.bloom_blame_vec,
+            &archive.block_receipts,
+        )
+        .unwrap();
+        assert_eq!(epoch_receipts.len(), REWARD_EPOCH_COUNT as usize);
+        let mut receipt_blame_vec = archive.receipt_blame_vec.clone();
+        receipt_blame_vec[blame_vec_offset + 1] = H256::zero();
+        assert!(SnapshotManifestManager::validate_epoch_receipts(
+            &sync,
+            blame_vec_offset,
+            &snapshot_epoch_id,
+            &receipt_blame_vec,
+            &archive.bloom_blame_vec,
+            &archive.block_receipts,
+        )
+        .is_none());
+        let mut block_receipts = archive.block_receipts.clone();
+        block_receipts.truncate(REWARD_EPOCH_COUNT as usize - 1);
+        assert!(SnapshotManifestManager::validate_epoch_receipts(
+            &sync,
+            blame_vec_offset,
+            &snapshot_epoch_id,
+            &archive.receipt_blame_vec,
+            &archive.bloom_blame_vec,
+            &block_receipts,
+        )
+        .is_none());
+
+        // A node with the headers from the archive restores the execution
+        // results of the epochs before the snapshot.
+        let import_dir = TempDir::new("snapshot_archive_import").unwrap();
+        let (import_data_man, _) = initialize_data_manager(
+            import_dir.path().to_str().unwrap(),
+            DbType::Rocksdb,
+            Arc::new(PowComputer::new()),
+            VmFactory::new(1024 * 32),
+        );
+        for header in headers {
+            import_data_man.insert_block_header(
+                header.hash(),
+                Arc::new(header),
+                true, /* persistent */
+            );
+        }
+        let snapshot_commitment = data_man
+            .get_epoch_execution_commitment_with_db(&snapshot_epoch_id)
+            .unwrap();
+        RelatedData {
+            true_state_root_by_blame_info: snapshot_commitment
+                .state_root_with_aux_info,
+            blame_vec_offset,
+            receipt_blame_vec: archive.receipt_blame_vec.clone(),
+            bloom_blame_vec: archive.bloom_blame_vec.clone(),
+            epoch_receipts,
+            snapshot_info: SnapshotInfo {
+                height: 13,
+                main_chain_parts: vec![snapshot_epoch_id],
+                ..SnapshotInfo::genesis_snapshot_info()
+            },
+            parent_snapshot_info: None,
+        }
+        .restore_execution_state(&import_data_man);
+        for i in 0..REWARD_EPOCH_COUNT as usize {
+            let epoch_hash = hashes[12 - i];
+            let commitment = import_data_man
+                .get_epoch_execution_commitment_with_db(&epoch_hash)
+                .unwrap();
+            assert_eq!(
+                commitment.receipts_root,
+                archive.receipt_blame_vec[blame_vec_offset + i]
+            );
+            assert_eq!(
+                commitment.logs_bloom_hash,
+                archive.bloom_blame_vec[blame_vec_offset + i]
+            );
+            assert!(import_data_man
+                .block_execution_result_by_hash_with_epoch(
+                    &epoch_hash,
+                    &epoch_hash,
+                    false, /* update_main_assumption */
+                    false, /* update_cache */
+                )
+                .is_some());
+        }
+        assert!(import_data_man
+            .get_epoch_execution_commitment_with_db(&hashes[0])
+            .is_none());
+    }
+
+    #[test]
+    fn test_snapshot_archive_directory_round_trip() {
+        let dir = TempDir::new("snapshot_archive_directory").unwrap();
+        check_archive_round_trip(&dir.path().join("archive"));
+    }
+
+    #[test]
+    fn test_snapshot_archive_tarball_round_trip() {
+        let dir = TempDir::new("snapshot_archive_tarball").unwrap();
+        check_archive_round_trip(&dir.path().join("archive.tar"));
+    }
+}
@@ -19,7 +19,6 @@ use crate::sync::{
     synchronization_state::PeerFilter,
     SynchronizationProtocolHandler,
 };
-use mazze_parameters::consensus_internal::REWARD_EPOCH_COUNT;
 use mazze_storage::Result as StorageResult;
 use mazze_types::H256;
 use network::{node_table::NodeId, NetworkContext};
@@ -292,44 +291,7 @@ impl SnapshotChunkSync {
             .related_data
             .as_ref()
             .expect("Set after receving manifest");
-        let mut deferred_block_hash =
-            related_data.snapshot_info.get_snapshot_epoch_id().clone();
-        // FIXME: Because state_root_aux_info can't be computed for state block
-        // FIXME: before snapshot, for the reward epoch count, maybe
-        // FIXME: save it to a dedicated place for reward computation.
-        for i in related_data.blame_vec_offset
-            ..(related_data.blame_vec_offset + REWARD_EPOCH_COUNT as usize)
-        {
-            info!(
-                "insert_epoch_execution_commitment for block hash {:?}",
-                &deferred_block_hash
-            );
-            sync_handler
-                .graph
-                .data_man
-                .insert_epoch_execution_commitment(
-                    deferred_block_hash,
-                    // FIXME: the state root is wrong for epochs before sync
-                    // FIXME: point. but these information won't be used.
-                    related_data.true_state_root_by_blame_info.clone(),
-                    related_data.receipt_blame_vec[i],
-                    related_data.bloom_blame_vec[i],
-                );
-            let block = sync_handler
-                .graph
-                .data_man
-                .block_header_by_hash(&deferred_block_hash)
-                .unwrap();
-            deferred_block_hash = *block.parent_hash();
-        }
-        for (block_hash, epoch_hash, receipts) in &related_data.epoch_receipts {
-            sync_handler.graph.data_man.insert_block_execution_result(
-                *block_hash,
-                *epoch_hash,
-                receipts.clone(),
-                true, /* persistent */
-            );
-        }
+        related_data.restore_execution_state(&sync_handler.graph.data_man);
     }
 
     /// TODO Handling manifest requesting separately
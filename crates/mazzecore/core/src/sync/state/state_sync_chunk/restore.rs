@@ -4,7 +4,7 @@ use crate::sync::state::storage::{Chunk, ChunkKey};
 use mazze_storage::{
     state_manager::StateManager,
     storage_db::{SnapshotDbManagerTrait, SnapshotInfo},
-    FullSyncVerifier, Result as StorageResult, SnapshotDbManagerSqlite,
+    FullSyncVerifier, Result as StorageResult, SnapshotDbManagerByBackend,
 };
 use primitives::{EpochId, MerkleHash, NULL_EPOCH};
 use std::sync::Arc;
@@ -15,7 +15,7 @@ pub struct Restorer {
 
     /// The verifier for chunks.
     /// Initialized after receiving a valid manifest.
-    verifier: Option<FullSyncVerifier<SnapshotDbManagerSqlite>>,
+    verifier: Option<FullSyncVerifier<SnapshotDbManagerByBackend>>,
 }
 
 impl Restorer {
@@ -30,7 +30,7 @@ impl Restorer {
     }
 
     pub fn initialize_verifier(
-        &mut self, verifier: FullSyncVerifier<SnapshotDbManagerSqlite>,
+        &mut self, verifier: FullSyncVerifier<SnapshotDbManagerByBackend>,
     ) {
         self.verifier = Some(verifier);
     }
@@ -1,7 +1,7 @@
 #![allow(unused)]
 
 use crate::{
-    block_data_manager::BlockExecutionResult,
+    block_data_manager::{BlockDataManager, BlockExecutionResult},
     message::NetworkContext,
     sync::{
         error::{Error, ErrorKind},
@@ -10,7 +10,7 @@ use crate::{
         },
         state::storage::SnapshotSyncCandidate,
         synchronization_state::PeerFilter,
-        SynchronizationProtocolHandler,
+        SynchronizationGraph, SynchronizationProtocolHandler,
     },
     verification::compute_receipts_root,
 };
@@ -63,6 +63,45 @@ pub struct RelatedData {
     pub parent_snapshot_info: Option<SnapshotInfo>,
 }
 
+impl RelatedData {
+    /// Insert the execution commitments and the receipts verified with the
+    /// manifest, which are needed to execute the epochs after the snapshot.
+    pub fn restore_execution_state(&self, data_man: &BlockDataManager) {
+        let mut deferred_block_hash =
+            self.snapshot_info.get_snapshot_epoch_id().clone();
+        // FIXME: Because state_root_aux_info can't be computed for state block
+        // FIXME: before snapshot, for the reward epoch count, maybe
+        // FIXME: save it to a dedicated place for reward computation.
+        for i in self.blame_vec_offset
+            ..(self.blame_vec_offset + REWARD_EPOCH_COUNT as usize)
+        {
+            info!(
+                "insert_epoch_execution_commitment for block hash {:?}",
+                &deferred_block_hash
+            );
+            data_man.insert_epoch_execution_commitment(
+                deferred_block_hash,
+                // FIXME: the state root is wrong for epochs before sync
+                // FIXME: point. but these information won't be used.
+                self.true_state_root_by_blame_info.clone(),
+                self.receipt_blame_vec[i],
+                self.bloom_blame_vec[i],
+            );
+            let block =
+                data_man.block_header_by_hash(&deferred_block_hash).unwrap();
+            deferred_block_hash = *block.parent_hash();
+        }
+        for (block_hash, epoch_hash, receipts) in &self.epoch_receipts {
+            data_man.insert_block_execution_result(
+                *block_hash,
+                *epoch_hash,
+                receipts.clone(),
+                true, /* persistent */
+            );
+        }
+    }
+}
+
 impl SnapshotManifestManager {
     pub fn new_and_start(
         snapshot_candidate: SnapshotSyncCandidate, trusted_blame_block: H256,
@@ -132,7 +171,7 @@ impl SnapshotManifestManager {
                 snapshot_info,
                 parent_snapshot_info,
             ) = match Self::validate_blame_states(
-                ctx,
+                &ctx.manager.graph,
                 self.snapshot_candidate.get_snapshot_epoch_id(),
                 &self.trusted_blame_block,
                 &response.state_root_vec,
@@ -151,7 +190,7 @@ impl SnapshotManifestManager {
 
             let epoch_receipts =
                 match SnapshotManifestManager::validate_epoch_receipts(
-                    ctx,
+                    &ctx.manager.graph,
                     blame_vec_offset,
                     self.snapshot_candidate.get_snapshot_epoch_id(),
                     &response.receipt_blame_vec,
@@ -283,9 +322,9 @@ impl SnapshotManifestManager {
     pub fn is_inactive(&self) -> bool { self.active_peers.is_empty() }
 
     pub fn validate_blame_states(
-        ctx: &Context, snapshot_epoch_id: &H256, trusted_blame_block: &H256,
-        state_root_vec: &Vec<StateRoot>, receipt_blame_vec: &Vec<H256>,
-        bloom_blame_vec: &Vec<H256>,
+        graph: &SynchronizationGraph, snapshot_epoch_id: &H256,
+        trusted_blame_block: &H256, state_root_vec: &Vec<StateRoot>,
+        receipt_blame_vec: &Vec<H256>, bloom_blame_vec: &Vec<H256>,
     ) -> Option<(
         usize,
         StateRootWithAuxInfo,
@@ -295,15 +334,11 @@ impl SnapshotManifestManager {
         let mut state_blame_vec = vec![];
 
         // these two header must exist in disk, it's safe to unwrap
-        let snapshot_block_header = ctx
-            .manager
-            .graph
+        let snapshot_block_header = graph
             .data_man
             .block_header_by_hash(snapshot_epoch_id)
             .expect("block header must exist for snapshot to sync");
-        let trusted_blame_block = ctx
-            .manager
-            .graph
+        let trusted_blame_block = graph
             .data_man
             .block_header_by_hash(trusted_blame_block)
             .expect("trusted_blame_block header must exist");
@@ -338,9 +373,7 @@ impl SnapshotManifestManager {
         // verify the length of vector.
         loop {
             vec_len += 1;
-            let block = ctx
-                .manager
-                .graph
+            let block = graph
                 .data_man
                 .block_header_by_hash(&block_hash)
                 .expect("block header must exist");
@@ -417,10 +450,9 @@ impl SnapshotManifestManager {
             slice_begin = slice_end;
         }
 
-        let snapshot_epoch_count =
-            ctx.manager.graph.data_man.get_snapshot_epoch_count();
+        let snapshot_epoch_count = graph.data_man.get_snapshot_epoch_count();
         let (parent_snapshot_epoch, main_chain_parts) =
-            ctx.manager.graph.data_man.get_parent_epochs_for(
+            graph.data_man.get_parent_epochs_for(
                 snapshot_epoch_id.clone(),
                 snapshot_epoch_count as u64,
             );
@@ -428,8 +460,7 @@ impl SnapshotManifestManager {
         let parent_snapshot_height = if parent_snapshot_epoch == NULL_EPOCH {
             0
         } else {
-            ctx.manager
-                .graph
+            graph
                 .data_man
                 .block_header_by_hash(&parent_snapshot_epoch)
                 .unwrap()
@@ -443,7 +474,7 @@ impl SnapshotManifestManager {
             > snapshot_epoch_count as u64
         {
             let (grandparent_snapshot_epoch, grandparent_main_chain_parts) =
-                ctx.manager.graph.data_man.get_parent_epochs_for(
+                graph.data_man.get_parent_epochs_for(
                     parent_snapshot_epoch.clone(),
                     snapshot_epoch_count as u64,
                 );
@@ -452,8 +483,7 @@ impl SnapshotManifestManager {
                 if grandparent_snapshot_epoch == NULL_EPOCH {
                     0
                 } else {
-                    ctx.manager
-                        .graph
+                    graph
                         .data_man
                         .block_header_by_hash(&grandparent_snapshot_epoch)
                         .unwrap()
@@ -504,12 +534,8 @@ impl SnapshotManifestManager {
                 serve_one_step_sync: false,
                 // We need the extra -1 to get a state root that points to the
                 // snapshot we want.
-                merkle_root: state_root_vec[offset
-                    - ctx
-                        .manager
-                        .graph
-                        .data_man
-                        .get_snapshot_blame_plus_depth()]
+                merkle_root: state_root_vec
+                    [offset - graph.data_man.get_snapshot_blame_plus_depth()]
                 .snapshot_root,
                 height: snapshot_block_header.height(),
                 parent_snapshot_epoch_id: parent_snapshot_epoch,
@@ -521,14 +547,13 @@ impl SnapshotManifestManager {
     }
 
     pub fn validate_epoch_receipts(
-        ctx: &Context, blame_vec_offset: usize, snapshot_epoch_id: &EpochId,
-        receipt_blame_vec: &Vec<H256>, bloom_blame_vec: &Vec<H256>,
+        graph: &SynchronizationGraph, blame_vec_offset: usize,
+        snapshot_epoch_id: &EpochId, receipt_blame_vec: &Vec<H256>,
+        bloom_blame_vec: &Vec<H256>,
         block_receipts: &Vec<BlockExecutionResult>,
     ) -> Option<Vec<(H256, H256, Arc<BlockReceipts>)>> {
         let mut epoch_hash = snapshot_epoch_id.clone();
-        let checkpoint = ctx
-            .manager
-            .graph
+        let checkpoint = graph
             .data_man
             .block_header_by_hash(snapshot_epoch_id)
             .expect("checkpoint header must exist");
@@ -540,15 +565,11 @@ impl SnapshotManifestManager {
         let mut receipts_vec_offset = 0;
         let mut result = Vec::new();
         for idx in 0..epoch_receipts_count {
-            let block_header = ctx
-                .manager
-                .graph
+            let block_header = graph
                 .data_man
                 .block_header_by_hash(&epoch_hash)
                 .expect("block header must exist");
-            let ordered_executable_epoch_blocks = ctx
-                .manager
-                .graph
+            let ordered_executable_epoch_blocks = graph
                 .consensus
                 .get_block_hashes_by_epoch(EpochNumber::Number(
                     block_header.height(),
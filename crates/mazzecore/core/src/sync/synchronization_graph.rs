@@ -23,6 +23,7 @@ use dag::{Graph, RichDAG, RichDETS, DAG, DETS};
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use malloc_size_of_derive::MallocSizeOf as DeriveMallocSizeOf;
 use mazze_executor::machine::Machine;
+use mazze_internal_common::StateAvailabilityBoundary;
 use mazze_types::{H256, U256};
 use metrics::{
     register_meter_with_group, register_queue, Meter, MeterTimer, Queue,
@@ -1945,6 +1946,59 @@ impl SynchronizationGraph {
         inner.remove_blocks(&index_set_and_future);
     }
 
+    /// Remove the blocks in `to_remove_set` and their future set from the
+    /// graph and the db, and rebuild the consensus graph from the remaining
+    /// blocks. Return the hashes of the removed blocks.
+    ///
+    /// This is only used by the dev mode to revert the chain. The caller
+    /// should make sure that no removed epoch is being executed, and that
+    /// the remaining blocks are in the current era.
+    pub fn remove_blocks_and_rebuild_consensus(
+        &self, to_remove_set: &HashSet<H256>,
+    ) -> Vec<H256> {
+        while self.is_consensus_worker_busy() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let inner = &mut *self.inner.write();
+        let index_set: Vec<_> = to_remove_set
+            .iter()
+            .filter_map(|hash| inner.hash_to_arena_indices.get(hash))
+            .cloned()
+            .collect();
+        let index_set_and_future: HashSet<_> = inner.get_future(index_set);
+        let removed: Vec<H256> = index_set_and_future
+            .iter()
+            .map(|index| inner.arena[*index].block_header.hash())
+            .collect();
+        inner.remove_blocks(&index_set_and_future);
+
+        // The main chain is built again from the era genesis.
+        let era_genesis_hash =
+            self.data_man.get_cur_consensus_era_genesis_hash();
+        let era_genesis_height = self
+            .data_man
+            .block_height_by_hash(&era_genesis_hash)
+            .expect("era genesis exists");
+        self.data_man
+            .state_availability_boundary
+            .write()
+            .truncate_main_chain(era_genesis_height);
+        self.consensus.reset();
+        let all_block_indices: HashSet<_> =
+            inner.hash_to_arena_indices.values().cloned().collect();
+        for i in inner.topological_sort(all_block_indices) {
+            let hash = inner.arena[i].block_header.hash();
+            if hash != era_genesis_hash {
+                self.consensus.on_new_block(&hash);
+            }
+        }
+
+        for hash in &removed {
+            self.data_man.remove_reverted_block(hash);
+        }
+        removed
+    }
+
     pub fn is_consensus_worker_busy(&self) -> bool {
         self.consensus_unprocessed_count.load(Ordering::SeqCst) != 0
     }
@@ -2033,6 +2087,60 @@ impl SynchronizationGraph {
         true
     }
 
+    /// Recover the graph from the blocks in db without syncing with peers, so
+    /// that the executed epochs can be inspected offline. It follows the
+    /// catch-up phases of a restarted node, and requires the checkpoint state
+    /// and all the block bodies to be in db.
+    ///
+    /// The consensus graph is left in the header only phase, so no epoch is
+    /// executed and only the stored execution results are used.
+    pub fn recover_graph_from_db_offline(&self) -> Result<(), String> {
+        self.recover_graph_from_db();
+        self.inner.write().locked_for_catchup = true;
+        while self.is_consensus_worker_busy() {
+            thread::sleep(Duration::from_millis(100));
+        }
+
+        let epoch_to_sync = self.consensus.get_to_sync_epoch_id();
+        if self
+            .data_man
+            .load_epoch_execution_commitment_from_db(&epoch_to_sync)
+            .is_none()
+        {
+            return Err(format!(
+                "the state of checkpoint {:?} is missing",
+                epoch_to_sync
+            ));
+        }
+        let synced = epoch_to_sync != self.data_man.true_genesis.hash();
+        let boundary_hash = if synced {
+            epoch_to_sync
+        } else {
+            self.data_man.get_cur_consensus_era_stable_hash()
+        };
+        let boundary_height = self
+            .data_man
+            .block_header_by_hash(&boundary_hash)
+            .expect("Header for checkpoint exists")
+            .height();
+        let storage_config = self.data_man.storage_manager.config();
+        let mut boundary = StateAvailabilityBoundary::new(
+            boundary_hash,
+            boundary_height,
+            storage_config.full_state_start_height(),
+            storage_config.single_mpt_space,
+        );
+        if synced {
+            boundary.set_synced_state_height(boundary_height);
+        }
+        *self.data_man.state_availability_boundary.write() = boundary;
+
+        if !self.consensus.get_blocks_needing_bodies().is_empty() {
+            return Err("some block bodies are missing".into());
+        }
+        Ok(())
+    }
+
     /// TODO(lpl): Only triggered when pos commits new blocks?
     /// Check if not_ready_frontier blocks become ready now.
     /// Blocks that are not ready because of missing pos references only become
@@ -367,6 +367,26 @@ impl SynchronizationPhaseTrait for CatchUpCheckpointPhase {
             if epoch_to_sync != sync_handler.graph.data_man.true_genesis.hash()
             {
                 *sync_handler.synced_epoch_id.lock() = Some(epoch_to_sync);
+
+                // The snapshot may have been restored before a restart, e.g.
+                // from a snapshot archive. Executing the next epoch needs
+                // the intermediate delta root of the restored snapshot,
+                // which is only kept in memory.
+                let storage_manager = sync_handler
+                    .graph
+                    .data_man
+                    .storage_manager
+                    .get_storage_manager();
+                let mut intermediate_trie_root_merkle =
+                    storage_manager.intermediate_trie_root_merkle.write();
+                if intermediate_trie_root_merkle.is_none() {
+                    *intermediate_trie_root_merkle = Some(
+                        commitment
+                            .state_root_with_aux_info
+                            .state_root
+                            .delta_root,
+                    );
+                }
             }
             return;
         }
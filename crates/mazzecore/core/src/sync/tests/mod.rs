@@ -3,14 +3,18 @@
 use crate::{
     block_data_manager::DbType,
     sync::{
-        utils::{create_simple_block_impl, initialize_synchronization_graph},
-        SynchronizationGraphNode,
+        utils::{
+            create_simple_block_impl, initialize_synchronization_graph,
+            remove_db_dir,
+        },
+        SynchronizationGraph, SynchronizationGraphNode,
     },
+    ConsensusGraph, ConsensusGraphTrait,
 };
 use mazze_types::{BigEndianHash, H256, U256};
 use primitives::Block;
 use std::{
-    fs,
+    collections::HashSet,
     sync::Arc,
     thread::sleep,
     time::{Duration, SystemTime, UNIX_EPOCH},
@@ -225,11 +229,90 @@ fn test_remove_expire_blocks() {
         }
     }
 
-    let mut retry = 3;
-    while let Err(e) = fs::remove_dir_all("./test.db") {
-        println!("failed to remove directory test.db, err = {:?}", e);
-        assert!(retry > 0);
-        retry -= 1;
-        sleep(Duration::from_millis(300));
+    remove_db_dir("./test.db");
+}
+
+fn insert_chain(
+    sync: &Arc<SynchronizationGraph>, consensus: &ConsensusGraph,
+    mut parent_hash: H256, mut height: u64, count: usize, nonce_base: u64,
+) -> Vec<H256> {
+    let mut hashes = Vec::new();
+    for _ in 0..count {
+        height += 1;
+        let (hash, mut block) = create_simple_block_impl(
+            parent_hash,
+            vec![],
+            height,
+            U256::from(nonce_base + height),
+            U256::from(10),
+            1,
+            false,
+        );
+        sync.insert_block_header(
+            &mut block.block_header,
+            false, // need_to_verify
+            true,  // bench_mode
+            false, // insert_to_consensus
+            true,  // persistent
+        );
+        let processed = consensus.get_processed_block_count();
+        sync.insert_block(
+            block, false, /* need_to_verify */
+            true,  /* persistent */
+            false, /* recover_from_db */
+        );
+        while consensus.get_processed_block_count() == processed {
+            sleep(Duration::from_millis(10));
+        }
+        hashes.push(hash);
+        parent_hash = hash;
     }
+    hashes
+}
+
+#[test]
+fn test_remove_blocks_and_rebuild_consensus() {
+    const DB_DIR: &str = "./test_rebuild_consensus.db/";
+    {
+        let (sync, consensus, data_man, genesis_block) =
+            initialize_synchronization_graph(
+                DB_DIR,
+                1,
+                1,
+                1,
+                1,
+                50000,
+                DbType::Rocksdb,
+            );
+        let hashes =
+            insert_chain(&sync, &consensus, genesis_block.hash(), 0, 6, 0);
+        assert_eq!(consensus.best_block_hash(), hashes[5]);
+        assert_eq!(consensus.best_epoch_number(), 6);
+
+        let to_remove: HashSet<H256> = vec![hashes[3]].into_iter().collect();
+        let removed: HashSet<H256> = sync
+            .remove_blocks_and_rebuild_consensus(&to_remove)
+            .into_iter()
+            .collect();
+        assert_eq!(removed, hashes[3..].iter().cloned().collect());
+
+        // The consensus graph is rebuilt from the remaining blocks.
+        assert_eq!(consensus.best_block_hash(), hashes[2]);
+        assert_eq!(consensus.best_epoch_number(), 3);
+        assert_eq!(sync.inner.read().hash_to_arena_indices.len(), 4);
+        for hash in &hashes[3..] {
+            assert!(data_man.block_header_by_hash(hash).is_none());
+            assert!(consensus.get_block_epoch_number(hash).is_none());
+        }
+        for hash in &hashes[..3] {
+            assert!(data_man.block_header_by_hash(hash).is_some());
+        }
+
+        // The chain grows again from the remaining blocks.
+        let new_hashes = insert_chain(&sync, &consensus, hashes[2], 3, 2, 1000);
+        assert_eq!(consensus.best_block_hash(), new_hashes[1]);
+        assert_eq!(consensus.best_epoch_number(), 5);
+    }
+
+    remove_db_dir(DB_DIR);
 }
@@ -1,5 +1,6 @@
 use std::{
-    collections::HashMap, path::Path, str::FromStr, sync::Arc, time::Duration,
+    collections::HashMap, fs, path::Path, str::FromStr, sync::Arc,
+    thread::sleep, time::Duration,
 };
 
 use parking_lot::Mutex;
@@ -40,7 +41,8 @@ use crate::{
     sync::{SyncGraphConfig, SynchronizationGraph},
     transaction_pool::TxPoolConfig,
     verification::VerificationConfig,
-    ConsensusGraph, NodeType, Notifications, TransactionPool,
+    ConsensusGraph, ConsensusGraphTrait, NodeType, Notifications,
+    TransactionPool,
 };
 use mazze_executor::{
     machine::{new_machine_with_builtin, VmFactory},
@@ -183,6 +185,24 @@ pub fn initialize_data_manager(
 pub fn initialize_synchronization_graph_with_data_manager(
     data_man: Arc<BlockDataManager>, beta: u64, h: u64, tcr: u64, tcb: u64,
     era_epoch_count: u64, pow: Arc<PowComputer>, vm: VmFactory,
+) -> (Arc<SynchronizationGraph>, Arc<ConsensusGraph>) {
+    initialize_synchronization_graph_impl(
+        data_man,
+        beta,
+        h,
+        tcr,
+        tcb,
+        era_epoch_count,
+        pow,
+        vm,
+        true, /* bench_mode */
+    )
+}
+
+fn initialize_synchronization_graph_impl(
+    data_man: Arc<BlockDataManager>, beta: u64, h: u64, tcr: u64, tcb: u64,
+    era_epoch_count: u64, pow: Arc<PowComputer>, vm: VmFactory,
+    bench_mode: bool,
 ) -> (Arc<SynchronizationGraph>, Arc<ConsensusGraph>) {
     let mut params = CommonParams::default();
     params.transition_heights.cip1559 = u64::MAX;
@@ -257,8 +277,8 @@ pub fn initialize_synchronization_graph_with_data_manager(
                 recovery_latest_mpt_snapshot: false,
                 use_isolated_db_for_mpt_table: false,
             },
-            bench_mode: true, /* Set bench_mode to true so that we skip
-                               * execution */
+            // The execution is skipped in bench_mode.
+            bench_mode,
             transaction_epoch_bound: TRANSACTION_DEFAULT_EPOCH_BOUND,
             referee_bound: REFEREE_DEFAULT_BOUND,
             get_logs_epoch_batch_size: 32,
@@ -267,6 +287,7 @@ pub fn initialize_synchronization_graph_with_data_manager(
             get_logs_filter_max_limit: None,
             sync_state_starting_epoch: None,
             sync_state_epoch_gap: None,
+            dev_mode: false,
         },
         txpool.clone(),
         statistics.clone(),
@@ -276,6 +297,7 @@ pub fn initialize_synchronization_graph_with_data_manager(
         notifications.clone(),
         ConsensusExecutionConfiguration {
             executive_trace: false,
+            parallel_execution: false,
         },
         verification_config.clone(),
         NodeType::Archive,
@@ -325,3 +347,94 @@ pub fn initialize_synchronization_graph(
 
     (sync, consensus, data_man, genesis_block)
 }
+
+/// This method is only used in tests. Unlike
+/// `initialize_synchronization_graph`, the epochs are executed, and the
+/// consensus graph is ready for the new blocks.
+pub fn initialize_executed_synchronization_graph(
+    db_dir: &str, era_epoch_count: u64,
+) -> (
+    Arc<SynchronizationGraph>,
+    Arc<ConsensusGraph>,
+    Arc<BlockDataManager>,
+    Arc<Block>,
+) {
+    let vm = VmFactory::new(1024 * 32);
+    let pow = Arc::new(PowComputer::new());
+
+    let (data_man, genesis_block) = initialize_data_manager(
+        db_dir,
+        DbType::Rocksdb,
+        pow.clone(),
+        vm.clone(),
+    );
+
+    let (sync, consensus) = initialize_synchronization_graph_impl(
+        data_man.clone(),
+        1,
+        1,
+        1,
+        1,
+        era_epoch_count,
+        pow,
+        vm,
+        false, /* bench_mode */
+    );
+    // There is nothing to recover, so the consensus graph leaves the header
+    // only phase at once.
+    consensus.construct_main_state();
+
+    (sync, consensus, data_man, genesis_block)
+}
+
+/// This method is only used in tests. Remove the database directory of a
+/// test, retrying while the database is still being closed.
+pub fn remove_db_dir(dir: &str) {
+    let mut retry = 3;
+    while let Err(e) = fs::remove_dir_all(dir) {
+        println!("failed to remove directory {}, err = {:?}", dir, e);
+        assert!(retry > 0);
+        retry -= 1;
+        sleep(Duration::from_millis(300));
+    }
+}
+
+/// This method is only used in tests. Insert a chain of `count` blocks after
+/// `parent_hash` into a graph made by
+/// `initialize_executed_synchronization_graph`, and return their hashes.
+pub fn insert_executed_chain(
+    sync: &SynchronizationGraph, consensus: &ConsensusGraph,
+    mut parent_hash: H256, count: u64,
+) -> Vec<H256> {
+    let mut hashes = Vec::new();
+    for height in 1..=count {
+        let (hash, mut block) = create_simple_block_impl(
+            parent_hash,
+            vec![],
+            height,
+            U256::from(height),
+            U256::from(10),
+            1,
+            false,
+        );
+        sync.insert_block_header(
+            &mut block.block_header,
+            false, // need_to_verify
+            true,  // bench_mode
+            false, // insert_to_consensus
+            true,  // persistent
+        );
+        let processed = consensus.get_processed_block_count();
+        sync.insert_block(
+            block, false, /* need_to_verify */
+            true,  /* persistent */
+            false, /* recover_from_db */
+        );
+        while consensus.get_processed_block_count() == processed {
+            sleep(Duration::from_millis(10));
+        }
+        hashes.push(hash);
+        parent_hash = hash;
+    }
+    hashes
+}
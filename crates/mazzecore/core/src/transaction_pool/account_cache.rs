@@ -36,4 +36,17 @@ impl AccountCache {
     ) -> DbResult<bool> {
         self.state.check_contract_whitelist(contract_address, user)
     }
+
+    pub fn get_eth_sponsor_info(
+        &self, contract_address: &Address,
+    ) -> DbResult<Option<SponsorInfo>> {
+        self.state.eth_sponsor_info(contract_address)
+    }
+
+    pub fn check_eth_commission_privilege(
+        &self, contract_address: &Address, user: &Address,
+    ) -> DbResult<bool> {
+        self.state
+            .check_eth_contract_whitelist(contract_address, user)
+    }
 }
@@ -42,11 +42,11 @@ use primitives::{
 };
 use std::{
     cmp::{max, min},
-    collections::{hash_map::HashMap, BTreeSet},
+    collections::{hash_map::HashMap, BTreeSet, HashSet},
     mem,
     ops::DerefMut,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicU64, Ordering},
         Arc,
     },
 };
@@ -134,6 +134,14 @@ pub struct TransactionPool {
     /// If it's `false`, operations on the tx pool will be ignored to save
     /// memory/CPU cost.
     ready_for_mining: AtomicBool,
+
+    /// Bumped whenever the set of packable transactions may have changed, so
+    /// that derived data (e.g. the pending block) knows when to refresh.
+    content_version: AtomicU64,
+
+    /// The senders whose transactions are accepted without a signature, set
+    /// by the dev mode cheat RPCs.
+    impersonated_senders: RwLock<HashSet<Address>>,
 }
 
 impl MallocSizeOf for TransactionPool {
@@ -193,6 +201,8 @@ impl TransactionPool {
             recycle_tx_requests: Mutex::new(Default::default()),
             machine,
             ready_for_mining: AtomicBool::new(false),
+            content_version: AtomicU64::new(0),
+            impersonated_senders: Default::default(),
         }
     }
 
@@ -461,6 +471,10 @@ impl TransactionPool {
             }
         }
 
+        if !passed_transactions.is_empty() {
+            self.bump_content_version();
+        }
+
         TX_POOL_DEFERRED_GAUGE.update(self.total_deferred(None));
         TX_POOL_UNPACKED_GAUGE.update(self.total_unpacked());
         TX_POOL_READY_GAUGE.update(self.total_ready_accounts());
@@ -582,6 +596,43 @@ impl TransactionPool {
         (passed_transactions, failure)
     }
 
+    pub fn impersonate_sender(&self, sender: Address) {
+        self.impersonated_senders.write().insert(sender);
+    }
+
+    /// Return `false` if `sender` is not impersonated.
+    pub fn stop_impersonating_sender(&self, sender: &Address) -> bool {
+        self.impersonated_senders.write().remove(sender)
+    }
+
+    pub fn is_impersonated_sender(&self, sender: &Address) -> bool {
+        self.impersonated_senders.read().contains(sender)
+    }
+
+    /// Insert an unsigned transaction of an impersonated sender. The
+    /// transaction is given a fake signature, which is not checked by the
+    /// locally mined blocks.
+    pub fn insert_impersonated_transaction(
+        &self, transaction: Transaction, sender: Address,
+    ) -> Result<Arc<SignedTransaction>, String> {
+        if transaction.space() != sender.space {
+            return Err("the sender is in another space".into());
+        }
+        if !self.is_impersonated_sender(&sender) {
+            return Err(format!("sender {:?} is not impersonated", sender));
+        }
+        let signed = Arc::new(match transaction {
+            Transaction::Native(tx) => tx.fake_sign_rpc(sender),
+            Transaction::Ethereum(tx) => tx.fake_sign_rpc(sender),
+        });
+        let (_, mut failure) =
+            self.insert_new_signed_transactions(vec![signed.clone()]);
+        match failure.remove(&signed.hash()) {
+            Some(e) => Err(e),
+            None => Ok(signed),
+        }
+    }
+
     /// verify transactions based on the rules that have nothing to do with
     /// readiness
     fn verify_transaction_tx_pool(
@@ -699,6 +750,7 @@ impl TransactionPool {
         for tx in transactions {
             recycle_req_buffer.push(tx);
         }
+        self.bump_content_version();
     }
 
     pub fn set_tx_packed(&self, transactions: &Vec<Arc<SignedTransaction>>) {
@@ -710,6 +762,7 @@ impl TransactionPool {
         for tx in transactions {
             tx_req_buffer.push(tx.clone());
         }
+        self.bump_content_version();
     }
 
     pub fn pack_transactions<'a>(
@@ -835,12 +888,24 @@ impl TransactionPool {
         &self, accounts_from_execution: Vec<Account>,
     ) {
         let mut inner = self.inner.write_with_metric(&NOTIFY_MODIFIED_LOCK);
-        inner.notify_modified_accounts(accounts_from_execution)
+        inner.notify_modified_accounts(accounts_from_execution);
+        self.bump_content_version();
     }
 
     pub fn clear_tx_pool(&self) {
         let mut inner = self.inner.write();
-        inner.clear()
+        inner.clear();
+        self.bump_content_version();
+    }
+
+    /// A counter that changes whenever the pool content or the best
+    /// information it packs against is updated.
+    pub fn content_version(&self) -> u64 {
+        self.content_version.load(Ordering::SeqCst)
+    }
+
+    fn bump_content_version(&self) {
+        self.content_version.fetch_add(1, Ordering::SeqCst);
     }
 
     pub fn total_deferred(&self, space: Option<Space>) -> usize {
@@ -954,6 +1019,7 @@ impl TransactionPool {
                 warn!("recycle tx err: e={:?}", e);
             }
         }
+        self.bump_content_version();
         debug!(
             "notify_new_best_info: {:?}",
             self.consensus_best_info.lock()
@@ -1167,3 +1233,76 @@ impl TransactionPool {
         self.ready_for_mining.store(true, Ordering::SeqCst);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        block_data_manager::DbType,
+        sync::utils::initialize_synchronization_graph,
+    };
+    use mazze_types::{Address, AddressSpaceUtil, U256};
+    use primitives::{
+        transaction::{Eip155Transaction, EthereumTransaction},
+        Action, Transaction,
+    };
+    use std::fs;
+
+    fn eth_transaction() -> Transaction {
+        Transaction::Ethereum(EthereumTransaction::Eip155(Eip155Transaction {
+            nonce: U256::zero(),
+            gas_price: U256::one(),
+            gas: 21000.into(),
+            action: Action::Call(Address::random()),
+            value: U256::zero(),
+            chain_id: Some(1),
+            data: vec![],
+        }))
+    }
+
+    #[test]
+    fn test_impersonated_senders() {
+        const DB_DIR: &str = "./test_impersonated_senders.db/";
+        {
+            let (_, consensus, _, _) = initialize_synchronization_graph(
+                DB_DIR,
+                1,
+                1,
+                1,
+                1,
+                50000,
+                DbType::Rocksdb,
+            );
+            let txpool = &consensus.txpool;
+            let sender = Address::random().with_evm_space();
+
+            let err = txpool
+                .insert_impersonated_transaction(eth_transaction(), sender)
+                .unwrap_err();
+            assert!(err.contains("is not impersonated"), "{}", err);
+
+            txpool.impersonate_sender(sender);
+            assert!(txpool.is_impersonated_sender(&sender));
+            // The impersonation is per space.
+            let native_sender = sender.address.with_native_space();
+            assert!(!txpool.is_impersonated_sender(&native_sender));
+            txpool.impersonate_sender(native_sender);
+            let err = txpool
+                .insert_impersonated_transaction(
+                    eth_transaction(),
+                    native_sender,
+                )
+                .unwrap_err();
+            assert!(err.contains("another space"), "{}", err);
+
+            assert!(txpool.stop_impersonating_sender(&sender));
+            assert!(!txpool.stop_impersonating_sender(&sender));
+            assert!(!txpool.is_impersonated_sender(&sender));
+            let err = txpool
+                .insert_impersonated_transaction(eth_transaction(), sender)
+                .unwrap_err();
+            assert!(err.contains("is not impersonated"), "{}", err);
+        }
+
+        fs::remove_dir_all(DB_DIR).unwrap();
+    }
+}
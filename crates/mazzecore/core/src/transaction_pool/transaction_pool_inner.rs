@@ -1404,7 +1404,9 @@ impl TransactionPoolInner {
                 }
                 Transaction::Ethereum(ref utx) => {
                     need_balance += utx.value().clone();
-                    need_balance += estimate_gas_fee;
+                    if sponsored_gas == U256::from(0) {
+                        need_balance += estimate_gas_fee;
+                    }
                 }
             }
 
@@ -1505,6 +1507,33 @@ impl TransactionPoolInner {
                     }
                 }
             }
+        } else if let Action::Call(ref callee) = transaction.action() {
+            // The eSpace contracts are only sponsored for gas.
+            if let Some(sponsor_info) =
+                account_cache.get_eth_sponsor_info(callee).map_err(|e| {
+                    format!("Failed to read account_cache from storage: {}", e)
+                })?
+            {
+                if account_cache
+                    .check_eth_commission_privilege(&callee, &sender.address)
+                    .map_err(|e| {
+                        format!(
+                            "Failed to read account_cache from storage: {}",
+                            e
+                        )
+                    })?
+                {
+                    let estimated_gas = Self::estimated_gas_fee(
+                        transaction.gas().clone(),
+                        transaction.gas_price().clone(),
+                    );
+                    if estimated_gas <= sponsor_info.sponsor_gas_bound
+                        && estimated_gas <= sponsor_info.sponsor_balance_for_gas
+                    {
+                        sponsored_gas = transaction.gas().clone();
+                    }
+                }
+            }
         }
         Ok((sponsored_gas, sponsored_storage))
     }
@@ -680,6 +680,10 @@ impl VerificationConfig {
                     return false;
                 }
 
+                if !Self::check_eip7702_transaction(tx, mode) {
+                    return false;
+                }
+
                 if let Transaction::Native(ref tx) = tx.unsigned {
                     Self::verify_transaction_epoch_height(
                         tx,
@@ -777,6 +781,10 @@ impl VerificationConfig {
             bail!(TransactionError::FutureTransactionType)
         }
 
+        if !Self::check_eip7702_transaction(tx, &mode) {
+            bail!(TransactionError::FutureTransactionType)
+        }
+
         Self::check_gas_limit(tx, cip76, &mode)?;
         Self::check_gas_limit_with_calldata(tx, cip130)?;
         Ok(())
@@ -812,6 +820,24 @@ impl VerificationConfig {
         }
     }
 
+    /// EIP-7702 activates at a block number, so only the packing mode knows
+    /// whether it is active. In the other modes, the executor rejects the
+    /// transaction if it is still inactive.
+    fn check_eip7702_transaction(
+        tx: &TransactionWithSignature, mode: &VerifyTxMode,
+    ) -> bool {
+        if tx.authorization_list().is_none() {
+            return true;
+        }
+
+        use VerifyTxLocalMode::*;
+        match mode {
+            VerifyTxMode::Local(Full, spec) => spec.eip7702,
+            VerifyTxMode::Local(MaybeLater, _spec) => true,
+            VerifyTxMode::Remote => true,
+        }
+    }
+
     /// Check transaction intrinsic gas. Influenced by CIP-76.
     fn check_gas_limit(
         tx: &TransactionWithSignature, cip76: bool, mode: &VerifyTxMode,
@@ -832,6 +858,7 @@ impl VerificationConfig {
                 *tx.action() == Action::Create,
                 &tx.data(),
                 tx.access_list(),
+                tx.authorization_list(),
                 &spec,
             );
             if *tx.gas() < (tx_intrinsic_gas as usize).into() {
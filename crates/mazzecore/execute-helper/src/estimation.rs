@@ -8,7 +8,8 @@ use mazze_executor::{
 };
 
 use super::observer::{
-    exec_tracer::ErrorUnwind, gasman::GasLimitEstimation, Observer,
+    exec_tracer::ErrorUnwind, gas_profiler::GasProfiler,
+    gasman::GasLimitEstimation, Observer,
 };
 use mazze_parameters::{consensus::ONE_MAZZE_IN_MAZZY, staking::*};
 use mazze_statedb::Result as DbResult;
@@ -450,6 +451,8 @@ pub struct EstimateRequest {
     pub has_gas_price: bool,
     pub has_nonce: bool,
     pub has_storage_limit: bool,
+    /// Profile the gas used by the execution, see [`GasProfiler`].
+    pub profile_gas: bool,
 }
 
 impl EstimateRequest {
@@ -470,16 +473,24 @@ impl EstimateRequest {
         }
     }
 
+    fn observer(self) -> Observer {
+        let mut observer = Observer::virtual_call();
+        if self.profile_gas {
+            observer.gas_profiler = Some(GasProfiler::default());
+        }
+        observer
+    }
+
     fn first_pass_options(self) -> TransactOptions<Observer> {
         TransactOptions {
-            observer: Observer::virtual_call(),
+            observer: self.observer(),
             settings: self.transact_settings(ChargeCollateral::EstimateSender),
         }
     }
 
     pub fn second_pass_options(self) -> TransactOptions<Observer> {
         TransactOptions {
-            observer: Observer::virtual_call(),
+            observer: self.observer(),
             settings: self.transact_settings(ChargeCollateral::EstimateSponsor),
         }
     }
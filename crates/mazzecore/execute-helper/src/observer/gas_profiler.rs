@@ -0,0 +1,514 @@
+use mazze_executor::{
+    observer::{
+        CallTracer, CheckpointTracer, DrainTrace, InternalTransferTracer,
+        OpcodeTracer, StorageTracer,
+    },
+    stack::FrameResult,
+};
+use mazze_types::{Address, U256};
+use mazze_vm_interpreter::instructions::Instruction;
+use mazze_vm_types::{ActionParams, InterpreterInfo};
+use std::{collections::BTreeMap, fmt};
+
+use typemap::ShareDebugMap;
+
+/// The function executed by a frame, identified by the 4-byte selector of
+/// its call data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Function {
+    Constructor,
+    /// A call with less than 4 bytes of call data.
+    Fallback,
+    Selector([u8; 4]),
+}
+
+impl Function {
+    fn from_params(params: &ActionParams, is_create: bool) -> Self {
+        if is_create {
+            return Function::Constructor;
+        }
+        match params.data.as_ref() {
+            Some(data) if data.len() >= 4 => {
+                Function::Selector([data[0], data[1], data[2], data[3]])
+            }
+            _ => Function::Fallback,
+        }
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Function::Constructor => write!(f, "constructor"),
+            Function::Fallback => write!(f, "fallback"),
+            Function::Selector(selector) => write!(
+                f,
+                "0x{:02x}{:02x}{:02x}{:02x}",
+                selector[0], selector[1], selector[2], selector[3]
+            ),
+        }
+    }
+}
+
+/// A function of a contract. The contract is the address of the executed
+/// code, so a `DELEGATECALL` is accounted to the library.
+pub type FunctionId = (Address, Function);
+
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct GasStat {
+    pub gas: U256,
+    pub steps: u64,
+    pub calls: u64,
+}
+
+impl GasStat {
+    fn accumulate(&mut self, other: &GasStat) {
+        self.gas += other.gas;
+        self.steps += other.steps;
+        self.calls += other.calls;
+    }
+}
+
+/// The gas consumed by the execution of a transaction. Except for
+/// `gas_used`, the gas of a frame excludes the gas used by its sub-calls.
+#[derive(Default, Clone, Debug)]
+pub struct GasProfile {
+    /// The gas used by the execution, without the intrinsic gas.
+    pub gas_used: U256,
+    pub contracts: BTreeMap<Address, GasStat>,
+    pub functions: BTreeMap<FunctionId, GasStat>,
+    pub depths: BTreeMap<usize, GasStat>,
+    /// The gas of each opcode. The cost of a call or create opcode excludes
+    /// the gas used by the sub-call, and also the stipend given with a value
+    /// transfer.
+    pub opcodes: BTreeMap<u8, GasStat>,
+    /// The gas of each call stack, from the outermost function.
+    pub stacks: BTreeMap<Vec<FunctionId>, U256>,
+}
+
+impl GasProfile {
+    /// The call stacks in the folded format of the flame graph tools, one
+    /// `contract:function;...;contract:function gas` line per stack.
+    pub fn folded(&self) -> String {
+        let mut folded = String::new();
+        for (stack, gas) in &self.stacks {
+            let frames: Vec<_> = stack
+                .iter()
+                .map(|(address, function)| {
+                    format!("{:?}:{}", address, function)
+                })
+                .collect();
+            folded.push_str(&format!("{} {}\n", frames.join(";"), gas));
+        }
+        folded
+    }
+}
+
+pub fn opcode_name(opcode: u8) -> String {
+    match Instruction::from_u8(opcode) {
+        Some(instruction) => instruction.info::<true>().name.into(),
+        None => format!("0x{:02x}", opcode),
+    }
+}
+
+struct FrameGas {
+    function: FunctionId,
+    gas_limit: U256,
+    gas_cost_in_subcall: U256,
+    steps: u64,
+    /// The opcode being executed and the gas before it. Its cost is known
+    /// at the next step, when the gas returned by a sub-call or an unused
+    /// gas refund has been added back.
+    pending_opcode: Option<(u8, U256)>,
+    /// The gas used by the sub-calls of the pending opcode.
+    pending_subcall_cost: U256,
+}
+
+impl FrameGas {
+    fn settle_pending_opcode(
+        &mut self, gas_left: &U256, opcodes: &mut BTreeMap<u8, GasStat>,
+    ) {
+        if let Some((opcode, gas_before)) = self.pending_opcode.take() {
+            let stat = opcodes.entry(opcode).or_default();
+            stat.gas += gas_before
+                .saturating_sub(*gas_left)
+                .saturating_sub(self.pending_subcall_cost);
+            stat.steps += 1;
+        }
+        self.pending_subcall_cost = U256::zero();
+    }
+}
+
+/// Aggregates the gas used by a transaction by contract, function, call
+/// depth and opcode.
+#[derive(Default)]
+pub struct GasProfiler {
+    frames: Vec<FrameGas>,
+    profile: GasProfile,
+}
+
+impl DrainTrace for GasProfiler {
+    fn drain_trace(self, map: &mut ShareDebugMap) {
+        map.insert::<GasProfileKey>(self.profile);
+    }
+}
+
+pub struct GasProfileKey;
+
+impl typemap::Key for GasProfileKey {
+    type Value = GasProfile;
+}
+
+impl GasProfiler {
+    fn record_frame_start(&mut self, params: &ActionParams, is_create: bool) {
+        self.frames.push(FrameGas {
+            function: (
+                params.code_address,
+                Function::from_params(params, is_create),
+            ),
+            gas_limit: params.gas,
+            gas_cost_in_subcall: U256::zero(),
+            steps: 0,
+            pending_opcode: None,
+            pending_subcall_cost: U256::zero(),
+        });
+    }
+
+    fn record_frame_end(&mut self, result: &FrameResult) {
+        let gas_left =
+            result.as_ref().map_or(U256::zero(), |r| r.gas_left.clone());
+        let mut frame = self.frames.pop().expect("frame has started");
+        let profile = &mut self.profile;
+        frame.settle_pending_opcode(&gas_left, &mut profile.opcodes);
+
+        // Due to gas stipend, the gas_left could be larger than gas limit.
+        let gas_cost = frame.gas_limit.saturating_sub(gas_left);
+        let stat = GasStat {
+            gas: gas_cost.saturating_sub(frame.gas_cost_in_subcall),
+            steps: frame.steps,
+            calls: 1,
+        };
+        let (address, _) = frame.function;
+        profile
+            .contracts
+            .entry(address)
+            .or_default()
+            .accumulate(&stat);
+        profile
+            .functions
+            .entry(frame.function)
+            .or_default()
+            .accumulate(&stat);
+        profile
+            .depths
+            .entry(self.frames.len())
+            .or_default()
+            .accumulate(&stat);
+
+        let stack = self
+            .frames
+            .iter()
+            .map(|frame| frame.function)
+            .chain(std::iter::once(frame.function))
+            .collect();
+        *profile.stacks.entry(stack).or_default() += stat.gas;
+
+        match self.frames.last_mut() {
+            Some(parent) => {
+                parent.gas_cost_in_subcall += gas_cost;
+                parent.pending_subcall_cost += gas_cost;
+            }
+            None => profile.gas_used = gas_cost,
+        }
+    }
+}
+
+impl CallTracer for GasProfiler {
+    fn record_call(&mut self, params: &ActionParams) {
+        self.record_frame_start(params, false);
+    }
+
+    fn record_call_result(&mut self, result: &FrameResult) {
+        self.record_frame_end(result);
+    }
+
+    fn record_create(&mut self, params: &ActionParams) {
+        self.record_frame_start(params, true);
+    }
+
+    fn record_create_result(&mut self, result: &FrameResult) {
+        self.record_frame_end(result);
+    }
+}
+
+impl OpcodeTracer for GasProfiler {
+    fn do_trace_opcode(&self, enabled: &mut bool) { *enabled = true; }
+
+    fn step(&mut self, interp: &dyn InterpreterInfo) {
+        let frame = match self.frames.last_mut() {
+            Some(frame) => frame,
+            None => return,
+        };
+        let gas = interp.gas_remainning();
+        frame.settle_pending_opcode(&gas, &mut self.profile.opcodes);
+        frame.pending_opcode = Some((interp.current_opcode(), gas));
+        frame.steps += 1;
+    }
+}
+
+impl CheckpointTracer for GasProfiler {}
+impl InternalTransferTracer for GasProfiler {}
+impl StorageTracer for GasProfiler {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mazze_executor::stack::FrameReturn;
+    use mazze_types::Space;
+    use mazze_vm_types::{Error as VmError, ReturnData};
+
+    struct Interp {
+        opcode: u8,
+        gas: u64,
+        mem: Vec<u8>,
+        stack: Vec<U256>,
+        return_stack: Vec<usize>,
+    }
+
+    impl InterpreterInfo for Interp {
+        fn gas_remainning(&self) -> U256 { self.gas.into() }
+
+        fn program_counter(&self) -> u64 { 0 }
+
+        fn current_opcode(&self) -> u8 { self.opcode }
+
+        fn opcode(&self, _pc: u64) -> Option<u8> { None }
+
+        fn mem(&self) -> &Vec<u8> { &self.mem }
+
+        fn stack(&self) -> &Vec<U256> { &self.stack }
+
+        fn return_stack(&self) -> &Vec<usize> { &self.return_stack }
+
+        fn contract_address(&self) -> Address { Address::zero() }
+    }
+
+    fn call(address: Address, data: Vec<u8>, gas: u64) -> ActionParams {
+        ActionParams {
+            code_address: address,
+            address,
+            gas: gas.into(),
+            data: Some(data),
+            ..Default::default()
+        }
+    }
+
+    fn frame_return(gas_left: u64) -> FrameResult {
+        Ok(FrameReturn {
+            space: Space::Ethereum,
+            gas_left: gas_left.into(),
+            apply_state: true,
+            return_data: ReturnData::empty(),
+            create_address: None,
+            substate: None,
+        })
+    }
+
+    fn step(profiler: &mut GasProfiler, opcode: u8, gas: u64) {
+        profiler.step(&Interp {
+            opcode,
+            gas,
+            mem: vec![],
+            stack: vec![],
+            return_stack: vec![],
+        });
+    }
+
+    #[test]
+    fn test_gas_profile_of_nested_call() {
+        let caller = Address::from_low_u64_be(1);
+        let callee = Address::from_low_u64_be(2);
+        let mut profiler = GasProfiler::default();
+
+        profiler.record_call(&call(caller, vec![1, 2, 3, 4, 5], 100_000));
+        step(&mut profiler, 0x60, 100_000); // PUSH1
+        step(&mut profiler, 0xf1, 99_997); // CALL
+        profiler.record_call(&call(callee, vec![], 50_000));
+        step(&mut profiler, 0x55, 50_000); // SSTORE
+        profiler.record_call_result(&frame_return(30_000));
+        step(&mut profiler, 0x00, 79_297); // STOP
+        profiler.record_call_result(&frame_return(79_297));
+
+        let profile = profiler.profile;
+        assert_eq!(profile.gas_used, 20_703.into());
+
+        let opcode_gas = |opcode| profile.opcodes[&opcode].gas;
+        assert_eq!(opcode_gas(0x60), 3.into());
+        assert_eq!(opcode_gas(0xf1), 700.into());
+        assert_eq!(opcode_gas(0x55), 20_000.into());
+        assert_eq!(opcode_gas(0x00), 0.into());
+
+        let selector = Function::Selector([1, 2, 3, 4]);
+        assert_eq!(
+            profile.functions[&(caller, selector)],
+            GasStat {
+                gas: 703.into(),
+                steps: 3,
+                calls: 1
+            }
+        );
+        assert_eq!(profile.contracts[&callee].gas, 20_000.into());
+        assert_eq!(profile.depths[&1].steps, 1);
+
+        assert_eq!(
+            profile.folded(),
+            format!(
+                "{:?}:0x01020304 703\n\
+                 {:?}:0x01020304;{:?}:fallback 20000\n",
+                caller, caller, callee
+            )
+        );
+        assert_eq!(opcode_name(0xf1), "CALL");
+    }
+
+    #[test]
+    fn test_gas_profile_of_delegate_call_and_failed_call() {
+        let caller = Address::from_low_u64_be(1);
+        let callee = Address::from_low_u64_be(2);
+        let library = Address::from_low_u64_be(3);
+        let mut profiler = GasProfiler::default();
+
+        profiler.record_call(&call(caller, vec![0xaa; 4], 100_000));
+        step(&mut profiler, 0x60, 100_000); // PUSH1
+        step(&mut profiler, 0xf1, 99_997); // CALL
+        profiler.record_call(&call(callee, vec![0xbb; 4], 60_000));
+        step(&mut profiler, 0xf4, 60_000); // DELEGATECALL
+        profiler.record_call(&ActionParams {
+            code_address: library,
+            address: callee,
+            gas: 40_000.into(),
+            data: Some(vec![0xcc; 4]),
+            ..Default::default()
+        });
+        step(&mut profiler, 0x55, 40_000); // SSTORE
+        profiler.record_call_result(&frame_return(37_900));
+        step(&mut profiler, 0xfe, 57_300); // INVALID
+        profiler.record_call_result(&Err(VmError::BadInstruction {
+            instruction: 0xfe,
+        }));
+        step(&mut profiler, 0x00, 39_297); // STOP
+        profiler.record_call_result(&frame_return(39_297));
+
+        let profile = profiler.profile;
+        assert_eq!(profile.gas_used, 60_703.into());
+
+        // The failed call consumes all of its gas.
+        let opcode_gas = |opcode| profile.opcodes[&opcode].gas;
+        assert_eq!(opcode_gas(0xf1), 700.into());
+        assert_eq!(opcode_gas(0xf4), 600.into());
+        assert_eq!(opcode_gas(0xfe), 57_300.into());
+        assert_eq!(profile.contracts[&callee].gas, 57_900.into());
+
+        // The delegate call is accounted to the library.
+        let library_function = (library, Function::Selector([0xcc; 4]));
+        assert_eq!(
+            profile.functions[&library_function],
+            GasStat {
+                gas: 2_100.into(),
+                steps: 1,
+                calls: 1
+            }
+        );
+        assert!(!profile
+            .functions
+            .contains_key(&(callee, Function::Selector([0xcc; 4]))));
+
+        let depth_gas = |depth| profile.depths[&depth].gas;
+        assert_eq!(depth_gas(0), 703.into());
+        assert_eq!(depth_gas(1), 57_900.into());
+        assert_eq!(depth_gas(2), 2_100.into());
+
+        let stack = vec![
+            (caller, Function::Selector([0xaa; 4])),
+            (callee, Function::Selector([0xbb; 4])),
+            library_function,
+        ];
+        assert_eq!(profile.stacks[&stack], 2_100.into());
+    }
+
+    #[test]
+    fn test_gas_profile_of_repeated_calls_and_create() {
+        let caller = Address::from_low_u64_be(1);
+        let callee = Address::from_low_u64_be(2);
+        let created = Address::from_low_u64_be(3);
+        let mut profiler = GasProfiler::default();
+
+        profiler.record_call(&call(caller, vec![], 100_000));
+        let mut gas = 100_000;
+        for _ in 0..2 {
+            step(&mut profiler, 0xf1, gas); // CALL
+            profiler.record_call(&call(callee, vec![1; 4], 10_000));
+            step(&mut profiler, 0x54, 10_000); // SLOAD
+            profiler.record_call_result(&frame_return(7_900));
+            gas -= 700 + 2_100;
+        }
+        step(&mut profiler, 0xf0, gas); // CREATE
+        profiler.record_create(&call(created, vec![], 50_000));
+        step(&mut profiler, 0xf3, 50_000); // RETURN
+        profiler.record_create_result(&frame_return(40_000));
+        step(&mut profiler, 0x00, 52_400); // STOP
+        profiler.record_call_result(&frame_return(52_400));
+
+        let profile = profiler.profile;
+        assert_eq!(profile.gas_used, 47_600.into());
+
+        // The calls to the same function are aggregated.
+        assert_eq!(
+            profile.functions[&(callee, Function::Selector([1; 4]))],
+            GasStat {
+                gas: 4_200.into(),
+                steps: 2,
+                calls: 2
+            }
+        );
+        assert_eq!(
+            profile.functions[&(caller, Function::Fallback)],
+            GasStat {
+                gas: 33_400.into(),
+                steps: 4,
+                calls: 1
+            }
+        );
+        assert_eq!(
+            profile.functions[&(created, Function::Constructor)].gas,
+            10_000.into()
+        );
+        assert_eq!(
+            profile.opcodes[&0xf1],
+            GasStat {
+                gas: 1_400.into(),
+                steps: 2,
+                calls: 0
+            }
+        );
+        assert_eq!(profile.opcodes[&0xf0].gas, 32_000.into());
+        assert_eq!(
+            profile.depths[&1],
+            GasStat {
+                gas: 14_200.into(),
+                steps: 3,
+                calls: 3
+            }
+        );
+
+        assert_eq!(
+            profile.folded(),
+            format!(
+                "{:?}:fallback 33400\n\
+                 {:?}:fallback;{:?}:0x01010101 4200\n\
+                 {:?}:fallback;{:?}:constructor 10000\n",
+                caller, caller, callee, caller, created
+            )
+        );
+    }
+}
@@ -1,8 +1,10 @@
 pub mod exec_tracer;
+pub mod gas_profiler;
 pub mod gasman;
 mod utils;
 
 use exec_tracer::ExecTracer;
+use gas_profiler::GasProfiler;
 use gasman::GasMan;
 
 use mazze_executor::{
@@ -20,6 +22,7 @@ pub struct Observer {
     pub tracer: Option<ExecTracer>,
     pub gas_man: Option<GasMan>,
     pub geth_tracer: Option<GethTracer>,
+    pub gas_profiler: Option<GasProfiler>,
 }
 
 impl Observer {
@@ -28,6 +31,7 @@ impl Observer {
             tracer: Some(ExecTracer::default()),
             gas_man: None,
             geth_tracer: None,
+            gas_profiler: None,
         }
     }
 
@@ -36,6 +40,7 @@ impl Observer {
             tracer: None,
             gas_man: None,
             geth_tracer: None,
+            gas_profiler: None,
         }
     }
 
@@ -44,6 +49,7 @@ impl Observer {
             tracer: Some(ExecTracer::default()),
             gas_man: Some(GasMan::default()),
             geth_tracer: None,
+            gas_profiler: None,
         }
     }
 
@@ -55,6 +61,7 @@ impl Observer {
             tracer: None,
             gas_man: None,
             geth_tracer: Some(GethTracer::new(tx_exec_context, machine, opts)),
+            gas_profiler: None,
         }
     }
 }
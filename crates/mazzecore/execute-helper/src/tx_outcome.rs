@@ -10,7 +10,10 @@ use alloy_rpc_types_trace::geth::GethTrace;
 use geth_tracer::GethTraceKey;
 
 use super::{
-    observer::exec_tracer::{ExecTrace, ExecTraceKey},
+    observer::{
+        exec_tracer::{ExecTrace, ExecTraceKey},
+        gas_profiler::{GasProfile, GasProfileKey},
+    },
     phantom_tx::{recover_phantom, PhantomTransaction},
 };
 
@@ -22,6 +25,7 @@ pub struct ProcessTxOutcome {
     pub tx_exec_error_msg: String,
     pub consider_repacked: bool,
     pub geth_trace: Option<GethTrace>,
+    pub gas_profile: Option<GasProfile>,
 }
 
 fn tx_traces(outcome: &ExecutionOutcome) -> Vec<ExecTrace> {
@@ -37,12 +41,19 @@ fn geth_traces(outcome: &ExecutionOutcome) -> Option<GethTrace> {
         .and_then(|executed| executed.ext_result.get::<GethTraceKey>().cloned())
 }
 
+fn gas_profile(outcome: &ExecutionOutcome) -> Option<GasProfile> {
+    outcome.try_as_executed().and_then(|executed| {
+        executed.ext_result.get::<GasProfileKey>().cloned()
+    })
+}
+
 pub fn make_process_tx_outcome(
     outcome: ExecutionOutcome, accumulated_gas_used: &mut U256, tx_hash: H256,
     spec: &Spec,
 ) -> ProcessTxOutcome {
     let tx_traces = tx_traces(&outcome);
     let geth_trace = geth_traces(&outcome);
+    let gas_profile = gas_profile(&outcome);
     let tx_exec_error_msg = outcome.error_message();
     let consider_repacked = outcome.consider_repacked();
     let receipt = outcome.make_receipt(accumulated_gas_used, spec);
@@ -59,5 +70,6 @@ pub fn make_process_tx_outcome(
         tx_exec_error_msg,
         consider_repacked,
         geth_trace,
+        gas_profile,
     }
 }
@@ -0,0 +1,184 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use mazze_executor::{
+    executive::{ExecutionOutcome, ExecutiveContext, TransactOptions},
+    machine::{new_machine_with_builtin, Machine, VmFactory},
+    parallel::execute_in_parallel,
+    state::{CleanupMode, State},
+};
+use mazze_statedb::StateDb;
+use mazze_storage::{
+    state_manager::StateManagerTrait, tests::new_state_manager_for_unit_test,
+    StateIndex, StorageManager,
+};
+use mazze_types::{AddressSpaceUtil, U256};
+use mazze_vm_types::{Env, Spec};
+use mazzekey::{Generator, KeyPair, Random};
+use primitives::{
+    transaction::{Action, Eip155Transaction},
+    EpochId, SignedTransaction, Transaction,
+};
+use std::sync::Arc;
+
+/// The number of transactions in the benchmarked block.
+const BLOCK_SIZE: usize = 512;
+
+struct BenchContext {
+    machine: Machine,
+    env: Env,
+    spec: Spec,
+}
+
+impl BenchContext {
+    fn new() -> Self {
+        let machine = new_machine_with_builtin(
+            Default::default(),
+            VmFactory::new(1024 * 32),
+        );
+        let mut env = Env::default();
+        env.gas_limit = U256::MAX;
+        let spec = machine.spec(env.number, env.epoch_height);
+        BenchContext { machine, env, spec }
+    }
+
+    fn execute(
+        &self, state: &mut State, tx: &SignedTransaction,
+    ) -> ExecutionOutcome {
+        ExecutiveContext::new(state, &self.env, &self.machine, &self.spec)
+            .transact(tx, TransactOptions::default())
+            .expect("no db error")
+    }
+}
+
+fn burn_fee(state: &mut State, outcome: &ExecutionOutcome) {
+    if let Some(burnt_fee) =
+        outcome.try_as_executed().and_then(|e| e.burnt_fee)
+    {
+        state.burn_by_cip1559(burnt_fee);
+    }
+}
+
+fn open_state_db(
+    storage_manager: &Arc<StorageManager>, parent: &EpochId,
+) -> StateDb {
+    StateDb::new(
+        storage_manager
+            .get_state_for_next_epoch(
+                StateIndex::new_for_test_only_delta_mpt(parent),
+                false,
+            )
+            .unwrap()
+            .unwrap(),
+    )
+}
+
+/// Commit a state where every sender has an eSpace balance, and return the
+/// committed epoch.
+fn commit_funded_state(
+    storage_manager: &Arc<StorageManager>, senders: &[KeyPair],
+) -> EpochId {
+    let genesis = EpochId::default();
+    State::new(StateDb::new(storage_manager.get_state_for_genesis_write()))
+        .unwrap()
+        .commit(genesis, None)
+        .unwrap();
+
+    let balance = U256::from(10).pow(24.into());
+    let mut state =
+        State::new(open_state_db(storage_manager, &genesis)).unwrap();
+    for sender in senders {
+        state
+            .add_balance(
+                &sender.evm_address().with_evm_space(),
+                &balance,
+                CleanupMode::NoEmpty,
+            )
+            .unwrap();
+        state.add_total_issued(balance);
+        state.add_total_evm_tokens(balance);
+    }
+    let epoch_id = EpochId::from_low_u64_be(1);
+    state.commit(epoch_id, None).unwrap();
+    epoch_id
+}
+
+/// A block of eSpace transfers from distinct senders to fresh recipients.
+/// Each of them subtracts its fee from the total eSpace tokens.
+fn eth_transfer_block(senders: &[KeyPair]) -> Vec<Arc<SignedTransaction>> {
+    senders
+        .iter()
+        .map(|sender| {
+            Arc::new(
+                Transaction::from(Eip155Transaction {
+                    nonce: U256::zero(),
+                    gas_price: U256::one(),
+                    gas: U256::from(21_000),
+                    action: Action::Call(
+                        Random.generate().unwrap().evm_address(),
+                    ),
+                    value: U256::from(1_000),
+                    chain_id: Some(1),
+                    data: vec![],
+                })
+                .sign(sender.secret()),
+            )
+        })
+        .collect()
+}
+
+fn bench_parallel_execution(c: &mut Criterion) {
+    let context = BenchContext::new();
+    let state_manager = new_state_manager_for_unit_test();
+    let storage_manager: &Arc<StorageManager> = &state_manager;
+    let senders: Vec<_> =
+        (0..BLOCK_SIZE).map(|_| Random.generate().unwrap()).collect();
+    let parent = commit_funded_state(storage_manager, &senders);
+    let block = eth_transfer_block(&senders);
+    let new_state =
+        || State::new(open_state_db(storage_manager, &parent)).unwrap();
+
+    c.bench_function("eth transfers serial", |b| {
+        b.iter_batched(
+            new_state,
+            |mut state| {
+                for tx in &block {
+                    let outcome = context.execute(&mut state, tx);
+                    burn_fee(&mut state, &outcome);
+                }
+                state
+            },
+            BatchSize::PerIteration,
+        );
+    });
+
+    c.bench_function("eth transfers parallel", |b| {
+        b.iter_batched(
+            new_state,
+            |mut state| {
+                let stats = execute_in_parallel(
+                    &mut state,
+                    &block,
+                    |tx| tx.sender(),
+                    |seed| {
+                        State::from_seed(
+                            open_state_db(storage_manager, &parent),
+                            seed,
+                        )
+                    },
+                    |state, _, tx| Ok(context.execute(state, tx)),
+                    |state, _, outcome: ExecutionOutcome| {
+                        burn_fee(state, &outcome);
+                        Ok(())
+                    },
+                )
+                .expect("no db error");
+                // The transactions must not conflict.
+                assert_eq!(stats.re_executed, 0);
+                state
+            },
+            BatchSize::PerIteration,
+        );
+    });
+}
+
+criterion_group!(benches, bench_parallel_execution);
+criterion_main!(benches);
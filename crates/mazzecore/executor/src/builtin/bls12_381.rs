@@ -0,0 +1,518 @@
+//! BLS12-381 curve operations of EIP-2537, backed by `blst`.
+//!
+//! A base field element is encoded in 64 bytes: 16 zero bytes followed by
+//! the 48-byte big-endian value, which must be smaller than the modulus. A G1
+//! point is encoded as `x ++ y`, a G2 point as `x.c0 ++ x.c1 ++ y.c0 ++
+//! y.c1`, and the point at infinity as all zeros. Scalars are 32-byte
+//! big-endian integers.
+
+use super::Error;
+use blst::{
+    blst_bendian_from_fp, blst_final_exp, blst_fp, blst_fp12, blst_fp12_is_one,
+    blst_fp12_mul, blst_fp2, blst_fp_from_bendian, blst_map_to_g1,
+    blst_map_to_g2, blst_miller_loop, blst_p1, blst_p1_add_or_double_affine,
+    blst_p1_affine, blst_p1_affine_in_g1, blst_p1_affine_on_curve,
+    blst_p1_from_affine, blst_p1_mult, blst_p1_to_affine,
+    blst_p1s_mult_pippenger, blst_p1s_mult_pippenger_scratch_sizeof, blst_p2,
+    blst_p2_add_or_double_affine, blst_p2_affine, blst_p2_affine_in_g2,
+    blst_p2_affine_on_curve, blst_p2_from_affine, blst_p2_mult,
+    blst_p2_to_affine, blst_p2s_mult_pippenger,
+    blst_p2s_mult_pippenger_scratch_sizeof, limb_t,
+};
+use hex_literal::hex;
+use std::{mem::size_of, ptr};
+
+const FP_LENGTH: usize = 64;
+const FP_PADDING: usize = 16;
+const SCALAR_LENGTH: usize = 32;
+const SCALAR_BITS: usize = 256;
+
+pub const G1_LENGTH: usize = 2 * FP_LENGTH;
+pub const G2_LENGTH: usize = 4 * FP_LENGTH;
+pub const G1_MSM_PAIR_LENGTH: usize = G1_LENGTH + SCALAR_LENGTH;
+pub const G2_MSM_PAIR_LENGTH: usize = G2_LENGTH + SCALAR_LENGTH;
+pub const PAIRING_PAIR_LENGTH: usize = G1_LENGTH + G2_LENGTH;
+
+/// The base field modulus, big-endian.
+const MODULUS: [u8; 48] = hex!(
+    "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf"
+    "6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab"
+);
+
+/// The MSM discounts of G1 in thousandths, indexed by the number of pairs
+/// minus one. Larger inputs use the last value.
+pub const G1_MSM_DISCOUNT: [u16; 128] = [
+    1000, 949, 848, 797, 764, 750, 738, 728, 719, 712, 705, 698, 692, 687, 682,
+    677, 673, 669, 665, 661, 658, 654, 651, 648, 645, 642, 640, 637, 635, 632,
+    630, 627, 625, 623, 621, 619, 617, 615, 613, 611, 609, 608, 606, 604, 603,
+    601, 599, 598, 596, 595, 593, 592, 591, 589, 588, 586, 585, 584, 582, 581,
+    580, 579, 577, 576, 575, 574, 573, 572, 570, 569, 568, 567, 566, 565, 564,
+    563, 562, 561, 560, 559, 558, 557, 556, 555, 554, 553, 552, 551, 550, 549,
+    548, 547, 547, 546, 545, 544, 543, 542, 541, 540, 540, 539, 538, 537, 536,
+    536, 535, 534, 533, 532, 532, 531, 530, 529, 528, 528, 527, 526, 525, 525,
+    524, 523, 522, 522, 521, 520, 520, 519,
+];
+
+/// The MSM discounts of G2 in thousandths.
+pub const G2_MSM_DISCOUNT: [u16; 128] = [
+    1000, 1000, 923, 884, 855, 832, 812, 796, 782, 770, 759, 749, 740, 732,
+    724, 717, 711, 704, 699, 693, 688, 683, 679, 674, 670, 666, 663, 659, 655,
+    652, 649, 646, 643, 640, 637, 634, 632, 629, 627, 624, 622, 620, 618, 615,
+    613, 611, 609, 607, 606, 604, 602, 600, 598, 597, 595, 593, 592, 590, 589,
+    587, 586, 584, 583, 582, 580, 579, 578, 576, 575, 574, 573, 571, 570, 569,
+    568, 567, 566, 565, 563, 562, 561, 560, 559, 558, 557, 556, 555, 554, 553,
+    552, 552, 551, 550, 549, 548, 547, 546, 545, 545, 544, 543, 542, 541, 541,
+    540, 539, 538, 537, 537, 536, 535, 535, 534, 533, 532, 532, 531, 530, 530,
+    529, 528, 528, 527, 526, 526, 525, 524, 524,
+];
+
+fn decode_fp(input: &[u8]) -> Result<blst_fp, Error> {
+    let (padding, value) = input.split_at(FP_PADDING);
+    if padding.iter().any(|byte| *byte != 0) {
+        return Err(Error("invalid field element padding"));
+    }
+    // Both are big-endian with the same length.
+    if value >= &MODULUS[..] {
+        return Err(Error("field element is not canonical"));
+    }
+    let mut fp = blst_fp::default();
+    unsafe { blst_fp_from_bendian(&mut fp, value.as_ptr()) };
+    Ok(fp)
+}
+
+fn decode_fp2(input: &[u8]) -> Result<blst_fp2, Error> {
+    Ok(blst_fp2 {
+        fp: [
+            decode_fp(&input[..FP_LENGTH])?,
+            decode_fp(&input[FP_LENGTH..])?,
+        ],
+    })
+}
+
+fn encode_fp(fp: &blst_fp, output: &mut [u8]) {
+    unsafe { blst_bendian_from_fp(output[FP_PADDING..].as_mut_ptr(), fp) };
+}
+
+/// Scalars are big-endian in the input while `blst` expects little-endian.
+fn decode_scalar(input: &[u8]) -> [u8; SCALAR_LENGTH] {
+    let mut scalar = [0u8; SCALAR_LENGTH];
+    scalar.copy_from_slice(input);
+    scalar.reverse();
+    scalar
+}
+
+fn is_infinity(input: &[u8]) -> bool { input.iter().all(|byte| *byte == 0) }
+
+/// Decode a G1 point, checking that it is on the curve and, if
+/// `subgroup_check` is set, in the prime-order subgroup. `blst` represents
+/// the point at infinity with zero coordinates, as in the encoding.
+fn decode_g1(
+    input: &[u8], subgroup_check: bool,
+) -> Result<blst_p1_affine, Error> {
+    let point = blst_p1_affine {
+        x: decode_fp(&input[..FP_LENGTH])?,
+        y: decode_fp(&input[FP_LENGTH..])?,
+    };
+    if is_infinity(input) {
+        return Ok(point);
+    }
+    if !unsafe { blst_p1_affine_on_curve(&point) } {
+        return Err(Error("G1 point is not on the curve"));
+    }
+    if subgroup_check && !unsafe { blst_p1_affine_in_g1(&point) } {
+        return Err(Error("G1 point is not in the subgroup"));
+    }
+    Ok(point)
+}
+
+fn decode_g2(
+    input: &[u8], subgroup_check: bool,
+) -> Result<blst_p2_affine, Error> {
+    let point = blst_p2_affine {
+        x: decode_fp2(&input[..2 * FP_LENGTH])?,
+        y: decode_fp2(&input[2 * FP_LENGTH..])?,
+    };
+    if is_infinity(input) {
+        return Ok(point);
+    }
+    if !unsafe { blst_p2_affine_on_curve(&point) } {
+        return Err(Error("G2 point is not on the curve"));
+    }
+    if subgroup_check && !unsafe { blst_p2_affine_in_g2(&point) } {
+        return Err(Error("G2 point is not in the subgroup"));
+    }
+    Ok(point)
+}
+
+fn encode_g1(point: &blst_p1) -> Vec<u8> {
+    let mut affine = blst_p1_affine::default();
+    unsafe { blst_p1_to_affine(&mut affine, point) };
+    let mut output = vec![0u8; G1_LENGTH];
+    encode_fp(&affine.x, &mut output[..FP_LENGTH]);
+    encode_fp(&affine.y, &mut output[FP_LENGTH..]);
+    output
+}
+
+fn encode_g2(point: &blst_p2) -> Vec<u8> {
+    let mut affine = blst_p2_affine::default();
+    unsafe { blst_p2_to_affine(&mut affine, point) };
+    let mut output = vec![0u8; G2_LENGTH];
+    let coordinates = [
+        affine.x.fp[0],
+        affine.x.fp[1],
+        affine.y.fp[0],
+        affine.y.fp[1],
+    ];
+    for (fp, chunk) in coordinates.iter().zip(output.chunks_mut(FP_LENGTH)) {
+        encode_fp(fp, chunk);
+    }
+    output
+}
+
+pub fn g1_add(input: &[u8]) -> Result<Vec<u8>, Error> {
+    if input.len() != 2 * G1_LENGTH {
+        return Err(Error("invalid G1ADD input length"));
+    }
+    let a = decode_g1(&input[..G1_LENGTH], false)?;
+    let b = decode_g1(&input[G1_LENGTH..], false)?;
+
+    let mut a_jacobian = blst_p1::default();
+    let mut sum = blst_p1::default();
+    unsafe {
+        blst_p1_from_affine(&mut a_jacobian, &a);
+        blst_p1_add_or_double_affine(&mut sum, &a_jacobian, &b);
+    }
+    Ok(encode_g1(&sum))
+}
+
+pub fn g2_add(input: &[u8]) -> Result<Vec<u8>, Error> {
+    if input.len() != 2 * G2_LENGTH {
+        return Err(Error("invalid G2ADD input length"));
+    }
+    let a = decode_g2(&input[..G2_LENGTH], false)?;
+    let b = decode_g2(&input[G2_LENGTH..], false)?;
+
+    let mut a_jacobian = blst_p2::default();
+    let mut sum = blst_p2::default();
+    unsafe {
+        blst_p2_from_affine(&mut a_jacobian, &a);
+        blst_p2_add_or_double_affine(&mut sum, &a_jacobian, &b);
+    }
+    Ok(encode_g2(&sum))
+}
+
+pub fn g1_msm(input: &[u8]) -> Result<Vec<u8>, Error> {
+    if input.is_empty() || input.len() % G1_MSM_PAIR_LENGTH != 0 {
+        return Err(Error("invalid G1MSM input length"));
+    }
+
+    let mut points = vec![];
+    let mut scalars = vec![];
+    for pair in input.chunks(G1_MSM_PAIR_LENGTH) {
+        let point = decode_g1(&pair[..G1_LENGTH], true)?;
+        // The point at infinity contributes nothing.
+        if !is_infinity(&pair[..G1_LENGTH]) {
+            points.push(point);
+            scalars.push(decode_scalar(&pair[G1_LENGTH..]));
+        }
+    }
+
+    let mut result = blst_p1::default();
+    match points.len() {
+        0 => {}
+        1 => unsafe {
+            let mut point = blst_p1::default();
+            blst_p1_from_affine(&mut point, &points[0]);
+            blst_p1_mult(&mut result, &point, scalars[0].as_ptr(), SCALAR_BITS);
+        },
+        n => unsafe {
+            let point_ptrs: Vec<*const blst_p1_affine> =
+                points.iter().map(|point| point as *const _).collect();
+            let scalar_ptrs: Vec<*const u8> =
+                scalars.iter().map(|scalar| scalar.as_ptr()).collect();
+            let scratch_size =
+                blst_p1s_mult_pippenger_scratch_sizeof(n) / size_of::<limb_t>();
+            let mut scratch = vec![0 as limb_t; scratch_size];
+            blst_p1s_mult_pippenger(
+                &mut result,
+                point_ptrs.as_ptr(),
+                n,
+                scalar_ptrs.as_ptr(),
+                SCALAR_BITS,
+                scratch.as_mut_ptr(),
+            );
+        },
+    }
+    Ok(encode_g1(&result))
+}
+
+pub fn g2_msm(input: &[u8]) -> Result<Vec<u8>, Error> {
+    if input.is_empty() || input.len() % G2_MSM_PAIR_LENGTH != 0 {
+        return Err(Error("invalid G2MSM input length"));
+    }
+
+    let mut points = vec![];
+    let mut scalars = vec![];
+    for pair in input.chunks(G2_MSM_PAIR_LENGTH) {
+        let point = decode_g2(&pair[..G2_LENGTH], true)?;
+        // The point at infinity contributes nothing.
+        if !is_infinity(&pair[..G2_LENGTH]) {
+            points.push(point);
+            scalars.push(decode_scalar(&pair[G2_LENGTH..]));
+        }
+    }
+
+    let mut result = blst_p2::default();
+    match points.len() {
+        0 => {}
+        1 => unsafe {
+            let mut point = blst_p2::default();
+            blst_p2_from_affine(&mut point, &points[0]);
+            blst_p2_mult(&mut result, &point, scalars[0].as_ptr(), SCALAR_BITS);
+        },
+        n => unsafe {
+            let point_ptrs: Vec<*const blst_p2_affine> =
+                points.iter().map(|point| point as *const _).collect();
+            let scalar_ptrs: Vec<*const u8> =
+                scalars.iter().map(|scalar| scalar.as_ptr()).collect();
+            let scratch_size =
+                blst_p2s_mult_pippenger_scratch_sizeof(n) / size_of::<limb_t>();
+            let mut scratch = vec![0 as limb_t; scratch_size];
+            blst_p2s_mult_pippenger(
+                &mut result,
+                point_ptrs.as_ptr(),
+                n,
+                scalar_ptrs.as_ptr(),
+                SCALAR_BITS,
+                scratch.as_mut_ptr(),
+            );
+        },
+    }
+    Ok(encode_g2(&result))
+}
+
+/// Returns 1 as a 32-byte word if the product of the pairings of all pairs is
+/// the identity, and 0 otherwise.
+pub fn pairing_check(input: &[u8]) -> Result<Vec<u8>, Error> {
+    if input.is_empty() || input.len() % PAIRING_PAIR_LENGTH != 0 {
+        return Err(Error("invalid PAIRING input length"));
+    }
+
+    let mut product: Option<blst_fp12> = None;
+    for pair in input.chunks(PAIRING_PAIR_LENGTH) {
+        let (g1, g2) = pair.split_at(G1_LENGTH);
+        let p = decode_g1(g1, true)?;
+        let q = decode_g2(g2, true)?;
+        // A pair with the point at infinity pairs to the identity.
+        if is_infinity(g1) || is_infinity(g2) {
+            continue;
+        }
+
+        let mut miller_loop = blst_fp12::default();
+        unsafe { blst_miller_loop(&mut miller_loop, &q, &p) };
+        product = Some(match product {
+            None => miller_loop,
+            Some(product) => {
+                let mut result = blst_fp12::default();
+                unsafe { blst_fp12_mul(&mut result, &product, &miller_loop) };
+                result
+            }
+        });
+    }
+
+    let is_identity = match product {
+        None => true,
+        Some(product) => {
+            let mut result = blst_fp12::default();
+            unsafe {
+                blst_final_exp(&mut result, &product);
+                blst_fp12_is_one(&result)
+            }
+        }
+    };
+    let mut output = vec![0u8; 32];
+    output[31] = is_identity as u8;
+    Ok(output)
+}
+
+pub fn map_fp_to_g1(input: &[u8]) -> Result<Vec<u8>, Error> {
+    if input.len() != FP_LENGTH {
+        return Err(Error("invalid MAP_FP_TO_G1 input length"));
+    }
+    let fp = decode_fp(input)?;
+    let mut point = blst_p1::default();
+    unsafe { blst_map_to_g1(&mut point, &fp, ptr::null()) };
+    Ok(encode_g1(&point))
+}
+
+pub fn map_fp2_to_g2(input: &[u8]) -> Result<Vec<u8>, Error> {
+    if input.len() != 2 * FP_LENGTH {
+        return Err(Error("invalid MAP_FP2_TO_G2 input length"));
+    }
+    let fp2 = decode_fp2(input)?;
+    let mut point = blst_p2::default();
+    unsafe { blst_map_to_g2(&mut point, &fp2, ptr::null()) };
+    Ok(encode_g2(&point))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_hex::FromHex;
+
+    // The generators and their multiples, computed independently.
+    const G1: &str = "0000000000000000000000000000000017f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb0000000000000000000000000000000008b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1";
+    const G1_NEG: &str = "0000000000000000000000000000000017f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb00000000000000000000000000000000114d1d6855d545a8aa7d76c8cf2e21f267816aef1db507c96655b9d5caac42364e6f38ba0ecb751bad54dcd6b939c2ca";
+    const G1_2: &str = "000000000000000000000000000000000572cbea904d67468808c8eb50a9450c9721db309128012543902d0ac358a62ae28f75bb8f1c7c42c39a8c5529bf0f4e00000000000000000000000000000000166a9d8cabc673a322fda673779d8e3822ba3ecb8670e461f73bb9021d5fd76a4c56d9d4cd16bd1bba86881979749d28";
+    const G1_4: &str = "000000000000000000000000000000000c9b60d5afcbd5663a8a44b7c5a02f19e9a77ab0a35bd65809bb5c67ec582c897feb04decc694b13e08587f3ff9b5b6000000000000000000000000000000000143be6d078c2b79a7d4f1d1b21486a030ec93f56aa54e1de880db5a66dd833a652a95bee27c824084006cb5644cbd43f";
+    // A point on the curve but outside of the subgroup, with x = 4.
+    const G1_NOT_IN_SUBGROUP: &str = "00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000a989badd40d6212b33cffc3f3763e9bc760f988c9926b26da9dd85e928483446346b8ed00e1de5d5ea93e354abe706c";
+    const G2: &str = "00000000000000000000000000000000024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb80000000000000000000000000000000013e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e000000000000000000000000000000000ce5d527727d6e118cc9cdc6da2e351aadfd9baa8cbdd3a76d429a695160d12c923ac9cc3baca289e193548608b82801000000000000000000000000000000000606c4a02ea734cc32acd2b02bc28b99cb3e287e85a763af267492ab572e99ab3f370d275cec1da1aaa9075ff05f79be";
+    const G2_2: &str = "000000000000000000000000000000001638533957d540a9d2370f17cc7ed5863bc0b995b8825e0ee1ea1e1e4d00dbae81f14b0bf3611b78c952aacab827a053000000000000000000000000000000000a4edef9c1ed7f729f520e47730a124fd70662a904ba1074728114d1031e1572c6c886f6b57ec72a6178288c47c33577000000000000000000000000000000000468fb440d82b0630aeb8dca2b5256789a66da69bf91009cbfe6bd221e47aa8ae88dece9764bf3bd999d95d71e4c9899000000000000000000000000000000000f6d4552fa65dd2638b361543f887136a43253d9c66c411697003f7a13c308f5422e1aa0a59c8967acdefd8b6e36ccf3";
+    const G2_4: &str = "000000000000000000000000000000000e7a30979a8853a077454eb63b8dcee75f106221b262886bb8e01b0abb043368da82f60899cc1412e33e4120195fc55700000000000000000000000000000000070227d3f13684fdb7ce31b8065ba3acb35f7bde6fe2ddfefa359f8b35d08a9ab9537b43e24f4ffb720b5a0bda2a82f2000000000000000000000000000000000701377cb7da22789d032737eabcea2b2eee6bb4634c4365864511a43c2caad50422993ccd3e99636eb8a5f189454b18000000000000000000000000000000000782c14e2c4ee61cbe7be6e462a66b2e3509f42d53ff333efc9bfe9a00307cd2f68b007606446d98a75fb808a405d8b9";
+
+    fn bytes(parts: &[&str]) -> Vec<u8> { parts.concat().from_hex().unwrap() }
+
+    fn scalar(value: u64) -> String { format!("{:064x}", value) }
+
+    fn infinity(length: usize) -> String { "00".repeat(length) }
+
+    fn pairing_result(is_identity: bool) -> Vec<u8> {
+        let mut output = vec![0u8; 32];
+        output[31] = is_identity as u8;
+        output
+    }
+
+    #[test]
+    fn test_g1_add() {
+        assert_eq!(g1_add(&bytes(&[G1, G1])).unwrap(), bytes(&[G1_2]));
+        assert_eq!(g1_add(&bytes(&[G1_2, G1_2])).unwrap(), bytes(&[G1_4]));
+        assert_eq!(
+            g1_add(&bytes(&[G1, &infinity(G1_LENGTH)])).unwrap(),
+            bytes(&[G1])
+        );
+        assert_eq!(
+            g1_add(&bytes(&[G1, G1_NEG])).unwrap(),
+            vec![0u8; G1_LENGTH]
+        );
+        // Addition does not check the subgroup.
+        assert_eq!(
+            g1_add(&bytes(&[G1_NOT_IN_SUBGROUP, &infinity(G1_LENGTH)]))
+                .unwrap(),
+            bytes(&[G1_NOT_IN_SUBGROUP])
+        );
+
+        assert!(g1_add(&bytes(&[G1])).is_err());
+        assert!(g1_add(&bytes(&[G1, G1, "00"])).is_err());
+    }
+
+    #[test]
+    fn test_g2_add() {
+        assert_eq!(g2_add(&bytes(&[G2, G2])).unwrap(), bytes(&[G2_2]));
+        assert_eq!(g2_add(&bytes(&[G2_2, G2_2])).unwrap(), bytes(&[G2_4]));
+        assert_eq!(
+            g2_add(&bytes(&[&infinity(G2_LENGTH), G2])).unwrap(),
+            bytes(&[G2])
+        );
+        assert!(g2_add(&bytes(&[G2])).is_err());
+    }
+
+    #[test]
+    fn test_g1_msm() {
+        assert_eq!(g1_msm(&bytes(&[G1, &scalar(4)])).unwrap(), bytes(&[G1_4]));
+        assert_eq!(
+            g1_msm(&bytes(&[G1, &scalar(2), G1_2, &scalar(1)])).unwrap(),
+            bytes(&[G1_4])
+        );
+        assert_eq!(
+            g1_msm(&bytes(&[G1, &scalar(0), &infinity(G1_LENGTH), &scalar(3)]))
+                .unwrap(),
+            vec![0u8; G1_LENGTH]
+        );
+        // The group order times the generator is the point at infinity.
+        let order =
+            "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001";
+        assert_eq!(g1_msm(&bytes(&[G1, order])).unwrap(), vec![0u8; G1_LENGTH]);
+
+        assert!(g1_msm(&[]).is_err());
+        assert!(g1_msm(&bytes(&[G1_NOT_IN_SUBGROUP, &scalar(1)])).is_err());
+    }
+
+    #[test]
+    fn test_g2_msm() {
+        assert_eq!(g2_msm(&bytes(&[G2, &scalar(4)])).unwrap(), bytes(&[G2_4]));
+        assert_eq!(
+            g2_msm(&bytes(&[G2, &scalar(2), G2_2, &scalar(1)])).unwrap(),
+            bytes(&[G2_4])
+        );
+        assert!(g2_msm(&bytes(&[G2])).is_err());
+    }
+
+    #[test]
+    fn test_pairing_check() {
+        assert_eq!(
+            pairing_check(&bytes(&[G1, G2, G1_NEG, G2])).unwrap(),
+            pairing_result(true)
+        );
+        // e(2 * G1, 2 * G2) = e(G1, 4 * G2)
+        assert_eq!(
+            pairing_check(&bytes(&[G1_2, G2_2, G1_NEG, G2_4])).unwrap(),
+            pairing_result(true)
+        );
+        assert_eq!(
+            pairing_check(&bytes(&[G1_2, G2, G1_NEG, G2])).unwrap(),
+            pairing_result(false)
+        );
+        assert_eq!(
+            pairing_check(&bytes(&[G1_4, G2, G1_NEG, G2_4])).unwrap(),
+            pairing_result(true)
+        );
+        assert_eq!(
+            pairing_check(&bytes(&[G1, G2])).unwrap(),
+            pairing_result(false)
+        );
+        assert_eq!(
+            pairing_check(&bytes(&[&infinity(G1_LENGTH), G2])).unwrap(),
+            pairing_result(true)
+        );
+
+        assert!(pairing_check(&[]).is_err());
+        assert!(pairing_check(&bytes(&[G1_NOT_IN_SUBGROUP, G2])).is_err());
+    }
+
+    #[test]
+    fn test_map_to_curve() {
+        let fp = format!("{}{}", "00".repeat(16), "11".repeat(48));
+        let g1 = map_fp_to_g1(&bytes(&[&fp])).unwrap();
+        // The result is in the subgroup.
+        let mut input = g1.clone();
+        input.extend(bytes(&[&scalar(1)]));
+        assert_eq!(g1_msm(&input).unwrap(), g1);
+
+        let g2 = map_fp2_to_g2(&bytes(&[&fp, &fp])).unwrap();
+        let mut input = g2.clone();
+        input.extend(bytes(&[&scalar(1)]));
+        assert_eq!(g2_msm(&input).unwrap(), g2);
+
+        assert!(map_fp_to_g1(&bytes(&[&fp, "00"])).is_err());
+        assert!(map_fp2_to_g2(&bytes(&[&fp])).is_err());
+    }
+
+    #[test]
+    fn test_invalid_field_elements() {
+        let modulus = format!(
+            "{}{}",
+            "00".repeat(16),
+            "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf\
+             6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab"
+        );
+        assert!(map_fp_to_g1(&bytes(&[&modulus])).is_err());
+
+        let bad_padding = format!("01{}", &G1[2..]);
+        assert!(g1_add(&bytes(&[&bad_padding, G1])).is_err());
+
+        // Not on the curve: the y coordinate of the generator plus one.
+        let not_on_curve = format!("{}{}", &G1[..254], "e2");
+        assert!(g1_add(&bytes(&[&not_on_curve, G1])).is_err());
+    }
+}
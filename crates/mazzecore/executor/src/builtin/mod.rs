@@ -19,9 +19,11 @@
 //! Standard built-in contracts.
 
 mod blake2f;
+mod bls12_381;
 mod ethereum_trusted_setup_points;
 mod executable;
 mod kzg_point_evaluations;
+mod p256_verify;
 
 pub use executable::BuiltinExec;
 
@@ -197,6 +199,66 @@ impl ModexpPricer {
     }
 }
 
+/// Pricing for the BLS12-381 multi-scalar multiplications of EIP-2537: the
+/// cost of a single multiplication per pair, reduced by a discount depending
+/// on the number of pairs.
+pub(crate) struct Bls12MsmPricer {
+    pair_length: usize,
+    multiplication_cost: u64,
+    discount: &'static [u16; 128],
+}
+
+impl Bls12MsmPricer {
+    pub(crate) fn g1() -> Self {
+        Bls12MsmPricer {
+            pair_length: bls12_381::G1_MSM_PAIR_LENGTH,
+            multiplication_cost: 12_000,
+            discount: &bls12_381::G1_MSM_DISCOUNT,
+        }
+    }
+
+    pub(crate) fn g2() -> Self {
+        Bls12MsmPricer {
+            pair_length: bls12_381::G2_MSM_PAIR_LENGTH,
+            multiplication_cost: 22_500,
+            discount: &bls12_381::G2_MSM_DISCOUNT,
+        }
+    }
+}
+
+impl Pricer for Bls12MsmPricer {
+    fn cost(&self, input: &[u8]) -> U256 {
+        let k = input.len() / self.pair_length;
+        if k == 0 {
+            return U256::zero();
+        }
+        let discount =
+            U256::from(self.discount[min(k, self.discount.len()) - 1]);
+        U256::from(k) * U256::from(self.multiplication_cost) * discount
+            / U256::from(1000)
+    }
+}
+
+/// Pricing for the BLS12-381 pairing check of EIP-2537: a base cost and a
+/// cost per pair.
+pub(crate) struct Bls12PairingPricer {
+    base: usize,
+    pair: usize,
+}
+
+impl Bls12PairingPricer {
+    pub(crate) fn new(base: usize, pair: usize) -> Self {
+        Bls12PairingPricer { base, pair }
+    }
+}
+
+impl Pricer for Bls12PairingPricer {
+    fn cost(&self, input: &[u8]) -> U256 {
+        let pairs = input.len() / bls12_381::PAIRING_PAIR_LENGTH;
+        U256::from(self.base) + U256::from(self.pair) * U256::from(pairs)
+    }
+}
+
 /// Pricing for Blake2 compression function: each call costs the same amount per
 /// round.
 pub(crate) struct Blake2FPricer {
@@ -278,6 +340,16 @@ pub fn builtin_factory(name: &str) -> Box<dyn Impl> {
         "alt_bn128_pairing" => Box::new(Bn128PairingImpl) as Box<dyn Impl>,
         "blake2_f" => Box::new(Blake2FImpl) as Box<dyn Impl>,
         "kzg_point_eval" => Box::new(KzgPointEval) as Box<dyn Impl>,
+        "p256_verify" => Box::new(P256Verify) as Box<dyn Impl>,
+        "bls12_g1_add" => Box::new(Bls12G1Add) as Box<dyn Impl>,
+        "bls12_g1_msm" => Box::new(Bls12G1Msm) as Box<dyn Impl>,
+        "bls12_g2_add" => Box::new(Bls12G2Add) as Box<dyn Impl>,
+        "bls12_g2_msm" => Box::new(Bls12G2Msm) as Box<dyn Impl>,
+        "bls12_pairing_check" => {
+            Box::new(Bls12PairingCheck) as Box<dyn Impl>
+        }
+        "bls12_map_fp_to_g1" => Box::new(Bls12MapFpToG1) as Box<dyn Impl>,
+        "bls12_map_fp2_to_g2" => Box::new(Bls12MapFp2ToG2) as Box<dyn Impl>,
         _ => panic!("invalid builtin name: {}", name),
     }
 }
@@ -330,6 +402,31 @@ struct Blake2FImpl;
 #[allow(dead_code)]
 struct KzgPointEval;
 
+#[derive(Debug)]
+#[allow(dead_code)]
+struct P256Verify;
+
+#[derive(Debug)]
+struct Bls12G1Add;
+
+#[derive(Debug)]
+struct Bls12G1Msm;
+
+#[derive(Debug)]
+struct Bls12G2Add;
+
+#[derive(Debug)]
+struct Bls12G2Msm;
+
+#[derive(Debug)]
+struct Bls12PairingCheck;
+
+#[derive(Debug)]
+struct Bls12MapFpToG1;
+
+#[derive(Debug)]
+struct Bls12MapFp2ToG2;
+
 impl Impl for Identity {
     fn execute(
         &self, input: &[u8], output: &mut BytesRef,
@@ -785,11 +882,89 @@ impl Impl for KzgPointEval {
         Ok(())
     }
 }
+
+impl Impl for P256Verify {
+    /// Following RIP-7212, a valid signature returns 1 as a 32-byte word and
+    /// an invalid one returns nothing, without failing the call.
+    fn execute(
+        &self, input: &[u8], output: &mut BytesRef,
+    ) -> Result<(), Error> {
+        if p256_verify::verify(input) {
+            let mut result = [0u8; 32];
+            result[31] = 1;
+            output.write(0, &result);
+        }
+        Ok(())
+    }
+}
+
+impl Impl for Bls12G1Add {
+    fn execute(
+        &self, input: &[u8], output: &mut BytesRef,
+    ) -> Result<(), Error> {
+        output.write(0, &bls12_381::g1_add(input)?);
+        Ok(())
+    }
+}
+
+impl Impl for Bls12G1Msm {
+    fn execute(
+        &self, input: &[u8], output: &mut BytesRef,
+    ) -> Result<(), Error> {
+        output.write(0, &bls12_381::g1_msm(input)?);
+        Ok(())
+    }
+}
+
+impl Impl for Bls12G2Add {
+    fn execute(
+        &self, input: &[u8], output: &mut BytesRef,
+    ) -> Result<(), Error> {
+        output.write(0, &bls12_381::g2_add(input)?);
+        Ok(())
+    }
+}
+
+impl Impl for Bls12G2Msm {
+    fn execute(
+        &self, input: &[u8], output: &mut BytesRef,
+    ) -> Result<(), Error> {
+        output.write(0, &bls12_381::g2_msm(input)?);
+        Ok(())
+    }
+}
+
+impl Impl for Bls12PairingCheck {
+    fn execute(
+        &self, input: &[u8], output: &mut BytesRef,
+    ) -> Result<(), Error> {
+        output.write(0, &bls12_381::pairing_check(input)?);
+        Ok(())
+    }
+}
+
+impl Impl for Bls12MapFpToG1 {
+    fn execute(
+        &self, input: &[u8], output: &mut BytesRef,
+    ) -> Result<(), Error> {
+        output.write(0, &bls12_381::map_fp_to_g1(input)?);
+        Ok(())
+    }
+}
+
+impl Impl for Bls12MapFp2ToG2 {
+    fn execute(
+        &self, input: &[u8], output: &mut BytesRef,
+    ) -> Result<(), Error> {
+        output.write(0, &bls12_381::map_fp2_to_g2(input)?);
+        Ok(())
+    }
+}
 #[cfg(test)]
 mod tests {
     use super::{
-        builtin_factory, modexp as me, Blake2FPricer, Builtin, Linear,
-        ModexpPricer, Pricer,
+        builtin_factory, modexp as me, Blake2FPricer, Bls12MsmPricer,
+        Bls12PairingPricer, Builtin, Linear, ModexpPricer, Pricer,
     };
     use mazze_bytes::BytesRef;
     use mazze_types::U256;
@@ -1483,4 +1658,292 @@ mod tests {
             .unwrap();
         assert_eq!(&out[..], &expected[..]);
     }
+
+    fn p256_verify_builtin() -> Builtin {
+        Builtin {
+            pricer: Box::new(Linear::new(3450, 0)) as Box<dyn Pricer>,
+            native: builtin_factory("p256_verify"),
+            activate_at: 0,
+        }
+    }
+
+    fn p256_verify(input: &str) -> Vec<u8> {
+        let input: Vec<u8> = FromHex::from_hex(input).unwrap();
+        let mut output = vec![];
+        p256_verify_builtin()
+            .execute(&input[..], &mut BytesRef::Flexible(&mut output))
+            .expect("Builtin should not fail");
+        output
+    }
+
+    // A valid signature from the RIP-7212 test vectors.
+    const P256_VALID: &str = "4cee90eb86eaa050036147a12d49004b6b9c72bd725d39d4785011fe190f0b4da73bd4903f0ce3b639bbbf6e8e80d16931ff4bcf5993d58468e8fb19086e8cac36dbcd03009df8c59286b162af3bd7fcc0450c9aa81be5d10d312af6c66b1d604aebd3099c618202fcfe16ae7770b0c49ab5eadf74b754204a3bb6060e44eff37618b065f9832de4ca6ca971a7a1adc826d0f7c00181a5fb2ddf79ae00b4e10e";
+
+    #[test]
+    fn p256_verify_valid_signatures() {
+        let mut success = vec![0u8; 32];
+        success[31] = 1;
+
+        assert_eq!(p256_verify(P256_VALID), success);
+        // The same signature with s replaced by n - s is also valid.
+        assert_eq!(p256_verify("4cee90eb86eaa050036147a12d49004b6b9c72bd725d39d4785011fe190f0b4da73bd4903f0ce3b639bbbf6e8e80d16931ff4bcf5993d58468e8fb19086e8cacc92432fbff62073b6d794e9d50c42802fca1ee12fefbb8b3e6889fcc35f807f14aebd3099c618202fcfe16ae7770b0c49ab5eadf74b754204a3bb6060e44eff37618b065f9832de4ca6ca971a7a1adc826d0f7c00181a5fb2ddf79ae00b4e10e"), success);
+        // Private key 1, message "mazze".
+        assert_eq!(p256_verify("c57a13d809440e6758a4b2bed323096d449af69c875bba072cf47ad830176ed4afc52cb49b8063c4117ebafa2b75d89b79a69e9b11734a5849ead118636f5d0e24ae4d593341cbe5a040c0508b7788c20f85d827a66134fe3080608bf544ffe36b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c2964fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5"), success);
+        // Private key 0x1234567890abcdef, message "webauthn passkey".
+        assert_eq!(p256_verify("0fdc13a392929b6807470c30292f814d5d8eb442cf350451c1f1f8fa440463430b8bbf01d78aea6235b45bd7f6f30a147da707fa4b26b15dd22ca29d30822d6408a2eeb9e9054c7cd070ab4568afda2c74b46a012359b9c641b7e8ee3ac3090f9fad84aeae08bbef7f010014d82cef6a09de2b0cf871b5ce0c4f1d13a59a593407cb45769f1070e2c2470fe5b1bfe63133c0b0cdc64ea4bf3791a8ec2a07fd4f"), success);
+    }
+
+    #[test]
+    fn p256_verify_invalid_signatures() {
+        const N: &str =
+            "ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551";
+        let (hash, rest) = P256_VALID.split_at(64);
+        let (r, rest) = rest.split_at(64);
+        let (s, public_key) = rest.split_at(64);
+        let (x, _) = public_key.split_at(64);
+        let zero = "0".repeat(64);
+
+        let invalid_inputs = vec![
+            // Modified message hash.
+            format!("{}{}{}{}", "00".repeat(32), r, s, public_key),
+            // r = 0 and s = 0.
+            format!("{}{}{}{}", hash, zero, s, public_key),
+            format!("{}{}{}{}", hash, r, zero, public_key),
+            // r = n and s = n.
+            format!("{}{}{}{}", hash, N, s, public_key),
+            format!("{}{}{}{}", hash, r, N, public_key),
+            // r and s swapped.
+            format!("{}{}{}{}", hash, s, r, public_key),
+            // Public key not on the curve.
+            format!("{}{}{}{}{}", hash, r, s, x, x),
+            // Point at infinity.
+            format!("{}{}{}{}{}", hash, r, s, zero, zero),
+            // Wrong input lengths.
+            String::new(),
+            P256_VALID[..318].to_string(),
+            format!("{}00", P256_VALID),
+        ];
+        for input in invalid_inputs {
+            assert_eq!(p256_verify(&input), Vec::<u8>::new(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn p256_verify_cost() {
+        let f = p256_verify_builtin();
+        let input: Vec<u8> = FromHex::from_hex(P256_VALID).unwrap();
+        assert_eq!(f.cost(&input[..]), U256::from(3450));
+    }
+
+    /// Run the precompile test vectors in `testdata`, in the format of the
+    /// go-ethereum precompile tests.
+    fn run_precompile_test_vectors(builtin: &Builtin, vectors: &str) {
+        let cases: Vec<serde_json::Value> =
+            serde_json::from_str(vectors).expect("valid test vectors");
+        assert!(!cases.is_empty());
+        for case in cases {
+            let name = case["Name"].as_str().unwrap();
+            let input: Vec<u8> =
+                FromHex::from_hex(case["Input"].as_str().unwrap()).unwrap();
+            let expected: Vec<u8> =
+                FromHex::from_hex(case["Expected"].as_str().unwrap()).unwrap();
+
+            let mut output = vec![];
+            builtin
+                .execute(&input[..], &mut BytesRef::Flexible(&mut output))
+                .expect("Builtin should not fail");
+            assert_eq!(output, expected, "{}", name);
+            assert_eq!(
+                builtin.cost(&input[..]),
+                U256::from(case["Gas"].as_u64().unwrap()),
+                "{}",
+                name
+            );
+        }
+    }
+
+    /// Run the failure vectors in `testdata`: every input must fail the call.
+    fn run_precompile_failure_vectors(builtin: &Builtin, vectors: &str) {
+        let cases: Vec<serde_json::Value> =
+            serde_json::from_str(vectors).expect("valid test vectors");
+        assert!(!cases.is_empty());
+        for case in cases {
+            let name = case["Name"].as_str().unwrap();
+            let input: Vec<u8> =
+                FromHex::from_hex(case["Input"].as_str().unwrap()).unwrap();
+
+            let mut output = vec![];
+            let result = builtin
+                .execute(&input[..], &mut BytesRef::Flexible(&mut output));
+            assert!(result.is_err(), "{}", name);
+        }
+    }
+
+    // The EIP-2537 vectors were published before the final gas schedule
+    // (and before G1MUL/G2MUL were folded into the MSM precompiles), so
+    // their `Gas` fields are stale: only the outputs are compared here and
+    // the final prices are asserted separately.
+    fn run_bls12_test_vectors(builtin: &Builtin, vectors: &str) {
+        let cases: Vec<serde_json::Value> =
+            serde_json::from_str(vectors).expect("valid test vectors");
+        assert!(!cases.is_empty());
+        for case in cases {
+            let name = case["Name"].as_str().unwrap();
+            let input: Vec<u8> =
+                FromHex::from_hex(case["Input"].as_str().unwrap()).unwrap();
+            let expected: Vec<u8> =
+                FromHex::from_hex(case["Expected"].as_str().unwrap()).unwrap();
+
+            let mut output = vec![];
+            builtin
+                .execute(&input[..], &mut BytesRef::Flexible(&mut output))
+                .expect("Builtin should not fail");
+            assert_eq!(output, expected, "{}", name);
+        }
+    }
+
+    fn bls12_builtin(name: &str, pricer: Box<dyn Pricer>) -> Builtin {
+        Builtin {
+            pricer,
+            native: builtin_factory(name),
+            activate_at: 0,
+        }
+    }
+
+    #[test]
+    fn bls12_g1_add_test_vectors() {
+        let builtin =
+            bls12_builtin("bls12_g1_add", Box::new(Linear::new(375, 0)));
+        run_bls12_test_vectors(
+            &builtin,
+            include_str!("testdata/add_G1_bls.json"),
+        );
+        run_precompile_failure_vectors(
+            &builtin,
+            include_str!("testdata/fail-add_G1_bls.json"),
+        );
+        assert_eq!(builtin.cost(&[0u8; 256]), U256::from(375));
+    }
+
+    #[test]
+    fn bls12_g2_add_test_vectors() {
+        let builtin =
+            bls12_builtin("bls12_g2_add", Box::new(Linear::new(600, 0)));
+        run_bls12_test_vectors(
+            &builtin,
+            include_str!("testdata/add_G2_bls.json"),
+        );
+        run_precompile_failure_vectors(
+            &builtin,
+            include_str!("testdata/fail-add_G2_bls.json"),
+        );
+        assert_eq!(builtin.cost(&[0u8; 512]), U256::from(600));
+    }
+
+    #[test]
+    fn bls12_g1_msm_test_vectors() {
+        let builtin =
+            bls12_builtin("bls12_g1_msm", Box::new(Bls12MsmPricer::g1()));
+        run_bls12_test_vectors(
+            &builtin,
+            include_str!("testdata/mul_G1_bls.json"),
+        );
+        run_bls12_test_vectors(
+            &builtin,
+            include_str!("testdata/multiexp_G1_bls.json"),
+        );
+        run_precompile_failure_vectors(
+            &builtin,
+            include_str!("testdata/fail-mul_G1_bls.json"),
+        );
+        run_precompile_failure_vectors(
+            &builtin,
+            include_str!("testdata/fail-multiexp_G1_bls.json"),
+        );
+        assert_eq!(builtin.cost(&[0u8; 160]), U256::from(12_000));
+        assert_eq!(builtin.cost(&[0u8; 320]), U256::from(22_776));
+    }
+
+    #[test]
+    fn bls12_g2_msm_test_vectors() {
+        let builtin =
+            bls12_builtin("bls12_g2_msm", Box::new(Bls12MsmPricer::g2()));
+        run_bls12_test_vectors(
+            &builtin,
+            include_str!("testdata/mul_G2_bls.json"),
+        );
+        run_bls12_test_vectors(
+            &builtin,
+            include_str!("testdata/multiexp_G2_bls.json"),
+        );
+        run_precompile_failure_vectors(
+            &builtin,
+            include_str!("testdata/fail-mul_G2_bls.json"),
+        );
+        run_precompile_failure_vectors(
+            &builtin,
+            include_str!("testdata/fail-multiexp_G2_bls.json"),
+        );
+        assert_eq!(builtin.cost(&[0u8; 288]), U256::from(22_500));
+        assert_eq!(builtin.cost(&[0u8; 576]), U256::from(45_000));
+    }
+
+    #[test]
+    fn bls12_pairing_check_test_vectors() {
+        let builtin = bls12_builtin(
+            "bls12_pairing_check",
+            Box::new(Bls12PairingPricer::new(37_700, 32_600)),
+        );
+        run_bls12_test_vectors(
+            &builtin,
+            include_str!("testdata/pairing_check_bls.json"),
+        );
+        run_precompile_failure_vectors(
+            &builtin,
+            include_str!("testdata/fail-pairing_check_bls.json"),
+        );
+        assert_eq!(builtin.cost(&[0u8; 768]), U256::from(102_900));
+    }
+
+    #[test]
+    fn bls12_map_to_curve_test_vectors() {
+        let builtin =
+            bls12_builtin("bls12_map_fp_to_g1", Box::new(Linear::new(5500, 0)));
+        run_bls12_test_vectors(
+            &builtin,
+            include_str!("testdata/map_fp_to_G1_bls.json"),
+        );
+        run_precompile_failure_vectors(
+            &builtin,
+            include_str!("testdata/fail-map_fp_to_G1_bls.json"),
+        );
+
+        let builtin = bls12_builtin(
+            "bls12_map_fp2_to_g2",
+            Box::new(Linear::new(23_800, 0)),
+        );
+        run_bls12_test_vectors(
+            &builtin,
+            include_str!("testdata/map_fp2_to_G2_bls.json"),
+        );
+        run_precompile_failure_vectors(
+            &builtin,
+            include_str!("testdata/fail-map_fp2_to_G2_bls.json"),
+        );
+    }
+
+    // The vectors are the Wycheproof `ecdsa_secp256r1_sha256` tests as
+    // distributed in the `wycheproof.blb` test data of the RustCrypto `p256`
+    // crate, numbered by their position there since that file carries
+    // neither the tcIds nor the comments. The message is replaced by its
+    // SHA-256 hash and the DER signature by r and s; the tests which only
+    // exercise the DER encoding, or whose r or s does not fit in 32 bytes,
+    // have no precompile input and are left out.
+    #[test]
+    fn p256_verify_test_vectors() {
+        run_precompile_test_vectors(
+            &p256_verify_builtin(),
+            include_str!("testdata/p256Verify.json"),
+        );
+    }
 }
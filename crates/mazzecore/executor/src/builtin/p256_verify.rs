@@ -0,0 +1,37 @@
+//! The secp256r1 (P-256) signature verification precompile of RIP-7212.
+
+use p256::ecdsa::{
+    signature::hazmat::PrehashVerifier, Signature, VerifyingKey,
+};
+
+/// The input length: message hash, r, s, public key x and public key y, 32
+/// bytes each.
+pub const INPUT_LENGTH: usize = 160;
+
+/// Verify a P-256 signature over a message hash. Returns `false` on any
+/// malformed input, including a public key not on the curve and `r` or `s`
+/// out of `[1, n)`.
+pub fn verify(input: &[u8]) -> bool {
+    if input.len() != INPUT_LENGTH {
+        return false;
+    }
+
+    let message_hash = &input[..32];
+    let signature = match Signature::from_slice(&input[32..96]) {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+
+    // Uncompressed SEC1 encoding of the public key.
+    let mut public_key = [0u8; 65];
+    public_key[0] = 0x04;
+    public_key[1..].copy_from_slice(&input[96..160]);
+    let verifying_key = match VerifyingKey::from_sec1_bytes(&public_key) {
+        Ok(verifying_key) => verifying_key,
+        Err(_) => return false,
+    };
+
+    verifying_key
+        .verify_prehash(message_hash, &signature)
+        .is_ok()
+}
@@ -22,11 +22,13 @@ use mazze_types::{
     Address, AddressSpaceUtil, AddressWithSpace, BigEndianHash, Space, H256,
     U256,
 };
+use mazze_vm_interpreter::eof::{is_eof, EOF_MAGIC};
 use mazze_vm_types::{
     self as vm, ActionParams, ActionValue, CallType, Context as ContextTrait,
     ContractCreateResult, CreateContractAddress, CreateType, Env, Error,
     MessageCallResult, ReturnData, Spec, TrapKind,
 };
+use keccak_hash::keccak;
 use primitives::transaction::UNSIGNED_SENDER;
 use std::sync::Arc;
 use vm::BlockHashSource;
@@ -42,6 +44,7 @@ pub struct OriginInfo {
     storage_owner: Address,
     gas_price: U256,
     value: U256,
+    create_type: CreateType,
 }
 
 impl OriginInfo {
@@ -55,6 +58,7 @@ impl OriginInfo {
             value: match params.value {
                 ActionValue::Transfer(val) | ActionValue::Apparent(val) => val,
             },
+            create_type: params.create_type,
         }
     }
 
@@ -155,6 +159,18 @@ impl<'a> Context<'a> {
 
         Ok(BigEndianHash::from_uint(&state_res))
     }
+
+    /// Whether the code at the address is an EOF container, which the
+    /// `EXTCODE*` instructions only see as the `0xEF00` magic (EIP-3540).
+    fn is_eof_code(&self, address: &AddressWithSpace) -> vm::Result<bool> {
+        if !*self.spec.eof.in_space(self.space) {
+            return Ok(false);
+        }
+        Ok(self
+            .state
+            .code(address)?
+            .map_or(false, |code| is_eof(&code)))
+    }
 }
 
 impl<'a> ContextTrait for Context<'a> {
@@ -312,6 +328,61 @@ impl<'a> ContextTrait for Context<'a> {
         return Ok(Err(TrapKind::Create(params)));
     }
 
+    fn eof_create(
+        &mut self, gas: &U256, value: &U256, init_container: &[u8],
+        input: &[u8], salt: H256,
+    ) -> mazze_statedb::Result<
+        ::std::result::Result<ContractCreateResult, TrapKind>,
+    > {
+        let caller = AddressWithSpace {
+            address: self.origin.address,
+            space: self.space,
+        };
+
+        // The address does not depend on the initcode (EIP-7620).
+        let mut buffer = [0u8; 1 + 32 + 32];
+        buffer[0] = 0xff;
+        buffer[13..33].copy_from_slice(caller.address.as_bytes());
+        buffer[33..].copy_from_slice(salt.as_bytes());
+        let address = Address::from(keccak(&buffer[..]));
+
+        // The same check as `create` in case EOF is activated in the native
+        // space.
+        if self.space == Space::Native
+            && self
+                .state
+                .is_contract_with_code(&address.with_space(self.space))?
+        {
+            debug!("Contract address conflict!");
+            let err = Error::ConflictAddress(address.clone());
+            return Ok(Ok(ContractCreateResult::Failed(err)));
+        }
+
+        let params = ActionParams {
+            space: self.space,
+            code_address: address,
+            address,
+            sender: self.origin.address,
+            original_sender: self.origin.original_sender,
+            storage_owner: self.origin.storage_owner,
+            gas: *gas,
+            gas_price: self.origin.gas_price,
+            value: ActionValue::Transfer(*value),
+            code: Some(Arc::new(init_container.to_vec())),
+            code_hash: keccak(init_container),
+            data: Some(input.to_vec()),
+            call_type: CallType::None,
+            create_type: CreateType::EOFCREATE,
+            params_type: vm::ParamsType::Separate,
+        };
+
+        if !self.is_static_or_reentrancy() {
+            self.state.inc_nonce(&caller)?;
+        }
+
+        return Ok(Err(TrapKind::Create(params)));
+    }
+
     fn call(
         &mut self, gas: &U256, sender_address: &Address,
         receive_address: &Address, value: Option<U256>, data: &[u8],
@@ -329,10 +400,8 @@ impl<'a> ContextTrait for Context<'a> {
         {
             (Some(contract.code()), contract.code_hash())
         } else {
-            (
-                self.state.code(&code_address_with_space)?,
-                self.state.code_hash(&code_address_with_space)?,
-            )
+            self.state
+                .code_for_call(&code_address_with_space, self.spec.eip7702)?
         };
 
         let mut params = ActionParams {
@@ -360,6 +429,8 @@ impl<'a> ContextTrait for Context<'a> {
         return Ok(Err(TrapKind::Call(params)));
     }
 
+    // The `EXTCODE*` instructions do not follow EIP-7702 delegations: they
+    // see the delegation designator itself.
     fn extcode(&self, address: &Address) -> vm::Result<Option<Arc<Bytes>>> {
         let address = address.with_space(self.space);
         if let Some(contract) = self
@@ -368,6 +439,8 @@ impl<'a> ContextTrait for Context<'a> {
             .contract(&address, self.spec)
         {
             Ok(Some(contract.code()))
+        } else if self.is_eof_code(&address)? {
+            Ok(Some(Arc::new(EOF_MAGIC.to_vec())))
         } else {
             Ok(self.state.code(&address)?)
         }
@@ -382,6 +455,8 @@ impl<'a> ContextTrait for Context<'a> {
             .contract(&address, self.spec)
         {
             Ok(contract.code_hash())
+        } else if self.is_eof_code(&address)? {
+            Ok(keccak(EOF_MAGIC))
         } else {
             Ok(self.state.code_hash(&address)?)
         }
@@ -396,6 +471,8 @@ impl<'a> ContextTrait for Context<'a> {
             .contract(&address, self.spec)
         {
             Ok(contract.code_size())
+        } else if self.is_eof_code(&address)? {
+            Ok(EOF_MAGIC.len())
         } else {
             Ok(self.state.code_size(&address)?)
         }
@@ -446,6 +523,18 @@ impl<'a> ContextTrait for Context<'a> {
             };
         }
 
+        // The 0xEF prefix is reserved for the delegation designators, and
+        // for the EOF containers deployed by `RETURNCONTRACT`.
+        let eof = *self.spec.eof.in_space(self.space);
+        let deploys_eof =
+            eof && self.origin.create_type == CreateType::EOFCREATE;
+        if (self.spec.eip7702 && self.space == Space::Ethereum || eof)
+            && data.first() == Some(&0xef)
+            && !deploys_eof
+        {
+            return Err(vm::Error::CreateContractStartingWithEF);
+        }
+
         if self.space == Space::Native {
             let collateral_units_for_code = code_collateral_units(data.len());
             let collateral_in_mazzies = U256::from(collateral_units_for_code)
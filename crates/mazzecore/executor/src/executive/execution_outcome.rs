@@ -56,6 +56,9 @@ pub enum ToRepackError {
         expected: U256,
         got: U256,
     },
+
+    /// The transaction type is not activated in the epoch to execute.
+    TransactionTypeNotActivated(u8),
 }
 
 #[derive(Debug)]
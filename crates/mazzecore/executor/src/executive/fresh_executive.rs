@@ -68,6 +68,7 @@ impl<'a, O: ExecutiveObserver> FreshExecutive<'a, O> {
             tx.action() == &Action::Create,
             &tx.data(),
             tx.access_list(),
+            tx.authorization_list(),
             context.spec,
         );
         FreshExecutive {
@@ -82,6 +83,7 @@ impl<'a, O: ExecutiveObserver> FreshExecutive<'a, O> {
     pub(super) fn check_all(
         self,
     ) -> DbResult<Result<PreCheckedExecutive<'a, O>, ExecutionOutcome>> {
+        early_return_on_err!(self.check_transaction_type());
         early_return_on_err!(self.check_base_price());
         // Validate transaction nonce
         early_return_on_err!(self.check_nonce()?);
@@ -131,6 +133,17 @@ impl<'a, O: ExecutiveObserver> FreshExecutive<'a, O> {
         })
     }
 
+    fn check_transaction_type(&self) -> Result<(), ExecutionOutcome> {
+        if self.tx.authorization_list().is_some() && !self.context.spec.eip7702
+        {
+            Err(ExecutionOutcome::NotExecutedToReconsiderPacking(
+                ToRepackError::TransactionTypeNotActivated(self.tx.type_id()),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
     fn check_base_price(&self) -> Result<(), ExecutionOutcome> {
         if !self.settings.check_base_price {
             return Ok(());
@@ -237,7 +250,54 @@ impl<'a, O: ExecutiveObserver> FreshExecutive<'a, O> {
 
         if sender.space == Space::Ethereum {
             assert_eq!(storage_cost, U256::zero());
-            let sender_cost = U512::from(tx.value()) + gas_cost;
+
+            // Check if contract will pay gas fee for the sender. The eSpace
+            // charges no storage collateral, so only the gas is sponsored.
+            let mut gas_sponsor_eligible = false;
+            let mut sponsor_balance_for_gas = U512::zero();
+            if let (true, Action::Call(ref address)) =
+                (spec.eth_sponsor, tx.action())
+            {
+                if state.is_contract_with_code(&address.with_evm_space())?
+                    && state.check_eth_contract_whitelist(
+                        address,
+                        &sender.address,
+                    )?
+                {
+                    gas_sponsor_eligible = gas_cost
+                        <= U512::from(state.eth_sponsor_gas_bound(address)?);
+                    sponsor_balance_for_gas =
+                        U512::from(state.eth_sponsor_balance_for_gas(address)?);
+                }
+            }
+            let gas_sponsored =
+                gas_sponsor_eligible && sponsor_balance_for_gas >= gas_cost;
+
+            let mut sender_intended_cost = U512::from(tx.value());
+            if !gas_sponsor_eligible {
+                sender_intended_cost += gas_cost;
+            }
+            let mut total_cost = U512::from(tx.value());
+            if !gas_sponsored {
+                total_cost += gas_cost;
+            }
+            // The same as the native space, the sender is not responsible for
+            // the sponsor running out of balance.
+            if sender_balance >= sender_intended_cost
+                && sender_balance < total_cost
+            {
+                return Ok(Err(
+                    ExecutionOutcome::NotExecutedToReconsiderPacking(
+                        ToRepackError::NotEnoughCashFromSponsor {
+                            required_gas_cost: gas_cost,
+                            gas_sponsor_balance: sponsor_balance_for_gas,
+                            required_storage_cost: storage_cost,
+                            storage_sponsor_balance: U256::zero(),
+                        },
+                    ),
+                ));
+            }
+
             return Ok(Ok(CostInfo {
                 sender_balance,
                 base_gas: self.base_gas,
@@ -245,9 +305,9 @@ impl<'a, O: ExecutiveObserver> FreshExecutive<'a, O> {
                 gas_price,
                 burnt_gas_price,
                 storage_cost,
-                sender_intended_cost: sender_cost,
-                total_cost: sender_cost,
-                gas_sponsored: false,
+                sender_intended_cost,
+                total_cost,
+                gas_sponsored,
                 storage_sponsored: false,
                 storage_sponsor_eligible: false,
             }));
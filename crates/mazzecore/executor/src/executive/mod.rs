@@ -13,7 +13,7 @@ use mazze_types::{
     U256,
 };
 use mazze_vm_types::{CreateContractAddress, Env, Spec};
-use primitives::{AccessList, SignedTransaction};
+use primitives::{transaction::AuthorizationList, AccessList, SignedTransaction};
 
 use fresh_executive::FreshExecutive;
 use pre_checked_executive::PreCheckedExecutive;
@@ -62,7 +62,8 @@ impl<'a> ExecutiveContext<'a> {
 }
 
 pub fn gas_required_for(
-    is_create: bool, data: &[u8], access_list: Option<&AccessList>, spec: &Spec,
+    is_create: bool, data: &[u8], access_list: Option<&AccessList>,
+    authorization_list: Option<&AuthorizationList>, spec: &Spec,
 ) -> u64 {
     let init_gas = (if is_create {
         spec.tx_create_gas
@@ -92,7 +93,11 @@ pub fn gas_required_for(
         0
     };
 
-    init_gas + data_gas + access_gas
+    let authorization_gas = authorization_list.map_or(0, |list| {
+        list.len() as u64 * spec.per_empty_account_cost as u64
+    });
+
+    init_gas + data_gas + access_gas + authorization_gas
 }
 
 pub fn contract_address(
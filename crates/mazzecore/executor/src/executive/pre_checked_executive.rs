@@ -17,6 +17,7 @@ use crate::{
     substate::{cleanup_mode, Substate},
 };
 use mazze_parameters::staking::code_collateral_units;
+use mazze_vm_interpreter::eof::{is_eof, split_initcode};
 use mazze_vm_types::{
     self as vm, ActionParams, ActionValue, CallType, CreateContractAddress,
     CreateType,
@@ -24,7 +25,10 @@ use mazze_vm_types::{
 
 use mazze_statedb::Result as DbResult;
 use mazze_types::{Address, AddressSpaceUtil, Space, U256, U512};
-use primitives::{transaction::Action, SignedTransaction};
+use primitives::{
+    transaction::{parse_delegation_designator, Action},
+    SignedTransaction,
+};
 use std::{convert::TryInto, sync::Arc};
 
 pub(super) struct PreCheckedExecutive<'a, O: ExecutiveObserver> {
@@ -50,6 +54,8 @@ impl<'a, O: ExecutiveObserver> PreCheckedExecutive<'a, O> {
             return self.finalize_on_insufficient_balance(actual_gas_cost);
         }
 
+        self.process_authorizations()?;
+
         let params = self.make_action_params()?;
         if self.tx.space() == Space::Native
             && !self.check_create_address(&params)?
@@ -80,6 +86,55 @@ impl<'a, O: ExecutiveObserver> PreCheckedExecutive<'a, O> {
         self.context.state.inc_nonce(&self.tx.sender())
     }
 
+    /// Apply the authorization list of an EIP-7702 transaction. Invalid
+    /// authorizations are skipped. The authorities which already exist are
+    /// added to the refund counter, since the intrinsic gas charges every
+    /// authority as an empty account.
+    fn process_authorizations(&mut self) -> DbResult<()> {
+        let authorization_list = match self.tx.authorization_list() {
+            Some(authorization_list) => authorization_list,
+            None => return Ok(()),
+        };
+        let spec = self.context.spec;
+        let chain_id = U256::from(self.context.env.chain_id[&Space::Ethereum]);
+        let state = &mut *self.context.state;
+
+        let mut refund = U256::zero();
+        for item in authorization_list {
+            if !item.chain_id.is_zero() && item.chain_id != chain_id {
+                continue;
+            }
+            if item.nonce == u64::MAX {
+                continue;
+            }
+            let authority = match item.authority() {
+                Some(authority) => authority.with_evm_space(),
+                None => continue,
+            };
+            // Only accounts without code or already delegated can delegate.
+            let code = state.code(&authority)?;
+            if code.map_or(false, |code| {
+                !code.is_empty() && parse_delegation_designator(&code).is_none()
+            }) {
+                continue;
+            }
+            if state.nonce(&authority)? != U256::from(item.nonce) {
+                continue;
+            }
+
+            if state.exists(&authority)? {
+                refund += U256::from(
+                    spec.per_empty_account_cost - spec.per_auth_base_cost,
+                );
+            }
+            state.set_delegation(&authority, &item.address)?;
+            state.inc_nonce(&authority)?;
+        }
+
+        self.substate.refund_counter += refund;
+        Ok(())
+    }
+
     fn charge_gas<'t>(&mut self) -> DbResult<(U256, bool)> {
         let sender = self.tx.sender();
         let spec = self.context.spec;
@@ -133,9 +188,17 @@ impl<'a, O: ExecutiveObserver> PreCheckedExecutive<'a, O> {
                 actual_gas_cost,
             );
 
-            self.context
-                .state
-                .sub_sponsor_balance_for_gas(&code_address, &actual_gas_cost)?;
+            if self.tx.space() == Space::Ethereum {
+                self.context.state.sub_eth_sponsor_balance_for_gas(
+                    &code_address,
+                    &actual_gas_cost,
+                )?;
+            } else {
+                self.context.state.sub_sponsor_balance_for_gas(
+                    &code_address,
+                    &actual_gas_cost,
+                )?;
+            }
         }
 
         // Don't subtract total_evm_balance here. It is maintained properly in
@@ -149,6 +212,7 @@ impl<'a, O: ExecutiveObserver> PreCheckedExecutive<'a, O> {
         let cost = &self.cost;
         let env = self.context.env;
         let state = &*self.context.state;
+        let spec = self.context.spec;
         let sender = tx.sender();
         let nonce = tx.nonce();
 
@@ -162,12 +226,30 @@ impl<'a, O: ExecutiveObserver> PreCheckedExecutive<'a, O> {
                     }
                     Space::Ethereum => CreateContractAddress::FromSenderNonce,
                 };
+                // The data of an EOF creation is the initcode container
+                // followed by its input data (EIP-7698). A malformed container
+                // is kept whole and fails the creation in the interpreter.
+                let (code, data, create_type) = match tx.space() {
+                    space if *spec.eof.in_space(space) && is_eof(tx.data()) => {
+                        match split_initcode(tx.data()) {
+                            Some((code, data)) => (
+                                code.to_vec(),
+                                Some(data.to_vec()),
+                                CreateType::EOFCREATE,
+                            ),
+                            None => {
+                                (tx.data().clone(), None, CreateType::EOFCREATE)
+                            }
+                        }
+                    }
+                    _ => (tx.data().clone(), None, CreateType::CREATE),
+                };
                 let (new_address, code_hash) = contract_address(
                     address_scheme,
                     env.number.into(),
                     &sender,
                     &nonce,
-                    &tx.data(),
+                    &code,
                 );
 
                 Ok(ActionParams {
@@ -181,10 +263,10 @@ impl<'a, O: ExecutiveObserver> PreCheckedExecutive<'a, O> {
                     gas: init_gas,
                     gas_price: cost.gas_price,
                     value: ActionValue::Transfer(*tx.value()),
-                    code: Some(Arc::new(tx.data().clone())),
-                    data: None,
+                    code: Some(Arc::new(code)),
+                    data,
                     call_type: CallType::None,
-                    create_type: CreateType::CREATE,
+                    create_type,
                     params_type: vm::ParamsType::Embedded,
                 })
             }
@@ -195,6 +277,8 @@ impl<'a, O: ExecutiveObserver> PreCheckedExecutive<'a, O> {
                 } else {
                     sender.address
                 };
+                let (code, code_hash) =
+                    state.code_for_call(&receipient, spec.eip7702)?;
                 Ok(ActionParams {
                     space: sender.space,
                     code_address: receipient.address,
@@ -205,8 +289,8 @@ impl<'a, O: ExecutiveObserver> PreCheckedExecutive<'a, O> {
                     gas: init_gas,
                     gas_price: cost.gas_price,
                     value: ActionValue::Transfer(*tx.value()),
-                    code: state.code(&receipient)?,
-                    code_hash: state.code_hash(&receipient)?,
+                    code,
+                    code_hash,
                     data: Some(tx.data().clone()),
                     call_type: CallType::Call,
                     create_type: CreateType::None,
@@ -479,7 +563,7 @@ impl<'a, O: ExecutiveObserver> PreCheckedExecutive<'a, O> {
         // gas_left should be smaller than 1/4 of gas_limit, otherwise
         // 3/4 of gas_limit is charged.
         let charge_all = (gas_left + gas_left + gas_left) >= gas_used;
-        let (gas_charged, gas_refunded) = if charge_all {
+        let (mut gas_charged, mut gas_refunded) = if charge_all {
             let gas_refunded = tx.gas() >> 2;
             let gas_charged = tx.gas() - gas_refunded;
             (gas_charged, gas_refunded)
@@ -487,6 +571,14 @@ impl<'a, O: ExecutiveObserver> PreCheckedExecutive<'a, O> {
             (gas_used, gas_left)
         };
 
+        // The refund counter is applied on top of the minimum charge and
+        // survives failed executions, capped at 1/5 of the gas charged as in
+        // EIP-3529.
+        let counter_refund =
+            U256::min(self.substate.refund_counter, gas_charged / 5);
+        gas_charged -= counter_refund;
+        gas_refunded += counter_refund;
+
         let fees_value = gas_charged.saturating_mul(cost.gas_price);
         let burnt_fees_value = spec
             .cip1559
@@ -511,7 +603,23 @@ impl<'a, O: ExecutiveObserver> PreCheckedExecutive<'a, O> {
         let state = &mut context.state;
         let mut tracer = self.observer.as_tracer();
 
-        if cost.gas_sponsored {
+        if cost.gas_sponsored && self.tx.space() == Space::Ethereum {
+            // The eSpace sponsor is bound to the callee of the transaction,
+            // which may delegate its code to another address.
+            let contract_address = match self.tx.action() {
+                Action::Create => Address::zero(),
+                Action::Call(ref address) => *address,
+            };
+            tracer.trace_internal_transfer(
+                AddressPocket::GasPayment,
+                AddressPocket::SponsorBalanceForGas(contract_address),
+                refund_value.clone(),
+            );
+            state.add_eth_sponsor_balance_for_gas(
+                &contract_address,
+                &refund_value,
+            )?;
+        } else if cost.gas_sponsored {
             tracer.trace_internal_transfer(
                 AddressPocket::GasPayment,
                 AddressPocket::SponsorBalanceForGas(params.code_address),
@@ -584,12 +692,19 @@ impl<'a, O: ExecutiveObserver> PreCheckedExecutive<'a, O> {
 
         let outcome = match result {
             Err(vm::Error::StateDbError(e)) => bail!(e.0),
-            Err(exception) => ExecutionOutcome::ExecutionErrorBumpNonce(
-                ExecutionError::VmError(exception),
-                Executed::execution_error_fully_charged(
+            Err(exception) => {
+                let mut executed = Executed::execution_error_fully_charged(
                     tx, cost, ext_result, spec,
-                ),
-            ),
+                );
+                // The refund counter is still honoured.
+                executed.gas_charged = refund_info.gas_charged;
+                executed.fee = refund_info.fees_value;
+                executed.burnt_fee = refund_info.burnt_fees_value;
+                ExecutionOutcome::ExecutionErrorBumpNonce(
+                    ExecutionError::VmError(exception),
+                    executed,
+                )
+            }
             Ok(r) => {
                 let executed = Executed::from_executive_return(
                     &r,
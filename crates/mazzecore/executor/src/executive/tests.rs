@@ -1,6 +1,6 @@
 
 
-use super::*;
+use super::{execution_outcome::ToRepackError, *};
 use crate::{
     machine::{Machine, VmFactory},
     state::{get_state_for_genesis_write, CleanupMode, State},
@@ -8,7 +8,10 @@ use crate::{
 };
 use mazze_internal_common::debug::ComputeEpochDebugRecord;
 use mazze_parameters::{
-    internal_contract_addresses::STORAGE_INTEREST_STAKING_CONTRACT_ADDRESS,
+    internal_contract_addresses::{
+        ETH_SPONSOR_CONTROL_CONTRACT_ADDRESS,
+        STORAGE_INTEREST_STAKING_CONTRACT_ADDRESS,
+    },
     staking::*,
 };
 use mazze_statedb::StateDb;
@@ -17,19 +20,23 @@ use mazze_storage::{
     StateIndex,
 };
 use mazze_types::{
-    address_util::AddressUtil, Address, AddressSpaceUtil, BigEndianHash, U256,
-    U512,
+    address_util::AddressUtil, Address, AddressSpaceUtil, BigEndianHash,
+    Space, U256, U512,
 };
 use mazze_vm_interpreter::{FinalizationResult, GasPriceTier};
 use mazze_vm_types::{
     self as vm, ActionParams, ActionValue, CallType, CreateContractAddress,
-    CreateType, Env,
+    CreateType, Env, Spec,
 };
-use mazzekey::{Generator, Random};
+use mazzekey::{sign, Generator, KeyPair, Random};
 use primitives::{
     storage::STORAGE_LAYOUT_REGULAR_V0,
-    transaction::{native_transaction::NativeTransaction, Action},
-    EpochId, Transaction,
+    transaction::{
+        delegation_designator, native_transaction::NativeTransaction, Action,
+        AuthorizationListItem, Eip155Transaction, Eip7702Transaction,
+        EIP7702_TYPE,
+    },
+    EpochId, Transaction, TransactionStatus,
 };
 use rustc_hex::FromHex;
 use std::{
@@ -2276,3 +2283,466 @@ fn test_push0() {
         assert!(matches!(error, vm::Error::BadInstruction { .. }));
     }
 }
+
+#[test]
+fn test_eip7702_delegation() {
+    let sender = Random.generate().unwrap();
+    let authority = Random.generate().unwrap();
+    let authority_address = authority.evm_address().with_evm_space();
+    let delegate_address = Address::from_low_u64_be(0x7702).with_evm_space();
+
+    let mut env = Env::default();
+    env.gas_limit = U256::from(1_000_000);
+    env.chain_id.insert(Space::Ethereum, 1);
+    let machine = make_byzantium_machine(0);
+    let mut spec = machine.spec_for_test(env.number);
+
+    let storage_manager = new_state_manager_for_unit_test();
+    let mut state = get_state_for_genesis_write(&storage_manager);
+    state
+        .add_balance(
+            &sender.evm_address().with_evm_space(),
+            &U256::from(1_000_000),
+            CleanupMode::NoEmpty,
+        )
+        .unwrap();
+    // code:
+    //
+    // 60 01 - push 1
+    // 60 00 - push 0
+    // 55 - sstore
+    state
+        .new_contract_with_code(&delegate_address, U256::zero())
+        .unwrap();
+    state
+        .init_code(
+            &delegate_address,
+            "6001600055".from_hex().unwrap(),
+            Address::zero(),
+        )
+        .unwrap();
+
+    let mut authorization = AuthorizationListItem {
+        chain_id: U256::one(),
+        address: delegate_address.address,
+        nonce: 0,
+        y_parity: 0,
+        r: U256::zero(),
+        s: U256::zero(),
+    };
+    let signature =
+        sign(authority.secret(), &authorization.signature_hash()).unwrap();
+    authorization.y_parity = signature.v();
+    authorization.r = U256::from_big_endian(signature.r());
+    authorization.s = U256::from_big_endian(signature.s());
+
+    let tx = Transaction::from(Eip7702Transaction {
+        chain_id: 1,
+        nonce: U256::zero(),
+        max_priority_fee_per_gas: U256::zero(),
+        max_fee_per_gas: U256::one(),
+        gas: U256::from(100_000),
+        action: Action::Call(authority_address.address),
+        value: U256::zero(),
+        data: vec![],
+        access_list: vec![],
+        authorization_list: vec![authorization],
+    })
+    .sign(sender.secret());
+
+    // Before activation of EIP-7702
+    {
+        spec.eip7702 = false;
+        let ex = ExecutiveContext::new(&mut state, &env, &machine, &spec);
+        let res = ex.transact(&tx, TransactOptions::default()).unwrap();
+        assert!(matches!(
+            res,
+            ExecutionOutcome::NotExecutedToReconsiderPacking(
+                ToRepackError::TransactionTypeNotActivated(EIP7702_TYPE)
+            )
+        ));
+    }
+
+    spec.eip7702 = true;
+    let res = {
+        let ex = ExecutiveContext::new(&mut state, &env, &machine, &spec);
+        ex.transact(&tx, TransactOptions::default()).unwrap()
+    };
+    let executed = match res {
+        ExecutionOutcome::Finished(executed) => executed,
+        _ => panic!("Expected the transaction to succeed. {:?}", res),
+    };
+    let intrinsic_gas = 21000 + spec.per_empty_account_cost;
+    assert!(executed.gas_used >= U256::from(intrinsic_gas));
+
+    // The authority is delegated and runs the code of the delegate on its
+    // own storage.
+    assert_eq!(
+        state.code(&authority_address).unwrap().unwrap().as_slice(),
+        delegation_designator(&delegate_address.address).as_slice()
+    );
+    assert_eq!(state.nonce(&authority_address).unwrap(), U256::one());
+    assert_eq!(
+        state.storage_at(&authority_address, &[0; 32]).unwrap(),
+        U256::one()
+    );
+    assert_eq!(
+        state.storage_at(&delegate_address, &[0; 32]).unwrap(),
+        U256::zero()
+    );
+}
+
+/// Execute an EIP-7702 transaction delegating an existing authority to a
+/// contract running `delegate_code`, with a gas limit of `gas` and a gas
+/// price of 1. Returns the outcome and the balance paid by the sender.
+fn transact_eip7702_with_existing_authority(
+    delegate_code: &str, gas: u64,
+) -> (ExecutionOutcome, U256, Spec) {
+    let sender = Random.generate().unwrap();
+    let sender_address = sender.evm_address().with_evm_space();
+    let authority = Random.generate().unwrap();
+    let authority_address = authority.evm_address().with_evm_space();
+    let delegate_address = Address::from_low_u64_be(0x7702).with_evm_space();
+
+    let mut env = Env::default();
+    env.gas_limit = U256::from(10_000_000);
+    env.chain_id.insert(Space::Ethereum, 1);
+    let machine = make_byzantium_machine(0);
+    let mut spec = machine.spec_for_test(env.number);
+    spec.eip7702 = true;
+
+    let storage_manager = new_state_manager_for_unit_test();
+    let mut state = get_state_for_genesis_write(&storage_manager);
+    let initial_balance = U256::from(10_000_000);
+    state
+        .add_balance(&sender_address, &initial_balance, CleanupMode::NoEmpty)
+        .unwrap();
+    // The authority exists, so its authorization is refunded.
+    state
+        .add_balance(&authority_address, &U256::one(), CleanupMode::NoEmpty)
+        .unwrap();
+    state
+        .new_contract_with_code(&delegate_address, U256::zero())
+        .unwrap();
+    state
+        .init_code(
+            &delegate_address,
+            delegate_code.from_hex().unwrap(),
+            Address::zero(),
+        )
+        .unwrap();
+
+    let mut authorization = AuthorizationListItem {
+        chain_id: U256::one(),
+        address: delegate_address.address,
+        nonce: 0,
+        y_parity: 0,
+        r: U256::zero(),
+        s: U256::zero(),
+    };
+    let signature =
+        sign(authority.secret(), &authorization.signature_hash()).unwrap();
+    authorization.y_parity = signature.v();
+    authorization.r = U256::from_big_endian(signature.r());
+    authorization.s = U256::from_big_endian(signature.s());
+
+    let tx = Transaction::from(Eip7702Transaction {
+        chain_id: 1,
+        nonce: U256::zero(),
+        max_priority_fee_per_gas: U256::one(),
+        max_fee_per_gas: U256::one(),
+        gas: U256::from(gas),
+        action: Action::Call(authority_address.address),
+        value: U256::zero(),
+        data: vec![],
+        access_list: vec![],
+        authorization_list: vec![authorization],
+    })
+    .sign(sender.secret());
+
+    let res = {
+        let ex = ExecutiveContext::new(&mut state, &env, &machine, &spec);
+        ex.transact(&tx, TransactOptions::default()).unwrap()
+    };
+    let paid = initial_balance - state.balance(&sender_address).unwrap();
+    (res, paid, spec)
+}
+
+#[test]
+fn test_eip7702_refund_after_minimum_charge() {
+    // A gas limit far above the gas used, so 3/4 of it is charged.
+    let gas = 1_000_000u64;
+    let (res, paid, spec) =
+        transact_eip7702_with_existing_authority("6001600055", gas);
+    let executed = match res {
+        ExecutionOutcome::Finished(executed) => executed,
+        _ => panic!("Expected the transaction to succeed. {:?}", res),
+    };
+
+    // The refund is applied to the minimum charge instead of being counted
+    // as gas left, which would be swallowed by the minimum charge.
+    let refund = (spec.per_empty_account_cost - spec.per_auth_base_cost) as u64;
+    let minimum_charge = gas - gas / 4;
+    assert!(refund > 0);
+    assert_eq!(executed.gas_charged, U256::from(minimum_charge - refund));
+    assert_eq!(executed.fee, U256::from(minimum_charge - refund));
+    assert_eq!(paid, executed.fee);
+}
+
+#[test]
+fn test_eip7702_refund_on_execution_error() {
+    // code:
+    //
+    // fe - invalid
+    let gas = 100_000u64;
+    let (res, paid, spec) = transact_eip7702_with_existing_authority("fe", gas);
+    let executed = match res {
+        ExecutionOutcome::ExecutionErrorBumpNonce(
+            ExecutionError::VmError(_),
+            executed,
+        ) => executed,
+        _ => panic!("Expected the execution to fail. {:?}", res),
+    };
+
+    // The delegation is kept by the failed execution, so is its refund.
+    let refund = (spec.per_empty_account_cost - spec.per_auth_base_cost) as u64;
+    assert!(refund > 0 && refund <= gas / 5);
+    assert_eq!(executed.gas_used, U256::from(gas));
+    assert_eq!(executed.gas_charged, U256::from(gas - refund));
+    assert_eq!(executed.fee, U256::from(gas - refund));
+    assert_eq!(paid, executed.fee);
+}
+
+/// Send an eSpace call to `contract` with a gas limit of 100_000 and a gas
+/// price of 1.
+fn transact_eth_call(
+    state: &mut State, spec: &Spec, sender: &KeyPair, nonce: u64,
+    contract: Address,
+) -> ExecutionOutcome {
+    let mut env = Env::default();
+    env.gas_limit = U256::from(10_000_000);
+    env.chain_id.insert(Space::Ethereum, 1);
+    let machine = make_byzantium_machine(0);
+
+    let tx = Transaction::from(Eip155Transaction {
+        nonce: nonce.into(),
+        gas_price: U256::one(),
+        gas: U256::from(100_000),
+        action: Action::Call(contract),
+        value: U256::zero(),
+        chain_id: Some(1),
+        data: vec![],
+    })
+    .sign(sender.secret());
+    ExecutiveContext::new(state, &env, &machine, spec)
+        .transact(&tx, TransactOptions::default())
+        .unwrap()
+}
+
+#[test]
+fn test_eth_sponsor() {
+    // code:
+    //
+    // 00 - stop
+    //
+    // A call uses 21_000 gas, so 75_000 gas is charged and the rest is
+    // refunded.
+    let contract = Address::from_low_u64_be(0x5050);
+    let contract_with_space = contract.with_evm_space();
+    let sponsor = Address::from_low_u64_be(0x5051);
+    let sponsor_control = ETH_SPONSOR_CONTROL_CONTRACT_ADDRESS.with_evm_space();
+
+    let machine = make_byzantium_machine(0);
+    let mut spec = machine.spec_for_test(0);
+    spec.eth_sponsor = true;
+
+    let storage_manager = new_state_manager_for_unit_test();
+    let mut state = get_state_for_genesis_write(&storage_manager);
+    state
+        .new_contract_with_code(&contract_with_space, U256::zero())
+        .unwrap();
+    state
+        .init_code(&contract_with_space, vec![0x00], Address::zero())
+        .unwrap();
+
+    let callers: Vec<KeyPair> =
+        (0..3).map(|_| Random.generate().unwrap()).collect();
+    let balance = |state: &State, caller: &KeyPair| {
+        state
+            .balance(&caller.evm_address().with_evm_space())
+            .unwrap()
+    };
+    for caller in &callers {
+        state
+            .add_balance(
+                &caller.evm_address().with_evm_space(),
+                &U256::from(100_000),
+                CleanupMode::NoEmpty,
+            )
+            .unwrap();
+    }
+
+    // add commission privilege to caller 0 and caller 1
+    for caller in &callers[..2] {
+        state
+            .add_to_eth_contract_whitelist(&contract, &caller.evm_address())
+            .unwrap();
+    }
+    assert!(state
+        .check_eth_contract_whitelist(&contract, &callers[1].evm_address())
+        .unwrap());
+    assert!(!state
+        .check_eth_contract_whitelist(&contract, &callers[2].evm_address())
+        .unwrap());
+    state
+        .set_eth_sponsor_for_gas(
+            &contract,
+            &sponsor,
+            &U256::from(110_000),
+            &U256::from(110_000),
+        )
+        .unwrap();
+    state
+        .add_balance(
+            &sponsor_control,
+            &U256::from(110_000),
+            CleanupMode::NoEmpty,
+        )
+        .unwrap();
+
+    // call with no commission privilege
+    let receipt =
+        transact_eth_call(&mut state, &spec, &callers[2], 0, contract)
+            .make_receipt(&mut U256::zero(), &spec);
+    assert_eq!(receipt.outcome_status, TransactionStatus::Success);
+    assert_eq!(receipt.gas_fee, U256::from(75_000));
+    assert!(!receipt.gas_sponsor_paid);
+    assert_eq!(balance(&state, &callers[2]), U256::from(25_000));
+    assert_eq!(
+        state.eth_sponsor_balance_for_gas(&contract).unwrap(),
+        U256::from(110_000)
+    );
+
+    // call with commission privilege and enough commission balance, the
+    // unused gas is refunded to the sponsor
+    let receipt =
+        transact_eth_call(&mut state, &spec, &callers[0], 0, contract)
+            .make_receipt(&mut U256::zero(), &spec);
+    assert_eq!(receipt.outcome_status, TransactionStatus::Success);
+    assert_eq!(receipt.gas_fee, U256::from(75_000));
+    assert!(receipt.gas_sponsor_paid);
+    assert_eq!(balance(&state, &callers[0]), U256::from(100_000));
+    assert_eq!(
+        state.eth_sponsor_balance_for_gas(&contract).unwrap(),
+        U256::from(35_000)
+    );
+    assert_eq!(state.balance(&sponsor_control).unwrap(), U256::from(35_000));
+
+    // call with commission privilege and not enough commission balance
+    let receipt =
+        transact_eth_call(&mut state, &spec, &callers[1], 0, contract)
+            .make_receipt(&mut U256::zero(), &spec);
+    assert!(!receipt.gas_sponsor_paid);
+    assert_eq!(balance(&state, &callers[1]), U256::from(25_000));
+    assert_eq!(
+        state.eth_sponsor_balance_for_gas(&contract).unwrap(),
+        U256::from(35_000)
+    );
+
+    // The sender can't pay the gas the sponsor runs out of, so the
+    // transaction is repacked.
+    let outcome =
+        transact_eth_call(&mut state, &spec, &callers[1], 1, contract);
+    match outcome {
+        ExecutionOutcome::NotExecutedToReconsiderPacking(
+            ToRepackError::NotEnoughCashFromSponsor {
+                required_gas_cost,
+                gas_sponsor_balance,
+                ..
+            },
+        ) if required_gas_cost == U512::from(100_000)
+            && gas_sponsor_balance == U512::from(35_000) => {}
+        _ => panic!("Expected not enough cash from sponsor. {:?}", outcome),
+    }
+    assert_eq!(
+        state
+            .nonce(&callers[1].evm_address().with_evm_space())
+            .unwrap(),
+        U256::one()
+    );
+    assert_eq!(balance(&state, &callers[1]), U256::from(25_000));
+
+    // The gas cost exceeds the upper bound.
+    state
+        .set_eth_sponsor_for_gas(
+            &contract,
+            &sponsor,
+            &U256::from(200_000),
+            &U256::from(50_000),
+        )
+        .unwrap();
+    state
+        .add_balance(
+            &sponsor_control,
+            &U256::from(165_000),
+            CleanupMode::NoEmpty,
+        )
+        .unwrap();
+    let receipt =
+        transact_eth_call(&mut state, &spec, &callers[0], 1, contract)
+            .make_receipt(&mut U256::zero(), &spec);
+    assert!(!receipt.gas_sponsor_paid);
+    assert_eq!(balance(&state, &callers[0]), U256::from(25_000));
+    assert_eq!(
+        state.eth_sponsor_balance_for_gas(&contract).unwrap(),
+        U256::from(200_000)
+    );
+
+    // add commission privilege to all the callers
+    state
+        .set_eth_sponsor_for_gas(
+            &contract,
+            &sponsor,
+            &U256::from(200_000),
+            &U256::from(200_000),
+        )
+        .unwrap();
+    state
+        .add_to_eth_contract_whitelist(&contract, &Address::zero())
+        .unwrap();
+    assert!(state
+        .check_eth_contract_whitelist(&contract, &callers[2].evm_address())
+        .unwrap());
+    let receipt =
+        transact_eth_call(&mut state, &spec, &callers[2], 1, contract)
+            .make_receipt(&mut U256::zero(), &spec);
+    assert!(receipt.gas_sponsor_paid);
+    assert_eq!(balance(&state, &callers[2]), U256::from(25_000));
+    assert_eq!(
+        state.eth_sponsor_balance_for_gas(&contract).unwrap(),
+        U256::from(125_000)
+    );
+    assert_eq!(
+        state.balance(&sponsor_control).unwrap(),
+        U256::from(125_000)
+    );
+
+    // Before the activation, the sender always pays the gas.
+    spec.eth_sponsor = false;
+    state
+        .add_balance(
+            &callers[0].evm_address().with_evm_space(),
+            &U256::from(100_000),
+            CleanupMode::NoEmpty,
+        )
+        .unwrap();
+    let receipt =
+        transact_eth_call(&mut state, &spec, &callers[0], 2, contract)
+            .make_receipt(&mut U256::zero(), &spec);
+    assert!(!receipt.gas_sponsor_paid);
+    assert_eq!(balance(&state, &callers[0]), U256::from(50_000));
+    assert_eq!(
+        state.eth_sponsor_balance_for_gas(&contract).unwrap(),
+        U256::from(125_000)
+    );
+}
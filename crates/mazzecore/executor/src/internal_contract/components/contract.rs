@@ -3,7 +3,7 @@
 use std::{collections::HashMap, sync::Arc};
 
 use mazze_bytes::Bytes;
-use mazze_types::{Address, H256};
+use mazze_types::{Address, Space, H256};
 use mazze_vm_types::{self as vm, ActionParams, GasLeft, Spec};
 use keccak_hash::keccak;
 use primitives::BlockNumber;
@@ -26,6 +26,9 @@ pub trait InternalContractTrait: Send + Sync + IsActive {
     /// Address of the internal contract
     fn address(&self) -> &Address;
 
+    /// The space of the internal contract
+    fn space(&self) -> Space { Space::Native }
+
     /// Time point to run `new_contract_with_admin` for such a internal contract
     fn initialize_block(&self, params: &CommonParams) -> BlockNumber;
 
@@ -91,7 +94,7 @@ macro_rules! make_solidity_contract {
             $(#[$attr])* $visibility struct $name ($addr, $gen_table, initialize: |_: &CommonParams| 0u64, is_active: |_: &Spec| true);
         }
     };
-    ( $(#[$attr:meta])* $visibility:vis struct $name:ident ($addr:expr, $gen_table:expr, initialize: $init:expr, is_active: $is_active:expr); ) => {
+    ( $(#[$attr:meta])* $visibility:vis struct $name:ident ($addr:expr, $gen_table:expr, initialize: $init:expr, is_active: $is_active:expr $(, space: $space:expr)?); ) => {
         $(#[$attr])*
         $visibility struct $name {
             function_table: SolFnTable
@@ -109,6 +112,7 @@ macro_rules! make_solidity_contract {
             fn address(&self) -> &Address { &$addr }
             fn get_func_table(&self) -> &SolFnTable { &self.function_table }
             fn initialize_block(&self, param: &CommonParams) -> BlockNumber{ $init(param) }
+            $(fn space(&self) -> mazze_types::Space { $space })?
         }
 
         impl IsActive for $name {
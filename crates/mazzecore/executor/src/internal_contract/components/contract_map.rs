@@ -1,6 +1,6 @@
 use std::collections::BTreeMap;
 
-use mazze_types::{Address, AddressWithSpace, Space};
+use mazze_types::{Address, AddressSpaceUtil, AddressWithSpace};
 use mazze_vm_types::Spec;
 use primitives::BlockNumber;
 
@@ -10,7 +10,7 @@ use crate::spec::CommonParams;
 #[derive(Default)]
 pub struct InternalContractMap {
     builtin: BTreeMap<Address, Box<dyn InternalContractTrait>>,
-    activation_info: BTreeMap<BlockNumber, Vec<Address>>,
+    activation_info: BTreeMap<BlockNumber, Vec<AddressWithSpace>>,
 }
 
 impl std::ops::Deref for InternalContractMap {
@@ -30,7 +30,7 @@ impl InternalContractMap {
         let mut internal_contracts = all_internal_contracts();
 
         while let Some(contract) = internal_contracts.pop() {
-            let address = *contract.address();
+            let address = contract.address().with_space(contract.space());
             let transition_block = if params.early_set_internal_contracts_states
             {
                 0
@@ -52,18 +52,18 @@ impl InternalContractMap {
     }
 
     #[cfg(test)]
-    pub fn initialize_for_test() -> Vec<Address> {
+    pub fn initialize_for_test() -> Vec<AddressWithSpace> {
         all_internal_contracts()
             .iter()
-            .map(|contract| *contract.address())
+            .map(|contract| contract.address().with_space(contract.space()))
             .collect()
     }
 
-    pub fn initialized_at_genesis(&self) -> &[Address] {
+    pub fn initialized_at_genesis(&self) -> &[AddressWithSpace] {
         self.initialized_at(0)
     }
 
-    pub fn initialized_at(&self, number: BlockNumber) -> &[Address] {
+    pub fn initialized_at(&self, number: BlockNumber) -> &[AddressWithSpace] {
         self.activation_info
             .get(&number)
             .map_or(&[], |vec| vec.as_slice())
@@ -72,11 +72,8 @@ impl InternalContractMap {
     pub fn contract(
         &self, address: &AddressWithSpace, spec: &Spec,
     ) -> Option<&Box<dyn InternalContractTrait>> {
-        if address.space != Space::Native {
-            return None;
-        }
-        self.builtin
-            .get(&address.address)
-            .filter(|&contract| contract.is_active(spec))
+        self.builtin.get(&address.address).filter(|&contract| {
+            contract.space() == address.space && contract.is_active(spec)
+        })
     }
 }
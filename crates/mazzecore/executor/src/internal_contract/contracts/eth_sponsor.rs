@@ -0,0 +1,196 @@
+use super::{super::impls::eth_sponsor::*, preludes::*};
+use mazze_parameters::internal_contract_addresses::ETH_SPONSOR_CONTROL_CONTRACT_ADDRESS;
+use mazze_types::{Address, Space, U256};
+
+make_solidity_contract! {
+    pub struct EthSponsorControl(ETH_SPONSOR_CONTROL_CONTRACT_ADDRESS, generate_fn_table, initialize: |params: &CommonParams| params.transition_numbers.eth_sponsor, is_active: |spec: &Spec| spec.eth_sponsor, space: Space::Ethereum);
+}
+// The eSpace charges no storage collateral, so unlike
+// `SponsorWhitelistControl` there is no sponsor for collateral.
+fn generate_fn_table() -> SolFnTable {
+    make_function_table!(
+        SetSponsorForGas,
+        AddPrivilege,
+        RemovePrivilege,
+        GetSponsorForGas,
+        GetSponsoredBalanceForGas,
+        GetSponsoredGasFeeUpperBound,
+        IsWhitelisted,
+        IsAllWhitelisted
+    )
+}
+group_impl_is_active!(
+    |spec: &Spec| spec.eth_sponsor,
+    SetSponsorForGas,
+    AddPrivilege,
+    RemovePrivilege,
+    GetSponsorForGas,
+    GetSponsoredBalanceForGas,
+    GetSponsoredGasFeeUpperBound,
+    IsWhitelisted,
+    IsAllWhitelisted,
+);
+
+make_solidity_function! {
+    struct SetSponsorForGas((Address, U256), "setSponsorForGas(address,uint256)");
+}
+impl_function_type!(SetSponsorForGas, "payable_write", gas: |spec: &Spec| 2 * spec.sstore_reset_gas);
+
+impl SimpleExecutionTrait for SetSponsorForGas {
+    fn execute_inner(
+        &self, inputs: (Address, U256), params: &ActionParams,
+        context: &mut InternalRefContext,
+    ) -> vm::Result<()> {
+        set_eth_sponsor_for_gas(inputs.0, inputs.1, params, context)
+    }
+}
+
+make_solidity_function! {
+    struct AddPrivilege(Vec<Address>, "addPrivilege(address[])");
+}
+impl_function_type!(AddPrivilege, "non_payable_write");
+
+impl UpfrontPaymentTrait for AddPrivilege {
+    fn upfront_gas_payment(
+        &self, input: &Vec<Address>, _: &ActionParams,
+        context: &InternalRefContext,
+    ) -> DbResult<U256> {
+        Ok(U256::from(context.spec.sstore_reset_gas) * input.len())
+    }
+}
+
+impl SimpleExecutionTrait for AddPrivilege {
+    fn execute_inner(
+        &self, addresses: Vec<Address>, params: &ActionParams,
+        context: &mut InternalRefContext,
+    ) -> vm::Result<()> {
+        // The eSpace addresses carry no type bits, but a normal account can
+        // only be the sender of the outermost call.
+        if params.sender == params.original_sender {
+            return Err(vm::Error::InternalContract(
+                "normal account is not allowed to set commission_privilege"
+                    .into(),
+            ));
+        }
+        add_eth_privilege(params.sender, addresses, context.state)
+    }
+}
+
+make_solidity_function! {
+    struct RemovePrivilege(Vec<Address>, "removePrivilege(address[])");
+}
+impl_function_type!(RemovePrivilege, "non_payable_write");
+
+impl UpfrontPaymentTrait for RemovePrivilege {
+    fn upfront_gas_payment(
+        &self, input: &Vec<Address>, _: &ActionParams,
+        context: &InternalRefContext,
+    ) -> DbResult<U256> {
+        Ok(U256::from(context.spec.sstore_reset_gas) * input.len())
+    }
+}
+
+impl SimpleExecutionTrait for RemovePrivilege {
+    fn execute_inner(
+        &self, addresses: Vec<Address>, params: &ActionParams,
+        context: &mut InternalRefContext,
+    ) -> vm::Result<()> {
+        if params.sender == params.original_sender {
+            return Err(vm::Error::InternalContract(
+                "normal account is not allowed to set commission_privilege"
+                    .into(),
+            ));
+        }
+        remove_eth_privilege(params.sender, addresses, context.state)
+    }
+}
+
+make_solidity_function! {
+    struct GetSponsorForGas(Address, "getSponsorForGas(address)", Address);
+}
+impl_function_type!(GetSponsorForGas, "query_with_default_gas");
+
+impl SimpleExecutionTrait for GetSponsorForGas {
+    fn execute_inner(
+        &self, input: Address, _: &ActionParams,
+        context: &mut InternalRefContext,
+    ) -> vm::Result<Address> {
+        Ok(context
+            .state
+            .eth_sponsor_for_gas(&input)?
+            .unwrap_or_default())
+    }
+}
+
+make_solidity_function! {
+    struct GetSponsoredBalanceForGas(Address, "getSponsoredBalanceForGas(address)", U256);
+}
+impl_function_type!(GetSponsoredBalanceForGas, "query_with_default_gas");
+
+impl SimpleExecutionTrait for GetSponsoredBalanceForGas {
+    fn execute_inner(
+        &self, input: Address, _: &ActionParams,
+        context: &mut InternalRefContext,
+    ) -> vm::Result<U256> {
+        Ok(context.state.eth_sponsor_balance_for_gas(&input)?)
+    }
+}
+
+make_solidity_function! {
+    struct GetSponsoredGasFeeUpperBound(Address, "getSponsoredGasFeeUpperBound(address)", U256);
+}
+impl_function_type!(GetSponsoredGasFeeUpperBound, "query_with_default_gas");
+
+impl SimpleExecutionTrait for GetSponsoredGasFeeUpperBound {
+    fn execute_inner(
+        &self, input: Address, _: &ActionParams,
+        context: &mut InternalRefContext,
+    ) -> vm::Result<U256> {
+        Ok(context.state.eth_sponsor_gas_bound(&input)?)
+    }
+}
+
+make_solidity_function! {
+    struct IsWhitelisted((Address,Address), "isWhitelisted(address,address)", bool);
+}
+impl_function_type!(IsWhitelisted, "query", gas: |spec: &Spec| spec.sload_gas);
+
+impl SimpleExecutionTrait for IsWhitelisted {
+    fn execute_inner(
+        &self, (contract, user): (Address, Address), _: &ActionParams,
+        context: &mut InternalRefContext,
+    ) -> vm::Result<bool> {
+        Ok(context
+            .state
+            .check_eth_contract_whitelist(&contract, &user)?)
+    }
+}
+
+make_solidity_function! {
+    struct IsAllWhitelisted(Address, "isAllWhitelisted(address)", bool);
+}
+impl_function_type!(IsAllWhitelisted, "query", gas: |spec: &Spec| spec.sload_gas);
+
+impl SimpleExecutionTrait for IsAllWhitelisted {
+    fn execute_inner(
+        &self, contract: Address, _: &ActionParams,
+        context: &mut InternalRefContext,
+    ) -> vm::Result<bool> {
+        Ok(context
+            .state
+            .check_eth_contract_whitelist(&contract, &Address::zero())?)
+    }
+}
+
+#[test]
+fn test_eth_sponsor_contract_sig() {
+    // The eSpace sponsor control shares the interface of the native one.
+    check_func_signature!(GetSponsorForGas, "33a1af31");
+    check_func_signature!(GetSponsoredBalanceForGas, "b3b28fac");
+    check_func_signature!(GetSponsoredGasFeeUpperBound, "d665f9dd");
+    check_func_signature!(IsWhitelisted, "b6b35272");
+    check_func_signature!(IsAllWhitelisted, "79b47faa");
+    check_func_signature!(SetSponsorForGas, "3e3e6428");
+    check_func_signature!(AddPrivilege, "10128d3e");
+    check_func_signature!(RemovePrivilege, "d2932db6");
+}
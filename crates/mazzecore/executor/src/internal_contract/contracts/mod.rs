@@ -3,6 +3,7 @@
 mod admin;
 mod context;
 pub(super) mod cross_space;
+mod eth_sponsor;
 mod future;
 pub(super) mod params_control;
 pub(super) mod pos;
@@ -54,6 +55,7 @@ pub fn all_internal_contracts() -> Vec<Box<dyn super::InternalContractTrait>> {
         Box::new(cross_space::CrossSpaceCall::instance()),
         Box::new(params_control::ParamsControl::instance()),
         Box::new(system_storage::SystemStorage::instance()),
+        Box::new(eth_sponsor::EthSponsorControl::instance()),
         Box::new(future::Reserved3::instance()),
         Box::new(future::Reserved8::instance()),
         Box::new(future::Reserved9::instance()),
@@ -63,18 +65,22 @@ pub fn all_internal_contracts() -> Vec<Box<dyn super::InternalContractTrait>> {
 
 use crate::state::State;
 use mazze_statedb::Result as DbResult;
-use mazze_types::{Address, AddressSpaceUtil, U256};
+use mazze_types::{Address, AddressWithSpace, Space, U256};
 use primitives::storage::STORAGE_LAYOUT_REGULAR_V0;
 
 pub fn initialize_internal_contract_accounts(
-    state: &mut State, addresses: &[Address],
+    state: &mut State, addresses: &[AddressWithSpace],
 ) -> DbResult<()> {
     for address in addresses {
+        let storage_layout = match address.space {
+            Space::Native => Some(STORAGE_LAYOUT_REGULAR_V0),
+            Space::Ethereum => None,
+        };
         state.new_contract_with_admin(
-            &address.with_native_space(),
+            address,
             /* No admin; admin = */ &Address::zero(),
             /* balance = */ U256::zero(),
-            Some(STORAGE_LAYOUT_REGULAR_V0),
+            storage_layout,
             false,
         )?;
     }
@@ -0,0 +1,133 @@
+use crate::{
+    executive_observer::AddressPocket,
+    internal_bail,
+    state::State,
+    substate::{cleanup_mode, Substate},
+};
+use mazze_types::{Address, AddressSpaceUtil, U256};
+use mazze_vm_types::{self as vm, ActionParams, Spec};
+
+use super::super::components::InternalRefContext;
+
+/// Implementation of `setSponsorForGas(address,uint256)` in the eSpace. The
+/// sponsor balance is kept in the balance of the sponsor control contract.
+pub fn set_eth_sponsor_for_gas(
+    contract_address: Address, upper_bound: U256, params: &ActionParams,
+    context: &mut InternalRefContext,
+) -> vm::Result<()> {
+    let sponsor = &params.sender;
+
+    if !context
+        .state
+        .is_contract_with_code(&contract_address.with_evm_space())?
+    {
+        internal_bail!("not allowed to sponsor non-contract account");
+    }
+
+    let (spec, state, substate): (&Spec, &mut State, &mut Substate) =
+        (context.spec, context.state, context.substate);
+
+    let sponsor_balance = params.value.value();
+
+    if sponsor_balance / U256::from(1000) < upper_bound {
+        internal_bail!("sponsor should at least sponsor upper_bound * 1000");
+    }
+
+    let prev_sponsor = state.eth_sponsor_for_gas(&contract_address)?;
+    let prev_sponsor_balance =
+        state.eth_sponsor_balance_for_gas(&contract_address)?;
+    let prev_upper_bound = state.eth_sponsor_gas_bound(&contract_address)?;
+    // If previous sponsor is not the same as current sponsor, we should try
+    // to replace the sponsor. Otherwise, we should try to charge
+    // `sponsor_balance`.
+    if prev_sponsor.as_ref().map_or_else(
+        || !sponsor.is_zero(),
+        |prev_sponsor| prev_sponsor != sponsor,
+    ) {
+        // `sponsor_balance` should exceed previous sponsor's
+        // `sponsor_balance`.
+        if sponsor_balance <= prev_sponsor_balance {
+            internal_bail!("sponsor_balance is not exceed previous sponsor");
+        }
+        // `upper_bound` should exceed previous sponsor's `upper_bound`,
+        // unless previous sponsor's `sponsor_balance` is not able to cover
+        // the upper bound.
+        if prev_sponsor_balance >= prev_upper_bound
+            && upper_bound < prev_upper_bound
+        {
+            internal_bail!("upper_bound is not exceed previous sponsor");
+        }
+        // refund to previous sponsor
+        if let Some(ref prev_sponsor) = prev_sponsor {
+            context.tracer.trace_internal_transfer(
+                AddressPocket::SponsorBalanceForGas(contract_address),
+                AddressPocket::Balance(prev_sponsor.with_evm_space()),
+                prev_sponsor_balance,
+            );
+            state.sub_balance(
+                &params.address.with_evm_space(),
+                &prev_sponsor_balance,
+                &mut cleanup_mode(substate, &spec),
+            )?;
+            state.add_balance(
+                &prev_sponsor.with_evm_space(),
+                &prev_sponsor_balance,
+                cleanup_mode(substate, &spec),
+            )?;
+        }
+        context.tracer.trace_internal_transfer(
+            AddressPocket::Balance(params.address.with_evm_space()),
+            AddressPocket::SponsorBalanceForGas(contract_address),
+            sponsor_balance,
+        );
+        state.set_eth_sponsor_for_gas(
+            &contract_address,
+            sponsor,
+            &sponsor_balance,
+            &upper_bound,
+        )?;
+    } else {
+        // if previous sponsor's `sponsor_balance` is not able to cover
+        // the `upper_bound`, we can adjust the `upper_bound` to a smaller
+        // one.
+        if prev_sponsor_balance >= prev_upper_bound
+            && upper_bound < prev_upper_bound
+        {
+            internal_bail!("cannot change upper_bound to a smaller one");
+        }
+
+        context.tracer.trace_internal_transfer(
+            AddressPocket::Balance(params.address.with_evm_space()),
+            AddressPocket::SponsorBalanceForGas(contract_address),
+            sponsor_balance,
+        );
+        state.set_eth_sponsor_for_gas(
+            &contract_address,
+            sponsor,
+            &(sponsor_balance + prev_sponsor_balance),
+            &upper_bound,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Implementation of `addPrivilege(address[])` in the eSpace.
+pub fn add_eth_privilege(
+    contract: Address, addresses: Vec<Address>, state: &mut State,
+) -> vm::Result<()> {
+    for user_addr in addresses {
+        state.add_to_eth_contract_whitelist(&contract, &user_addr)?;
+    }
+    Ok(())
+}
+
+/// Implementation of `removePrivilege(address[])` in the eSpace.
+pub fn remove_eth_privilege(
+    contract: Address, addresses: Vec<Address>, state: &mut State,
+) -> vm::Result<()> {
+    for user_addr in addresses {
+        state.remove_from_eth_contract_whitelist(&contract, &user_addr)?;
+    }
+    Ok(())
+}
@@ -3,6 +3,7 @@
 pub(super) mod admin;
 pub(super) mod context;
 pub(super) mod cross_space;
+pub(super) mod eth_sponsor;
 pub(super) mod params_control;
 pub(super) mod pos;
 pub(super) mod sponsor;
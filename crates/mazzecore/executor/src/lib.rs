@@ -36,6 +36,10 @@ pub mod machine;
 /// Tool Macros
 mod macros;
 
+/// Parallel Execution: Executes the transactions of a block optimistically in
+/// parallel, producing exactly the same result as serial execution.
+pub mod parallel;
+
 /// Observability Interface: Defines a trait for extending functionality.
 /// Extensions can implement this trait to observe detailed aspects of the
 /// execution process.
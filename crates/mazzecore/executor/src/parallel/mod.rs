@@ -0,0 +1,213 @@
+//! Optimistic parallel execution of the transactions in a block, in the
+//! style of Block-STM.
+//!
+//! Transactions are grouped by sender. Every group is executed speculatively
+//! on a state view seeded from the state at the beginning of the block, with
+//! the earlier transactions of the same group applied. The accesses of each
+//! transaction are recorded. Transactions are then committed in the block
+//! order: a speculative result is only used if everything it read has not
+//! been changed since it was observed by the view, otherwise the transaction
+//! is executed again on the main state. Hence the result is identical to
+//! serial execution.
+//!
+//! The global statistic variables which a transaction only adds to or
+//! subtracts from, e.g. the burnt fees and the total issued tokens, are not
+//! considered read. Their changes are applied to the current values when the
+//! transaction is committed, so such transactions do not conflict.
+
+#[cfg(test)]
+mod tests;
+
+use crate::state::{
+    GlobalStatSnapshot, State, StateAccessSet, StateSeed, StateWriteSet,
+};
+use mazze_statedb::Result as DbResult;
+use mazze_types::AddressWithSpace;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// The outcome of a transaction executed on a speculative view.
+struct SpeculativeResult<O> {
+    outcome: O,
+    access: StateAccessSet,
+    global_stat_before: GlobalStatSnapshot,
+    writes: StateWriteSet,
+}
+
+/// The execution statistics of a block.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParallelExecutionStats {
+    /// The transactions committed from their speculative results.
+    pub speculative_committed: usize,
+    /// The transactions executed again on the main state.
+    pub re_executed: usize,
+}
+
+/// Marks an account modified by something other than a speculatively
+/// executed transaction.
+const EXTERNAL_WRITER: usize = usize::MAX;
+
+/// Execute `transactions` on `state`, producing the same state and outcomes
+/// as executing them one by one with `execute` and then `commit`.
+///
+/// * `open_view` opens a state over the same ledger as `state` and seeded
+///   with `seed`. It is called once per worker.
+/// * `execute` runs a transaction, either on a view or on `state`.
+/// * `commit` post-processes the outcome of the transaction on `state` in the
+///   block order, before the next transaction is validated.
+pub fn execute_in_parallel<T, O, S, V, E, C>(
+    state: &mut State, transactions: &[T], sender_of: S, open_view: V,
+    execute: E, mut commit: C,
+) -> DbResult<ParallelExecutionStats>
+where
+    T: Sync,
+    O: Send,
+    S: Fn(&T) -> AddressWithSpace,
+    V: Fn(&StateSeed) -> DbResult<State> + Sync,
+    E: Fn(&mut State, usize, &T) -> DbResult<O> + Sync,
+    C: FnMut(&mut State, usize, O) -> DbResult<()>,
+{
+    let seed = state.make_seed();
+
+    // Group the transactions by sender, following the order of the first
+    // appearance to keep everything deterministic.
+    let mut groups: Vec<Vec<usize>> = vec![];
+    let mut group_of_tx = Vec::with_capacity(transactions.len());
+    {
+        let mut group_by_sender = HashMap::new();
+        for (idx, tx) in transactions.iter().enumerate() {
+            let group = *group_by_sender
+                .entry(sender_of(tx))
+                .or_insert_with(|| {
+                    groups.push(vec![]);
+                    groups.len() - 1
+                });
+            groups[group].push(idx);
+            group_of_tx.push(group);
+        }
+    }
+
+    let speculative_groups: Vec<Vec<SpeculativeResult<O>>> = groups
+        .par_iter()
+        .map_init(
+            || open_view(&seed).ok(),
+            |view, group| match view {
+                Some(view) => {
+                    execute_group(view, group, transactions, &execute)
+                }
+                // Leave the whole group to the main state.
+                None => vec![],
+            },
+        )
+        .collect();
+
+    let mut speculative: Vec<Option<SpeculativeResult<O>>> =
+        (0..transactions.len()).map(|_| None).collect();
+    for (group, results) in groups.iter().zip(speculative_groups) {
+        for (idx, result) in group.iter().zip(results) {
+            speculative[*idx] = Some(result);
+        }
+    }
+
+    let mut stats = ParallelExecutionStats::default();
+    // The last writer of each account modified since the seed.
+    let mut last_writer: HashMap<AddressWithSpace, usize> = HashMap::new();
+    // The last writer of each account in each group, as observed by the view.
+    let mut group_last_writer: Vec<HashMap<AddressWithSpace, usize>> =
+        vec![HashMap::new(); groups.len()];
+    let mut group_broken = vec![false; groups.len()];
+
+    for (idx, tx) in transactions.iter().enumerate() {
+        let group = group_of_tx[idx];
+
+        let valid_result = speculative[idx].take().filter(|result| {
+            !group_broken[group]
+                && result.access.read_accounts.iter().all(|address| {
+                    last_writer.get(address)
+                        == group_last_writer[group].get(address)
+                })
+                && state.global_stat_consistent_with(
+                    &result.access,
+                    &result.global_stat_before,
+                )
+        });
+
+        let outcome = match valid_result {
+            Some(SpeculativeResult {
+                outcome,
+                access,
+                writes,
+                ..
+            }) => {
+                state.apply_writes(writes);
+                for address in access.written_accounts {
+                    last_writer.insert(address, idx);
+                    group_last_writer[group].insert(address, idx);
+                }
+                stats.speculative_committed += 1;
+                outcome
+            }
+            None => {
+                // The later transactions of the group have observed a
+                // different version of this transaction.
+                group_broken[group] = true;
+
+                state.start_access_tracking();
+                let outcome = execute(state, idx, tx);
+                let access = state.finish_access_tracking();
+                for address in access.written_accounts {
+                    last_writer.insert(address, EXTERNAL_WRITER);
+                }
+                stats.re_executed += 1;
+                outcome?
+            }
+        };
+
+        state.start_access_tracking();
+        let result = commit(state, idx, outcome);
+        let access = state.finish_access_tracking();
+        for address in access.written_accounts {
+            last_writer.insert(address, EXTERNAL_WRITER);
+        }
+        result?;
+    }
+
+    Ok(stats)
+}
+
+/// Execute the transactions of a group one after another on `view`, stopping
+/// at the first database error. The view is restored afterwards.
+fn execute_group<T, O, E>(
+    view: &mut State, group: &[usize], transactions: &[T], execute: &E,
+) -> Vec<SpeculativeResult<O>>
+where E: Fn(&mut State, usize, &T) -> DbResult<O> {
+    let mut results = Vec::with_capacity(group.len());
+
+    view.checkpoint();
+    for &idx in group {
+        let global_stat_before = view.global_stat_snapshot();
+
+        view.start_access_tracking();
+        let outcome = execute(view, idx, &transactions[idx]);
+        let access = view.finish_access_tracking();
+
+        let outcome = match outcome {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                debug!("speculative execution failed: tx_idx={} {:?}", idx, e);
+                break;
+            }
+        };
+
+        let writes = view.collect_writes(&access, &global_stat_before);
+        results.push(SpeculativeResult {
+            outcome,
+            access,
+            global_stat_before,
+            writes,
+        });
+    }
+    view.revert_to_checkpoint();
+
+    results
+}
@@ -0,0 +1,550 @@
+use super::{execute_in_parallel, ParallelExecutionStats};
+use crate::{
+    executive::{ExecutiveContext, TransactOptions},
+    machine::{new_machine_with_builtin, Machine, VmFactory},
+    state::{get_state_for_genesis_write, CleanupMode, State},
+};
+use mazze_statedb::StateDb;
+use mazze_storage::{
+    state_manager::StateManagerTrait, tests::new_state_manager_for_unit_test,
+    StateIndex, StorageManager,
+};
+use mazze_types::{
+    address_util::AddressUtil, Address, AddressSpaceUtil, H256, U256,
+};
+use mazze_vm_types::{Env, Spec};
+use mazzekey::{Generator, KeyPair, Random};
+use primitives::{
+    transaction::{
+        native_transaction::NativeTransaction, Action, Eip155Transaction,
+    },
+    EpochId, Receipt, SignedTransaction, Transaction,
+};
+use rlp::Rlp;
+use rustc_hex::{FromHex, ToHex};
+use std::sync::Arc;
+
+/// A recorded epoch: the blocks, each encoded as the RLP list of its
+/// transactions in the local storage format.
+type RecordedEpoch = Vec<Vec<u8>>;
+
+/// The blocks of an epoch, and the transactions in each block.
+type Epoch = Vec<Vec<Arc<SignedTransaction>>>;
+
+/// `counter()`: increments slot 0.
+const COUNTER_CODE: &str = "600054600101600055";
+
+/// `per_caller()`: increments the slot keyed by the caller.
+const PER_CALLER_CODE: &str = "3354600101335500";
+
+/// `branch()`: adds 3 to slot 0 if it is even. Otherwise adds 1 to it and
+/// records the caller in slot 1.
+const BRANCH_CODE: &str =
+    "60005480600116601157600301600055005b60010160005533600155";
+
+struct TestContext {
+    machine: Machine,
+    env: Env,
+    spec: Spec,
+}
+
+impl TestContext {
+    fn new() -> Self {
+        let machine = new_machine_with_builtin(
+            Default::default(),
+            VmFactory::new(1024 * 32),
+        );
+        let mut env = Env::default();
+        env.gas_limit = U256::MAX;
+        let spec = machine.spec_for_test(env.number);
+        TestContext { machine, env, spec }
+    }
+
+    fn execute(&self, state: &mut State, tx: &SignedTransaction) -> Receipt {
+        ExecutiveContext::new(state, &self.env, &self.machine, &self.spec)
+            .transact(tx, TransactOptions::default())
+            .expect("no db error")
+            .make_receipt(&mut U256::zero(), &self.spec)
+    }
+}
+
+/// The contracts installed in the initial state of the contract tests.
+struct Contracts {
+    counter: Address,
+    per_caller: Address,
+    branch: Address,
+    /// Forwards the value it receives to the shared recipient.
+    forwarder: Address,
+    /// Stores the balance of the shared recipient in slot 0.
+    balance_reader: Address,
+}
+
+impl Contracts {
+    fn new() -> Self {
+        Contracts {
+            counter: contract_address(),
+            per_caller: contract_address(),
+            branch: contract_address(),
+            forwarder: contract_address(),
+            balance_reader: contract_address(),
+        }
+    }
+
+    fn install(&self, state: &mut State, shared_recipient: &Address) {
+        let recipient: String = shared_recipient.as_bytes().to_hex();
+        let forwarder_code =
+            format!("60006000600060003473{}5af15000", recipient);
+        let balance_reader_code = format!("73{}3160005500", recipient);
+        for (address, code) in [
+            (&self.counter, COUNTER_CODE.to_string()),
+            (&self.per_caller, PER_CALLER_CODE.to_string()),
+            (&self.branch, BRANCH_CODE.to_string()),
+            (&self.forwarder, forwarder_code),
+            (&self.balance_reader, balance_reader_code),
+        ] {
+            state
+                .set_code(
+                    &address.with_native_space(),
+                    code.from_hex().unwrap(),
+                    Address::zero(),
+                )
+                .unwrap();
+        }
+    }
+
+    fn all(&self) -> [Address; 5] {
+        [
+            self.counter,
+            self.per_caller,
+            self.branch,
+            self.forwarder,
+            self.balance_reader,
+        ]
+    }
+}
+
+fn contract_address() -> Address {
+    let mut address = Address::random();
+    address.set_contract_type_bits();
+    address
+}
+
+fn native_tx(
+    sender: &KeyPair, nonce: u64, action: Action, value: u64, data: Vec<u8>,
+) -> Arc<SignedTransaction> {
+    Arc::new(
+        Transaction::from(NativeTransaction {
+            nonce: nonce.into(),
+            gas_price: U256::one(),
+            gas: U256::from(300_000),
+            value: U256::from(value),
+            action,
+            storage_limit: 10_000,
+            epoch_height: 0,
+            chain_id: 1,
+            data,
+        })
+        .sign(sender.secret()),
+    )
+}
+
+fn transfer(
+    sender: &KeyPair, nonce: u64, to: Address, value: u64,
+) -> Arc<SignedTransaction> {
+    native_tx(sender, nonce, Action::Call(to), value, vec![])
+}
+
+fn eth_transfer(
+    sender: &KeyPair, nonce: u64, to: Address, value: u64,
+) -> Arc<SignedTransaction> {
+    Arc::new(
+        Transaction::from(Eip155Transaction {
+            nonce: nonce.into(),
+            gas_price: U256::one(),
+            gas: U256::from(300_000),
+            action: Action::Call(to),
+            value: U256::from(value),
+            chain_id: Some(1),
+            data: vec![],
+        })
+        .sign(sender.secret()),
+    )
+}
+
+/// Encode the blocks of `epoch` as they would be recorded.
+fn record(epoch: &Epoch) -> RecordedEpoch {
+    epoch
+        .iter()
+        .map(|block| {
+            let txs: Vec<SignedTransaction> =
+                block.iter().map(|tx| (**tx).clone()).collect();
+            rlp::encode_list::<SignedTransaction, _>(&txs).to_vec()
+        })
+        .collect()
+}
+
+fn decode_recorded(epoch: &RecordedEpoch) -> Epoch {
+    epoch
+        .iter()
+        .map(|block| {
+            Rlp::new(block)
+                .as_list::<SignedTransaction>()
+                .expect("valid recorded block")
+                .into_iter()
+                .map(Arc::new)
+                .collect()
+        })
+        .collect()
+}
+
+/// Build an epoch mixing independent transfers, transfers to a shared
+/// recipient, chained transfers between senders and transactions with a
+/// wrong nonce. `nonces` tracks the next nonce of each sender.
+fn transfer_epoch(senders: &[KeyPair], nonces: &mut [u64]) -> Epoch {
+    let shared_recipient = senders[0].address();
+    let mut next_tx = |i: usize, to: Address, value: u64| {
+        let tx = transfer(&senders[i], nonces[i], to, value);
+        nonces[i] += 1;
+        tx
+    };
+
+    let mut epoch = vec![];
+    for round in 0..4usize {
+        let mut block = vec![];
+        for i in 1..senders.len() {
+            let to = match (i + round) % 4 {
+                0 => shared_recipient,
+                1 => senders[(i + 1) % senders.len()].address(),
+                _ => Random.generate().unwrap().address(),
+            };
+            block.push(next_tx(i, to, 1_000 + i as u64));
+            if i % 3 == 0 {
+                // A second transaction of the same sender in the block.
+                block.push(next_tx(i, shared_recipient, 7));
+            }
+        }
+        // A transaction with a stale nonce.
+        block.push(transfer(&senders[1], 0, shared_recipient, 1));
+        epoch.push(block);
+    }
+    epoch
+}
+
+/// Build an epoch of eSpace transfers to fresh recipients. They are
+/// independent, except that each of them subtracts its fee from the total
+/// eSpace tokens.
+fn eth_transfer_epoch(senders: &[KeyPair], nonces: &mut [u64]) -> Epoch {
+    let mut epoch = vec![];
+    for _ in 0..2 {
+        let mut block = vec![];
+        for i in 1..senders.len() {
+            let to = Random.generate().unwrap().evm_address();
+            block.push(eth_transfer(&senders[i], nonces[i], to, 1_000));
+            nonces[i] += 1;
+        }
+        epoch.push(block);
+    }
+    epoch
+}
+
+/// Build an epoch of contract calls: every sender writes the same slot,
+/// writes its own slot of a shared contract, takes a branch depending on a
+/// slot written by the others, sends value through a contract to the shared
+/// recipient, reads the balance of the shared recipient or deploys a
+/// contract.
+fn contract_epoch(
+    senders: &[KeyPair], nonces: &mut [u64], contracts: &Contracts,
+) -> Epoch {
+    let shared_recipient = senders[0].address();
+    // The init code returns `COUNTER_CODE`.
+    let deploy_code: Vec<u8> =
+        format!("6009600c60003960096000f3{}", COUNTER_CODE)
+            .from_hex()
+            .unwrap();
+
+    let mut epoch = vec![];
+    for round in 0..3usize {
+        let mut block = vec![];
+        for i in 1..senders.len() {
+            let (action, value, data) = match (i + round) % 7 {
+                0 => (Action::Call(contracts.counter), 0, vec![]),
+                1 => (Action::Call(contracts.per_caller), 0, vec![]),
+                2 => (Action::Call(contracts.branch), 0, vec![]),
+                3 => {
+                    (Action::Call(contracts.forwarder), 500 + i as u64, vec![])
+                }
+                4 => (Action::Call(contracts.balance_reader), 0, vec![]),
+                5 => (Action::Create, 0, deploy_code.clone()),
+                _ => (Action::Call(shared_recipient), 3, vec![]),
+            };
+            block.push(native_tx(&senders[i], nonces[i], action, value, data));
+            nonces[i] += 1;
+            if i % 4 == 0 {
+                // A second call of the same sender, depending on the first.
+                block.push(native_tx(
+                    &senders[i],
+                    nonces[i],
+                    Action::Call(contracts.counter),
+                    0,
+                    vec![],
+                ));
+                nonces[i] += 1;
+            }
+        }
+        epoch.push(block);
+    }
+    epoch
+}
+
+fn new_funded_state(
+    storage_manager: &Arc<StorageManager>, senders: &[KeyPair],
+) -> State {
+    let balance = U256::from(10).pow(24.into());
+    let mut state = get_state_for_genesis_write(storage_manager);
+    for sender in senders {
+        for address in [
+            sender.address().with_native_space(),
+            sender.evm_address().with_evm_space(),
+        ] {
+            state
+                .add_balance(&address, &balance, CleanupMode::NoEmpty)
+                .unwrap();
+            state.add_total_issued(balance);
+        }
+        state.add_total_evm_tokens(balance);
+    }
+    state
+}
+
+fn open_state_db(
+    storage_manager: &Arc<StorageManager>, parent: &EpochId,
+) -> StateDb {
+    StateDb::new(
+        storage_manager
+            .get_state_for_next_epoch(
+                StateIndex::new_for_test_only_delta_mpt(parent),
+                false,
+            )
+            .unwrap()
+            .unwrap(),
+    )
+}
+
+fn run_serial(
+    context: &TestContext, state: &mut State, epoch: &Epoch,
+) -> Vec<Vec<Receipt>> {
+    epoch
+        .iter()
+        .map(|block| {
+            block
+                .iter()
+                .map(|tx| {
+                    let receipt = context.execute(state, tx);
+                    state.burn_by_cip1559(
+                        receipt.burnt_gas_fee.unwrap_or_default(),
+                    );
+                    receipt
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn run_parallel(
+    context: &TestContext, storage_manager: &Arc<StorageManager>,
+    parent: &EpochId, state: &mut State, epoch: &Epoch,
+) -> (Vec<Vec<Receipt>>, ParallelExecutionStats) {
+    let mut total_stats = ParallelExecutionStats::default();
+    let receipts = epoch
+        .iter()
+        .map(|block| {
+            let mut receipts = vec![];
+            let stats = execute_in_parallel(
+                state,
+                block,
+                |tx| tx.sender(),
+                |seed| {
+                    State::from_seed(
+                        open_state_db(storage_manager, parent),
+                        seed,
+                    )
+                },
+                |state, _, tx| Ok(context.execute(state, tx)),
+                |state, _, receipt: Receipt| {
+                    state.burn_by_cip1559(
+                        receipt.burnt_gas_fee.unwrap_or_default(),
+                    );
+                    receipts.push(receipt);
+                    Ok(())
+                },
+            )
+            .expect("no db error");
+            total_stats.speculative_committed += stats.speculative_committed;
+            total_stats.re_executed += stats.re_executed;
+            receipts
+        })
+        .collect();
+    (receipts, total_stats)
+}
+
+/// Replay the recorded epochs one after another, serially and in parallel,
+/// committing the state after each epoch. Every receipt and state root must
+/// be identical.
+fn replay_recorded_epochs(
+    senders: &[KeyPair], contracts: Option<&Contracts>,
+    recorded: &[RecordedEpoch],
+) -> ParallelExecutionStats {
+    let context = TestContext::new();
+    let shared_recipient = senders[0].address();
+
+    let serial_storage_manager = new_state_manager_for_unit_test();
+    let mut serial_state = new_funded_state(&serial_storage_manager, senders);
+    let parallel_storage_manager = new_state_manager_for_unit_test();
+    let mut parallel_state =
+        new_funded_state(&parallel_storage_manager, senders);
+    if let Some(contracts) = contracts {
+        contracts.install(&mut serial_state, &shared_recipient);
+        contracts.install(&mut parallel_state, &shared_recipient);
+    }
+
+    let mut total_stats = ParallelExecutionStats::default();
+    let mut parent = EpochId::default();
+    for (epoch_number, recorded_epoch) in recorded.iter().enumerate() {
+        let epoch = decode_recorded(recorded_epoch);
+
+        let serial_receipts = run_serial(&context, &mut serial_state, &epoch);
+        let (parallel_receipts, stats) = run_parallel(
+            &context,
+            &parallel_storage_manager,
+            &parent,
+            &mut parallel_state,
+            &epoch,
+        );
+        assert_eq!(
+            serial_receipts, parallel_receipts,
+            "receipts differ in epoch {}",
+            epoch_number
+        );
+        assert_eq!(
+            stats.speculative_committed + stats.re_executed,
+            epoch.iter().map(Vec::len).sum::<usize>()
+        );
+        total_stats.speculative_committed += stats.speculative_committed;
+        total_stats.re_executed += stats.re_executed;
+
+        let addresses = senders
+            .iter()
+            .map(|sender| sender.address())
+            .chain(contracts.iter().flat_map(|c| c.all()));
+        for address in addresses {
+            let address = address.with_native_space();
+            assert_eq!(
+                serial_state.balance(&address).unwrap(),
+                parallel_state.balance(&address).unwrap()
+            );
+            assert_eq!(
+                serial_state.nonce(&address).unwrap(),
+                parallel_state.nonce(&address).unwrap()
+            );
+        }
+
+        let epoch_id = EpochId::from_low_u64_be(epoch_number as u64 + 1);
+        let serial_root =
+            serial_state.commit(epoch_id, None).unwrap().state_root;
+        let parallel_root =
+            parallel_state.commit(epoch_id, None).unwrap().state_root;
+        assert_eq!(
+            serial_root, parallel_root,
+            "state roots differ in epoch {}",
+            epoch_number
+        );
+
+        serial_state =
+            State::new(open_state_db(&serial_storage_manager, &epoch_id))
+                .unwrap();
+        parallel_state =
+            State::new(open_state_db(&parallel_storage_manager, &epoch_id))
+                .unwrap();
+        parent = epoch_id;
+    }
+
+    total_stats
+}
+
+#[test]
+fn test_parallel_execution_matches_serial_execution() {
+    let senders: Vec<_> = (0..13).map(|_| Random.generate().unwrap()).collect();
+    let mut nonces = vec![0u64; senders.len()];
+    let recorded = vec![record(&transfer_epoch(&senders, &mut nonces))];
+
+    let stats = replay_recorded_epochs(&senders, None, &recorded);
+    // Independent transfers must not be executed twice.
+    assert!(stats.speculative_committed > 0);
+    // Transfers to the shared recipient always conflict.
+    assert!(stats.re_executed > 0);
+}
+
+#[test]
+fn test_parallel_execution_replays_recorded_epochs() {
+    let senders: Vec<_> = (0..9).map(|_| Random.generate().unwrap()).collect();
+    let mut nonces = vec![0u64; senders.len()];
+    // Later epochs start from the state committed by the earlier ones, with
+    // nonces continuing across epochs.
+    let recorded: Vec<_> = (0..5)
+        .map(|_| record(&transfer_epoch(&senders, &mut nonces)))
+        .collect();
+
+    let stats = replay_recorded_epochs(&senders, None, &recorded);
+    assert!(stats.speculative_committed > 0);
+    assert!(stats.re_executed > 0);
+}
+
+#[test]
+fn test_parallel_execution_contract_conflicts() {
+    let senders: Vec<_> = (0..15).map(|_| Random.generate().unwrap()).collect();
+    let contracts = Contracts::new();
+    let mut nonces = vec![0u64; senders.len()];
+    let recorded: Vec<_> = (0..3)
+        .map(|_| record(&contract_epoch(&senders, &mut nonces, &contracts)))
+        .collect();
+
+    let stats = replay_recorded_epochs(&senders, Some(&contracts), &recorded);
+    // Calls to the shared contracts conflict with each other.
+    assert!(stats.re_executed > 0);
+}
+
+#[test]
+fn test_parallel_execution_global_stat_updates() {
+    let senders: Vec<_> = (0..9).map(|_| Random.generate().unwrap()).collect();
+    let mut nonces = vec![0u64; senders.len()];
+    let recorded: Vec<_> = (0..2)
+        .map(|_| record(&eth_transfer_epoch(&senders, &mut nonces)))
+        .collect();
+
+    let stats = replay_recorded_epochs(&senders, None, &recorded);
+    // Subtracting the fees from the total eSpace tokens does not conflict.
+    assert_eq!(stats.re_executed, 0);
+    assert_eq!(
+        stats.speculative_committed,
+        2 * recorded.len() * (senders.len() - 1)
+    );
+}
+
+#[test]
+fn test_recorded_epoch_round_trip() {
+    let senders: Vec<_> = (0..4).map(|_| Random.generate().unwrap()).collect();
+    let mut nonces = vec![0u64; senders.len()];
+    let epoch = transfer_epoch(&senders, &mut nonces);
+
+    let decoded = decode_recorded(&record(&epoch));
+    assert_eq!(decoded.len(), epoch.len());
+    for (decoded_block, block) in decoded.iter().zip(&epoch) {
+        let hashes = |txs: &Vec<Arc<SignedTransaction>>| -> Vec<H256> {
+            txs.iter().map(|tx| tx.hash()).collect()
+        };
+        assert_eq!(hashes(decoded_block), hashes(block));
+        for (decoded_tx, tx) in decoded_block.iter().zip(block) {
+            assert_eq!(decoded_tx.sender(), tx.sender());
+        }
+    }
+}
@@ -113,6 +113,17 @@ pub struct TransitionsBlockNumber {
     pub cip144: BlockNumber,
     /// CIP-145: Fix Receipts upon `NotEnoughBalance` Error
     pub cip145: BlockNumber,
+    /// EIP-7702: Set EOA Account Code
+    pub eip7702: BlockNumber,
+    /// RIP-7212: Precompile for secp256r1 Curve Support
+    pub rip7212: BlockNumber,
+    /// EIP-2537: Precompile for BLS12-381 Curve Operations
+    pub eip2537: BlockNumber,
+    /// EIP-7692: EVM Object Format (EOF) v1, activated in each space
+    /// separately
+    pub eip7692: SpaceMap<BlockNumber>,
+    /// Gas sponsorship for eSpace contracts
+    pub eth_sponsor: BlockNumber,
 }
 
 #[derive(Default, Debug, Clone)]
@@ -191,6 +202,9 @@ impl CommonParams {
         spec.cip137 = number >= self.transition_numbers.cip137;
         spec.cip144 = number >= self.transition_numbers.cip144;
         spec.cip145 = number >= self.transition_numbers.cip145;
+        spec.eip7702 = number >= self.transition_numbers.eip7702;
+        spec.eof = self.transition_numbers.eip7692.map_all(|n| number >= n);
+        spec.eth_sponsor = number >= self.transition_numbers.eth_sponsor;
         spec.cip1559 = height >= self.transition_heights.cip1559;
         spec.cancun_opcodes = number >= self.transition_numbers.cancun_opcodes;
         if spec.cancun_opcodes {
@@ -0,0 +1,105 @@
+use mazze_statedb::global_params::TOTAL_GLOBAL_PARAMS;
+use mazze_types::{AddressWithSpace, U256};
+use parking_lot::Mutex;
+use std::collections::HashSet;
+
+// The global statistic variables accessed are recorded in a bitmap.
+const _: () = assert!(TOTAL_GLOBAL_PARAMS <= 64);
+
+/// The accounts and global statistic variables accessed by an execution.
+#[derive(Default, Debug, Clone)]
+pub struct StateAccessSet {
+    /// Accounts loaded or modified. An account is always loaded before being
+    /// modified, so this is a superset of `written_accounts`.
+    pub read_accounts: HashSet<AddressWithSpace>,
+    /// Accounts modified, including the modifications reverted later.
+    pub written_accounts: HashSet<AddressWithSpace>,
+    /// Bitmap of the global statistic variables read, indexed by
+    /// `GlobalParamKey::ID`.
+    pub(super) read_global_stat: u64,
+    /// Bitmap of the global statistic variables modified.
+    pub(super) written_global_stat: u64,
+    /// Bitmap of the global statistic variables only added to or subtracted
+    /// from without being read. Applying the same delta to another version
+    /// of such a variable gives the same result as executing on it.
+    pub(super) updated_global_stat: u64,
+    /// The lowest version of each updated variable, before the execution, on
+    /// which none of the subtractions underflows.
+    pub(super) global_stat_lower_bound: [U256; TOTAL_GLOBAL_PARAMS],
+}
+
+impl StateAccessSet {
+    pub(super) fn global_stat_read(&self, id: usize) -> bool {
+        self.read_global_stat & (1 << id) != 0
+    }
+
+    pub(super) fn global_stat_written(&self, id: usize) -> bool {
+        self.written_global_stat & (1 << id) != 0
+    }
+
+    pub(super) fn global_stat_updated(&self, id: usize) -> bool {
+        self.updated_global_stat & (1 << id) != 0
+    }
+}
+
+/// Records the accesses to a `State` while tracking is enabled. It is shared
+/// by the state and its global statistic variables, so the records survive
+/// reverting checkpoints.
+#[derive(Debug)]
+pub(super) struct AccessRecorder {
+    access_set: Mutex<StateAccessSet>,
+    /// The global statistic variables when the recording starts.
+    global_stat_start: [U256; TOTAL_GLOBAL_PARAMS],
+}
+
+impl AccessRecorder {
+    pub fn new(global_stat_start: [U256; TOTAL_GLOBAL_PARAMS]) -> Self {
+        AccessRecorder {
+            access_set: Default::default(),
+            global_stat_start,
+        }
+    }
+
+    pub fn read_account(&self, address: &AddressWithSpace) {
+        self.access_set.lock().read_accounts.insert(*address);
+    }
+
+    pub fn write_account(&self, address: &AddressWithSpace) {
+        let mut access_set = self.access_set.lock();
+        access_set.read_accounts.insert(*address);
+        access_set.written_accounts.insert(*address);
+    }
+
+    pub fn read_global_stat(&self, id: usize) {
+        self.access_set.lock().read_global_stat |= 1 << id;
+    }
+
+    pub fn write_global_stat(&self, id: usize) {
+        let mut access_set = self.access_set.lock();
+        access_set.read_global_stat |= 1 << id;
+        access_set.written_global_stat |= 1 << id;
+    }
+
+    pub fn add_global_stat(&self, id: usize) {
+        self.access_set.lock().updated_global_stat |= 1 << id;
+    }
+
+    /// Record subtracting `by` from the variable `id` while its value is
+    /// `current`. On another version of the variable, the subtraction does
+    /// not underflow if the version is at least `by + start - current`.
+    pub fn sub_global_stat(&self, id: usize, current: &U256, by: &U256) {
+        let lower_bound = match by.checked_add(self.global_stat_start[id]) {
+            Some(bound) => bound.saturating_sub(*current),
+            None => U256::MAX,
+        };
+
+        let mut access_set = self.access_set.lock();
+        access_set.updated_global_stat |= 1 << id;
+        let bound = &mut access_set.global_stat_lower_bound[id];
+        *bound = (*bound).max(lower_bound);
+    }
+
+    pub fn take(&self) -> StateAccessSet {
+        std::mem::take(&mut *self.access_set.lock())
+    }
+}
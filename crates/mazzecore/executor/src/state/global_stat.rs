@@ -5,6 +5,9 @@ use mazze_statedb::{
     Result as DbResult, StateDbExt, StateDbGeneric as StateDb,
 };
 use mazze_types::U256;
+use std::sync::Arc;
+
+use super::access_recorder::AccessRecorder;
 
 /// Manages specially-treated global variables during execution.
 ///
@@ -23,8 +26,12 @@ use mazze_types::U256;
 // TODO: Incorporating these variables into existing cache/checkpoint logic
 // would make the code clean, but it would be difficult to achieve back forward
 // compatibility.
-#[derive(Copy, Clone, Debug)]
-pub(super) struct GlobalStat([U256; TOTAL_GLOBAL_PARAMS]);
+#[derive(Clone, Debug)]
+pub(super) struct GlobalStat {
+    values: [U256; TOTAL_GLOBAL_PARAMS],
+    /// Records the accessed variables if access tracking is enabled.
+    recorder: Option<Arc<AccessRecorder>>,
+}
 
 impl GlobalStat {
     /// Make new global statistical variables with their initialization value.
@@ -39,7 +46,7 @@ impl GlobalStat {
         for_all_global_param_keys! {
             init_value::<Key>(&mut ans);
         }
-        GlobalStat(ans)
+        GlobalStat::from_values(ans)
     }
 
     /// Get loaded global statistic variables from the database.
@@ -56,7 +63,7 @@ impl GlobalStat {
         for_all_global_param_keys! {
             load_value::<Key>(&mut ans, db)?;
         }
-        Ok(GlobalStat(ans))
+        Ok(GlobalStat::from_values(ans))
     }
 
     /// Assert the global statistic variables have never been inited in the
@@ -95,17 +102,72 @@ impl GlobalStat {
         }
         use global_params::*;
         for_all_global_param_keys! {
-            commit_param::<Key>(&self.0, db, debug_record.as_deref_mut())?;
+            commit_param::<Key>(&self.values, db, debug_record.as_deref_mut())?;
         }
         Ok(())
     }
 
     /// Get the owned value of a variable
-    pub fn get<T: GlobalParamKey>(&self) -> U256 { self.0[T::ID] }
+    pub fn get<T: GlobalParamKey>(&self) -> U256 { *self.refr::<T>() }
 
     /// Get the immutable reference of a variable
-    pub fn refr<T: GlobalParamKey>(&self) -> &U256 { &self.0[T::ID] }
+    pub fn refr<T: GlobalParamKey>(&self) -> &U256 {
+        if let Some(recorder) = &self.recorder {
+            recorder.read_global_stat(T::ID);
+        }
+        &self.values[T::ID]
+    }
 
     /// Get the mutable reference of a variable
-    pub fn val<T: GlobalParamKey>(&mut self) -> &mut U256 { &mut self.0[T::ID] }
+    pub fn val<T: GlobalParamKey>(&mut self) -> &mut U256 {
+        if let Some(recorder) = &self.recorder {
+            recorder.write_global_stat(T::ID);
+        }
+        &mut self.values[T::ID]
+    }
+
+    /// Add to a variable without reading it.
+    pub fn add<T: GlobalParamKey>(&mut self, v: U256) {
+        if let Some(recorder) = &self.recorder {
+            recorder.add_global_stat(T::ID);
+        }
+        self.values[T::ID] += v;
+    }
+
+    /// Subtract from a variable without reading it.
+    pub fn sub<T: GlobalParamKey>(&mut self, v: U256) {
+        if let Some(recorder) = &self.recorder {
+            recorder.sub_global_stat(T::ID, &self.values[T::ID], &v);
+        }
+        self.values[T::ID] -= v;
+    }
+
+    /// Subtract from a variable, saturating at zero. The variable is only
+    /// read if the subtraction saturates.
+    pub fn saturating_sub<T: GlobalParamKey>(&mut self, v: U256) {
+        if self.values[T::ID] < v {
+            *self.val::<T>() = U256::zero();
+        } else {
+            self.sub::<T>(v);
+        }
+    }
+
+    /// Get all the values without recording the access.
+    pub fn values(&self) -> &[U256; TOTAL_GLOBAL_PARAMS] { &self.values }
+
+    /// Set a value by its id without recording the access.
+    pub fn set_value(&mut self, id: usize, value: U256) {
+        self.values[id] = value;
+    }
+
+    pub fn set_recorder(&mut self, recorder: Option<Arc<AccessRecorder>>) {
+        self.recorder = recorder;
+    }
+
+    fn from_values(values: [U256; TOTAL_GLOBAL_PARAMS]) -> Self {
+        GlobalStat {
+            values,
+            recorder: None,
+        }
+    }
 }
@@ -3,6 +3,11 @@
 //! Ledger State: Acts as a caching and checkpoint layer built upon semantically
 //! meaningful database interfaces for the execution.
 
+/// Access Recorder: Records the accounts and global statistic variables
+/// accessed during execution, so that a transaction executed speculatively can
+/// be validated against the changes made by other transactions.
+mod access_recorder;
+
 /// Global Statistic Variables: Manages global variables with different
 /// checkpoint and caching mechanisms compared to other state variables, such
 /// as, `total_issued_tokens`.
@@ -18,10 +23,12 @@ mod state_object;
 
 #[cfg(test)]
 pub use state_object::get_state_for_genesis_write;
+pub use access_recorder::StateAccessSet;
 pub use state_object::{
     distribute_pos_interest, initialize_cip107, initialize_cip137,
     initialize_or_update_dao_voted_params, settle_collateral_for_all,
-    update_pos_status, State, StateCommitResult,
+    update_pos_status, AccountDiff, GlobalStatSnapshot, State,
+    StateCommitResult, StateSeed, StateWriteSet, StorageDiff,
     COMMISSION_PRIVILEGE_SPECIAL_KEY,
 };
 
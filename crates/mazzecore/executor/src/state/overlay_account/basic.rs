@@ -44,6 +44,11 @@ impl OverlayAccount {
         });
     }
 
+    pub fn clear_code(&mut self) {
+        self.code_hash = KECCAK_EMPTY;
+        self.code = None;
+    }
+
     pub(super) fn is_code_loaded(&self) -> bool {
         self.code.is_some() || self.code_hash == KECCAK_EMPTY
     }
@@ -171,6 +171,11 @@ impl OverlayAccount {
         self.transient_storage.get(key).cloned().unwrap_or_default()
     }
 
+    /// The keys of the storage entries written since last commit.
+    pub fn written_storage_keys(&self) -> impl Iterator<Item = &Vec<u8>> {
+        self.storage_write_cache.keys()
+    }
+
     fn get_and_cache_storage(
         &self, db: &StateDbGeneric, key: &[u8],
     ) -> DbResult<StorageValue> {
@@ -212,6 +217,18 @@ impl OverlayAccount {
         Ok(())
     }
 
+    /// Overwrite a storage value outside transaction without settling the
+    /// collateral. An occupied entry keeps its owner, and a new entry has no
+    /// owner.
+    pub fn overwrite_storage_value(
+        &mut self, db: &StateDbGeneric, key: Vec<u8>, value: U256,
+    ) -> DbResult<()> {
+        let mut entry = self.storage_entry_at(db, &key)?;
+        entry.value = value;
+        Arc::make_mut(&mut self.storage_write_cache).insert(key, entry);
+        Ok(())
+    }
+
     #[cfg(test)]
     pub fn storage_layout_change(&self) -> Option<&StorageLayout> {
         self.storage_layout_change.as_ref()
@@ -3,9 +3,13 @@ use crate::{state::CleanupMode, try_loaded};
 use mazze_bytes::Bytes;
 use mazze_statedb::Result as DbResult;
 use mazze_types::{
-    address_util::AddressUtil, Address, AddressWithSpace, Space, H256, U256,
+    address_util::AddressUtil, Address, AddressSpaceUtil, AddressWithSpace,
+    Space, H256, U256,
 };
 use keccak_hash::KECCAK_EMPTY;
+use primitives::transaction::{
+    delegation_designator, parse_delegation_designator,
+};
 #[cfg(test)]
 use primitives::StorageLayout;
 use std::sync::Arc;
@@ -141,6 +145,54 @@ impl State {
         Ok(())
     }
 
+    /// Replace the code of an account without a transaction, creating the
+    /// account if it does not exist. Empty code clears it.
+    pub fn set_code(
+        &mut self, address: &AddressWithSpace, code: Bytes, owner: Address,
+    ) -> DbResult<()> {
+        let mut account = self.write_account_or_new_lock(address)?;
+        if code.is_empty() {
+            account.clear_code();
+        } else {
+            account.init_code(code, owner);
+        }
+        Ok(())
+    }
+
+    /// The code to run when `address` is called, together with its hash.
+    /// After EIP-7702, an eth space account holding a delegation designator
+    /// runs the code of the delegated account. Delegations are not followed
+    /// recursively.
+    pub fn code_for_call(
+        &self, address: &AddressWithSpace, eip7702: bool,
+    ) -> DbResult<(Option<Arc<Vec<u8>>>, H256)> {
+        let code = self.code(address)?;
+        if eip7702 && address.space == Space::Ethereum {
+            let delegate = code
+                .as_ref()
+                .and_then(|code| parse_delegation_designator(code));
+            if let Some(delegate) = delegate {
+                let delegate = delegate.with_evm_space();
+                return Ok((self.code(&delegate)?, self.code_hash(&delegate)?));
+            }
+        }
+        Ok((code, self.code_hash(address)?))
+    }
+
+    /// Delegate the code of an eth space account to `delegate` (EIP-7702).
+    /// Delegating to the zero address clears the code.
+    pub fn set_delegation(
+        &mut self, address: &AddressWithSpace, delegate: &Address,
+    ) -> DbResult<()> {
+        let mut account = self.write_account_or_new_lock(address)?;
+        if delegate.is_zero() {
+            account.clear_code();
+        } else {
+            account.init_code(delegation_designator(delegate), Address::zero());
+        }
+        Ok(())
+    }
+
     pub fn admin(&self, address: &Address) -> DbResult<Address> {
         let acc = try_loaded!(self.read_native_account_lock(address));
         Ok(*acc.admin())
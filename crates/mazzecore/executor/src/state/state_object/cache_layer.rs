@@ -42,6 +42,8 @@ impl State {
     pub(super) fn read_account_ext_lock(
         &self, address: &AddressWithSpace, require: RequireFields,
     ) -> DbResult<Option<AccountReadGuard>> {
+        self.record_account_read(address);
+
         let mut cache = self.cache.write();
         let account_entry =
             Self::fetch_account_mut(&mut cache, &self.db, address, require)?;
@@ -61,6 +63,8 @@ impl State {
     pub(super) fn prefetch(
         &self, address: &AddressWithSpace, require: RequireFields,
     ) -> DbResult<()> {
+        self.record_account_read(address);
+
         if let Some(account_entry) = self.cache.read().get(address) {
             if let Some(account) = account_entry.account() {
                 if !account.should_load_ext_fields(require) {
@@ -154,6 +158,8 @@ impl State {
         &self, address: &AddressWithSpace, require: RequireFields, default: F,
     ) -> DbResult<AccountWriteGuard>
     where F: Fn(&AddressWithSpace) -> DbResult<OverlayAccount> {
+        self.record_account_write(address);
+
         let mut cache = self.cache.write();
         let account_entry =
             Self::fetch_account_mut(&mut cache, &self.db, address, require)?;
@@ -178,6 +184,18 @@ impl State {
 }
 
 impl State {
+    fn record_account_read(&self, address: &AddressWithSpace) {
+        if let Some(recorder) = &self.access_recorder {
+            recorder.read_account(address);
+        }
+    }
+
+    pub(super) fn record_account_write(&self, address: &AddressWithSpace) {
+        if let Some(recorder) = &self.access_recorder {
+            recorder.write_account(address);
+        }
+    }
+
     /// Retrieves data using a read-through caching strategy and automatically
     /// loads extension fields as required.
     fn fetch_account_mut<'a>(
@@ -61,7 +61,7 @@ impl State {
         let checkpoints = self.checkpoints.get_mut();
         let index = checkpoints.len();
         checkpoints.push(CheckpointLayer {
-            global_stat: self.global_stat,
+            global_stat: self.global_stat.clone(),
             entries: HashMap::new(),
         });
         index
@@ -133,6 +133,8 @@ impl State {
     /// to the checkpoint in needed.
     pub(super) fn insert_to_cache(&mut self, account: OverlayAccount) {
         let address = *account.address();
+        self.record_account_write(&address);
+
         let old_account_entry = self
             .cache
             .get_mut()
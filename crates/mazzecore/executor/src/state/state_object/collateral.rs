@@ -46,8 +46,10 @@ impl State {
         let storage_points_used = self
             .write_native_account_lock(&address)?
             .add_collateral_for_storage(by);
-        *self.global_stat.val::<TotalStorage>() += *by - storage_points_used;
-        *self.global_stat.val::<UsedStoragePoints>() += storage_points_used;
+        self.global_stat
+            .add::<TotalStorage>(*by - storage_points_used);
+        self.global_stat
+            .add::<UsedStoragePoints>(storage_points_used);
         Ok(storage_points_used)
     }
 
@@ -66,8 +68,10 @@ impl State {
             U256::zero()
         };
 
-        *self.global_stat.val::<TotalStorage>() -= *by - storage_points_refund;
-        *self.global_stat.val::<UsedStoragePoints>() -= storage_points_refund;
+        self.global_stat
+            .sub::<TotalStorage>(*by - storage_points_refund);
+        self.global_stat
+            .sub::<UsedStoragePoints>(storage_points_refund);
         self.sub_total_issued(burnt);
 
         Ok(storage_points_refund)
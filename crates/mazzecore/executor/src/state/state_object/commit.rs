@@ -5,10 +5,13 @@ use mazze_internal_common::{
 use mazze_statedb::{access_mode, Result as DbResult};
 use mazze_types::AddressWithSpace;
 use primitives::{Account, EpochId, StorageKey};
+use std::sync::Arc;
 
 pub struct StateCommitResult {
     pub state_root: StateRootWithAuxInfo,
     pub accounts_for_txpool: Vec<Account>,
+    /// The original values of the modified keys in the storage format.
+    pub change_set: Vec<(Vec<u8>, Option<Arc<[u8]>>)>,
 }
 
 impl State {
@@ -25,9 +28,17 @@ impl State {
         Ok(StateCommitResult {
             state_root,
             accounts_for_txpool,
+            change_set: self.db.take_change_set(),
         })
     }
 
+    /// Compute the state root with all the changes applied, without
+    /// committing them to the storage.
+    pub fn compute_state_root(mut self) -> DbResult<StateRootWithAuxInfo> {
+        self.apply_changes_to_statedb(None)?;
+        self.db.compute_state_root(None)
+    }
+
     /// Commit to the statedb and compute state root. Only called in the genesis
     pub fn compute_state_root_for_genesis(
         &mut self, mut debug_record: Option<&mut ComputeEpochDebugRecord>,
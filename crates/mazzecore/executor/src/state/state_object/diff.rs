@@ -0,0 +1,82 @@
+//! State Diff: Compares the accounts and the storage entries of two states,
+//! which is used to locate the divergence when an epoch executed again does
+//! not reach the same result.
+
+use super::{cache_layer::AccountReadGuard, State};
+use mazze_statedb::Result as DbResult;
+use mazze_types::{AddressWithSpace, U256};
+use primitives::Account;
+
+/// The difference of an account between two states.
+#[derive(Clone, Debug)]
+pub struct AccountDiff {
+    pub address: AddressWithSpace,
+    /// The account in the base state, `None` if it does not exist.
+    pub before: Option<Account>,
+    /// The account in the compared state, `None` if it does not exist.
+    pub after: Option<Account>,
+    /// The storage entries with different values.
+    pub storage: Vec<StorageDiff>,
+}
+
+/// The difference of a storage entry between two states.
+#[derive(Clone, Debug)]
+pub struct StorageDiff {
+    pub key: Vec<u8>,
+    pub before: U256,
+    pub after: U256,
+}
+
+impl State {
+    /// Compare the accounts at `addresses` in this state with those in `base`.
+    /// Only the storage entries written in this state since last commit are
+    /// compared. The accounts without difference are omitted.
+    pub fn diff_accounts<'a>(
+        &self, base: &State,
+        addresses: impl IntoIterator<Item = &'a AddressWithSpace>,
+    ) -> DbResult<Vec<AccountDiff>> {
+        let mut diffs = vec![];
+        for address in addresses {
+            let after = existing_account(self.read_account_lock(address)?);
+            let before = existing_account(base.read_account_lock(address)?);
+
+            let mut storage = vec![];
+            if let Some(account) = &after {
+                for key in account.written_storage_keys() {
+                    let after_value = account.storage_at(&self.db, key)?;
+                    let before_value = match &before {
+                        Some(account) => account.storage_at(&base.db, key)?,
+                        None => U256::zero(),
+                    };
+                    if before_value != after_value {
+                        storage.push(StorageDiff {
+                            key: key.clone(),
+                            before: before_value,
+                            after: after_value,
+                        });
+                    }
+                }
+            }
+            storage.sort_by(|a, b| a.key.cmp(&b.key));
+
+            let before = before.map(|account| account.as_account());
+            let after = after.map(|account| account.as_account());
+            if before != after || !storage.is_empty() {
+                diffs.push(AccountDiff {
+                    address: *address,
+                    before,
+                    after,
+                    storage,
+                });
+            }
+        }
+        Ok(diffs)
+    }
+}
+
+/// A killed account is regarded as non-existent.
+fn existing_account(
+    account: Option<AccountReadGuard>,
+) -> Option<AccountReadGuard> {
+    account.filter(|account| !account.removed_without_update())
+}
@@ -0,0 +1,173 @@
+use mazze_parameters::internal_contract_addresses::ETH_SPONSOR_CONTROL_CONTRACT_ADDRESS;
+use mazze_statedb::Result as DbResult;
+use mazze_types::{
+    maybe_address, Address, AddressSpaceUtil, AddressWithSpace,
+    BigEndianHash, H256, U256,
+};
+use keccak_hash::keccak;
+use primitives::SponsorInfo;
+
+use super::{State, Substate};
+use crate::{return_if, state::CleanupMode};
+
+// The sponsorship of the eSpace contracts is kept in the storage of the eSpace
+// sponsor control contract, and the sponsor balances are kept in its balance.
+// The eSpace charges no storage collateral, so only the gas is sponsored.
+
+const SPONSOR_FOR_GAS: u8 = 0;
+const SPONSOR_BALANCE_FOR_GAS: u8 = 1;
+const SPONSOR_GAS_BOUND: u8 = 2;
+
+impl State {
+    pub fn eth_sponsor_info(
+        &self, contract: &Address,
+    ) -> DbResult<Option<SponsorInfo>> {
+        let sponsor_for_gas = match self.eth_sponsor_for_gas(contract)? {
+            Some(sponsor) => sponsor,
+            None => return Ok(None),
+        };
+        Ok(Some(SponsorInfo {
+            sponsor_for_gas,
+            sponsor_gas_bound: self.eth_sponsor_gas_bound(contract)?,
+            sponsor_balance_for_gas: self
+                .eth_sponsor_balance_for_gas(contract)?,
+            ..Default::default()
+        }))
+    }
+
+    pub fn eth_sponsor_for_gas(
+        &self, contract: &Address,
+    ) -> DbResult<Option<Address>> {
+        let value = self.eth_sponsor_field(contract, SPONSOR_FOR_GAS)?;
+        Ok(maybe_address(&Address::from(H256::from_uint(&value))))
+    }
+
+    pub fn eth_sponsor_balance_for_gas(
+        &self, contract: &Address,
+    ) -> DbResult<U256> {
+        self.eth_sponsor_field(contract, SPONSOR_BALANCE_FOR_GAS)
+    }
+
+    pub fn eth_sponsor_gas_bound(&self, contract: &Address) -> DbResult<U256> {
+        self.eth_sponsor_field(contract, SPONSOR_GAS_BOUND)
+    }
+
+    /// Records the sponsor for gas of an eSpace contract. The sponsor balance
+    /// must have been transferred to the sponsor control contract.
+    pub fn set_eth_sponsor_for_gas(
+        &mut self, contract: &Address, sponsor: &Address,
+        sponsor_balance: &U256, upper_bound: &U256,
+    ) -> DbResult<()> {
+        self.set_eth_sponsor_field(
+            contract,
+            SPONSOR_FOR_GAS,
+            H256::from(*sponsor).into_uint(),
+        )?;
+        self.set_eth_sponsor_field(
+            contract,
+            SPONSOR_BALANCE_FOR_GAS,
+            *sponsor_balance,
+        )?;
+        self.set_eth_sponsor_field(contract, SPONSOR_GAS_BOUND, *upper_bound)
+    }
+
+    /// Adds to the sponsor balance for gas of an eSpace contract, which is
+    /// added to the balance of the sponsor control contract.
+    pub fn add_eth_sponsor_balance_for_gas(
+        &mut self, contract: &Address, by: &U256,
+    ) -> DbResult<()> {
+        return_if!(by.is_zero());
+
+        let balance = self.eth_sponsor_balance_for_gas(contract)?;
+        self.set_eth_sponsor_field(
+            contract,
+            SPONSOR_BALANCE_FOR_GAS,
+            balance + by,
+        )?;
+        self.add_balance(&eth_sponsor_address(), by, CleanupMode::NoEmpty)
+    }
+
+    /// Subtracts from the sponsor balance for gas of an eSpace contract, which
+    /// is subtracted from the balance of the sponsor control contract.
+    pub fn sub_eth_sponsor_balance_for_gas(
+        &mut self, contract: &Address, by: &U256,
+    ) -> DbResult<()> {
+        return_if!(by.is_zero());
+
+        let balance = self.eth_sponsor_balance_for_gas(contract)?;
+        self.set_eth_sponsor_field(
+            contract,
+            SPONSOR_BALANCE_FOR_GAS,
+            balance - by,
+        )?;
+        self.sub_balance(&eth_sponsor_address(), by, &mut CleanupMode::NoEmpty)
+    }
+
+    // Whitelist
+
+    pub fn check_eth_contract_whitelist(
+        &self, contract: &Address, user: &Address,
+    ) -> DbResult<bool> {
+        let all_value = self.storage_at(
+            &eth_sponsor_address(),
+            &whitelist_key(contract, &Address::zero()),
+        )?;
+        if !all_value.is_zero() {
+            return Ok(true);
+        }
+        self.storage_at(&eth_sponsor_address(), &whitelist_key(contract, user))
+            .map(|x| !x.is_zero())
+    }
+
+    pub fn add_to_eth_contract_whitelist(
+        &mut self, contract: &Address, user: &Address,
+    ) -> DbResult<()> {
+        self.set_eth_sponsor_storage(whitelist_key(contract, user), U256::one())
+    }
+
+    pub fn remove_from_eth_contract_whitelist(
+        &mut self, contract: &Address, user: &Address,
+    ) -> DbResult<()> {
+        self.set_eth_sponsor_storage(whitelist_key(contract, user), U256::zero())
+    }
+
+    fn eth_sponsor_field(&self, contract: &Address, field: u8) -> DbResult<U256> {
+        self.storage_at(&eth_sponsor_address(), &field_key(contract, field))
+    }
+
+    fn set_eth_sponsor_field(
+        &mut self, contract: &Address, field: u8, value: U256,
+    ) -> DbResult<()> {
+        self.set_eth_sponsor_storage(field_key(contract, field), value)
+    }
+
+    fn set_eth_sponsor_storage(
+        &mut self, key: Vec<u8>, value: U256,
+    ) -> DbResult<()> {
+        // The eSpace storage has no owner.
+        self.set_storage(
+            &eth_sponsor_address(),
+            key,
+            value,
+            Address::zero(),
+            &mut Substate::new(),
+        )
+    }
+}
+
+#[inline]
+fn eth_sponsor_address() -> AddressWithSpace {
+    ETH_SPONSOR_CONTROL_CONTRACT_ADDRESS.with_evm_space()
+}
+
+fn field_key(contract: &Address, field: u8) -> Vec<u8> {
+    let mut preimage = contract.as_bytes().to_vec();
+    preimage.push(field);
+    keccak(preimage).as_bytes().to_vec()
+}
+
+fn whitelist_key(contract: &Address, user: &Address) -> Vec<u8> {
+    let mut preimage = contract.as_bytes().to_vec();
+    preimage.extend_from_slice(user.as_bytes());
+    keccak(preimage).as_bytes().to_vec()
+}
@@ -12,27 +12,25 @@ impl State {
 
     /// Maintain `total_issued_tokens`.
     pub fn add_total_issued(&mut self, v: U256) {
-        *self.global_stat.val::<TotalIssued>() += v;
+        self.global_stat.add::<TotalIssued>(v);
     }
 
     /// Maintain `total_issued_tokens`. This is only used in the extremely
     /// unlikely case that there are a lot of partial invalid blocks.
     pub fn sub_total_issued(&mut self, v: U256) {
-        *self.global_stat.val::<TotalIssued>() =
-            self.global_stat.refr::<TotalIssued>().saturating_sub(v);
+        self.global_stat.saturating_sub::<TotalIssued>(v);
     }
 
     pub fn add_total_pos_staking(&mut self, v: U256) {
-        *self.global_stat.val::<TotalPosStaking>() += v;
+        self.global_stat.add::<TotalPosStaking>(v);
     }
 
     pub fn add_total_evm_tokens(&mut self, v: U256) {
-        *self.global_stat.val::<TotalEvmToken>() += v;
+        self.global_stat.add::<TotalEvmToken>(v);
     }
 
     pub fn sub_total_evm_tokens(&mut self, v: U256) {
-        *self.global_stat.val::<TotalEvmToken>() =
-            self.global_stat.refr::<TotalEvmToken>().saturating_sub(v);
+        self.global_stat.saturating_sub::<TotalEvmToken>(v);
     }
 
     pub fn total_staking_tokens(&self) -> U256 {
@@ -60,8 +58,7 @@ impl State {
     }
 
     pub fn sub_total_pos_staking(&mut self, v: U256) {
-        *self.global_stat.val::<TotalPosStaking>() =
-            self.global_stat.refr::<TotalPosStaking>().saturating_sub(v)
+        self.global_stat.saturating_sub::<TotalPosStaking>(v);
     }
 
     pub fn total_circulating_tokens(&self) -> DbResult<U256> {
@@ -74,11 +71,11 @@ impl State {
     pub fn add_converted_storage_point(
         &mut self, from_balance: U256, from_collateral: U256,
     ) {
-        *self.global_stat.val::<TotalIssued>() -=
-            from_balance + from_collateral;
-        *self.global_stat.val::<TotalStorage>() -= from_collateral;
-        *self.global_stat.val::<UsedStoragePoints>() += from_collateral;
-        *self.global_stat.val::<ConvertedStoragePoints>() +=
-            from_balance + from_collateral;
+        self.global_stat
+            .sub::<TotalIssued>(from_balance + from_collateral);
+        self.global_stat.sub::<TotalStorage>(from_collateral);
+        self.global_stat.add::<UsedStoragePoints>(from_collateral);
+        self.global_stat
+            .add::<ConvertedStoragePoints>(from_balance + from_collateral);
     }
 }
@@ -24,6 +24,14 @@ mod collateral;
 /// Implements functions for committing `State` changes to db.
 mod commit;
 
+/// State Diff: Implements comparing the accounts and storage entries of two
+/// states.
+mod diff;
+
+/// Implements functions for the gas sponsorship of eSpace contracts of
+/// `State`.
+mod eth_sponsor;
+
 /// Implements access functions global statistic variables of `State`.
 mod global_statistics;
 
@@ -39,6 +47,11 @@ mod staking;
 /// Implements access functions for the account storage entries of `State`.
 mod storage_entry;
 
+/// Speculative Execution: Implements seeding a `State` with the uncommitted
+/// changes of another one, and moving the effects of a transaction between
+/// them.
+mod speculative;
+
 mod reward;
 
 #[cfg(test)]
@@ -47,8 +60,10 @@ mod tests;
 pub use self::{
     collateral::{initialize_cip107, settle_collateral_for_all},
     commit::StateCommitResult,
+    diff::{AccountDiff, StorageDiff},
     pos::{distribute_pos_interest, update_pos_status},
     reward::initialize_cip137,
+    speculative::{GlobalStatSnapshot, StateSeed, StateWriteSet},
     sponsor::COMMISSION_PRIVILEGE_SPECIAL_KEY,
     staking::initialize_or_update_dao_voted_params,
 };
@@ -57,6 +72,7 @@ pub use tests::get_state_for_genesis_write;
 
 use self::checkpoints::CheckpointLayer;
 use super::{
+    access_recorder::AccessRecorder,
     global_stat::GlobalStat,
     overlay_account::{AccountEntry, OverlayAccount, RequireFields},
 };
@@ -64,7 +80,7 @@ use crate::substate::Substate;
 use mazze_statedb::{Result as DbResult, StateDbExt, StateDbGeneric as StateDb};
 use mazze_types::AddressWithSpace;
 use parking_lot::RwLock;
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 /// A caching and checkpoint layer built upon semantically meaningful database
 /// interfaces, providing interfaces and logics for managing accounts and global
@@ -85,6 +101,9 @@ pub struct State {
 
     /// Checkpoint layers for the account entries
     checkpoints: RwLock<Vec<CheckpointLayer>>,
+
+    /// Records the accessed accounts if access tracking is enabled.
+    access_recorder: Option<Arc<AccessRecorder>>,
 }
 
 impl State {
@@ -103,6 +122,7 @@ impl State {
             cache: Default::default(),
             checkpoints: Default::default(),
             global_stat: world_stat,
+            access_recorder: None,
         })
     }
 
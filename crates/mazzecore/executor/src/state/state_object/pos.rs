@@ -48,7 +48,8 @@ impl State {
         ) / (BLOCKS_PER_YEAR
             * INVERSE_INTEREST_RATE
             * INITIAL_INTEREST_RATE_PER_BLOCK.as_u64());
-        *self.global_stat.val::<DistributablePoSInterest>() += interest_amount;
+        self.global_stat
+            .add::<DistributablePoSInterest>(interest_amount);
 
         Ok(())
     }
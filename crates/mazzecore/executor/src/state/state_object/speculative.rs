@@ -0,0 +1,176 @@
+//! Speculative Execution: Implements seeding a `State` with the uncommitted
+//! changes of another one, and moving the effects of a transaction between
+//! them.
+
+use super::{AccessRecorder, AccountEntry, State};
+use crate::state::StateAccessSet;
+use mazze_statedb::{
+    global_params::TOTAL_GLOBAL_PARAMS, Result as DbResult,
+    StateDbGeneric as StateDb,
+};
+use mazze_types::{AddressWithSpace, U256};
+use std::sync::Arc;
+
+/// The uncommitted changes of a `State`, from which states seeing the same
+/// ledger can be built on other threads.
+pub struct StateSeed {
+    accounts: Vec<(AddressWithSpace, AccountEntry)>,
+    global_stat: GlobalStatSnapshot,
+}
+
+/// The values of the global statistic variables at some point.
+#[derive(Clone, Copy, Debug)]
+pub struct GlobalStatSnapshot([U256; TOTAL_GLOBAL_PARAMS]);
+
+/// The final version of the accounts and global statistic variables modified
+/// by an execution.
+pub struct StateWriteSet {
+    accounts: Vec<(AddressWithSpace, AccountEntry)>,
+    global_stat: Vec<(usize, U256)>,
+    /// The changes of the global statistic variables which are only added to
+    /// or subtracted from, e.g. the burnt fees. They are applied to the
+    /// current version of the variables, so executions updating the same
+    /// variable do not conflict.
+    global_stat_delta: Vec<(usize, U256)>,
+}
+
+impl State {
+    /// Make a seed from the uncommitted changes. It must be called between
+    /// transactions, when there is no checkpoint.
+    pub fn make_seed(&self) -> StateSeed {
+        assert!(self.checkpoints.read().is_empty());
+
+        let accounts = self
+            .cache
+            .read()
+            .iter()
+            .filter(|(_, entry)| entry.is_dirty())
+            .map(|(address, entry)| (*address, entry.clone_cache_entry()))
+            .collect();
+
+        StateSeed {
+            accounts,
+            global_stat: self.global_stat_snapshot(),
+        }
+    }
+
+    /// Build a state over `db` which sees the changes in `seed`. `db` must be
+    /// opened at the same ledger state as the db of the seeded state.
+    pub fn from_seed(db: StateDb, seed: &StateSeed) -> DbResult<State> {
+        let mut state = State::new(db)?;
+
+        let cache = state.cache.get_mut();
+        for (address, entry) in &seed.accounts {
+            cache.insert(*address, entry.clone_cache_entry());
+        }
+        for (id, value) in seed.global_stat.0.iter().enumerate() {
+            state.global_stat.set_value(id, *value);
+        }
+
+        Ok(state)
+    }
+
+    /// Start recording the accessed accounts and global statistic variables.
+    /// The records survive reverting checkpoints.
+    pub fn start_access_tracking(&mut self) {
+        let recorder =
+            Arc::new(AccessRecorder::new(*self.global_stat.values()));
+        self.global_stat.set_recorder(Some(recorder.clone()));
+        self.access_recorder = Some(recorder);
+    }
+
+    /// Stop recording and return what was accessed since
+    /// `start_access_tracking`.
+    pub fn finish_access_tracking(&mut self) -> StateAccessSet {
+        self.global_stat.set_recorder(None);
+        self.access_recorder
+            .take()
+            .map_or_else(Default::default, |recorder| recorder.take())
+    }
+
+    pub fn global_stat_snapshot(&self) -> GlobalStatSnapshot {
+        GlobalStatSnapshot(*self.global_stat.values())
+    }
+
+    /// Whether every global statistic variable read in `access` has the same
+    /// value as in `snapshot`, and every variable only updated is large
+    /// enough for the subtractions in `access`.
+    pub fn global_stat_consistent_with(
+        &self, access: &StateAccessSet, snapshot: &GlobalStatSnapshot,
+    ) -> bool {
+        let values = self.global_stat.values();
+        (0..TOTAL_GLOBAL_PARAMS).all(|id| {
+            if access.global_stat_read(id) {
+                values[id] == snapshot.0[id]
+            } else if access.global_stat_updated(id) {
+                values[id] >= access.global_stat_lower_bound[id]
+            } else {
+                true
+            }
+        })
+    }
+
+    /// Collect the current version of everything modified in `access`, which
+    /// was recorded since the global statistic variables were `before`.
+    pub fn collect_writes(
+        &self, access: &StateAccessSet, before: &GlobalStatSnapshot,
+    ) -> StateWriteSet {
+        let cache = self.cache.read();
+        // An account missing in cache has been reverted to its db version.
+        let accounts = access
+            .written_accounts
+            .iter()
+            .filter_map(|address| {
+                cache
+                    .get(address)
+                    .map(|entry| (*address, entry.clone_cache_entry()))
+            })
+            .collect();
+
+        let values = self.global_stat.values();
+        let global_stat = (0..TOTAL_GLOBAL_PARAMS)
+            .filter(|id| access.global_stat_written(*id))
+            .map(|id| (id, values[id]))
+            .collect();
+        let global_stat_delta = (0..TOTAL_GLOBAL_PARAMS)
+            .filter(|id| {
+                access.global_stat_updated(*id)
+                    && !access.global_stat_written(*id)
+            })
+            .map(|id| (id, values[id].overflowing_sub(before.0[id]).0))
+            .filter(|(_, delta)| !delta.is_zero())
+            .collect();
+
+        StateWriteSet {
+            accounts,
+            global_stat,
+            global_stat_delta,
+        }
+    }
+
+    /// Apply the writes collected from another state. The caller must
+    /// guarantee that the writing execution has seen the same version of
+    /// everything it accessed as in this state, see
+    /// `global_stat_consistent_with` for the global statistic variables.
+    pub fn apply_writes(&mut self, writes: StateWriteSet) {
+        assert!(self.checkpoints.get_mut().is_empty());
+
+        let StateWriteSet {
+            accounts,
+            global_stat,
+            global_stat_delta,
+        } = writes;
+
+        let cache = self.cache.get_mut();
+        for (address, entry) in accounts {
+            cache.insert(address, entry);
+        }
+        for (id, value) in global_stat {
+            self.global_stat.set_value(id, value);
+        }
+        for (id, delta) in global_stat_delta {
+            let value = self.global_stat.values()[id].overflowing_add(delta).0;
+            self.global_stat.set_value(id, value);
+        }
+    }
+}
@@ -113,9 +113,10 @@ impl State {
             .write_native_account_lock(&address)?
             .set_sponsor_for_collateral(sponsor, sponsor_balance, prop);
 
-        *self.global_stat.val::<TotalIssued>() -= converted_storage_points;
-        *self.global_stat.val::<ConvertedStoragePoints>() +=
-            converted_storage_points;
+        self.global_stat
+            .sub::<TotalIssued>(converted_storage_points);
+        self.global_stat
+            .add::<ConvertedStoragePoints>(converted_storage_points);
         Ok(converted_storage_points)
     }
 
@@ -76,6 +76,15 @@ impl State {
         Ok(())
     }
 
+    /// Overwrite a storage value without a transaction, as the dev mode
+    /// state overrides do. The storage collateral is not settled.
+    pub fn overwrite_storage(
+        &mut self, address: &AddressWithSpace, key: Vec<u8>, value: U256,
+    ) -> DbResult<()> {
+        self.write_account_or_new_lock(address)?
+            .overwrite_storage_value(&self.db, key, value)
+    }
+
     #[inline]
     pub fn transient_set_storage(
         &mut self, address: &AddressWithSpace, key: Vec<u8>, value: U256,
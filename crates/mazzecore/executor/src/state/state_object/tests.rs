@@ -902,6 +902,62 @@ fn check_result_of_simple_payment_to_killed_account() {
     // assert_eq!(state.storage_at(&a, &k).unwrap(), U256::zero());
 }
 
+#[test]
+fn diff_accounts() {
+    let storage_manager = new_state_manager_for_unit_test();
+    let mut state = get_state_for_genesis_write(&storage_manager);
+    let a_s = Address::from_low_u64_be(1000).with_native_space();
+    let b_s = Address::from_low_u64_be(1001).with_native_space();
+    let k = u256_to_vec(&U256::from(0));
+
+    state
+        .add_balance(&a_s, &U256::from(1), CleanupMode::NoEmpty)
+        .unwrap();
+    state.new_contract_with_code(&b_s, U256::zero()).unwrap();
+    state
+        .set_storage(
+            &b_s,
+            k.clone(),
+            U256::from(1),
+            a_s.address,
+            &mut Substate::new(),
+        )
+        .unwrap();
+    let epoch_id_1 = EpochId::from_uint(&U256::from(1));
+    state.commit(epoch_id_1, /* debug_record = */ None).unwrap();
+
+    let base = get_state(&storage_manager, &epoch_id_1);
+    let mut state = get_state(&storage_manager, &epoch_id_1);
+    state
+        .add_balance(&a_s, &U256::from(1), CleanupMode::NoEmpty)
+        .unwrap();
+    state
+        .set_storage(
+            &b_s,
+            k.clone(),
+            U256::from(2),
+            a_s.address,
+            &mut Substate::new(),
+        )
+        .unwrap();
+
+    let diffs = state.diff_accounts(&base, &[a_s, b_s]).unwrap();
+    assert_eq!(diffs.len(), 2);
+    assert_eq!(diffs[0].address, a_s);
+    assert_eq!(diffs[0].before.as_ref().unwrap().balance, U256::from(1));
+    assert_eq!(diffs[0].after.as_ref().unwrap().balance, U256::from(2));
+    assert!(diffs[0].storage.is_empty());
+    assert_eq!(diffs[1].address, b_s);
+    assert_eq!(diffs[1].before, diffs[1].after);
+    assert_eq!(diffs[1].storage.len(), 1);
+    assert_eq!(diffs[1].storage[0].key, k);
+    assert_eq!(diffs[1].storage[0].before, U256::from(1));
+    assert_eq!(diffs[1].storage[0].after, U256::from(2));
+
+    // The accounts without difference are omitted.
+    assert!(base.diff_accounts(&base, &[a_s, b_s]).unwrap().is_empty());
+}
+
 #[test]
 fn create_contract_fail() {
     let storage_manager = new_state_manager_for_unit_test();
@@ -1,7 +1,7 @@
 
 
 use crate::state::CleanupMode;
-use mazze_types::{Address, AddressWithSpace};
+use mazze_types::{Address, AddressWithSpace, U256};
 use mazze_vm_types::{CleanDustMode, Spec};
 use primitives::{
     receipt::{SortedStorageChanges, StorageChange},
@@ -27,6 +27,9 @@ pub struct Substate {
     pub logs: Vec<LogEntry>,
     /// Created contracts.
     pub contracts_created: Vec<AddressWithSpace>,
+    /// The gas refunded at the end of the transaction, e.g. for the EIP-7702
+    /// authorities which already exist.
+    pub refund_counter: U256,
 }
 
 impl Substate {
@@ -35,6 +38,7 @@ impl Substate {
         self.touched.extend(s.touched);
         self.logs.extend(s.logs);
         self.contracts_created.extend(s.contracts_created);
+        self.refund_counter += s.refund_counter;
         for (address, amount) in s.storage_collateralized {
             *self.storage_collateralized.entry(address).or_insert(0) += amount;
         }
@@ -110,6 +110,17 @@ impl StateAvailabilityBoundary {
         }
     }
 
+    /// Truncate the main chain after `height`, so the states above it are
+    /// unavailable until they are executed again. This is used when the
+    /// consensus graph is rebuilt.
+    pub fn truncate_main_chain(&mut self, height: u64) {
+        assert!(self.lower_bound <= height);
+        self.main_chain
+            .truncate((height - self.lower_bound + 1) as usize);
+        self.upper_bound = min(self.upper_bound, height);
+        self.optimistic_executed_height = None;
+    }
+
     /// This function will record the most recent synced_state_height for
     /// special case handling.
     pub fn set_synced_state_height(&mut self, synced_state_height: u64) {
@@ -153,3 +164,4 @@ use mazze_types::{Space, H256};
 use derivative::Derivative;
 use malloc_size_of_derive::MallocSizeOf;
 use primitives::BlockHeader;
+use std::cmp::min;
@@ -21,6 +21,10 @@ pub const PARAMS_CONTROL_CONTRACT_ADDRESS: Address =
 pub const SYSTEM_STORAGE_ADDRESS: Address =
     H160(hex!("088800000000000000000000000000000000000a"));
 
+// The internal contracts in the eSpace.
+pub const ETH_SPONSOR_CONTROL_CONTRACT_ADDRESS: Address =
+    H160(hex!("088800000000000000000000000000000000000c"));
+
 // We reserve more addresses so we don't need to change the genesis hash
 // in test mode each time adding new internal contracts.
 pub const RESERVED3: Address =
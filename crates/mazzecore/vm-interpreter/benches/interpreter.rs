@@ -0,0 +1,100 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use keccak_hash::keccak;
+use mazze_types::U256;
+use mazze_vm_interpreter::{Factory, VMType};
+use mazze_vm_types::{tests::MockContext, ActionParams, Context};
+use rustc_hex::FromHex;
+use std::sync::Arc;
+
+// The balance bookkeeping of an ERC-20 transfer, repeated 64 times.
+//
+// 0000 60 40      PUSH1 0x40
+// 0002 5B         JUMPDEST
+// 0003 33         CALLER
+// 0004 60 00      PUSH1 0x00
+// 0006 52         MSTORE
+// 0007 60 00      PUSH1 0x00
+// 0009 60 20      PUSH1 0x20
+// 000b 52         MSTORE
+// 000c 60 40      PUSH1 0x40
+// 000e 60 00      PUSH1 0x00
+// 0010 20         SHA3
+// 0011 80         DUP1
+// 0012 54         SLOAD
+// 0013 82         DUP3
+// 0014 01         ADD
+// 0015 90         SWAP1
+// 0016 55         SSTORE
+// 0017 80         DUP1
+// 0018 60 20      PUSH1 0x20
+// 001a 60 00      PUSH1 0x00
+// 001c A1         LOG1
+// 001d 60 01      PUSH1 0x01
+// 001f 90         SWAP1
+// 0020 03         SUB
+// 0021 80         DUP1
+// 0022 60 02      PUSH1 0x02
+// 0024 57         JUMPI
+// 0025 00         STOP
+const ERC20_CODE: &str = "60405b33600052600060205260406000208054820190558060\
+                          206000a1600190038060025700";
+
+// The constant product formula of an AMM swap, repeated 256 times.
+//
+// 0000 61 0100      PUSH2 0x0100
+// 0003 5B           JUMPDEST
+// 0004 62 0f4240    PUSH3 0x0f4240
+// 0008 61 03e5      PUSH2 0x03e5
+// 000b 02           MUL
+// 000c 80           DUP1
+// 000d 63 3b9aca00  PUSH4 0x3b9aca00
+// 0012 02           MUL
+// 0013 90           SWAP1
+// 0014 63 05f5e100  PUSH4 0x05f5e100
+// 0019 61 03e8      PUSH2 0x03e8
+// 001c 02           MUL
+// 001d 01           ADD
+// 001e 90           SWAP1
+// 001f 04           DIV
+// 0020 50           POP
+// 0021 60 01        PUSH1 0x01
+// 0023 90           SWAP1
+// 0024 03           SUB
+// 0025 80           DUP1
+// 0026 60 03        PUSH1 0x03
+// 0028 57           JUMPI
+// 0029 00           STOP
+const AMM_CODE: &str = "6101005b620f42406103e50280633b9aca0002906305f5e100\
+                        6103e80201900450600190038060035700";
+
+fn execute(factory: &Factory, code: &Arc<Vec<u8>>) {
+    let mut params = ActionParams::default();
+    params.gas = U256::from(10_000_000);
+    params.code_hash = keccak(code.as_slice());
+    params.code = Some(code.clone());
+    let mut ctx = MockContext::new();
+
+    let vm = factory.create(params, ctx.spec(), ctx.depth());
+    assert!(vm.exec(&mut ctx).ok().unwrap().is_ok());
+}
+
+fn bench_interpreter(c: &mut Criterion) {
+    for (name, code) in [("erc20", ERC20_CODE), ("amm", AMM_CODE)] {
+        let code = Arc::new(code.from_hex::<Vec<u8>>().unwrap());
+
+        c.bench_function(&format!("interpreter {}", name), |b| {
+            let factory = Factory::new(VMType::Interpreter, 1024 * 32);
+            b.iter(|| execute(&factory, &code));
+        });
+
+        c.bench_function(&format!("interpreter {} uncached", name), |b| {
+            b.iter(|| {
+                let factory = Factory::new(VMType::Interpreter, 1024 * 32);
+                execute(&factory, &code)
+            });
+        });
+    }
+}
+
+criterion_group!(benches, bench_interpreter);
+criterion_main!(benches);
@@ -0,0 +1,87 @@
+//! Runs the EOF validation fixtures of `testdata/eof_tests`, the `eof_tests`
+//! of the Osaka release of the Ethereum execution spec tests (EEST), as they
+//! are published. Each vector is validated as the kind of container it
+//! declares, and must be valid exactly when its Osaka result is.
+
+use super::{validate_container, ContainerKind};
+use rustc_hex::FromHex;
+use serde_json::Value;
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+const FIXTURES_DIR: &str = "src/eof/testdata/eof_tests";
+
+/// The vectors whose expected result doesn't hold for Mazze, as
+/// `(vector, reason)` where the reason names the part of the spec Mazze
+/// diverges from.
+const KNOWN_DIVERGENCES: &[(&str, &str)] = &[];
+
+fn fixture_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            fixture_files(&path, files);
+        } else if path.extension().map_or(false, |ext| ext == "json") {
+            files.push(path);
+        }
+    }
+}
+
+fn container_kind(vector: &Value) -> ContainerKind {
+    match vector["containerKind"].as_str() {
+        None | Some("RUNTIME") => ContainerKind::Runtime,
+        Some("INITCODE") => ContainerKind::Initcode,
+        Some(kind) => panic!("unknown container kind {}", kind),
+    }
+}
+
+#[test]
+#[ignore = "needs the EEST Osaka eof_tests fixtures in src/eof/testdata"]
+fn test_eof_validation_fixtures() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join(FIXTURES_DIR);
+    let mut files = vec![];
+    fixture_files(&root, &mut files);
+    files.sort();
+
+    let known: HashSet<&str> = KNOWN_DIVERGENCES
+        .iter()
+        .map(|(vector, _)| *vector)
+        .collect();
+    let mut skipped = 0;
+    let mut checked = 0;
+    let mut failures = vec![];
+    for file in files {
+        let suite: Value =
+            serde_json::from_str(&fs::read_to_string(&file).unwrap()).unwrap();
+        for (test_name, test) in suite.as_object().unwrap() {
+            for (vector_name, vector) in test["vectors"].as_object().unwrap() {
+                let id = format!("{}/{}", test_name, vector_name);
+                if known.contains(id.as_str()) {
+                    skipped += 1;
+                    continue;
+                }
+                let code: Vec<u8> = vector["code"]
+                    .as_str()
+                    .unwrap()
+                    .trim_start_matches("0x")
+                    .from_hex()
+                    .unwrap();
+                let valid =
+                    validate_container(&code, container_kind(vector)).is_ok();
+                let expected =
+                    vector["results"]["Osaka"]["result"].as_bool().unwrap();
+                if valid != expected {
+                    failures.push(id);
+                }
+                checked += 1;
+            }
+        }
+    }
+
+    assert_eq!(skipped, known.len(), "unknown divergence in the list");
+    assert!(checked > 0, "no fixtures in {}", root.display());
+    assert!(failures.is_empty(), "failed vectors: {:?}", failures);
+}
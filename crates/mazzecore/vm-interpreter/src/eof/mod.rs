@@ -0,0 +1,649 @@
+//! EVM Object Format (EOF) v1, as specified by the EIPs of the EOF meta
+//! EIP-7692. An EOF container is the `0xEF00` magic, a header of section
+//! sizes, and the type, code, container and data sections. Containers are
+//! validated once at deploy time, so that their code can be executed without
+//! jump destination analysis nor stack checks.
+
+#[cfg(test)]
+mod fixtures;
+mod validation;
+
+pub use self::validation::{validate_container, ContainerKind};
+
+use mazze_bytes::Bytes;
+use std::{fmt, sync::Arc};
+
+/// The magic prefix of EOF code.
+pub const EOF_MAGIC: [u8; 2] = [0xef, 0x00];
+/// The only EOF version.
+pub const EOF_VERSION: u8 = 0x01;
+
+const KIND_TYPES: u8 = 0x01;
+const KIND_CODE: u8 = 0x02;
+const KIND_CONTAINER: u8 = 0x03;
+const KIND_DATA: u8 = 0xff;
+const TERMINATOR: u8 = 0x00;
+
+/// The size of an entry of the type section.
+const TYPE_SIZE: usize = 4;
+
+/// The maximal number of code sections of a container.
+pub const MAX_CODE_SECTIONS: usize = 1024;
+/// The maximal number of subcontainers of a container.
+pub const MAX_CONTAINERS: usize = 256;
+/// The maximal stack height of a code section.
+pub const MAX_STACK_HEIGHT: usize = 1023;
+/// The maximal depth of the return stack of `CALLF`.
+pub const MAX_RETURN_STACK_DEPTH: usize = 1024;
+/// The `outputs` of a code section which never returns.
+pub const NON_RETURNING: u8 = 0x80;
+
+/// Returns true if the code has the EOF magic prefix.
+pub fn is_eof(code: &[u8]) -> bool { code.starts_with(&EOF_MAGIC) }
+
+/// Splits the data of a creation transaction with EOF initcode into the
+/// initcode container and its input data (EIP-7698). Returns `None` if the
+/// header is invalid or the container is truncated.
+pub fn split_initcode(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let size = EofHeader::decode(data).ok()?.container_size();
+    match data.len() >= size {
+        true => Some(data.split_at(size)),
+        false => None,
+    }
+}
+
+/// The reason why a container is invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EofError {
+    /// The code does not start with the EOF magic.
+    InvalidMagic,
+    /// The version is not `EOF_VERSION`.
+    InvalidVersion,
+    /// The header is truncated or its section kinds are out of order.
+    InvalidHeader,
+    /// The type section size does not match the number of code sections.
+    InvalidTypesSize,
+    /// There are no or too many code sections.
+    InvalidCodeSectionCount,
+    /// There are no or too many subcontainers.
+    InvalidContainerCount,
+    /// A code section or a subcontainer is empty.
+    EmptySection,
+    /// The container size does not match its header.
+    InvalidContainerSize,
+    /// The data section is shorter than declared in the header.
+    TruncatedData,
+    /// The first code section takes inputs or returns.
+    InvalidFirstSectionType,
+    /// The inputs, outputs or maximal stack height of a code section are out
+    /// of range.
+    InvalidTypeSection,
+    /// An instruction is undefined or deprecated in EOF code.
+    UndefinedInstruction(u8),
+    /// The immediate of the last instruction is truncated.
+    TruncatedImmediate,
+    /// A relative jump lands outside of the code section or on an immediate.
+    InvalidJumpDestination,
+    /// `CALLF` or `JUMPF` refers to a missing code section.
+    InvalidCodeSectionIndex,
+    /// `EOFCREATE` or `RETURNCONTRACT` refers to a missing subcontainer.
+    InvalidContainerIndex,
+    /// `DATALOADN` reads past the declared data section.
+    InvalidDataLoadOffset,
+    /// `CALLF` calls a code section which never returns.
+    CallToNonReturning,
+    /// The `outputs` of a code section do not match whether it returns.
+    InvalidNonReturningFlag,
+    /// An instruction can not be reached by forward jumps.
+    UnreachableCode,
+    /// A code section can not be reached from the first one.
+    UnreachableCodeSection,
+    /// A subcontainer is not referenced by the code.
+    UnreferencedSubcontainer,
+    /// A subcontainer is referenced both by `EOFCREATE` and
+    /// `RETURNCONTRACT`.
+    AmbiguousContainerKind,
+    /// `RETURNCONTRACT` is used in runtime code, or `RETURN` or `STOP` in
+    /// initcode.
+    IncompatibleContainerKind,
+    /// The code can run past the end of its code section.
+    NoTerminatingInstruction,
+    /// An instruction can be reached with too few stack items.
+    StackUnderflow,
+    /// The stack height can exceed the stack limit.
+    StackOverflow,
+    /// The stack height at a backward jump target or at a return does not
+    /// match.
+    InvalidStackHeight,
+    /// The declared maximal stack increase of a code section does not match
+    /// its code.
+    InvalidMaxStackIncrease,
+}
+
+impl fmt::Display for EofError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::EofError::*;
+        match self {
+            InvalidMagic => write!(f, "invalid EOF magic"),
+            InvalidVersion => write!(f, "invalid EOF version"),
+            InvalidHeader => write!(f, "invalid EOF header"),
+            InvalidTypesSize => write!(f, "invalid type section size"),
+            InvalidCodeSectionCount => write!(f, "invalid code section count"),
+            InvalidContainerCount => write!(f, "invalid container count"),
+            EmptySection => write!(f, "empty section"),
+            InvalidContainerSize => write!(f, "invalid container size"),
+            TruncatedData => write!(f, "truncated data section"),
+            InvalidFirstSectionType => {
+                write!(f, "invalid type of the first code section")
+            }
+            InvalidTypeSection => write!(f, "invalid type section"),
+            UndefinedInstruction(opcode) => {
+                write!(f, "undefined instruction {:x}", opcode)
+            }
+            TruncatedImmediate => write!(f, "truncated immediate"),
+            InvalidJumpDestination => write!(f, "invalid jump destination"),
+            InvalidCodeSectionIndex => write!(f, "invalid code section index"),
+            InvalidContainerIndex => write!(f, "invalid container index"),
+            InvalidDataLoadOffset => write!(f, "invalid DATALOADN offset"),
+            CallToNonReturning => {
+                write!(f, "CALLF to a non-returning code section")
+            }
+            InvalidNonReturningFlag => write!(f, "invalid non-returning flag"),
+            UnreachableCode => write!(f, "unreachable code"),
+            UnreachableCodeSection => write!(f, "unreachable code section"),
+            UnreferencedSubcontainer => write!(f, "unreferenced subcontainer"),
+            AmbiguousContainerKind => write!(f, "ambiguous container kind"),
+            IncompatibleContainerKind => {
+                write!(f, "incompatible container kind")
+            }
+            NoTerminatingInstruction => {
+                write!(f, "no terminating instruction")
+            }
+            StackUnderflow => write!(f, "stack underflow"),
+            StackOverflow => write!(f, "stack overflow"),
+            InvalidStackHeight => write!(f, "invalid stack height"),
+            InvalidMaxStackIncrease => write!(f, "invalid max stack increase"),
+        }
+    }
+}
+
+/// The signature of a code section.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TypeSection {
+    /// The number of stack items taken by the code section.
+    pub inputs: u8,
+    /// The number of stack items returned, or `NON_RETURNING`.
+    pub outputs: u8,
+    /// The maximal stack height above the inputs.
+    pub max_stack_increase: u16,
+}
+
+impl TypeSection {
+    pub fn is_returning(&self) -> bool { self.outputs != NON_RETURNING }
+
+    /// The maximal stack height of the code section, with its inputs.
+    pub fn max_stack_height(&self) -> usize {
+        self.inputs as usize + self.max_stack_increase as usize
+    }
+}
+
+/// The header of an EOF container.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EofHeader {
+    pub types_size: usize,
+    pub code_sizes: Vec<usize>,
+    pub container_sizes: Vec<usize>,
+    /// The data section size, which can be larger than the data section of a
+    /// subcontainer deployed by `RETURNCONTRACT`.
+    pub data_size: usize,
+    /// The size of the header itself.
+    pub header_size: usize,
+}
+
+impl EofHeader {
+    /// Decodes the header at the start of the code.
+    pub fn decode(code: &[u8]) -> Result<Self, EofError> {
+        let mut reader = HeaderReader { code, position: 0 };
+        if reader.read(2)? != EOF_MAGIC {
+            return Err(EofError::InvalidMagic);
+        }
+        if reader.read_u8()? != EOF_VERSION {
+            return Err(EofError::InvalidVersion);
+        }
+
+        reader.expect_kind(KIND_TYPES)?;
+        let types_size = reader.read_u16()?;
+
+        reader.expect_kind(KIND_CODE)?;
+        let code_sizes = reader.read_sizes(MAX_CODE_SECTIONS, 2)?;
+        if code_sizes.is_empty() {
+            return Err(EofError::InvalidCodeSectionCount);
+        }
+        if types_size != code_sizes.len() * TYPE_SIZE {
+            return Err(EofError::InvalidTypesSize);
+        }
+
+        let mut container_sizes = Vec::new();
+        if reader.peek_u8()? == KIND_CONTAINER {
+            reader.expect_kind(KIND_CONTAINER)?;
+            container_sizes = reader.read_sizes(MAX_CONTAINERS, 4)?;
+            if container_sizes.is_empty() {
+                return Err(EofError::InvalidContainerCount);
+            }
+        }
+
+        reader.expect_kind(KIND_DATA)?;
+        let data_size = reader.read_u16()?;
+        reader.expect_kind(TERMINATOR)?;
+
+        Ok(EofHeader {
+            types_size,
+            code_sizes,
+            container_sizes,
+            data_size,
+            header_size: reader.position,
+        })
+    }
+
+    /// The size of the container with the full declared data section.
+    pub fn container_size(&self) -> usize {
+        self.header_size
+            + self.types_size
+            + self.code_sizes.iter().sum::<usize>()
+            + self.container_sizes.iter().sum::<usize>()
+            + self.data_size
+    }
+
+    /// The position of the data section in the container.
+    fn data_offset(&self) -> usize { self.container_size() - self.data_size }
+}
+
+struct HeaderReader<'a> {
+    code: &'a [u8],
+    position: usize,
+}
+
+impl<'a> HeaderReader<'a> {
+    fn read(&mut self, size: usize) -> Result<&'a [u8], EofError> {
+        let end = self.position + size;
+        let bytes = self
+            .code
+            .get(self.position..end)
+            .ok_or(EofError::InvalidHeader)?;
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn peek_u8(&self) -> Result<u8, EofError> {
+        self.code
+            .get(self.position)
+            .copied()
+            .ok_or(EofError::InvalidHeader)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, EofError> { Ok(self.read(1)?[0]) }
+
+    fn read_u16(&mut self) -> Result<usize, EofError> {
+        let bytes = self.read(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]) as usize)
+    }
+
+    fn expect_kind(&mut self, kind: u8) -> Result<(), EofError> {
+        match self.read_u8()? == kind {
+            true => Ok(()),
+            false => Err(EofError::InvalidHeader),
+        }
+    }
+
+    /// Reads a section count and the `width` bytes size of each section.
+    fn read_sizes(
+        &mut self, max_count: usize, width: usize,
+    ) -> Result<Vec<usize>, EofError> {
+        let count = self.read_u16()?;
+        if count > max_count {
+            return Err(match width {
+                2 => EofError::InvalidCodeSectionCount,
+                _ => EofError::InvalidContainerCount,
+            });
+        }
+        let mut sizes = Vec::with_capacity(count);
+        for _ in 0..count {
+            let size = self
+                .read(width)?
+                .iter()
+                .fold(0usize, |size, byte| size << 8 | *byte as usize);
+            if size == 0 {
+                return Err(EofError::EmptySection);
+            }
+            sizes.push(size);
+        }
+        Ok(sizes)
+    }
+}
+
+/// A decoded EOF container. Its sections are only checked against the header
+/// by `decode`, the code is checked by `validate_container`.
+#[derive(Clone, Debug)]
+pub struct EofContainer {
+    pub header: EofHeader,
+    pub types: Vec<TypeSection>,
+    pub code_sections: Vec<Arc<Bytes>>,
+    pub containers: Vec<Bytes>,
+    pub data: Bytes,
+}
+
+impl EofContainer {
+    /// Decodes a container which spans the whole code. If
+    /// `allow_truncated_data` is set, the data section can be shorter than
+    /// declared, as for the subcontainers deployed by `RETURNCONTRACT`.
+    pub fn decode(
+        code: &[u8], allow_truncated_data: bool,
+    ) -> Result<Self, EofError> {
+        let header = EofHeader::decode(code)?;
+        let data_offset = header.data_offset();
+        if code.len() < data_offset || code.len() > header.container_size() {
+            return Err(EofError::InvalidContainerSize);
+        }
+        if code.len() < header.container_size() && !allow_truncated_data {
+            return Err(EofError::TruncatedData);
+        }
+
+        let mut position = header.header_size;
+        let types = code[position..position + header.types_size]
+            .chunks(TYPE_SIZE)
+            .map(|entry| TypeSection {
+                inputs: entry[0],
+                outputs: entry[1],
+                max_stack_increase: u16::from_be_bytes([entry[2], entry[3]]),
+            })
+            .collect();
+        position += header.types_size;
+
+        let mut code_sections = Vec::with_capacity(header.code_sizes.len());
+        for size in &header.code_sizes {
+            code_sections
+                .push(Arc::new(code[position..position + size].to_vec()));
+            position += size;
+        }
+
+        let mut containers = Vec::with_capacity(header.container_sizes.len());
+        for size in &header.container_sizes {
+            containers.push(code[position..position + size].to_vec());
+            position += size;
+        }
+
+        Ok(EofContainer {
+            types,
+            code_sections,
+            containers,
+            data: code[data_offset..].to_vec(),
+            header,
+        })
+    }
+
+    /// Returns the container deployed by `RETURNCONTRACT` with a subcontainer
+    /// and the auxiliary data appended to its data section. Returns `None` if
+    /// the data section would still be truncated, or too large.
+    pub fn deploy_container(
+        subcontainer: &[u8], aux_data: &[u8],
+    ) -> Option<Bytes> {
+        let header = EofHeader::decode(subcontainer).ok()?;
+        let data_size =
+            subcontainer.len() - header.data_offset() + aux_data.len();
+        if data_size < header.data_size || data_size > u16::MAX as usize {
+            return None;
+        }
+
+        let mut container = subcontainer.to_vec();
+        container.extend_from_slice(aux_data);
+        // The data size is followed by the terminator in the header.
+        let size_offset = header.header_size - 3;
+        container[size_offset..size_offset + 2]
+            .copy_from_slice(&(data_size as u16).to_be_bytes());
+        Some(container)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a container with the given `(inputs, outputs,
+    /// max_stack_increase)` types, code sections, subcontainers and data.
+    /// The declared data size can be larger than the data.
+    fn container(
+        types: &[(u8, u8, u16)], codes: &[&[u8]], containers: &[&[u8]],
+        data: &[u8], data_size: u16,
+    ) -> Vec<u8> {
+        let mut code = vec![0xef, 0x00, 0x01, 0x01];
+        code.extend_from_slice(&(types.len() as u16 * 4).to_be_bytes());
+        code.push(0x02);
+        code.extend_from_slice(&(codes.len() as u16).to_be_bytes());
+        for section in codes {
+            code.extend_from_slice(&(section.len() as u16).to_be_bytes());
+        }
+        if !containers.is_empty() {
+            code.push(0x03);
+            code.extend_from_slice(&(containers.len() as u16).to_be_bytes());
+            for section in containers {
+                code.extend_from_slice(&(section.len() as u32).to_be_bytes());
+            }
+        }
+        code.push(0xff);
+        code.extend_from_slice(&data_size.to_be_bytes());
+        code.push(0x00);
+        for (inputs, outputs, max_stack_increase) in types {
+            code.push(*inputs);
+            code.push(*outputs);
+            code.extend_from_slice(&max_stack_increase.to_be_bytes());
+        }
+        for section in codes.iter().chain(containers.iter()) {
+            code.extend_from_slice(section);
+        }
+        code.extend_from_slice(data);
+        code
+    }
+
+    fn single(code: &[u8], max_stack_increase: u16) -> Vec<u8> {
+        container(&[(0, 0x80, max_stack_increase)], &[code], &[], &[], 0)
+    }
+
+    fn validate(code: &[u8], kind: ContainerKind) -> Result<(), EofError> {
+        validate_container(code, kind).map(|_| ())
+    }
+
+    #[test]
+    fn test_decode_header() {
+        let code = container(&[(0, 0x80, 0)], &[&[0x00]], &[], &[1, 2], 2);
+        let header = EofHeader::decode(&code).unwrap();
+        assert_eq!(header.code_sizes, vec![1]);
+        assert_eq!(header.data_size, 2);
+        assert_eq!(header.header_size, 15);
+        assert_eq!(header.container_size(), code.len());
+
+        let decoded = EofContainer::decode(&code, false).unwrap();
+        assert_eq!(decoded.code_sections[0][..], [0x00]);
+        assert_eq!(decoded.data, vec![1, 2]);
+
+        let mut wrong_version = code.clone();
+        wrong_version[2] = 0x02;
+        assert_eq!(
+            EofHeader::decode(&wrong_version),
+            Err(EofError::InvalidVersion)
+        );
+        assert_eq!(
+            EofHeader::decode(&[0xef, 0x01]),
+            Err(EofError::InvalidMagic)
+        );
+        assert_eq!(
+            EofHeader::decode(&code[..10]),
+            Err(EofError::InvalidHeader)
+        );
+        assert_eq!(
+            EofContainer::decode(&code[..code.len() - 1], false).err(),
+            Some(EofError::TruncatedData)
+        );
+        assert!(EofContainer::decode(&code[..code.len() - 1], true).is_ok());
+    }
+
+    #[test]
+    fn test_validate_instructions() {
+        let kind = ContainerKind::Runtime;
+        assert_eq!(validate(&single(&[0x00], 0), kind), Ok(()));
+        assert_eq!(
+            validate(&single(&[0x00], 0), ContainerKind::Initcode),
+            Err(EofError::IncompatibleContainerKind)
+        );
+        // JUMP is deprecated.
+        assert_eq!(
+            validate(&single(&[0x5f, 0x56], 1), kind),
+            Err(EofError::UndefinedInstruction(0x56))
+        );
+        assert_eq!(
+            validate(&single(&[0x60], 0), kind),
+            Err(EofError::TruncatedImmediate)
+        );
+        // RJUMP into the immediate of PUSH1.
+        assert_eq!(
+            validate(&single(&[0xe0, 0x00, 0x01, 0x60, 0x00, 0x00], 0), kind),
+            Err(EofError::InvalidJumpDestination)
+        );
+        // RJUMPI over INVALID.
+        assert_eq!(
+            validate(&single(&[0x5f, 0xe1, 0x00, 0x01, 0xfe, 0x00], 1), kind),
+            Ok(())
+        );
+        assert_eq!(
+            validate(&single(&[0x5f, 0x00, 0x00], 1), kind),
+            Err(EofError::UnreachableCode)
+        );
+        assert_eq!(
+            validate(&single(&[0x5f], 1), kind),
+            Err(EofError::NoTerminatingInstruction)
+        );
+    }
+
+    #[test]
+    fn test_validate_stack() {
+        let kind = ContainerKind::Runtime;
+        assert_eq!(
+            validate(&single(&[0x01, 0x00], 0), kind),
+            Err(EofError::StackUnderflow)
+        );
+        assert_eq!(
+            validate(&single(&[0x5f, 0x50, 0x00], 0), kind),
+            Err(EofError::InvalidMaxStackIncrease)
+        );
+        assert_eq!(validate(&single(&[0x5f, 0x50, 0x00], 1), kind), Ok(()));
+        // An infinite loop which pushes is not allowed.
+        assert_eq!(
+            validate(&single(&[0x5f, 0xe0, 0xff, 0xfc], 1), kind),
+            Err(EofError::InvalidStackHeight)
+        );
+        assert_eq!(validate(&single(&[0xe0, 0xff, 0xfd], 0), kind), Ok(()));
+        // DUPN 1 requires two stack items.
+        assert_eq!(
+            validate(&single(&[0x5f, 0xe6, 0x01, 0x00], 2), kind),
+            Err(EofError::StackUnderflow)
+        );
+    }
+
+    #[test]
+    fn test_validate_code_sections() {
+        let kind = ContainerKind::Runtime;
+        let callf: &[u8] = &[0xe3, 0x00, 0x01, 0x00];
+        let retf: &[u8] = &[0x5f, 0xe4];
+        let code =
+            container(&[(0, 0x80, 1), (0, 1, 1)], &[callf, retf], &[], &[], 0);
+        assert_eq!(validate(&code, kind), Ok(()));
+
+        let code = container(
+            &[(0, 0x80, 0), (0, 1, 1)],
+            &[&[0x00], retf],
+            &[],
+            &[],
+            0,
+        );
+        assert_eq!(
+            validate(&code, kind),
+            Err(EofError::UnreachableCodeSection)
+        );
+
+        let code = container(
+            &[(0, 0x80, 1), (0, 0x80, 1)],
+            &[callf, &[0x5f, 0x00]],
+            &[],
+            &[],
+            0,
+        );
+        assert_eq!(validate(&code, kind), Err(EofError::CallToNonReturning));
+
+        let code =
+            container(&[(0, 0x80, 1), (0, 0x80, 1)], &[callf], &[], &[], 0);
+        assert!(validate(&code, kind).is_err());
+
+        let code = container(
+            &[(0, 0x80, 1)],
+            &[&[0xd1, 0x00, 0x00, 0x00]],
+            &[],
+            &[0; 32],
+            32,
+        );
+        assert_eq!(validate(&code, kind), Ok(()));
+        let code = container(
+            &[(0, 0x80, 1)],
+            &[&[0xd1, 0x00, 0x01, 0x00]],
+            &[],
+            &[0; 32],
+            32,
+        );
+        assert_eq!(validate(&code, kind), Err(EofError::InvalidDataLoadOffset));
+    }
+
+    #[test]
+    fn test_validate_subcontainers() {
+        let runtime = container(&[(0, 0x80, 0)], &[&[0x00]], &[], &[], 4);
+        let returncontract: &[u8] = &[0x5f, 0x5f, 0xee, 0x00];
+        let initcode =
+            container(&[(0, 0x80, 2)], &[returncontract], &[&runtime], &[], 0);
+        assert_eq!(validate(&initcode, ContainerKind::Initcode), Ok(()));
+        assert_eq!(
+            validate(&initcode, ContainerKind::Runtime),
+            Err(EofError::IncompatibleContainerKind)
+        );
+
+        let eofcreate: &[u8] =
+            &[0x5f, 0x5f, 0x5f, 0x5f, 0xec, 0x00, 0x50, 0x00];
+        let factory =
+            container(&[(0, 0x80, 4)], &[eofcreate], &[&initcode], &[], 0);
+        assert_eq!(validate(&factory, ContainerKind::Runtime), Ok(()));
+
+        // The runtime container has a truncated data section, so it can not
+        // be created.
+        let code =
+            container(&[(0, 0x80, 4)], &[eofcreate], &[&runtime], &[], 0);
+        assert_eq!(
+            validate(&code, ContainerKind::Runtime),
+            Err(EofError::TruncatedData)
+        );
+
+        let revert: &[u8] = &[0x5f, 0x5f, 0xfd];
+        let code = container(&[(0, 0x80, 2)], &[revert], &[&runtime], &[], 0);
+        assert_eq!(
+            validate(&code, ContainerKind::Runtime),
+            Err(EofError::UnreferencedSubcontainer)
+        );
+    }
+
+    #[test]
+    fn test_deploy_container() {
+        let runtime = container(&[(0, 0x80, 0)], &[&[0x00]], &[], &[1], 3);
+        assert_eq!(EofContainer::deploy_container(&runtime, &[2]), None);
+
+        let deployed =
+            EofContainer::deploy_container(&runtime, &[2, 3, 4]).unwrap();
+        let decoded = EofContainer::decode(&deployed, false).unwrap();
+        assert_eq!(decoded.header.data_size, 4);
+        assert_eq!(decoded.data, vec![1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,391 @@
+//! Deploy time validation of EOF containers (EIP-3670, EIP-4200, EIP-4750,
+//! EIP-5450, EIP-6206, EIP-7480 and EIP-7620).
+
+use super::{
+    EofContainer, EofError, TypeSection, MAX_STACK_HEIGHT, NON_RETURNING,
+};
+use crate::instructions::{self, Instruction};
+use std::cmp;
+
+/// The kind of code held by a container.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ContainerKind {
+    /// The code of a deployed contract.
+    Runtime,
+    /// The code executed by a creation, which ends with `RETURNCONTRACT`.
+    Initcode,
+}
+
+/// The opcode of `INVALID`, which is not part of `Instruction`.
+const INVALID: u8 = 0xfe;
+
+/// Validates a container and all its subcontainers, and returns the decoded
+/// container.
+pub fn validate_container(
+    code: &[u8], kind: ContainerKind,
+) -> Result<EofContainer, EofError> {
+    let container = EofContainer::decode(code, false)?;
+    let mut subcontainers = validate_single(&container, kind)?;
+
+    while let Some((code, kind)) = subcontainers.pop() {
+        let container =
+            EofContainer::decode(&code, kind == ContainerKind::Runtime)?;
+        subcontainers.extend(validate_single(&container, kind)?);
+    }
+
+    Ok(container)
+}
+
+/// Validates the code of a container, and returns its subcontainers with the
+/// kind they are referenced with.
+fn validate_single(
+    container: &EofContainer, kind: ContainerKind,
+) -> Result<Vec<(Vec<u8>, ContainerKind)>, EofError> {
+    let first = &container.types[0];
+    if first.inputs != 0 || first.outputs != NON_RETURNING {
+        return Err(EofError::InvalidFirstSectionType);
+    }
+    for types in &container.types {
+        if types.inputs > 0x7f
+            || (types.outputs > 0x7f && types.outputs != NON_RETURNING)
+            || types.max_stack_height() > MAX_STACK_HEIGHT
+        {
+            return Err(EofError::InvalidTypeSection);
+        }
+    }
+
+    let mut container_kinds = vec![None; container.containers.len()];
+    let mut section_reachable = vec![false; container.code_sections.len()];
+    section_reachable[0] = true;
+    let mut worklist = vec![0];
+
+    let mut callees = Vec::with_capacity(container.code_sections.len());
+    for index in 0..container.code_sections.len() {
+        callees.push(validate_code(
+            container,
+            index,
+            kind,
+            &mut container_kinds,
+        )?);
+    }
+
+    while let Some(index) = worklist.pop() {
+        for &callee in &callees[index] {
+            if !section_reachable[callee] {
+                section_reachable[callee] = true;
+                worklist.push(callee);
+            }
+        }
+    }
+    if section_reachable.contains(&false) {
+        return Err(EofError::UnreachableCodeSection);
+    }
+
+    container_kinds
+        .into_iter()
+        .zip(container.containers.iter())
+        .map(|(kind, code)| match kind {
+            Some(kind) => Ok((code.clone(), kind)),
+            None => Err(EofError::UnreferencedSubcontainer),
+        })
+        .collect()
+}
+
+fn read_u16(code: &[u8], position: usize) -> u16 {
+    u16::from_be_bytes([code[position], code[position + 1]])
+}
+
+fn read_i16(code: &[u8], position: usize) -> isize {
+    i16::from_be_bytes([code[position], code[position + 1]]) as isize
+}
+
+/// Returns the positions an `RJUMP`, `RJUMPI` or `RJUMPV` at `position` can
+/// jump to, or `None` if one is out of the code.
+fn relative_jump_targets(
+    code: &[u8], position: usize, instruction: Instruction,
+) -> Option<Vec<usize>> {
+    let offsets: Vec<usize> = match instruction {
+        instructions::RJUMPV => (0..code[position + 1] as usize + 1)
+            .map(|i| 2 + 2 * i)
+            .collect(),
+        _ => vec![1],
+    };
+    let immediate_size = instruction
+        .eof_immediate_size(&code[position + 1..])
+        .expect("immediate is checked");
+    let next = (position + 1 + immediate_size) as isize;
+    offsets
+        .into_iter()
+        .map(|offset| {
+            let target = next + read_i16(code, position + offset);
+            match target >= 0 && (target as usize) < code.len() {
+                true => Some(target as usize),
+                false => None,
+            }
+        })
+        .collect()
+}
+
+/// Validates a code section, records the kind of the subcontainers it
+/// references, and returns the code sections it calls or jumps to.
+fn validate_code(
+    container: &EofContainer, index: usize, kind: ContainerKind,
+    container_kinds: &mut [Option<ContainerKind>],
+) -> Result<Vec<usize>, EofError> {
+    let code = &container.code_sections[index][..];
+    let types = &container.types;
+    let mut is_instruction = vec![false; code.len()];
+    let mut jump_targets = Vec::new();
+    let mut callees = Vec::new();
+    let mut is_returning = false;
+
+    let mut position = 0;
+    while position < code.len() {
+        is_instruction[position] = true;
+        let opcode = code[position];
+        if opcode == INVALID {
+            position += 1;
+            continue;
+        }
+        let instruction = match Instruction::from_u8(opcode) {
+            Some(instruction) if !instruction.is_legacy_only() => instruction,
+            _ => return Err(EofError::UndefinedInstruction(opcode)),
+        };
+        let immediate_size = instruction
+            .eof_immediate_size(&code[position + 1..])
+            .ok_or(EofError::TruncatedImmediate)?;
+        if position + 1 + immediate_size > code.len() {
+            return Err(EofError::TruncatedImmediate);
+        }
+
+        match instruction {
+            instructions::RJUMP
+            | instructions::RJUMPI
+            | instructions::RJUMPV => {
+                jump_targets.extend(
+                    relative_jump_targets(code, position, instruction)
+                        .ok_or(EofError::InvalidJumpDestination)?,
+                );
+            }
+            instructions::CALLF | instructions::JUMPF => {
+                let callee = read_u16(code, position + 1) as usize;
+                let callee_types = types
+                    .get(callee)
+                    .ok_or(EofError::InvalidCodeSectionIndex)?;
+                if instruction == instructions::CALLF {
+                    if !callee_types.is_returning() {
+                        return Err(EofError::CallToNonReturning);
+                    }
+                } else if callee_types.is_returning() {
+                    is_returning = true;
+                }
+                callees.push(callee);
+            }
+            instructions::RETF => is_returning = true,
+            instructions::DATALOADN => {
+                let offset = read_u16(code, position + 1) as usize;
+                if offset + 32 > container.header.data_size {
+                    return Err(EofError::InvalidDataLoadOffset);
+                }
+            }
+            instructions::EOFCREATE | instructions::RETURNCONTRACT => {
+                let target = code[position + 1] as usize;
+                let target_kind = match instruction {
+                    instructions::EOFCREATE => ContainerKind::Initcode,
+                    _ => ContainerKind::Runtime,
+                };
+                if instruction == instructions::RETURNCONTRACT
+                    && kind == ContainerKind::Runtime
+                {
+                    return Err(EofError::IncompatibleContainerKind);
+                }
+                match container_kinds.get_mut(target) {
+                    None => return Err(EofError::InvalidContainerIndex),
+                    Some(Some(previous)) if *previous != target_kind => {
+                        return Err(EofError::AmbiguousContainerKind);
+                    }
+                    Some(entry) => *entry = Some(target_kind),
+                }
+            }
+            instructions::RETURN | instructions::STOP
+                if kind == ContainerKind::Initcode =>
+            {
+                return Err(EofError::IncompatibleContainerKind);
+            }
+            _ => {}
+        }
+
+        position += 1 + immediate_size;
+    }
+
+    if jump_targets
+        .into_iter()
+        .any(|target| !is_instruction[target])
+    {
+        return Err(EofError::InvalidJumpDestination);
+    }
+    if is_returning != types[index].is_returning() {
+        return Err(EofError::InvalidNonReturningFlag);
+    }
+
+    validate_stack(code, types, index)?;
+    Ok(callees)
+}
+
+/// Returns the number of stack items required and the stack height change of
+/// an instruction other than `RETF` and `JUMPF`.
+fn stack_effect(
+    code: &[u8], position: usize, instruction: Instruction,
+    types: &[TypeSection],
+) -> (usize, isize) {
+    match instruction {
+        instructions::DUPN => (code[position + 1] as usize + 1, 1),
+        instructions::SWAPN => (code[position + 1] as usize + 2, 0),
+        instructions::EXCHANGE => {
+            let immediate = code[position + 1] as usize;
+            ((immediate >> 4) + (immediate & 0x0f) + 3, 0)
+        }
+        instructions::CALLF => {
+            let callee = &types[read_u16(code, position + 1) as usize];
+            (
+                callee.inputs as usize,
+                callee.outputs as isize - callee.inputs as isize,
+            )
+        }
+        _ => {
+            let info = instruction.info::<true>();
+            (info.args, info.ret as isize - info.args as isize)
+        }
+    }
+}
+
+/// Validates the stack heights of a code section (EIP-5450). The stack height
+/// at each instruction is tracked as a range, which must not change at the
+/// targets of backward jumps.
+fn validate_stack(
+    code: &[u8], types: &[TypeSection], index: usize,
+) -> Result<(), EofError> {
+    let section_types = &types[index];
+    let mut heights: Vec<Option<(usize, usize)>> = vec![None; code.len()];
+    let inputs = section_types.inputs as usize;
+    heights[0] = Some((inputs, inputs));
+    let mut max_height = inputs;
+
+    let mut position = 0;
+    while position < code.len() {
+        let (min, max) = heights[position].ok_or(EofError::UnreachableCode)?;
+        max_height = cmp::max(max_height, max);
+
+        let instruction = match Instruction::from_u8(code[position]) {
+            Some(instruction) => instruction,
+            // `INVALID` terminates the execution.
+            None => {
+                position += 1;
+                continue;
+            }
+        };
+        let next = position
+            + 1
+            + instruction
+                .eof_immediate_size(&code[position + 1..])
+                .expect("immediate is checked");
+
+        match instruction {
+            instructions::RETF => {
+                let outputs = section_types.outputs as usize;
+                if min != max || max != outputs {
+                    return Err(EofError::InvalidStackHeight);
+                }
+                position = next;
+                continue;
+            }
+            instructions::JUMPF => {
+                let callee = &types[read_u16(code, position + 1) as usize];
+                if max + callee.max_stack_increase as usize
+                    > MAX_STACK_HEIGHT + 1
+                {
+                    return Err(EofError::StackOverflow);
+                }
+                if callee.is_returning() {
+                    if callee.outputs > section_types.outputs {
+                        return Err(EofError::InvalidNonReturningFlag);
+                    }
+                    let expected = section_types.outputs as usize
+                        + callee.inputs as usize
+                        - callee.outputs as usize;
+                    if min != max || max != expected {
+                        return Err(EofError::InvalidStackHeight);
+                    }
+                } else if min < callee.inputs as usize {
+                    return Err(EofError::StackUnderflow);
+                }
+                position = next;
+                continue;
+            }
+            instructions::CALLF => {
+                let callee = &types[read_u16(code, position + 1) as usize];
+                if max + callee.max_stack_increase as usize
+                    > MAX_STACK_HEIGHT + 1
+                {
+                    return Err(EofError::StackOverflow);
+                }
+            }
+            _ => {}
+        }
+
+        let (required, change) =
+            stack_effect(code, position, instruction, types);
+        if min < required {
+            return Err(EofError::StackUnderflow);
+        }
+        let height = (
+            (min as isize + change) as usize,
+            (max as isize + change) as usize,
+        );
+
+        let mut successors = Vec::new();
+        match instruction {
+            instructions::RJUMP => {}
+            instructions::STOP
+            | instructions::RETURN
+            | instructions::REVERT
+            | instructions::RETURNCONTRACT => {}
+            _ if next >= code.len() => {
+                return Err(EofError::NoTerminatingInstruction);
+            }
+            _ => successors.push(next),
+        }
+        if let instructions::RJUMP
+        | instructions::RJUMPI
+        | instructions::RJUMPV = instruction
+        {
+            successors.extend(
+                relative_jump_targets(code, position, instruction)
+                    .expect("jump targets are checked"),
+            );
+        }
+
+        for successor in successors {
+            if successor > position {
+                heights[successor] = Some(match heights[successor] {
+                    Some((min, max)) => {
+                        (cmp::min(min, height.0), cmp::max(max, height.1))
+                    }
+                    None => height,
+                });
+            } else if heights[successor] != Some(height) {
+                return Err(EofError::InvalidStackHeight);
+            }
+        }
+
+        position = next;
+    }
+
+    if max_height > MAX_STACK_HEIGHT {
+        return Err(EofError::StackOverflow);
+    }
+    if max_height != section_types.max_stack_height() {
+        return Err(EofError::InvalidMaxStackIncrease);
+    }
+    Ok(())
+}
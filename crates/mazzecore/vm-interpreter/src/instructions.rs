@@ -341,6 +341,38 @@ enum_with_from_u8! {
         #[doc = "Makes a log entry, 4 topics."]
         LOG4 = 0xa4,
 
+        #[doc = "load a word from the data section of EOF code"]
+        DATALOAD = 0xd0,
+        #[doc = "load a word at an immediate offset from the data section of EOF code"]
+        DATALOADN = 0xd1,
+        #[doc = "get the size of the data section of EOF code"]
+        DATASIZE = 0xd2,
+        #[doc = "copy the data section of EOF code to memory"]
+        DATACOPY = 0xd3,
+
+        #[doc = "relative jump to an immediate offset in EOF code"]
+        RJUMP = 0xe0,
+        #[doc = "conditional relative jump to an immediate offset in EOF code"]
+        RJUMPI = 0xe1,
+        #[doc = "relative jump through an immediate jump table in EOF code"]
+        RJUMPV = 0xe2,
+        #[doc = "call a code section of EOF code"]
+        CALLF = 0xe3,
+        #[doc = "return from a code section of EOF code"]
+        RETF = 0xe4,
+        #[doc = "jump to a code section of EOF code"]
+        JUMPF = 0xe5,
+        #[doc = "copies the stack item at an immediate depth in EOF code"]
+        DUPN = 0xe6,
+        #[doc = "swaps the highest value with the stack item at an immediate depth in EOF code"]
+        SWAPN = 0xe7,
+        #[doc = "swaps two stack items at immediate depths in EOF code"]
+        EXCHANGE = 0xe8,
+        #[doc = "create a new account with a subcontainer of EOF code"]
+        EOFCREATE = 0xec,
+        #[doc = "halt the initcode of EOF code and deploy a subcontainer"]
+        RETURNCONTRACT = 0xee,
+
         #[doc = "create a new account with associated code"]
         CREATE = 0xf0,
         #[doc = "message-call into an account"]
@@ -353,8 +385,16 @@ enum_with_from_u8! {
         DELEGATECALL = 0xf4,
         #[doc = "create a new account and set creation address to sha3(sender + sha3(init code)) % 2**160"]
         CREATE2 = 0xf5,
+        #[doc = "load a word from the return data buffer in EOF code"]
+        RETURNDATALOAD = 0xf7,
+        #[doc = "message-call into an account from EOF code"]
+        EXTCALL = 0xf8,
+        #[doc = "like EXTCALL but keeps caller's value and sender, and only calls EOF code"]
+        EXTDELEGATECALL = 0xf9,
         #[doc = "like CALL but it does not take value, nor modify the state"]
         STATICCALL = 0xfa,
+        #[doc = "like EXTCALL but it does not take value, nor modify the state"]
+        EXTSTATICCALL = 0xfb,
         #[doc = "stop execution and revert state changes. Return output data."]
         REVERT = 0xfd,
 
@@ -377,9 +417,72 @@ impl Instruction {
         if instruction == Some(BASEFEE) && !spec.cip1559 {
             instruction = None;
         }
+        if instruction.map_or(false, |i| i.is_eof_only()) {
+            instruction = None;
+        }
         return instruction;
     }
 
+    /// Returns true if the instruction is only defined in EOF code.
+    pub fn is_eof_only(&self) -> bool {
+        matches!(
+            *self,
+            DATALOAD
+                | DATALOADN
+                | DATASIZE
+                | DATACOPY
+                | RJUMP
+                | RJUMPI
+                | RJUMPV
+                | CALLF
+                | RETF
+                | JUMPF
+                | DUPN
+                | SWAPN
+                | EXCHANGE
+                | EOFCREATE
+                | RETURNCONTRACT
+                | RETURNDATALOAD
+                | EXTCALL
+                | EXTDELEGATECALL
+                | EXTSTATICCALL
+        )
+    }
+
+    /// Returns true if the instruction is deprecated in EOF code (EIP-3670).
+    pub fn is_legacy_only(&self) -> bool {
+        matches!(
+            *self,
+            CALL | CALLCODE
+                | DELEGATECALL
+                | STATICCALL
+                | SUICIDE
+                | JUMP
+                | JUMPI
+                | PC
+                | CREATE
+                | CREATE2
+                | CODESIZE
+                | CODECOPY
+                | EXTCODESIZE
+                | EXTCODECOPY
+                | EXTCODEHASH
+                | GAS
+        )
+    }
+
+    /// Returns the number of immediate bytes of the instruction in EOF code,
+    /// given the code following the opcode. Returns `None` for `RJUMPV` if
+    /// its jump table size is missing.
+    pub fn eof_immediate_size(&self, following: &[u8]) -> Option<usize> {
+        Some(match *self {
+            RJUMP | RJUMPI | CALLF | JUMPF | DATALOADN => 2,
+            DUPN | SWAPN | EXCHANGE | EOFCREATE | RETURNCONTRACT => 1,
+            RJUMPV => 1 + (*following.first()? as usize + 1) * 2,
+            _ => self.push_bytes().unwrap_or(0),
+        })
+    }
+
     pub fn u8(self) -> u8 { self as u8 }
 
     /// Returns number of bytes to read for `PUSHN` instruction
@@ -647,6 +750,25 @@ lazy_static! {
         arr[SUICIDE as usize] = Some(InstructionInfo::new("SUICIDE", 1, 0, GasPriceTier::Special));
         arr[CREATE2 as usize] = Some(InstructionInfo::new("CREATE2", 4, 1, GasPriceTier::Special));
         arr[REVERT as usize] = Some(InstructionInfo::new("REVERT", 2, 0, GasPriceTier::Zero));
+        arr[DATALOAD as usize] = Some(InstructionInfo::new("DATALOAD", 1, 1, GasPriceTier::Special));
+        arr[DATALOADN as usize] = Some(InstructionInfo::new("DATALOADN", 0, 1, GasPriceTier::VeryLow));
+        arr[DATASIZE as usize] = Some(InstructionInfo::new("DATASIZE", 0, 1, GasPriceTier::Base));
+        arr[DATACOPY as usize] = Some(InstructionInfo::new("DATACOPY", 3, 0, GasPriceTier::VeryLow));
+        arr[RJUMP as usize] = Some(InstructionInfo::new("RJUMP", 0, 0, GasPriceTier::Base));
+        arr[RJUMPI as usize] = Some(InstructionInfo::new("RJUMPI", 1, 0, GasPriceTier::Special));
+        arr[RJUMPV as usize] = Some(InstructionInfo::new("RJUMPV", 1, 0, GasPriceTier::Special));
+        arr[CALLF as usize] = Some(InstructionInfo::new("CALLF", 0, 0, GasPriceTier::Low));
+        arr[RETF as usize] = Some(InstructionInfo::new("RETF", 0, 0, GasPriceTier::VeryLow));
+        arr[JUMPF as usize] = Some(InstructionInfo::new("JUMPF", 0, 0, GasPriceTier::Low));
+        arr[DUPN as usize] = Some(InstructionInfo::new("DUPN", 0, 1, GasPriceTier::VeryLow));
+        arr[SWAPN as usize] = Some(InstructionInfo::new("SWAPN", 0, 0, GasPriceTier::VeryLow));
+        arr[EXCHANGE as usize] = Some(InstructionInfo::new("EXCHANGE", 0, 0, GasPriceTier::VeryLow));
+        arr[EOFCREATE as usize] = Some(InstructionInfo::new("EOFCREATE", 4, 1, GasPriceTier::Special));
+        arr[RETURNCONTRACT as usize] = Some(InstructionInfo::new("RETURNCONTRACT", 2, 0, GasPriceTier::Zero));
+        arr[RETURNDATALOAD as usize] = Some(InstructionInfo::new("RETURNDATALOAD", 1, 1, GasPriceTier::VeryLow));
+        arr[EXTCALL as usize] = Some(InstructionInfo::new("EXTCALL", 4, 1, GasPriceTier::Special));
+        arr[EXTDELEGATECALL as usize] = Some(InstructionInfo::new("EXTDELEGATECALL", 3, 1, GasPriceTier::Special));
+        arr[EXTSTATICCALL as usize] = Some(InstructionInfo::new("EXTSTATICCALL", 3, 1, GasPriceTier::Special));
         arr
     };
 
@@ -700,4 +822,23 @@ mod tests {
         assert_eq!(LOG2.log_topics(), Some(2));
         assert_eq!(LOG4.log_topics(), Some(4));
     }
+
+    #[test]
+    fn test_eof_only_instructions() {
+        let spec = Spec::new_spec_for_test();
+        assert_eq!(Instruction::from_u8(0xe0), Some(RJUMP));
+        assert_eq!(Instruction::from_u8_versioned(0xe0, &spec), None);
+        assert_eq!(Instruction::from_u8_versioned(0xf8, &spec), None);
+        assert_eq!(Instruction::from_u8_versioned(0x01, &spec), Some(ADD));
+    }
+
+    #[test]
+    fn test_get_eof_immediate_size() {
+        assert_eq!(RJUMP.eof_immediate_size(&[]), Some(2));
+        assert_eq!(DUPN.eof_immediate_size(&[]), Some(1));
+        assert_eq!(PUSH3.eof_immediate_size(&[]), Some(3));
+        assert_eq!(RJUMPV.eof_immediate_size(&[2]), Some(7));
+        assert_eq!(RJUMPV.eof_immediate_size(&[]), None);
+        assert_eq!(ADD.eof_immediate_size(&[]), Some(0));
+    }
 }
@@ -38,11 +38,18 @@ macro_rules! overflowing {
     }};
 }
 
+/// The gas an `EXTCALL` like instruction retains at least (EIP-7069).
+const MIN_RETAINED_GAS: usize = 5000;
+/// The gas below which the callee of an `EXTCALL` like instruction is not
+/// called (EIP-7069).
+const MIN_CALLEE_GAS: usize = 2300;
+
 enum Request<Cost: CostType> {
     Gas(Cost),
     GasMem(Cost, Cost),
     GasMemProvide(Cost, Cost, Option<U256>),
     GasMemCopy(Cost, Cost, Cost),
+    GasMemEofCall(Cost, Cost),
 }
 
 pub struct InstructionRequirements<Cost> {
@@ -108,13 +115,30 @@ impl<Gas: CostType> Gasometer<Gas> {
         }
     }
 
+    /// How much gas is provided to an `EXTCALL` like instruction, given that
+    /// we need to deduct `needed` for this operation. Returns zero if the
+    /// callee would get too little gas to be called.
+    pub fn eof_call_gas_provided(&self, needed: Gas) -> Gas {
+        if self.current_gas < needed {
+            return Gas::from(0);
+        }
+        let gas_remaining = self.current_gas - needed;
+        let retained =
+            cmp::max(gas_remaining >> 6, Gas::from(MIN_RETAINED_GAS));
+        if gas_remaining < retained + Gas::from(MIN_CALLEE_GAS) {
+            Gas::from(0)
+        } else {
+            gas_remaining - retained
+        }
+    }
+
     /// Determine how much gas is used by the given instruction, given the
     /// machine's state.
     ///
     /// We guarantee that the final element of the returned tuple (`provided`)
-    /// will be `Some` iff the `instruction` is one of `CREATE`, or any of
-    /// the `CALL` variants. In this case, it will be the amount of gas
-    /// that the current context
+    /// will be `Some` iff the `instruction` is one of `CREATE`, `EOFCREATE`,
+    /// or any of the `CALL` variants. In this case, it will be the amount of
+    /// gas that the current context
     /// provides to the child context.
     pub fn requirements(
         &mut self, context: &dyn vm::Context, instruction: Instruction,
@@ -218,6 +242,18 @@ impl<Gas: CostType> Gasometer<Gas> {
             instructions::RETURNSUB_TSTORE if spec.cancun_opcodes => {
                 Request::Gas(Gas::from(spec.tstore_gas))
             }
+            instructions::RJUMPI
+            | instructions::RJUMPV
+            | instructions::DATALOAD => Request::Gas(Gas::from(4)),
+            instructions::DATACOPY => Request::GasMemCopy(
+                default_gas,
+                mem_needed(stack.peek(0), stack.peek(2))?,
+                Gas::from_u256(*stack.peek(2))?,
+            ),
+            instructions::RETURNCONTRACT => Request::GasMem(
+                default_gas,
+                mem_needed(stack.peek(0), stack.peek(1))?,
+            ),
             instructions::EXTCODECOPY => Request::GasMemCopy(
                 spec.extcodecopy_base_gas.into(),
                 mem_needed(stack.peek(1), stack.peek(3))?,
@@ -303,6 +339,38 @@ impl<Gas: CostType> Gasometer<Gas> {
 
                 Request::GasMemProvide(gas, mem, None)
             }
+            instructions::EOFCREATE => Request::GasMemProvide(
+                Gas::from(spec.create_gas),
+                mem_needed(stack.peek(2), stack.peek(3))?,
+                None,
+            ),
+            instructions::EXTCALL
+            | instructions::EXTDELEGATECALL
+            | instructions::EXTSTATICCALL => {
+                let mut gas = Gas::from(spec.call_gas);
+                let mem = mem_needed(stack.peek(1), stack.peek(2))?;
+
+                if instruction == instructions::EXTCALL
+                    && !stack.peek(3).is_zero()
+                {
+                    let address = u256_to_address(stack.peek(0));
+                    if !context.exists_and_not_null(&address)? {
+                        let ratio = if context.space() == Space::Ethereum {
+                            spec.evm_gas_ratio
+                        } else {
+                            1
+                        };
+                        gas = overflowing!(gas.overflow_add(
+                            (spec.call_new_account_gas * ratio).into()
+                        ));
+                    }
+                    gas =
+                        overflowing!(gas
+                            .overflow_add(spec.call_value_transfer_gas.into()));
+                }
+
+                Request::GasMemEofCall(gas, mem)
+            }
             instructions::EXP => {
                 let expon = stack.peek(1);
                 let bytes = ((expon.bits() + 7) / 8) as usize;
@@ -351,6 +419,20 @@ impl<Gas: CostType> Gasometer<Gas> {
                     memory_total_gas: new_mem_gas,
                 }
             }
+            Request::GasMemEofCall(gas, mem_size) => {
+                let (mem_gas_cost, new_mem_gas, new_mem_size) =
+                    self.mem_gas_cost(spec, current_mem_size, &mem_size)?;
+                let gas = overflowing!(gas.overflow_add(mem_gas_cost));
+                let provided = self.eof_call_gas_provided(gas);
+                let total_gas = overflowing!(gas.overflow_add(provided));
+
+                InstructionRequirements {
+                    gas_cost: total_gas,
+                    provide_gas: Some(provided),
+                    memory_required_size: new_mem_size,
+                    memory_total_gas: new_mem_gas,
+                }
+            }
             Request::GasMemCopy(gas, mem_size, copy) => {
                 let (mem_gas_cost, new_mem_gas, new_mem_size) =
                     self.mem_gas_cost(spec, current_mem_size, &mem_size)?;
@@ -31,9 +31,11 @@ pub use self::shared_cache::SharedCache;
 use self::{
     gasometer::Gasometer,
     memory::Memory,
+    shared_cache::BasicBlock,
     stack::{Stack, VecStack},
 };
 use super::{
+    eof::{self, ContainerKind, EofContainer, MAX_RETURN_STACK_DEPTH},
     evm::CostType,
     instructions::{self, Instruction, InstructionInfo},
 };
@@ -42,13 +44,15 @@ use mazze_bytes::Bytes;
 use mazze_types::{Address, BigEndianHash, Space, H256, U256, U512};
 use mazze_vm_types::{
     self as vm, ActionParams, ActionValue, CallType, ContractCreateResult,
-    CreateContractAddress, GasLeft, InstructionResult, InterpreterInfo,
-    MessageCallResult, ParamsType, ReturnData, Spec, TrapError, TrapKind,
+    CreateContractAddress, CreateType, GasLeft, InstructionResult,
+    InterpreterInfo, MessageCallResult, ParamsType, ReturnData, Spec,
+    TrapError, TrapKind,
 };
 use keccak_hash::keccak;
 use std::{cmp, convert::TryFrom, marker::PhantomData, mem, sync::Arc};
 
 const GASOMETER_PROOF: &str = "If gasometer is None, Err is immediately returned in step; this function is only called by step; qed";
+const EOF_PROOF: &str = "EOF instructions are only decoded in EOF code; qed";
 
 type ProgramCounter = usize;
 
@@ -166,12 +170,24 @@ pub struct Interpreter<Cost: CostType, const CANCUN: bool> {
     done: bool,
     valid_jump_destinations: Option<Arc<BitSet>>,
     valid_subroutine_destinations: Option<Arc<BitSet>>,
+    basic_blocks: Option<Arc<Vec<BasicBlock>>>,
     gasometer: Option<Gasometer<Cost>>,
     stack: VecStack<U256>,
     return_stack: Vec<usize>,
     resume_output_range: Option<(U256, U256)>,
     resume_result: Option<InstructionResult<Cost>>,
+    /// Whether the pending call is an `EXTCALL` like instruction, whose
+    /// status codes differ from the legacy calls.
+    resume_eof_call: bool,
     last_stack_ret_len: usize,
+    /// The container of EOF code, whose code sections are executed by the
+    /// reader.
+    eof: Option<Arc<EofContainer>>,
+    /// The EOF initcode of a creation is not a valid container.
+    invalid_eof_initcode: bool,
+    code_section: usize,
+    /// The code sections and positions to return to with `RETF`.
+    eof_return_stack: Vec<(usize, ProgramCounter)>,
     _type: PhantomData<Cost>,
 }
 
@@ -218,6 +234,8 @@ impl<Cost: 'static + CostType, const CANCUN: bool> vm::ResumeCall
         {
             let this = &mut *self;
             let (out_off, out_size) = this.resume_output_range.take().expect("Box<ResumeCall> is obtained from a call opcode; resume_output_range is always set after those opcodes are executed; qed");
+            let eof_call = mem::replace(&mut this.resume_eof_call, false);
+            this.stack.push(call_status(&result, eof_call));
 
             match result {
                 MessageCallResult::Success(gas_left, data) => {
@@ -226,7 +244,6 @@ impl<Cost: 'static + CostType, const CANCUN: bool> vm::ResumeCall
                     (&mut output[..len]).copy_from_slice(&data[..len]);
 
                     this.return_data = data;
-                    this.stack.push(U256::one());
                     this.resume_result = Some(InstructionResult::UnusedGas(
                         Cost::from_u256(gas_left).expect(
                             "Gas left cannot be greater than current one",
@@ -239,7 +256,6 @@ impl<Cost: 'static + CostType, const CANCUN: bool> vm::ResumeCall
                     (&mut output[..len]).copy_from_slice(&data[..len]);
 
                     this.return_data = data;
-                    this.stack.push(U256::zero());
                     this.resume_result = Some(InstructionResult::UnusedGas(
                         Cost::from_u256(gas_left).expect(
                             "Gas left cannot be greater than current one",
@@ -247,7 +263,6 @@ impl<Cost: 'static + CostType, const CANCUN: bool> vm::ResumeCall
                     ));
                 }
                 MessageCallResult::Failed(_) => {
-                    this.stack.push(U256::zero());
                     this.resume_result = Some(InstructionResult::Ok);
                 }
             }
@@ -293,9 +308,31 @@ impl<Cost: CostType, const CANCUN: bool> Interpreter<Cost, CANCUN> {
         mut params: ActionParams, cache: Arc<SharedCache<CANCUN>>, spec: &Spec,
         depth: usize,
     ) -> Interpreter<Cost, CANCUN> {
-        let reader = CodeReader::new(
-            params.code.take().expect("VM always called with code; qed"),
-        );
+        let code = params.code.take().expect("VM always called with code; qed");
+        // EOF code is only executed in the spaces with EOF activated. A legacy
+        // creation with EOF initcode fails on the `0xEF` opcode, as before.
+        let eof_kind = match params.create_type {
+            CreateType::None => Some(ContainerKind::Runtime),
+            CreateType::EOFCREATE => Some(ContainerKind::Initcode),
+            CreateType::CREATE | CreateType::CREATE2 => None,
+        };
+        let mut eof = None;
+        let mut invalid_eof_initcode = false;
+        if let Some(kind) = eof_kind.filter(|_| {
+            CANCUN && *spec.eof.in_space(params.space) && eof::is_eof(&code)
+        }) {
+            match cache.eof_container(&params.code_hash, &code, kind) {
+                Ok(container) => eof = Some(container),
+                Err(e) => {
+                    debug!("Invalid EOF container: {}", e);
+                    invalid_eof_initcode = kind == ContainerKind::Initcode;
+                }
+            }
+        }
+        let reader = CodeReader::new(match &eof {
+            Some(container) => container.code_sections[0].clone(),
+            None => code,
+        });
         let params = InterpreterParams::from(params);
         let informant = informant::EvmInformant::new(depth);
         let valid_jump_destinations = None;
@@ -313,6 +350,7 @@ impl<Cost: CostType, const CANCUN: bool> Interpreter<Cost, CANCUN> {
             informant,
             valid_jump_destinations,
             valid_subroutine_destinations,
+            basic_blocks: None,
             gasometer,
             stack,
             return_stack,
@@ -323,6 +361,11 @@ impl<Cost: CostType, const CANCUN: bool> Interpreter<Cost, CANCUN> {
             last_stack_ret_len: 0,
             resume_output_range: None,
             resume_result: None,
+            resume_eof_call: false,
+            eof,
+            invalid_eof_initcode,
+            code_section: 0,
+            eof_return_stack: Vec::new(),
             _type: PhantomData,
         }
     }
@@ -337,6 +380,8 @@ impl<Cost: CostType, const CANCUN: bool> Interpreter<Cost, CANCUN> {
 
         let result = if self.gasometer.is_none() {
             InterpreterResult::Done(Err(vm::Error::OutOfGas))
+        } else if self.invalid_eof_initcode {
+            InterpreterResult::Done(Err(vm::Error::InvalidEofInitCode))
         } else if self.reader.len() == 0 {
             InterpreterResult::Done(Ok(GasLeft::Known(
                 self.gasometer
@@ -368,7 +413,10 @@ impl<Cost: CostType, const CANCUN: bool> Interpreter<Cost, CANCUN> {
                     context.trace_step(self);
                 }
 
-                let op_result = self.exec_instruction(context);
+                let op_result = match self.exec_basic_block(context) {
+                    Some(op_result) => op_result,
+                    None => self.exec_instruction(context),
+                };
 
                 if self.do_trace {
                     context.trace_step_end(self);
@@ -560,8 +608,11 @@ impl<Cost: CostType, const CANCUN: bool> Interpreter<Cost, CANCUN> {
         &mut self, context: &mut dyn vm::Context,
     ) -> Result<InstructionResult<Cost>, InterpreterResult> {
         let opcode = self.reader.code[self.reader.position];
-        let instruction =
-            Instruction::from_u8_versioned(opcode, context.spec());
+        // EOF code is validated at deployment, so all its opcodes are defined.
+        let instruction = match self.eof {
+            Some(_) => Instruction::from_u8(opcode),
+            None => Instruction::from_u8_versioned(opcode, context.spec()),
+        };
         self.reader.position += 1;
 
         // TODO: make compile-time removable if too much of a
@@ -666,6 +717,87 @@ impl<Cost: CostType, const CANCUN: bool> Interpreter<Cost, CANCUN> {
         Ok(result)
     }
 
+    /// Executes the basic block starting at the current position, with its
+    /// gas and stack bounds checked once. Returns `None` if no block starts
+    /// here, or if a check fails within the block: the instructions are then
+    /// executed one by one so that the error is raised by the same
+    /// instruction. Tracing and EOF code always go through the per-instruction
+    /// path.
+    fn exec_basic_block(
+        &mut self, context: &mut dyn vm::Context,
+    ) -> Option<Result<InstructionResult<Cost>, InterpreterResult>> {
+        if self.do_trace || self.eof.is_some() {
+            return None;
+        }
+        if self.basic_blocks.is_none() {
+            self.basic_blocks = Some(
+                self.cache
+                    .basic_blocks(&self.params.code_hash, &self.reader.code),
+            );
+        }
+        let blocks = self
+            .basic_blocks
+            .as_ref()
+            .expect("basic_blocks are initialized above; qed");
+        let block = match blocks
+            .binary_search_by_key(&self.reader.position, |block| block.start)
+        {
+            Ok(index) => blocks[index],
+            Err(_) => return None,
+        };
+
+        let spec = context.spec();
+        if !self.stack.has(block.stack_required)
+            || self.stack.size() + block.stack_growth > spec.stack_limit
+        {
+            return None;
+        }
+        let gas_cost = Cost::from(block.gas(spec));
+        let gasometer = self.gasometer.as_mut().expect(GASOMETER_PROOF);
+        if gasometer.verify_gas(&gas_cost).is_err() {
+            return None;
+        }
+        gasometer.current_gas = gasometer.current_gas - gas_cost;
+
+        while self.reader.position < block.end {
+            let instruction =
+                Instruction::from_u8(self.reader.code[self.reader.position])
+                    .expect("basic blocks only have defined opcodes; qed");
+            self.reader.position += 1;
+            let info = instruction.info::<CANCUN>();
+            self.last_stack_ret_len = info.ret;
+
+            evm_debug!({
+                self.informant.before_instruction(
+                    self.reader.position,
+                    instruction,
+                    info,
+                    &self
+                        .gasometer
+                        .as_mut()
+                        .expect(GASOMETER_PROOF)
+                        .current_gas,
+                    &self.stack,
+                )
+            });
+
+            let current_gas =
+                self.gasometer.as_mut().expect(GASOMETER_PROOF).current_gas;
+            if let Err(e) = self.exec_instruction_inner(
+                current_gas,
+                context,
+                instruction,
+                None,
+            ) {
+                return Some(Err(InterpreterResult::Done(Err(e))));
+            }
+
+            evm_debug!({ self.informant.after_instruction(instruction) });
+        }
+
+        Some(Ok(InstructionResult::Ok))
+    }
+
     fn exec_instruction_inner(
         &mut self, gas: Cost, context: &mut dyn vm::Context,
         instruction: Instruction, provided: Option<Cost>,
@@ -686,6 +818,61 @@ impl<Cost: CostType, const CANCUN: bool> Interpreter<Cost, CANCUN> {
             instructions::JUMPDEST => {
                 // ignore
             }
+            instructions::RJUMP => {
+                let offset = self.reader.read(2);
+                self.relative_jump(offset);
+            }
+            instructions::RJUMPI => {
+                let offset = self.reader.read(2);
+                let condition = self.stack.pop_back();
+                if !condition.is_zero() {
+                    self.relative_jump(offset);
+                }
+            }
+            instructions::RJUMPV => {
+                let max_index = self.reader.read(1).low_u64() as usize;
+                let case = self.stack.pop_back();
+                let table = self.reader.position;
+                self.reader.position += (max_index + 1) * 2;
+                if case <= U256::from(max_index) {
+                    let entry = table + case.as_usize() * 2;
+                    let offset =
+                        U256::from(&self.reader.code[entry..entry + 2]);
+                    self.relative_jump(offset);
+                }
+            }
+            instructions::CALLF => {
+                let section = self.reader.read(2).low_u64() as usize;
+                if self.eof_return_stack.len() >= MAX_RETURN_STACK_DEPTH {
+                    return Err(vm::Error::OutOfSubStack {
+                        wanted: 1,
+                        limit: MAX_RETURN_STACK_DEPTH,
+                    });
+                }
+                self.check_section_stack(
+                    "CALLF",
+                    section,
+                    context.spec().stack_limit,
+                )?;
+                self.eof_return_stack
+                    .push((self.code_section, self.reader.position));
+                self.enter_code_section(section, 0);
+            }
+            instructions::RETF => {
+                let (section, position) = self.eof_return_stack.pop().expect(
+                    "RETF is only valid in returning sections, which are entered by CALLF; qed",
+                );
+                self.enter_code_section(section, position);
+            }
+            instructions::JUMPF => {
+                let section = self.reader.read(2).low_u64() as usize;
+                self.check_section_stack(
+                    "JUMPF",
+                    section,
+                    context.spec().stack_limit,
+                )?;
+                self.enter_code_section(section, 0);
+            }
             instructions::BEGINSUB_TLOAD => {
                 if !CANCUN {
                     // BEGINSUB
@@ -779,31 +966,167 @@ impl<Cost: CostType, const CANCUN: bool> Interpreter<Cost, CANCUN> {
                     contract_code,
                     address_scheme,
                 )?;
-                return match create_result {
-                    Ok(ContractCreateResult::Created(address, gas_left)) => {
-                        // Only reachable in Mocktest
-                        self.stack.push(address_to_u256(address));
-                        Ok(InstructionResult::UnusedGas(
-                            Cost::from_u256(gas_left)
-                                .expect("Gas left cannot be greater."),
-                        ))
-                    }
-                    Ok(ContractCreateResult::Reverted(
-                        gas_left,
-                        return_data,
-                    )) => {
-                        self.stack.push(U256::zero());
-                        self.return_data = return_data;
-                        Ok(InstructionResult::UnusedGas(
-                            Cost::from_u256(gas_left)
-                                .expect("Gas left cannot be greater."),
-                        ))
+                return Ok(self.create_result(create_result));
+            }
+            instructions::EOFCREATE => {
+                let index = self.reader.read(1).low_u64() as usize;
+                let endowment = self.stack.pop_back();
+                let salt: H256 =
+                    BigEndianHash::from_uint(&self.stack.pop_back());
+                let input_off = self.stack.pop_back();
+                let input_size = self.stack.pop_back();
+
+                let create_gas = provided.expect("`provided` comes through Self::exec from `Gasometer::get_gas_cost_mem`; `gas_gas_mem_cost` guarantees `Some` when instruction is `EOFCREATE`; qed");
+
+                if context.is_static_or_reentrancy() {
+                    return Err(vm::Error::MutableCallInStaticContext);
+                }
+
+                // clear return data buffer before creating new call frame.
+                self.return_data = ReturnData::empty();
+
+                let can_create = context.balance(&self.params.address)?
+                    >= endowment
+                    && context.depth() < context.spec().max_depth;
+                if !can_create {
+                    self.stack.push(U256::zero());
+                    return Ok(InstructionResult::UnusedGas(create_gas));
+                }
+
+                let container = self.eof.clone().expect(EOF_PROOF);
+                let create_result = context.eof_create(
+                    &create_gas.as_u256(),
+                    &endowment,
+                    &container.containers[index],
+                    self.mem.read_slice(input_off, input_size),
+                    salt,
+                )?;
+                return Ok(self.create_result(create_result));
+            }
+            instructions::RETURNCONTRACT => {
+                let index = self.reader.read(1).low_u64() as usize;
+                let aux_off = self.stack.pop_back();
+                let aux_size = self.stack.pop_back();
+
+                let container = self.eof.clone().expect(EOF_PROOF);
+                let deployed = EofContainer::deploy_container(
+                    &container.containers[index],
+                    self.mem.read_slice(aux_off, aux_size),
+                )
+                .ok_or(vm::Error::OutOfBounds)?;
+
+                // The deployed container is returned in place of the memory.
+                let init_size = U256::from(deployed.len());
+                self.mem = deployed;
+                return Ok(InstructionResult::StopExecutionNeedsReturn {
+                    gas,
+                    init_off: U256::zero(),
+                    init_size,
+                    apply: true,
+                });
+            }
+            instructions::EXTCALL
+            | instructions::EXTDELEGATECALL
+            | instructions::EXTSTATICCALL => {
+                let target = self.stack.pop_back();
+                if !(target >> 160).is_zero() {
+                    return Err(vm::Error::InvalidExtCallTarget);
+                }
+                let code_address = u256_to_address(&target);
+                let in_off = self.stack.pop_back();
+                let in_size = self.stack.pop_back();
+                let value = match instruction {
+                    instructions::EXTCALL => Some(self.stack.pop_back()),
+                    instructions::EXTSTATICCALL => Some(U256::zero()),
+                    _ => None,
+                };
+
+                let call_gas = provided.expect("`provided` comes through Self::exec from `Gasometer::requirements`; it is `Some` when instruction is one of `EXTCALL`/`EXTDELEGATECALL`/`EXTSTATICCALL`; qed");
+
+                let transfers_value = value.map_or(false, |v| !v.is_zero());
+                if context.is_static_or_reentrancy() && transfers_value {
+                    return Err(vm::Error::MutableCallInStaticContext);
+                }
+
+                let (sender_address, receive_address, call_type) =
+                    match instruction {
+                        instructions::EXTCALL => (
+                            &self.params.address,
+                            &code_address,
+                            CallType::Call,
+                        ),
+                        instructions::EXTDELEGATECALL => (
+                            &self.params.sender,
+                            &self.params.address,
+                            CallType::DelegateCall,
+                        ),
+                        _ => (
+                            &self.params.address,
+                            &code_address,
+                            CallType::StaticCall,
+                        ),
+                    };
+
+                // clear return data buffer before creating new call frame.
+                self.return_data = ReturnData::empty();
+
+                let has_balance = !transfers_value
+                    || context.balance(&self.params.address)?
+                        >= value.expect("value is transferred; qed");
+                // Only EOF code can be delegated to.
+                let delegates_to_legacy = instruction
+                    == instructions::EXTDELEGATECALL
+                    && !context
+                        .extcode(&code_address)?
+                        .map_or(false, |code| eof::is_eof(&code));
+
+                let can_call = call_gas > Cost::from(0)
+                    && has_balance
+                    && context.depth() < context.spec().max_depth
+                    && !delegates_to_legacy;
+                if !can_call {
+                    self.stack.push(U256::one());
+                    return Ok(InstructionResult::UnusedGas(call_gas));
+                }
+
+                let call_result = {
+                    let input = self.mem.read_slice(in_off, in_size);
+                    context.call(
+                        &call_gas.as_u256(),
+                        sender_address,
+                        receive_address,
+                        value,
+                        input,
+                        &code_address,
+                        call_type,
+                    )?
+                };
+
+                return match call_result {
+                    Ok(result) => {
+                        self.stack.push(call_status(&result, true));
+                        match result {
+                            MessageCallResult::Success(gas_left, data)
+                            | MessageCallResult::Reverted(gas_left, data) => {
+                                self.return_data = data;
+                                Ok(InstructionResult::UnusedGas(
+                                    Cost::from_u256(gas_left).expect(
+                                        "Gas left cannot be greater than current one",
+                                    ),
+                                ))
+                            }
+                            MessageCallResult::Failed(_) => {
+                                Ok(InstructionResult::Ok)
+                            }
+                        }
                     }
-                    Ok(ContractCreateResult::Failed(_)) => {
-                        self.stack.push(U256::zero());
-                        Ok(InstructionResult::Ok)
+                    Err(trap) => {
+                        // The output is not copied to the memory.
+                        self.resume_output_range =
+                            Some((U256::zero(), U256::zero()));
+                        self.resume_eof_call = true;
+                        Ok(InstructionResult::Trap(trap))
                     }
-                    Err(trap) => Ok(InstructionResult::Trap(trap)),
                 };
             }
             instructions::CALL
@@ -1172,6 +1495,32 @@ impl<Cost: CostType, const CANCUN: bool> Interpreter<Cost, CANCUN> {
                         .map_or_else(|| &[] as &[u8], |d| &*d as &[u8]),
                 );
             }
+            instructions::RETURNDATALOAD => {
+                let offset = self.stack.pop_back();
+                self.stack.push(load_word(&*self.return_data, offset));
+            }
+            instructions::DATALOAD => {
+                let offset = self.stack.pop_back();
+                let container = self.eof.as_ref().expect(EOF_PROOF);
+                self.stack.push(load_word(&container.data, offset));
+            }
+            instructions::DATALOADN => {
+                let offset = self.reader.read(2);
+                let container = self.eof.as_ref().expect(EOF_PROOF);
+                self.stack.push(load_word(&container.data, offset));
+            }
+            instructions::DATASIZE => {
+                let container = self.eof.as_ref().expect(EOF_PROOF);
+                self.stack.push(U256::from(container.data.len()));
+            }
+            instructions::DATACOPY => {
+                let container = self.eof.clone().expect(EOF_PROOF);
+                Self::copy_data_to_memory(
+                    &mut self.mem,
+                    &mut self.stack,
+                    &container.data,
+                );
+            }
             instructions::RETURNDATACOPY => {
                 {
                     let source_offset = self.stack.peek(1);
@@ -1284,6 +1633,21 @@ impl<Cost: CostType, const CANCUN: bool> Interpreter<Cost, CANCUN> {
                 );
                 self.stack.swap_with_top(position)
             }
+            instructions::DUPN => {
+                let position = self.reader.read(1).low_u64() as usize;
+                let val = self.stack.peek(position).clone();
+                self.stack.push(val);
+            }
+            instructions::SWAPN => {
+                let position = self.reader.read(1).low_u64() as usize;
+                self.stack.swap_with_top(position + 1);
+            }
+            instructions::EXCHANGE => {
+                let immediate = self.reader.read(1).low_u64() as usize;
+                let n = (immediate >> 4) + 1;
+                let m = (immediate & 0x0f) + 1;
+                self.stack.swap(n, n + m);
+            }
             instructions::POP => {
                 self.stack.pop_back();
             }
@@ -1645,6 +2009,66 @@ impl<Cost: CostType, const CANCUN: bool> Interpreter<Cost, CANCUN> {
         }
     }
 
+    fn create_result(
+        &mut self, result: Result<ContractCreateResult, TrapKind>,
+    ) -> InstructionResult<Cost> {
+        match result {
+            Ok(ContractCreateResult::Created(address, gas_left)) => {
+                // Only reachable in Mocktest
+                self.stack.push(address_to_u256(address));
+                InstructionResult::UnusedGas(
+                    Cost::from_u256(gas_left)
+                        .expect("Gas left cannot be greater."),
+                )
+            }
+            Ok(ContractCreateResult::Reverted(gas_left, return_data)) => {
+                self.stack.push(U256::zero());
+                self.return_data = return_data;
+                InstructionResult::UnusedGas(
+                    Cost::from_u256(gas_left)
+                        .expect("Gas left cannot be greater."),
+                )
+            }
+            Ok(ContractCreateResult::Failed(_)) => {
+                self.stack.push(U256::zero());
+                InstructionResult::Ok
+            }
+            Err(trap) => InstructionResult::Trap(trap),
+        }
+    }
+
+    /// Moves the reader by a signed 16-bit offset, relative to the end of
+    /// the current instruction (EIP-4200).
+    fn relative_jump(&mut self, offset: U256) {
+        let offset = offset.low_u32() as u16 as i16 as isize;
+        self.reader.position =
+            (self.reader.position as isize + offset) as usize;
+    }
+
+    /// Checks that the stack can hold the maximum height of a code section
+    /// before it is entered (EIP-4750).
+    fn check_section_stack(
+        &self, instruction: &'static str, section: usize, stack_limit: usize,
+    ) -> vm::Result<()> {
+        let container = self.eof.as_ref().expect(EOF_PROOF);
+        let increase = container.types[section].max_stack_increase as usize;
+        if self.stack.size() + increase > stack_limit {
+            return Err(vm::Error::OutOfStack {
+                instruction,
+                wanted: increase,
+                limit: stack_limit,
+            });
+        }
+        Ok(())
+    }
+
+    fn enter_code_section(&mut self, section: usize, position: ProgramCounter) {
+        let container = self.eof.as_ref().expect(EOF_PROOF);
+        self.reader.code = container.code_sections[section].clone();
+        self.reader.position = position;
+        self.code_section = section;
+    }
+
     fn verify_jump(
         &self, jump_u: U256, valid_jump_destinations: &BitSet,
     ) -> vm::Result<usize> {
@@ -1721,14 +2145,38 @@ fn u256_to_address(value: &U256) -> Address {
 #[inline]
 fn address_to_u256(value: Address) -> U256 { H256::from(value).into_uint() }
 
+/// Reads a word from `data` at `offset`, padded with zeros past the end.
+fn load_word(data: &[u8], offset: U256) -> U256 {
+    let mut word = [0u8; 32];
+    if offset < U256::from(data.len()) {
+        let offset = offset.as_usize();
+        let len = cmp::min(32, data.len() - offset);
+        word[..len].copy_from_slice(&data[offset..offset + len]);
+    }
+    U256::from(&word[..])
+}
+
+/// The status pushed to the stack by a call. The EOF calls (EIP-7069) push 0
+/// on success, 1 on revert and 2 on failure, and the legacy calls push 1 on
+/// success and 0 otherwise.
+fn call_status(result: &MessageCallResult, eof_call: bool) -> U256 {
+    match (result, eof_call) {
+        (MessageCallResult::Success(..), false) => U256::one(),
+        (_, false) => U256::zero(),
+        (MessageCallResult::Success(..), true) => U256::zero(),
+        (MessageCallResult::Reverted(..), true) => U256::one(),
+        (MessageCallResult::Failed(_), true) => U256::from(2),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{factory::Factory, vmtype::VMType};
-    use mazze_types::Address;
+    use mazze_types::{Address, Space, SpaceMap};
     use mazze_vm_types::{
         self as vm,
         tests::{test_finalize, MockContext},
-        ActionParams, ActionValue, Exec,
+        ActionParams, ActionValue, CreateType, Exec, GasLeft,
     };
     use rustc_hex::FromHex;
     use std::sync::Arc;
@@ -1795,4 +2243,75 @@ mod tests {
 
         assert_eq!(err, mazze_vm_types::Error::OutOfBounds);
     }
+
+    fn eof_context() -> MockContext {
+        let mut context = MockContext::new_spec();
+        context.spec.cancun_opcodes = true;
+        context.spec.eof = SpaceMap::new(false, true);
+        context.space = Space::Ethereum;
+        context
+    }
+
+    #[test]
+    fn should_execute_eof_functions() {
+        // Section 0 calls section 1, which returns 0x2a, adds the word at
+        // data offset 0 and returns the sum.
+        let code = concat!(
+            "ef0001010008020002000d0003ff002000",
+            "00800002",
+            "00010001",
+            "e30001d10000015f5260205ff3",
+            "602ae4",
+            "0000000000000000000000000000000000000000000000000000000000000001"
+        )
+        .from_hex()
+        .unwrap();
+
+        let mut params = ActionParams::default();
+        params.address = Address::from_low_u64_be(5);
+        params.gas = 300_000.into();
+        params.space = Space::Ethereum;
+        params.code = Some(Arc::new(code));
+        let mut context = eof_context();
+
+        let result = {
+            let vm = interpreter(params, &context);
+            vm.exec(&mut context).ok().unwrap().unwrap()
+        };
+
+        match result {
+            GasLeft::NeedsReturn {
+                data, apply_state, ..
+            } => {
+                let mut expected = vec![0u8; 32];
+                expected[31] = 0x2b;
+                assert!(apply_state);
+                assert_eq!(&*data, &expected[..]);
+            }
+            _ => panic!("EOF code should return data"),
+        }
+    }
+
+    #[test]
+    fn should_reject_invalid_eof_initcode() {
+        // The code section does not end with a terminating instruction.
+        let code = "ef00010100040200010001ff00000000800000".from_hex().unwrap();
+        let code = [code, vec![0x5f]].concat();
+
+        let mut params = ActionParams::default();
+        params.gas = 300_000.into();
+        params.space = Space::Ethereum;
+        params.create_type = CreateType::EOFCREATE;
+        params.code = Some(Arc::new(code));
+        let mut context = eof_context();
+
+        let err = {
+            let vm = interpreter(params, &context);
+            test_finalize(vm.exec(&mut context).ok().unwrap())
+                .err()
+                .unwrap()
+        };
+
+        assert_eq!(err, vm::Error::InvalidEofInitCode);
+    }
 }
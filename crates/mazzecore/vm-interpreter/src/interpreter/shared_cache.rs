@@ -14,9 +14,13 @@
 // You should have received a copy of the GNU General Public License
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::instructions::{self, Instruction};
+use crate::{
+    eof::{validate_container, ContainerKind, EofContainer, EofError},
+    instructions::{self, Instruction},
+};
 use bit_set::BitSet;
 use mazze_types::H256;
+use mazze_vm_types::Spec;
 use keccak_hash::KECCAK_EMPTY;
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use memory_cache::MemoryLruCache;
@@ -40,21 +44,96 @@ impl MallocSizeOf for Bits {
     }
 }
 
+/// A run of instructions whose gas cost only depends on the spec, and which
+/// can neither fail nor change the control flow. The interpreter checks the
+/// gas and the stack bounds of a block once, when it enters the block.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BasicBlock {
+    /// The position of the first instruction.
+    pub start: usize,
+    /// The position after the last instruction.
+    pub end: usize,
+    /// Whether the block starts with a `JUMPDEST`.
+    pub jump_destination: bool,
+    /// The number of instructions of each `GasPriceTier`.
+    pub tier_steps: [u32; 8],
+    /// The stack size needed by the block.
+    pub stack_required: usize,
+    /// The maximal growth of the stack within the block.
+    pub stack_growth: usize,
+}
+
+impl BasicBlock {
+    fn new(start: usize) -> Self {
+        BasicBlock {
+            start,
+            end: start,
+            jump_destination: false,
+            tier_steps: [0; 8],
+            stack_required: 0,
+            stack_growth: 0,
+        }
+    }
+
+    /// The gas charged for the instructions of the block.
+    pub fn gas(&self, spec: &Spec) -> usize {
+        let mut gas = self.jump_destination as usize;
+        for (steps, step_gas) in self.tier_steps.iter().zip(&spec.tier_step_gas)
+        {
+            gas += *steps as usize * step_gas;
+        }
+        gas
+    }
+}
+
+/// Stub for a sharing `BasicBlock` list in cache (reference counted)
+/// and implementing MallocSizeOf on it.
+#[derive(Clone)]
+struct Blocks(Arc<Vec<BasicBlock>>);
+
+impl MallocSizeOf for Blocks {
+    fn size_of(&self, _ops: &mut MallocSizeOfOps) -> usize {
+        self.0.capacity() * std::mem::size_of::<BasicBlock>()
+    }
+}
+
 #[derive(Clone)]
 struct CacheItem {
     jump_destination: Bits,
     sub_entrypoint: Bits,
+    basic_blocks: Blocks,
 }
 
 impl MallocSizeOf for CacheItem {
     fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
-        self.jump_destination.size_of(ops) + self.sub_entrypoint.size_of(ops)
+        self.jump_destination.size_of(ops)
+            + self.sub_entrypoint.size_of(ops)
+            + self.basic_blocks.size_of(ops)
+    }
+}
+
+/// The result of validating an EOF container, which is cached whether the
+/// container is valid or not.
+#[derive(Clone)]
+struct EofCacheItem(Result<Arc<EofContainer>, EofError>);
+
+impl MallocSizeOf for EofCacheItem {
+    fn size_of(&self, _ops: &mut MallocSizeOfOps) -> usize {
+        match &self.0 {
+            Ok(container) => {
+                container.header.container_size()
+                    + container.types.capacity()
+                        * std::mem::size_of::<crate::eof::TypeSection>()
+            }
+            Err(_) => 0,
+        }
     }
 }
 
 /// Global cache for EVM interpreter
 pub struct SharedCache<const CANCUN: bool> {
     jump_destinations: Mutex<MemoryLruCache<H256, CacheItem>>,
+    eof_containers: Mutex<MemoryLruCache<(H256, ContainerKind), EofCacheItem>>,
 }
 
 impl<const CANCUN: bool> SharedCache<CANCUN> {
@@ -63,32 +142,67 @@ impl<const CANCUN: bool> SharedCache<CANCUN> {
     pub fn new(max_size: usize) -> Self {
         SharedCache {
             jump_destinations: Mutex::new(MemoryLruCache::new(max_size)),
+            eof_containers: Mutex::new(MemoryLruCache::new(max_size)),
         }
     }
 
+    /// Get the validated EOF container of a contract, executed as the given
+    /// kind of code.
+    pub fn eof_container(
+        &self, code_hash: &H256, code: &[u8], kind: ContainerKind,
+    ) -> Result<Arc<EofContainer>, EofError> {
+        let key = (*code_hash, kind);
+        if let Some(item) = self.eof_containers.lock().get_mut(&key) {
+            return item.0.clone();
+        }
+
+        let container = validate_container(code, kind).map(Arc::new);
+        self.eof_containers
+            .lock()
+            .insert(key, EofCacheItem(container.clone()));
+        container
+    }
+
     /// Get jump destinations bitmap for a contract.
     pub fn jump_and_sub_destinations(
         &self, code_hash: &H256, code: &[u8],
     ) -> (Arc<BitSet>, Arc<BitSet>) {
+        let d = self.cache_item(code_hash, code);
+        (d.jump_destination.0, d.sub_entrypoint.0)
+    }
+
+    /// Get the basic blocks of a contract, sorted by position.
+    pub fn basic_blocks(
+        &self, code_hash: &H256, code: &[u8],
+    ) -> Arc<Vec<BasicBlock>> {
+        self.cache_item(code_hash, code).basic_blocks.0
+    }
+
+    fn cache_item(&self, code_hash: &H256, code: &[u8]) -> CacheItem {
         if code_hash == &KECCAK_EMPTY {
-            let cache_item = Self::find_jump_and_sub_destinations(code);
-            return (
-                cache_item.jump_destination.0,
-                cache_item.sub_entrypoint.0,
-            );
+            return Self::analyze(code);
         }
 
         if let Some(d) = self.jump_destinations.lock().get_mut(code_hash) {
-            return (d.jump_destination.0.clone(), d.sub_entrypoint.0.clone());
+            return d.clone();
         }
 
-        let d = Self::find_jump_and_sub_destinations(code);
+        let d = Self::analyze(code);
         self.jump_destinations.lock().insert(*code_hash, d.clone());
+        d
+    }
 
-        (d.jump_destination.0, d.sub_entrypoint.0)
+    fn analyze(code: &[u8]) -> CacheItem {
+        let (jump_dests, sub_entrypoints) =
+            Self::find_jump_and_sub_destinations(code);
+        CacheItem {
+            jump_destination: Bits(Arc::new(jump_dests)),
+            sub_entrypoint: Bits(Arc::new(sub_entrypoints)),
+            basic_blocks: Blocks(Arc::new(Self::find_basic_blocks(code))),
+        }
     }
 
-    fn find_jump_and_sub_destinations(code: &[u8]) -> CacheItem {
+    fn find_jump_and_sub_destinations(code: &[u8]) -> (BitSet, BitSet) {
         let mut jump_dests = BitSet::with_capacity(code.len());
         let mut sub_entrypoints = BitSet::with_capacity(code.len());
         let mut position = 0;
@@ -115,11 +229,120 @@ impl<const CANCUN: bool> SharedCache<CANCUN> {
         }
 
         jump_dests.shrink_to_fit();
-        CacheItem {
-            jump_destination: Bits(Arc::new(jump_dests)),
-            sub_entrypoint: Bits(Arc::new(sub_entrypoints)),
+        (jump_dests, sub_entrypoints)
+    }
+
+    fn find_basic_blocks(code: &[u8]) -> Vec<BasicBlock> {
+        let mut blocks = Vec::new();
+        let mut block: Option<BasicBlock> = None;
+        // The stack size relative to the start of the current block.
+        let mut height: isize = 0;
+        let mut position = 0;
+
+        while position < code.len() {
+            let instruction = Instruction::from_u8(code[position])
+                .filter(|instruction| is_static(*instruction));
+            let instruction = match instruction {
+                Some(instruction) => instruction,
+                None => {
+                    blocks.extend(block.take());
+                    position += 1;
+                    continue;
+                }
+            };
+
+            // A jump destination can be entered from anywhere, so it starts a
+            // new block.
+            if instruction == instructions::JUMPDEST {
+                blocks.extend(block.take());
+            }
+            let current = block.get_or_insert_with(|| {
+                height = 0;
+                BasicBlock::new(position)
+            });
+
+            let info = instruction.info::<CANCUN>();
+            if instruction == instructions::JUMPDEST {
+                current.jump_destination = true;
+            } else {
+                current.tier_steps[info.tier.idx()] += 1;
+            }
+            let args = info.args as isize;
+            let ret = info.ret as isize;
+            current.stack_required =
+                current.stack_required.max((args - height).max(0) as usize);
+            height += ret - args;
+            current.stack_growth =
+                current.stack_growth.max(height.max(0) as usize);
+
+            position += 1 + instruction.push_bytes().unwrap_or(0);
+            current.end = position;
         }
+        blocks.extend(block);
+
+        blocks
+    }
+}
+
+/// Whether the instruction is charged with its tier gas, always succeeds once
+/// its stack requirements are met, and falls through to the next instruction
+/// without reading the remaining gas.
+///
+/// `PUSH0` and `BASEFEE` are excluded because they are only defined in some
+/// specs.
+fn is_static(instruction: Instruction) -> bool {
+    if instruction.is_push()
+        || instruction.dup_position().is_some()
+        || instruction.swap_position().is_some()
+    {
+        return true;
     }
+    matches!(
+        instruction,
+        instructions::ADD
+            | instructions::MUL
+            | instructions::SUB
+            | instructions::DIV
+            | instructions::SDIV
+            | instructions::MOD
+            | instructions::SMOD
+            | instructions::ADDMOD
+            | instructions::MULMOD
+            | instructions::SIGNEXTEND
+            | instructions::LT
+            | instructions::GT
+            | instructions::SLT
+            | instructions::SGT
+            | instructions::EQ
+            | instructions::ISZERO
+            | instructions::AND
+            | instructions::OR
+            | instructions::XOR
+            | instructions::NOT
+            | instructions::BYTE
+            | instructions::SHL
+            | instructions::SHR
+            | instructions::SAR
+            | instructions::ADDRESS
+            | instructions::ORIGIN
+            | instructions::CALLER
+            | instructions::CALLVALUE
+            | instructions::CALLDATALOAD
+            | instructions::CALLDATASIZE
+            | instructions::CODESIZE
+            | instructions::RETURNDATASIZE
+            | instructions::GASPRICE
+            | instructions::COINBASE
+            | instructions::TIMESTAMP
+            | instructions::NUMBER
+            | instructions::DIFFICULTY
+            | instructions::GASLIMIT
+            | instructions::CHAINID
+            | instructions::POP
+            | instructions::PC
+            | instructions::MSIZE
+            | instructions::JUMPDEST
+    )
 }
 
 impl<const CANCUN: bool> Default for SharedCache<CANCUN> {
@@ -141,8 +364,7 @@ fn test_find_jump_destinations() {
     let code: Vec<u8> = "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff5b01600055".from_hex().unwrap();
 
     // when
-    let cache_item =
-        SharedCache::<false>::find_jump_and_sub_destinations(&code);
+    let cache_item = SharedCache::<false>::analyze(&code);
 
     // then
     assert!(cache_item
@@ -167,8 +389,7 @@ fn test_find_jump_destinations_not_in_data_segments() {
     let code: Vec<u8> = "600656605B565B6004".from_hex().unwrap();
 
     // when
-    let cache_item =
-        SharedCache::<false>::find_jump_and_sub_destinations(&code);
+    let cache_item = SharedCache::<false>::analyze(&code);
 
     // then
     assert!(cache_item.jump_destination.0.iter().eq(vec![6].into_iter()));
@@ -184,8 +405,7 @@ fn test_find_sub_entrypoints() {
         "6800000000000000000c5e005c60115e5d5c5d".from_hex().unwrap();
 
     // when
-    let cache_item =
-        SharedCache::<false>::find_jump_and_sub_destinations(&code);
+    let cache_item = SharedCache::<false>::analyze(&code);
 
     // then
     assert!(cache_item.jump_destination.0.is_empty());
@@ -209,10 +429,76 @@ fn test_find_jump_and_sub_allowing_unknown_opcodes() {
     let code: Vec<u8> = "5BCC5C".from_hex().unwrap();
 
     // when
-    let cache_item =
-        SharedCache::<false>::find_jump_and_sub_destinations(&code);
+    let cache_item = SharedCache::<false>::analyze(&code);
 
     // then
     assert!(cache_item.jump_destination.0.iter().eq(vec![0].into_iter()));
     assert!(cache_item.sub_entrypoint.0.iter().eq(vec![2].into_iter()));
 }
+
+#[test]
+fn test_find_basic_blocks() {
+    // given
+
+    // 0000 60 04   PUSH1 04
+    // 0002 35      CALLDATALOAD
+    // 0003 80      DUP1
+    // 0004 60 0b   PUSH1 0b
+    // 0006 57      JUMPI
+    // 0007 50      POP
+    // 0008 60 00   PUSH1 00
+    // 000a 00      STOP
+    // 000b 5B      JUMPDEST
+    // 000c 01      ADD
+    // 000d 60 00   PUSH1 00
+    // 000f 55      SSTORE
+    let code: Vec<u8> = "60043580600b57506000005b01600055".from_hex().unwrap();
+
+    // when
+    let blocks = SharedCache::<false>::find_basic_blocks(&code);
+
+    // then
+    assert_eq!(
+        blocks.iter().map(|b| (b.start, b.end)).collect::<Vec<_>>(),
+        vec![(0, 6), (7, 10), (11, 15)]
+    );
+
+    let spec = Spec::new_spec_for_test();
+    assert_eq!(blocks[0].gas(&spec), 12);
+    assert_eq!(blocks[0].stack_required, 0);
+    assert_eq!(blocks[0].stack_growth, 3);
+
+    assert_eq!(blocks[1].gas(&spec), 5);
+    assert_eq!(blocks[1].stack_required, 1);
+    assert_eq!(blocks[1].stack_growth, 0);
+
+    assert!(blocks[2].jump_destination);
+    assert_eq!(blocks[2].gas(&spec), 7);
+    assert_eq!(blocks[2].stack_required, 2);
+    assert_eq!(blocks[2].stack_growth, 0);
+}
+
+#[test]
+fn test_find_basic_blocks_split_at_jump_destinations() {
+    // given
+
+    // 0000 60 01   PUSH1 01
+    // 0002 5B      JUMPDEST
+    // 0003 5F      PUSH0
+    // 0004 80      DUP1
+    // 0005 CC      ???
+    // 0006 90      SWAP1
+    let code: Vec<u8> = "60015B5F80CC90".from_hex().unwrap();
+
+    // when
+    let blocks = SharedCache::<false>::find_basic_blocks(&code);
+
+    // then
+    assert_eq!(
+        blocks.iter().map(|b| (b.start, b.end)).collect::<Vec<_>>(),
+        vec![(0, 2), (2, 3), (4, 5), (6, 7)]
+    );
+    assert_eq!(blocks[2].stack_required, 1);
+    assert_eq!(blocks[2].stack_growth, 1);
+    assert_eq!(blocks[3].stack_required, 2);
+}
@@ -25,6 +25,9 @@ pub trait Stack<T> {
     fn peek(&self, no_from_top: usize) -> &T;
     /// Swaps Stack[len(Stack)] and Stack[len(Stack) - no_from_top]
     fn swap_with_top(&mut self, no_from_top: usize);
+    /// Swaps Stack[len(Stack) - first_from_top] and
+    /// Stack[len(Stack) - second_from_top]
+    fn swap(&mut self, first_from_top: usize, second_from_top: usize);
     /// Returns true if Stack has at least `no_of_elems` elements
     fn has(&self, no_of_elems: usize) -> bool;
     /// Get element from top and remove it from Stack. Panics if stack is empty.
@@ -67,6 +70,12 @@ impl<S: fmt::Display> Stack<S> for VecStack<S> {
         self.stack.swap(len - no_from_top - 1, len - 1);
     }
 
+    fn swap(&mut self, first_from_top: usize, second_from_top: usize) {
+        let len = self.stack.len();
+        self.stack
+            .swap(len - first_from_top - 1, len - second_from_top - 1);
+    }
+
     fn has(&self, no_of_elems: usize) -> bool {
         self.stack.len() >= no_of_elems
     }
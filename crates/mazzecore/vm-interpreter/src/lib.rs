@@ -3,6 +3,7 @@
 #[macro_use]
 extern crate log;
 
+pub mod eof;
 mod evm;
 #[macro_use]
 pub mod factory;
@@ -80,6 +80,8 @@ pub enum CreateType {
     CREATE,
     /// CREATE2
     CREATE2,
+    /// EOFCREATE, or a creation transaction with EOF initcode
+    EOFCREATE,
 }
 
 impl CreateType {
@@ -105,6 +107,7 @@ impl Encodable for CreateType {
             CreateType::None => 0u32,
             CreateType::CREATE => 1,
             CreateType::CREATE2 => 2,
+            CreateType::EOFCREATE => 3,
         };
         Encodable::rlp_append(&v, s);
     }
@@ -117,6 +120,7 @@ impl Decodable for CreateType {
                 0u32 => CreateType::None,
                 1 => CreateType::CREATE,
                 2 => CreateType::CREATE2,
+                3 => CreateType::EOFCREATE,
                 _ => {
                     return Err(DecoderError::Custom(
                         "Invalid value of CreateType item",
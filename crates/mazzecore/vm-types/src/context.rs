@@ -194,6 +194,16 @@ pub trait Context {
         address: CreateContractAddress,
     ) -> DbResult<::std::result::Result<ContractCreateResult, TrapKind>>;
 
+    /// Creates new contract with an EOF initcode container and its input
+    /// data (EIP-7620).
+    ///
+    /// Returns gas_left and contract address if contract creation was
+    /// succesfull.
+    fn eof_create(
+        &mut self, gas: &U256, value: &U256, init_container: &[u8],
+        input: &[u8], salt: H256,
+    ) -> DbResult<::std::result::Result<ContractCreateResult, TrapKind>>;
+
     /// Message call.
     ///
     /// Returns Err, if we run out of gas.
@@ -118,6 +118,14 @@ pub enum Error {
     InvalidAddress(Address),
     /// Create a contract on an address with existing contract
     ConflictAddress(Address),
+    /// Deploy code starting with the `0xEF` byte, which is reserved for the
+    /// delegation designators of EIP-7702
+    CreateContractStartingWithEF,
+    /// The EOF initcode of a creation transaction is not a valid container
+    InvalidEofInitCode,
+    /// The target address of `EXTCALL`, `EXTDELEGATECALL` or
+    /// `EXTSTATICCALL` has non-zero high 12 bytes
+    InvalidExtCallTarget,
 }
 
 #[derive(Debug)]
@@ -194,6 +202,13 @@ impl fmt::Display for Error {
             ConflictAddress(ref addr) => {
                 write!(f, "Contract creation on an existing address: {}", addr)
             }
+            CreateContractStartingWithEF => {
+                write!(f, "Contract code starting with 0xEF")
+            }
+            InvalidEofInitCode => write!(f, "Invalid EOF initcode"),
+            InvalidExtCallTarget => {
+                write!(f, "Call target address out of range")
+            }
         }
     }
 }
@@ -17,7 +17,7 @@
 
 
 //! Cost spec and other parameterisations for the EVM.
-use mazze_types::{address_util::AddressUtil, Address};
+use mazze_types::{address_util::AddressUtil, Address, SpaceMap};
 use primitives::{block::BlockHeight, BlockNumber};
 
 /// Definition of the cost spec and other parameterisations for the VM.
@@ -105,6 +105,11 @@ pub struct Spec {
     pub eip1820_gas: usize,
     pub access_list_storage_key_gas: usize,
     pub access_list_address_gas: usize,
+    /// EIP-7702: Intrinsic gas for each authorization
+    pub per_empty_account_cost: usize,
+    /// EIP-7702: Gas actually spent by an authorization of an existing
+    /// authority, the rest of `per_empty_account_cost` is refunded
+    pub per_auth_base_cost: usize,
     /// Amount of additional gas to pay when SUICIDE credits a non-existant
     /// account
     pub suicide_to_new_account_cost: usize,
@@ -183,6 +188,12 @@ pub struct Spec {
     pub cip144: bool,
     /// CIP-145: Fix Receipts upon `NotEnoughBalance` Error
     pub cip145: bool,
+    /// EIP-7702: Set EOA Account Code
+    pub eip7702: bool,
+    /// EIP-7692: EVM Object Format (EOF) v1 in each space
+    pub eof: SpaceMap<bool>,
+    /// Gas sponsorship for eSpace contracts
+    pub eth_sponsor: bool,
 }
 
 /// Wasm cost table
@@ -301,6 +312,8 @@ impl Spec {
             eip1820_gas: 1_500_000,
             access_list_storage_key_gas: 1900,
             access_list_address_gas: 2400,
+            per_empty_account_cost: 25000,
+            per_auth_base_cost: 12500,
             suicide_to_new_account_cost: 25000,
             sub_gas_cap_divisor: Some(64),
             no_empty: true,
@@ -338,6 +351,9 @@ impl Spec {
             cip1559: false,
             cancun_opcodes: false,
             cip144: false,
+            eip7702: false,
+            eof: SpaceMap::default(),
+            eth_sponsor: false,
         }
     }
 
@@ -73,6 +73,7 @@ pub struct MockContext {
     pub balances: HashMap<Address, U256>,
     pub tracing: bool,
     pub is_static: bool,
+    pub space: Space,
 
     chain_id: u64,
 }
@@ -173,6 +174,25 @@ impl Context for MockContext {
         Ok(Ok(ContractCreateResult::Failed(Error::OutOfGas)))
     }
 
+    fn eof_create(
+        &mut self, gas: &U256, value: &U256, init_container: &[u8],
+        input: &[u8], salt: H256,
+    ) -> DbResult<::std::result::Result<ContractCreateResult, TrapKind>> {
+        self.calls.insert(MockCall {
+            call_type: MockCallType::Create,
+            create_scheme: Some(
+                CreateContractAddress::FromSenderSaltAndCodeHash(salt),
+            ),
+            gas: *gas,
+            sender_address: None,
+            receive_address: None,
+            value: Some(*value),
+            data: [init_container, input].concat(),
+            code_address: None,
+        });
+        Ok(Ok(ContractCreateResult::Failed(Error::OutOfGas)))
+    }
+
     fn call(
         &mut self, gas: &U256, sender_address: &Address,
         receive_address: &Address, value: Option<U256>, data: &[u8],
@@ -253,7 +273,7 @@ impl Context for MockContext {
     //     self.tracing
     // }
 
-    fn space(&self) -> Space { Space::Native }
+    fn space(&self) -> Space { self.space }
 
     fn blockhash_source(&self) -> BlockHashSource { BlockHashSource::Env }
 }
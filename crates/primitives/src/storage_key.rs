@@ -396,7 +396,10 @@ impl<'a> StorageKeyWithSpace<'a> {
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DeltaMptKeyPadding([u8; delta_mpt_storage_key::KEY_PADDING_BYTES]);
 
-pub use delta_mpt_storage_key::KEY_PADDING_BYTES as DELTA_MPT_KEY_PADDING_BYTES;
+pub use delta_mpt_storage_key::{
+    ACCOUNT_KEYPART_BYTES as DELTA_MPT_ACCOUNT_KEYPART_BYTES,
+    KEY_PADDING_BYTES as DELTA_MPT_KEY_PADDING_BYTES,
+};
 lazy_static! {
     pub static ref GENESIS_DELTA_MPT_KEY_PADDING: DeltaMptKeyPadding =
         StorageKeyWithSpace::delta_mpt_padding(
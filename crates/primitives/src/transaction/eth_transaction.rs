@@ -1,10 +1,13 @@
 use crate::{
-    transaction::AccessList, Action, SignedTransaction, Transaction,
-    TransactionWithSignature, TransactionWithSignatureSerializePart,
+    hash::keccak,
+    transaction::{AccessList, EIP7702_AUTHORIZATION_MAGIC},
+    Action, SignedTransaction, Transaction, TransactionWithSignature,
+    TransactionWithSignatureSerializePart,
 };
 use bytes::Bytes;
-use mazze_types::{AddressWithSpace, H256, U256};
-use rlp::{Encodable, RlpStream};
+use keylib::{public_to_address, recover, Signature};
+use mazze_types::{Address, AddressWithSpace, BigEndianHash, H256, U256};
+use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
 use serde_derive::{Deserialize, Serialize};
 
 impl Eip155Transaction {
@@ -161,11 +164,141 @@ impl Encodable for Eip1559Transaction {
     }
 }
 
+/// EIP-7702: a transaction setting the code of the signers of its
+/// authorization list to a delegation designator. It cannot create contracts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Eip7702Transaction {
+    pub chain_id: u32,
+    pub nonce: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub gas: U256,
+    pub action: Action,
+    pub value: U256,
+    pub data: Bytes,
+    pub access_list: AccessList,
+    pub authorization_list: AuthorizationList,
+}
+
+impl Encodable for Eip7702Transaction {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(10);
+        s.append(&self.chain_id);
+        s.append(&self.nonce);
+        s.append(&self.max_priority_fee_per_gas);
+        s.append(&self.max_fee_per_gas);
+        s.append(&self.gas);
+        s.append(&self.action);
+        s.append(&self.value);
+        s.append(&self.data);
+        s.append_list(&self.access_list);
+        s.append_list(&self.authorization_list);
+    }
+}
+
+/// A signed authorization in an EIP-7702 transaction, delegating the code of
+/// the signer (the authority) to `address`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorizationListItem {
+    /// The chain id the authorization is valid on, zero for any chain.
+    pub chain_id: U256,
+    /// The delegated address, the zero address clears the delegation.
+    pub address: Address,
+    /// The nonce of the authority.
+    pub nonce: u64,
+    pub y_parity: u8,
+    pub r: U256,
+    pub s: U256,
+}
+
+pub type AuthorizationList = Vec<AuthorizationListItem>;
+
+impl Encodable for AuthorizationListItem {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(6);
+        s.append(&self.chain_id);
+        s.append(&self.address);
+        s.append(&self.nonce);
+        s.append(&self.y_parity);
+        s.append(&self.r);
+        s.append(&self.s);
+    }
+}
+
+impl Decodable for AuthorizationListItem {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        if rlp.item_count()? != 6 {
+            return Err(DecoderError::RlpIncorrectListLen);
+        }
+        Ok(Self {
+            chain_id: rlp.val_at(0)?,
+            address: rlp.val_at(1)?,
+            nonce: rlp.val_at(2)?,
+            y_parity: rlp.val_at(3)?,
+            r: rlp.val_at(4)?,
+            s: rlp.val_at(5)?,
+        })
+    }
+}
+
+impl AuthorizationListItem {
+    /// The hash signed by the authority:
+    /// `keccak(MAGIC || rlp([chain_id, address, nonce]))`.
+    pub fn signature_hash(&self) -> H256 {
+        let mut s = RlpStream::new_list(3);
+        s.append(&self.chain_id);
+        s.append(&self.address);
+        s.append(&self.nonce);
+        let mut message = vec![EIP7702_AUTHORIZATION_MAGIC];
+        message.extend_from_slice(s.as_raw());
+        keccak(&message)
+    }
+
+    /// Recover the signer. Returns `None` for an invalid or high-s signature,
+    /// in which case the authorization is skipped.
+    pub fn authority(&self) -> Option<Address> {
+        let r: H256 = BigEndianHash::from_uint(&self.r);
+        let s: H256 = BigEndianHash::from_uint(&self.s);
+        let signature = Signature::from_rsv(&r, &s, self.y_parity);
+        if !signature.is_valid() || !signature.is_low_s() {
+            return None;
+        }
+        let public = recover(&signature, &self.signature_hash()).ok()?;
+        Some(public_to_address(&public, /* type_nibble */ false))
+    }
+}
+
+/// The code prefix of an account delegated by an EIP-7702 authorization. The
+/// full code is the prefix followed by the delegated address.
+pub const DELEGATION_DESIGNATOR_PREFIX: [u8; 3] = [0xef, 0x01, 0x00];
+
+pub const DELEGATION_DESIGNATOR_LENGTH: usize =
+    DELEGATION_DESIGNATOR_PREFIX.len() + 20;
+
+/// The code of an account delegated to `address`.
+pub fn delegation_designator(address: &Address) -> Bytes {
+    let mut code = DELEGATION_DESIGNATOR_PREFIX.to_vec();
+    code.extend_from_slice(address.as_bytes());
+    code
+}
+
+/// The delegated address if `code` is a delegation designator.
+pub fn parse_delegation_designator(code: &[u8]) -> Option<Address> {
+    if code.len() == DELEGATION_DESIGNATOR_LENGTH
+        && code.starts_with(&DELEGATION_DESIGNATOR_PREFIX)
+    {
+        Some(Address::from_slice(&code[DELEGATION_DESIGNATOR_PREFIX.len()..]))
+    } else {
+        None
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EthereumTransaction {
     Eip155(Eip155Transaction),
     Eip1559(Eip1559Transaction),
     Eip2930(Eip2930Transaction),
+    Eip7702(Eip7702Transaction),
 }
 use EthereumTransaction::*;
 
@@ -196,6 +329,7 @@ macro_rules! eth_access_common_ref {
                 EthereumTransaction::Eip155(tx) => &tx.$field,
                 EthereumTransaction::Eip2930(tx) => &tx.$field,
                 EthereumTransaction::Eip1559(tx) => &tx.$field,
+                EthereumTransaction::Eip7702(tx) => &tx.$field,
             }
         }
     };
@@ -217,6 +351,7 @@ impl EthereumTransaction {
             Eip155(tx) => &tx.gas_price,
             Eip1559(tx) => &tx.max_fee_per_gas,
             Eip2930(tx) => &tx.gas_price,
+            Eip7702(tx) => &tx.max_fee_per_gas,
         }
     }
 
@@ -225,6 +360,7 @@ impl EthereumTransaction {
             Eip155(tx) => &tx.gas_price,
             Eip1559(tx) => &tx.max_priority_fee_per_gas,
             Eip2930(tx) => &tx.gas_price,
+            Eip7702(tx) => &tx.max_priority_fee_per_gas,
         }
     }
 
@@ -233,6 +369,7 @@ impl EthereumTransaction {
             Eip155(tx) => tx.chain_id,
             Eip1559(tx) => Some(tx.chain_id),
             Eip2930(tx) => Some(tx.chain_id),
+            Eip7702(tx) => Some(tx.chain_id),
         }
     }
 
@@ -241,6 +378,7 @@ impl EthereumTransaction {
             Eip155(tx) => &mut tx.nonce,
             Eip2930(tx) => &mut tx.nonce,
             Eip1559(tx) => &mut tx.nonce,
+            Eip7702(tx) => &mut tx.nonce,
         }
     }
 
@@ -249,6 +387,14 @@ impl EthereumTransaction {
             Eip155(_tx) => None,
             Eip2930(tx) => Some(&tx.access_list),
             Eip1559(tx) => Some(&tx.access_list),
+            Eip7702(tx) => Some(&tx.access_list),
+        }
+    }
+
+    pub fn authorization_list(&self) -> Option<&AuthorizationList> {
+        match self {
+            Eip7702(tx) => Some(&tx.authorization_list),
+            _ => None,
         }
     }
 }